@@ -4,78 +4,50 @@
 use geo::{polygon, Centroid};
 use lib_common::grpc::get_endpoint_from_env;
 use lib_common::time::{DateTime, Duration, Utc};
+use svc_gis_client_grpc::fixtures::{self, Scenario};
 use svc_gis_client_grpc::prelude::{gis::*, *};
+use svc_gis_client_grpc::publisher::Publisher;
 
-const VERTIPORT_1_ID: &str = "Kamino";
-const VERTIPORT_2_ID: &str = "Bespin";
+const VERTIPORT_1_ID: &str = fixtures::BLOCKED_PORT_A_ID;
+const VERTIPORT_2_ID: &str = fixtures::BLOCKED_PORT_B_ID;
 const VERTIPORT_3_ID: &str = "Coruscant";
 const AIRCRAFT_1_ID: &str = "Marauder";
 
-async fn add_vertiports(client: &GisClient) -> Result<(), Box<dyn std::error::Error>> {
+async fn add_vertiports(
+    client: &GisIngestClient,
+    scenario: &Scenario,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n\u{1F6EB} Add Vertiports");
-    let vertiports = vec![
-        Vertiport {
-            identifier: VERTIPORT_1_ID.to_string(),
-            altitude_meters: 10.0,
-            vertices: vec![
-                (52.3746368, 4.9163718),
-                (52.3747387, 4.9162102),
-                (52.3748374, 4.9163691),
-                (52.3747375, 4.9165381),
-                (52.3746368, 4.9163718),
-            ]
-            .iter()
-            .map(|(x, y)| Coordinates {
-                latitude: *x,
-                longitude: *y,
-            })
-            .collect(),
-            label: Some("VertiportA".to_string()),
-            timestamp_network: Some(Utc::now().into()),
-        },
-        Vertiport {
-            identifier: VERTIPORT_2_ID.to_string(),
-            altitude_meters: 10.0,
-            vertices: vec![
-                (52.3751407, 4.916294),
-                (52.3752201, 4.9162611),
-                (52.3752627, 4.9163657),
-                (52.3752107, 4.9164683),
-                (52.3751436, 4.9164355),
-                (52.3751407, 4.916294),
-            ]
-            .iter()
-            .map(|(x, y)| Coordinates {
-                latitude: *x,
-                longitude: *y,
-            })
-            .collect(),
-            label: Some("VertiportB".to_string()),
-            timestamp_network: Some(Utc::now().into()),
-        },
-        Vertiport {
-            identifier: VERTIPORT_3_ID.to_string(),
-            altitude_meters: 10.0,
-            vertices: vec![
-                (52.3753536, 4.9157569),
-                (52.3752766, 4.9157193),
-                (52.375252, 4.9158829),
-                (52.3753306, 4.9159232),
-                (52.3753536, 4.9157569),
-            ]
-            .iter()
-            .map(|(x, y)| Coordinates {
-                latitude: *x,
-                longitude: *y,
-            })
-            .collect(),
-            label: Some("Blocker Port".to_string()),
-            timestamp_network: Some(Utc::now().into()),
-        },
-    ];
+    let mut vertiports = scenario.vertiports.clone();
+    vertiports.push(Vertiport {
+        identifier: VERTIPORT_3_ID.to_string(),
+        altitude_meters: 10.0,
+        vertices: vec![
+            (52.3753536, 4.9157569),
+            (52.3752766, 4.9157193),
+            (52.375252, 4.9158829),
+            (52.3753306, 4.9159232),
+            (52.3753536, 4.9157569),
+        ]
+        .iter()
+        .map(|(x, y)| Coordinates {
+            latitude: *x,
+            longitude: *y,
+        })
+        .collect(),
+        label: Some("Blocker Port".to_string()),
+        timestamp_network: Some(Utc::now().into()),
+        status: None,
+        layers: None,
+        compact_vertices: None,
+        operating_hours: vec![],
+    });
 
     let response = client
-        .update_vertiports(UpdateVertiportsRequest { vertiports })
+        .update_vertiports(UpdateVertiportsRequest {
+            vertiports,
+            validate_only: false,
+        })
         .await?;
 
     println!("RESPONSE={:?}", response.into_inner());
@@ -83,29 +55,13 @@ async fn add_vertiports(client: &GisClient) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
-async fn add_waypoints(client: &GisClient) -> Result<(), Box<dyn std::error::Error>> {
+async fn add_waypoints(
+    client: &GisIngestClient,
+    scenario: &Scenario,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n\u{1F4CD} Add Waypoints");
-    let nodes = vec![
-        ("ORANGE", 52.3745905, 4.9160036),
-        ("STRAWBERRY", 52.3749819, 4.9156925),
-        ("BANANA", 52.3752144, 4.9153733),
-        ("LEMON", 52.3753012, 4.9156845),
-        ("RASPBERRY", 52.3750703, 4.9161538),
-    ];
-
-    let waypoints = nodes
-        .iter()
-        .map(|(identifier, latitude, longitude)| Waypoint {
-            identifier: identifier.to_string(),
-            location: Some(Coordinates {
-                latitude: *latitude,
-                longitude: *longitude,
-            }),
-        })
-        .collect();
-
     let response = client
-        .update_waypoints(UpdateWaypointsRequest { waypoints })
+        .update_waypoints(scenario.waypoints_request())
         .await?;
 
     println!("RESPONSE={:?}", response.into_inner());
@@ -130,9 +86,9 @@ async fn add_aircraft(connection: &mut redis::Connection) -> Result<(), ()> {
             |(identifier, latitude, longitude, altitude_meters)| AircraftPosition {
                 identifier: identifier.to_string(),
                 position: Position {
-                    latitude: *latitude,
-                    longitude: *longitude,
-                    altitude_meters: *altitude_meters,
+                    latitude: Degrees(*latitude),
+                    longitude: Degrees(*longitude),
+                    altitude_meters: Meters(*altitude_meters),
                 },
                 timestamp_network: Utc::now(),
                 timestamp_asset: None,
@@ -140,15 +96,10 @@ async fn add_aircraft(connection: &mut redis::Connection) -> Result<(), ()> {
         )
         .collect();
 
-    let mut pipe = redis::pipe();
-    aircraft.iter().for_each(|aircraft| {
-        (&mut pipe).rpush(
-            REDIS_KEY_AIRCRAFT_POSITION,
-            serde_json::to_vec(&aircraft).unwrap(),
-        );
-    });
-
-    pipe.query::<()>(connection).unwrap();
+    let mut publisher = Publisher::new(connection);
+    for aircraft in &aircraft {
+        publisher.push_position(aircraft).map_err(|_| ())?;
+    }
 
     let aircraft: Vec<AircraftId> = sample
         .iter()
@@ -158,46 +109,37 @@ async fn add_aircraft(connection: &mut redis::Connection) -> Result<(), ()> {
             aircraft_type: AircraftType::Rotorcraft,
             timestamp_network: Utc::now(),
             timestamp_asset: None,
+            session_ended: false,
         })
         .collect();
 
-    let mut pipe = redis::pipe();
-    aircraft.iter().for_each(|aircraft| {
-        (&mut pipe).rpush(
-            REDIS_KEY_AIRCRAFT_ID,
-            serde_json::to_vec(&aircraft).unwrap(),
-        );
-    });
-
-    pipe.query::<()>(connection).unwrap();
+    for aircraft in &aircraft {
+        publisher.push_id(aircraft).map_err(|_| ())?;
+    }
 
     let aircraft: Vec<AircraftVelocity> = sample
         .iter()
         .map(|(identifier, _, _, _)| AircraftVelocity {
             identifier: identifier.to_string(),
             velocity_horizontal_air_mps: None,
-            velocity_horizontal_ground_mps: 100.0,
-            velocity_vertical_mps: 10.0,
-            track_angle_degrees: 10.0,
+            velocity_horizontal_ground_mps: MetersPerSecond(100.0),
+            velocity_vertical_mps: MetersPerSecond(10.0),
+            track_angle_degrees: Degrees(10.0),
             timestamp_network: Utc::now(),
             timestamp_asset: None,
         })
         .collect();
 
-    let mut pipe = redis::pipe();
-    aircraft.iter().for_each(|aircraft| {
-        (&mut pipe).rpush(
-            REDIS_KEY_AIRCRAFT_VELOCITY,
-            serde_json::to_vec(&aircraft).unwrap(),
-        );
-    });
+    for aircraft in &aircraft {
+        publisher.push_velocity(aircraft).map_err(|_| ())?;
+    }
 
-    pipe.query::<()>(connection).unwrap();
+    publisher.flush().map_err(|_| ())?;
 
     Ok(())
 }
 
-async fn add_flight_paths(client: &GisClient) -> Result<(), ()> {
+async fn add_flight_paths(client: &GisIngestClient) -> Result<(), ()> {
     println!("\n\u{1F681} Add Flights");
 
     let path = vec![
@@ -231,6 +173,7 @@ async fn add_flight_paths(client: &GisClient) -> Result<(), ()> {
             timestamp_end: Some((Utc::now() + Duration::try_minutes(20).unwrap()).into()),
             simulated: false,
             aircraft_type: AircraftType::Rotorcraft as i32,
+            priority: FlightPriority::Routine as i32,
         })
         .collect();
 
@@ -243,7 +186,8 @@ async fn add_flight_paths(client: &GisClient) -> Result<(), ()> {
 
 async fn best_path_flight_avoidance(
     _connection: &mut redis::Connection,
-    client: &GisClient,
+    ingest: &GisIngestClient,
+    query: &GisQueryClient,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Add two vertiports specifically for this test
     // Place somewhere far from the others in this test
@@ -260,21 +204,12 @@ async fn best_path_flight_avoidance(
         (x: 4.713655228916873, y: 52.63040456142831)
     ];
 
-    let vertices = alkmaar_1_polygon
-        .exterior()
-        .points()
-        .map(|pt| Coordinates {
-            latitude: pt.y(),
-            longitude: pt.x(),
-        })
-        .collect();
-
     let alkmaar_1 = Vertiport {
         identifier: ALKMAAR_1_ID.to_string(),
         altitude_meters: DEFAULT_ALTITUDE as f32,
-        vertices,
         label: Some("Alkmaar 1".to_string()),
         timestamp_network: Some(Utc::now().into()),
+        ..Vertiport::try_from(&alkmaar_1_polygon)?
     };
 
     const ALKMAAR_2_ID: &str = "ALKMAAR_2";
@@ -286,26 +221,20 @@ async fn best_path_flight_avoidance(
         (x: 4.7183918814820895, y: 52.63404933036138)
     ];
 
-    let vertices = alkmaar_2_polygon
-        .exterior()
-        .points()
-        .map(|pt| Coordinates {
-            latitude: pt.y(),
-            longitude: pt.x(),
-        })
-        .collect();
-
     let alkmaar_2 = Vertiport {
         identifier: ALKMAAR_2_ID.to_string(),
         altitude_meters: DEFAULT_ALTITUDE as f32,
-        vertices,
         label: Some("Alkmaar 2".to_string()),
         timestamp_network: Some(Utc::now().into()),
+        ..Vertiport::try_from(&alkmaar_2_polygon)?
     };
 
     let vertiports = vec![alkmaar_1.clone(), alkmaar_2.clone()];
-    let _ = client
-        .update_vertiports(UpdateVertiportsRequest { vertiports })
+    let _ = ingest
+        .update_vertiports(UpdateVertiportsRequest {
+            vertiports,
+            validate_only: false,
+        })
         .await?;
 
     let time_start: DateTime<Utc> = Utc::now();
@@ -321,9 +250,19 @@ async fn best_path_flight_avoidance(
         time_start: Some(time_start.clone().into()),
         time_end: Some(time_end.clone().into()),
         limit: 1,
+        max_path_node_count: None,
+        weights: None,
+        include_simulated: false,
+        priority: FlightPriority::Routine as i32,
+        max_window_offset_minutes: None,
+        aircraft_identifier: None,
+        flight_identifier: None,
+        arrival_window_minutes: None,
+        aircraft_type: None,
+        operator_identifier: None,
     };
 
-    let response = client.best_path(request).await?.into_inner();
+    let response = query.best_path(request).await?.into_inner();
     let Some(path) = response.paths.first() else {
         panic!("No path found.");
     };
@@ -356,9 +295,10 @@ async fn best_path_flight_avoidance(
         timestamp_end: Some(time_end.into()),
         simulated: false,
         aircraft_type: AircraftType::Rotorcraft as i32,
+        priority: FlightPriority::Routine as i32,
     };
 
-    let _ = client.update_flight_path(request).await?.into_inner();
+    let _ = ingest.update_flight_path(request).await?.into_inner();
 
     // Best Path Request
     println!("\n\u{1F426} Best Path With Prior Flight Path @ Different Altitude (No-Intersect)");
@@ -370,9 +310,19 @@ async fn best_path_flight_avoidance(
         time_start: Some(time_start.clone().into()),
         time_end: Some(time_end.clone().into()),
         limit: 1,
+        max_path_node_count: None,
+        weights: None,
+        include_simulated: false,
+        priority: FlightPriority::Routine as i32,
+        max_window_offset_minutes: None,
+        aircraft_identifier: None,
+        flight_identifier: None,
+        arrival_window_minutes: None,
+        aircraft_type: None,
+        operator_identifier: None,
     };
 
-    let response = client.best_path(request).await?.into_inner();
+    let response = query.best_path(request).await?.into_inner();
     let Some(path) = response.paths.first() else {
         panic!("No path found.");
     };
@@ -403,9 +353,10 @@ async fn best_path_flight_avoidance(
         timestamp_end: Some(time_end.into()),
         simulated: false,
         aircraft_type: AircraftType::Rotorcraft as i32,
+        priority: FlightPriority::Routine as i32,
     };
 
-    let _ = client.update_flight_path(request).await?.into_inner();
+    let _ = ingest.update_flight_path(request).await?.into_inner();
 
     //
     // Try Best Path again during same time as other flight, should be none
@@ -419,9 +370,19 @@ async fn best_path_flight_avoidance(
         time_start: Some(time_start.clone().into()),
         time_end: Some(time_end.clone().into()),
         limit: 1,
+        max_path_node_count: None,
+        weights: None,
+        include_simulated: false,
+        priority: FlightPriority::Routine as i32,
+        max_window_offset_minutes: None,
+        aircraft_identifier: None,
+        flight_identifier: None,
+        arrival_window_minutes: None,
+        aircraft_type: None,
+        operator_identifier: None,
     };
 
-    let response = client.best_path(request).await?.into_inner();
+    let response = query.best_path(request).await?.into_inner();
     match response.paths.first() {
         Some(path) => {
             println!("Path found when it should not have been possible.");
@@ -443,9 +404,19 @@ async fn best_path_flight_avoidance(
         time_start: Some((time_end.clone() + Duration::try_seconds(1).unwrap()).into()),
         time_end: Some((time_end.clone() + Duration::try_minutes(1).unwrap()).into()),
         limit: 1,
+        max_path_node_count: None,
+        weights: None,
+        include_simulated: false,
+        priority: FlightPriority::Routine as i32,
+        max_window_offset_minutes: None,
+        aircraft_identifier: None,
+        flight_identifier: None,
+        arrival_window_minutes: None,
+        aircraft_type: None,
+        operator_identifier: None,
     };
 
-    let response = client.best_path(request).await?.into_inner();
+    let response = query.best_path(request).await?.into_inner();
     let Some(path) = response.paths.first() else {
         panic!("No path found.");
     };
@@ -465,9 +436,19 @@ async fn best_path_flight_avoidance(
         time_start: Some((time_end - Duration::try_seconds(2).unwrap()).into()),
         time_end: Some((time_end + Duration::try_minutes(13).unwrap()).into()),
         limit: 1,
+        max_path_node_count: None,
+        weights: None,
+        include_simulated: false,
+        priority: FlightPriority::Routine as i32,
+        max_window_offset_minutes: None,
+        aircraft_identifier: None,
+        flight_identifier: None,
+        arrival_window_minutes: None,
+        aircraft_type: None,
+        operator_identifier: None,
     };
 
-    let response = client.best_path(request).await?.into_inner();
+    let response = query.best_path(request).await?.into_inner();
     let Some(path) = response.paths.first() else {
         panic!("No path found.");
     };
@@ -479,7 +460,7 @@ async fn best_path_flight_avoidance(
 }
 
 /// Get active flights
-async fn get_flights(client: &GisClient) -> Result<(), Box<dyn std::error::Error>> {
+async fn get_flights(client: &GisQueryClient) -> Result<(), Box<dyn std::error::Error>> {
     {
         println!("\n\u{1F426} Get Active Flights");
         let time_start: Timestamp = (Utc::now() - Duration::try_seconds(30).unwrap()).into();
@@ -491,6 +472,13 @@ async fn get_flights(client: &GisClient) -> Result<(), Box<dyn std::error::Error
             window_max_y: 52.376,
             time_start: Some(time_start),
             time_end: Some(time_end),
+            window_vertices: vec![],
+            altitude_meters_min: None,
+            altitude_meters_max: None,
+            page: None,
+            zone_proximity_meters: Some(500.0),
+            planned_only: false,
+            include_simulated: false,
         };
 
         let response = client.get_flights(request).await?.into_inner();
@@ -501,10 +489,40 @@ async fn get_flights(client: &GisClient) -> Result<(), Box<dyn std::error::Error
         }
     }
 
+    // Get Planned Flights
+    {
+        println!("\n\u{1F4C5} Get Flights Planned Tomorrow");
+        let time_start: Timestamp = (Utc::now() + Duration::try_days(1).unwrap()).into();
+        let time_end: Timestamp = (Utc::now() + Duration::try_days(2).unwrap()).into();
+        let request = GetFlightsRequest {
+            window_min_x: 4.915,
+            window_min_y: 52.374,
+            window_max_x: 4.917,
+            window_max_y: 52.376,
+            time_start: Some(time_start),
+            time_end: Some(time_end),
+            window_vertices: vec![],
+            altitude_meters_min: None,
+            altitude_meters_max: None,
+            page: None,
+            zone_proximity_meters: None,
+            planned_only: true,
+            include_simulated: false,
+        };
+
+        let response = client.get_flights(request).await?.into_inner();
+        println!("RESPONSE={:?}", response);
+    }
+
     Ok(())
 }
 
-async fn best_paths(client: &GisClient) -> Result<(), Box<dyn std::error::Error>> {
+async fn best_paths(
+    ingest: &GisIngestClient,
+    query: &GisQueryClient,
+    scenario: &Scenario,
+    no_fly_start_time: DateTime<Utc>,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Best Path Without No-Fly Zone
     {
         println!("\n\u{1F426} Best Path WITHOUT Temporary No-Fly Zone");
@@ -518,50 +536,31 @@ async fn best_paths(client: &GisClient) -> Result<(), Box<dyn std::error::Error>
             time_start: Some(time_start),
             time_end: Some(time_end),
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
-        let response = client.best_path(request).await?.into_inner();
+        let response = query.best_path(request).await?.into_inner();
 
         println!("RESPONSE={:?}", response);
         display_paths(&response.paths);
     }
 
-    let no_fly_start_time = Utc::now();
-    let no_fly_end_time = Utc::now() + Duration::try_hours(2).unwrap();
+    let no_fly_end_time = no_fly_start_time + Duration::try_hours(2).unwrap();
 
     // Update No-Fly Zones
     {
         println!("\n\u{26D4} Add No-Fly Zones");
-        let mut zones: Vec<Zone> = vec![];
-
-        // No Fly 1
-        let vertices: Vec<(f64, f64)> = vec![
-            (52.3751734, 4.9158481),
-            (52.3750752, 4.9157998),
-            (52.3749409, 4.9164569),
-            (52.3751047, 4.9164999),
-            (52.3751734, 4.9158481),
-        ];
-
-        let vertices: Vec<Coordinates> = vertices
-            .iter()
-            .map(|(x, y)| Coordinates {
-                latitude: *x,
-                longitude: *y,
-            })
-            .collect();
-
-        let time_start: Timestamp = no_fly_start_time.into();
-        let time_end: Timestamp = no_fly_end_time.into();
-        zones.push(Zone {
-            identifier: "NL-NFZ-01".to_string(),
-            zone_type: ZoneType::Restriction as i32,
-            altitude_meters_max: 1000.0,
-            altitude_meters_min: 0.0,
-            vertices,
-            time_start: Some(time_start),
-            time_end: Some(time_end),
-        });
+        // No Fly 1, across the direct route
+        let mut zones: Vec<Zone> = scenario.zones.clone();
 
         // No Fly 2
         let vertices = vec![
@@ -596,9 +595,41 @@ async fn best_paths(client: &GisClient) -> Result<(), Box<dyn std::error::Error>
             vertices,
             time_start: None,
             time_end: None,
+            // Active 08:00 - 17:00 UTC on weekdays
+            schedule: vec![ActiveWindow {
+                days: vec![
+                    Weekday::Monday as i32,
+                    Weekday::Tuesday as i32,
+                    Weekday::Wednesday as i32,
+                    Weekday::Thursday as i32,
+                    Weekday::Friday as i32,
+                ],
+                start_minute: 8 * 60,
+                end_minute: 17 * 60,
+            }],
+            waypoint_buffer_meters: Some(50.0),
+            parts: vec![],
+            compact_vertices: None,
+            metadata: None,
+            port_operating_hours: vec![],
         });
 
-        let response = client.update_zones(UpdateZonesRequest { zones }).await?;
+        // Check the zones before applying them
+        let response = ingest
+            .update_zones(UpdateZonesRequest {
+                zones: zones.clone(),
+                validate_only: true,
+            })
+            .await?;
+
+        println!("VALIDATIONS={:?}", response.into_inner().validations);
+
+        let response = ingest
+            .update_zones(UpdateZonesRequest {
+                zones,
+                validate_only: false,
+            })
+            .await?;
 
         println!("RESPONSE={:?}", response.into_inner());
     }
@@ -616,9 +647,19 @@ async fn best_paths(client: &GisClient) -> Result<(), Box<dyn std::error::Error>
             time_start: Some(time_start),
             time_end: Some(time_end),
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
-        let mut response = client.best_path(request).await?.into_inner();
+        let mut response = query.best_path(request).await?.into_inner();
 
         println!("RESPONSE={:?}", response);
         display_paths(&response.paths);
@@ -642,9 +683,19 @@ async fn best_paths(client: &GisClient) -> Result<(), Box<dyn std::error::Error>
             time_start: Some(time_start),
             time_end: Some(time_end),
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
-        let response = client.best_path(request).await?.into_inner();
+        let response = query.best_path(request).await?.into_inner();
 
         println!("RESPONSE={:?}", response);
         display_paths(&response.paths);
@@ -663,9 +714,19 @@ async fn best_paths(client: &GisClient) -> Result<(), Box<dyn std::error::Error>
             time_start: Some(time_start),
             time_end: Some(time_end),
             limit: 5,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
-        let response = client.best_path(request).await?.into_inner();
+        let response = query.best_path(request).await?.into_inner();
 
         println!("RESPONSE={:?}", response);
         display_paths(&response.paths);
@@ -697,29 +758,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    let client = GisClient::new_client(&host, port, "gis");
-    println!("Client created");
+    let ingest = GisIngestClient::new_client(&host, port, "gis");
+    let query = GisQueryClient::new_client(&host, port, "gis");
+    println!("Clients created");
     println!(
         "NOTE: Ensure the server is running on {} or this example will fail.",
-        client.get_address()
+        query.get_address()
     );
 
     {
         println!("\n\u{1F44D} Ready Check");
-        let response = client.is_ready(ReadyRequest {}).await?.into_inner();
+        let response = query.is_ready(ReadyRequest {}).await?.into_inner();
 
         println!("RESPONSE={:?}", response);
         assert_eq!(response.ready, true);
     }
 
     add_aircraft(&mut connection).await.unwrap();
-    add_flight_paths(&client).await.unwrap();
+    add_flight_paths(&ingest).await.unwrap();
     std::thread::sleep(std::time::Duration::from_secs(1));
-    get_flights(&client).await?;
-    add_vertiports(&client).await?;
-    add_waypoints(&client).await?;
-    best_paths(&client).await?;
-    best_path_flight_avoidance(&mut connection, &client).await?;
+    get_flights(&query).await?;
+    let no_fly_start_time = Utc::now();
+    let scenario = fixtures::blocking_zone(no_fly_start_time);
+    add_vertiports(&ingest, &scenario).await?;
+    add_waypoints(&ingest, &scenario).await?;
+    best_paths(&ingest, &query, &scenario, no_fly_start_time).await?;
+    best_path_flight_avoidance(&mut connection, &ingest, &query).await?;
 
     Ok(())
 }
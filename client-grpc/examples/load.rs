@@ -0,0 +1,412 @@
+//! Load test against a running svc-gis instance
+//!
+//! Generates a synthetic network of vertiports and zones, then measures:
+//! - telemetry ingestion: aircraft positions are pushed to the Redis queue at
+//!   a fixed rate, and the throughput is the rate at which the server drains
+//!   the queue. It only shows the capacity of the consumers once it falls
+//!   behind the offered rate, so raise `LOAD_AIRCRAFT` or `LOAD_RATE_HZ`
+//!   until it does;
+//! - best path: random vertiport pairs are routed concurrently, and the
+//!   P50/P99 latencies of successful requests are reported.
+//!
+//! Run the same workload before and after a change to spot regressions.
+//!  The workload is configured with these environment variables:
+//! - `LOAD_VERTIPORTS` (default: `25`): vertiports, laid out on a grid
+//! - `LOAD_ZONES` (default: `10`): restricted zones between the vertiports
+//! - `LOAD_AIRCRAFT` (default: `100`): aircraft reporting positions
+//! - `LOAD_RATE_HZ` (default: `1`): positions per aircraft per second
+//! - `LOAD_DURATION_SECONDS` (default: `30`): time spent pushing positions
+//! - `LOAD_BEST_PATH_REQUESTS` (default: `200`): best path requests
+//! - `LOAD_CONCURRENCY` (default: `8`): best path requests in flight
+
+use lib_common::grpc::get_endpoint_from_env;
+use lib_common::time::{Duration, Utc};
+use rand::Rng;
+use std::time::Instant;
+use svc_gis_client_grpc::prelude::{gis::*, *};
+
+/// Southwest corner of the generated network
+const ORIGIN: (f64, f64) = (52.30, 4.80);
+
+/// Distance between two neighbouring vertiports in degrees, about a kilometer
+const SPACING_DEGREES: f64 = 0.01;
+
+/// Half the side of a vertiport in degrees
+const VERTIPORT_HALF_SIDE_DEGREES: f64 = 0.0002;
+
+/// Half the side of a zone in degrees
+const ZONE_HALF_SIDE_DEGREES: f64 = 0.002;
+
+/// Radius of the circles flown by the aircraft
+const CIRCLE_RADIUS_METERS: f64 = 500.0;
+
+/// Ground speed of the aircraft
+const SPEED_MPS: f64 = 20.0;
+
+/// Meters per degree of latitude
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Max time to wait for the server to drain the position queue
+const DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// The synthetic workload
+#[derive(Debug, Copy, Clone)]
+struct Workload {
+    vertiports: usize,
+    zones: usize,
+    aircraft: usize,
+    rate_hz: u32,
+    duration_seconds: u64,
+    best_path_requests: usize,
+    concurrency: usize,
+}
+
+/// Reads a setting from the environment, or its default
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    match std::env::var(key) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("{key} is not valid: {value}")),
+        Err(_) => default,
+    }
+}
+
+impl Workload {
+    fn from_env() -> Self {
+        let workload = Workload {
+            vertiports: env_or("LOAD_VERTIPORTS", 25),
+            zones: env_or("LOAD_ZONES", 10),
+            aircraft: env_or("LOAD_AIRCRAFT", 100),
+            rate_hz: env_or("LOAD_RATE_HZ", 1),
+            duration_seconds: env_or("LOAD_DURATION_SECONDS", 30),
+            best_path_requests: env_or("LOAD_BEST_PATH_REQUESTS", 200),
+            concurrency: env_or("LOAD_CONCURRENCY", 8),
+        };
+
+        assert!(
+            workload.vertiports >= 2,
+            "LOAD_VERTIPORTS must be at least 2"
+        );
+        assert!(workload.rate_hz > 0, "LOAD_RATE_HZ must be positive");
+        assert!(
+            workload.concurrency > 0,
+            "LOAD_CONCURRENCY must be positive"
+        );
+        workload
+    }
+
+    /// Vertiports per row of the grid
+    fn columns(&self) -> usize {
+        (self.vertiports as f64).sqrt().ceil() as usize
+    }
+}
+
+/// Identifier of a generated vertiport
+fn vertiport_identifier(index: usize) -> String {
+    format!("LOAD-VERTIPORT-{index}")
+}
+
+/// A closed square around a point
+fn square(latitude: f64, longitude: f64, half_side: f64) -> Vec<Coordinates> {
+    [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.), (-1., -1.)]
+        .into_iter()
+        .map(|(north, east)| Coordinates {
+            latitude: latitude + north * half_side,
+            longitude: longitude + east * half_side,
+        })
+        .collect()
+}
+
+/// Vertiports on a grid, one per cell
+async fn add_vertiports(
+    client: &GisIngestClient,
+    workload: &Workload,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let columns = workload.columns();
+    let vertiports = (0..workload.vertiports)
+        .map(|index| {
+            let latitude = ORIGIN.0 + (index / columns) as f64 * SPACING_DEGREES;
+            let longitude = ORIGIN.1 + (index % columns) as f64 * SPACING_DEGREES;
+
+            Vertiport {
+                identifier: vertiport_identifier(index),
+                vertices: square(latitude, longitude, VERTIPORT_HALF_SIDE_DEGREES),
+                altitude_meters: 10.0,
+                label: None,
+                timestamp_network: Some(Utc::now().into()),
+                status: None,
+                layers: None,
+                compact_vertices: None,
+                operating_hours: vec![],
+            }
+        })
+        .collect();
+
+    client
+        .update_vertiports(UpdateVertiportsRequest {
+            vertiports,
+            validate_only: false,
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Restricted zones in the middle of grid cells, clear of the vertiports
+async fn add_zones(
+    client: &GisIngestClient,
+    workload: &Workload,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let columns = workload.columns().max(2) - 1;
+    let zones = (0..workload.zones)
+        .map(|index| {
+            let latitude = ORIGIN.0 + ((index / columns) as f64 + 0.5) * SPACING_DEGREES;
+            let longitude = ORIGIN.1 + ((index % columns) as f64 + 0.5) * SPACING_DEGREES;
+
+            Zone {
+                identifier: format!("LOAD-ZONE-{index}"),
+                zone_type: ZoneType::Restriction as i32,
+                vertices: square(latitude, longitude, ZONE_HALF_SIDE_DEGREES),
+                altitude_meters_min: 0.0,
+                altitude_meters_max: 1000.0,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    client
+        .update_zones(UpdateZonesRequest {
+            zones,
+            validate_only: false,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Position of an aircraft flying circles around its vertiport
+fn aircraft_position(workload: &Workload, aircraft: usize, tick: u64) -> AircraftPosition {
+    let cell = aircraft % workload.vertiports;
+    let columns = workload.columns();
+    let center_latitude = ORIGIN.0 + (cell / columns) as f64 * SPACING_DEGREES;
+    let center_longitude = ORIGIN.1 + (cell % columns) as f64 * SPACING_DEGREES;
+
+    let step_radians = SPEED_MPS / workload.rate_hz as f64 / CIRCLE_RADIUS_METERS;
+    let angle = aircraft as f64 + tick as f64 * step_radians;
+    let (north, east) = (
+        CIRCLE_RADIUS_METERS * angle.cos(),
+        CIRCLE_RADIUS_METERS * angle.sin(),
+    );
+
+    let latitude = center_latitude + north / METERS_PER_DEGREE;
+    let longitude =
+        center_longitude + east / (METERS_PER_DEGREE * center_latitude.to_radians().cos());
+
+    AircraftPosition {
+        identifier: format!("LOAD-AIRCRAFT-{aircraft}"),
+        position: Position {
+            latitude: Degrees(latitude),
+            longitude: Degrees(longitude),
+            altitude_meters: Meters(120.0),
+        },
+        timestamp_network: Utc::now(),
+        timestamp_asset: None,
+    }
+}
+
+/// Pushes positions at the configured rate, then waits for the queue to be
+///  drained. Returns the number of positions and the reports per second.
+fn ingest(
+    connection: &mut redis::Connection,
+    workload: &Workload,
+) -> Result<(usize, f64), redis::RedisError> {
+    let ids: Vec<AircraftId> = (0..workload.aircraft)
+        .map(|aircraft| AircraftId {
+            identifier: Some(format!("LOAD-AIRCRAFT-{aircraft}")),
+            session_id: None,
+            aircraft_type: AircraftType::Rotorcraft,
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+            session_ended: false,
+        })
+        .collect();
+
+    let mut pipe = redis::pipe();
+    for id in &ids {
+        pipe.lpush(REDIS_KEY_AIRCRAFT_ID, serde_json::to_vec(id).unwrap());
+    }
+    pipe.query::<()>(connection)?;
+
+    let period = std::time::Duration::from_secs(1) / workload.rate_hz;
+    let ticks = workload.duration_seconds * workload.rate_hz as u64;
+    let started = Instant::now();
+    let mut pushed = 0;
+
+    for tick in 0..ticks {
+        let mut pipe = redis::pipe();
+        for aircraft in 0..workload.aircraft {
+            let position = aircraft_position(workload, aircraft, tick);
+            pipe.lpush(
+                REDIS_KEY_AIRCRAFT_POSITION,
+                serde_json::to_vec(&position).unwrap(),
+            );
+        }
+
+        pipe.query::<()>(connection)?;
+        pushed += workload.aircraft;
+
+        let next = period * (tick + 1) as u32;
+        if let Some(wait) = next.checked_sub(started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+    }
+
+    loop {
+        let queued: usize = redis::cmd("LLEN")
+            .arg(REDIS_KEY_AIRCRAFT_POSITION)
+            .query(connection)?;
+
+        if queued == 0 {
+            break;
+        }
+
+        if started.elapsed() > DRAIN_TIMEOUT {
+            println!("Queue not drained after {DRAIN_TIMEOUT:?}, {queued} positions left.");
+            pushed -= queued;
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    Ok((pushed, pushed as f64 / started.elapsed().as_secs_f64()))
+}
+
+/// Routes random vertiport pairs, returning the latencies of the successful
+///  requests and the number of failed requests
+async fn best_paths(
+    client: &GisQueryClient,
+    workload: &Workload,
+) -> (Vec<std::time::Duration>, usize) {
+    let workers: Vec<_> = (0..workload.concurrency)
+        .map(|worker| {
+            let client = client.clone();
+            let workload = *workload;
+            let requests = (worker..workload.best_path_requests)
+                .step_by(workload.concurrency)
+                .count();
+
+            tokio::spawn(async move {
+                let mut latencies = vec![];
+                let mut failures = 0;
+
+                for _ in 0..requests {
+                    let (origin, target) = {
+                        let mut rng = rand::thread_rng();
+                        let origin = rng.gen_range(0..workload.vertiports);
+                        let offset = rng.gen_range(1..workload.vertiports);
+                        (origin, (origin + offset) % workload.vertiports)
+                    };
+
+                    let time_start = Utc::now();
+                    let request = BestPathRequest {
+                        origin_identifier: vertiport_identifier(origin),
+                        target_identifier: vertiport_identifier(target),
+                        origin_type: NodeType::Vertiport as i32,
+                        target_type: NodeType::Vertiport as i32,
+                        time_start: Some(time_start.into()),
+                        time_end: Some((time_start + Duration::try_hours(1).unwrap()).into()),
+                        limit: 1,
+                        max_path_node_count: None,
+                        weights: None,
+                        include_simulated: false,
+                        priority: FlightPriority::Routine as i32,
+                        max_window_offset_minutes: None,
+                        aircraft_identifier: None,
+                        flight_identifier: None,
+                        arrival_window_minutes: None,
+                        aircraft_type: None,
+                        operator_identifier: None,
+                    };
+
+                    let started = Instant::now();
+                    match client.best_path(request).await {
+                        Ok(_) => latencies.push(started.elapsed()),
+                        Err(_) => failures += 1,
+                    }
+                }
+
+                (latencies, failures)
+            })
+        })
+        .collect();
+
+    let mut latencies = vec![];
+    let mut failures = 0;
+    for worker in workers {
+        let (worker_latencies, worker_failures) = worker.await.unwrap();
+        latencies.extend(worker_latencies);
+        failures += worker_failures;
+    }
+
+    (latencies, failures)
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[std::time::Duration], percent: f64) -> std::time::Duration {
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let workload = Workload::from_env();
+    println!("Workload: {workload:?}");
+
+    let redis_client = redis::Client::open(std::env::var("REDIS__URL")?)?;
+    let mut connection = redis_client.get_connection()?;
+
+    let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    let ingest_client = GisIngestClient::new_client(&host, port, "gis");
+    let query_client = GisQueryClient::new_client(&host, port, "gis");
+    println!(
+        "NOTE: Ensure the server is running on {} or this load test will fail.",
+        query_client.get_address()
+    );
+
+    let response = query_client.is_ready(ReadyRequest {}).await?.into_inner();
+    assert!(response.ready, "server is not ready");
+
+    add_vertiports(&ingest_client, &workload).await?;
+    add_zones(&ingest_client, &workload).await?;
+
+    println!("\n\u{1F4E1} Ingesting telemetry...");
+    let (positions, throughput) =
+        tokio::task::block_in_place(|| ingest(&mut connection, &workload))?;
+    println!(
+        "{positions} positions ingested, {throughput:.1} positions/s (offered {} positions/s).",
+        workload.aircraft * workload.rate_hz as usize
+    );
+
+    println!("\n\u{1F426} Routing...");
+    let started = Instant::now();
+    let (mut latencies, failures) = best_paths(&query_client, &workload).await;
+    let elapsed = started.elapsed();
+    latencies.sort();
+
+    println!(
+        "{} paths in {elapsed:?} ({failures} failed), {:.1} requests/s.",
+        latencies.len(),
+        (latencies.len() + failures) as f64 / elapsed.as_secs_f64()
+    );
+
+    if !latencies.is_empty() {
+        println!(
+            "best_path latency: P50 {:?}, P99 {:?}, max {:?}.",
+            percentile(&latencies, 50.0),
+            percentile(&latencies, 99.0),
+            latencies[latencies.len() - 1]
+        );
+    }
+
+    Ok(())
+}
@@ -7,72 +7,84 @@ use super::*;
 #[cfg(feature = "stub_client")]
 use lib_common::time::Utc;
 
+use ingest_service_client::IngestServiceClient;
 #[cfg(any(not(feature = "stub_client"), feature = "stub_backends"))]
 use lib_common::grpc::ClientConnect;
 use lib_common::grpc::{Client, GrpcClient};
-use rpc_service_client::RpcServiceClient;
-/// GrpcClient implementation of the RpcServiceClient
-pub type GisClient = GrpcClient<RpcServiceClient<Channel>>;
+use query_service_client::QueryServiceClient;
+/// GrpcClient implementation of the IngestServiceClient
+pub type GisIngestClient = GrpcClient<IngestServiceClient<Channel>>;
+/// GrpcClient implementation of the QueryServiceClient
+pub type GisQueryClient = GrpcClient<QueryServiceClient<Channel>>;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "stub_backends")] {
-        use svc_gis::grpc::server::{RpcServiceServer, ServerImpl};
+        use svc_gis::grpc::server::{IngestServiceServer, QueryServiceServer, ServerImpl};
         use deadpool_postgres::{Config, ManagerConfig, RecyclingMethod, Runtime};
         use tokio_postgres::NoTls;
 
-        #[tonic::async_trait]
-        impl lib_common::grpc::ClientConnect<RpcServiceClient<Channel>> for GisClient {
-            /// Get a connected client object
-            async fn connect(
-                &self,
-            ) -> Result<RpcServiceClient<Channel>, tonic::transport::Error> {
-                let (client, server) = tokio::io::duplex(1024);
-                let mut cfg = Config::new();
-                cfg.dbname = Some("deadpool".to_string());
-                cfg.manager = Some(ManagerConfig { recycling_method: RecyclingMethod::Fast });
-
-                let _pool = cfg.create_pool(Some(Runtime::Tokio1), NoTls).unwrap();
-                let grpc_service = ServerImpl { };
-                lib_common::grpc::mock::start_mock_server(
-                    server,
-                    RpcServiceServer::new(grpc_service),
-                )
-                .await?;
-
-                // Move client to an option so we can _move_ the inner value
-                // on the first attempt to connect. All other attempts will fail.
-                let mut client = Some(client);
-                let channel = tonic::transport::Endpoint::try_from("http://[::]:50051")?
-                    .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
-                        let client = client.take();
-
-                        async move {
-                            if let Some(client) = client {
-                                Ok(client)
-                            } else {
-                                Err(std::io::Error::new(
-                                    std::io::ErrorKind::Other,
-                                    "Client already taken",
-                                ))
-                            }
-                        }
-                    }))
-                    .await?;
-
-                Ok(RpcServiceClient::new(channel))
-            }
+        /// Connects a client to an in-process server of the service
+        macro_rules! stub_backends_connect {
+            ($client: ident, $server: ident) => {
+                #[tonic::async_trait]
+                impl lib_common::grpc::ClientConnect<$client<Channel>> for GrpcClient<$client<Channel>> {
+                    /// Get a connected client object
+                    async fn connect(
+                        &self,
+                    ) -> Result<$client<Channel>, tonic::transport::Error> {
+                        let (client, server) = tokio::io::duplex(1024);
+                        let mut cfg = Config::new();
+                        cfg.dbname = Some("deadpool".to_string());
+                        cfg.manager = Some(ManagerConfig { recycling_method: RecyclingMethod::Fast });
+
+                        let _pool = cfg.create_pool(Some(Runtime::Tokio1), NoTls).unwrap();
+                        let grpc_service = ServerImpl { };
+                        lib_common::grpc::mock::start_mock_server(
+                            server,
+                            $server::new(grpc_service),
+                        )
+                        .await?;
+
+                        // Move client to an option so we can _move_ the inner value
+                        // on the first attempt to connect. All other attempts will fail.
+                        let mut client = Some(client);
+                        let channel = tonic::transport::Endpoint::try_from("http://[::]:50051")?
+                            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                                let client = client.take();
+
+                                async move {
+                                    if let Some(client) = client {
+                                        Ok(client)
+                                    } else {
+                                        Err(std::io::Error::new(
+                                            std::io::ErrorKind::Other,
+                                            "Client already taken",
+                                        ))
+                                    }
+                                }
+                            }))
+                            .await?;
+
+                        Ok($client::new(channel))
+                    }
+                }
+            };
         }
 
+        stub_backends_connect!(IngestServiceClient, IngestServiceServer);
+        stub_backends_connect!(QueryServiceClient, QueryServiceServer);
+
         super::log_macros!("grpc", "app::client::mock::gis");
     } else {
-        lib_common::grpc_client!(RpcServiceClient);
+        lib_common::grpc_client!(IngestServiceClient);
+        lib_common::grpc_client!(QueryServiceClient);
         super::log_macros!("grpc", "app::client::gis");
     }
 }
 
 #[cfg(not(feature = "stub_client"))]
 #[async_trait]
-impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
+impl crate::service::IngestClient<IngestServiceClient<Channel>> for GisIngestClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
 
@@ -94,6 +106,15 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
         self.get_client().await?.update_waypoints(request).await
     }
 
+    async fn import_waypoints(
+        &self,
+        request: ImportWaypointsRequest,
+    ) -> Result<tonic::Response<ImportWaypointsSummary>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.import_waypoints(request).await
+    }
+
     async fn update_vertiports(
         &self,
         request: UpdateVertiportsRequest,
@@ -103,6 +124,15 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
         self.get_client().await?.update_vertiports(request).await
     }
 
+    async fn set_vertiport_status(
+        &self,
+        request: SetVertiportStatusRequest,
+    ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.set_vertiport_status(request).await
+    }
+
     async fn update_zones(
         &self,
         request: UpdateZonesRequest,
@@ -112,6 +142,50 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
         self.get_client().await?.update_zones(request).await
     }
 
+    async fn update_noise_areas(
+        &self,
+        request: UpdateNoiseAreasRequest,
+    ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.update_noise_areas(request).await
+    }
+
+    async fn request_zone_authorization(
+        &self,
+        request: ZoneAuthorizationRequest,
+    ) -> Result<tonic::Response<ZoneAuthorization>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client()
+            .await?
+            .request_zone_authorization(request)
+            .await
+    }
+
+    async fn update_zones_stream<S>(
+        &self,
+        request: S,
+    ) -> Result<tonic::Response<UpdateZonesSummary>, tonic::Status>
+    where
+        S: tonic::IntoStreamingRequest<Message = UpdateZonesRequest> + Send + 'static,
+    {
+        grpc_info!("{} client.", self.get_name());
+        self.get_client().await?.update_zones_stream(request).await
+    }
+
+    async fn update_zone_attributes(
+        &self,
+        request: UpdateZoneAttributesRequest,
+    ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client()
+            .await?
+            .update_zone_attributes(request)
+            .await
+    }
+
     async fn update_flight_path(
         &self,
         request: UpdateFlightPathRequest,
@@ -121,6 +195,61 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
         self.get_client().await?.update_flight_path(request).await
     }
 
+    async fn reserve_volume(
+        &self,
+        request: ReserveVolumeRequest,
+    ) -> Result<tonic::Response<VolumeReservation>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.reserve_volume(request).await
+    }
+
+    async fn release_reservation(
+        &self,
+        request: ReleaseReservationRequest,
+    ) -> Result<tonic::Response<VolumeReservation>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.release_reservation(request).await
+    }
+}
+
+#[cfg(not(feature = "stub_client"))]
+#[async_trait]
+impl crate::service::QueryClient<QueryServiceClient<Channel>> for GisQueryClient {
+    type ReadyRequest = ReadyRequest;
+    type ReadyResponse = ReadyResponse;
+
+    async fn is_ready(
+        &self,
+        request: Self::ReadyRequest,
+    ) -> Result<tonic::Response<Self::ReadyResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.is_ready(request).await
+    }
+
+    async fn list_authorizations(
+        &self,
+        request: ListAuthorizationsRequest,
+    ) -> Result<tonic::Response<ListAuthorizationsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.list_authorizations(request).await
+    }
+
+    async fn preview_zone_waypoints(
+        &self,
+        request: PreviewZoneWaypointsRequest,
+    ) -> Result<tonic::Response<PreviewZoneWaypointsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client()
+            .await?
+            .preview_zone_waypoints(request)
+            .await
+    }
+
     async fn best_path(
         &self,
         request: BestPathRequest,
@@ -139,6 +268,63 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
         self.get_client().await?.check_intersection(request).await
     }
 
+    async fn suggest_deconfliction(
+        &self,
+        request: SuggestDeconflictionRequest,
+    ) -> Result<tonic::Response<SuggestDeconflictionResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client()
+            .await?
+            .suggest_deconfliction(request)
+            .await
+    }
+
+    async fn distance_matrix(
+        &self,
+        request: DistanceMatrixRequest,
+    ) -> Result<tonic::Response<DistanceMatrixResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.distance_matrix(request).await
+    }
+
+    async fn check_volume(
+        &self,
+        request: CheckVolumeRequest,
+    ) -> Result<tonic::Response<CheckVolumeResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.check_volume(request).await
+    }
+
+    async fn find_zone_gaps(
+        &self,
+        request: FindZoneGapsRequest,
+    ) -> Result<tonic::Response<FindZoneGapsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.find_zone_gaps(request).await
+    }
+
+    async fn get_zone_constraints(
+        &self,
+        request: GetZoneConstraintsRequest,
+    ) -> Result<tonic::Response<GetZoneConstraintsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_zone_constraints(request).await
+    }
+
+    async fn get_best_path_audits(
+        &self,
+        request: GetBestPathAuditsRequest,
+    ) -> Result<tonic::Response<GetBestPathAuditsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_best_path_audits(request).await
+    }
+
     async fn get_flights(
         &self,
         request: GetFlightsRequest,
@@ -147,11 +333,91 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
         grpc_debug!("request: {:?}", request);
         self.get_client().await?.get_flights(request).await
     }
+
+    async fn get_zones(
+        &self,
+        request: GetZonesRequest,
+    ) -> Result<tonic::Response<GetZonesResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_zones(request).await
+    }
+
+    async fn get_zones_at_point(
+        &self,
+        request: GetZonesAtPointRequest,
+    ) -> Result<tonic::Response<GetZonesAtPointResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_zones_at_point(request).await
+    }
+
+    async fn get_waypoints(
+        &self,
+        request: GetWaypointsRequest,
+    ) -> Result<tonic::Response<GetWaypointsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_waypoints(request).await
+    }
+
+    async fn sync_airspace(
+        &self,
+        request: SyncAirspaceRequest,
+    ) -> Result<tonic::Response<SyncAirspaceResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.sync_airspace(request).await
+    }
+
+    async fn get_vertiports(
+        &self,
+        request: GetVertiportsRequest,
+    ) -> Result<tonic::Response<GetVertiportsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_vertiports(request).await
+    }
+
+    async fn get_service_info(
+        &self,
+        request: ServiceInfoRequest,
+    ) -> Result<tonic::Response<ServiceInfoResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_service_info(request).await
+    }
+
+    async fn get_statistics(
+        &self,
+        request: GetStatisticsRequest,
+    ) -> Result<tonic::Response<GetStatisticsResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_statistics(request).await
+    }
+    async fn check_reachability(
+        &self,
+        request: CheckReachabilityRequest,
+    ) -> Result<tonic::Response<CheckReachabilityResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.check_reachability(request).await
+    }
+
+    async fn get_snapshot(
+        &self,
+        request: GetSnapshotRequest,
+    ) -> Result<tonic::Response<GetSnapshotResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.get_snapshot(request).await
+    }
 }
 
 #[cfg(feature = "stub_client")]
 #[async_trait]
-impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
+impl crate::service::IngestClient<IngestServiceClient<Channel>> for GisIngestClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
 
@@ -161,7 +427,10 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
     ) -> Result<tonic::Response<Self::ReadyResponse>, tonic::Status> {
         grpc_warn!("(MOCK) {} client.", self.get_name());
         grpc_debug!("(MOCK) request: {:?}", request);
-        Ok(tonic::Response::new(ReadyResponse { ready: true }))
+        Ok(tonic::Response::new(ReadyResponse {
+            ready: true,
+            interface_version: crate::prelude::INTERFACE_VERSION.to_string(),
+        }))
     }
 
     async fn update_waypoints(
@@ -170,7 +439,19 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
     ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
         grpc_warn!("(MOCK) {} client.", self.get_name());
         grpc_debug!("(MOCK) request: {:?}", request);
-        Ok(tonic::Response::new(UpdateResponse { updated: true }))
+        Ok(tonic::Response::new(UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn import_waypoints(
+        &self,
+        request: ImportWaypointsRequest,
+    ) -> Result<tonic::Response<ImportWaypointsSummary>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ImportWaypointsSummary::default()))
     }
 
     async fn update_vertiports(
@@ -179,7 +460,22 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
     ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
         grpc_warn!("(MOCK) {} client.", self.get_name());
         grpc_debug!("(MOCK) request: {:?}", request);
-        Ok(tonic::Response::new(UpdateResponse { updated: true }))
+        Ok(tonic::Response::new(UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn set_vertiport_status(
+        &self,
+        request: SetVertiportStatusRequest,
+    ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
     }
 
     async fn update_zones(
@@ -188,7 +484,62 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
     ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
         grpc_warn!("(MOCK) {} client.", self.get_name());
         grpc_debug!("(MOCK) request: {:?}", request);
-        Ok(tonic::Response::new(UpdateResponse { updated: true }))
+        Ok(tonic::Response::new(UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn update_noise_areas(
+        &self,
+        request: UpdateNoiseAreasRequest,
+    ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn request_zone_authorization(
+        &self,
+        request: ZoneAuthorizationRequest,
+    ) -> Result<tonic::Response<ZoneAuthorization>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ZoneAuthorization {
+            identifier: lib_common::uuid::Uuid::new_v4().to_string(),
+            zone_identifier: request.zone_identifier,
+            aircraft_identifier: request.aircraft_identifier,
+            flight_identifier: request.flight_identifier,
+            time_start: request.time_start,
+            time_end: request.time_end,
+            reference: request.reference,
+        }))
+    }
+
+    async fn update_zones_stream<S>(
+        &self,
+        _request: S,
+    ) -> Result<tonic::Response<UpdateZonesSummary>, tonic::Status>
+    where
+        S: tonic::IntoStreamingRequest<Message = UpdateZonesRequest> + Send + 'static,
+    {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        Ok(tonic::Response::new(UpdateZonesSummary::default()))
+    }
+
+    async fn update_zone_attributes(
+        &self,
+        request: UpdateZoneAttributesRequest,
+    ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
     }
 
     async fn update_flight_path(
@@ -197,7 +548,87 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
     ) -> Result<tonic::Response<UpdateResponse>, tonic::Status> {
         grpc_warn!("(MOCK) {} client.", self.get_name());
         grpc_debug!("(MOCK) request: {:?}", request);
-        Ok(tonic::Response::new(UpdateResponse { updated: true }))
+        Ok(tonic::Response::new(UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn reserve_volume(
+        &self,
+        request: ReserveVolumeRequest,
+    ) -> Result<tonic::Response<VolumeReservation>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(VolumeReservation {
+            identifier: lib_common::uuid::Uuid::new_v4().to_string(),
+            operator_identifier: request.operator_identifier,
+            altitude_meters_min: request.altitude_meters_min,
+            altitude_meters_max: request.altitude_meters_max,
+            time_start: request.time_start,
+            time_end: request.time_end,
+            reference: request.reference,
+            released: None,
+        }))
+    }
+
+    async fn release_reservation(
+        &self,
+        request: ReleaseReservationRequest,
+    ) -> Result<tonic::Response<VolumeReservation>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(VolumeReservation {
+            identifier: request.identifier,
+            operator_identifier: request.operator_identifier,
+            released: Some(Utc::now().into()),
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(feature = "stub_client")]
+#[async_trait]
+impl crate::service::QueryClient<QueryServiceClient<Channel>> for GisQueryClient {
+    type ReadyRequest = ReadyRequest;
+    type ReadyResponse = ReadyResponse;
+
+    async fn is_ready(
+        &self,
+        request: Self::ReadyRequest,
+    ) -> Result<tonic::Response<Self::ReadyResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ReadyResponse {
+            ready: true,
+            interface_version: crate::prelude::INTERFACE_VERSION.to_string(),
+        }))
+    }
+
+    async fn list_authorizations(
+        &self,
+        request: ListAuthorizationsRequest,
+    ) -> Result<tonic::Response<ListAuthorizationsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ListAuthorizationsResponse::default()))
+    }
+
+    async fn preview_zone_waypoints(
+        &self,
+        request: PreviewZoneWaypointsRequest,
+    ) -> Result<tonic::Response<PreviewZoneWaypointsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(PreviewZoneWaypointsResponse {
+            waypoints: vec![WaypointCandidate {
+                location: Some(Coordinates {
+                    latitude: 52.3745905,
+                    longitude: 4.9160036,
+                }),
+                blocked: false,
+            }],
+        }))
     }
 
     async fn best_path(
@@ -219,7 +650,15 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
                     }),
                 }],
                 distance_meters: 0.0,
+                costs: Some(PathCosts::default()),
+                advisories: vec![],
+                arrival_congestion: 0,
             }],
+            blockers: vec![],
+            time_start: request.time_start,
+            time_end: request.time_end,
+            truncated: false,
+            rejected_count: 0,
         }))
     }
 
@@ -231,6 +670,89 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
         grpc_debug!("(MOCK) request: {:?}", request);
         Ok(tonic::Response::new(CheckIntersectionResponse {
             intersects: false,
+            advisories: vec![],
+        }))
+    }
+
+    async fn suggest_deconfliction(
+        &self,
+        request: SuggestDeconflictionRequest,
+    ) -> Result<tonic::Response<SuggestDeconflictionResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(SuggestDeconflictionResponse {
+            intersects: false,
+            suggestion: None,
+        }))
+    }
+
+    async fn distance_matrix(
+        &self,
+        request: DistanceMatrixRequest,
+    ) -> Result<tonic::Response<DistanceMatrixResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let entries = request
+            .origin_identifiers
+            .iter()
+            .flat_map(|origin| {
+                request
+                    .target_identifiers
+                    .iter()
+                    .map(|target| DistanceMatrixEntry {
+                        origin_identifier: origin.clone(),
+                        target_identifier: target.clone(),
+                        straight_line_meters: 1000.0,
+                        routable_meters: Some(1200.0),
+                        feasible: true,
+                    })
+            })
+            .collect();
+
+        Ok(tonic::Response::new(DistanceMatrixResponse { entries }))
+    }
+
+    async fn check_volume(
+        &self,
+        request: CheckVolumeRequest,
+    ) -> Result<tonic::Response<CheckVolumeResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(CheckVolumeResponse {
+            zone_identifiers: vec![],
+            flights: vec![],
+            reservation_identifiers: vec![],
+        }))
+    }
+
+    async fn find_zone_gaps(
+        &self,
+        request: FindZoneGapsRequest,
+    ) -> Result<tonic::Response<FindZoneGapsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(FindZoneGapsResponse { gaps: vec![] }))
+    }
+
+    async fn get_zone_constraints(
+        &self,
+        request: GetZoneConstraintsRequest,
+    ) -> Result<tonic::Response<GetZoneConstraintsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(GetZoneConstraintsResponse {
+            constraints: vec![],
+        }))
+    }
+
+    async fn get_best_path_audits(
+        &self,
+        request: GetBestPathAuditsRequest,
+    ) -> Result<tonic::Response<GetBestPathAuditsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(GetBestPathAuditsResponse {
+            audits: vec![],
         }))
     }
 
@@ -265,9 +787,266 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
                     track_angle_degrees: 12.0,
                     ground_speed_mps: 5.0,
                     vertical_speed_mps: 1.0,
+                    staleness_seconds: 0.0,
+                    quality: crate::DataQuality::Fresh.into(),
                 }),
+                nearest_zone: None,
+                time_start: None,
+                time_end: None,
+                progress: None,
+            }],
+            next_offset: None,
+        }))
+    }
+
+    async fn get_zones(
+        &self,
+        request: GetZonesRequest,
+    ) -> Result<tonic::Response<GetZonesResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(GetZonesResponse {
+            zones: vec![Zone {
+                identifier: "mock zone".to_string(),
+                zone_type: ZoneType::Restriction.into(),
+                vertices: vec![
+                    Coordinates {
+                        latitude: 52.3745905,
+                        longitude: 4.9160036,
+                    },
+                    Coordinates {
+                        latitude: 52.3749819,
+                        longitude: 4.9156925,
+                    },
+                    Coordinates {
+                        latitude: 52.3752144,
+                        longitude: 4.9153733,
+                    },
+                    Coordinates {
+                        latitude: 52.3745905,
+                        longitude: 4.9160036,
+                    },
+                ],
+                altitude_meters_min: 0.0,
+                altitude_meters_max: 100.0,
+                time_start: None,
+                time_end: None,
+                schedule: vec![],
+                waypoint_buffer_meters: None,
+                parts: vec![],
+                compact_vertices: None,
+                metadata: None,
+                port_operating_hours: vec![],
             }],
-            // isas: vec![],
+            next_offset: None,
+        }))
+    }
+
+    async fn get_zones_at_point(
+        &self,
+        request: GetZonesAtPointRequest,
+    ) -> Result<tonic::Response<GetZonesAtPointResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(GetZonesAtPointResponse::default()))
+    }
+
+    async fn get_waypoints(
+        &self,
+        request: GetWaypointsRequest,
+    ) -> Result<tonic::Response<GetWaypointsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let location = Some(Coordinates {
+            latitude: 52.3745905,
+            longitude: 4.9160036,
+        });
+
+        if request.zoom.is_some() {
+            return Ok(tonic::Response::new(GetWaypointsResponse {
+                waypoints: vec![],
+                next_offset: None,
+                clusters: vec![WaypointCluster {
+                    location,
+                    count: 1,
+                    identifier: Some("mock waypoint".to_string()),
+                }],
+            }));
+        }
+
+        Ok(tonic::Response::new(GetWaypointsResponse {
+            waypoints: vec![Waypoint {
+                identifier: "mock waypoint".to_string(),
+                location,
+            }],
+            next_offset: None,
+            clusters: vec![],
+        }))
+    }
+
+    async fn sync_airspace(
+        &self,
+        request: SyncAirspaceRequest,
+    ) -> Result<tonic::Response<SyncAirspaceResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(SyncAirspaceResponse {
+            changes: vec![AirspaceChange {
+                sequence: request.cursor + 1,
+                entity: AirspaceEntity::Waypoint as i32,
+                identifier: "mock waypoint".to_string(),
+                deleted: false,
+                zone: None,
+                waypoint: Some(Waypoint {
+                    identifier: "mock waypoint".to_string(),
+                    location: Some(Coordinates {
+                        latitude: 52.3745905,
+                        longitude: 4.9160036,
+                    }),
+                }),
+            }],
+            cursor: request.cursor + 1,
+            has_more: false,
+        }))
+    }
+
+    async fn get_vertiports(
+        &self,
+        request: GetVertiportsRequest,
+    ) -> Result<tonic::Response<GetVertiportsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(GetVertiportsResponse {
+            vertiports: vec![Vertiport {
+                identifier: "mock vertiport".to_string(),
+                vertices: vec![
+                    Coordinates {
+                        latitude: 52.3745905,
+                        longitude: 4.9160036,
+                    },
+                    Coordinates {
+                        latitude: 52.3749819,
+                        longitude: 4.9156925,
+                    },
+                    Coordinates {
+                        latitude: 52.3752144,
+                        longitude: 4.9153733,
+                    },
+                    Coordinates {
+                        latitude: 52.3745905,
+                        longitude: 4.9160036,
+                    },
+                ],
+                altitude_meters: 10.0,
+                label: Some("Mock Vertiport".to_string()),
+                timestamp_network: None,
+                status: Some(VertiportStatus::Operational.into()),
+                layers: None,
+                compact_vertices: None,
+                operating_hours: vec![],
+            }],
+            next_offset: None,
+        }))
+    }
+
+    async fn get_service_info(
+        &self,
+        request: ServiceInfoRequest,
+    ) -> Result<tonic::Response<ServiceInfoResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ServiceInfoResponse {
+            version: "mock".to_string(),
+            features: vec!["mock".to_string()],
+            schema: "arrow".to_string(),
+            limits: Some(ServiceLimits {
+                max_path_count: 5,
+                min_path_node_count: 2,
+                max_path_node_count: 10,
+                default_path_node_count: 3,
+                max_flight_distance_meters: 300_000.0,
+                flight_levels_meters: vec![40.0, 80.0, 120.0],
+                flight_separation_meters: 10.0,
+                max_distance_matrix_node_count: 25,
+                min_polygon_vertices: 4,
+                max_waypoint_buffer_meters: 5000.0,
+                default_page_size: 100,
+                max_page_size: 1000,
+                aircraft_identifier_max_length: 255,
+                aircraft_stale_seconds: 10,
+                aircraft_expired_seconds: 120,
+                telemetry_altitude_ceiling_meters: 0.0,
+                telemetry_clock_skew_seconds: 0,
+                max_cluster_zoom: 22,
+                max_window_offset_minutes: 120,
+                max_arrival_window_minutes: 120,
+            }),
+            database: Some(DatabaseInfo {
+                postgres_version: "mock".to_string(),
+                postgis_version: "mock".to_string(),
+                sfcgal_version: "mock".to_string(),
+            }),
+            queue_latencies: vec![],
+            interface_version: crate::prelude::INTERFACE_VERSION.to_string(),
+        }))
+    }
+
+    async fn get_statistics(
+        &self,
+        request: GetStatisticsRequest,
+    ) -> Result<tonic::Response<GetStatisticsResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(GetStatisticsResponse {
+            active_zones: vec![ZoneTypeCount {
+                zone_type: ZoneType::Restriction.into(),
+                count: 1,
+            }],
+            flights_in_progress: 1,
+            aircraft_seen: 1,
+            waypoint_count: 1,
+            best_path_count: 0,
+            best_path_average_latency_ms: None,
+        }))
+    }
+    async fn check_reachability(
+        &self,
+        request: CheckReachabilityRequest,
+    ) -> Result<tonic::Response<CheckReachabilityResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(CheckReachabilityResponse {
+            reachable: true,
+            confidence: ReachabilityConfidence::High.into(),
+            straight_line_meters: 1000.0,
+            estimated_meters: 1300.0,
+            range_meters: request.remaining_range_meters.unwrap_or(30000.0),
+            direct_blocked: false,
+            estimated_arrival: Some(Utc::now().into()),
+        }))
+    }
+
+    async fn get_snapshot(
+        &self,
+        request: GetSnapshotRequest,
+    ) -> Result<tonic::Response<GetSnapshotResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let snapshot = AirspaceSnapshot {
+            time: request.time.or_else(|| Some(Utc::now().into())),
+            ..Default::default()
+        };
+
+        if request.bundle {
+            return Ok(tonic::Response::new(GetSnapshotResponse {
+                snapshot: None,
+                bundle: prost::Message::encode_to_vec(&snapshot),
+            }));
+        }
+
+        Ok(tonic::Response::new(GetSnapshotResponse {
+            snapshot: Some(snapshot),
+            bundle: vec![],
         }))
     }
 }
@@ -275,30 +1054,43 @@ impl crate::service::Client<RpcServiceClient<Channel>> for GisClient {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::service::Client as ServiceClient;
+    use crate::service::{IngestClient, QueryClient};
     use tonic::transport::Channel;
 
-    fn get_client() -> GrpcClient<RpcServiceClient<Channel>> {
-        let name = "gis";
-        let (server_host, server_port) =
-            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+    fn get_endpoint() -> (String, u16) {
+        lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT")
+    }
+
+    fn get_ingest_client() -> GrpcClient<IngestServiceClient<Channel>> {
+        let (server_host, server_port) = get_endpoint();
+        GrpcClient::new_client(&server_host, server_port, "gis")
+    }
 
-        GrpcClient::new_client(&server_host, server_port, name)
+    fn get_query_client() -> GrpcClient<QueryServiceClient<Channel>> {
+        let (server_host, server_port) = get_endpoint();
+        GrpcClient::new_client(&server_host, server_port, "gis")
     }
 
     #[tokio::test]
     #[cfg(not(feature = "stub_client"))]
     async fn test_client_connect() {
-        let client = get_client();
-        let connection = client.get_client().await;
+        let connection = get_ingest_client().get_client().await;
+        println!("{:?}", connection);
+        assert!(connection.is_ok());
+
+        let connection = get_query_client().get_client().await;
         println!("{:?}", connection);
         assert!(connection.is_ok());
     }
 
     #[tokio::test]
     async fn test_client_is_ready_request() {
-        let client = get_client();
-        let result = client.is_ready(ReadyRequest {}).await;
+        let result = get_ingest_client().is_ready(ReadyRequest {}).await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().into_inner().ready, true);
+
+        let result = get_query_client().is_ready(ReadyRequest {}).await;
         println!("{:?}", result);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().into_inner().ready, true);
@@ -0,0 +1,191 @@
+//! Checking the interface version of svc-gis
+//!
+//! The server reports the version of its gRPC interface in the
+//!  [`ReadyResponse`] of `isReady` and in `getServiceInfo`. Servers of a
+//!  different major version are refused, a different minor version is
+//!  logged as a warning.
+//!
+//! ```
+//! use svc_gis_client_grpc::client::ReadyResponse;
+//! use svc_gis_client_grpc::compat::{check_ready, CompatError};
+//!
+//! fn connect(response: &ReadyResponse) -> Result<(), CompatError> {
+//!     let compatibility = check_ready(response)?;
+//!     println!("svc-gis interface: {:?}", compatibility);
+//!     Ok(())
+//! }
+//! ```
+
+use super::client::ReadyResponse;
+use super::prelude::INTERFACE_VERSION;
+use std::fmt::{self, Display, Formatter};
+
+super::log_macros!("grpc", "app::client::compat");
+
+/// Possible errors checking the interface version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatError {
+    /// The version is not `MAJOR.MINOR`
+    Version(String),
+
+    /// The server has a different major version
+    Incompatible {
+        /// Interface version of the server
+        server: String,
+
+        /// Interface version of this client
+        client: String,
+    },
+}
+
+impl Display for CompatError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CompatError::Version(version) => {
+                write!(f, "Invalid interface version '{version}'.")
+            }
+            CompatError::Incompatible { server, client } => write!(
+                f,
+                "Server interface version {server} is incompatible with client version {client}."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CompatError {}
+
+/// How the interface of the server compares with this client
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Same major and minor version
+    Compatible,
+
+    /// Same major version, different minor version
+    MinorMismatch,
+
+    /// The server does not report its version, predating the handshake
+    Unknown,
+}
+
+/// Splits a `MAJOR.MINOR` version
+fn parse_version(version: &str) -> Result<(u32, u32), CompatError> {
+    let invalid = || CompatError::Version(version.to_string());
+    let (major, minor) = version.trim().split_once('.').ok_or_else(invalid)?;
+    let major = major.parse::<u32>().map_err(|_| invalid())?;
+    let minor = minor.parse::<u32>().map_err(|_| invalid())?;
+    Ok((major, minor))
+}
+
+/// Compares two interface versions, the server's against the client's
+fn compare(server: &str, client: &str) -> Result<Compatibility, CompatError> {
+    if server.trim().is_empty() {
+        return Ok(Compatibility::Unknown);
+    }
+
+    let (server_major, server_minor) = parse_version(server)?;
+    let (client_major, client_minor) = parse_version(client)?;
+    if server_major != client_major {
+        return Err(CompatError::Incompatible {
+            server: server.to_string(),
+            client: client.to_string(),
+        });
+    }
+
+    if server_minor != client_minor {
+        return Ok(Compatibility::MinorMismatch);
+    }
+
+    Ok(Compatibility::Compatible)
+}
+
+/// Checks the interface version reported by the server against the one
+///  this client was built with. Fails if the major versions differ, warns
+///  if the minor versions differ or the server reports none.
+pub fn check_interface_version(server: &str) -> Result<Compatibility, CompatError> {
+    let compatibility = compare(server, INTERFACE_VERSION).map_err(|e| {
+        grpc_error!("{e}");
+        e
+    })?;
+
+    match compatibility {
+        Compatibility::Compatible => (),
+        Compatibility::MinorMismatch => grpc_warn!(
+            "server interface version {server} differs from client version {INTERFACE_VERSION}."
+        ),
+        Compatibility::Unknown => {
+            grpc_warn!("server does not report its interface version.")
+        }
+    }
+
+    Ok(compatibility)
+}
+
+/// Checks the interface version of a [`ReadyResponse`]
+pub fn check_ready(response: &ReadyResponse) -> Result<Compatibility, CompatError> {
+    check_interface_version(&response.interface_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_parse_version() {
+        assert_eq!(parse_version("1.0").unwrap(), (1, 0));
+        assert_eq!(parse_version(" 2.13 ").unwrap(), (2, 13));
+        assert_eq!(
+            parse_version("1").unwrap_err(),
+            CompatError::Version("1".to_string())
+        );
+        assert!(parse_version("1.x").is_err());
+        assert!(parse_version("-1.0").is_err());
+        assert!(parse_version(INTERFACE_VERSION).is_ok());
+    }
+
+    #[test]
+    fn ut_compare() {
+        assert_eq!(compare("1.0", "1.0").unwrap(), Compatibility::Compatible);
+        assert_eq!(compare("1.2", "1.0").unwrap(), Compatibility::MinorMismatch);
+        assert_eq!(compare("1.0", "1.2").unwrap(), Compatibility::MinorMismatch);
+        assert_eq!(compare("", "1.0").unwrap(), Compatibility::Unknown);
+        assert_eq!(
+            compare("2.0", "1.0").unwrap_err(),
+            CompatError::Incompatible {
+                server: "2.0".to_string(),
+                client: "1.0".to_string(),
+            }
+        );
+        assert!(compare("two", "1.0").is_err());
+    }
+
+    #[test]
+    fn ut_check_ready() {
+        let response = ReadyResponse {
+            ready: true,
+            interface_version: INTERFACE_VERSION.to_string(),
+        };
+        assert_eq!(check_ready(&response).unwrap(), Compatibility::Compatible);
+
+        let response = ReadyResponse {
+            ready: true,
+            interface_version: "0.1".to_string(),
+        };
+        assert!(check_ready(&response).is_err());
+    }
+
+    #[test]
+    fn test_compat_error_display() {
+        assert_eq!(
+            CompatError::Version("1".to_string()).to_string(),
+            "Invalid interface version '1'."
+        );
+        assert_eq!(
+            CompatError::Incompatible {
+                server: "2.0".to_string(),
+                client: "1.0".to_string(),
+            }
+            .to_string(),
+            "Server interface version 2.0 is incompatible with client version 1.0."
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Reading the error details of svc-gis statuses
+//!
+//! Update calls rejected for invalid fields return
+//!  [`Code::InvalidArgument`](tonic::Code::InvalidArgument) with a
+//!  [`BadRequest`] in the status details, naming each field and the entity
+//!  it belongs to.
+//!
+//! ```
+//! use svc_gis_client_grpc::details::field_violations;
+//!
+//! fn report(status: &tonic::Status) {
+//!     for violation in field_violations(status) {
+//!         println!("{}: {}", violation.field, violation.description);
+//!     }
+//! }
+//! ```
+
+use super::client::{BadRequest, FieldViolation};
+use prost::Message;
+
+/// Type URL of a packed `google.rpc.BadRequest`
+pub const BAD_REQUEST_TYPE_URL: &str = "type.googleapis.com/google.rpc.BadRequest";
+
+/// Envelope of the status details, as `google.rpc.Status`
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    /// The status code
+    #[prost(int32, tag = "1")]
+    code: i32,
+
+    /// The status message
+    #[prost(string, tag = "2")]
+    message: String,
+
+    /// The packed details
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<prost_types::Any>,
+}
+
+/// Gets the rejected fields listed in the details of a status, empty if
+///  the status has none
+pub fn field_violations(status: &tonic::Status) -> Vec<FieldViolation> {
+    let Ok(details) = RpcStatus::decode(status.details()) else {
+        return vec![];
+    };
+
+    details
+        .details
+        .into_iter()
+        .filter(|any| any.type_url == BAD_REQUEST_TYPE_URL)
+        .filter_map(|any| BadRequest::decode(any.value.as_slice()).ok())
+        .flat_map(|bad_request| bad_request.field_violations)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::codegen::Bytes;
+    use tonic::Code;
+
+    #[test]
+    fn ut_field_violations() {
+        let violation = FieldViolation {
+            field: "zones[0].identifier".to_string(),
+            description: "zone 'NFZ A': Invalid identifier provided.".to_string(),
+        };
+
+        let details = RpcStatus {
+            code: Code::InvalidArgument as i32,
+            message: "invalid zones".to_string(),
+            details: vec![prost_types::Any {
+                type_url: BAD_REQUEST_TYPE_URL.to_string(),
+                value: BadRequest {
+                    field_violations: vec![violation.clone()],
+                }
+                .encode_to_vec(),
+            }],
+        };
+
+        let status = tonic::Status::with_details(
+            Code::InvalidArgument,
+            "invalid zones",
+            Bytes::from(details.encode_to_vec()),
+        );
+        assert_eq!(field_violations(&status), vec![violation]);
+
+        let status = tonic::Status::internal("database error");
+        assert!(field_violations(&status).is_empty());
+    }
+}
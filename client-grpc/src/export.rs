@@ -0,0 +1,309 @@
+//! Export of best paths to mapping formats
+//!
+//! Paths are written as GeoJSON `LineString` features or KML line strings,
+//!  with the altitude of each node as the third coordinate and the node
+//!  identifiers and types kept as metadata:
+//!
+//! ```
+//! use svc_gis_client_grpc::export;
+//! use svc_gis_client_grpc::prelude::gis::{BestPathResponse, NodeType, Path, PathNode, PointZ};
+//!
+//! let node = |index: i32, identifier: &str, latitude: f64| PathNode {
+//!     index,
+//!     node_type: NodeType::Vertiport as i32,
+//!     identifier: identifier.to_string(),
+//!     geom: Some(PointZ {
+//!         latitude,
+//!         longitude: 4.9160,
+//!         altitude_meters: 50.0,
+//!     }),
+//! };
+//!
+//! let response = BestPathResponse {
+//!     paths: vec![Path {
+//!         path: vec![node(0, "vertiport-1", 52.3740), node(1, "vertiport-2", 52.3840)],
+//!         distance_meters: 1112.0,
+//!         costs: None,
+//!         advisories: vec![],
+//!         arrival_congestion: 0,
+//!     }],
+//!     ..Default::default()
+//! };
+//!
+//! let geojson = export::paths_to_geojson(&response);
+//! assert_eq!(geojson["features"][0]["geometry"]["type"], "LineString");
+//!
+//! let kml = export::paths_to_kml(&response);
+//! assert!(kml.contains("<LineString>"));
+//! ```
+//!
+//! Nodes without a location are left out of the line, but are still listed
+//!  in the metadata.
+
+use crate::client::{BestPathResponse, NodeType, Path, PathNode, PointZ};
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+/// Gets the name of the type of a node
+fn node_type_name(node: &PathNode) -> &'static str {
+    NodeType::try_from(node.node_type)
+        .map(|node_type| node_type.as_str_name())
+        .unwrap_or("UNKNOWN")
+}
+
+/// The located nodes of a path, in order
+fn located_nodes(path: &Path) -> impl Iterator<Item = (&PathNode, &PointZ)> {
+    path.path
+        .iter()
+        .filter_map(|node| node.geom.as_ref().map(|geom| (node, geom)))
+}
+
+/// Converts a path into a GeoJSON `LineString` feature
+pub fn path_to_geojson(path: &Path) -> Value {
+    let coordinates: Vec<Value> = located_nodes(path)
+        .map(|(_, geom)| json!([geom.longitude, geom.latitude, geom.altitude_meters]))
+        .collect();
+
+    let nodes: Vec<Value> = path
+        .path
+        .iter()
+        .map(|node| {
+            json!({
+                "index": node.index,
+                "node_type": node_type_name(node),
+                "identifier": node.identifier,
+                "altitude_meters": node.geom.map(|geom| geom.altitude_meters),
+            })
+        })
+        .collect();
+
+    let costs = path.costs.map(|costs| {
+        json!({
+            "distance_meters": costs.distance_meters,
+            "time_seconds": costs.time_seconds,
+            "ground_risk_meters": costs.ground_risk_meters,
+            "noise_meters": costs.noise_meters,
+            "energy_meters": costs.energy_meters,
+            "total": costs.total,
+        })
+    });
+
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": coordinates,
+        },
+        "properties": {
+            "distance_meters": path.distance_meters,
+            "costs": costs,
+            "nodes": nodes,
+        },
+    })
+}
+
+/// Converts the paths of a response into a GeoJSON feature collection,
+///  one feature per path
+pub fn paths_to_geojson(response: &BestPathResponse) -> Value {
+    let features: Vec<Value> = response.paths.iter().map(path_to_geojson).collect();
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// Escapes the characters reserved in XML text
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Formats a location as a KML coordinate tuple
+fn kml_coordinates(geom: &PointZ) -> String {
+    format!(
+        "{},{},{}",
+        geom.longitude, geom.latitude, geom.altitude_meters
+    )
+}
+
+/// Writes a path as a KML folder holding its line and its nodes
+fn write_kml_path(kml: &mut String, index: usize, path: &Path) -> std::fmt::Result {
+    let coordinates: Vec<String> = located_nodes(path)
+        .map(|(_, geom)| kml_coordinates(geom))
+        .collect();
+
+    writeln!(kml, "    <Folder>")?;
+    writeln!(kml, "      <name>Path {index}</name>")?;
+    writeln!(kml, "      <Placemark>")?;
+    writeln!(kml, "        <name>Path {index}</name>")?;
+    writeln!(kml, "        <ExtendedData>")?;
+    writeln!(
+        kml,
+        "          <Data name=\"distance_meters\"><value>{}</value></Data>",
+        path.distance_meters
+    )?;
+    writeln!(kml, "        </ExtendedData>")?;
+    writeln!(kml, "        <LineString>")?;
+    writeln!(kml, "          <altitudeMode>absolute</altitudeMode>")?;
+    writeln!(
+        kml,
+        "          <coordinates>{}</coordinates>",
+        coordinates.join(" ")
+    )?;
+    writeln!(kml, "        </LineString>")?;
+    writeln!(kml, "      </Placemark>")?;
+
+    for (node, geom) in located_nodes(path) {
+        writeln!(kml, "      <Placemark>")?;
+        writeln!(kml, "        <name>{}</name>", escape_xml(&node.identifier))?;
+        writeln!(kml, "        <ExtendedData>")?;
+        writeln!(
+            kml,
+            "          <Data name=\"index\"><value>{}</value></Data>",
+            node.index
+        )?;
+        writeln!(
+            kml,
+            "          <Data name=\"node_type\"><value>{}</value></Data>",
+            node_type_name(node)
+        )?;
+        writeln!(kml, "        </ExtendedData>")?;
+        writeln!(kml, "        <Point>")?;
+        writeln!(kml, "          <altitudeMode>absolute</altitudeMode>")?;
+        writeln!(
+            kml,
+            "          <coordinates>{}</coordinates>",
+            kml_coordinates(geom)
+        )?;
+        writeln!(kml, "        </Point>")?;
+        writeln!(kml, "      </Placemark>")?;
+    }
+
+    writeln!(kml, "    </Folder>")
+}
+
+/// Converts the paths of a response into a KML document, one folder per
+///  path holding the path line and a placemark for each of its nodes
+pub fn paths_to_kml(response: &BestPathResponse) -> String {
+    let mut kml = String::new();
+    kml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    kml.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n");
+    kml.push_str("  <Document>\n");
+
+    for (index, path) in response.paths.iter().enumerate() {
+        // writing to a String can't fail
+        let _ = write_kml_path(&mut kml, index, path);
+    }
+
+    kml.push_str("  </Document>\n");
+    kml.push_str("</kml>\n");
+    kml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::PathCosts;
+
+    fn node(index: i32, identifier: &str, geom: Option<(f64, f64, f32)>) -> PathNode {
+        PathNode {
+            index,
+            node_type: NodeType::Waypoint as i32,
+            identifier: identifier.to_string(),
+            geom: geom.map(|(latitude, longitude, altitude_meters)| PointZ {
+                latitude,
+                longitude,
+                altitude_meters,
+            }),
+        }
+    }
+
+    fn path() -> Path {
+        Path {
+            path: vec![
+                node(0, "vertiport-1", Some((52.3740, 4.9160, 0.0))),
+                node(1, "waypoint-1", Some((52.3790, 4.9170, 120.0))),
+                node(2, "waypoint-2", None),
+                node(3, "vertiport-2", Some((52.3840, 4.9180, 0.0))),
+            ],
+            distance_meters: 1200.0,
+            costs: None,
+            advisories: vec![],
+            arrival_congestion: 0,
+        }
+    }
+
+    #[test]
+    fn ut_path_to_geojson() {
+        let feature = path_to_geojson(&path());
+        assert_eq!(feature["type"], "Feature");
+
+        let coordinates = feature["geometry"]["coordinates"].as_array().unwrap();
+        assert_eq!(coordinates.len(), 3);
+        assert_eq!(coordinates[1], json!([4.9170, 52.3790, 120.0]));
+
+        let nodes = feature["properties"]["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[1]["identifier"], "waypoint-1");
+        assert_eq!(nodes[1]["node_type"], "WAYPOINT");
+        assert_eq!(nodes[2]["altitude_meters"], Value::Null);
+        assert_eq!(feature["properties"]["distance_meters"], 1200.0);
+        assert_eq!(feature["properties"]["costs"], Value::Null);
+
+        let costs = PathCosts {
+            time_seconds: 40.0,
+            total: 1240.0,
+            ..Default::default()
+        };
+        let feature = path_to_geojson(&Path {
+            costs: Some(costs),
+            ..path()
+        });
+        assert_eq!(feature["properties"]["costs"]["time_seconds"], 40.0);
+        assert_eq!(feature["properties"]["costs"]["total"], 1240.0);
+    }
+
+    #[test]
+    fn ut_paths_to_geojson() {
+        let response = BestPathResponse {
+            paths: vec![path(), path()],
+            ..Default::default()
+        };
+
+        let collection = paths_to_geojson(&response);
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["features"].as_array().unwrap().len(), 2);
+
+        let empty = paths_to_geojson(&BestPathResponse::default());
+        assert!(empty["features"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn ut_paths_to_kml() {
+        let mut path = path();
+        path.path[0].identifier = "<vertiport & 1>".to_string();
+        let response = BestPathResponse {
+            paths: vec![path],
+            ..Default::default()
+        };
+
+        let kml = paths_to_kml(&response);
+        assert!(kml.starts_with("<?xml"));
+        assert!(kml
+            .contains("<coordinates>4.916,52.374,0 4.917,52.379,120 4.918,52.384,0</coordinates>"));
+        assert_eq!(kml.matches("<Placemark>").count(), 4);
+        assert!(kml.contains("<name>&lt;vertiport &amp; 1&gt;</name>"));
+        assert!(kml.contains("<Data name=\"node_type\"><value>WAYPOINT</value></Data>"));
+        assert!(kml.trim_end().ends_with("</kml>"));
+    }
+
+    #[test]
+    fn ut_escape_xml() {
+        assert_eq!(escape_xml(r#"a<b>&"c'"#), "a&lt;b&gt;&amp;&quot;c&apos;");
+        assert_eq!(escape_xml("waypoint-1"), "waypoint-1");
+    }
+}
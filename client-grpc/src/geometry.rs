@@ -0,0 +1,387 @@
+//! Conversions from [`geo`] types into zone and vertiport messages
+//!
+//! Only the geometry of the message is filled in, the remaining fields are
+//!  set with struct update syntax:
+//!
+//! ```
+//! use svc_gis_client_grpc::prelude::gis::Zone;
+//!
+//! let rect = geo::Rect::new((4.9160, 52.3740), (4.9170, 52.3750));
+//! let zone = Zone {
+//!     identifier: "NOTAM-1".to_string(),
+//!     altitude_meters_max: 120.0,
+//!     ..Zone::try_from(&rect).unwrap()
+//! };
+//!
+//! assert_eq!(zone.vertices.len(), 5);
+//! ```
+//!
+//! Exterior rings are wound counterclockwise and must be closed, with at
+//!  least three distinct vertices. Holes can't be represented by a zone or
+//!  vertiport and are rejected.
+//!
+//! Responses requested with the compact geometry encoding carry their
+//!  vertices as a [`CompactRing`], which [`decode_compact_ring`] turns back
+//!  into coordinates.
+
+use crate::client::{CompactRing, Coordinates, Vertiport, Zone, ZonePart};
+use geo::{LineString, MultiPolygon, Polygon, Rect, Winding};
+use std::fmt::{self, Display, Formatter};
+
+/// A closed ring needs at least three distinct vertices, the first
+///  of which is repeated at the end
+const MIN_RING_VERTICES: usize = 4;
+
+/// Integer units per degree in a compact ring
+const COMPACT_RING_SCALE: f64 = 1e7;
+
+/// Errors converting a geometry into a zone or vertiport
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GeometryError {
+    /// The exterior ring has fewer than three distinct vertices
+    VertexCount,
+
+    /// The first and last vertices of the exterior ring differ
+    OpenRing,
+
+    /// The polygon has interior rings
+    Holes,
+
+    /// The multipolygon holds no polygon
+    Empty,
+
+    /// A vertex is outside of the valid range of latitude and longitude
+    OutOfBounds,
+
+    /// A compact ring has an odd number of deltas
+    CompactRing,
+}
+
+impl Display for GeometryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GeometryError::VertexCount => write!(f, "Invalid number of vertices provided."),
+            GeometryError::OpenRing => write!(
+                f,
+                "The first and last vertices do not match (open polygon)."
+            ),
+            GeometryError::Holes => write!(f, "Polygons with holes are not supported."),
+            GeometryError::Empty => write!(f, "No polygons provided."),
+            GeometryError::OutOfBounds => write!(f, "One or more vertices are out of bounds."),
+            GeometryError::CompactRing => write!(f, "Invalid compact ring provided."),
+        }
+    }
+}
+
+impl std::error::Error for GeometryError {}
+
+/// Converts an exterior ring into counterclockwise vertices
+fn ring_vertices(ring: &LineString<f64>) -> Result<Vec<Coordinates>, GeometryError> {
+    if ring.0.len() < MIN_RING_VERTICES {
+        return Err(GeometryError::VertexCount);
+    }
+
+    if !ring.is_closed() {
+        return Err(GeometryError::OpenRing);
+    }
+
+    let in_bounds = ring
+        .coords()
+        .all(|c| (-180.0..=180.0).contains(&c.x) && (-90.0..=90.0).contains(&c.y));
+
+    if !in_bounds {
+        return Err(GeometryError::OutOfBounds);
+    }
+
+    let mut ring = ring.clone();
+    ring.make_ccw_winding();
+
+    Ok(ring
+        .coords()
+        .map(|c| Coordinates {
+            latitude: c.y,
+            longitude: c.x,
+        })
+        .collect())
+}
+
+/// Converts the exterior of a polygon without holes into vertices
+fn polygon_vertices(polygon: &Polygon<f64>) -> Result<Vec<Coordinates>, GeometryError> {
+    if !polygon.interiors().is_empty() {
+        return Err(GeometryError::Holes);
+    }
+
+    ring_vertices(polygon.exterior())
+}
+
+impl TryFrom<&Polygon<f64>> for Zone {
+    type Error = GeometryError;
+
+    fn try_from(polygon: &Polygon<f64>) -> Result<Self, Self::Error> {
+        Ok(Zone {
+            vertices: polygon_vertices(polygon)?,
+            ..Default::default()
+        })
+    }
+}
+
+impl TryFrom<&MultiPolygon<f64>> for Zone {
+    type Error = GeometryError;
+
+    /// The first polygon is the main area of the zone, the others
+    ///  are additional parts
+    fn try_from(multipolygon: &MultiPolygon<f64>) -> Result<Self, Self::Error> {
+        let mut polygons = multipolygon.iter();
+        let first = polygons.next().ok_or(GeometryError::Empty)?;
+        let parts = polygons
+            .map(|polygon| {
+                Ok(ZonePart {
+                    vertices: polygon_vertices(polygon)?,
+                    compact_vertices: None,
+                })
+            })
+            .collect::<Result<Vec<ZonePart>, GeometryError>>()?;
+
+        Ok(Zone {
+            vertices: polygon_vertices(first)?,
+            parts,
+            ..Default::default()
+        })
+    }
+}
+
+impl TryFrom<&Rect<f64>> for Zone {
+    type Error = GeometryError;
+
+    fn try_from(rect: &Rect<f64>) -> Result<Self, Self::Error> {
+        Zone::try_from(&rect.to_polygon())
+    }
+}
+
+impl TryFrom<&Polygon<f64>> for Vertiport {
+    type Error = GeometryError;
+
+    fn try_from(polygon: &Polygon<f64>) -> Result<Self, Self::Error> {
+        Ok(Vertiport {
+            vertices: polygon_vertices(polygon)?,
+            ..Default::default()
+        })
+    }
+}
+
+impl TryFrom<&Rect<f64>> for Vertiport {
+    type Error = GeometryError;
+
+    fn try_from(rect: &Rect<f64>) -> Result<Self, Self::Error> {
+        Vertiport::try_from(&rect.to_polygon())
+    }
+}
+
+/// Decodes the vertices of a compact ring
+///
+/// ```
+/// use svc_gis_client_grpc::geometry::decode_compact_ring;
+/// use svc_gis_client_grpc::prelude::gis::CompactRing;
+///
+/// let ring = CompactRing {
+///     deltas: vec![49160000, 523740000, 10000, 0, 0, 10000],
+/// };
+///
+/// let vertices = decode_compact_ring(&ring).unwrap();
+/// assert_eq!(vertices.len(), 3);
+/// assert_eq!(vertices[2].latitude, 52.375);
+/// ```
+pub fn decode_compact_ring(ring: &CompactRing) -> Result<Vec<Coordinates>, GeometryError> {
+    if ring.deltas.len() % 2 != 0 {
+        return Err(GeometryError::CompactRing);
+    }
+
+    let mut current = (0_i64, 0_i64);
+    ring.deltas
+        .chunks_exact(2)
+        .map(|delta| {
+            current = (
+                current
+                    .0
+                    .checked_add(delta[0])
+                    .ok_or(GeometryError::CompactRing)?,
+                current
+                    .1
+                    .checked_add(delta[1])
+                    .ok_or(GeometryError::CompactRing)?,
+            );
+
+            let vertex = Coordinates {
+                latitude: current.1 as f64 / COMPACT_RING_SCALE,
+                longitude: current.0 as f64 / COMPACT_RING_SCALE,
+            };
+
+            let in_bounds = (-180.0..=180.0).contains(&vertex.longitude)
+                && (-90.0..=90.0).contains(&vertex.latitude);
+
+            match in_bounds {
+                true => Ok(vertex),
+                false => Err(GeometryError::OutOfBounds),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::{line_string, polygon};
+
+    fn square() -> Polygon<f64> {
+        polygon![
+            (x: 4.9160, y: 52.3740),
+            (x: 4.9170, y: 52.3740),
+            (x: 4.9170, y: 52.3750),
+            (x: 4.9160, y: 52.3750),
+        ]
+    }
+
+    #[test]
+    fn ut_zone_from_polygon() {
+        let zone = Zone::try_from(&square()).unwrap();
+        assert_eq!(zone.vertices.len(), 5);
+        assert_eq!(zone.vertices.first(), zone.vertices.last());
+        assert_eq!(zone.vertices[0].latitude, 52.3740);
+        assert_eq!(zone.vertices[0].longitude, 4.9160);
+        assert!(zone.parts.is_empty());
+    }
+
+    #[test]
+    fn ut_winding() {
+        let mut clockwise = square();
+        clockwise.exterior_mut(|ring| ring.make_cw_winding());
+
+        let zone = Zone::try_from(&clockwise).unwrap();
+        let ring: LineString<f64> = zone
+            .vertices
+            .iter()
+            .map(|v| (v.longitude, v.latitude))
+            .collect();
+
+        assert!(ring.is_ccw());
+    }
+
+    #[test]
+    fn ut_zone_from_multipolygon() {
+        let other = polygon![
+            (x: 4.9200, y: 52.3800),
+            (x: 4.9210, y: 52.3800),
+            (x: 4.9210, y: 52.3810),
+        ];
+
+        let zone = Zone::try_from(&MultiPolygon::new(vec![square(), other])).unwrap();
+        assert_eq!(zone.vertices.len(), 5);
+        assert_eq!(zone.parts.len(), 1);
+        assert_eq!(zone.parts[0].vertices.len(), 4);
+
+        let error = Zone::try_from(&MultiPolygon::<f64>::new(vec![])).unwrap_err();
+        assert_eq!(error, GeometryError::Empty);
+    }
+
+    #[test]
+    fn ut_from_rect() {
+        let rect = Rect::new((4.9160, 52.3740), (4.9170, 52.3750));
+        let zone = Zone::try_from(&rect).unwrap();
+        let vertiport = Vertiport::try_from(&rect).unwrap();
+        assert_eq!(zone.vertices, vertiport.vertices);
+        assert_eq!(vertiport.vertices.len(), 5);
+    }
+
+    #[test]
+    fn ut_invalid_geometry() {
+        let holes = Polygon::new(
+            square().exterior().clone(),
+            vec![line_string![
+                (x: 4.9162, y: 52.3742),
+                (x: 4.9164, y: 52.3742),
+                (x: 4.9164, y: 52.3744),
+                (x: 4.9162, y: 52.3742),
+            ]],
+        );
+        assert_eq!(Zone::try_from(&holes).unwrap_err(), GeometryError::Holes);
+
+        let line = polygon![(x: 4.9160, y: 52.3740), (x: 4.9170, y: 52.3740)];
+        assert_eq!(
+            Vertiport::try_from(&line).unwrap_err(),
+            GeometryError::VertexCount
+        );
+
+        let out_of_bounds = polygon![
+            (x: 4.9160, y: 90.5),
+            (x: 4.9170, y: 90.5),
+            (x: 4.9170, y: 91.0),
+        ];
+        assert_eq!(
+            Zone::try_from(&out_of_bounds).unwrap_err(),
+            GeometryError::OutOfBounds
+        );
+
+        let open = LineString::from(vec![
+            (4.9160, 52.3740),
+            (4.9170, 52.3740),
+            (4.9170, 52.3750),
+            (4.9160, 52.3750),
+        ]);
+        assert_eq!(ring_vertices(&open).unwrap_err(), GeometryError::OpenRing);
+    }
+
+    #[test]
+    fn test_geometry_error_display() {
+        assert_eq!(
+            GeometryError::VertexCount.to_string(),
+            "Invalid number of vertices provided."
+        );
+        assert_eq!(
+            GeometryError::OpenRing.to_string(),
+            "The first and last vertices do not match (open polygon)."
+        );
+        assert_eq!(
+            GeometryError::Holes.to_string(),
+            "Polygons with holes are not supported."
+        );
+        assert_eq!(GeometryError::Empty.to_string(), "No polygons provided.");
+        assert_eq!(
+            GeometryError::OutOfBounds.to_string(),
+            "One or more vertices are out of bounds."
+        );
+        assert_eq!(
+            GeometryError::CompactRing.to_string(),
+            "Invalid compact ring provided."
+        );
+    }
+
+    #[test]
+    fn ut_decode_compact_ring() {
+        let ring = CompactRing {
+            deltas: vec![49160036, 523745905, 0, 1000, 1000, 0, -1000, -1000],
+        };
+
+        let vertices = decode_compact_ring(&ring).unwrap();
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(vertices[0], vertices[3]);
+        assert!((vertices[1].latitude - 52.3746905).abs() < 1e-9);
+        assert!((vertices[2].longitude - 4.9161036).abs() < 1e-9);
+
+        let odd = CompactRing {
+            deltas: vec![49160036],
+        };
+        assert_eq!(decode_compact_ring(&odd), Err(GeometryError::CompactRing));
+
+        let out_of_bounds = CompactRing {
+            deltas: vec![0, 1_000_000_000],
+        };
+        assert_eq!(
+            decode_compact_ring(&out_of_bounds),
+            Err(GeometryError::OutOfBounds)
+        );
+
+        assert!(decode_compact_ring(&CompactRing::default())
+            .unwrap()
+            .is_empty());
+    }
+}
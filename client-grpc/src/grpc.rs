@@ -7,24 +7,48 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReadyRequest {}
 /// Ready Response object
-#[derive(Eq, Copy)]
+#[derive(Eq)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReadyResponse {
     /// True if ready
     #[prost(bool, tag = "1")]
     pub ready: bool,
+    /// Version of the gRPC interface, as MAJOR.MINOR
+    #[prost(string, tag = "2")]
+    pub interface_version: ::prost::alloc::string::String,
 }
 /// General update response object
-#[derive(Eq, Copy)]
+#[derive(Eq)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateResponse {
     /// True if updated
     #[prost(bool, tag = "1")]
     pub updated: bool,
+    /// Outcome of each item, only set for `validate_only` requests
+    #[prost(message, repeated, tag = "2")]
+    pub validations: ::prost::alloc::vec::Vec<ItemValidation>,
+}
+/// Outcome of checking one item of a `validate_only` update
+#[derive(Eq)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ItemValidation {
+    /// Identifier of the item
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// True if the item would be applied
+    #[prost(bool, tag = "2")]
+    pub valid: bool,
+    /// Why the item would be rejected, if it would be
+    #[prost(message, optional, tag = "3")]
+    pub violation: ::core::option::Option<FieldViolation>,
 }
 /// Geospatial Coordinates
+///   Stored rounded to the configured precision, 7 decimal places
+///   (about a centimeter) by default; coordinates equal after rounding
+///   are the same location.
 #[derive(Copy)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -55,6 +79,43 @@ pub struct Vertiport {
     /// Network Timestamp
     #[prost(message, optional, tag = "5")]
     pub timestamp_network: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Operational status
+    /// If not provided on update, the current status is kept
+    #[prost(enumeration = "VertiportStatus", optional, tag = "6")]
+    pub status: ::core::option::Option<i32>,
+    /// Touchdown, approach and safety areas, if surveyed
+    /// If provided, approach points are placed on the approach paths to the
+    ///   FATO instead of around the whole vertiport
+    #[prost(message, optional, tag = "7")]
+    pub layers: ::core::option::Option<VertiportLayers>,
+    /// Vertices in the compact encoding, only set in responses that asked
+    ///   for it, in which case `vertices` is left empty
+    #[prost(message, optional, tag = "8")]
+    pub compact_vertices: ::core::option::Option<CompactRing>,
+    /// Weekly windows during which this vertiport accepts arrivals
+    /// If empty, the vertiport is open at all hours
+    #[prost(message, repeated, tag = "9")]
+    pub operating_hours: ::prost::alloc::vec::Vec<ActiveWindow>,
+}
+/// Surveyed areas of a vertiport
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VertiportLayers {
+    /// Touchdown and lift-off area (TLOF) polygon, within the FATO
+    /// Optional, may be empty
+    #[prost(message, repeated, tag = "1")]
+    pub tlof: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Final approach and take-off area (FATO) polygon, within the vertiport
+    #[prost(message, repeated, tag = "2")]
+    pub fato: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Safety area polygon around the FATO
+    /// Optional, may be empty
+    #[prost(message, repeated, tag = "3")]
+    pub safety_area: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Headings flown on final approach to the FATO, in degrees from true north
+    /// If empty, approaches follow the long axis of the FATO from both ends
+    #[prost(float, repeated, tag = "4")]
+    pub approach_headings_degrees: ::prost::alloc::vec::Vec<f32>,
 }
 /// Waypoint Type
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -74,6 +135,21 @@ pub struct UpdateVertiportsRequest {
     /// Nodes to update
     #[prost(message, repeated, tag = "1")]
     pub vertiports: ::prost::alloc::vec::Vec<Vertiport>,
+    /// Check the vertiports against the stored airspace without applying
+    ///   them, returning the outcome of each
+    #[prost(bool, tag = "2")]
+    pub validate_only: bool,
+}
+/// Set Vertiport Status Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetVertiportStatusRequest {
+    /// Identifier of the vertiport
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// New operational status
+    #[prost(enumeration = "VertiportStatus", tag = "2")]
+    pub status: i32,
 }
 /// Update Waypoints Request object
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -83,6 +159,63 @@ pub struct UpdateWaypointsRequest {
     #[prost(message, repeated, tag = "1")]
     pub waypoints: ::prost::alloc::vec::Vec<Waypoint>,
 }
+/// Import Waypoints Request object
+/// Replaces the waypoints previously imported from the same source
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportWaypointsRequest {
+    /// Publisher and format of the export
+    #[prost(enumeration = "WaypointSource", tag = "1")]
+    pub source: i32,
+    /// Contents of the export
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+/// Summary of a waypoint import
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportWaypointsSummary {
+    /// Number of waypoints inserted or updated
+    #[prost(uint32, tag = "1")]
+    pub imported: u32,
+    /// Number of waypoints previously imported from the source that are no
+    ///   longer in the export
+    #[prost(uint32, tag = "2")]
+    pub removed: u32,
+    /// Number of records in the export that could not be read
+    #[prost(uint32, tag = "3")]
+    pub skipped: u32,
+}
+/// Weekly recurring window, such as when a zone is active or a vertiport
+///   is open
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActiveWindow {
+    /// Days of the week on which this window starts
+    #[prost(enumeration = "Weekday", repeated, tag = "1")]
+    pub days: ::prost::alloc::vec::Vec<i32>,
+    /// Start of the window in minutes after midnight UTC
+    #[prost(uint32, tag = "2")]
+    pub start_minute: u32,
+    /// End of the window in minutes after midnight UTC
+    /// A window ending at or before its start runs past midnight
+    #[prost(uint32, tag = "3")]
+    pub end_minute: u32,
+}
+/// A polygon making up part of a zone
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZonePart {
+    /// Vertices bounding this part
+    /// The first vertex should match the end vertex (closed shape)
+    #[prost(message, repeated, tag = "1")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Vertices in the compact encoding, only set in responses that asked
+    ///   for it, in which case `vertices` is left empty
+    #[prost(message, optional, tag = "2")]
+    pub compact_vertices: ::core::option::Option<CompactRing>,
+}
 /// Points in space used for routing (waypoints, vertiports, etc.)
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -109,6 +242,47 @@ pub struct Zone {
     /// End datetime for this zone
     #[prost(message, optional, tag = "7")]
     pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Weekly windows during which this zone is active
+    /// If empty, the zone is active for its whole lifetime
+    #[prost(message, repeated, tag = "8")]
+    pub schedule: ::prost::alloc::vec::Vec<ActiveWindow>,
+    /// Standoff distance of waypoints placed around this zone
+    /// If not provided, the default for the zone type is used
+    #[prost(float, optional, tag = "9")]
+    pub waypoint_buffer_meters: ::core::option::Option<f32>,
+    /// Additional disjoint areas covered by this zone, besides `vertices`
+    /// Each part shares the altitudes and times of the zone
+    #[prost(message, repeated, tag = "10")]
+    pub parts: ::prost::alloc::vec::Vec<ZonePart>,
+    /// Vertices in the compact encoding, only set in responses that asked
+    ///   for it, in which case `vertices` is left empty
+    #[prost(message, optional, tag = "11")]
+    pub compact_vertices: ::core::option::Option<CompactRing>,
+    /// Descriptive information for display, not used for routing
+    #[prost(message, optional, tag = "12")]
+    pub metadata: ::core::option::Option<ZoneMetadata>,
+    /// Operating hours of the vertiport of a port zone, only set in
+    ///   `getZones` responses
+    /// Empty for other zones and for vertiports open at all hours
+    #[prost(message, repeated, tag = "13")]
+    pub port_operating_hours: ::prost::alloc::vec::Vec<ActiveWindow>,
+}
+/// Descriptive information about a zone, such as the issuer of a NOTAM
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneMetadata {
+    /// Free text description of the zone
+    #[prost(string, optional, tag = "1")]
+    pub description: ::core::option::Option<::prost::alloc::string::String>,
+    /// Authority that issued the zone
+    #[prost(string, optional, tag = "2")]
+    pub authority: ::core::option::Option<::prost::alloc::string::String>,
+    /// Contact details of the authority, such as a phone number or email
+    #[prost(string, optional, tag = "3")]
+    pub contact: ::core::option::Option<::prost::alloc::string::String>,
+    /// Link to the source publication (http or https)
+    #[prost(string, optional, tag = "4")]
+    pub source_url: ::core::option::Option<::prost::alloc::string::String>,
 }
 /// Update No Fly Zones Request object
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -117,6 +291,284 @@ pub struct UpdateZonesRequest {
     /// Nodes to update
     #[prost(message, repeated, tag = "1")]
     pub zones: ::prost::alloc::vec::Vec<Zone>,
+    /// Check the zones against the stored airspace without applying them,
+    ///   returning the outcome of each
+    /// Ignored by `updateZonesStream`
+    #[prost(bool, tag = "2")]
+    pub validate_only: bool,
+}
+/// An area where low overflights are limited during quiet hours, such as
+///   a residential district at night
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NoiseArea {
+    /// Unique identifier
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Vertices bounding the area
+    /// The first vertex should match the end vertex (closed shape)
+    #[prost(message, repeated, tag = "2")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Flights below this altitude count as crossing the area
+    #[prost(float, tag = "3")]
+    pub altitude_meters_floor: f32,
+    /// Weekly windows during which overflights are limited
+    /// If empty, overflights are always limited
+    #[prost(message, repeated, tag = "4")]
+    pub quiet_hours: ::prost::alloc::vec::Vec<ActiveWindow>,
+}
+/// Update Noise Areas Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateNoiseAreasRequest {
+    /// Areas to insert or update
+    #[prost(message, repeated, tag = "1")]
+    pub areas: ::prost::alloc::vec::Vec<NoiseArea>,
+}
+/// Zone Authorization Request object
+/// At least one of the aircraft or flight identifiers is required
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneAuthorizationRequest {
+    /// The zone to be entered
+    #[prost(string, tag = "1")]
+    pub zone_identifier: ::prost::alloc::string::String,
+    /// The aircraft allowed to enter the zone
+    #[prost(string, optional, tag = "2")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// The flight allowed to enter the zone
+    #[prost(string, optional, tag = "3")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Start of the validity window
+    #[prost(message, optional, tag = "4")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the validity window
+    #[prost(message, optional, tag = "5")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Reference of the approval, such as a permit number
+    #[prost(string, optional, tag = "6")]
+    pub reference: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Permission for an aircraft or flight to enter a zone during a time
+///   window, paths checked for them don't conflict with the zone
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneAuthorization {
+    /// Unique identifier, assigned when the authorization is granted
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// The zone that may be entered
+    #[prost(string, tag = "2")]
+    pub zone_identifier: ::prost::alloc::string::String,
+    /// The aircraft allowed to enter the zone
+    #[prost(string, optional, tag = "3")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// The flight allowed to enter the zone
+    #[prost(string, optional, tag = "4")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Start of the validity window
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the validity window
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Reference of the approval, such as a permit number
+    #[prost(string, optional, tag = "7")]
+    pub reference: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Reserve Volume Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveVolumeRequest {
+    /// The operator holding the reservation, the only one that may
+    ///   release it
+    #[prost(string, tag = "1")]
+    pub operator_identifier: ::prost::alloc::string::String,
+    /// Vertices bounding the volume, closed (first and last are equal)
+    #[prost(message, repeated, tag = "2")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Lower altitude bound of the volume, in meters
+    #[prost(float, tag = "3")]
+    pub altitude_meters_min: f32,
+    /// Upper altitude bound of the volume, in meters
+    #[prost(float, tag = "4")]
+    pub altitude_meters_max: f32,
+    /// Start of the reservation
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the reservation
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Reference of the aerial work, such as a work order number
+    #[prost(string, optional, tag = "7")]
+    pub reference: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// A volume reserved by an operator during a time window
+///   Paths through the volume conflict with it as with a filed flight
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VolumeReservation {
+    /// Unique identifier, assigned when the volume is reserved
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// The operator holding the reservation
+    #[prost(string, tag = "2")]
+    pub operator_identifier: ::prost::alloc::string::String,
+    /// Lower altitude bound of the volume, in meters
+    #[prost(float, tag = "3")]
+    pub altitude_meters_min: f32,
+    /// Upper altitude bound of the volume, in meters
+    #[prost(float, tag = "4")]
+    pub altitude_meters_max: f32,
+    /// Start of the reservation
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the reservation
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Reference of the aerial work, such as a work order number
+    #[prost(string, optional, tag = "7")]
+    pub reference: ::core::option::Option<::prost::alloc::string::String>,
+    /// When the reservation was released, if it was released early
+    ///   The volume is free from this time on
+    #[prost(message, optional, tag = "8")]
+    pub released: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Release Reservation Request object
+#[derive(Eq)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseReservationRequest {
+    /// The reservation to release
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// The operator holding the reservation
+    #[prost(string, tag = "2")]
+    pub operator_identifier: ::prost::alloc::string::String,
+}
+/// List Authorizations Request object
+/// Filters left empty match any authorization
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuthorizationsRequest {
+    /// Only authorizations to enter this zone
+    #[prost(string, optional, tag = "1")]
+    pub zone_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Only authorizations of this aircraft
+    #[prost(string, optional, tag = "2")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Only authorizations of this flight
+    #[prost(string, optional, tag = "3")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// If true, authorizations whose window has ended are included
+    #[prost(bool, tag = "4")]
+    pub include_expired: bool,
+}
+/// List Authorizations Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuthorizationsResponse {
+    /// Authorizations matching the filters, by start of their window
+    #[prost(message, repeated, tag = "1")]
+    pub authorizations: ::prost::alloc::vec::Vec<ZoneAuthorization>,
+}
+/// Preview Zone Waypoints Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PreviewZoneWaypointsRequest {
+    /// The zone to place waypoints around, it is not stored
+    #[prost(message, optional, tag = "1")]
+    pub zone: ::core::option::Option<Zone>,
+}
+/// A waypoint that would be generated around a zone
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaypointCandidate {
+    /// Location of the waypoint
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<Coordinates>,
+    /// True if the waypoint falls inside another zone, in which
+    ///   case it would not be created
+    #[prost(bool, tag = "2")]
+    pub blocked: bool,
+}
+/// Preview Zone Waypoints Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PreviewZoneWaypointsResponse {
+    /// Waypoints around the zone and its parts
+    #[prost(message, repeated, tag = "1")]
+    pub waypoints: ::prost::alloc::vec::Vec<WaypointCandidate>,
+}
+/// A zone that could not be imported by a zone stream
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneFailure {
+    /// Identifier of the zone
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Why the zone was not imported
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// Outcome of a zone stream
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateZonesSummary {
+    /// Number of new zones
+    #[prost(uint32, tag = "1")]
+    pub inserted: u32,
+    /// Number of existing zones that were replaced
+    #[prost(uint32, tag = "2")]
+    pub updated: u32,
+    /// Zones that were not imported
+    #[prost(message, repeated, tag = "3")]
+    pub failed: ::prost::alloc::vec::Vec<ZoneFailure>,
+}
+/// Update Zone Attributes Request object
+/// Fields that are not provided keep their current values
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateZoneAttributesRequest {
+    /// Identifier of the zone to update
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// New zone type
+    #[prost(enumeration = "ZoneType", optional, tag = "2")]
+    pub zone_type: ::core::option::Option<i32>,
+    /// New minimum altitude
+    #[prost(float, optional, tag = "3")]
+    pub altitude_meters_min: ::core::option::Option<f32>,
+    /// New maximum altitude
+    #[prost(float, optional, tag = "4")]
+    pub altitude_meters_max: ::core::option::Option<f32>,
+    /// New start datetime
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// New end datetime
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Remove the start datetime, so the zone is active from its creation
+    /// Can't be combined with `time_start`
+    #[prost(bool, tag = "7")]
+    pub clear_time_start: bool,
+    /// Remove the end datetime, so the zone no longer expires
+    /// Can't be combined with `time_end`
+    #[prost(bool, tag = "8")]
+    pub clear_time_end: bool,
+    /// New weekly windows, replacing the current ones
+    /// Provided with no windows, the zone is active for its whole lifetime
+    #[prost(message, optional, tag = "9")]
+    pub schedule: ::core::option::Option<ZoneSchedule>,
+}
+/// Weekly windows during which a zone is active
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneSchedule {
+    /// If empty, the zone is active for its whole lifetime
+    #[prost(message, repeated, tag = "1")]
+    pub windows: ::prost::alloc::vec::Vec<ActiveWindow>,
 }
 /// Update flight paths
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -143,6 +595,9 @@ pub struct UpdateFlightPathRequest {
     /// The planned end time of the flight
     #[prost(message, optional, tag = "7")]
     pub timestamp_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Right-of-way of the flight
+    #[prost(enumeration = "FlightPriority", tag = "8")]
+    pub priority: i32,
 }
 /// Best Path Request object
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -169,6 +624,102 @@ pub struct BestPathRequest {
     /// Number of paths to return
     #[prost(int32, tag = "7")]
     pub limit: i32,
+    /// Max number of nodes in a path, including the origin and target
+    ///   Defaults to a value based on the distance between them
+    #[prost(uint32, optional, tag = "8")]
+    pub max_path_node_count: ::core::option::Option<u32>,
+    /// Weights of the objectives paths are ranked by
+    ///   Defaults to the weights configured on the server
+    #[prost(message, optional, tag = "9")]
+    pub weights: ::core::option::Option<CostWeights>,
+    /// If true, simulated flights are treated as conflicts
+    ///   By default only real flights can block a path
+    #[prost(bool, tag = "10")]
+    pub include_simulated: bool,
+    /// Right-of-way of the flight, filed flights of a strictly lower
+    ///   priority don't block the path
+    #[prost(enumeration = "FlightPriority", tag = "11")]
+    pub priority: i32,
+    /// If set and no path is clear in the requested window, windows
+    ///   shifted earlier or later by up to this many minutes are searched
+    ///   too, closest first
+    #[prost(uint32, optional, tag = "12")]
+    pub max_window_offset_minutes: ::core::option::Option<u32>,
+    /// Aircraft flying the path, zones it is authorized to enter don't
+    ///   block the path. Defaults to the origin when it is an aircraft.
+    #[prost(string, optional, tag = "13")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Flight of the path, zones it is authorized to enter don't block
+    ///   the path
+    #[prost(string, optional, tag = "14")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Filed flights arriving at the target within this many minutes of a
+    ///   path's estimated arrival are counted in its arrival_congestion
+    ///   Defaults to 10 minutes, at most 120
+    #[prost(uint32, optional, tag = "15")]
+    pub arrival_window_minutes: ::core::option::Option<u32>,
+    /// Type of the aircraft flying the path, waypoints are only flown at
+    ///   the flight levels within the altitude limits configured for it
+    ///   Defaults to undeclared
+    #[prost(enumeration = "crate::prelude::AircraftType", optional, tag = "16")]
+    pub aircraft_type: ::core::option::Option<i32>,
+    /// Operator of the flight, its own volume reservations don't block
+    ///   the path
+    #[prost(string, optional, tag = "17")]
+    pub operator_identifier: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Weights of the objectives combined into the cost of a path
+///   Each weight scales the matching field of PathCosts, a weight of zero
+///   ignores the objective
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CostWeights {
+    /// Weight per meter flown
+    #[prost(float, tag = "1")]
+    pub distance: f32,
+    /// Weight per second of flight
+    #[prost(float, tag = "2")]
+    pub time: f32,
+    /// Weight per meter flown over populated ground
+    ///   Reserved until a population density layer is available, must be
+    ///   zero
+    #[prost(float, tag = "3")]
+    pub ground_risk: f32,
+    /// Weight per meter flown through noise-sensitive areas
+    #[prost(float, tag = "4")]
+    pub noise: f32,
+    /// Weight per meter of level-flight equivalent energy
+    #[prost(float, tag = "5")]
+    pub energy: f32,
+}
+/// Details of a failed best path request, attached to the error status
+#[derive(Eq)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PathFailure {
+    /// Why no path could be found
+    #[prost(enumeration = "PathFailureReason", tag = "1")]
+    pub reason: i32,
+    /// Filed flights that most often blocked candidate paths, most
+    ///   frequent first
+    #[prost(message, repeated, tag = "2")]
+    pub blockers: ::prost::alloc::vec::Vec<PathBlocker>,
+}
+/// A filed flight that blocked candidate paths during a search
+#[derive(Eq)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PathBlocker {
+    /// Flight identifier
+    #[prost(string, tag = "1")]
+    pub flight_identifier: ::prost::alloc::string::String,
+    /// Aircraft identifier
+    #[prost(string, tag = "2")]
+    pub aircraft_identifier: ::prost::alloc::string::String,
+    /// Number of candidate paths discarded because of this flight
+    #[prost(uint32, tag = "3")]
+    pub blocked_count: u32,
 }
 /// Check Intersection Request object
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -189,66 +740,544 @@ pub struct CheckIntersectionRequest {
     /// Time of arrival
     #[prost(message, optional, tag = "5")]
     pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// If true, simulated flights are treated as conflicts
+    #[prost(bool, tag = "6")]
+    pub include_simulated: bool,
+    /// Right-of-way of the flight, filed flights of a strictly lower
+    ///   priority don't count as intersections
+    #[prost(enumeration = "FlightPriority", tag = "7")]
+    pub priority: i32,
+    /// Aircraft flying the path, zones it is authorized to enter
+    ///   don't count as intersections
+    #[prost(string, optional, tag = "8")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Flight of the path, zones it is authorized to enter don't count
+    ///   as intersections
+    #[prost(string, optional, tag = "9")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Type of the aircraft flying the path, filed flights are kept apart
+    ///   by the larger of its separation margins and theirs
+    ///   Defaults to undeclared
+    #[prost(enumeration = "crate::prelude::AircraftType", optional, tag = "10")]
+    pub aircraft_type: ::core::option::Option<i32>,
 }
 /// Check Intersection Response object
-#[derive(Eq, Copy)]
+#[derive(Eq)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CheckIntersectionResponse {
-    /// True if the path intersects a zone or previous plan
+    /// True if the path intersects a zone, volume reservation or previous plan
     #[prost(bool, tag = "1")]
     pub intersects: bool,
+    /// Lower-priority flights that conflict with the path
+    #[prost(message, repeated, tag = "2")]
+    pub advisories: ::prost::alloc::vec::Vec<ReplanAdvisory>,
 }
-/// / Geospatial Point with Altitude
-#[derive(Copy, ::serde::Serialize, ::serde::Deserialize)]
-#[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct PointZ {
-    /// Latitude
-    #[prost(double, tag = "1")]
-    pub latitude: f64,
-    /// Longitude
-    #[prost(double, tag = "2")]
-    pub longitude: f64,
-    /// Altitude
-    #[prost(float, tag = "3")]
-    pub altitude_meters: f32,
-}
-/// / A node in a path
+/// Suggest Deconfliction Request object
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct PathNode {
-    /// Path Node Index
-    #[prost(int32, tag = "1")]
-    pub index: i32,
-    /// Node Type (Vertiport or Waypoint)
-    #[prost(enumeration = "NodeType", tag = "2")]
-    pub node_type: i32,
-    /// Node Identifier
-    #[prost(string, tag = "3")]
-    pub identifier: ::prost::alloc::string::String,
-    /// Location
+pub struct SuggestDeconflictionRequest {
+    /// Start Node Identifier
+    #[prost(string, tag = "1")]
+    pub origin_identifier: ::prost::alloc::string::String,
+    /// End Node (Vertiport UUID)
+    #[prost(string, tag = "2")]
+    pub target_identifier: ::prost::alloc::string::String,
+    /// The path to deconflict
+    #[prost(message, repeated, tag = "3")]
+    pub path: ::prost::alloc::vec::Vec<PointZ>,
+    /// Time of departure
     #[prost(message, optional, tag = "4")]
-    pub geom: ::core::option::Option<PointZ>,
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Time of arrival
+    #[prost(message, optional, tag = "5")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// If true, simulated flights are treated as conflicts
+    #[prost(bool, tag = "6")]
+    pub include_simulated: bool,
+    /// Right-of-way of the flight, filed flights of a strictly lower
+    ///   priority don't count as conflicts
+    #[prost(enumeration = "FlightPriority", tag = "7")]
+    pub priority: i32,
+    /// Aircraft flying the path, zones it is authorized to enter
+    ///   don't count as conflicts
+    #[prost(string, optional, tag = "8")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Flight of the path, zones it is authorized to enter don't count
+    ///   as conflicts
+    #[prost(string, optional, tag = "9")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Type of the aircraft flying the path, filed flights are kept apart
+    ///   by the larger of its separation margins and theirs
+    ///   Defaults to undeclared
+    #[prost(enumeration = "crate::prelude::AircraftType", optional, tag = "10")]
+    pub aircraft_type: ::core::option::Option<i32>,
 }
-/// / A path between nodes
+/// A change to a path that clears all of its conflicts
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Path {
-    /// The nodes in this path
-    #[prost(message, repeated, tag = "1")]
-    pub path: ::prost::alloc::vec::Vec<PathNode>,
-    /// Total distance of this path
+pub struct Deconfliction {
+    /// Minutes the departure and arrival are delayed by
+    #[prost(uint32, tag = "1")]
+    pub delay_minutes: u32,
+    /// Meters the en-route nodes are raised by (negative to lower them)
     #[prost(float, tag = "2")]
-    pub distance_meters: f32,
+    pub altitude_change_meters: f32,
+    /// Meters the en-route nodes are moved to the right of the direction
+    ///   of travel (negative to move them left)
+    #[prost(float, tag = "3")]
+    pub lateral_offset_meters: f32,
+    /// The changed path
+    ///   A direct path gains a midpoint when moved
+    #[prost(message, repeated, tag = "4")]
+    pub path: ::prost::alloc::vec::Vec<PointZ>,
+    /// Time of departure after the delay
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Time of arrival after the delay
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
 }
-/// Best Path Response object
+/// Suggest Deconfliction Response object
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct BestPathResponse {
-    /// Best paths
-    #[prost(message, repeated, tag = "1")]
-    pub paths: ::prost::alloc::vec::Vec<Path>,
+pub struct SuggestDeconflictionResponse {
+    /// True if the requested path intersects a zone or previous plan
+    #[prost(bool, tag = "1")]
+    pub intersects: bool,
+    /// The least disruptive change that clears the path
+    ///   None if the path is clear, or if no change tried clears it
+    #[prost(message, optional, tag = "2")]
+    pub suggestion: ::core::option::Option<Deconfliction>,
+}
+/// A filed flight that conflicts with a higher-priority path, and would
+///   need to be re-planned if that path is flown
+#[derive(Eq)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplanAdvisory {
+    /// Flight identifier
+    #[prost(string, tag = "1")]
+    pub flight_identifier: ::prost::alloc::string::String,
+    /// Aircraft identifier
+    #[prost(string, tag = "2")]
+    pub aircraft_identifier: ::prost::alloc::string::String,
+    /// Right-of-way of the flight
+    #[prost(enumeration = "FlightPriority", tag = "3")]
+    pub priority: i32,
+}
+/// Check Volume Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckVolumeRequest {
+    /// Vertices bounding the volume
+    /// The first vertex should match the end vertex (closed shape)
+    #[prost(message, repeated, tag = "1")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Minimum altitude of the volume
+    #[prost(float, tag = "2")]
+    pub altitude_meters_min: f32,
+    /// Maximum altitude of the volume
+    #[prost(float, tag = "3")]
+    pub altitude_meters_max: f32,
+    /// Start of the time window
+    #[prost(message, optional, tag = "4")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the time window
+    #[prost(message, optional, tag = "5")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// If true, simulated flights are included in the response
+    #[prost(bool, tag = "6")]
+    pub include_simulated: bool,
+}
+/// A filed flight plan passing through a volume
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VolumeFlight {
+    /// Flight identifier
+    #[prost(string, optional, tag = "1")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Aircraft identifier
+    #[prost(string, optional, tag = "2")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Planned start of the flight
+    #[prost(message, optional, tag = "3")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Planned end of the flight
+    #[prost(message, optional, tag = "4")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Check Volume Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckVolumeResponse {
+    /// Identifiers of zones active in the volume during the time window
+    #[prost(string, repeated, tag = "1")]
+    pub zone_identifiers: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Filed flights passing through the volume during the time window
+    #[prost(message, repeated, tag = "2")]
+    pub flights: ::prost::alloc::vec::Vec<VolumeFlight>,
+    /// Identifiers of volume reservations overlapping the volume during
+    ///   the time window
+    #[prost(string, repeated, tag = "3")]
+    pub reservation_identifiers: ::prost::alloc::vec::Vec<
+        ::prost::alloc::string::String,
+    >,
+}
+/// Find Zone Gaps Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindZoneGapsRequest {
+    /// Vertices bounding the area to analyze
+    /// The first vertex should match the end vertex (closed shape)
+    #[prost(message, repeated, tag = "1")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Gaps narrower than this many meters are returned
+    #[prost(float, tag = "2")]
+    pub min_width_meters: f32,
+    /// Start of the time window, defaults to now
+    #[prost(message, optional, tag = "3")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the time window, defaults to the start
+    #[prost(message, optional, tag = "4")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// A gap between two zones narrower than the requested width
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneGap {
+    /// Identifier of the first zone
+    #[prost(string, tag = "1")]
+    pub zone_identifier_a: ::prost::alloc::string::String,
+    /// Identifier of the second zone
+    #[prost(string, tag = "2")]
+    pub zone_identifier_b: ::prost::alloc::string::String,
+    /// Distance between the zones at the narrowest point, in meters
+    #[prost(float, tag = "3")]
+    pub width_meters: f32,
+    /// Point of the first zone closest to the second
+    #[prost(message, optional, tag = "4")]
+    pub closest_a: ::core::option::Option<Coordinates>,
+    /// Point of the second zone closest to the first
+    #[prost(message, optional, tag = "5")]
+    pub closest_b: ::core::option::Option<Coordinates>,
+    /// Outline of the area between the zones within the requested width
+    ///   of both, empty if it has no area
+    #[prost(message, repeated, tag = "6")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Bottom of the altitude band shared by the zones
+    #[prost(float, tag = "7")]
+    pub altitude_meters_min: f32,
+    /// Top of the altitude band shared by the zones
+    #[prost(float, tag = "8")]
+    pub altitude_meters_max: f32,
+}
+/// Find Zone Gaps Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindZoneGapsResponse {
+    /// Gaps from narrowest to widest
+    #[prost(message, repeated, tag = "1")]
+    pub gaps: ::prost::alloc::vec::Vec<ZoneGap>,
+}
+/// Get Zone Constraints Request object
+/// Exactly one of a volume (vertices) or a path must be provided
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetZoneConstraintsRequest {
+    /// Vertices bounding the volume
+    /// The first vertex should match the end vertex (closed shape)
+    #[prost(message, repeated, tag = "1")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Bottom of the volume
+    #[prost(float, tag = "2")]
+    pub altitude_meters_min: f32,
+    /// Top of the volume
+    #[prost(float, tag = "3")]
+    pub altitude_meters_max: f32,
+    /// Points of the path, at least two
+    #[prost(message, repeated, tag = "4")]
+    pub path: ::prost::alloc::vec::Vec<PointZ>,
+    /// Start of the time window
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the time window
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Aircraft entering the volume or flying the path, if known
+    #[prost(string, optional, tag = "7")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Flight entering the volume or flying the path, if known
+    #[prost(string, optional, tag = "8")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// A zone crossed by the volume or path
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneConstraint {
+    /// Identifier of the zone
+    #[prost(string, tag = "1")]
+    pub zone_identifier: ::prost::alloc::string::String,
+    /// Type of the zone
+    #[prost(enumeration = "ZoneType", tag = "2")]
+    pub zone_type: i32,
+    /// How the zone constrains the volume or path
+    #[prost(enumeration = "ConstraintEffect", tag = "3")]
+    pub effect: i32,
+    /// Bottom of the zone
+    #[prost(float, tag = "4")]
+    pub altitude_meters_min: f32,
+    /// Top of the zone
+    #[prost(float, tag = "5")]
+    pub altitude_meters_max: f32,
+    /// Start of the part of the time window during which the zone is valid
+    #[prost(message, optional, tag = "6")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End of the part of the time window during which the zone is valid
+    #[prost(message, optional, tag = "7")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Authority responsible for the zone, if known
+    #[prost(string, optional, tag = "8")]
+    pub authority: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Get Zone Constraints Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetZoneConstraintsResponse {
+    /// Zones by effect from most severe, then by start time
+    /// The first blocking zone, if any, governs the volume or path
+    #[prost(message, repeated, tag = "1")]
+    pub constraints: ::prost::alloc::vec::Vec<ZoneConstraint>,
+}
+/// Distance Matrix Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DistanceMatrixRequest {
+    /// Origin vertiport identifiers
+    #[prost(string, repeated, tag = "1")]
+    pub origin_identifiers: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Target vertiport identifiers
+    #[prost(string, repeated, tag = "2")]
+    pub target_identifiers: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Time of departure
+    #[prost(message, optional, tag = "3")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Time of arrival
+    #[prost(message, optional, tag = "4")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Distance between an origin and a target vertiport
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DistanceMatrixEntry {
+    /// Origin vertiport identifier
+    #[prost(string, tag = "1")]
+    pub origin_identifier: ::prost::alloc::string::String,
+    /// Target vertiport identifier
+    #[prost(string, tag = "2")]
+    pub target_identifier: ::prost::alloc::string::String,
+    /// Straight-line distance between the vertiports
+    /// Zero if either vertiport is closed
+    #[prost(float, tag = "3")]
+    pub straight_line_meters: f32,
+    /// Estimated distance of a route around active zones
+    /// Not provided if no route was found
+    #[prost(float, optional, tag = "4")]
+    pub routable_meters: ::core::option::Option<f32>,
+    /// True if a flight between the vertiports appears possible
+    #[prost(bool, tag = "5")]
+    pub feasible: bool,
+}
+/// Distance Matrix Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DistanceMatrixResponse {
+    /// One entry per origin and target pair
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<DistanceMatrixEntry>,
+}
+/// / Geospatial Point with Altitude
+/// /  Rounded like `Coordinates`, with the altitude to 2 decimal places
+/// /  (a centimeter) by default
+#[derive(Copy, ::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointZ {
+    /// Latitude
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Longitude
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+    /// Altitude
+    #[prost(float, tag = "3")]
+    pub altitude_meters: f32,
+}
+/// / A node in a path
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PathNode {
+    /// Path Node Index
+    #[prost(int32, tag = "1")]
+    pub index: i32,
+    /// Node Type (Vertiport or Waypoint)
+    #[prost(enumeration = "NodeType", tag = "2")]
+    pub node_type: i32,
+    /// Node Identifier
+    #[prost(string, tag = "3")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Location
+    #[prost(message, optional, tag = "4")]
+    pub geom: ::core::option::Option<PointZ>,
+}
+/// / A path between nodes
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Path {
+    /// The nodes in this path
+    #[prost(message, repeated, tag = "1")]
+    pub path: ::prost::alloc::vec::Vec<PathNode>,
+    /// Total distance of this path
+    #[prost(float, tag = "2")]
+    pub distance_meters: f32,
+    /// Cost of this path per objective
+    #[prost(message, optional, tag = "3")]
+    pub costs: ::core::option::Option<PathCosts>,
+    /// Lower-priority flights that conflict with this path
+    #[prost(message, repeated, tag = "4")]
+    pub advisories: ::prost::alloc::vec::Vec<ReplanAdvisory>,
+    /// Number of filed flights arriving at the target vertiport within the
+    ///   arrival window of this path's estimated arrival
+    #[prost(uint32, tag = "5")]
+    pub arrival_congestion: u32,
+}
+/// Cost of a path per objective, before weighting
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PathCosts {
+    /// Distance flown
+    #[prost(float, tag = "1")]
+    pub distance_meters: f32,
+    /// Estimated flight time
+    #[prost(float, tag = "2")]
+    pub time_seconds: f32,
+    /// Distance flown over populated ground
+    ///   Reserved, always zero
+    #[prost(float, tag = "3")]
+    pub ground_risk_meters: f32,
+    /// Distance flown through noise-sensitive areas
+    #[prost(float, tag = "4")]
+    pub noise_meters: f32,
+    /// Estimated energy, as the distance of level flight using as much
+    #[prost(float, tag = "5")]
+    pub energy_meters: f32,
+    /// Weighted sum of the objectives the path was ranked by
+    #[prost(float, tag = "6")]
+    pub total: f32,
+}
+/// Best Path Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BestPathResponse {
+    /// Best paths
+    #[prost(message, repeated, tag = "1")]
+    pub paths: ::prost::alloc::vec::Vec<Path>,
+    /// Filed flights that most often blocked candidate paths, most
+    ///   frequent first
+    #[prost(message, repeated, tag = "2")]
+    pub blockers: ::prost::alloc::vec::Vec<PathBlocker>,
+    /// Time of departure of the window the paths were found in
+    ///   Differs from the requested one if the window was shifted
+    #[prost(message, optional, tag = "3")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Time of arrival of the window the paths were found in
+    #[prost(message, optional, tag = "4")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// If the search stopped at the deadline of the call, the paths
+    ///   completed by then are returned and better ones may exist
+    #[prost(bool, tag = "5")]
+    pub truncated: bool,
+    /// Number of candidate paths to the target rejected for crossing a
+    ///   zone or filed flight
+    #[prost(uint32, tag = "6")]
+    pub rejected_count: u32,
+}
+/// Get Best Path Audits Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBestPathAuditsRequest {
+    /// Flight the best paths were requested for
+    #[prost(string, tag = "1")]
+    pub flight_identifier: ::prost::alloc::string::String,
+}
+/// A best path returned by bestPath, as recorded for audit
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BestPathAudit {
+    /// Unique identifier of the record
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Flight the path was requested for
+    #[prost(string, optional, tag = "2")]
+    pub flight_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Aircraft the path was requested for
+    #[prost(string, optional, tag = "3")]
+    pub aircraft_identifier: ::core::option::Option<::prost::alloc::string::String>,
+    /// Parameters of the request, as a JSON object
+    #[prost(string, tag = "4")]
+    pub request_parameters: ::prost::alloc::string::String,
+    /// Vertices of the best path returned
+    #[prost(message, repeated, tag = "5")]
+    pub path: ::prost::alloc::vec::Vec<PointZ>,
+    /// Number of candidate paths to the target rejected for crossing a
+    ///   zone or filed flight
+    #[prost(uint32, tag = "6")]
+    pub rejected_count: u32,
+    /// Sequence number of the last airspace change when the path was
+    ///   returned, as a syncAirspace cursor
+    #[prost(uint64, tag = "7")]
+    pub airspace_cursor: u64,
+    /// Time the path was returned
+    #[prost(message, optional, tag = "8")]
+    pub created: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Get Best Path Audits Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBestPathAuditsResponse {
+    /// Recorded best paths of the flight, newest first
+    #[prost(message, repeated, tag = "1")]
+    pub audits: ::prost::alloc::vec::Vec<BestPathAudit>,
+}
+/// Vertices of a ring as integers of 1e-7 degrees, each pair of
+///   longitude and latitude relative to the previous vertex (the first to
+///   the origin). Zigzag-encoded varints keep the small deltas small.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CompactRing {
+    /// Longitude and latitude deltas, alternating
+    #[prost(sint64, repeated, tag = "1")]
+    pub deltas: ::prost::alloc::vec::Vec<i64>,
+}
+/// Pagination options for list queries
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Page {
+    /// Max number of results to return
+    /// Bounded by the server's max page size
+    #[prost(uint32, optional, tag = "1")]
+    pub limit: ::core::option::Option<u32>,
+    /// Number of results to skip
+    #[prost(uint32, tag = "2")]
+    pub offset: u32,
+    /// Field to order the results by
+    #[prost(enumeration = "OrderBy", tag = "3")]
+    pub order_by: i32,
+    /// Sort the results in descending order
+    #[prost(bool, tag = "4")]
+    pub descending: bool,
 }
 /// Get Flights Request object
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -272,6 +1301,30 @@ pub struct GetFlightsRequest {
     /// Time window end
     #[prost(message, optional, tag = "6")]
     pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Arbitrary polygon window (closed shape)
+    /// If provided, this is used instead of the rectangular window
+    #[prost(message, repeated, tag = "7")]
+    pub window_vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Only return flights at or above this altitude
+    #[prost(float, optional, tag = "8")]
+    pub altitude_meters_min: ::core::option::Option<f32>,
+    /// Only return flights at or below this altitude
+    #[prost(float, optional, tag = "9")]
+    pub altitude_meters_max: ::core::option::Option<f32>,
+    /// Pagination options
+    #[prost(message, optional, tag = "10")]
+    pub page: ::core::option::Option<Page>,
+    /// If provided, each flight includes the nearest active restriction
+    ///   zone within this many meters of the aircraft
+    #[prost(float, optional, tag = "11")]
+    pub zone_proximity_meters: ::core::option::Option<f32>,
+    /// If true, only filed flights are returned and aircraft telemetry
+    ///   is ignored. The time window can then extend into the future.
+    #[prost(bool, tag = "12")]
+    pub planned_only: bool,
+    /// If true, simulated aircraft and flights are included
+    #[prost(bool, tag = "13")]
+    pub include_simulated: bool,
 }
 /// Timestamped position of an aircraft
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -306,6 +1359,12 @@ pub struct AircraftState {
     /// The vertical speed of the aircraft
     #[prost(float, tag = "6")]
     pub vertical_speed_mps: f32,
+    /// Seconds since the position was last updated
+    #[prost(float, tag = "7")]
+    pub staleness_seconds: f32,
+    /// The quality of the position, based on its staleness
+    #[prost(enumeration = "DataQuality", tag = "8")]
+    pub quality: i32,
 }
 /// Aircraft Flight Information
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -329,6 +1388,32 @@ pub struct Flight {
     /// The state of the aircraft
     #[prost(message, optional, tag = "6")]
     pub state: ::core::option::Option<AircraftState>,
+    /// The nearest active restriction zone, if within the requested
+    ///   zone_proximity_meters
+    #[prost(message, optional, tag = "7")]
+    pub nearest_zone: ::core::option::Option<ZoneProximity>,
+    /// Start time of the filed flight path, if any
+    #[prost(message, optional, tag = "8")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End time of the filed flight path, if any
+    #[prost(message, optional, tag = "9")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Fraction of the filed flight path completed, from 0 to 1, if the
+    ///   flight is active. Measured along the path from the live position of
+    ///   the aircraft flying it, or from the elapsed time without one.
+    #[prost(float, optional, tag = "10")]
+    pub progress: ::core::option::Option<f32>,
+}
+/// Distance from an aircraft to a zone
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneProximity {
+    /// Zone identifier
+    #[prost(string, tag = "1")]
+    pub zone_identifier: ::prost::alloc::string::String,
+    /// Distance from the aircraft to the zone in meters
+    #[prost(float, tag = "2")]
+    pub distance_meters: f32,
 }
 /// Get Flights Response object
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -337,39 +1422,636 @@ pub struct GetFlightsResponse {
     /// Flights in the requested zone
     #[prost(message, repeated, tag = "1")]
     pub flights: ::prost::alloc::vec::Vec<Flight>,
+    /// Offset of the next page, if more results may be available
+    #[prost(uint32, optional, tag = "2")]
+    pub next_offset: ::core::option::Option<u32>,
 }
-/// The nodes involved in the best path request
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum NodeType {
-    /// Vertiport
-    Vertiport = 0,
-    /// Waypoint
-    Waypoint = 1,
-    /// Aircraft
-    Aircraft = 2,
+/// Get Zones Request object
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetZonesRequest {
+    /// Pagination options
+    #[prost(message, optional, tag = "1")]
+    pub page: ::core::option::Option<Page>,
+    /// Encoding of the returned vertices
+    #[prost(enumeration = "GeometryEncoding", tag = "2")]
+    pub encoding: i32,
 }
-impl NodeType {
-    /// String value of the enum field names used in the ProtoBuf definition.
-    ///
-    /// The values are not transformed in any way and thus are considered stable
-    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-    pub fn as_str_name(&self) -> &'static str {
-        match self {
-            NodeType::Vertiport => "VERTIPORT",
-            NodeType::Waypoint => "WAYPOINT",
-            NodeType::Aircraft => "AIRCRAFT",
-        }
-    }
-    /// Creates an enum from field names used in the ProtoBuf definition.
-    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
-        match value {
-            "VERTIPORT" => Some(Self::Vertiport),
-            "WAYPOINT" => Some(Self::Waypoint),
-            "AIRCRAFT" => Some(Self::Aircraft),
-            _ => None,
-        }
-    }
+/// Get Zones Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetZonesResponse {
+    /// Zones in the requested page
+    #[prost(message, repeated, tag = "1")]
+    pub zones: ::prost::alloc::vec::Vec<Zone>,
+    /// Offset of the next page, if more results may be available
+    #[prost(uint32, optional, tag = "2")]
+    pub next_offset: ::core::option::Option<u32>,
+}
+/// Get Zones At Point Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetZonesAtPointRequest {
+    /// The point to check
+    #[prost(message, optional, tag = "1")]
+    pub point: ::core::option::Option<PointZ>,
+    /// The time to check, defaults to now
+    #[prost(message, optional, tag = "2")]
+    pub time: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Get Zones At Point Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetZonesAtPointResponse {
+    /// Zones containing the point at the requested time
+    #[prost(message, repeated, tag = "1")]
+    pub zones: ::prost::alloc::vec::Vec<Zone>,
+}
+/// Get Waypoints Request object
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetWaypointsRequest {
+    /// Pagination options
+    #[prost(message, optional, tag = "1")]
+    pub page: ::core::option::Option<Page>,
+    /// Map zoom level (0 to 22) at which nearby waypoints are clustered
+    ///   If not provided, waypoints are returned individually
+    #[prost(uint32, optional, tag = "2")]
+    pub zoom: ::core::option::Option<u32>,
+}
+/// Nearby waypoints grouped into one point
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaypointCluster {
+    /// Centroid of the waypoints in the cluster
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<Coordinates>,
+    /// Number of waypoints in the cluster
+    #[prost(uint32, tag = "2")]
+    pub count: u32,
+    /// Identifier of the waypoint if the cluster has only one
+    #[prost(string, optional, tag = "3")]
+    pub identifier: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Get Waypoints Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetWaypointsResponse {
+    /// Waypoints in the requested page
+    #[prost(message, repeated, tag = "1")]
+    pub waypoints: ::prost::alloc::vec::Vec<Waypoint>,
+    /// Offset of the next page, if more results may be available
+    #[prost(uint32, optional, tag = "2")]
+    pub next_offset: ::core::option::Option<u32>,
+    /// Clusters in the requested page, if a zoom level was provided
+    #[prost(message, repeated, tag = "3")]
+    pub clusters: ::prost::alloc::vec::Vec<WaypointCluster>,
+}
+/// Sync Airspace Request object
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SyncAirspaceRequest {
+    /// Cursor returned by the previous sync, zero for a full sync
+    #[prost(uint64, tag = "1")]
+    pub cursor: u64,
+    /// Max number of changes returned, up to the max page size
+    #[prost(uint32, optional, tag = "2")]
+    pub limit: ::core::option::Option<u32>,
+    /// Encoding of the returned zone vertices
+    #[prost(enumeration = "GeometryEncoding", tag = "3")]
+    pub encoding: i32,
+}
+/// A zone or waypoint added, updated or removed
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AirspaceChange {
+    /// Sequence number of the change
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    /// Kind of object changed
+    #[prost(enumeration = "AirspaceEntity", tag = "2")]
+    pub entity: i32,
+    /// Identifier of the object changed
+    #[prost(string, tag = "3")]
+    pub identifier: ::prost::alloc::string::String,
+    /// True if the object was removed
+    #[prost(bool, tag = "4")]
+    pub deleted: bool,
+    /// The zone as of this change, unless removed
+    #[prost(message, optional, tag = "5")]
+    pub zone: ::core::option::Option<Zone>,
+    /// The waypoint as of this change, unless removed
+    #[prost(message, optional, tag = "6")]
+    pub waypoint: ::core::option::Option<Waypoint>,
+}
+/// Sync Airspace Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SyncAirspaceResponse {
+    /// Latest change of each object since the cursor, oldest first
+    #[prost(message, repeated, tag = "1")]
+    pub changes: ::prost::alloc::vec::Vec<AirspaceChange>,
+    /// Cursor to provide to the next sync
+    #[prost(uint64, tag = "2")]
+    pub cursor: u64,
+    /// True if more changes follow the returned ones
+    #[prost(bool, tag = "3")]
+    pub has_more: bool,
+}
+/// Get Vertiports Request object
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetVertiportsRequest {
+    /// Pagination options
+    #[prost(message, optional, tag = "1")]
+    pub page: ::core::option::Option<Page>,
+    /// Encoding of the returned vertices
+    #[prost(enumeration = "GeometryEncoding", tag = "2")]
+    pub encoding: i32,
+}
+/// Get Vertiports Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetVertiportsResponse {
+    /// Vertiports in the requested page
+    #[prost(message, repeated, tag = "1")]
+    pub vertiports: ::prost::alloc::vec::Vec<Vertiport>,
+    /// Offset of the next page, if more results may be available
+    #[prost(uint32, optional, tag = "2")]
+    pub next_offset: ::core::option::Option<u32>,
+}
+/// Service Info Request object
+///
+/// No arguments
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServiceInfoRequest {}
+/// Limits applied by the server to incoming requests
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServiceLimits {
+    /// Max number of paths returned by a best path request
+    #[prost(uint32, tag = "1")]
+    pub max_path_count: u32,
+    /// Min number of nodes of a best path, origin and target included
+    #[prost(uint32, tag = "2")]
+    pub min_path_node_count: u32,
+    /// Max number of nodes of a best path
+    #[prost(uint32, tag = "3")]
+    pub max_path_node_count: u32,
+    /// Number of nodes of a best path when not provided
+    #[prost(uint32, tag = "4")]
+    pub default_path_node_count: u32,
+    /// Max length of a path
+    #[prost(float, tag = "5")]
+    pub max_flight_distance_meters: f32,
+    /// Altitudes at which best paths cruise
+    #[prost(float, repeated, tag = "6")]
+    pub flight_levels_meters: ::prost::alloc::vec::Vec<f32>,
+    /// Paths closer than this distance to a filed flight intersect it
+    #[prost(float, tag = "7")]
+    pub flight_separation_meters: f32,
+    /// Max number of origins or targets of a distance matrix
+    #[prost(uint32, tag = "8")]
+    pub max_distance_matrix_node_count: u32,
+    /// Min number of vertices of a polygon, the first and last being equal
+    #[prost(uint32, tag = "9")]
+    pub min_polygon_vertices: u32,
+    /// Max standoff distance of waypoints placed around a zone
+    #[prost(float, tag = "10")]
+    pub max_waypoint_buffer_meters: f32,
+    /// Number of results of a page when not provided
+    #[prost(uint32, tag = "11")]
+    pub default_page_size: u32,
+    /// Max number of results of a page
+    #[prost(uint32, tag = "12")]
+    pub max_page_size: u32,
+    /// Max length of an aircraft identifier
+    #[prost(uint32, tag = "13")]
+    pub aircraft_identifier_max_length: u32,
+    /// Age after which an aircraft position is stale
+    #[prost(uint32, tag = "14")]
+    pub aircraft_stale_seconds: u32,
+    /// Age after which an aircraft position is expired
+    #[prost(uint32, tag = "15")]
+    pub aircraft_expired_seconds: u32,
+    /// Max altitude of an aircraft position, zero if not enforced
+    #[prost(float, tag = "16")]
+    pub telemetry_altitude_ceiling_meters: f32,
+    /// How far in the future a report may be timestamped
+    #[prost(uint32, tag = "17")]
+    pub telemetry_clock_skew_seconds: u32,
+    /// Highest map zoom level waypoints can be clustered at
+    #[prost(uint32, tag = "18")]
+    pub max_cluster_zoom: u32,
+    /// Max shift of a best path search window, in minutes
+    #[prost(uint32, tag = "19")]
+    pub max_window_offset_minutes: u32,
+    /// Max margin around a best path's estimated arrival within which
+    ///   arriving flights are counted, in minutes
+    #[prost(uint32, tag = "20")]
+    pub max_arrival_window_minutes: u32,
+}
+/// Service Info Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServiceInfoResponse {
+    /// Version of the service
+    #[prost(string, tag = "1")]
+    pub version: ::prost::alloc::string::String,
+    /// Optional features the service was built with
+    #[prost(string, repeated, tag = "2")]
+    pub features: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Database schema holding the tables of the service
+    #[prost(string, tag = "3")]
+    pub schema: ::prost::alloc::string::String,
+    /// Active limits
+    #[prost(message, optional, tag = "4")]
+    pub limits: ::core::option::Option<ServiceLimits>,
+    /// Versions of the database and its extensions, detected at startup
+    #[prost(message, optional, tag = "5")]
+    pub database: ::core::option::Option<DatabaseInfo>,
+    /// Time items waited in each Redis queue before being consumed,
+    ///   since startup
+    #[prost(message, repeated, tag = "6")]
+    pub queue_latencies: ::prost::alloc::vec::Vec<QueueLatency>,
+    /// Version of the gRPC interface, as MAJOR.MINOR
+    #[prost(string, tag = "7")]
+    pub interface_version: ::prost::alloc::string::String,
+}
+/// Histogram of the time items waited in a Redis queue, from their
+///   network timestamp until they were consumed
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueueLatency {
+    /// Key of the queue
+    #[prost(string, tag = "1")]
+    pub queue: ::prost::alloc::string::String,
+    /// Upper bounds of the buckets, in milliseconds
+    #[prost(uint64, repeated, tag = "2")]
+    pub bucket_bounds_ms: ::prost::alloc::vec::Vec<u64>,
+    /// Number of items in each bucket, the last one counting items
+    ///   above all bounds
+    #[prost(uint64, repeated, tag = "3")]
+    pub counts: ::prost::alloc::vec::Vec<u64>,
+    /// 95th percentile, as the upper bound of its bucket, in milliseconds
+    #[prost(uint64, tag = "4")]
+    pub p95_ms: u64,
+    /// Highest time waited, in milliseconds
+    #[prost(uint64, tag = "5")]
+    pub max_ms: u64,
+}
+/// Versions of the database used by the service
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DatabaseInfo {
+    /// PostgreSQL version
+    #[prost(string, tag = "1")]
+    pub postgres_version: ::prost::alloc::string::String,
+    /// PostGIS version, empty if not installed
+    #[prost(string, tag = "2")]
+    pub postgis_version: ::prost::alloc::string::String,
+    /// postgis_sfcgal version, empty if not installed
+    #[prost(string, tag = "3")]
+    pub sfcgal_version: ::prost::alloc::string::String,
+}
+/// Get Statistics Request object
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStatisticsRequest {
+    /// Aircraft that reported a position within this many minutes are
+    ///   counted as seen, 5 if not provided
+    #[prost(uint32, optional, tag = "1")]
+    pub aircraft_window_minutes: ::core::option::Option<u32>,
+}
+/// Number of zones of a type
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneTypeCount {
+    /// Type of the zones
+    #[prost(enumeration = "ZoneType", tag = "1")]
+    pub zone_type: i32,
+    /// Number of zones
+    #[prost(uint32, tag = "2")]
+    pub count: u32,
+}
+/// Get Statistics Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStatisticsResponse {
+    /// Zones in effect now, within their time bounds and schedule, for
+    ///   each zone type
+    #[prost(message, repeated, tag = "1")]
+    pub active_zones: ::prost::alloc::vec::Vec<ZoneTypeCount>,
+    /// Non-simulated flights scheduled to be underway now
+    #[prost(uint32, tag = "2")]
+    pub flights_in_progress: u32,
+    /// Aircraft that reported a position within the requested window
+    #[prost(uint32, tag = "3")]
+    pub aircraft_seen: u32,
+    /// Waypoints in the routing graph
+    #[prost(uint32, tag = "4")]
+    pub waypoint_count: u32,
+    /// Best path searches run by this instance since startup
+    #[prost(uint64, tag = "5")]
+    pub best_path_count: u64,
+    /// Average time of a best path search on this instance, in
+    ///   milliseconds, if any search was run
+    #[prost(float, optional, tag = "6")]
+    pub best_path_average_latency_ms: ::core::option::Option<f32>,
+}
+/// A field of a request that was rejected
+///   Encoded as google.rpc.BadRequest.FieldViolation
+#[derive(Eq)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldViolation {
+    /// Path of the field in the request, such as `zones\[2\].vertices\[5\]`
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    /// Why the field was rejected, naming the entity it belongs to
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+}
+/// The fields of a request that were rejected, sent in the details of an
+///   INVALID_ARGUMENT status by the update calls
+///   Encoded as google.rpc.BadRequest, so that standard gRPC error detail
+///   decoders can read it too
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BadRequest {
+    /// Rejected fields, in the order of the request
+    #[prost(message, repeated, tag = "1")]
+    pub field_violations: ::prost::alloc::vec::Vec<FieldViolation>,
+}
+/// Check Reachability Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckReachabilityRequest {
+    /// Identifier of the aircraft, flying or not
+    #[prost(string, tag = "1")]
+    pub aircraft_identifier: ::prost::alloc::string::String,
+    /// Identifier of the vertiport to reach
+    #[prost(string, tag = "2")]
+    pub target_identifier: ::prost::alloc::string::String,
+    /// Distance the aircraft can still fly, if known
+    ///   The range of the performance profile of its type otherwise
+    #[prost(float, optional, tag = "3")]
+    pub remaining_range_meters: ::core::option::Option<f32>,
+}
+/// Check Reachability Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckReachabilityResponse {
+    /// True if the aircraft appears able to reach the vertiport
+    ///   Always false if the vertiport is closed
+    #[prost(bool, tag = "1")]
+    pub reachable: bool,
+    /// How much the answer can be relied on, lowered if the position of
+    ///   the aircraft is stale
+    #[prost(enumeration = "ReachabilityConfidence", tag = "2")]
+    pub confidence: i32,
+    /// Straight-line distance from the aircraft to the vertiport
+    #[prost(float, tag = "3")]
+    pub straight_line_meters: f32,
+    /// Straight-line distance stretched by the configured detour factor
+    #[prost(float, tag = "4")]
+    pub estimated_meters: f32,
+    /// Range the answer was computed with
+    #[prost(float, tag = "5")]
+    pub range_meters: f32,
+    /// True if active zones block the straight line
+    #[prost(bool, tag = "6")]
+    pub direct_blocked: bool,
+    /// Estimated time of arrival at the cruise speed of the aircraft type
+    ///   Not provided if the vertiport is not reachable
+    #[prost(message, optional, tag = "7")]
+    pub estimated_arrival: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Get Snapshot Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSnapshotRequest {
+    /// Time of the snapshot, not in the future
+    ///   Must fall within the history retention period
+    #[prost(message, optional, tag = "1")]
+    pub time: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Return the snapshot encoded as a single bundle instead
+    #[prost(bool, tag = "2")]
+    pub bundle: bool,
+}
+/// A flight filed over the time of a snapshot
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotFlight {
+    /// Flight identifier
+    #[prost(string, tag = "1")]
+    pub flight_identifier: ::prost::alloc::string::String,
+    /// Identifier of the aircraft flying it
+    #[prost(string, tag = "2")]
+    pub aircraft_identifier: ::prost::alloc::string::String,
+    /// The type of aircraft
+    #[prost(enumeration = "crate::prelude::AircraftType", tag = "3")]
+    pub aircraft_type: i32,
+    /// If this is a simulated flight
+    #[prost(bool, tag = "4")]
+    pub simulated: bool,
+    /// Priority of the flight
+    #[prost(enumeration = "FlightPriority", tag = "5")]
+    pub priority: i32,
+    /// The filed flight path
+    #[prost(message, repeated, tag = "6")]
+    pub path: ::prost::alloc::vec::Vec<PointZ>,
+    /// Start time of the filed flight path
+    #[prost(message, optional, tag = "7")]
+    pub time_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// End time of the filed flight path
+    #[prost(message, optional, tag = "8")]
+    pub time_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// True if the flight has since been moved to the archive
+    #[prost(bool, tag = "9")]
+    pub archived: bool,
+}
+/// An aircraft as it was known at the time of a snapshot
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotAircraft {
+    /// Aircraft identifier
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// The type of aircraft
+    #[prost(enumeration = "crate::prelude::AircraftType", tag = "2")]
+    pub aircraft_type: i32,
+    /// If this is a simulated aircraft
+    #[prost(bool, tag = "3")]
+    pub simulated: bool,
+    /// The state of the aircraft from its last position before the
+    ///   snapshot, aged relative to the snapshot time
+    #[prost(message, optional, tag = "4")]
+    pub state: ::core::option::Option<AircraftState>,
+}
+/// The airspace picture at a point in time
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AirspaceSnapshot {
+    /// Time of the snapshot
+    #[prost(message, optional, tag = "1")]
+    pub time: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// Zones as they were defined at the time, within their time bounds
+    ///   and schedule
+    #[prost(message, repeated, tag = "2")]
+    pub zones: ::prost::alloc::vec::Vec<Zone>,
+    /// Flights filed over the time, archived ones included
+    #[prost(message, repeated, tag = "3")]
+    pub flights: ::prost::alloc::vec::Vec<SnapshotFlight>,
+    /// Aircraft whose last position before the time had not expired
+    #[prost(message, repeated, tag = "4")]
+    pub aircraft: ::prost::alloc::vec::Vec<SnapshotAircraft>,
+}
+/// Get Snapshot Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSnapshotResponse {
+    /// The snapshot, if a bundle was not requested
+    #[prost(message, optional, tag = "1")]
+    pub snapshot: ::core::option::Option<AirspaceSnapshot>,
+    /// The snapshot as an encoded AirspaceSnapshot message, if a bundle
+    ///   was requested. Can be decoded later with the shipped proto or
+    ///   its descriptors, such as with `protoc --decode=grpc.AirspaceSnapshot`.
+    #[prost(bytes = "vec", tag = "2")]
+    pub bundle: ::prost::alloc::vec::Vec<u8>,
+}
+/// The nodes involved in the best path request
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum NodeType {
+    /// Vertiport
+    Vertiport = 0,
+    /// Waypoint
+    Waypoint = 1,
+    /// Aircraft
+    Aircraft = 2,
+}
+impl NodeType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            NodeType::Vertiport => "VERTIPORT",
+            NodeType::Waypoint => "WAYPOINT",
+            NodeType::Aircraft => "AIRCRAFT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "VERTIPORT" => Some(Self::Vertiport),
+            "WAYPOINT" => Some(Self::Waypoint),
+            "AIRCRAFT" => Some(Self::Aircraft),
+            _ => None,
+        }
+    }
+}
+/// Operational status of a vertiport
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum VertiportStatus {
+    /// Open for arrivals and departures
+    Operational = 0,
+    /// Temporarily out of service (maintenance, weather, etc.)
+    Closed = 1,
+}
+impl VertiportStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            VertiportStatus::Operational => "OPERATIONAL",
+            VertiportStatus::Closed => "CLOSED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "OPERATIONAL" => Some(Self::Operational),
+            "CLOSED" => Some(Self::Closed),
+            _ => None,
+        }
+    }
+}
+/// Publisher and format of a waypoint export
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WaypointSource {
+    /// openAIP reporting points, exported as JSON
+    Openaip = 0,
+    /// DAFIF waypoints file, tab delimited with a header row
+    Dafif = 1,
+}
+impl WaypointSource {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            WaypointSource::Openaip => "OPENAIP",
+            WaypointSource::Dafif => "DAFIF",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "OPENAIP" => Some(Self::Openaip),
+            "DAFIF" => Some(Self::Dafif),
+            _ => None,
+        }
+    }
+}
+/// Right-of-way of a flight
+///   Flights only give way to flights of a strictly higher priority
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FlightPriority {
+    /// Scheduled passenger and cargo flights
+    Routine = 0,
+    /// Time-critical flights, such as organ transport
+    Urgent = 1,
+    /// Emergency medical and public safety flights
+    Emergency = 2,
+}
+impl FlightPriority {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FlightPriority::Routine => "ROUTINE",
+            FlightPriority::Urgent => "URGENT",
+            FlightPriority::Emergency => "EMERGENCY",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ROUTINE" => Some(Self::Routine),
+            "URGENT" => Some(Self::Urgent),
+            "EMERGENCY" => Some(Self::Emergency),
+            _ => None,
+        }
+    }
 }
 /// Airspace Zone Type
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -400,18 +2082,697 @@ impl ZoneType {
         }
     }
 }
-/// Generated client implementations.
-#[cfg(not(tarpaulin_include))]
-pub mod rpc_service_client {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    use tonic::codegen::http::Uri;
-    #[derive(Debug, Clone)]
-    pub struct RpcServiceClient<T> {
-        inner: tonic::client::Grpc<T>,
-    }
-    impl RpcServiceClient<tonic::transport::Channel> {
-        /// Attempt to create a new client by connecting to a given endpoint.
+/// Day of the week
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Weekday {
+    /// Monday
+    Monday = 0,
+    /// Tuesday
+    Tuesday = 1,
+    /// Wednesday
+    Wednesday = 2,
+    /// Thursday
+    Thursday = 3,
+    /// Friday
+    Friday = 4,
+    /// Saturday
+    Saturday = 5,
+    /// Sunday
+    Sunday = 6,
+}
+impl Weekday {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Weekday::Monday => "MONDAY",
+            Weekday::Tuesday => "TUESDAY",
+            Weekday::Wednesday => "WEDNESDAY",
+            Weekday::Thursday => "THURSDAY",
+            Weekday::Friday => "FRIDAY",
+            Weekday::Saturday => "SATURDAY",
+            Weekday::Sunday => "SUNDAY",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "MONDAY" => Some(Self::Monday),
+            "TUESDAY" => Some(Self::Tuesday),
+            "WEDNESDAY" => Some(Self::Wednesday),
+            "THURSDAY" => Some(Self::Thursday),
+            "FRIDAY" => Some(Self::Friday),
+            "SATURDAY" => Some(Self::Saturday),
+            "SUNDAY" => Some(Self::Sunday),
+            _ => None,
+        }
+    }
+}
+/// Reason no path could be found between two nodes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PathFailureReason {
+    /// The direct route is blocked and there are no waypoints to get around it
+    NoWaypointsNearby = 0,
+    /// Every route to the target is longer than an aircraft can fly
+    ExceededMaxDistance = 1,
+    /// Every route to the target crosses a zone or another flight plan
+    AllCandidatesConflicted = 2,
+    /// The search ran out of time before finding a route
+    TimeBudgetExhausted = 3,
+    /// No flight level is within the altitude limits of the aircraft type
+    OutsideAltitudeLimits = 4,
+    /// Every path arrives while the target vertiport is outside its
+    ///   operating hours
+    OutsideOperatingHours = 5,
+}
+impl PathFailureReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            PathFailureReason::NoWaypointsNearby => "NO_WAYPOINTS_NEARBY",
+            PathFailureReason::ExceededMaxDistance => "EXCEEDED_MAX_DISTANCE",
+            PathFailureReason::AllCandidatesConflicted => "ALL_CANDIDATES_CONFLICTED",
+            PathFailureReason::TimeBudgetExhausted => "TIME_BUDGET_EXHAUSTED",
+            PathFailureReason::OutsideAltitudeLimits => "OUTSIDE_ALTITUDE_LIMITS",
+            PathFailureReason::OutsideOperatingHours => "OUTSIDE_OPERATING_HOURS",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "NO_WAYPOINTS_NEARBY" => Some(Self::NoWaypointsNearby),
+            "EXCEEDED_MAX_DISTANCE" => Some(Self::ExceededMaxDistance),
+            "ALL_CANDIDATES_CONFLICTED" => Some(Self::AllCandidatesConflicted),
+            "TIME_BUDGET_EXHAUSTED" => Some(Self::TimeBudgetExhausted),
+            "OUTSIDE_ALTITUDE_LIMITS" => Some(Self::OutsideAltitudeLimits),
+            "OUTSIDE_OPERATING_HOURS" => Some(Self::OutsideOperatingHours),
+            _ => None,
+        }
+    }
+}
+/// How a zone constrains a volume or path
+/// Ordered from most to least severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ConstraintEffect {
+    /// The zone is active and may not be entered
+    ConstraintBlocking = 0,
+    /// The aircraft or flight is authorized to enter the zone
+    ConstraintAuthorized = 1,
+    /// The zone is valid but its schedule is closed during the time window
+    ConstraintUnscheduled = 2,
+}
+impl ConstraintEffect {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ConstraintEffect::ConstraintBlocking => "CONSTRAINT_BLOCKING",
+            ConstraintEffect::ConstraintAuthorized => "CONSTRAINT_AUTHORIZED",
+            ConstraintEffect::ConstraintUnscheduled => "CONSTRAINT_UNSCHEDULED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CONSTRAINT_BLOCKING" => Some(Self::ConstraintBlocking),
+            "CONSTRAINT_AUTHORIZED" => Some(Self::ConstraintAuthorized),
+            "CONSTRAINT_UNSCHEDULED" => Some(Self::ConstraintUnscheduled),
+            _ => None,
+        }
+    }
+}
+/// Encoding of the vertices of geometries in responses
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum GeometryEncoding {
+    /// Vertices as a list of coordinates
+    Coordinates = 0,
+    /// Vertices as a compact ring, several times smaller on the wire
+    Compact = 1,
+}
+impl GeometryEncoding {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            GeometryEncoding::Coordinates => "COORDINATES",
+            GeometryEncoding::Compact => "COMPACT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "COORDINATES" => Some(Self::Coordinates),
+            "COMPACT" => Some(Self::Compact),
+            _ => None,
+        }
+    }
+}
+/// Field used to order paginated results
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum OrderBy {
+    /// Order by identifier
+    Identifier = 0,
+    /// Order by the most recent update
+    LastUpdated = 1,
+}
+impl OrderBy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            OrderBy::Identifier => "IDENTIFIER",
+            OrderBy::LastUpdated => "LAST_UPDATED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "IDENTIFIER" => Some(Self::Identifier),
+            "LAST_UPDATED" => Some(Self::LastUpdated),
+            _ => None,
+        }
+    }
+}
+/// Quality of the latest reported position of an aircraft
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DataQuality {
+    /// The position was reported recently
+    Fresh = 0,
+    /// The position is older than the configured stale threshold
+    Stale = 1,
+    /// The position is older than the configured expiry threshold
+    ///   and is no longer used to predict conflicts
+    Expired = 2,
+}
+impl DataQuality {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DataQuality::Fresh => "FRESH",
+            DataQuality::Stale => "STALE",
+            DataQuality::Expired => "EXPIRED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FRESH" => Some(Self::Fresh),
+            "STALE" => Some(Self::Stale),
+            "EXPIRED" => Some(Self::Expired),
+            _ => None,
+        }
+    }
+}
+/// Kinds of airspace objects kept in sync
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AirspaceEntity {
+    /// Zone
+    Zone = 0,
+    /// Waypoint
+    Waypoint = 1,
+}
+impl AirspaceEntity {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AirspaceEntity::Zone => "AIRSPACE_ENTITY_ZONE",
+            AirspaceEntity::Waypoint => "AIRSPACE_ENTITY_WAYPOINT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "AIRSPACE_ENTITY_ZONE" => Some(Self::Zone),
+            "AIRSPACE_ENTITY_WAYPOINT" => Some(Self::Waypoint),
+            _ => None,
+        }
+    }
+}
+/// How much a reachability answer can be relied on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReachabilityConfidence {
+    /// A detour is needed and may not fit the range
+    Low = 0,
+    /// The range fits with little margin, or a detour is needed
+    Medium = 1,
+    /// The answer holds even with a detour
+    High = 2,
+}
+impl ReachabilityConfidence {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReachabilityConfidence::Low => "LOW",
+            ReachabilityConfidence::Medium => "MEDIUM",
+            ReachabilityConfidence::High => "HIGH",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "LOW" => Some(Self::Low),
+            "MEDIUM" => Some(Self::Medium),
+            "HIGH" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+#[cfg(not(tarpaulin_include))]
+pub mod ingest_service_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// Writes: assets, zones and flight plans
+    #[derive(Debug, Clone)]
+    pub struct IngestServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl IngestServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> IngestServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> IngestServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            IngestServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn is_ready(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReadyRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReadyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/isReady",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "isReady"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_vertiports(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateVertiportsRequest>,
+        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/updateVertiports",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "updateVertiports"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn set_vertiport_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetVertiportStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/setVertiportStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "setVertiportStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_waypoints(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateWaypointsRequest>,
+        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/updateWaypoints",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "updateWaypoints"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn import_waypoints(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportWaypointsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportWaypointsSummary>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/importWaypoints",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "importWaypoints"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_zones(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateZonesRequest>,
+        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/updateZones",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "updateZones"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_zones_stream(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<
+                Message = super::UpdateZonesRequest,
+            >,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateZonesSummary>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/updateZonesStream",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "updateZonesStream"));
+            self.inner.client_streaming(req, path, codec).await
+        }
+        pub async fn update_zone_attributes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateZoneAttributesRequest>,
+        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/updateZoneAttributes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "updateZoneAttributes"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_noise_areas(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateNoiseAreasRequest>,
+        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/updateNoiseAreas",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "updateNoiseAreas"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn request_zone_authorization(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ZoneAuthorizationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ZoneAuthorization>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/requestZoneAuthorization",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("grpc.IngestService", "requestZoneAuthorization"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_flight_path(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateFlightPathRequest>,
+        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/updateFlightPath",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "updateFlightPath"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn reserve_volume(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReserveVolumeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::VolumeReservation>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/reserveVolume",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "reserveVolume"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn release_reservation(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReleaseReservationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::VolumeReservation>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.IngestService/releaseReservation",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.IngestService", "releaseReservation"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated client implementations.
+#[cfg(not(tarpaulin_include))]
+pub mod query_service_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// Reads: routing, conflict checks and listings
+    /// Only best path audits are written, which query-only replicas skip
+    #[derive(Debug, Clone)]
+    pub struct QueryServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl QueryServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
         pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
         where
             D: TryInto<tonic::transport::Endpoint>,
@@ -420,76 +2781,400 @@ pub mod rpc_service_client {
             let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
             Ok(Self::new(conn))
         }
-    }
-    impl<T> RpcServiceClient<T>
-    where
-        T: tonic::client::GrpcService<tonic::body::BoxBody>,
-        T::Error: Into<StdError>,
-        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
-        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
-    {
-        pub fn new(inner: T) -> Self {
-            let inner = tonic::client::Grpc::new(inner);
-            Self { inner }
+    }
+    impl<T> QueryServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> QueryServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            QueryServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn is_ready(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReadyRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReadyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/isReady",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("grpc.QueryService", "isReady"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_authorizations(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListAuthorizationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListAuthorizationsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/listAuthorizations",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "listAuthorizations"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn preview_zone_waypoints(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PreviewZoneWaypointsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PreviewZoneWaypointsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/previewZoneWaypoints",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "previewZoneWaypoints"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn best_path(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BestPathRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BestPathResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/bestPath",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "bestPath"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn check_intersection(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckIntersectionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckIntersectionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/checkIntersection",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "checkIntersection"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn suggest_deconfliction(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SuggestDeconflictionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SuggestDeconflictionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/suggestDeconfliction",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "suggestDeconfliction"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn distance_matrix(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DistanceMatrixRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DistanceMatrixResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/distanceMatrix",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "distanceMatrix"));
+            self.inner.unary(req, path, codec).await
         }
-        pub fn with_origin(inner: T, origin: Uri) -> Self {
-            let inner = tonic::client::Grpc::with_origin(inner, origin);
-            Self { inner }
+        pub async fn check_volume(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckVolumeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckVolumeResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/checkVolume",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "checkVolume"));
+            self.inner.unary(req, path, codec).await
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> RpcServiceClient<InterceptedService<T, F>>
-        where
-            F: tonic::service::Interceptor,
-            T::ResponseBody: Default,
-            T: tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-                Response = http::Response<
-                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
-                >,
-            >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::BoxBody>,
-            >>::Error: Into<StdError> + Send + Sync,
-        {
-            RpcServiceClient::new(InterceptedService::new(inner, interceptor))
+        pub async fn find_zone_gaps(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FindZoneGapsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FindZoneGapsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/findZoneGaps",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "findZoneGaps"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Compress requests with the given encoding.
-        ///
-        /// This requires the server to support it otherwise it might respond with an
-        /// error.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.send_compressed(encoding);
-            self
+        pub async fn get_zone_constraints(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetZoneConstraintsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetZoneConstraintsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/getZoneConstraints",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "getZoneConstraints"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Enable decompressing responses.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.accept_compressed(encoding);
-            self
+        pub async fn get_best_path_audits(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetBestPathAuditsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetBestPathAuditsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/getBestPathAudits",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "getBestPathAudits"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_decoding_message_size(limit);
-            self
+        pub async fn get_flights(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetFlightsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetFlightsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/getFlights",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "getFlights"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_encoding_message_size(limit);
-            self
+        pub async fn get_zones(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetZonesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetZonesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/getZones",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "getZones"));
+            self.inner.unary(req, path, codec).await
         }
-        pub async fn is_ready(
+        pub async fn get_zones_at_point(
             &mut self,
-            request: impl tonic::IntoRequest<super::ReadyRequest>,
-        ) -> std::result::Result<tonic::Response<super::ReadyResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::GetZonesAtPointRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetZonesAtPointResponse>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -500,15 +3185,21 @@ pub mod rpc_service_client {
                     )
                 })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/grpc.RpcService/isReady");
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/getZonesAtPoint",
+            );
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("grpc.RpcService", "isReady"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "getZonesAtPoint"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn update_vertiports(
+        pub async fn get_waypoints(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateVertiportsRequest>,
-        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::GetWaypointsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetWaypointsResponse>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -520,17 +3211,20 @@ pub mod rpc_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/grpc.RpcService/updateVertiports",
+                "/grpc.QueryService/getWaypoints",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("grpc.RpcService", "updateVertiports"));
+                .insert(GrpcMethod::new("grpc.QueryService", "getWaypoints"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn update_waypoints(
+        pub async fn sync_airspace(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateWaypointsRequest>,
-        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::SyncAirspaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SyncAirspaceResponse>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -542,17 +3236,20 @@ pub mod rpc_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/grpc.RpcService/updateWaypoints",
+                "/grpc.QueryService/syncAirspace",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("grpc.RpcService", "updateWaypoints"));
+                .insert(GrpcMethod::new("grpc.QueryService", "syncAirspace"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn update_zones(
+        pub async fn get_vertiports(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateZonesRequest>,
-        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::GetVertiportsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetVertiportsResponse>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -564,17 +3261,20 @@ pub mod rpc_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/grpc.RpcService/updateZones",
+                "/grpc.QueryService/getVertiports",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("grpc.RpcService", "updateZones"));
+                .insert(GrpcMethod::new("grpc.QueryService", "getVertiports"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn update_flight_path(
+        pub async fn get_service_info(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateFlightPathRequest>,
-        ) -> std::result::Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::ServiceInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ServiceInfoResponse>,
+            tonic::Status,
+        > {
             self.inner
                 .ready()
                 .await
@@ -586,18 +3286,18 @@ pub mod rpc_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/grpc.RpcService/updateFlightPath",
+                "/grpc.QueryService/getServiceInfo",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("grpc.RpcService", "updateFlightPath"));
+                .insert(GrpcMethod::new("grpc.QueryService", "getServiceInfo"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn best_path(
+        pub async fn get_statistics(
             &mut self,
-            request: impl tonic::IntoRequest<super::BestPathRequest>,
+            request: impl tonic::IntoRequest<super::GetStatisticsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::BestPathResponse>,
+            tonic::Response<super::GetStatisticsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -610,16 +3310,19 @@ pub mod rpc_service_client {
                     )
                 })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/grpc.RpcService/bestPath");
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.QueryService/getStatistics",
+            );
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("grpc.RpcService", "bestPath"));
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.QueryService", "getStatistics"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn check_intersection(
+        pub async fn check_reachability(
             &mut self,
-            request: impl tonic::IntoRequest<super::CheckIntersectionRequest>,
+            request: impl tonic::IntoRequest<super::CheckReachabilityRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::CheckIntersectionResponse>,
+            tonic::Response<super::CheckReachabilityResponse>,
             tonic::Status,
         > {
             self.inner
@@ -633,18 +3336,18 @@ pub mod rpc_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/grpc.RpcService/checkIntersection",
+                "/grpc.QueryService/checkReachability",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("grpc.RpcService", "checkIntersection"));
+                .insert(GrpcMethod::new("grpc.QueryService", "checkReachability"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_flights(
+        pub async fn get_snapshot(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetFlightsRequest>,
+            request: impl tonic::IntoRequest<super::GetSnapshotRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetFlightsResponse>,
+            tonic::Response<super::GetSnapshotResponse>,
             tonic::Status,
         > {
             self.inner
@@ -658,11 +3361,11 @@ pub mod rpc_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/grpc.RpcService/getFlights",
+                "/grpc.QueryService/getSnapshot",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("grpc.RpcService", "getFlights"));
+                .insert(GrpcMethod::new("grpc.QueryService", "getSnapshot"));
             self.inner.unary(req, path, codec).await
         }
     }
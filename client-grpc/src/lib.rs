@@ -1,8 +1,26 @@
 #![doc = include_str!("../README.md")]
 
 pub mod client;
+pub mod compat;
+pub mod details;
+pub mod export;
+#[cfg(feature = "geo")]
+pub mod geometry;
 pub mod prelude;
+pub mod proto;
+#[cfg(feature = "redis-publisher")]
+pub mod publisher;
 pub mod service;
+#[cfg(feature = "stream")]
+pub mod stream;
+
+/// Canned airspace scenarios shared by the tests and examples
+#[cfg(feature = "mock")]
+pub mod fixtures {
+    use super::client::*;
+    use super::prelude::types::AircraftType;
+    include!("../../common/fixtures.rs");
+}
 
 use client::*;
 
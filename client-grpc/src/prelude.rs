@@ -1,8 +1,11 @@
 //! Re-export of used objects
 
 pub use super::client as gis;
-pub use super::service::Client as GisServiceClient;
-pub use gis::GisClient;
+pub use super::service::{IngestClient, QueryClient};
+pub use gis::{GisIngestClient, GisQueryClient};
+
+#[cfg(feature = "geo")]
+pub use super::geometry::GeometryError;
 
 /// Types used with svc-gis Redis queues
 pub mod types {
@@ -0,0 +1,37 @@
+//! The svc-gis service definition, for clients in other languages
+//!
+//! The proto file and its encoded `FileDescriptorSet` ship with this crate
+//!  under `proto/`, and are exposed here for tools that generate bindings
+//!  or call the service dynamically. The server also serves the descriptors
+//!  through gRPC reflection, so grpcurl can be used without them.
+
+/// The svc-gis proto file
+pub const PROTO: &str = include_str!("../proto/grpc.proto");
+
+/// The encoded `FileDescriptorSet` of the proto file and its imports
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("../proto/grpc_descriptor.bin");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use prost_types::FileDescriptorSet;
+
+    #[test]
+    fn ut_file_descriptor_set() {
+        let set = FileDescriptorSet::decode(FILE_DESCRIPTOR_SET).unwrap();
+        let file = set
+            .file
+            .iter()
+            .find(|file| file.name() == "grpc.proto")
+            .unwrap();
+
+        assert_eq!(file.package(), "grpc");
+        let services: Vec<&str> = file.service.iter().map(|s| s.name()).collect();
+        assert!(services.contains(&"IngestService"));
+        assert!(services.contains(&"QueryService"));
+
+        // the descriptors are generated from the shipped proto
+        assert!(PROTO.contains("service QueryService"));
+    }
+}
@@ -0,0 +1,358 @@
+//! Publishing aircraft reports to the svc-gis Redis queues
+//!
+//! Telemetry producers push [`AircraftId`], [`AircraftPosition`] and
+//!  [`AircraftVelocity`] reports onto Redis lists that the server pops from
+//!  the other end. [`Publisher`] serializes the reports as the server reads
+//!  them and sends them in batches, one pipeline per batch.
+//!
+//! ```no_run
+//! use lib_common::time::Utc;
+//! use svc_gis_client_grpc::prelude::*;
+//! use svc_gis_client_grpc::publisher::Publisher;
+//!
+//! fn example() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = redis::Client::open(std::env::var("REDIS__URL")?)?;
+//!     let mut connection = client.get_connection()?;
+//!     let mut publisher = Publisher::new(&mut connection);
+//!     publisher.push_position(&AircraftPosition {
+//!         identifier: "Marauder".to_string(),
+//!         position: Position {
+//!             latitude: Degrees(52.3746),
+//!             longitude: Degrees(4.9160036),
+//!             altitude_meters: Meters(100.0),
+//!         },
+//!         timestamp_network: Utc::now(),
+//!         timestamp_asset: None,
+//!     })?;
+//!
+//!     // Reports are sent when a batch is full, or when flushed
+//!     publisher.flush()?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Async producers can fill a [`Batch`] and send its
+//!  [`pipeline`](Batch::pipeline) with their own connection.
+
+use super::prelude::types::{
+    AircraftId, AircraftPosition, AircraftVelocity, REDIS_KEY_AIRCRAFT_ID,
+    REDIS_KEY_AIRCRAFT_POSITION, REDIS_KEY_AIRCRAFT_VELOCITY,
+};
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+
+/// Default number of reports sent in one pipeline
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Possible errors publishing reports
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PublishError {
+    /// A report could not be serialized
+    Serialize,
+
+    /// The reports could not be sent to Redis
+    Redis,
+}
+
+impl Display for PublishError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PublishError::Serialize => write!(f, "Could not serialize the report."),
+            PublishError::Redis => write!(f, "Could not send the reports to Redis."),
+        }
+    }
+}
+
+impl std::error::Error for PublishError {}
+
+/// A report the server reads from a Redis queue
+pub trait Report: Serialize {
+    /// Key of the queue the report is pushed to
+    const KEY: &'static str;
+}
+
+impl Report for AircraftId {
+    const KEY: &'static str = REDIS_KEY_AIRCRAFT_ID;
+}
+
+impl Report for AircraftPosition {
+    const KEY: &'static str = REDIS_KEY_AIRCRAFT_POSITION;
+}
+
+impl Report for AircraftVelocity {
+    const KEY: &'static str = REDIS_KEY_AIRCRAFT_VELOCITY;
+}
+
+/// Reports waiting to be sent, as a pipeline of pushes
+#[derive(Clone, Default)]
+pub struct Batch {
+    pipeline: redis::Pipeline,
+    len: usize,
+}
+
+impl Batch {
+    /// Creates an empty batch
+    pub fn new() -> Self {
+        Batch::default()
+    }
+
+    /// Adds a report to the batch
+    ///  Reports are pushed to the head of their queue, which the server
+    ///  pops from the tail, so they are processed in the order pushed.
+    pub fn push<R: Report>(&mut self, report: &R) -> Result<(), PublishError> {
+        let value = serde_json::to_vec(report).map_err(|_| PublishError::Serialize)?;
+        self.pipeline.lpush(R::KEY, value).ignore();
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Number of reports in the batch
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// If the batch holds no reports
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The pipeline pushing the reports of the batch
+    pub fn pipeline(&self) -> &redis::Pipeline {
+        &self.pipeline
+    }
+
+    /// Removes the reports of the batch
+    pub fn clear(&mut self) {
+        self.pipeline.clear();
+        self.len = 0;
+    }
+}
+
+impl fmt::Debug for Batch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Batch").field("len", &self.len).finish()
+    }
+}
+
+/// Sends reports to the svc-gis queues in batches
+///  Reports still pending when the publisher is dropped are lost; call
+///  [`Publisher::flush`] first.
+pub struct Publisher<'a, C: redis::ConnectionLike> {
+    connection: &'a mut C,
+    batch: Batch,
+    batch_size: usize,
+}
+
+impl<'a, C: redis::ConnectionLike> Publisher<'a, C> {
+    /// Creates a publisher sending batches of [`DEFAULT_BATCH_SIZE`] reports
+    pub fn new(connection: &'a mut C) -> Self {
+        Publisher::with_batch_size(connection, DEFAULT_BATCH_SIZE)
+    }
+
+    /// Creates a publisher sending batches of `batch_size` reports,
+    ///  sending each report immediately with a size of 0 or 1
+    pub fn with_batch_size(connection: &'a mut C, batch_size: usize) -> Self {
+        Publisher {
+            connection,
+            batch: Batch::new(),
+            batch_size,
+        }
+    }
+
+    /// Adds a report, sending the batch if it is full
+    pub fn push<R: Report>(&mut self, report: &R) -> Result<(), PublishError> {
+        self.batch.push(report)?;
+        if self.batch.len() >= self.batch_size {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds an aircraft identification report
+    pub fn push_id(&mut self, id: &AircraftId) -> Result<(), PublishError> {
+        self.push(id)
+    }
+
+    /// Adds an aircraft position report
+    pub fn push_position(&mut self, position: &AircraftPosition) -> Result<(), PublishError> {
+        self.push(position)
+    }
+
+    /// Adds an aircraft velocity report
+    pub fn push_velocity(&mut self, velocity: &AircraftVelocity) -> Result<(), PublishError> {
+        self.push(velocity)
+    }
+
+    /// Number of reports not sent yet
+    pub fn pending(&self) -> usize {
+        self.batch.len()
+    }
+
+    /// Sends the pending reports
+    ///  The reports are kept if they could not be sent, so that the flush
+    ///  can be retried.
+    pub fn flush(&mut self) -> Result<(), PublishError> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        self.batch
+            .pipeline()
+            .query::<()>(&mut *self.connection)
+            .map_err(|_| PublishError::Redis)?;
+
+        self.batch.clear();
+        Ok(())
+    }
+}
+
+impl<C: redis::ConnectionLike> fmt::Debug for Publisher<'_, C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Publisher")
+            .field("batch", &self.batch)
+            .field("batch_size", &self.batch_size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::types::{AircraftType, Degrees, Meters, Position};
+    use lib_common::time::Utc;
+
+    /// Records the commands sent, failing them while `fail` is set
+    #[derive(Default)]
+    struct Connection {
+        sent: Vec<Vec<u8>>,
+        fail: bool,
+    }
+
+    impl redis::ConnectionLike for Connection {
+        fn req_packed_command(&mut self, cmd: &[u8]) -> redis::RedisResult<redis::Value> {
+            self.req_packed_commands(cmd, 0, 1)
+                .map(|_| redis::Value::Okay)
+        }
+
+        fn req_packed_commands(
+            &mut self,
+            cmd: &[u8],
+            _offset: usize,
+            count: usize,
+        ) -> redis::RedisResult<Vec<redis::Value>> {
+            if self.fail {
+                return Err((redis::ErrorKind::IoError, "unavailable").into());
+            }
+
+            self.sent.push(cmd.to_vec());
+            Ok(vec![redis::Value::Int(1); count])
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+
+        fn check_connection(&mut self) -> bool {
+            true
+        }
+
+        fn is_open(&self) -> bool {
+            true
+        }
+    }
+
+    fn position(identifier: &str) -> AircraftPosition {
+        AircraftPosition {
+            identifier: identifier.to_string(),
+            position: Position {
+                latitude: Degrees(52.3746),
+                longitude: Degrees(4.9160036),
+                altitude_meters: Meters(100.0),
+            },
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        }
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn ut_batch_push() {
+        let mut batch = Batch::new();
+        assert!(batch.is_empty());
+
+        let report = position("Marauder");
+        batch.push(&report).unwrap();
+        batch
+            .push(&AircraftId {
+                identifier: Some("Marauder".to_string()),
+                session_id: None,
+                aircraft_type: AircraftType::Rotorcraft,
+                timestamp_network: Utc::now(),
+                timestamp_asset: None,
+                session_ended: false,
+            })
+            .unwrap();
+
+        assert_eq!(batch.len(), 2);
+        let packed = batch.pipeline().get_packed_pipeline();
+        assert!(contains(&packed, b"LPUSH"));
+        assert!(contains(&packed, REDIS_KEY_AIRCRAFT_POSITION.as_bytes()));
+        assert!(contains(&packed, REDIS_KEY_AIRCRAFT_ID.as_bytes()));
+        assert!(contains(&packed, &serde_json::to_vec(&report).unwrap()));
+
+        batch.clear();
+        assert!(batch.is_empty());
+        assert!(batch.pipeline().get_packed_pipeline().is_empty());
+    }
+
+    #[test]
+    fn ut_publisher_batches() {
+        let mut connection = Connection::default();
+        let mut publisher = Publisher::with_batch_size(&mut connection, 2);
+        publisher.push_position(&position("Marauder")).unwrap();
+        assert_eq!(publisher.pending(), 1);
+
+        publisher.push_position(&position("Mantis")).unwrap();
+        assert_eq!(publisher.pending(), 0);
+
+        publisher.push_position(&position("Ghost")).unwrap();
+        publisher.flush().unwrap();
+        publisher.flush().unwrap();
+        assert_eq!(publisher.pending(), 0);
+
+        assert_eq!(connection.sent.len(), 2);
+        assert!(contains(&connection.sent[0], b"Mantis"));
+        assert!(contains(&connection.sent[1], b"Ghost"));
+    }
+
+    #[test]
+    fn ut_publisher_flush_failure() {
+        let mut connection = Connection {
+            fail: true,
+            ..Default::default()
+        };
+
+        let mut publisher = Publisher::new(&mut connection);
+        publisher.push_position(&position("Marauder")).unwrap();
+        assert_eq!(publisher.flush(), Err(PublishError::Redis));
+        assert_eq!(publisher.pending(), 1);
+    }
+
+    #[test]
+    fn test_publish_error_display() {
+        assert_eq!(
+            PublishError::Serialize.to_string(),
+            "Could not serialize the report."
+        );
+        assert_eq!(
+            PublishError::Redis.to_string(),
+            "Could not send the reports to Redis."
+        );
+    }
+}
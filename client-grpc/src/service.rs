@@ -1,8 +1,9 @@
 //! Client Library: Client Functions, Structs, Traits
 
-/// gRPC object traits to provide wrappers for grpc functions
+/// Wrappers for the functions of the ingest service, which stores assets,
+///  zones and flight plans
 #[tonic::async_trait]
-pub trait Client<T>
+pub trait IngestClient<T>
 where
     Self: Sized + lib_common::grpc::Client<T> + lib_common::grpc::ClientConnect<T>,
     T: Send + Clone,
@@ -27,7 +28,7 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
     ///     let response = client
     ///         .is_ready(gis::ReadyRequest {})
     ///         .await?;
@@ -55,7 +56,7 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
     ///     let request = gis::UpdateWaypointsRequest { waypoints: vec![] };
     ///     let response = client.update_waypoints(request).await?;
     ///     println!("RESPONSE={:?}", response.into_inner());
@@ -67,6 +68,36 @@ where
         request: super::UpdateWaypointsRequest,
     ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
 
+    /// Returns a [`tonic::Response`] containing an [`ImportWaypointsSummary`](super::ImportWaypointsSummary)
+    /// Takes an [`ImportWaypointsRequest`](super::ImportWaypointsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let request = gis::ImportWaypointsRequest {
+    ///         source: gis::WaypointSource::Openaip as i32,
+    ///         data: std::fs::read("reporting-points.json")?,
+    ///     };
+    ///     let response = client.import_waypoints(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn import_waypoints(
+        &self,
+        request: super::ImportWaypointsRequest,
+    ) -> Result<tonic::Response<super::ImportWaypointsSummary>, tonic::Status>;
+
     /// Returns a [`tonic::Response`] containing a [`UpdateResponse`](super::UpdateResponse)
     /// Takes an [`UpdateVertiportsRequest`](super::UpdateVertiportsRequest).
     ///
@@ -82,8 +113,11 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
-    ///     let request = gis::UpdateVertiportsRequest { vertiports: vec![] };
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let request = gis::UpdateVertiportsRequest {
+    ///         vertiports: vec![],
+    ///         validate_only: false,
+    ///     };
     ///     let response = client.update_vertiports(request).await?;
     ///     println!("RESPONSE={:?}", response.into_inner());
     ///     Ok(())
@@ -94,6 +128,36 @@ where
         request: super::UpdateVertiportsRequest,
     ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
 
+    /// Returns a [`tonic::Response`] containing a [`UpdateResponse`](super::UpdateResponse)
+    /// Takes an [`SetVertiportStatusRequest`](super::SetVertiportStatusRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let request = gis::SetVertiportStatusRequest {
+    ///         identifier: "Kamino".to_string(),
+    ///         status: gis::VertiportStatus::Closed as i32,
+    ///     };
+    ///     let response = client.set_vertiport_status(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn set_vertiport_status(
+        &self,
+        request: super::SetVertiportStatusRequest,
+    ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
+
     /// Returns a [`tonic::Response`] containing a [`UpdateResponse`](super::UpdateResponse)
     /// Takes an [`UpdateZonesRequest`](super::UpdateZonesRequest).
     ///
@@ -109,8 +173,11 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
-    ///     let request = gis::UpdateZonesRequest { zones: vec![] };
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let request = gis::UpdateZonesRequest {
+    ///         zones: vec![],
+    ///         validate_only: false,
+    ///     };
     ///     let response = client.update_zones(request).await?;
     ///     println!("RESPONSE={:?}", response.into_inner());
     ///     Ok(())
@@ -121,6 +188,154 @@ where
         request: super::UpdateZonesRequest,
     ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
 
+    /// Returns a [`tonic::Response`] containing a [`UpdateResponse`](super::UpdateResponse)
+    /// Takes an [`UpdateNoiseAreasRequest`](super::UpdateNoiseAreasRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let request = gis::UpdateNoiseAreasRequest {
+    ///         areas: vec![gis::NoiseArea {
+    ///             identifier: "residential-1".to_string(),
+    ///             vertices: vec![],
+    ///             altitude_meters_floor: 150.0,
+    ///             quiet_hours: vec![gis::ActiveWindow {
+    ///                 days: vec![gis::Weekday::Friday as i32, gis::Weekday::Saturday as i32],
+    ///                 start_minute: 22 * 60,
+    ///                 end_minute: 7 * 60,
+    ///             }],
+    ///         }],
+    ///     };
+    ///     let response = client.update_noise_areas(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn update_noise_areas(
+        &self,
+        request: super::UpdateNoiseAreasRequest,
+    ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing the granted [`ZoneAuthorization`](super::ZoneAuthorization)
+    /// Takes a [`ZoneAuthorizationRequest`](super::ZoneAuthorizationRequest).
+    ///
+    /// Paths checked for the aircraft or flight don't conflict with the
+    /// zone during the validity window.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Duration, Utc};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let time_start = Utc::now();
+    ///     let request = gis::ZoneAuthorizationRequest {
+    ///         zone_identifier: "NFZ-1".to_string(),
+    ///         aircraft_identifier: Some("N12345".to_string()),
+    ///         flight_identifier: None,
+    ///         time_start: Some(time_start.into()),
+    ///         time_end: Some((time_start + Duration::try_hours(1).unwrap()).into()),
+    ///         reference: Some("PERMIT-42".to_string()),
+    ///     };
+    ///     let response = client.request_zone_authorization(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn request_zone_authorization(
+        &self,
+        request: super::ZoneAuthorizationRequest,
+    ) -> Result<tonic::Response<super::ZoneAuthorization>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing an [`UpdateZonesSummary`](super::UpdateZonesSummary)
+    /// Takes a stream of [`UpdateZonesRequest`](super::UpdateZonesRequest) chunks.
+    ///
+    /// Intended for imports too large for a single request. The server applies
+    ///  the zones in bounded-size transactions as the chunks arrive and reports
+    ///  the zones that could not be imported in the summary.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    /// use tonic::codegen::tokio_stream;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let chunks = vec![
+    ///         gis::UpdateZonesRequest::default(),
+    ///         gis::UpdateZonesRequest::default(),
+    ///     ];
+    ///     let response = client
+    ///         .update_zones_stream(tokio_stream::iter(chunks))
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn update_zones_stream<S>(
+        &self,
+        request: S,
+    ) -> Result<tonic::Response<super::UpdateZonesSummary>, tonic::Status>
+    where
+        S: tonic::IntoStreamingRequest<Message = super::UpdateZonesRequest> + Send + 'static;
+
+    /// Returns a [`tonic::Response`] containing a [`UpdateResponse`](super::UpdateResponse)
+    /// Takes an [`UpdateZoneAttributesRequest`](super::UpdateZoneAttributesRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Utc, Duration};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let request = gis::UpdateZoneAttributesRequest {
+    ///         identifier: "NL-NFZ-01".to_string(),
+    ///         time_end: Some((Utc::now() + Duration::hours(1)).into()),
+    ///         ..Default::default()
+    ///     };
+    ///     let response = client.update_zone_attributes(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn update_zone_attributes(
+        &self,
+        request: super::UpdateZoneAttributesRequest,
+    ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
+
     /// Returns a [`tonic::Response`] containing a [`UpdateResponse`](super::UpdateResponse)
     /// Takes an [`UpdateFlightPathRequest`](super::UpdateFlightPathRequest).
     ///
@@ -137,7 +352,7 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
     ///     let request = gis::UpdateFlightPathRequest {
     ///         flight_identifier: Some("flight-x".to_string()),
     ///         aircraft_identifier: Some("aircraft-x".to_string()),
@@ -146,6 +361,7 @@ where
     ///         timestamp_start: Some(Utc::now().into()),
     ///         timestamp_end: Some(Utc::now().into()),
     ///         path: vec![],
+    ///         priority: gis::FlightPriority::Routine as i32,
     ///     };
     ///     let response = client.update_flight_path(request).await?;
     ///     println!("RESPONSE={:?}", response.into_inner());
@@ -157,6 +373,192 @@ where
         request: super::UpdateFlightPathRequest,
     ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
 
+    /// Returns a [`tonic::Response`] containing the granted [`VolumeReservation`](super::VolumeReservation)
+    /// Takes a [`ReserveVolumeRequest`](super::ReserveVolumeRequest).
+    ///
+    /// Paths crossing the volume during the window conflict with the
+    /// reservation as with a filed flight. Fails if another reservation
+    /// overlaps the volume during the window.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Duration, Utc};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let vertex = |latitude, longitude| gis::Coordinates { latitude, longitude };
+    ///     let time_start = Utc::now();
+    ///     let request = gis::ReserveVolumeRequest {
+    ///         operator_identifier: "OPERATOR-1".to_string(),
+    ///         vertices: vec![
+    ///             vertex(52.374, 4.915),
+    ///             vertex(52.376, 4.916),
+    ///             vertex(52.374, 4.917),
+    ///             vertex(52.374, 4.915),
+    ///         ],
+    ///         altitude_meters_min: 20.0,
+    ///         altitude_meters_max: 120.0,
+    ///         time_start: Some(time_start.into()),
+    ///         time_end: Some((time_start + Duration::try_hours(1).unwrap()).into()),
+    ///         reference: Some("SURVEY-7".to_string()),
+    ///     };
+    ///     let response = client.reserve_volume(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn reserve_volume(
+        &self,
+        request: super::ReserveVolumeRequest,
+    ) -> Result<tonic::Response<super::VolumeReservation>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing the released [`VolumeReservation`](super::VolumeReservation)
+    /// Takes a [`ReleaseReservationRequest`](super::ReleaseReservationRequest).
+    ///
+    /// Only the operator holding the reservation may release it, the
+    /// volume is free from then on.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisIngestClient::new_client(&host, port, "gis");
+    ///     let request = gis::ReleaseReservationRequest {
+    ///         identifier: "00000000-0000-0000-0000-000000000000".to_string(),
+    ///         operator_identifier: "OPERATOR-1".to_string(),
+    ///     };
+    ///     let response = client.release_reservation(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn release_reservation(
+        &self,
+        request: super::ReleaseReservationRequest,
+    ) -> Result<tonic::Response<super::VolumeReservation>, tonic::Status>;
+}
+
+/// Wrappers for the functions of the query service, which plans and checks
+///  paths and lists what is stored
+#[tonic::async_trait]
+pub trait QueryClient<T>
+where
+    Self: Sized + lib_common::grpc::Client<T> + lib_common::grpc::ClientConnect<T>,
+    T: Send + Clone,
+{
+    /// The type expected for ReadyRequest structs.
+    type ReadyRequest;
+    /// The type expected for ReadyResponse structs.
+    type ReadyResponse;
+
+    /// Returns a [`tonic::Response`] containing a [`ReadyResponse`](Self::ReadyResponse)
+    /// Takes an [`ReadyRequest`](Self::ReadyRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let response = client
+    ///         .is_ready(gis::ReadyRequest {})
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn is_ready(
+        &self,
+        request: Self::ReadyRequest,
+    ) -> Result<tonic::Response<Self::ReadyResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`ListAuthorizationsResponse`](super::ListAuthorizationsResponse)
+    /// Takes a [`ListAuthorizationsRequest`](super::ListAuthorizationsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::ListAuthorizationsRequest {
+    ///         zone_identifier: Some("NFZ-1".to_string()),
+    ///         aircraft_identifier: None,
+    ///         flight_identifier: None,
+    ///         include_expired: false,
+    ///     };
+    ///     let response = client.list_authorizations(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn list_authorizations(
+        &self,
+        request: super::ListAuthorizationsRequest,
+    ) -> Result<tonic::Response<super::ListAuthorizationsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`PreviewZoneWaypointsResponse`](super::PreviewZoneWaypointsResponse)
+    /// Takes a [`PreviewZoneWaypointsRequest`](super::PreviewZoneWaypointsRequest).
+    ///
+    /// The zone is not stored, the response lists the waypoints that would
+    ///  be generated around it if it were.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::PreviewZoneWaypointsRequest {
+    ///         zone: Some(gis::Zone::default()),
+    ///     };
+    ///     let response = client.preview_zone_waypoints(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn preview_zone_waypoints(
+        &self,
+        request: super::PreviewZoneWaypointsRequest,
+    ) -> Result<tonic::Response<super::PreviewZoneWaypointsResponse>, tonic::Status>;
+
     /// Returns a [`tonic::Response`] containing a [`BestPathResponse`](super::BestPathResponse)
     /// Takes an [`BestPathRequest`](super::BestPathRequest).
     ///
@@ -165,6 +567,11 @@ where
     /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
     /// the server is not ready.
     ///
+    /// Returns [`tonic::Status`] with [`Code::NotFound`](tonic::Code::NotFound) if
+    /// no path could be found. The status details hold an encoded
+    /// [`PathFailure`](super::PathFailure) with the reason, which can be used to
+    /// decide whether to retry with a different time window.
+    ///
     /// # Examples
     /// ```
     /// use lib_common::grpc::get_endpoint_from_env;
@@ -173,7 +580,7 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
     ///     let time_start: Timestamp = Utc::now().into();
     ///     let time_end: Timestamp = Utc::now().into();
     ///     let request = gis::BestPathRequest {
@@ -183,7 +590,17 @@ where
     ///         target_type: 0,
     ///         time_start: Some(time_start),
     ///         time_end: Some(time_end),
-    ///         limit: 1
+    ///         limit: 1,
+    ///         max_path_node_count: None,
+    ///         weights: None,
+    ///         include_simulated: false,
+    ///         priority: gis::FlightPriority::Routine as i32,
+    ///         max_window_offset_minutes: None,
+    ///         aircraft_identifier: None,
+    ///         flight_identifier: None,
+    ///         arrival_window_minutes: None,
+    ///         aircraft_type: None,
+    ///         operator_identifier: None,
     ///     };
     ///     let response = client.best_path(request).await?;
     ///     println!("RESPONSE={:?}", response.into_inner());
@@ -211,7 +628,7 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
     ///     let time_start: Timestamp = Utc::now().into();
     ///     let time_end: Timestamp = Utc::now().into();
     ///     let request = gis::CheckIntersectionRequest {
@@ -219,7 +636,12 @@ where
     ///         target_identifier: "Coruscant".to_string(),
     ///         path: vec![],
     ///         time_start: Some(time_start),
-    ///         time_end: Some(time_end)
+    ///         time_end: Some(time_end),
+    ///         include_simulated: false,
+    ///         priority: gis::FlightPriority::Routine as i32,
+    ///         aircraft_identifier: None,
+    ///         flight_identifier: None,
+    ///         aircraft_type: None,
     ///     };
     ///     let response = client.check_intersection(request).await?;
     ///     println!("RESPONSE={:?}", response.into_inner());
@@ -231,7 +653,247 @@ where
         request: super::CheckIntersectionRequest,
     ) -> Result<tonic::Response<super::CheckIntersectionResponse>, tonic::Status>;
 
-    /// Returns a [`tonic::Response`] containing a [`GetFlightsResponse`](super::GetFlightsResponse)
+    /// Returns a [`tonic::Response`] containing a [`SuggestDeconflictionResponse`](super::SuggestDeconflictionResponse)
+    /// Takes an [`SuggestDeconflictionRequest`](super::SuggestDeconflictionRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Utc, Timestamp};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let time_start: Timestamp = Utc::now().into();
+    ///     let time_end: Timestamp = Utc::now().into();
+    ///     let request = gis::SuggestDeconflictionRequest {
+    ///         origin_identifier: "Kamino".to_string(),
+    ///         target_identifier: "Coruscant".to_string(),
+    ///         path: vec![],
+    ///         time_start: Some(time_start),
+    ///         time_end: Some(time_end),
+    ///         include_simulated: false,
+    ///         priority: gis::FlightPriority::Routine as i32,
+    ///         aircraft_identifier: None,
+    ///         flight_identifier: None,
+    ///         aircraft_type: None,
+    ///     };
+    ///     let response = client.suggest_deconfliction(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn suggest_deconfliction(
+        &self,
+        request: super::SuggestDeconflictionRequest,
+    ) -> Result<tonic::Response<super::SuggestDeconflictionResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`DistanceMatrixResponse`](super::DistanceMatrixResponse)
+    /// Takes an [`DistanceMatrixRequest`](super::DistanceMatrixRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Utc, Timestamp};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let time_start: Timestamp = Utc::now().into();
+    ///     let time_end: Timestamp = Utc::now().into();
+    ///     let request = gis::DistanceMatrixRequest {
+    ///         origin_identifiers: vec!["Kamino".to_string(), "Naboo".to_string()],
+    ///         target_identifiers: vec!["Coruscant".to_string()],
+    ///         time_start: Some(time_start),
+    ///         time_end: Some(time_end)
+    ///     };
+    ///     let response = client.distance_matrix(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn distance_matrix(
+        &self,
+        request: super::DistanceMatrixRequest,
+    ) -> Result<tonic::Response<super::DistanceMatrixResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`CheckVolumeResponse`](super::CheckVolumeResponse)
+    /// Takes an [`CheckVolumeRequest`](super::CheckVolumeRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Utc, Timestamp};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let time_start: Timestamp = Utc::now().into();
+    ///     let time_end: Timestamp = Utc::now().into();
+    ///     let vertex = |latitude: f64, longitude: f64| gis::Coordinates {
+    ///         latitude,
+    ///         longitude,
+    ///     };
+    ///
+    ///     let request = gis::CheckVolumeRequest {
+    ///         vertices: vec![
+    ///             vertex(52.374, 4.915),
+    ///             vertex(52.376, 4.916),
+    ///             vertex(52.374, 4.917),
+    ///             vertex(52.374, 4.915),
+    ///         ],
+    ///         altitude_meters_min: 20.0,
+    ///         altitude_meters_max: 120.0,
+    ///         time_start: Some(time_start),
+    ///         time_end: Some(time_end),
+    ///         include_simulated: false,
+    ///     };
+    ///     let response = client.check_volume(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn check_volume(
+        &self,
+        request: super::CheckVolumeRequest,
+    ) -> Result<tonic::Response<super::CheckVolumeResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`FindZoneGapsResponse`](super::FindZoneGapsResponse)
+    /// with the pairs of zones in an area separated by less than a width.
+    /// Takes an [`FindZoneGapsRequest`](super::FindZoneGapsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let vertex = |latitude: f64, longitude: f64| gis::Coordinates {
+    ///         latitude,
+    ///         longitude,
+    ///     };
+    ///
+    ///     // gaps narrower than 150 meters between zones active now
+    ///     let request = gis::FindZoneGapsRequest {
+    ///         vertices: vec![
+    ///             vertex(52.37, 4.91),
+    ///             vertex(52.38, 4.91),
+    ///             vertex(52.38, 4.92),
+    ///             vertex(52.37, 4.92),
+    ///             vertex(52.37, 4.91),
+    ///         ],
+    ///         min_width_meters: 150.0,
+    ///         time_start: None,
+    ///         time_end: None,
+    ///     };
+    ///     let response = client.find_zone_gaps(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn find_zone_gaps(
+        &self,
+        request: super::FindZoneGapsRequest,
+    ) -> Result<tonic::Response<super::FindZoneGapsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetZoneConstraintsResponse`](super::GetZoneConstraintsResponse)
+    /// with the zones crossed by a volume or path, most severe first.
+    /// Takes a [`GetZoneConstraintsRequest`](super::GetZoneConstraintsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Utc, Duration};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let point = |latitude: f64, longitude: f64| gis::PointZ {
+    ///         latitude,
+    ///         longitude,
+    ///         altitude_meters: 80.0,
+    ///     };
+    ///
+    ///     // zones constraining a path flown in the next hour
+    ///     let request = gis::GetZoneConstraintsRequest {
+    ///         path: vec![point(52.37, 4.91), point(52.38, 4.92)],
+    ///         time_start: Some(Utc::now().into()),
+    ///         time_end: Some((Utc::now() + Duration::hours(1)).into()),
+    ///         aircraft_identifier: Some("Mantis".to_string()),
+    ///         ..Default::default()
+    ///     };
+    ///     let response = client.get_zone_constraints(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_zone_constraints(
+        &self,
+        request: super::GetZoneConstraintsRequest,
+    ) -> Result<tonic::Response<super::GetZoneConstraintsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetBestPathAuditsResponse`](super::GetBestPathAuditsResponse)
+    /// with the best paths recorded for a flight, newest first.
+    /// Takes a [`GetBestPathAuditsRequest`](super::GetBestPathAuditsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::GetBestPathAuditsRequest {
+    ///         flight_identifier: "FLIGHT-1".to_string(),
+    ///     };
+    ///     let response = client.get_best_path_audits(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_best_path_audits(
+        &self,
+        request: super::GetBestPathAuditsRequest,
+    ) -> Result<tonic::Response<super::GetBestPathAuditsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetFlightsResponse`](super::GetFlightsResponse)
     /// Takes an [`GetFlightsRequest`](super::GetFlightsRequest).
     ///
     /// # Errors
@@ -247,7 +909,7 @@ where
     ///
     /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
     ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
-    ///     let client = GisClient::new_client(&host, port, "gis");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
     ///     let time_start: Timestamp = Utc::now().into();
     ///     let time_end: Timestamp = Utc::now().into();
     ///     let request = gis::GetFlightsRequest {
@@ -257,6 +919,13 @@ where
     ///         window_max_y: 0.0,
     ///         time_start: Some(time_start),
     ///         time_end: Some(time_end),
+    ///         window_vertices: vec![],
+    ///         altitude_meters_min: None,
+    ///         altitude_meters_max: None,
+    ///         page: None,
+    ///         zone_proximity_meters: None,
+    ///         planned_only: false,
+    ///         include_simulated: false,
     ///     };
     ///     let response = client.get_flights(request).await?;
     ///     println!("RESPONSE={:?}", response.into_inner());
@@ -267,4 +936,300 @@ where
         &self,
         request: super::GetFlightsRequest,
     ) -> Result<tonic::Response<super::GetFlightsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetZonesResponse`](super::GetZonesResponse)
+    /// Takes an [`GetZonesRequest`](super::GetZonesRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::GetZonesRequest {
+    ///         page: Some(gis::Page {
+    ///             limit: Some(50),
+    ///             offset: 0,
+    ///             order_by: gis::OrderBy::LastUpdated as i32,
+    ///             descending: true,
+    ///         }),
+    ///         encoding: gis::GeometryEncoding::Coordinates as i32,
+    ///     };
+    ///     let response = client.get_zones(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_zones(
+        &self,
+        request: super::GetZonesRequest,
+    ) -> Result<tonic::Response<super::GetZonesResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetZonesAtPointResponse`](super::GetZonesAtPointResponse)
+    /// Takes an [`GetZonesAtPointRequest`](super::GetZonesAtPointRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::Utc;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::GetZonesAtPointRequest {
+    ///         point: Some(gis::PointZ {
+    ///             latitude: 52.3746,
+    ///             longitude: 4.9160,
+    ///             altitude_meters: 50.0,
+    ///         }),
+    ///         time: Some(Utc::now().into()),
+    ///     };
+    ///     let response = client.get_zones_at_point(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_zones_at_point(
+        &self,
+        request: super::GetZonesAtPointRequest,
+    ) -> Result<tonic::Response<super::GetZonesAtPointResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetWaypointsResponse`](super::GetWaypointsResponse)
+    /// Takes an [`GetWaypointsRequest`](super::GetWaypointsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     // cluster nearby waypoints for a map at zoom level 10
+    ///     let request = gis::GetWaypointsRequest {
+    ///         page: None,
+    ///         zoom: Some(10),
+    ///     };
+    ///     let response = client.get_waypoints(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_waypoints(
+        &self,
+        request: super::GetWaypointsRequest,
+    ) -> Result<tonic::Response<super::GetWaypointsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`SyncAirspaceResponse`](super::SyncAirspaceResponse)
+    /// with the zone and waypoint changes after a cursor.
+    /// Takes an [`SyncAirspaceRequest`](super::SyncAirspaceRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let mut cursor = 0;
+    ///     loop {
+    ///         let request = gis::SyncAirspaceRequest {
+    ///             cursor,
+    ///             limit: None,
+    ///             encoding: gis::GeometryEncoding::Compact as i32,
+    ///         };
+    ///
+    ///         let response = client.sync_airspace(request).await?.into_inner();
+    ///         println!("CHANGES={:?}", response.changes);
+    ///         cursor = response.cursor;
+    ///         if !response.has_more {
+    ///             break;
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn sync_airspace(
+        &self,
+        request: super::SyncAirspaceRequest,
+    ) -> Result<tonic::Response<super::SyncAirspaceResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetVertiportsResponse`](super::GetVertiportsResponse)
+    /// Takes an [`GetVertiportsRequest`](super::GetVertiportsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::GetVertiportsRequest {
+    ///         page: None,
+    ///         encoding: gis::GeometryEncoding::Compact as i32,
+    ///     };
+    ///     let response = client.get_vertiports(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_vertiports(
+        &self,
+        request: super::GetVertiportsRequest,
+    ) -> Result<tonic::Response<super::GetVertiportsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`ServiceInfoResponse`](super::ServiceInfoResponse)
+    /// with the version, features and limits of the server.
+    /// Takes an [`ServiceInfoRequest`](super::ServiceInfoRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let response = client.get_service_info(gis::ServiceInfoRequest {}).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_service_info(
+        &self,
+        request: super::ServiceInfoRequest,
+    ) -> Result<tonic::Response<super::ServiceInfoResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetStatisticsResponse`](super::GetStatisticsResponse)
+    /// with the active zones by type, the flights in progress, the aircraft
+    /// seen recently, the waypoint count and the best path latency.
+    /// Takes a [`GetStatisticsRequest`](super::GetStatisticsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::GetStatisticsRequest {
+    ///         aircraft_window_minutes: Some(10),
+    ///     };
+    ///     let response = client.get_statistics(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_statistics(
+        &self,
+        request: super::GetStatisticsRequest,
+    ) -> Result<tonic::Response<super::GetStatisticsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`CheckReachabilityResponse`](super::CheckReachabilityResponse)
+    /// telling if an aircraft can still reach a vertiport from its last
+    /// reported position, and how confidently, without searching a path.
+    /// Takes a [`CheckReachabilityRequest`](super::CheckReachabilityRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::CheckReachabilityRequest {
+    ///         aircraft_identifier: "Marauder".to_string(),
+    ///         target_identifier: "vertiport-1".to_string(),
+    ///         remaining_range_meters: None,
+    ///     };
+    ///     let response = client.check_reachability(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn check_reachability(
+        &self,
+        request: super::CheckReachabilityRequest,
+    ) -> Result<tonic::Response<super::CheckReachabilityResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`GetSnapshotResponse`](super::GetSnapshotResponse)
+    /// with the zones, filed flights and aircraft states as they were at a
+    /// past time, or the same snapshot encoded as a single bundle.
+    /// Takes a [`GetSnapshotRequest`](super::GetSnapshotRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Duration, Utc};
+    /// use svc_gis_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = GisQueryClient::new_client(&host, port, "gis");
+    ///     let request = gis::GetSnapshotRequest {
+    ///         time: Some((Utc::now() - Duration::try_hours(1).unwrap()).into()),
+    ///         bundle: false,
+    ///     };
+    ///     let response = client.get_snapshot(request).await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_snapshot(
+        &self,
+        request: super::GetSnapshotRequest,
+    ) -> Result<tonic::Response<super::GetSnapshotResponse>, tonic::Status>;
 }
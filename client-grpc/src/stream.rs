@@ -0,0 +1,317 @@
+//! Helpers for consuming server-streaming RPCs
+//!
+//! [`resumable`] wraps a streaming call into a [`Stream`] that survives
+//!  dropped connections. When the stream fails with a transient error the
+//!  call is reopened after an exponential backoff, passing the resume token
+//!  of the last item received so that the server continues where it left
+//!  off. Other errors are yielded once and end the stream.
+//!
+//! ```
+//! use futures_util::{stream, StreamExt};
+//! use svc_gis_client_grpc::stream::{resumable, Backoff, Resumable};
+//!
+//! #[derive(Debug)]
+//! struct Event {
+//!     sequence: u32,
+//! }
+//!
+//! impl Resumable for Event {
+//!     fn resume_token(&self) -> Option<String> {
+//!         Some(self.sequence.to_string())
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! // With a generated client, open the call with something like:
+//! //  client.subscribe_flights(request(token)).await.map(Response::into_inner)
+//! let events = resumable(
+//!     |token: Option<String>| async move {
+//!         let first = token.map_or(0, |token| token.parse::<u32>().unwrap() + 1);
+//!         Ok(stream::iter((first..3).map(|sequence| Ok(Event { sequence }))))
+//!     },
+//!     Backoff::default(),
+//! );
+//!
+//! let events: Vec<_> = events.collect().await;
+//! assert_eq!(events.len(), 3);
+//! # }
+//! ```
+
+use futures_util::{Stream, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+use tonic::{Code, Status};
+
+super::log_macros!("grpc", "app::client::stream");
+
+/// An item of a stream that can be resumed after it
+pub trait Resumable {
+    /// Token passed to the server to resume the stream after this item,
+    ///  `None` to keep the token of the previous item
+    fn resume_token(&self) -> Option<String>;
+}
+
+/// Delays between attempts to reopen a stream
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Backoff {
+    /// Delay before the first attempt
+    pub initial: Duration,
+
+    /// Longest delay between two attempts
+    pub max: Duration,
+
+    /// Attempts without receiving an item before giving up,
+    ///  `None` to retry forever
+    pub max_retries: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+            max_retries: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before an attempt, doubling with each failed attempt
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial
+            .checked_mul(2u32.saturating_pow(attempt))
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+}
+
+/// Whether a stream failing with this status may succeed if reopened
+pub fn is_transient(status: &Status) -> bool {
+    matches!(
+        status.code(),
+        Code::Unavailable
+            | Code::Unknown
+            | Code::Aborted
+            | Code::DeadlineExceeded
+            | Code::ResourceExhausted
+    )
+}
+
+/// State of a resumable stream between items
+struct State<F, S> {
+    open: F,
+    backoff: Backoff,
+    token: Option<String>,
+    inner: Option<S>,
+    attempt: u32,
+    done: bool,
+}
+
+impl<F, S> State<F, S> {
+    /// Waits before reopening the stream, `false` if it shouldn't be
+    async fn retry(&mut self, status: &Status) -> bool {
+        let exhausted = self
+            .backoff
+            .max_retries
+            .is_some_and(|max_retries| self.attempt >= max_retries);
+
+        if !is_transient(status) || exhausted {
+            grpc_error!("(resumable) stream failed: {}", status);
+            return false;
+        }
+
+        let delay = self.backoff.delay(self.attempt);
+        grpc_warn!(
+            "(resumable) stream interrupted ({}), reopening in {:?}.",
+            status,
+            delay
+        );
+
+        self.attempt += 1;
+        tokio::time::sleep(delay).await;
+        true
+    }
+}
+
+/// Wraps a streaming call into a stream that reopens the call when it is
+///  interrupted
+///
+/// `open` is called with the resume token of the last item received, or
+///  `None` for the first call. The stream ends when the server ends it, or
+///  after yielding an error that can't be retried.
+pub fn resumable<T, F, Fut, S>(open: F, backoff: Backoff) -> impl Stream<Item = Result<T, Status>>
+where
+    T: Resumable,
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<S, Status>>,
+    S: Stream<Item = Result<T, Status>> + Unpin,
+{
+    let state = State {
+        open,
+        backoff,
+        token: None,
+        inner: None,
+        attempt: 0,
+        done: false,
+    };
+
+    futures_util::stream::unfold(state, |mut state| async move {
+        loop {
+            if state.done {
+                return None;
+            }
+
+            let Some(mut inner) = state.inner.take() else {
+                match (state.open)(state.token.clone()).await {
+                    Ok(inner) => state.inner = Some(inner),
+                    Err(status) => {
+                        if !state.retry(&status).await {
+                            state.done = true;
+                            return Some((Err(status), state));
+                        }
+                    }
+                }
+
+                continue;
+            };
+
+            match inner.next().await {
+                Some(Ok(item)) => {
+                    state.attempt = 0;
+                    if let Some(token) = item.resume_token() {
+                        state.token = Some(token);
+                    }
+
+                    state.inner = Some(inner);
+                    return Some((Ok(item), state));
+                }
+                Some(Err(status)) => {
+                    if !state.retry(&status).await {
+                        state.done = true;
+                        return Some((Err(status), state));
+                    }
+                }
+                None => return None,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Event {
+        sequence: u32,
+    }
+
+    impl Resumable for Event {
+        fn resume_token(&self) -> Option<String> {
+            Some(self.sequence.to_string())
+        }
+    }
+
+    fn no_delay() -> Backoff {
+        Backoff {
+            initial: Duration::ZERO,
+            max: Duration::ZERO,
+            max_retries: Some(3),
+        }
+    }
+
+    #[test]
+    fn ut_backoff_delay() {
+        let backoff = Backoff::default();
+        assert_eq!(backoff.delay(0), Duration::from_millis(500));
+        assert_eq!(backoff.delay(2), Duration::from_secs(2));
+        assert_eq!(backoff.delay(10), Duration::from_secs(30));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn ut_is_transient() {
+        assert!(is_transient(&Status::unavailable("connection reset")));
+        assert!(!is_transient(&Status::invalid_argument("bad request")));
+        assert!(!is_transient(&Status::permission_denied("denied")));
+    }
+
+    #[tokio::test]
+    async fn ut_resumable_reconnects() {
+        let mut tokens = vec![];
+        let events = resumable(
+            |token: Option<String>| {
+                tokens.push(token.clone());
+                async move {
+                    let items = match token.as_deref() {
+                        None => vec![
+                            Ok(Event { sequence: 0 }),
+                            Ok(Event { sequence: 1 }),
+                            Err(Status::unavailable("connection reset")),
+                        ],
+                        Some("1") => vec![Ok(Event { sequence: 2 })],
+                        Some(_) => vec![Err(Status::internal("unexpected token"))],
+                    };
+
+                    Ok(stream::iter(items))
+                }
+            },
+            no_delay(),
+        );
+
+        let events: Vec<_> = events.collect().await;
+        let sequences: Vec<u32> = events
+            .into_iter()
+            .map(|event| event.unwrap().sequence)
+            .collect();
+
+        assert_eq!(sequences, vec![0, 1, 2]);
+        assert_eq!(tokens, vec![None, Some("1".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn ut_resumable_permanent_error() {
+        let mut calls = 0;
+        let events = resumable(
+            |_token: Option<String>| {
+                calls += 1;
+                async move {
+                    Err::<stream::Iter<std::vec::IntoIter<Result<Event, Status>>>, _>(
+                        Status::permission_denied("denied"),
+                    )
+                }
+            },
+            no_delay(),
+        );
+
+        let events: Vec<_> = events.collect().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].as_ref().unwrap_err().code(),
+            Code::PermissionDenied
+        );
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn ut_resumable_retries_exhausted() {
+        let mut calls = 0;
+        let events = resumable(
+            |_token: Option<String>| {
+                calls += 1;
+                async move {
+                    Err::<stream::Iter<std::vec::IntoIter<Result<Event, Status>>>, _>(
+                        Status::unavailable("down"),
+                    )
+                }
+            },
+            no_delay(),
+        );
+
+        let events: Vec<_> = events.collect().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].as_ref().unwrap_err().code(), Code::Unavailable);
+        assert_eq!(calls, 4);
+    }
+}
@@ -13,7 +13,7 @@ const AIRCRAFT_1_ID: &str = "00000000-0000-0000-0000-000000000002";
 #[tokio::test]
 async fn test_add_aircraft() -> Result<(), ()> {
     let (server_host, server_port) = get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
-    let _client = GisClient::new_client(&server_host, server_port, "compliance");
+    let _client = GisIngestClient::new_client(&server_host, server_port, "compliance");
 
     let _sample: Vec<(&str, f64, f64, f32)> = vec![
         (AIRCRAFT_1_ID, 52.3746, 4.9160036, 100.0),
@@ -86,7 +86,7 @@ async fn test_add_aircraft() -> Result<(), ()> {
 #[tokio::test]
 async fn test_add_vertiport() -> Result<(), Box<dyn std::error::Error>> {
     let (server_host, server_port) = get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
-    let client = GisClient::new_client(&server_host, server_port, "compliance");
+    let client = GisIngestClient::new_client(&server_host, server_port, "compliance");
 
     let vertiports = vec![
         Vertiport {
@@ -107,6 +107,10 @@ async fn test_add_vertiport() -> Result<(), Box<dyn std::error::Error>> {
             })
             .collect(),
             timestamp_network: Some(Utc::now().into()),
+            status: None,
+            layers: None,
+            compact_vertices: None,
+            operating_hours: vec![],
         },
         Vertiport {
             identifier: VERTIPORT_2_ID.to_string(),
@@ -127,6 +131,10 @@ async fn test_add_vertiport() -> Result<(), Box<dyn std::error::Error>> {
             })
             .collect(),
             timestamp_network: Some(Utc::now().into()),
+            status: None,
+            layers: None,
+            compact_vertices: None,
+            operating_hours: vec![],
         },
         Vertiport {
             identifier: VERTIPORT_3_ID.to_string(),
@@ -146,11 +154,18 @@ async fn test_add_vertiport() -> Result<(), Box<dyn std::error::Error>> {
             })
             .collect(),
             timestamp_network: Some(Utc::now().into()),
+            status: None,
+            layers: None,
+            compact_vertices: None,
+            operating_hours: vec![],
         },
     ];
 
     let response = client
-        .update_vertiports(UpdateVertiportsRequest { vertiports })
+        .update_vertiports(UpdateVertiportsRequest {
+            vertiports,
+            validate_only: false,
+        })
         .await?;
 
     println!("Response: {:?}", response);
@@ -161,7 +176,7 @@ async fn test_add_vertiport() -> Result<(), Box<dyn std::error::Error>> {
 #[tokio::test]
 async fn test_add_waypoints() -> Result<(), Box<dyn std::error::Error>> {
     let (server_host, server_port) = get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
-    let client = GisClient::new_client(&server_host, server_port, "compliance");
+    let client = GisIngestClient::new_client(&server_host, server_port, "compliance");
 
     let nodes = vec![
         ("ORANGE", 52.3745905, 4.9160036),
@@ -193,7 +208,7 @@ async fn test_add_waypoints() -> Result<(), Box<dyn std::error::Error>> {
 #[tokio::test]
 async fn test_is_ready() -> Result<(), Box<dyn std::error::Error>> {
     let (server_host, server_port) = get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
-    let client = GisClient::new_client(&server_host, server_port, "compliance");
+    let client = GisQueryClient::new_client(&server_host, server_port, "compliance");
     let response = client.is_ready(ReadyRequest {}).await?;
     //println!("RESPONSE={:?}", response.into_inner());
     assert_eq!(response.into_inner().ready, true);
@@ -0,0 +1,457 @@
+use lib_common::time::{DateTime, Duration, Utc};
+
+/// Altitude of the fixture vertiports, in meters
+pub const PORT_ALTITUDE_METERS: f32 = 10.0;
+
+/// Identifier of the vertiport south of the blocking zone
+pub const BLOCKED_PORT_A_ID: &str = "Kamino";
+
+/// Identifier of the vertiport north of the blocking zone
+pub const BLOCKED_PORT_B_ID: &str = "Bespin";
+
+/// Identifier of the zone between the blocked vertiports
+pub const BLOCKING_ZONE_ID: &str = "NL-NFZ-01";
+
+/// Identifier of the vertiport inside of the restriction
+pub const ENCLOSED_PORT_ID: &str = "FX-PORT-ENCLOSED";
+
+/// Identifier of the vertiport outside of the restriction
+pub const OUTSIDE_PORT_ID: &str = "FX-PORT-OUTSIDE";
+
+/// Identifier of the restriction around the enclosed vertiport
+pub const ENCLOSING_ZONE_ID: &str = "FX-NFZ-ENCLOSING";
+
+/// Identifier of the vertiport west of the unrelated vertiport
+pub const WEST_PORT_ID: &str = "FX-PORT-WEST";
+
+/// Identifier of the vertiport on the direct route between the others
+pub const MIDWAY_PORT_ID: &str = "FX-PORT-MIDWAY";
+
+/// Identifier of the vertiport east of the unrelated vertiport
+pub const EAST_PORT_ID: &str = "FX-PORT-EAST";
+
+/// Identifier of the first overlapping zone, active first
+pub const OVERLAP_ZONE_A_ID: &str = "FX-TFR-A";
+
+/// Identifier of the second overlapping zone, active last
+pub const OVERLAP_ZONE_B_ID: &str = "FX-TFR-B";
+
+/// Identifier of the flight converging from the west
+pub const CONVERGING_FLIGHT_A_ID: &str = "FX-FLIGHT-A";
+
+/// Identifier of the flight converging from the south
+pub const CONVERGING_FLIGHT_B_ID: &str = "FX-FLIGHT-B";
+
+/// Altitude of the converging flights, in meters
+pub const CONVERGING_ALTITUDE_METERS: f32 = 120.0;
+
+/// Assets describing one airspace situation, inserted through the
+///  ingest service
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    /// Vertiports of the scenario
+    pub vertiports: Vec<Vertiport>,
+
+    /// Waypoints of the scenario
+    pub waypoints: Vec<Waypoint>,
+
+    /// Zones of the scenario
+    pub zones: Vec<Zone>,
+
+    /// Flights filed in the scenario
+    pub flights: Vec<UpdateFlightPathRequest>,
+}
+
+impl Scenario {
+    /// Request inserting the vertiports
+    pub fn vertiports_request(&self) -> UpdateVertiportsRequest {
+        UpdateVertiportsRequest {
+            vertiports: self.vertiports.clone(),
+            validate_only: false,
+        }
+    }
+
+    /// Request inserting the waypoints
+    pub fn waypoints_request(&self) -> UpdateWaypointsRequest {
+        UpdateWaypointsRequest {
+            waypoints: self.waypoints.clone(),
+        }
+    }
+
+    /// Request inserting the zones
+    pub fn zones_request(&self) -> UpdateZonesRequest {
+        UpdateZonesRequest {
+            zones: self.zones.clone(),
+            validate_only: false,
+        }
+    }
+
+    /// Gets a vertiport of the scenario
+    pub fn vertiport(&self, identifier: &str) -> Option<&Vertiport> {
+        self.vertiports.iter().find(|v| v.identifier == identifier)
+    }
+}
+
+/// Vertices from (latitude, longitude) pairs
+pub fn ring(points: &[(f64, f64)]) -> Vec<Coordinates> {
+    points
+        .iter()
+        .map(|(latitude, longitude)| Coordinates {
+            latitude: *latitude,
+            longitude: *longitude,
+        })
+        .collect()
+}
+
+/// Rectangle between two corners, as (longitude, latitude)
+pub fn rectangle(min: (f64, f64), max: (f64, f64)) -> Vec<Coordinates> {
+    ring(&[
+        (max.1, min.0),
+        (min.1, min.0),
+        (min.1, max.0),
+        (max.1, max.0),
+        (max.1, min.0),
+    ])
+}
+
+/// Centroid of a vertiport, at the provided altitude
+pub fn centroid(vertiport: &Vertiport, altitude_meters: f32) -> PointZ {
+    let corners = &vertiport.vertices[..vertiport.vertices.len() - 1];
+    let count = corners.len() as f64;
+
+    PointZ {
+        latitude: corners.iter().map(|c| c.latitude).sum::<f64>() / count,
+        longitude: corners.iter().map(|c| c.longitude).sum::<f64>() / count,
+        altitude_meters,
+    }
+}
+
+/// Request for the best path between two vertiports
+pub fn best_path_request(
+    origin_identifier: &str,
+    target_identifier: &str,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> BestPathRequest {
+    BestPathRequest {
+        origin_identifier: origin_identifier.to_string(),
+        target_identifier: target_identifier.to_string(),
+        origin_type: NodeType::Vertiport as i32,
+        target_type: NodeType::Vertiport as i32,
+        time_start: Some(time_start.into()),
+        time_end: Some(time_end.into()),
+        limit: 1,
+        priority: FlightPriority::Routine as i32,
+        ..Default::default()
+    }
+}
+
+/// Vertiport at [`PORT_ALTITUDE_METERS`]
+fn vertiport(identifier: &str, label: &str, vertices: Vec<Coordinates>) -> Vertiport {
+    Vertiport {
+        identifier: identifier.to_string(),
+        vertices,
+        altitude_meters: PORT_ALTITUDE_METERS,
+        label: Some(label.to_string()),
+        timestamp_network: Some(Utc::now().into()),
+        ..Default::default()
+    }
+}
+
+/// Restriction between two altitudes and an optional time window
+fn restriction(
+    identifier: &str,
+    vertices: Vec<Coordinates>,
+    altitudes: (f32, f32),
+    window: Option<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Zone {
+    Zone {
+        identifier: identifier.to_string(),
+        zone_type: ZoneType::Restriction as i32,
+        vertices,
+        altitude_meters_min: altitudes.0,
+        altitude_meters_max: altitudes.1,
+        time_start: window.map(|(start, _)| start.into()),
+        time_end: window.map(|(_, end)| end.into()),
+        ..Default::default()
+    }
+}
+
+/// Flight filed by a rotorcraft along a path
+fn flight(
+    flight_identifier: &str,
+    path: Vec<PointZ>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> UpdateFlightPathRequest {
+    UpdateFlightPathRequest {
+        flight_identifier: Some(flight_identifier.to_string()),
+        aircraft_identifier: Some(format!("{flight_identifier}-CRAFT")),
+        path,
+        timestamp_start: Some(time_start.into()),
+        timestamp_end: Some(time_end.into()),
+        simulated: false,
+        aircraft_type: AircraftType::Rotorcraft as i32,
+        priority: FlightPriority::Routine as i32,
+    }
+}
+
+/// Two vertiports in Amsterdam with a temporary restriction across the
+///  direct route, active for two hours from `time_start`. The waypoints
+///  around the restriction leave a detour.
+pub fn blocking_zone(time_start: DateTime<Utc>) -> Scenario {
+    let waypoint = |identifier: &str, latitude, longitude| Waypoint {
+        identifier: identifier.to_string(),
+        location: Some(Coordinates {
+            latitude,
+            longitude,
+        }),
+    };
+
+    Scenario {
+        vertiports: vec![
+            vertiport(
+                BLOCKED_PORT_A_ID,
+                "VertiportA",
+                ring(&[
+                    (52.3746368, 4.9163718),
+                    (52.3747387, 4.9162102),
+                    (52.3748374, 4.9163691),
+                    (52.3747375, 4.9165381),
+                    (52.3746368, 4.9163718),
+                ]),
+            ),
+            vertiport(
+                BLOCKED_PORT_B_ID,
+                "VertiportB",
+                ring(&[
+                    (52.3751407, 4.916294),
+                    (52.3752201, 4.9162611),
+                    (52.3752627, 4.9163657),
+                    (52.3752107, 4.9164683),
+                    (52.3751436, 4.9164355),
+                    (52.3751407, 4.916294),
+                ]),
+            ),
+        ],
+        waypoints: vec![
+            waypoint("ORANGE", 52.3745905, 4.9160036),
+            waypoint("STRAWBERRY", 52.3749819, 4.9156925),
+            waypoint("BANANA", 52.3752144, 4.9153733),
+            waypoint("LEMON", 52.3753012, 4.9156845),
+            waypoint("RASPBERRY", 52.3750703, 4.9161538),
+        ],
+        zones: vec![restriction(
+            BLOCKING_ZONE_ID,
+            ring(&[
+                (52.3751734, 4.9158481),
+                (52.3750752, 4.9157998),
+                (52.3749409, 4.9164569),
+                (52.3751047, 4.9164999),
+                (52.3751734, 4.9158481),
+            ]),
+            (0.0, 1000.0),
+            Some((time_start, time_start + Duration::try_hours(2).unwrap())),
+        )],
+        flights: vec![],
+    }
+}
+
+/// A vertiport in Haarlem enclosed by a permanent restriction, and another
+///  vertiport outside of it. No route reaches the enclosed vertiport.
+pub fn port_in_restriction() -> Scenario {
+    Scenario {
+        vertiports: vec![
+            vertiport(
+                ENCLOSED_PORT_ID,
+                "Enclosed",
+                rectangle((4.6368, 52.3808), (4.6372, 52.3812)),
+            ),
+            vertiport(
+                OUTSIDE_PORT_ID,
+                "Outside",
+                rectangle((4.6498, 52.3808), (4.6502, 52.3812)),
+            ),
+        ],
+        waypoints: vec![],
+        zones: vec![restriction(
+            ENCLOSING_ZONE_ID,
+            rectangle((4.6320, 52.3780), (4.6420, 52.3840)),
+            (0.0, 500.0),
+            None,
+        )],
+        flights: vec![],
+    }
+}
+
+/// Three vertiports in Leiden along the same parallel, about 680 meters
+///  apart, and a waypoint north of the midway one. The port zone of the
+///  midway vertiport lies on the direct route between the outer ones.
+pub fn unrelated_port() -> Scenario {
+    let port = |identifier: &str, label: &str, longitude: f64| {
+        vertiport(
+            identifier,
+            label,
+            rectangle((longitude - 0.0002, 52.1598), (longitude + 0.0002, 52.1602)),
+        )
+    };
+
+    Scenario {
+        vertiports: vec![
+            port(WEST_PORT_ID, "West", 4.4800),
+            port(MIDWAY_PORT_ID, "Midway", 4.4900),
+            port(EAST_PORT_ID, "East", 4.5000),
+        ],
+        waypoints: vec![Waypoint {
+            identifier: "FX-WP-NORTH".to_string(),
+            location: Some(Coordinates {
+                latitude: 52.1650,
+                longitude: 4.4900,
+            }),
+        }],
+        zones: vec![],
+        flights: vec![],
+    }
+}
+
+/// Two temporary restrictions in Utrecht, overlapping between
+///  (5.120, 52.090) and (5.125, 52.095), from 100 to 300 meters, during
+///  the second hour after `time_start`
+pub fn overlapping_zones(time_start: DateTime<Utc>) -> Scenario {
+    let hours = |h| time_start + Duration::try_hours(h).unwrap();
+
+    Scenario {
+        vertiports: vec![],
+        waypoints: vec![],
+        zones: vec![
+            restriction(
+                OVERLAP_ZONE_A_ID,
+                rectangle((5.115, 52.085), (5.125, 52.095)),
+                (0.0, 300.0),
+                Some((hours(0), hours(2))),
+            ),
+            restriction(
+                OVERLAP_ZONE_B_ID,
+                rectangle((5.120, 52.090), (5.130, 52.100)),
+                (100.0, 400.0),
+                Some((hours(1), hours(3))),
+            ),
+        ],
+        flights: vec![],
+    }
+}
+
+/// Two flights in Rotterdam, from the west and from the south, reaching
+///  (4.4792, 51.9225) at [`CONVERGING_ALTITUDE_METERS`] during the same ten
+///  minutes after `time_start`
+pub fn converging_flights(time_start: DateTime<Utc>) -> Scenario {
+    let time_end = time_start + Duration::try_minutes(10).unwrap();
+    let point = |longitude, latitude| PointZ {
+        latitude,
+        longitude,
+        altitude_meters: CONVERGING_ALTITUDE_METERS,
+    };
+
+    Scenario {
+        vertiports: vec![],
+        waypoints: vec![],
+        zones: vec![],
+        flights: vec![
+            flight(
+                CONVERGING_FLIGHT_A_ID,
+                vec![point(4.4600, 51.9225), point(4.4792, 51.9225)],
+                time_start,
+                time_end,
+            ),
+            flight(
+                CONVERGING_FLIGHT_B_ID,
+                vec![point(4.4792, 51.9050), point(4.4792, 51.9225)],
+                time_start,
+                time_end,
+            ),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod fixture_tests {
+    use super::*;
+
+    /// If a point is inside the bounding box of a ring
+    fn within_bounds(vertices: &[Coordinates], latitude: f64, longitude: f64) -> bool {
+        let min = |f: fn(&Coordinates) -> f64| vertices.iter().map(f).fold(f64::MAX, f64::min);
+        let max = |f: fn(&Coordinates) -> f64| vertices.iter().map(f).fold(f64::MIN, f64::max);
+
+        (min(|c| c.latitude)..=max(|c| c.latitude)).contains(&latitude)
+            && (min(|c| c.longitude)..=max(|c| c.longitude)).contains(&longitude)
+    }
+
+    #[test]
+    fn ut_rings_closed() {
+        let now = Utc::now();
+        let scenarios = [
+            blocking_zone(now),
+            port_in_restriction(),
+            overlapping_zones(now),
+            converging_flights(now),
+        ];
+
+        for scenario in scenarios {
+            let rings = scenario
+                .vertiports
+                .iter()
+                .map(|v| &v.vertices)
+                .chain(scenario.zones.iter().map(|z| &z.vertices));
+
+            for ring in rings {
+                assert!(ring.len() >= 4);
+                assert_eq!(ring.first(), ring.last());
+            }
+        }
+    }
+
+    #[test]
+    fn ut_port_in_restriction() {
+        let scenario = port_in_restriction();
+        let zone = &scenario.zones[0].vertices;
+        let enclosed = centroid(scenario.vertiport(ENCLOSED_PORT_ID).unwrap(), 0.0);
+        let outside = centroid(scenario.vertiport(OUTSIDE_PORT_ID).unwrap(), 0.0);
+
+        assert!(within_bounds(zone, enclosed.latitude, enclosed.longitude));
+        assert!(!within_bounds(zone, outside.latitude, outside.longitude));
+    }
+
+    #[test]
+    fn ut_overlapping_zones() {
+        let now = Utc::now();
+        let scenario = overlapping_zones(now);
+        let (a, b) = (&scenario.zones[0], &scenario.zones[1]);
+
+        assert!(within_bounds(&a.vertices, 52.0925, 5.1225));
+        assert!(within_bounds(&b.vertices, 52.0925, 5.1225));
+        assert!(a.altitude_meters_min < b.altitude_meters_max);
+        assert!(b.altitude_meters_min < a.altitude_meters_max);
+        let b_start: DateTime<Utc> = b.time_start.clone().unwrap().into();
+        let a_end: DateTime<Utc> = a.time_end.clone().unwrap().into();
+        assert!(b_start < a_end);
+    }
+
+    #[test]
+    fn ut_converging_flights() {
+        let scenario = converging_flights(Utc::now());
+        let (a, b) = (&scenario.flights[0], &scenario.flights[1]);
+
+        assert_eq!(a.path.last(), b.path.last());
+        assert_ne!(a.path.first(), b.path.first());
+        assert_eq!(a.timestamp_start, b.timestamp_start);
+    }
+
+    #[test]
+    fn ut_best_path_request() {
+        let now = Utc::now();
+        let request = best_path_request(BLOCKED_PORT_A_ID, BLOCKED_PORT_B_ID, now, now);
+        assert_eq!(request.origin_identifier, BLOCKED_PORT_A_ID);
+        assert_eq!(request.origin_type, NodeType::Vertiport as i32);
+        assert_eq!(request.limit, 1);
+    }
+}
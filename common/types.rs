@@ -10,6 +10,110 @@ pub const REDIS_KEY_AIRCRAFT_POSITION: &str = "gis:aircraft:position";
 /// The key for the Redis queue containing aircraft velocity information
 pub const REDIS_KEY_AIRCRAFT_VELOCITY: &str = "gis:aircraft:velocity";
 
+/// The key for the Redis queue containing zone updates (e.g. from svc-compliance)
+pub const REDIS_KEY_ZONE: &str = "gis:zone";
+
+/// The key folder for the Redis cache of recent aircraft positions
+pub const REDIS_KEY_AIRCRAFT_TRACK: &str = "gis:aircraft:track";
+
+/// The default key for the Redis stream of merged aircraft state
+pub const REDIS_KEY_AIRCRAFT_STATE: &str = "gis:aircraft:state";
+
+/// The default key for the Redis stream and pub/sub channel of airspace updates
+pub const REDIS_KEY_AIRSPACE: &str = "gis:airspace";
+
+/// Version of the gRPC interface, as `MAJOR.MINOR`
+///  The major version is raised with changes that existing clients can't
+///  handle, the minor version with additions they can ignore.
+pub const INTERFACE_VERSION: &str = "1.0";
+
+/// Defines a wrapper around a float carrying a unit
+///  Values of different units can't be added or assigned to each other,
+///  conversions from and to raw floats are explicit.
+macro_rules! unit_type {
+    ($(#[$meta:meta])* $name:ident, $symbol:literal) => {
+        $(#[$meta])*
+        #[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+        #[serde(transparent)]
+        pub struct $name(pub f64);
+
+        impl $name {
+            /// The value as a single precision float, for `FLOAT(4)` columns
+            ///  and `float` gRPC fields
+            pub fn as_f32(self) -> f32 {
+                self.0 as f32
+            }
+        }
+
+        impl From<f64> for $name {
+            fn from(value: f64) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<f32> for $name {
+            fn from(value: f32) -> Self {
+                $name(value as f64)
+            }
+        }
+
+        impl std::ops::Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                $name(self.0 + other.0)
+            }
+        }
+
+        impl std::ops::Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                $name(self.0 - other.0)
+            }
+        }
+
+        impl std::ops::Mul<f64> for $name {
+            type Output = Self;
+
+            fn mul(self, factor: f64) -> Self {
+                $name(self.0 * factor)
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{} {}", self.0, $symbol)
+            }
+        }
+    };
+}
+
+unit_type!(
+    /// A distance or altitude in meters
+    Meters,
+    "m"
+);
+
+unit_type!(
+    /// A speed in meters per second
+    MetersPerSecond,
+    "m/s"
+);
+
+unit_type!(
+    /// An angle, latitude or longitude in degrees
+    Degrees,
+    "deg"
+);
+
+impl Degrees {
+    /// The angle in radians
+    pub fn to_radians(self) -> f64 {
+        self.0.to_radians()
+    }
+}
+
 /// Aircraft Type
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 #[derive(strum::EnumString)]
@@ -102,14 +206,14 @@ pub enum OperationalStatus {
 /// 3D Point with Altitude
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub struct Position {
-    /// Longitude in degrees
-    pub longitude: f64,
+    /// Longitude
+    pub longitude: Degrees,
 
-    /// Latitude in degrees
-    pub latitude: f64,
+    /// Latitude
+    pub latitude: Degrees,
 
-    /// Altitude in meters
-    pub altitude_meters: f64,
+    /// Altitude
+    pub altitude_meters: Meters,
 }
 
 /// Generic Location Information for an Aircraft
@@ -146,7 +250,11 @@ pub struct AircraftId {
     pub timestamp_network: DateTime<Utc>,
 
     /// The timestamp reported by the asset
-    pub timestamp_asset: Option<DateTime<Utc>>
+    pub timestamp_asset: Option<DateTime<Utc>>,
+
+    /// If true, the session is ended and released from the aircraft
+    #[serde(default)]
+    pub session_ended: bool
 }
 
 /// Generic Velocity Information for an Aircraft
@@ -159,16 +267,16 @@ pub struct AircraftVelocity {
     /// The velocity of the aircraft relative to ground in meters per second
     ///  If the aircraft has a headwind of 100 kph and is not moving
     ///  with respect to ground, its ground speed is 0 but its airspeed is 100 kph.
-    pub velocity_horizontal_ground_mps: f32,
+    pub velocity_horizontal_ground_mps: MetersPerSecond,
 
     /// The velocity of the aircraft relative to the air in meters per second
-    pub velocity_horizontal_air_mps: Option<f32>,
+    pub velocity_horizontal_air_mps: Option<MetersPerSecond>,
 
     /// The vertical velocity of the aircraft in meters per second
-    pub velocity_vertical_mps: f32,
+    pub velocity_vertical_mps: MetersPerSecond,
 
     /// The angle of the velocity vector with respect to true north in degrees
-    pub track_angle_degrees: f32,
+    pub track_angle_degrees: Degrees,
 
     /// The network timestamp of the velocity
     pub timestamp_network: DateTime<Utc>,
@@ -178,3 +286,166 @@ pub struct AircraftVelocity {
 
     // TODO(R5): velocity uncertainty
 }
+
+/// Merged state of an aircraft, published after each update
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AircraftState {
+    /// The unique identifier for the aircraft
+    pub identifier: String,
+
+    /// The flight ID of this aircraft
+    pub session_id: Option<String>,
+
+    /// The type of aircraft
+    pub aircraft_type: AircraftType,
+
+    /// The operational status of the aircraft
+    pub op_status: OperationalStatus,
+
+    /// If this is a simulated aircraft
+    pub simulated: bool,
+
+    /// The last known 3D position of the aircraft
+    pub position: Option<Position>,
+
+    /// The velocity of the aircraft relative to ground in meters per second
+    pub velocity_horizontal_ground_mps: Option<MetersPerSecond>,
+
+    /// The vertical velocity of the aircraft in meters per second
+    pub velocity_vertical_mps: Option<MetersPerSecond>,
+
+    /// The angle of the velocity vector with respect to true north in degrees
+    pub track_angle_degrees: Option<Degrees>,
+
+    /// The time of the last identification update
+    pub last_identifier_update: Option<DateTime<Utc>>,
+
+    /// The time of the last position update
+    pub last_position_update: Option<DateTime<Utc>>,
+
+    /// The time of the last velocity update
+    pub last_velocity_update: Option<DateTime<Utc>>,
+}
+
+/// A vertex of a zone boundary
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct ZoneVertex {
+    /// Latitude in degrees
+    pub latitude: f64,
+
+    /// Longitude in degrees
+    pub longitude: f64,
+}
+
+/// A weekly recurring window during which a zone is active
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ZoneWindow {
+    /// Days of the week on which this window starts (0 is Monday)
+    pub days: Vec<i32>,
+
+    /// Start of the window in minutes after midnight UTC
+    pub start_minute: u32,
+
+    /// End of the window in minutes after midnight UTC
+    pub end_minute: u32,
+}
+
+/// A zone to add, replace or remove, pushed to the zone queue
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ZoneUpdate {
+    /// Unique identifier (NOTAM id, etc.)
+    pub identifier: String,
+
+    /// If true, the zone is removed and the other fields are ignored
+    #[serde(default)]
+    pub deleted: bool,
+
+    /// The type of zone, as the gRPC `ZoneType` value
+    #[serde(default)]
+    pub zone_type: i32,
+
+    /// Vertices bounding the zone
+    /// The first vertex should match the end vertex (closed shape)
+    #[serde(default)]
+    pub vertices: Vec<ZoneVertex>,
+
+    /// Additional disjoint areas covered by the zone, besides `vertices`
+    #[serde(default)]
+    pub parts: Vec<Vec<ZoneVertex>>,
+
+    /// Minimum altitude of the zone
+    #[serde(default)]
+    pub altitude_meters_min: f32,
+
+    /// Maximum altitude of the zone
+    #[serde(default)]
+    pub altitude_meters_max: f32,
+
+    /// Start time of the zone, if applicable
+    #[serde(default)]
+    pub time_start: Option<DateTime<Utc>>,
+
+    /// End time of the zone, if applicable
+    #[serde(default)]
+    pub time_end: Option<DateTime<Utc>>,
+
+    /// Weekly windows during which the zone is active
+    /// If empty, the zone is active for its whole lifetime
+    #[serde(default)]
+    pub schedule: Vec<ZoneWindow>,
+
+    /// Standoff distance of waypoints placed around the zone
+    #[serde(default)]
+    pub waypoint_buffer_meters: Option<f32>,
+
+    /// Free text description of the zone
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Authority that issued the zone
+    #[serde(default)]
+    pub authority: Option<String>,
+
+    /// Contact details of the authority
+    #[serde(default)]
+    pub contact: Option<String>,
+
+    /// Link to the source publication
+    #[serde(default)]
+    pub source_url: Option<String>,
+
+    /// The network timestamp of the update
+    ///  The latest update of a zone wins if several are queued
+    pub timestamp_network: DateTime<Utc>,
+}
+
+/// The kind of change announced by a [`ZoneEvent`]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneEventKind {
+    /// The zone becomes active at its start time
+    Activation,
+
+    /// The zone stops being active at its end time
+    Expiry,
+}
+
+/// Advance notice of a zone activating or expiring, published to the
+///  airspace stream
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ZoneEvent {
+    /// Unique identifier of the zone
+    pub identifier: String,
+
+    /// The type of zone, as the gRPC `ZoneType` value
+    pub zone_type: i32,
+
+    /// Whether the zone activates or expires
+    pub kind: ZoneEventKind,
+
+    /// When the zone activates or expires
+    pub time: DateTime<Utc>,
+
+    /// When the notice was published
+    pub timestamp_network: DateTime<Utc>,
+}
@@ -0,0 +1,96 @@
+-- Compares the flight path intersection query with the path transformed per
+--  row (expression index) against the stored ECEF path (n-d index).
+--
+-- Run against the postgis container from docker-compose:
+--  psql -h localhost -U svc_gis -d gis -f scripts/bench-flight-intersection.sql
+--
+-- Everything is created in a scratch schema that is dropped at the end.
+
+\set flights 100000
+\set margin 100.0
+
+DROP SCHEMA IF EXISTS "bench_flights" CASCADE;
+CREATE SCHEMA "bench_flights";
+SET search_path TO "bench_flights", "$user", arrow, postgis, topology, public;
+
+CREATE TABLE "flights" (
+    "flight_identifier" VARCHAR(255) PRIMARY KEY,
+    "geom" GEOMETRY(LINESTRINGZ, 4326),
+    "time_start" TIMESTAMPTZ,
+    "time_end" TIMESTAMPTZ,
+    "simulated" BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+-- Short paths scattered around a 1 x 1 degree area, over one day
+INSERT INTO "flights" ("flight_identifier", "geom", "time_start", "time_end")
+SELECT
+    'FLIGHT-' || n,
+    ST_SetSRID(ST_MakeLine(
+        ST_MakePoint(lon, lat, 100.0),
+        ST_MakePoint(lon + 0.02, lat + 0.02, 150.0)
+    ), 4326),
+    start,
+    start + INTERVAL '30 minutes'
+FROM (
+    SELECT
+        n,
+        4.0 + random() AS lon,
+        52.0 + random() AS lat,
+        NOW() + (random() * INTERVAL '1 day') AS start
+    FROM generate_series(1, :flights) AS n
+) AS "samples";
+
+-- The candidate path checked against every stored flight
+CREATE TABLE "candidate" AS
+SELECT
+    ST_SetSRID(ST_MakeLine(
+        ST_MakePoint(4.5, 52.5, 120.0),
+        ST_MakePoint(4.6, 52.6, 120.0)
+    ), 4326) AS "geom",
+    NOW() AS "time_start",
+    NOW() + INTERVAL '1 day' AS "time_end";
+
+-- Before: expression index, path transformed for every row
+CREATE INDEX "flights_geom_idx" ON "flights" USING GIST (ST_Transform("geom", 4978));
+ANALYZE "flights";
+
+EXPLAIN (ANALYZE, BUFFERS)
+SELECT
+    "flight_identifier",
+    ST_3DLength(ST_Transform("flights"."geom", 4978)) AS "distance",
+    "distance_to_path"
+FROM "flights", "candidate",
+    ST_3DDistance(
+        ST_Transform("flights"."geom", 4978),
+        ST_Transform("candidate"."geom", 4978)
+    ) AS "distance_to_path"
+WHERE
+    ("distance_to_path" < :margin OR "distance_to_path" IS NULL)
+    AND ("flights"."time_start" <= "candidate"."time_end" OR "flights"."time_start" IS NULL)
+    AND ("flights"."time_end" >= "candidate"."time_start" OR "flights"."time_end" IS NULL)
+    AND "simulated" = FALSE;
+
+-- After: stored ECEF path and length with an n-d index
+DROP INDEX "flights_geom_idx";
+ALTER TABLE "flights"
+    ADD COLUMN "geom_ecef" GEOMETRY(LINESTRINGZ, 4978)
+        GENERATED ALWAYS AS (ST_Transform("geom", 4978)) STORED,
+    ADD COLUMN "length_meters" FLOAT8
+        GENERATED ALWAYS AS (ST_3DLength(ST_Transform("geom", 4978))) STORED;
+CREATE INDEX "flights_geom_ecef_idx" ON "flights" USING GIST ("geom_ecef" gist_geometry_ops_nd);
+ANALYZE "flights";
+
+EXPLAIN (ANALYZE, BUFFERS)
+SELECT
+    "flight_identifier",
+    "length_meters" AS "distance",
+    ST_3DDistance("geom_ecef", "path") AS "distance_to_path"
+FROM "flights", "candidate",
+    ST_Transform("candidate"."geom", 4978) AS "path"
+WHERE
+    ("flights"."time_start" <= "candidate"."time_end" OR "flights"."time_start" IS NULL)
+    AND ("flights"."time_end" >= "candidate"."time_start" OR "flights"."time_end" IS NULL)
+    AND "simulated" = FALSE
+    AND ("geom_ecef" IS NULL OR ST_3DDWithin("geom_ecef", "path", :margin));
+
+DROP SCHEMA "bench_flights" CASCADE;
@@ -4,6 +4,13 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let proto_dir = "../proto";
     let proto_file = &format!("{}/grpc.proto", proto_dir);
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+
+    // The client crate ships the proto and its descriptors, so that
+    //  clients in other languages can generate their bindings from it
+    let client_proto_dir = "../client-grpc/proto";
+    std::fs::create_dir_all(client_proto_dir)?;
+    std::fs::copy(proto_file, format!("{}/grpc.proto", client_proto_dir))?;
 
     let server_config = tonic_build::configure()
         .extern_path(
@@ -11,15 +18,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "::lib_common::time::Timestamp",
         )
         .type_attribute("ReadyRequest", "#[derive(Eq, Copy)]")
-        .type_attribute("ReadyResponse", "#[derive(Eq, Copy)]")
-        .type_attribute("UpdateResponse", "#[derive(Eq, Copy)]")
-        .type_attribute("CheckIntersectionResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("ReadyResponse", "#[derive(Eq)]")
+        .type_attribute("UpdateResponse", "#[derive(Eq)]")
+        .type_attribute("ItemValidation", "#[derive(Eq)]")
+        .type_attribute("FieldViolation", "#[derive(Eq)]")
+        .type_attribute("ImportWaypointsSummary", "#[derive(Eq, Copy)]")
+        .type_attribute("CheckIntersectionResponse", "#[derive(Eq)]")
+        .type_attribute("ReplanAdvisory", "#[derive(Eq)]")
         .type_attribute(
             "PointZ",
             "#[derive(Copy, ::serde::Serialize, ::serde::Deserialize)]",
         )
         .type_attribute("PathSegment", "#[derive(Copy)]")
-        .type_attribute("Coordinates", "#[derive(Copy)]");
+        .type_attribute("PathFailure", "#[derive(Eq)]")
+        .type_attribute("PathBlocker", "#[derive(Eq)]")
+        .type_attribute("CostWeights", "#[derive(Copy)]")
+        .type_attribute("PathCosts", "#[derive(Copy)]")
+        .type_attribute("Coordinates", "#[derive(Copy)]")
+        .type_attribute("WaypointCandidate", "#[derive(Copy)]")
+        .type_attribute("Page", "#[derive(Copy)]")
+        .type_attribute("GetZonesRequest", "#[derive(Copy)]")
+        .type_attribute("GetWaypointsRequest", "#[derive(Copy)]")
+        .type_attribute("GetVertiportsRequest", "#[derive(Copy)]")
+        .type_attribute("SyncAirspaceRequest", "#[derive(Copy)]")
+        .type_attribute("ServiceInfoRequest", "#[derive(Eq, Copy)]")
+        .type_attribute("GetStatisticsRequest", "#[derive(Eq, Copy)]")
+        .type_attribute("ZoneTypeCount", "#[derive(Eq, Copy)]")
+        .type_attribute("ReleaseReservationRequest", "#[derive(Eq)]");
 
     let client_config = server_config.clone();
 
@@ -31,6 +56,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "crate::prelude::OperationalStatus",
         )
         .build_server(false)
+        .file_descriptor_set_path(format!("{}/grpc_descriptor.bin", client_proto_dir))
         .out_dir("../client-grpc/src/")
         .compile(&[proto_file], &[proto_dir])?;
 
@@ -39,6 +65,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .type_attribute("NodeType", "#[derive(::num_derive::FromPrimitive)]")
         .type_attribute("NodeType", "#[derive(::strum::EnumString)]")
         .type_attribute("NodeType", "#[derive(::strum::Display)]")
+        .type_attribute("FlightPriority", "#[derive(::num_derive::FromPrimitive)]")
         .type_attribute("ZoneType", "#[derive(::strum::EnumString)]")
         .type_attribute("ZoneType", "#[derive(::strum::Display)]")
         .type_attribute("ZoneType", "#[derive(::strum::EnumIter)]")
@@ -46,7 +73,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .type_attribute("ZoneType", "#[derive(::postgres_types::ToSql)]")
         .type_attribute("ZoneType", "#[derive(::num_derive::FromPrimitive)]")
         .type_attribute("ZoneType", r#"#[postgres(name = "zonetype")]"#)
+        .type_attribute("VertiportStatus", "#[derive(::strum::Display)]")
+        .type_attribute("VertiportStatus", "#[derive(::strum::EnumIter)]")
+        .type_attribute("VertiportStatus", "#[derive(::postgres_types::FromSql)]")
+        .type_attribute("VertiportStatus", "#[derive(::postgres_types::ToSql)]")
+        .type_attribute("VertiportStatus", "#[derive(::num_derive::FromPrimitive)]")
+        .type_attribute(
+            "VertiportStatus",
+            r#"#[postgres(name = "vertiportstatus")]"#,
+        )
         .build_client(false)
+        .file_descriptor_set_path(out_dir.join("grpc_descriptor.bin"))
         .compile(&[proto_file], &[proto_dir])?;
 
     println!("cargo:rerun-if-changed={}", proto_file);
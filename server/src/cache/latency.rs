@@ -0,0 +1,294 @@
+//! Time items spend in the Redis queues before they are consumed.
+//!
+//! Each consumed item's latency is the difference between its
+//!  `timestamp_network` and the time it was taken from its queue. The
+//!  latencies are counted in a histogram per queue since startup. The
+//!  95th percentile of each reporting period is checked against a
+//!  configurable threshold, over which a warning is logged.
+
+use crate::types::{AircraftId, AircraftPosition, AircraftVelocity, ZoneUpdate};
+use lib_common::time::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Upper bounds of the latency buckets, in milliseconds
+///  A last bucket counts the latencies above the highest bound.
+pub const LATENCY_BUCKETS_MS: [u64; 9] = [10, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+/// Default p95 latency over which a warning is logged, in milliseconds
+pub const DEFAULT_WARN_MS: u64 = 1_000;
+
+/// Length of the periods over which the p95 latency is checked, in seconds
+const REPORT_INTERVAL_SECONDS: i64 = 60;
+
+/// p95 latency over which a warning is logged, zero to disable
+static WARN_MS: AtomicU64 = AtomicU64::new(DEFAULT_WARN_MS);
+
+/// Latencies of each queue, by queue key
+static QUEUES: Lazy<Mutex<BTreeMap<String, QueueLatency>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Items carrying the time they were received from the network
+pub trait Timestamped {
+    /// The network timestamp of the item
+    fn timestamp_network(&self) -> DateTime<Utc>;
+}
+
+impl Timestamped for AircraftId {
+    fn timestamp_network(&self) -> DateTime<Utc> {
+        self.timestamp_network
+    }
+}
+
+impl Timestamped for AircraftPosition {
+    fn timestamp_network(&self) -> DateTime<Utc> {
+        self.timestamp_network
+    }
+}
+
+impl Timestamped for AircraftVelocity {
+    fn timestamp_network(&self) -> DateTime<Utc> {
+        self.timestamp_network
+    }
+}
+
+impl Timestamped for ZoneUpdate {
+    fn timestamp_network(&self) -> DateTime<Utc> {
+        self.timestamp_network
+    }
+}
+
+/// Counts of latencies per bucket of [`LATENCY_BUCKETS_MS`]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    /// Number of latencies in each bucket, the last one above all bounds
+    pub counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+
+    /// Highest latency counted, in milliseconds
+    pub max_ms: u64,
+}
+
+impl Histogram {
+    /// Counts a latency
+    pub fn record(&mut self, latency_ms: u64) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        self.counts[bucket] += 1;
+        self.max_ms = self.max_ms.max(latency_ms);
+    }
+
+    /// Number of latencies counted
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Upper bound of the bucket holding the given percentile, in
+    ///  milliseconds, or the highest latency if above all bounds.
+    ///  `None` if no latency was counted.
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+
+        let rank = ((percentile / 100.0) * count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, n) in self.counts.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Some(
+                    LATENCY_BUCKETS_MS
+                        .get(bucket)
+                        .copied()
+                        .unwrap_or(self.max_ms)
+                        .min(self.max_ms),
+                );
+            }
+        }
+
+        Some(self.max_ms)
+    }
+}
+
+/// Latencies of a queue since startup and in the current reporting period
+#[derive(Debug)]
+struct QueueLatency {
+    /// Latencies since startup
+    total: Histogram,
+
+    /// Latencies since the start of the reporting period
+    period: Histogram,
+
+    /// Start of the reporting period
+    period_start: DateTime<Utc>,
+}
+
+/// Sets the p95 latency over which a warning is logged, zero to disable
+pub fn set_warn_ms(warn_ms: u64) {
+    WARN_MS.store(warn_ms, Ordering::Relaxed);
+}
+
+/// Latency of an item taken from its queue at the given time, in
+///  milliseconds. Items timestamped in the future count as no latency.
+fn latency_ms(timestamp_network: DateTime<Utc>, now: DateTime<Utc>) -> u64 {
+    (now - timestamp_network).num_milliseconds().max(0) as u64
+}
+
+/// Counts the latencies of items taken from a queue at the given time.
+///
+/// Once per reporting period, logs a warning if the p95 latency of the
+///  period exceeds the configured threshold.
+pub fn record<T: Timestamped>(queue: &str, items: &[T], now: DateTime<Utc>) {
+    if items.is_empty() {
+        return;
+    }
+
+    let Ok(mut queues) = QUEUES.lock() else {
+        cache_error!("could not lock queue latencies.");
+        return;
+    };
+
+    let latency = queues
+        .entry(queue.to_string())
+        .or_insert_with(|| QueueLatency {
+            total: Histogram::default(),
+            period: Histogram::default(),
+            period_start: now,
+        });
+
+    for item in items {
+        let ms = latency_ms(item.timestamp_network(), now);
+        latency.total.record(ms);
+        latency.period.record(ms);
+    }
+
+    if (now - latency.period_start).num_seconds() < REPORT_INTERVAL_SECONDS {
+        return;
+    }
+
+    let warn_ms = WARN_MS.load(Ordering::Relaxed);
+    if let Some(p95) = latency.period.percentile(95.0) {
+        cache_debug!("queue '{queue}' p95 latency: {p95} ms.");
+        if warn_ms > 0 && p95 > warn_ms {
+            cache_warn!(
+                "queue '{queue}' p95 latency of {p95} ms over the last {} items exceeds {warn_ms} ms.",
+                latency.period.count()
+            );
+        }
+    }
+
+    latency.period = Histogram::default();
+    latency.period_start = now;
+}
+
+/// Gets the latency histograms of each queue since startup, by queue key
+pub fn histograms() -> Vec<(String, Histogram)> {
+    let Ok(queues) = QUEUES.lock() else {
+        cache_error!("could not lock queue latencies.");
+        return vec![];
+    };
+
+    queues
+        .iter()
+        .map(|(queue, latency)| (queue.clone(), latency.total))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    fn item(timestamp_network: DateTime<Utc>) -> AircraftId {
+        AircraftId {
+            identifier: Some("TEST-1".to_string()),
+            session_id: None,
+            aircraft_type: crate::types::AircraftType::Undeclared,
+            timestamp_network,
+            timestamp_asset: None,
+            session_ended: false,
+        }
+    }
+
+    #[test]
+    fn ut_histogram_record() {
+        let mut histogram = Histogram::default();
+        assert_eq!(histogram.count(), 0);
+        assert_eq!(histogram.percentile(95.0), None);
+
+        histogram.record(0);
+        histogram.record(10);
+        histogram.record(11);
+        histogram.record(20_000);
+        assert_eq!(histogram.counts[0], 2);
+        assert_eq!(histogram.counts[1], 1);
+        assert_eq!(histogram.counts[LATENCY_BUCKETS_MS.len()], 1);
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(histogram.max_ms, 20_000);
+    }
+
+    #[test]
+    fn ut_histogram_percentile() {
+        let mut histogram = Histogram::default();
+        for _ in 0..95 {
+            histogram.record(30);
+        }
+
+        // the bound never exceeds the highest latency
+        assert_eq!(histogram.percentile(95.0), Some(30));
+
+        for _ in 0..5 {
+            histogram.record(3_000);
+        }
+        assert_eq!(histogram.percentile(50.0), Some(50));
+        assert_eq!(histogram.percentile(95.0), Some(50));
+        assert_eq!(histogram.percentile(96.0), Some(3_000));
+        assert_eq!(histogram.percentile(100.0), Some(3_000));
+
+        histogram.record(60_000);
+        assert_eq!(histogram.percentile(100.0), Some(60_000));
+    }
+
+    #[test]
+    fn ut_latency_ms() {
+        let now = Utc::now();
+        assert_eq!(
+            latency_ms(now - Duration::try_seconds(2).unwrap(), now),
+            2_000
+        );
+        assert_eq!(latency_ms(now + Duration::try_seconds(2).unwrap(), now), 0);
+    }
+
+    #[test]
+    fn ut_record() {
+        let queue = "test:latency:record";
+        let now = Utc::now();
+        record::<AircraftId>(queue, &[], now);
+        assert!(histograms().iter().all(|(q, _)| q != queue));
+
+        let items = [
+            item(now - Duration::try_milliseconds(5).unwrap()),
+            item(now - Duration::try_milliseconds(400).unwrap()),
+        ];
+        record(queue, &items, now);
+
+        // a new reporting period starts after the interval
+        let later = now + Duration::try_seconds(REPORT_INTERVAL_SECONDS).unwrap();
+        record(queue, &items[..1], later);
+
+        let (_, histogram) = histograms().into_iter().find(|(q, _)| q == queue).unwrap();
+        assert_eq!(histogram.count(), 3);
+        assert_eq!(histogram.counts[0], 1);
+        assert_eq!(histogram.max_ms, 60_005);
+
+        let queues = QUEUES.lock().unwrap();
+        let latency = queues.get(queue).unwrap();
+        assert_eq!(latency.period.count(), 0);
+        assert_eq!(latency.period_start, later);
+    }
+}
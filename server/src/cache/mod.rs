@@ -3,15 +3,23 @@
 
 #[macro_use]
 pub mod macros;
+pub mod latency;
 pub mod pool;
+pub mod replay;
 
-use pool::RedisPool;
-use serde::Deserialize;
+use latency::Timestamped;
+use lib_common::time::Utc;
+use once_cell::sync::OnceCell;
+use pool::{CacheError, Connection, RedisPool};
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use tonic::async_trait;
 
 use tokio::time::{interval, Duration};
 
+/// Global pool for the recent aircraft track cache
+pub static TRACK_CACHE: OnceCell<RedisPool> = OnceCell::new();
+
 /// A consumer of Redis Queue data.
 #[derive(Debug)]
 pub struct Consumer {
@@ -20,6 +28,9 @@ pub struct Consumer {
 
     /// The time to sleep between consuming data
     pub sleep_ms: u64,
+
+    /// The Redis stream to publish processed results to, if any
+    pub publisher: Option<RedisPool>,
 }
 
 impl Consumer {
@@ -34,7 +45,25 @@ impl Consumer {
             .map_err(|_| {
                 cache_error!("could not get Redis pool for folder '{key_folder}'.");
             })
-            .map(|pool| Self { pool, sleep_ms })
+            .map(|pool| Self {
+                pool,
+                sleep_ms,
+                publisher: None,
+            })
+    }
+
+    /// Publish processed results to the provided Redis stream
+    pub async fn with_publisher(
+        mut self,
+        config: &crate::config::Config,
+        stream_key: &str,
+    ) -> Result<Self, ()> {
+        let publisher = RedisPool::new(config, stream_key).await.map_err(|_| {
+            cache_error!("could not get Redis pool for stream '{stream_key}'.");
+        })?;
+
+        self.publisher = Some(publisher);
+        Ok(self)
     }
 }
 
@@ -45,11 +74,23 @@ pub trait Processor<T> {
     async fn process(&mut self, items: Vec<T>) -> Result<(), ()>;
 }
 
+/// Has a method to "process" items, reporting those that failed on their own
+#[async_trait]
+pub trait ItemProcessor<T> {
+    /// Process the items from the Redis queue. Returns the items that could
+    ///  not be processed because of their content, or an error if none
+    ///  could be processed and the whole batch should be retried.
+    async fn process_items(&mut self, items: Vec<T>) -> Result<Vec<T>, ()>;
+}
+
+/// Times an item is processed before it is moved to the dead-letter list
+pub const MAX_ATTEMPTS: u32 = 5;
+
 /// A consumer of Redis Queue data.
 #[async_trait]
 pub trait IsConsumer<T>: Processor<T>
 where
-    T: for<'a> Deserialize<'a> + Clone + Debug + Send,
+    T: for<'a> Deserialize<'a> + Clone + Debug + Send + Timestamped,
 {
     /// The Redis pool to use for consuming data
     fn pool(&self) -> RedisPool;
@@ -62,28 +103,117 @@ where
     // no_coverage: (Rnever) need running redis instance, not unit testable
     async fn begin(&mut self) -> Result<(), ()> {
         let mut redis_pool: RedisPool = self.pool();
-        let mut connection = redis_pool.pool.get().await.map_err(|e| {
-            cache_error!("could not get connection from Redis pool: {e}");
+        let mut connection = redis_pool.connection().await.map_err(|_| ())?;
+        let mut interval = interval(Duration::from_millis(self.sleep_ms()));
+
+        loop {
+            match redis_pool.pop(&mut connection).await {
+                Ok(result) => {
+                    latency::record(&redis_pool.key_folder(), &result, Utc::now());
+                    let _ = self.process(result).await;
+                }
+                Err(e) => {
+                    cache_error!(
+                        "(AircraftConsumer::begin) could not get aircraft from Redis: {e}"
+                    );
+
+                    // reconnect, to the new master after a failover
+                    if let Ok(fresh) = redis_pool.connection().await {
+                        connection = fresh;
+                    }
+                }
+            }
+
+            interval.tick().await;
+        }
+    }
+}
+
+/// A consumer of Redis Queue data that must not lose items.
+///
+/// Items are held in a processing list until processed, and returned to the
+///  queue if processing fails or the consumer is interrupted. Items can be
+///  processed more than once, so processing must be idempotent.
+///
+/// Items that fail on their own go to the back of the queue, and to the
+///  dead-letter list once they failed [`MAX_ATTEMPTS`] times.
+#[async_trait]
+pub trait IsReliableConsumer<T>: IsConsumer<T> + ItemProcessor<T>
+where
+    T: for<'a> Deserialize<'a> + Serialize + Clone + Debug + Send + Sync + Timestamped,
+{
+    /// Starts a loop to consume data from the Redis queue
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) need running redis instance, not unit testable
+    async fn begin_reliable(&mut self) -> Result<(), ()> {
+        let mut redis_pool: RedisPool = self.pool();
+
+        // Items left over from an interrupted run go back to the queue
+        let mut connection = recover(&mut redis_pool).await.map_err(|e| {
+            cache_error!("could not requeue unprocessed items: {e}");
         })?;
 
         let mut interval = interval(Duration::from_millis(self.sleep_ms()));
 
         loop {
-            let result = redis_pool.pop(&mut connection).await.map_err(|e| {
-                cache_error!("(AircraftConsumer::begin) could not get aircraft from Redis: {e}");
-            })?;
+            let settled = match redis_pool.pop_reliable(&mut connection).await {
+                Ok(result) => {
+                    latency::record(&redis_pool.key_folder(), &result, Utc::now());
+
+                    match self.process_items(result.clone()).await {
+                        Ok(rejected) => {
+                            redis_pool
+                                .settle(&mut connection, &result, &rejected, MAX_ATTEMPTS)
+                                .await
+                        }
+                        Err(_) => redis_pool.requeue(&mut connection).await.map(|_| ()),
+                    }
+                }
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = settled {
+                cache_error!("could not consume items from Redis: {e}");
+
+                // reconnect, to the new master after a failover, and return
+                //  the items that were not acknowledged to the queue
+                match recover(&mut redis_pool).await {
+                    Ok(fresh) => connection = fresh,
+                    Err(e) => cache_error!("could not requeue unprocessed items: {e}"),
+                }
+            }
 
-            let _ = self.process(result).await;
             interval.tick().await;
         }
     }
 }
 
+/// Gets a new connection and returns the items of the processing list to
+///  the queue
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running redis instance, not unit testable
+async fn recover(redis_pool: &mut RedisPool) -> Result<Connection, CacheError> {
+    let mut connection = redis_pool.connection().await?;
+    let count = redis_pool.requeue(&mut connection).await?;
+    if count > 0 {
+        cache_warn!("requeued {count} unprocessed items.");
+    }
+
+    Ok(connection)
+}
+
+impl<T> IsReliableConsumer<T> for Consumer
+where
+    Consumer: Processor<T> + ItemProcessor<T>,
+    T: for<'a> Deserialize<'a> + Serialize + Clone + Debug + Send + Sync + Timestamped,
+{
+}
+
 /// Implement the `IsConsumer` trait for `Consumer`
 impl<T> IsConsumer<T> for Consumer
 where
     Consumer: Processor<T>,
-    T: for<'a> Deserialize<'a> + Clone + Debug + Send,
+    T: for<'a> Deserialize<'a> + Clone + Debug + Send + Timestamped,
 {
     fn pool(&self) -> RedisPool {
         self.pool.clone()
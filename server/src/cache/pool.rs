@@ -1,9 +1,15 @@
 //! Redis connection pool implementation
 
+use crate::types::AircraftPosition;
 use deadpool_redis::{redis, Pool, Runtime};
-use serde::Deserialize;
+use lib_common::time::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 /// Represents a pool of connections to a Redis server.
 ///
@@ -12,12 +18,80 @@ use std::num::NonZeroUsize;
 /// connection management, such as connection pooling and reusing connections.
 #[derive(Clone)]
 pub struct RedisPool {
-    /// The underlying pool of Redis connections.
-    pub pool: Pool,
+    /// The underlying pool of Redis connections, shared by clones.
+    backend: Arc<RwLock<Backend>>,
+    /// How to find the current master, when behind Redis Sentinel.
+    sentinel: Option<Arc<Sentinel>>,
+    /// Set when an error suggests the master moved or went away.
+    stale: Arc<AtomicBool>,
     /// The string prepended to the key being stored.
     key_folder: String,
 }
 
+/// The kind of Redis deployment behind a [`RedisPool`]
+#[derive(Clone)]
+enum Backend {
+    /// A single server, or the master resolved through Redis Sentinel
+    Standalone(Pool),
+
+    /// A Redis Cluster, discovered from its seed nodes
+    Cluster(deadpool_redis::cluster::Pool),
+}
+
+/// A connection taken from a [`RedisPool`]
+pub enum Connection {
+    /// A connection to a single server
+    Standalone(deadpool_redis::Connection),
+
+    /// A connection to a Redis Cluster
+    Cluster(deadpool_redis::cluster::Connection),
+}
+
+impl redis::aio::ConnectionLike for Connection {
+    fn req_packed_command<'a>(
+        &'a mut self,
+        cmd: &'a redis::Cmd,
+    ) -> redis::RedisFuture<'a, redis::Value> {
+        match self {
+            Connection::Standalone(connection) => connection.req_packed_command(cmd),
+            Connection::Cluster(connection) => connection.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> redis::RedisFuture<'a, Vec<redis::Value>> {
+        match self {
+            Connection::Standalone(connection) => {
+                connection.req_packed_commands(cmd, offset, count)
+            }
+            Connection::Cluster(connection) => connection.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Connection::Standalone(connection) => connection.get_db(),
+            Connection::Cluster(connection) => connection.get_db(),
+        }
+    }
+}
+
+/// How to look up the current master through Redis Sentinel
+struct Sentinel {
+    /// Credentials, database and pool settings of the master
+    base: deadpool_redis::Config,
+
+    /// URLs of the Sentinel nodes
+    nodes: Vec<String>,
+
+    /// Name of the monitored master
+    master_name: String,
+}
+
 impl Debug for RedisPool {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("RedisPool")
@@ -26,6 +100,24 @@ impl Debug for RedisPool {
     }
 }
 
+/// Approximate max number of entries kept in a Redis stream
+const MAX_STREAM_LENGTH: usize = 10_000;
+
+/// Max number of items taken from a queue at once
+const POP_COUNT: usize = 20;
+
+/// The field name of the serialized item in a Redis stream entry
+const STREAM_FIELD: &str = "data";
+
+/// Default name of the master monitored by Redis Sentinel
+pub const DEFAULT_SENTINEL_MASTER: &str = "mymaster";
+
+/// How long aircraft positions are kept in the track cache
+pub const TRACK_RETENTION_SECONDS: i64 = 60;
+
+/// The span of time covered by a single track cache bucket
+const TRACK_BUCKET_SECONDS: i64 = 10;
+
 /// Represents errors that can occur during cache operations.
 #[derive(Debug, Clone, Copy)]
 pub enum CacheError {
@@ -49,6 +141,133 @@ impl Display for CacheError {
     }
 }
 
+/// Splits a comma-separated list of Redis Sentinel or Cluster URLs
+fn node_urls(nodes: &str) -> Vec<String> {
+    nodes
+        .split(',')
+        .map(str::trim)
+        .filter(|node| !node.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Builds the configuration of a pool connected to the master at the
+///  provided address, keeping the credentials, database and pool settings
+///  of the base configuration
+fn master_config(
+    base: &deadpool_redis::Config,
+    host: String,
+    port: u16,
+) -> Result<deadpool_redis::Config, CacheError> {
+    let redis = match &base.url {
+        Some(url) => {
+            redis::IntoConnectionInfo::into_connection_info(url.as_str())
+                .map_err(|e| {
+                    cache_error!("invalid Redis URL: {}", e);
+                    CacheError::CouldNotConfigure
+                })?
+                .redis
+        }
+        None => redis::RedisConnectionInfo::default(),
+    };
+
+    Ok(deadpool_redis::Config {
+        url: None,
+        connection: Some(deadpool_redis::ConnectionInfo {
+            addr: deadpool_redis::ConnectionAddr::Tcp(host, port),
+            redis: redis.into(),
+        }),
+        ..base.clone()
+    })
+}
+
+/// Asks a Redis Sentinel node for the address of the current master
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs redis sentinel backend to integration test
+async fn query_sentinel(node: &str, master_name: &str) -> Result<(String, u16), CacheError> {
+    let client = redis::Client::open(node).map_err(|e| {
+        cache_error!("invalid sentinel URL {node}: {}", e);
+        CacheError::CouldNotConfigure
+    })?;
+
+    let mut connection = client.get_async_connection().await.map_err(|e| {
+        cache_warn!("could not connect to sentinel {node}: {}", e);
+        CacheError::CouldNotConnect
+    })?;
+
+    redis::cmd("SENTINEL")
+        .arg("get-master-addr-by-name")
+        .arg(master_name)
+        .query_async::<_, Option<(String, u16)>>(&mut connection)
+        .await
+        .map_err(|e| {
+            cache_warn!(
+                "sentinel {node} could not get master '{master_name}': {}",
+                e
+            );
+            CacheError::OperationFailed
+        })?
+        .ok_or_else(|| {
+            cache_warn!("sentinel {node} does not monitor master '{master_name}'.");
+            CacheError::OperationFailed
+        })
+}
+
+/// Resolves the current master through the first Redis Sentinel node
+///  that knows it
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs redis sentinel backend to integration test
+async fn sentinel_config(
+    base: &deadpool_redis::Config,
+    nodes: &[String],
+    master_name: &str,
+) -> Result<deadpool_redis::Config, ()> {
+    for node in nodes {
+        let Ok((host, port)) = query_sentinel(node, master_name).await else {
+            continue;
+        };
+
+        cache_info!("sentinel {node} reports master '{master_name}' at {host}:{port}.");
+        return master_config(base, host, port).map_err(|_| ());
+    }
+
+    cache_error!("no sentinel could resolve master '{master_name}'.");
+    Err(())
+}
+
+/// Whether a failed operation suggests the master moved or went away, so
+///  that connecting again may reach the new one
+fn needs_reconnect(error: &redis::RedisError) -> bool {
+    error.is_io_error()
+        || error.is_connection_dropped()
+        || error.is_connection_refusal()
+        || error.is_timeout()
+        || matches!(
+            error.kind(),
+            redis::ErrorKind::ReadOnly | redis::ErrorKind::MasterDown
+        )
+}
+
+/// Creates a pool to a Redis Cluster from its seed nodes
+fn cluster_pool(base: &deadpool_redis::Config, nodes: Vec<String>) -> Result<Backend, ()> {
+    let mut cfg = deadpool_redis::cluster::Config::from_urls(nodes);
+    cfg.pool = base.pool;
+    cfg.create_pool(Some(Runtime::Tokio1))
+        .map(Backend::Cluster)
+        .map_err(|e| {
+            cache_error!("could not create cluster pool: {}", e);
+        })
+}
+
+/// Creates a pool to a single Redis server
+fn standalone_pool(cfg: &deadpool_redis::Config) -> Result<Backend, ()> {
+    cfg.create_pool(Some(Runtime::Tokio1))
+        .map(Backend::Standalone)
+        .map_err(|e| {
+            cache_error!("could not create pool: {}", e);
+        })
+}
+
 impl RedisPool {
     /// Create a new RedisPool
     /// The 'key_folder' argument is prepended to the key being stored. The
@@ -56,33 +275,147 @@ impl RedisPool {
     ///  This is used to differentiate keys inserted into Redis by different
     ///  microservices. For example, an ADS-B key in svc-telemetry might be
     ///  formatted `telemetry:adsb:1234567890`.
+    ///
+    /// When Redis Sentinel nodes are configured, the current master is
+    ///  looked up through them and `REDIS__URL` only provides the
+    ///  credentials and database. After a connection failure or a write
+    ///  refused by a demoted master, the master is looked up again and the
+    ///  pool rebuilt on the next connection.
+    ///
+    /// When Redis Cluster seed nodes are configured instead, the cluster
+    ///  topology is discovered from them.
     pub async fn new(config: &crate::config::Config, key_folder: &str) -> Result<Self, ()> {
-        // the .env file must have REDIS__URL="redis://\<host\>:\<port\>"
-        let cfg: deadpool_redis::Config = config.redis.clone();
-        let details = cfg.url.clone().ok_or_else(|| {
-            cache_error!("no connection address found.");
-        })?;
+        let cluster_nodes = node_urls(&config.redis_cluster_nodes);
+        let sentinel_nodes = node_urls(&config.redis_sentinel_nodes);
 
-        cache_info!(
-            "creating pool with key folder '{}' at {:?}...",
-            key_folder,
-            details
-        );
+        let (backend, sentinel) = match (cluster_nodes.is_empty(), sentinel_nodes.is_empty()) {
+            (false, false) => {
+                cache_error!("Redis Cluster and Sentinel nodes can't both be configured.");
+                return Err(());
+            }
+            (false, true) => {
+                cache_info!(
+                    "creating cluster pool with key folder '{}' from {:?}...",
+                    key_folder,
+                    cluster_nodes
+                );
 
-        cfg.create_pool(Some(Runtime::Tokio1))
-            .map_err(|e| {
-                cache_error!("could not create pool: {}", e);
-            })
-            .map(|pool| {
-                cache_info!("pool created.");
-                Self {
-                    pool,
-                    key_folder: String::from(key_folder),
-                }
-            })
+                (cluster_pool(&config.redis, cluster_nodes)?, None)
+            }
+            (true, sentinel_empty) => {
+                let sentinel = (!sentinel_empty).then(|| Sentinel {
+                    base: config.redis.clone(),
+                    nodes: sentinel_nodes,
+                    master_name: config.redis_sentinel_master.clone(),
+                });
+
+                let cfg = match &sentinel {
+                    // the .env file must have REDIS__URL="redis://\<host\>:\<port\>"
+                    None => config.redis.clone(),
+                    Some(sentinel) => {
+                        sentinel_config(&sentinel.base, &sentinel.nodes, &sentinel.master_name)
+                            .await?
+                    }
+                };
+
+                let details = match (&cfg.url, &cfg.connection) {
+                    (Some(url), _) => url.clone(),
+                    (None, Some(connection)) => format!("{:?}", connection.addr),
+                    (None, None) => {
+                        cache_error!("no connection address found.");
+                        return Err(());
+                    }
+                };
+
+                cache_info!(
+                    "creating pool with key folder '{}' at {:?}...",
+                    key_folder,
+                    details
+                );
+
+                (standalone_pool(&cfg)?, sentinel.map(Arc::new))
+            }
+        };
+
+        cache_info!("pool created.");
+        Ok(Self {
+            backend: Arc::new(RwLock::new(backend)),
+            sentinel,
+            stale: Arc::new(AtomicBool::new(false)),
+            key_folder: String::from(key_folder),
+        })
+    }
+
+    /// Gets a connection from the pool, first rebuilding it against the
+    ///  current master if an earlier failure suggested it moved
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn connection(&self) -> Result<Connection, CacheError> {
+        if self.stale.swap(false, Ordering::Relaxed) {
+            self.reconnect().await;
+        }
+
+        let backend = self.backend.read().await.clone();
+        let connection = match backend {
+            Backend::Standalone(pool) => pool
+                .get()
+                .await
+                .map(Connection::Standalone)
+                .map_err(|e| e.to_string()),
+            Backend::Cluster(pool) => pool
+                .get()
+                .await
+                .map(Connection::Cluster)
+                .map_err(|e| e.to_string()),
+        };
+
+        connection.map_err(|e| {
+            cache_error!("could not get connection from Redis pool: {e}");
+            self.stale.store(true, Ordering::Relaxed);
+            CacheError::CouldNotConnect
+        })
     }
 
-    fn key_folder(&self) -> String {
+    /// Looks up the current master through Redis Sentinel and rebuilds the
+    ///  pool against it. Without Sentinel, the pool reconnects on its own.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis sentinel backend to integration test
+    async fn reconnect(&self) {
+        let Some(sentinel) = &self.sentinel else {
+            return;
+        };
+
+        let Ok(pool) = sentinel_config(&sentinel.base, &sentinel.nodes, &sentinel.master_name)
+            .await
+            .and_then(|cfg| standalone_pool(&cfg))
+        else {
+            // try again on the next connection
+            self.stale.store(true, Ordering::Relaxed);
+            return;
+        };
+
+        let mut backend = self.backend.write().await;
+        if let Backend::Standalone(previous) = &*backend {
+            previous.close();
+        }
+
+        *backend = pool;
+        cache_info!("rebuilt pool with key folder '{}'.", self.key_folder);
+    }
+
+    /// Logs a failed operation, marking the pool for reconnection if the
+    ///  master may have moved
+    fn operation_failed(&self, e: redis::RedisError) -> CacheError {
+        cache_error!("Operation failed, redis error: {}", e);
+        if needs_reconnect(&e) {
+            self.stale.store(true, Ordering::Relaxed);
+        }
+
+        CacheError::OperationFailed
+    }
+
+    /// The key of the queue or stream this pool reads and writes
+    pub fn key_folder(&self) -> String {
         self.key_folder.clone()
     }
 
@@ -136,7 +469,7 @@ impl RedisPool {
         C: redis::aio::ConnectionLike,
     {
         // TODO(R5): As static when that is supported
-        let pop_count = NonZeroUsize::new(20).ok_or_else(|| {
+        let pop_count = NonZeroUsize::new(POP_COUNT).ok_or_else(|| {
             cache_error!("Operation failed, could not create NonZeroUsize.");
             CacheError::OperationFailed
         })?;
@@ -147,10 +480,7 @@ impl RedisPool {
             .rpop(self.key_folder(), Some(pop_count))
             .query_async(connection)
             .await
-            .map_err(|e| {
-                cache_error!("Operation failed, redis error: {}", e);
-                CacheError::OperationFailed
-            })?;
+            .map_err(|e| self.operation_failed(e))?;
 
         let redis::Value::Bulk(values) = result else {
             cache_error!("Operation failed, unexpected redis response: {:?}", result);
@@ -164,11 +494,415 @@ impl RedisPool {
 
         RedisPool::process_bulk::<T>(values)
     }
+
+    /// Gets the key of the list holding items being processed. The queue
+    ///  key is its hash tag, so that both lists are in the same Redis
+    ///  Cluster slot.
+    fn processing_key(&self) -> String {
+        format!("{{{}}}:processing", self.key_folder)
+    }
+
+    ///
+    /// Move items from the queue to its processing list and return them
+    ///
+    /// The items stay in the processing list until [`RedisPool::ack`]
+    ///  removes them or [`RedisPool::requeue`] returns them to the queue.
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn pop_reliable<T, C>(&mut self, connection: &mut C) -> Result<Vec<T>, CacheError>
+    where
+        T: for<'a> Deserialize<'a> + Clone + Debug,
+        C: redis::aio::ConnectionLike,
+    {
+        let mut pipe = redis::pipe();
+        for _ in 0..POP_COUNT {
+            pipe.cmd("RPOPLPUSH")
+                .arg(self.key_folder())
+                .arg(self.processing_key());
+        }
+
+        let values = pipe
+            .query_async::<_, Vec<redis::Value>>(connection)
+            .await
+            .map_err(|e| self.operation_failed(e))?
+            .into_iter()
+            .filter(|value| *value != redis::Value::Nil)
+            .collect::<Vec<redis::Value>>();
+
+        if values.is_empty() {
+            cache_debug!("No values found.");
+            return Ok(vec![]);
+        }
+
+        RedisPool::process_bulk::<T>(vec![redis::Value::Bulk(values)])
+    }
+
+    ///
+    /// Remove processed items from the processing list
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn ack<C>(&mut self, connection: &mut C) -> Result<(), CacheError>
+    where
+        C: redis::aio::ConnectionLike,
+    {
+        redis::cmd("DEL")
+            .arg(self.processing_key())
+            .query_async::<_, ()>(connection)
+            .await
+            .map_err(|e| self.operation_failed(e))
+    }
+
+    /// Gets the key of the hash counting the failed attempts of each item
+    fn attempts_key(&self) -> String {
+        format!("{{{}}}:attempts", self.key_folder)
+    }
+
+    /// Gets the key of the list holding items that failed too many times
+    pub fn dead_letter_key(&self) -> String {
+        format!("{{{}}}:dead", self.key_folder)
+    }
+
+    ///
+    /// Settle the items of the processing list. Rejected items go to the
+    ///  back of the queue, or to the dead-letter list once they were
+    ///  rejected `max_attempts` times. The others are acknowledged.
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn settle<T, C>(
+        &mut self,
+        connection: &mut C,
+        items: &[T],
+        rejected: &[T],
+        max_attempts: u32,
+    ) -> Result<(), CacheError>
+    where
+        T: Serialize,
+        C: redis::aio::ConnectionLike,
+    {
+        let rejected = RedisPool::stream_entries(rejected)?;
+        let processed: Vec<String> = RedisPool::stream_entries(items)?
+            .into_iter()
+            .filter(|entry| !rejected.contains(entry))
+            .collect();
+
+        let attempts = if rejected.is_empty() {
+            vec![]
+        } else {
+            let mut pipe = redis::pipe();
+            for entry in &rejected {
+                pipe.cmd("HINCRBY")
+                    .arg(self.attempts_key())
+                    .arg(entry)
+                    .arg(1);
+            }
+
+            pipe.query_async::<_, Vec<u32>>(connection)
+                .await
+                .map_err(|e| self.operation_failed(e))?
+        };
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        if !processed.is_empty() {
+            pipe.cmd("HDEL")
+                .arg(self.attempts_key())
+                .arg(&processed)
+                .ignore();
+        }
+
+        for (entry, attempts) in rejected.iter().zip(attempts) {
+            if attempts < max_attempts {
+                pipe.cmd("LPUSH").arg(self.key_folder()).arg(entry).ignore();
+                continue;
+            }
+
+            cache_error!(
+                "moving item to {} after {attempts} attempts: {entry}",
+                self.dead_letter_key()
+            );
+            pipe.cmd("LPUSH")
+                .arg(self.dead_letter_key())
+                .arg(entry)
+                .ignore();
+            pipe.cmd("HDEL")
+                .arg(self.attempts_key())
+                .arg(entry)
+                .ignore();
+        }
+
+        pipe.cmd("DEL").arg(self.processing_key()).ignore();
+        pipe.query_async::<_, ()>(connection)
+            .await
+            .map_err(|e| self.operation_failed(e))
+    }
+
+    ///
+    /// Return unprocessed items to the front of the queue, in their
+    ///  original order
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn requeue<C>(&mut self, connection: &mut C) -> Result<usize, CacheError>
+    where
+        C: redis::aio::ConnectionLike,
+    {
+        let mut count = 0;
+        loop {
+            let value = redis::cmd("LMOVE")
+                .arg(self.processing_key())
+                .arg(self.key_folder())
+                .arg("LEFT")
+                .arg("RIGHT")
+                .query_async::<_, redis::Value>(connection)
+                .await
+                .map_err(|e| self.operation_failed(e))?;
+
+            if value == redis::Value::Nil {
+                return Ok(count);
+            }
+
+            count += 1;
+        }
+    }
+
+    /// Serializes items for insertion into a Redis stream
+    fn stream_entries<T>(items: &[T]) -> Result<Vec<String>, CacheError>
+    where
+        T: Serialize,
+    {
+        items
+            .iter()
+            .map(|item| {
+                serde_json::to_string(item).map_err(|e| {
+                    cache_error!("could not serialize value: {:?}", e);
+                    CacheError::OperationFailed
+                })
+            })
+            .collect()
+    }
+
+    ///
+    /// Push items onto the queue, to be taken by [`RedisPool::pop`]
+    ///  in the order they were pushed
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn push<T>(&self, items: &[T]) -> Result<(), CacheError>
+    where
+        T: Serialize,
+    {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let entries = RedisPool::stream_entries(items)?;
+        let mut connection = self.connection().await?;
+
+        redis::cmd("LPUSH")
+            .arg(self.key_folder())
+            .arg(entries)
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| self.operation_failed(e))
+    }
+
+    ///
+    /// Append items to a capped Redis stream
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn push_stream<T>(&self, items: &[T]) -> Result<(), CacheError>
+    where
+        T: Serialize,
+    {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let entries = RedisPool::stream_entries(items)?;
+        let mut connection = self.connection().await?;
+
+        let mut pipe = redis::pipe();
+        for entry in entries {
+            pipe.cmd("XADD")
+                .arg(self.key_folder())
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(MAX_STREAM_LENGTH)
+                .arg("*")
+                .arg(STREAM_FIELD)
+                .arg(entry)
+                .ignore();
+        }
+
+        pipe.query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| self.operation_failed(e))
+    }
+
+    ///
+    /// Publish items on the pub/sub channel named after the key folder.
+    ///  Only subscribers connected at the time receive them.
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn publish<T>(&self, items: &[T]) -> Result<(), CacheError>
+    where
+        T: Serialize,
+    {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let entries = RedisPool::stream_entries(items)?;
+        let mut connection = self.connection().await?;
+
+        let mut pipe = redis::pipe();
+        for entry in entries {
+            pipe.cmd("PUBLISH")
+                .arg(self.key_folder())
+                .arg(entry)
+                .ignore();
+        }
+
+        pipe.query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| self.operation_failed(e))
+    }
+}
+
+/// Gets the bucket index of a track cache timestamp
+fn track_bucket(timestamp: DateTime<Utc>) -> i64 {
+    timestamp.timestamp().div_euclid(TRACK_BUCKET_SECONDS)
+}
+
+/// Gets the track cache key of an aircraft for a given bucket. The aircraft
+///  identifier is its hash tag, so that the buckets of one aircraft share a
+///  Redis Cluster slot while the aircraft are spread over the cluster.
+fn track_key(key_folder: &str, identifier: &str, bucket: i64) -> String {
+    format!("{key_folder}:{{{identifier}}}:{bucket}")
+}
+
+/// Gets the track cache keys of an aircraft covering the provided time range
+fn track_keys(
+    key_folder: &str,
+    identifier: &str,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Vec<String> {
+    (track_bucket(time_start)..=track_bucket(time_end))
+        .map(|bucket| track_key(key_folder, identifier, bucket))
+        .collect()
+}
+
+impl RedisPool {
+    ///
+    /// Mirror aircraft positions into the time-bucketed track cache
+    ///
+    /// Each aircraft has one Redis list per [`TRACK_BUCKET_SECONDS`] of
+    ///  telemetry, which expires once it falls outside of
+    ///  [`TRACK_RETENTION_SECONDS`].
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn push_track(&self, items: &[AircraftPosition]) -> Result<(), CacheError> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let entries = RedisPool::stream_entries(items)?;
+        let mut connection = self.connection().await?;
+
+        // One pipeline per aircraft, as their keys are in different slots
+        let expiry = TRACK_RETENTION_SECONDS + TRACK_BUCKET_SECONDS;
+        let mut pipes: BTreeMap<&str, redis::Pipeline> = BTreeMap::new();
+        for (item, entry) in items.iter().zip(entries) {
+            let key = track_key(
+                &self.key_folder,
+                &item.identifier,
+                track_bucket(item.timestamp_network),
+            );
+
+            let pipe = pipes.entry(&item.identifier).or_insert_with(redis::pipe);
+            pipe.cmd("RPUSH").arg(&key).arg(entry).ignore();
+            pipe.cmd("EXPIRE").arg(&key).arg(expiry).ignore();
+        }
+
+        for pipe in pipes.values() {
+            pipe.query_async::<_, ()>(&mut connection)
+                .await
+                .map_err(|e| self.operation_failed(e))?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Get the cached positions of an aircraft within a time range,
+    ///  ordered from oldest to newest
+    ///
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn get_track(
+        &self,
+        identifier: &str,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> Result<Vec<AircraftPosition>, CacheError> {
+        let retained = Utc::now() - Duration::seconds(TRACK_RETENTION_SECONDS);
+        let time_start = time_start.max(retained);
+        if time_start > time_end {
+            return Ok(vec![]);
+        }
+
+        let mut connection = self.connection().await?;
+
+        let mut pipe = redis::pipe();
+        for key in track_keys(&self.key_folder, identifier, time_start, time_end) {
+            pipe.cmd("LRANGE").arg(key).arg(0).arg(-1);
+        }
+
+        let buckets = pipe
+            .query_async::<_, Vec<Vec<String>>>(&mut connection)
+            .await
+            .map_err(|e| self.operation_failed(e))?;
+
+        Ok(RedisPool::process_track(buckets, time_start, time_end))
+    }
+
+    /// Deserializes track cache entries, keeping those within the time range
+    fn process_track(
+        buckets: Vec<Vec<String>>,
+        time_start: DateTime<Utc>,
+        time_end: DateTime<Utc>,
+    ) -> Vec<AircraftPosition> {
+        let mut track = buckets
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                serde_json::from_str::<AircraftPosition>(&entry)
+                    .map_err(|e| {
+                        cache_error!("could not deserialize value: {:?}", e);
+                    })
+                    .ok()
+            })
+            .filter(|item| {
+                item.timestamp_network >= time_start && item.timestamp_network <= time_end
+            })
+            .collect::<Vec<AircraftPosition>>();
+
+        track.sort_by_key(|item| item.timestamp_network);
+        track
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{Degrees, Meters};
 
     #[test]
     fn test_cache_error_display() {
@@ -186,6 +920,168 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_stream_entries() {
+        #[derive(Serialize)]
+        struct Item {
+            identifier: String,
+            value: u32,
+        }
+
+        let items = vec![
+            Item {
+                identifier: "a".to_string(),
+                value: 1,
+            },
+            Item {
+                identifier: "b".to_string(),
+                value: 2,
+            },
+        ];
+
+        let entries = RedisPool::stream_entries(&items).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                r#"{"identifier":"a","value":1}"#.to_string(),
+                r#"{"identifier":"b","value":2}"#.to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_track_keys() {
+        let time_start = DateTime::from_timestamp(1_700_000_005, 0).unwrap();
+        let time_end = DateTime::from_timestamp(1_700_000_027, 0).unwrap();
+        let keys = track_keys("gis:aircraft:track", "N12345", time_start, time_end);
+        assert_eq!(
+            keys,
+            vec![
+                "gis:aircraft:track:{N12345}:170000000".to_string(),
+                "gis:aircraft:track:{N12345}:170000001".to_string(),
+                "gis:aircraft:track:{N12345}:170000002".to_string(),
+            ]
+        );
+
+        let keys = track_keys("gis:aircraft:track", "N12345", time_start, time_start);
+        assert_eq!(keys.len(), 1);
+    }
+
+    #[test]
+    fn test_process_track() {
+        let time_end = Utc::now();
+        let time_start = time_end - Duration::try_seconds(30).unwrap();
+        let position = |seconds_ago: i64| AircraftPosition {
+            identifier: "N12345".to_string(),
+            position: crate::types::Position {
+                longitude: Degrees(4.9),
+                latitude: Degrees(52.3),
+                altitude_meters: Meters(seconds_ago as f64),
+            },
+            timestamp_network: time_end - Duration::try_seconds(seconds_ago).unwrap(),
+            timestamp_asset: None,
+        };
+
+        let entries = |seconds_ago: &[i64]| {
+            let items: Vec<AircraftPosition> = seconds_ago.iter().map(|s| position(*s)).collect();
+            RedisPool::stream_entries(&items).unwrap()
+        };
+
+        let buckets = vec![
+            entries(&[45, 20]),
+            vec!["not json".to_string()],
+            entries(&[5, 25]),
+        ];
+
+        let track = RedisPool::process_track(buckets, time_start, time_end);
+        let altitudes: Vec<f64> = track
+            .iter()
+            .map(|item| item.position.altitude_meters.0)
+            .collect();
+        assert_eq!(altitudes, vec![25.0, 20.0, 5.0]);
+    }
+
+    #[test]
+    fn test_node_urls() {
+        assert!(node_urls("").is_empty());
+        assert!(node_urls(" , ").is_empty());
+        assert_eq!(
+            node_urls("redis://sentinel-1:26379, redis://sentinel-2:26379,"),
+            vec!["redis://sentinel-1:26379", "redis://sentinel-2:26379"]
+        );
+    }
+
+    #[test]
+    fn test_needs_reconnect() {
+        let error = redis::RedisError::from((redis::ErrorKind::ReadOnly, "replica"));
+        assert!(needs_reconnect(&error));
+
+        let error = redis::RedisError::from((redis::ErrorKind::MasterDown, "master down"));
+        assert!(needs_reconnect(&error));
+
+        let error =
+            redis::RedisError::from(std::io::Error::from(std::io::ErrorKind::ConnectionReset));
+        assert!(needs_reconnect(&error));
+
+        let error = redis::RedisError::from((redis::ErrorKind::TypeError, "wrong type"));
+        assert!(!needs_reconnect(&error));
+    }
+
+    #[test]
+    fn test_processing_key_slot() {
+        let pool = RedisPool {
+            backend: Arc::new(RwLock::new(Backend::Standalone(
+                deadpool_redis::Config::from_url("redis://localhost:6379")
+                    .create_pool(Some(Runtime::Tokio1))
+                    .unwrap(),
+            ))),
+            sentinel: None,
+            stale: Arc::new(AtomicBool::new(false)),
+            key_folder: "gis:aircraft:id".to_string(),
+        };
+
+        // the hash tag of the processing list is the queue key
+        assert_eq!(pool.processing_key(), "{gis:aircraft:id}:processing");
+        assert_eq!(pool.attempts_key(), "{gis:aircraft:id}:attempts");
+        assert_eq!(pool.dead_letter_key(), "{gis:aircraft:id}:dead");
+    }
+
+    #[test]
+    fn test_master_config() {
+        let base = deadpool_redis::Config {
+            url: Some("redis://:secret@ignored:6379/3".to_string()),
+            connection: None,
+            pool: Some(deadpool_redis::PoolConfig::new(16)),
+        };
+
+        let config = master_config(&base, "10.0.0.2".to_string(), 6380).unwrap();
+        assert!(config.url.is_none());
+        assert_eq!(config.pool.unwrap().max_size, 16);
+
+        let connection = config.connection.unwrap();
+        let deadpool_redis::ConnectionAddr::Tcp(host, port) = connection.addr else {
+            panic!("expected a TCP address");
+        };
+        assert_eq!(host, "10.0.0.2");
+        assert_eq!(port, 6380);
+        assert_eq!(connection.redis.db, 3);
+        assert_eq!(connection.redis.password, Some("secret".to_string()));
+
+        let base = deadpool_redis::Config::default();
+        let connection = master_config(&base, "master".to_string(), 6379)
+            .unwrap()
+            .connection
+            .unwrap();
+        assert_eq!(connection.redis.db, 0);
+        assert!(connection.redis.password.is_none());
+
+        let base = deadpool_redis::Config {
+            url: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(master_config(&base, "master".to_string(), 6379).is_err());
+    }
+
     // #[tokio::test]
     // async fn test_redis_pool_debug() {
     //     let key_folder = "test";
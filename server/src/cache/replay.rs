@@ -0,0 +1,331 @@
+//! Replay of recorded aircraft reports, run with `replay-telemetry`.
+//!
+//! Reads a JSONL dump of [`AircraftId`], [`AircraftPosition`] and
+//!  [`AircraftVelocity`] reports, one per line as they were pushed to the
+//!  queues, and pushes them again onto the Redis queues the consumers pop
+//!  from. The reports are sent in the order of their network timestamps,
+//!  spaced as they were recorded and scaled by the replay speed, so that an
+//!  incident can be reproduced against a local server and database.
+//!
+//! By default the timestamps of each report are moved to the time it is
+//!  sent, keeping the gap between its network and asset timestamps, so that
+//!  the telemetry rules and staleness checks treat it as live.
+
+use super::latency::Timestamped;
+use super::pool::RedisPool;
+use crate::config::Config;
+use crate::types::{
+    AircraftId, AircraftPosition, AircraftVelocity, REDIS_KEY_AIRCRAFT_ID,
+    REDIS_KEY_AIRCRAFT_POSITION, REDIS_KEY_AIRCRAFT_VELOCITY,
+};
+use lib_common::time::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+use std::io::BufRead;
+use std::path::Path;
+
+/// Possible errors replaying reports
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReplayError {
+    /// The dump could not be read
+    Read,
+
+    /// A line of the dump is not a known report
+    Parse,
+
+    /// Invalid replay speed
+    Speed,
+
+    /// The reports could not be sent to Redis
+    Redis,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Read => write!(f, "Could not read the dump."),
+            ReplayError::Parse => write!(f, "Could not parse a report of the dump."),
+            ReplayError::Speed => write!(f, "Invalid replay speed provided."),
+            ReplayError::Redis => write!(f, "Could not send the reports to Redis."),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// A recorded report, told apart by its fields
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Report {
+    /// Position report
+    Position(AircraftPosition),
+
+    /// Velocity report
+    Velocity(AircraftVelocity),
+
+    /// Identification report
+    Id(AircraftId),
+}
+
+impl Report {
+    /// The network timestamp of the report
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            Report::Position(report) => report.timestamp_network(),
+            Report::Velocity(report) => report.timestamp_network(),
+            Report::Id(report) => report.timestamp_network(),
+        }
+    }
+
+    /// Moves the network and asset timestamps of the report
+    fn shift(&mut self, offset: Duration) {
+        let (network, asset) = match self {
+            Report::Position(report) => {
+                (&mut report.timestamp_network, &mut report.timestamp_asset)
+            }
+            Report::Velocity(report) => {
+                (&mut report.timestamp_network, &mut report.timestamp_asset)
+            }
+            Report::Id(report) => (&mut report.timestamp_network, &mut report.timestamp_asset),
+        };
+
+        *network += offset;
+        if let Some(asset) = asset {
+            *asset += offset;
+        }
+    }
+}
+
+/// Number of reports replayed, by kind
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// Identification reports
+    pub ids: u64,
+
+    /// Position reports
+    pub positions: u64,
+
+    /// Velocity reports
+    pub velocities: u64,
+}
+
+/// Reads the reports of a dump, in the order of their network timestamps.
+///  Reports sharing a timestamp keep the order of the dump. Blank lines
+///  are skipped.
+pub fn read_reports(reader: impl BufRead) -> Result<Vec<Report>, ReplayError> {
+    let mut reports = vec![];
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| {
+            cache_error!("could not read line {}: {e}", index + 1);
+            ReplayError::Read
+        })?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let report = serde_json::from_str::<Report>(&line).map_err(|e| {
+            cache_error!("line {} is not a known report: {e}", index + 1);
+            ReplayError::Parse
+        })?;
+
+        reports.push(report);
+    }
+
+    reports.sort_by_key(Report::timestamp);
+    Ok(reports)
+}
+
+/// Time after the start of the replay at which a report is sent. A speed
+///  of zero sends the reports as fast as possible.
+fn send_offset(first: DateTime<Utc>, timestamp: DateTime<Utc>, speed: f64) -> std::time::Duration {
+    if speed == 0.0 {
+        return std::time::Duration::ZERO;
+    }
+
+    let elapsed = (timestamp - first).to_std().unwrap_or_default();
+    elapsed.div_f64(speed)
+}
+
+/// Checks the replay speed, a factor of the recorded pace
+fn check_speed(speed: f64) -> Result<(), ReplayError> {
+    if !speed.is_finite() || speed < 0.0 {
+        cache_error!("replay speed must be zero or positive, got {speed}.");
+        return Err(ReplayError::Speed);
+    }
+
+    Ok(())
+}
+
+/// Pushes a report onto its queue
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs redis backend to integration test
+async fn send(queues: &[RedisPool; 3], report: &Report) -> Result<(), ReplayError> {
+    let [ids, positions, velocities] = queues;
+    let result = match report {
+        Report::Id(report) => ids.push(std::slice::from_ref(report)).await,
+        Report::Position(report) => positions.push(std::slice::from_ref(report)).await,
+        Report::Velocity(report) => velocities.push(std::slice::from_ref(report)).await,
+    };
+
+    result.map_err(|e| {
+        cache_error!("could not push report: {e}");
+        ReplayError::Redis
+    })
+}
+
+/// Replays the reports of a dump onto the Redis queues at a factor of the
+///  recorded pace, moving their timestamps to the time each is sent unless
+///  the original timestamps are kept
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs redis backend to integration test
+pub async fn replay(
+    config: &Config,
+    path: &Path,
+    speed: f64,
+    original_timestamps: bool,
+) -> Result<Summary, ReplayError> {
+    check_speed(speed)?;
+    let file = std::fs::File::open(path).map_err(|e| {
+        cache_error!("could not open {}: {e}", path.display());
+        ReplayError::Read
+    })?;
+
+    let reports = read_reports(std::io::BufReader::new(file))?;
+    let Some(first) = reports.first().map(Report::timestamp) else {
+        cache_warn!("no reports in {}.", path.display());
+        return Ok(Summary::default());
+    };
+
+    let queue = |key| async move {
+        RedisPool::new(config, key)
+            .await
+            .map_err(|_| ReplayError::Redis)
+    };
+    let queues = [
+        queue(REDIS_KEY_AIRCRAFT_ID).await?,
+        queue(REDIS_KEY_AIRCRAFT_POSITION).await?,
+        queue(REDIS_KEY_AIRCRAFT_VELOCITY).await?,
+    ];
+
+    cache_info!(
+        "replaying {} report(s) from {} at {speed}x.",
+        reports.len(),
+        path.display()
+    );
+
+    let mut summary = Summary::default();
+    let start = tokio::time::Instant::now();
+    for mut report in reports {
+        tokio::time::sleep_until(start + send_offset(first, report.timestamp(), speed)).await;
+        if !original_timestamps {
+            report.shift(Utc::now() - report.timestamp());
+        }
+
+        send(&queues, &report).await?;
+        match report {
+            Report::Id(_) => summary.ids += 1,
+            Report::Position(_) => summary.positions += 1,
+            Report::Velocity(_) => summary.velocities += 1,
+        }
+    }
+
+    cache_info!("replay complete: {:?}", summary);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::TimeZone;
+
+    const DUMP: &str = r#"{"identifier":"Marauder","position":{"latitude":52.3746,"longitude":4.9160036,"altitude_meters":100.0},"timestamp_network":"2024-01-01T10:00:02Z","timestamp_asset":"2024-01-01T10:00:01Z"}
+
+{"identifier":"Marauder","velocity_horizontal_ground_mps":12.0,"velocity_horizontal_air_mps":null,"velocity_vertical_mps":0.5,"track_angle_degrees":90.0,"timestamp_network":"2024-01-01T10:00:02Z","timestamp_asset":null}
+{"identifier":"Marauder","session_id":null,"aircraft_type":"Rotorcraft","timestamp_network":"2024-01-01T10:00:00Z","timestamp_asset":null}
+"#;
+
+    #[test]
+    fn ut_read_reports() {
+        let reports = read_reports(DUMP.as_bytes()).unwrap();
+        assert_eq!(reports.len(), 3);
+
+        // sorted by network timestamp, ties in the order of the dump
+        assert!(matches!(reports[0], Report::Id(_)));
+        assert!(matches!(reports[1], Report::Position(_)));
+        assert!(matches!(reports[2], Report::Velocity(_)));
+        assert_eq!(
+            reports[0].timestamp(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap()
+        );
+
+        let dump = r#"{"identifier":"Marauder"}"#;
+        assert_eq!(
+            read_reports(dump.as_bytes()).unwrap_err(),
+            ReplayError::Parse
+        );
+    }
+
+    #[test]
+    fn ut_report_shift() {
+        let mut reports = read_reports(DUMP.as_bytes()).unwrap();
+        let offset = Duration::try_hours(1).unwrap();
+        reports[1].shift(offset);
+
+        let Report::Position(report) = &reports[1] else {
+            panic!("expected a position report");
+        };
+
+        // the gap between the network and asset timestamps is kept
+        assert_eq!(
+            report.timestamp_network,
+            Utc.with_ymd_and_hms(2024, 1, 1, 11, 0, 2).unwrap()
+        );
+        assert_eq!(
+            report.timestamp_asset,
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 11, 0, 1).unwrap())
+        );
+    }
+
+    #[test]
+    fn ut_send_offset() {
+        let first = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let later = first + Duration::try_seconds(10).unwrap();
+        assert_eq!(
+            send_offset(first, later, 1.0),
+            std::time::Duration::from_secs(10)
+        );
+        assert_eq!(
+            send_offset(first, later, 2.0),
+            std::time::Duration::from_secs(5)
+        );
+        assert_eq!(send_offset(first, later, 0.0), std::time::Duration::ZERO);
+        assert_eq!(send_offset(later, first, 1.0), std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn ut_check_speed() {
+        assert!(check_speed(0.0).is_ok());
+        assert!(check_speed(10.0).is_ok());
+        assert_eq!(check_speed(-1.0), Err(ReplayError::Speed));
+        assert_eq!(check_speed(f64::NAN), Err(ReplayError::Speed));
+        assert_eq!(check_speed(f64::INFINITY), Err(ReplayError::Speed));
+    }
+
+    #[test]
+    fn test_replay_error_display() {
+        assert_eq!(ReplayError::Read.to_string(), "Could not read the dump.");
+        assert_eq!(
+            ReplayError::Parse.to_string(),
+            "Could not parse a report of the dump."
+        );
+        assert_eq!(
+            ReplayError::Speed.to_string(),
+            "Invalid replay speed provided."
+        );
+        assert_eq!(
+            ReplayError::Redis.to_string(),
+            "Could not send the reports to Redis."
+        );
+    }
+}
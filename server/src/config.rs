@@ -12,6 +12,10 @@ use serde::Deserialize;
 pub struct Config {
     /// PostGIS configuration
     pub pg: deadpool_postgres::Config,
+    /// PostGIS standby configuration, used if the primary fails
+    pub pg_standby: Option<deadpool_postgres::Config>,
+    /// interval between PostGIS health checks, in seconds
+    pub pg_health_check_interval_seconds: u64,
     /// path to CA certificate file
     pub db_ca_cert: String,
     /// path to client certificate file
@@ -20,10 +24,120 @@ pub struct Config {
     pub db_client_key: String,
     /// port to be used for gRPC server
     pub docker_port_grpc: u16,
+    /// only serve the gRPC query service, for read replicas. Nothing is
+    ///  written to the database: the tables are not created, and the Redis
+    ///  consumers, maintenance, failover monitor and best path audits don't run.
+    pub grpc_query_only: bool,
+    /// serve gRPC reflection, for tools such as grpcurl
+    pub grpc_reflection: bool,
     /// path to log configuration YAML file
     pub log_config: String,
     /// redis details
     pub redis: deadpool_redis::Config,
+    /// comma-separated Redis Sentinel URLs, empty to connect to the redis url directly
+    pub redis_sentinel_nodes: String,
+    /// name of the master monitored by Redis Sentinel
+    pub redis_sentinel_master: String,
+    /// comma-separated Redis Cluster seed URLs, empty if not a cluster
+    pub redis_cluster_nodes: String,
+    /// Redis stream to publish merged aircraft state to
+    pub redis_aircraft_state_stream: String,
+    /// max length of aircraft identifiers and session IDs. Existing columns
+    ///  are only ever widened; a value below them fails the startup.
+    pub aircraft_identifier_max_length: u32,
+    /// characters added to the identifier charset per entity, such as `Zone::/`
+    pub identifier_extra_characters: String,
+    /// identifier max lengths per entity, such as `Zone:64`
+    pub identifier_max_lengths: String,
+    /// path to a snapshot of assets imported into an empty database, if any
+    pub bootstrap_snapshot: String,
+    /// max deviation in meters of positions dropped from returned tracks, zero to disable
+    pub track_simplify_epsilon_meters: f64,
+    /// the most recent seconds of returned tracks are not simplified
+    pub track_raw_window_seconds: u32,
+    /// max deviation in meters of points dropped from submitted flight paths, zero to disable
+    pub flight_path_simplify_epsilon_meters: f64,
+    /// max deviation in meters of vertices dropped from the volumes of detailed zones, zero to disable
+    pub zone_simplify_epsilon_meters: f64,
+    /// zones with more vertices than this are simplified
+    pub zone_simplify_min_vertices: u32,
+    /// aircraft positions older than this many seconds are reported as stale
+    pub aircraft_stale_seconds: u32,
+    /// aircraft positions older than this many seconds are reported as expired
+    pub aircraft_expired_seconds: u32,
+    /// default weight per meter flown when ranking paths
+    pub path_weight_distance: f32,
+    /// default weight per second of flight when ranking paths
+    pub path_weight_time: f32,
+    /// default weight per meter flown over populated ground when ranking paths,
+    ///  reserved: must be zero until a population density layer is available
+    pub path_weight_ground_risk: f32,
+    /// default weight per meter flown through noise-sensitive areas when ranking paths
+    pub path_weight_noise: f32,
+    /// default weight per meter of level-flight equivalent energy when ranking paths
+    pub path_weight_energy: f32,
+    /// altitudes each aircraft type may cruise at, such as `Rotorcraft:0-120,Hybridlift:60-300`
+    pub aircraft_altitude_limits: String,
+    /// range and cruise speed of each aircraft type, such as `Rotorcraft:30000@20`
+    pub aircraft_performance_profiles: String,
+    /// horizontal and vertical separation kept by each aircraft type, such as `Rotorcraft:10/15`
+    pub flight_separation_margins: String,
+    /// ratio of the flown distance to the straight-line distance when checking reachability
+    pub reachability_detour_factor: f32,
+    /// interval between database maintenance passes, in seconds
+    pub maintenance_interval_seconds: u64,
+    /// flights that ended more than this many days ago are removed, zero to keep them
+    pub flight_retention_days: u32,
+    /// move removed flights to the archive table instead of deleting them
+    pub flight_retention_archive: bool,
+    /// record each best path returned, for audit
+    pub best_path_audit: bool,
+    /// best path audit records older than this many days are removed, zero to keep them
+    pub best_path_audit_retention_days: u32,
+    /// zone and aircraft history older than this many days is removed, zero to keep it
+    pub history_retention_days: u32,
+    /// sessions of aircraft quiet for this many seconds are released, zero to keep them
+    pub aircraft_session_expiry_seconds: u32,
+    /// Redis stream and pub/sub channel to announce upcoming zone changes on
+    pub redis_airspace_stream: String,
+    /// zones activating or expiring within this many seconds are announced, zero to disable
+    pub zone_notice_lead_seconds: u32,
+    /// interval between checks for upcoming zone changes, in seconds
+    pub zone_notice_interval_seconds: u64,
+    /// address of an ADS-B receiver serving SBS (BaseStation) messages, empty to disable
+    pub adsb_sbs_address: String,
+    /// address of an ADS-B receiver serving Beast binary frames, empty to disable
+    pub adsb_beast_address: String,
+    /// local address receiving Remote ID messages over UDP, empty to disable
+    pub remote_id_udp_address: String,
+    /// size in degrees of the grid cells partitioning the aircraft table, zero to disable
+    pub aircraft_partition_cell_degrees: u32,
+    /// max change of aircraft speed between two velocity updates, zero to disable
+    pub telemetry_max_speed_jump_mps: f32,
+    /// max distance between two aircraft position updates, zero to disable
+    pub telemetry_max_position_jump_meters: f32,
+    /// max altitude of aircraft positions, zero to disable
+    pub telemetry_altitude_ceiling_meters: f32,
+    /// how far in the future aircraft reports may be timestamped, in seconds
+    pub telemetry_clock_skew_seconds: u32,
+    /// comma-separated identifiers of aircraft exempt from the telemetry rules
+    pub telemetry_bypass_identifiers: String,
+    /// aircraft positions within this distance of the stored position aren't written, zero to disable
+    pub dedupe_position_epsilon_meters: f32,
+    /// aircraft velocities within this difference of the stored velocity aren't written, zero to disable
+    pub dedupe_velocity_epsilon_mps: f32,
+    /// seconds between writes of an unchanged aircraft state
+    pub dedupe_heartbeat_seconds: u32,
+    /// p95 time items wait in a Redis queue before a warning is logged, in milliseconds, zero to disable
+    pub redis_queue_latency_warn_ms: u64,
+    /// decimal places kept in stored longitudes and latitudes
+    pub coordinate_degree_decimals: u32,
+    /// decimal places kept in stored altitudes in meters
+    pub coordinate_altitude_decimals: u32,
+    /// seconds vertiport centroids and the waypoints between vertiports are served from memory for routing, zero to disable
+    pub vertiport_cache_ttl_seconds: u32,
+    /// dotenv-style file whose changes reload the tunable settings, empty to reload on SIGHUP only
+    pub config_reload_file: String,
 }
 
 impl Default for Config {
@@ -36,10 +150,16 @@ impl Default for Config {
 impl Config {
     /// Create new configuration object with default values
     pub fn new() -> Self {
+        let weights = crate::postgis::routing::CostWeights::default();
+
         Config {
             docker_port_grpc: 50051,
+            grpc_query_only: false,
+            grpc_reflection: false,
             log_config: String::from("log4rs.yaml"),
             pg: deadpool_postgres::Config::new(),
+            pg_standby: None,
+            pg_health_check_interval_seconds: 5,
             db_ca_cert: "".to_string(),
             db_client_cert: "".to_string(),
             db_client_key: "".to_string(),
@@ -48,27 +168,308 @@ impl Config {
                 pool: None,
                 connection: None,
             },
+            redis_sentinel_nodes: "".to_string(),
+            redis_sentinel_master: crate::cache::pool::DEFAULT_SENTINEL_MASTER.to_string(),
+            redis_cluster_nodes: "".to_string(),
+            redis_aircraft_state_stream: crate::types::REDIS_KEY_AIRCRAFT_STATE.to_string(),
+            aircraft_identifier_max_length: crate::postgis::aircraft::DEFAULT_IDENTIFIER_MAX_LENGTH,
+            identifier_extra_characters: "".to_string(),
+            identifier_max_lengths: "".to_string(),
+            bootstrap_snapshot: "".to_string(),
+            track_simplify_epsilon_meters: crate::postgis::simplify::DEFAULT_EPSILON_METERS,
+            track_raw_window_seconds: crate::postgis::simplify::DEFAULT_RAW_WINDOW_SECONDS,
+            flight_path_simplify_epsilon_meters:
+                crate::postgis::simplify::DEFAULT_PATH_EPSILON_METERS,
+            zone_simplify_epsilon_meters: crate::postgis::simplify::DEFAULT_ZONE_EPSILON_METERS,
+            zone_simplify_min_vertices: crate::postgis::simplify::DEFAULT_ZONE_MIN_VERTICES,
+            aircraft_stale_seconds: crate::postgis::aircraft::DEFAULT_STALE_SECONDS,
+            aircraft_expired_seconds: crate::postgis::aircraft::DEFAULT_EXPIRED_SECONDS,
+            path_weight_distance: weights.distance,
+            path_weight_time: weights.time,
+            path_weight_ground_risk: weights.ground_risk,
+            path_weight_noise: weights.noise,
+            path_weight_energy: weights.energy,
+            aircraft_altitude_limits: "".to_string(),
+            aircraft_performance_profiles: "".to_string(),
+            flight_separation_margins: "".to_string(),
+            reachability_detour_factor: crate::postgis::reachability::DEFAULT_DETOUR_FACTOR,
+            maintenance_interval_seconds: crate::postgis::maintenance::DEFAULT_INTERVAL_SECONDS,
+            flight_retention_days: crate::postgis::maintenance::DEFAULT_FLIGHT_RETENTION_DAYS,
+            flight_retention_archive: true,
+            best_path_audit: false,
+            best_path_audit_retention_days: crate::postgis::audit::DEFAULT_RETENTION_DAYS,
+            history_retention_days: crate::postgis::snapshot::DEFAULT_HISTORY_RETENTION_DAYS,
+            aircraft_session_expiry_seconds:
+                crate::postgis::aircraft::DEFAULT_SESSION_EXPIRY_SECONDS,
+            redis_airspace_stream: crate::types::REDIS_KEY_AIRSPACE.to_string(),
+            zone_notice_lead_seconds: crate::postgis::zone_events::DEFAULT_LEAD_SECONDS,
+            zone_notice_interval_seconds: crate::postgis::zone_events::DEFAULT_INTERVAL_SECONDS,
+            adsb_sbs_address: "".to_string(),
+            adsb_beast_address: "".to_string(),
+            remote_id_udp_address: "".to_string(),
+            aircraft_partition_cell_degrees: crate::postgis::partition::DEFAULT_CELL_DEGREES,
+            telemetry_max_speed_jump_mps: 0.0,
+            telemetry_max_position_jump_meters: 0.0,
+            telemetry_altitude_ceiling_meters: 0.0,
+            telemetry_clock_skew_seconds: 0,
+            telemetry_bypass_identifiers: "".to_string(),
+            dedupe_position_epsilon_meters: 0.0,
+            dedupe_velocity_epsilon_mps: 0.0,
+            dedupe_heartbeat_seconds: crate::postgis::dedupe::DEFAULT_HEARTBEAT_SECONDS,
+            redis_queue_latency_warn_ms: crate::cache::latency::DEFAULT_WARN_MS,
+            coordinate_degree_decimals: crate::postgis::utils::DEFAULT_DEGREE_DECIMALS,
+            coordinate_altitude_decimals: crate::postgis::utils::DEFAULT_ALTITUDE_DECIMALS,
+            vertiport_cache_ttl_seconds: crate::postgis::vertiport::DEFAULT_CACHE_TTL_SECONDS,
+            config_reload_file: String::new(),
         }
     }
 
     /// Create a new `Config` object using environment variables
     pub fn try_from_env() -> Result<Self, ConfigError> {
+        Config::try_from_env_with(vec![])
+    }
+
+    /// Create a new `Config` object using environment variables, the
+    ///  variables of a dotenv-style file taking precedence over them
+    pub fn try_from_env_and_file(path: &str) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Message(format!("could not read {path}: {e}")))?;
+
+        Config::try_from_env_with(parse_variables(&text))
+    }
+
+    /// Create a new `Config` object using environment variables and the
+    ///  given overriding variables
+    fn try_from_env_with(overrides: Vec<(String, String)>) -> Result<Self, ConfigError> {
         // read .env file if present
         dotenv().ok();
         let default_config = Config::default();
 
-        config::Config::builder()
+        let mut builder = config::Config::builder()
             .set_default("docker_port_grpc", default_config.docker_port_grpc)?
+            .set_default("grpc_query_only", default_config.grpc_query_only)?
+            .set_default("grpc_reflection", default_config.grpc_reflection)?
             .set_default("log_config", default_config.log_config)?
-            .add_source(Environment::default().separator("__"))
-            .build()?
-            .try_deserialize()
+            .set_default(
+                "pg_health_check_interval_seconds",
+                default_config.pg_health_check_interval_seconds,
+            )?
+            .set_default("redis_sentinel_nodes", default_config.redis_sentinel_nodes)?
+            .set_default(
+                "redis_sentinel_master",
+                default_config.redis_sentinel_master,
+            )?
+            .set_default("redis_cluster_nodes", default_config.redis_cluster_nodes)?
+            .set_default(
+                "redis_aircraft_state_stream",
+                default_config.redis_aircraft_state_stream,
+            )?
+            .set_default(
+                "aircraft_identifier_max_length",
+                default_config.aircraft_identifier_max_length,
+            )?
+            .set_default(
+                "identifier_extra_characters",
+                default_config.identifier_extra_characters,
+            )?
+            .set_default(
+                "identifier_max_lengths",
+                default_config.identifier_max_lengths,
+            )?
+            .set_default("bootstrap_snapshot", default_config.bootstrap_snapshot)?
+            .set_default(
+                "track_simplify_epsilon_meters",
+                default_config.track_simplify_epsilon_meters,
+            )?
+            .set_default(
+                "track_raw_window_seconds",
+                default_config.track_raw_window_seconds,
+            )?
+            .set_default(
+                "flight_path_simplify_epsilon_meters",
+                default_config.flight_path_simplify_epsilon_meters,
+            )?
+            .set_default(
+                "zone_simplify_epsilon_meters",
+                default_config.zone_simplify_epsilon_meters,
+            )?
+            .set_default(
+                "zone_simplify_min_vertices",
+                default_config.zone_simplify_min_vertices,
+            )?
+            .set_default(
+                "aircraft_stale_seconds",
+                default_config.aircraft_stale_seconds,
+            )?
+            .set_default(
+                "aircraft_expired_seconds",
+                default_config.aircraft_expired_seconds,
+            )?
+            .set_default(
+                "path_weight_distance",
+                f64::from(default_config.path_weight_distance),
+            )?
+            .set_default(
+                "path_weight_time",
+                f64::from(default_config.path_weight_time),
+            )?
+            .set_default(
+                "path_weight_ground_risk",
+                f64::from(default_config.path_weight_ground_risk),
+            )?
+            .set_default(
+                "path_weight_noise",
+                f64::from(default_config.path_weight_noise),
+            )?
+            .set_default(
+                "path_weight_energy",
+                f64::from(default_config.path_weight_energy),
+            )?
+            .set_default(
+                "aircraft_altitude_limits",
+                default_config.aircraft_altitude_limits,
+            )?
+            .set_default(
+                "aircraft_performance_profiles",
+                default_config.aircraft_performance_profiles,
+            )?
+            .set_default(
+                "flight_separation_margins",
+                default_config.flight_separation_margins,
+            )?
+            .set_default(
+                "reachability_detour_factor",
+                f64::from(default_config.reachability_detour_factor),
+            )?
+            .set_default(
+                "maintenance_interval_seconds",
+                default_config.maintenance_interval_seconds,
+            )?
+            .set_default(
+                "flight_retention_days",
+                default_config.flight_retention_days,
+            )?
+            .set_default(
+                "flight_retention_archive",
+                default_config.flight_retention_archive,
+            )?
+            .set_default("best_path_audit", default_config.best_path_audit)?
+            .set_default(
+                "best_path_audit_retention_days",
+                default_config.best_path_audit_retention_days,
+            )?
+            .set_default(
+                "history_retention_days",
+                default_config.history_retention_days,
+            )?
+            .set_default(
+                "aircraft_session_expiry_seconds",
+                default_config.aircraft_session_expiry_seconds,
+            )?
+            .set_default(
+                "redis_airspace_stream",
+                default_config.redis_airspace_stream,
+            )?
+            .set_default(
+                "zone_notice_lead_seconds",
+                default_config.zone_notice_lead_seconds,
+            )?
+            .set_default(
+                "zone_notice_interval_seconds",
+                default_config.zone_notice_interval_seconds,
+            )?
+            .set_default("adsb_sbs_address", default_config.adsb_sbs_address)?
+            .set_default("adsb_beast_address", default_config.adsb_beast_address)?
+            .set_default(
+                "remote_id_udp_address",
+                default_config.remote_id_udp_address,
+            )?
+            .set_default(
+                "aircraft_partition_cell_degrees",
+                default_config.aircraft_partition_cell_degrees,
+            )?
+            .set_default(
+                "telemetry_max_speed_jump_mps",
+                f64::from(default_config.telemetry_max_speed_jump_mps),
+            )?
+            .set_default(
+                "telemetry_max_position_jump_meters",
+                f64::from(default_config.telemetry_max_position_jump_meters),
+            )?
+            .set_default(
+                "telemetry_altitude_ceiling_meters",
+                f64::from(default_config.telemetry_altitude_ceiling_meters),
+            )?
+            .set_default(
+                "telemetry_clock_skew_seconds",
+                default_config.telemetry_clock_skew_seconds,
+            )?
+            .set_default(
+                "telemetry_bypass_identifiers",
+                default_config.telemetry_bypass_identifiers,
+            )?
+            .set_default(
+                "dedupe_position_epsilon_meters",
+                f64::from(default_config.dedupe_position_epsilon_meters),
+            )?
+            .set_default(
+                "dedupe_velocity_epsilon_mps",
+                f64::from(default_config.dedupe_velocity_epsilon_mps),
+            )?
+            .set_default(
+                "dedupe_heartbeat_seconds",
+                default_config.dedupe_heartbeat_seconds,
+            )?
+            .set_default(
+                "redis_queue_latency_warn_ms",
+                default_config.redis_queue_latency_warn_ms,
+            )?
+            .set_default(
+                "coordinate_degree_decimals",
+                default_config.coordinate_degree_decimals,
+            )?
+            .set_default(
+                "coordinate_altitude_decimals",
+                default_config.coordinate_altitude_decimals,
+            )?
+            .set_default(
+                "vertiport_cache_ttl_seconds",
+                default_config.vertiport_cache_ttl_seconds,
+            )?
+            .set_default("config_reload_file", default_config.config_reload_file)?
+            .add_source(Environment::default().separator("__"));
+
+        // variables use the same nesting separator as the environment
+        for (key, value) in overrides {
+            builder = builder.set_override(key.to_lowercase().replace("__", "."), value)?;
+        }
+
+        builder.build()?.try_deserialize()
     }
 }
 
+/// Parses `KEY=value` lines, skipping blank lines and `#` comments. Values
+///  may be quoted.
+fn parse_variables(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+
+            (key.trim().to_string(), value.to_string())
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use super::{parse_variables, Config};
 
     #[tokio::test]
     async fn test_config_from_default() {
@@ -78,10 +479,140 @@ mod tests {
         let config = Config::default();
 
         assert_eq!(config.docker_port_grpc, 50051);
+        assert!(!config.grpc_query_only);
+        assert!(!config.grpc_reflection);
         assert_eq!(config.log_config, String::from("log4rs.yaml"));
+        assert!(config.pg_standby.is_none());
+        assert!(config.bootstrap_snapshot.is_empty());
+        assert_eq!(config.pg_health_check_interval_seconds, 5);
         assert!(config.redis.url.is_none());
         assert!(config.redis.pool.is_none());
         assert!(config.redis.connection.is_none());
+        assert!(config.redis_sentinel_nodes.is_empty());
+        assert_eq!(
+            config.redis_sentinel_master,
+            crate::cache::pool::DEFAULT_SENTINEL_MASTER
+        );
+        assert!(config.redis_cluster_nodes.is_empty());
+        assert_eq!(
+            config.redis_aircraft_state_stream,
+            crate::types::REDIS_KEY_AIRCRAFT_STATE
+        );
+        assert_eq!(
+            config.aircraft_identifier_max_length,
+            crate::postgis::aircraft::DEFAULT_IDENTIFIER_MAX_LENGTH
+        );
+        assert!(config.identifier_extra_characters.is_empty());
+        assert!(config.identifier_max_lengths.is_empty());
+        assert_eq!(
+            config.track_simplify_epsilon_meters,
+            crate::postgis::simplify::DEFAULT_EPSILON_METERS
+        );
+        assert_eq!(
+            config.track_raw_window_seconds,
+            crate::postgis::simplify::DEFAULT_RAW_WINDOW_SECONDS
+        );
+        assert_eq!(
+            config.flight_path_simplify_epsilon_meters,
+            crate::postgis::simplify::DEFAULT_PATH_EPSILON_METERS
+        );
+        assert_eq!(
+            config.zone_simplify_epsilon_meters,
+            crate::postgis::simplify::DEFAULT_ZONE_EPSILON_METERS
+        );
+        assert_eq!(
+            config.zone_simplify_min_vertices,
+            crate::postgis::simplify::DEFAULT_ZONE_MIN_VERTICES
+        );
+        assert_eq!(
+            config.aircraft_stale_seconds,
+            crate::postgis::aircraft::DEFAULT_STALE_SECONDS
+        );
+        assert_eq!(
+            config.aircraft_expired_seconds,
+            crate::postgis::aircraft::DEFAULT_EXPIRED_SECONDS
+        );
+        assert_eq!(config.path_weight_distance, 1.0);
+        assert_eq!(config.path_weight_time, 0.0);
+        assert_eq!(config.path_weight_ground_risk, 0.0);
+        assert_eq!(config.path_weight_noise, 1.0);
+        assert_eq!(config.path_weight_energy, 0.0);
+        assert!(config.aircraft_altitude_limits.is_empty());
+        assert!(config.aircraft_performance_profiles.is_empty());
+        assert!(config.flight_separation_margins.is_empty());
+        assert_eq!(
+            config.reachability_detour_factor,
+            crate::postgis::reachability::DEFAULT_DETOUR_FACTOR
+        );
+        assert_eq!(
+            config.maintenance_interval_seconds,
+            crate::postgis::maintenance::DEFAULT_INTERVAL_SECONDS
+        );
+        assert_eq!(
+            config.flight_retention_days,
+            crate::postgis::maintenance::DEFAULT_FLIGHT_RETENTION_DAYS
+        );
+        assert!(config.flight_retention_archive);
+        assert!(!config.best_path_audit);
+        assert_eq!(
+            config.best_path_audit_retention_days,
+            crate::postgis::audit::DEFAULT_RETENTION_DAYS
+        );
+        assert_eq!(
+            config.history_retention_days,
+            crate::postgis::snapshot::DEFAULT_HISTORY_RETENTION_DAYS
+        );
+        assert_eq!(
+            config.aircraft_session_expiry_seconds,
+            crate::postgis::aircraft::DEFAULT_SESSION_EXPIRY_SECONDS
+        );
+        assert_eq!(
+            config.redis_airspace_stream,
+            crate::types::REDIS_KEY_AIRSPACE
+        );
+        assert_eq!(
+            config.zone_notice_lead_seconds,
+            crate::postgis::zone_events::DEFAULT_LEAD_SECONDS
+        );
+        assert_eq!(
+            config.zone_notice_interval_seconds,
+            crate::postgis::zone_events::DEFAULT_INTERVAL_SECONDS
+        );
+        assert!(config.adsb_sbs_address.is_empty());
+        assert!(config.adsb_beast_address.is_empty());
+        assert!(config.remote_id_udp_address.is_empty());
+        assert_eq!(
+            config.aircraft_partition_cell_degrees,
+            crate::postgis::partition::DEFAULT_CELL_DEGREES
+        );
+        assert_eq!(config.telemetry_max_speed_jump_mps, 0.0);
+        assert_eq!(config.telemetry_max_position_jump_meters, 0.0);
+        assert_eq!(config.telemetry_altitude_ceiling_meters, 0.0);
+        assert_eq!(config.telemetry_clock_skew_seconds, 0);
+        assert!(config.telemetry_bypass_identifiers.is_empty());
+        assert_eq!(config.dedupe_position_epsilon_meters, 0.0);
+        assert_eq!(config.dedupe_velocity_epsilon_mps, 0.0);
+        assert_eq!(
+            config.dedupe_heartbeat_seconds,
+            crate::postgis::dedupe::DEFAULT_HEARTBEAT_SECONDS
+        );
+        assert_eq!(
+            config.redis_queue_latency_warn_ms,
+            crate::cache::latency::DEFAULT_WARN_MS
+        );
+        assert_eq!(
+            config.coordinate_degree_decimals,
+            crate::postgis::utils::DEFAULT_DEGREE_DECIMALS
+        );
+        assert_eq!(
+            config.coordinate_altitude_decimals,
+            crate::postgis::utils::DEFAULT_ALTITUDE_DECIMALS
+        );
+        assert_eq!(
+            config.vertiport_cache_ttl_seconds,
+            crate::postgis::vertiport::DEFAULT_CACHE_TTL_SECONDS
+        );
+        assert!(config.config_reload_file.is_empty());
 
         ut_info!("Success.");
     }
@@ -92,24 +623,213 @@ mod tests {
         ut_info!("Start.");
 
         std::env::set_var("DOCKER_PORT_GRPC", "6789");
+        std::env::set_var("GRPC_QUERY_ONLY", "true");
+        std::env::set_var("GRPC_REFLECTION", "true");
         std::env::set_var("LOG_CONFIG", "config_file.yaml");
+        std::env::set_var("PG_STANDBY__HOST", "standby");
+        std::env::set_var("PG_HEALTH_CHECK_INTERVAL_SECONDS", "10");
         std::env::set_var("REDIS__URL", "redis://test_redis:6379");
         std::env::set_var("REDIS__POOL__MAX_SIZE", "16");
         std::env::set_var("REDIS__POOL__TIMEOUTS__WAIT__SECS", "2");
         std::env::set_var("REDIS__POOL__TIMEOUTS__WAIT__NANOS", "0");
+        std::env::set_var("REDIS_AIRCRAFT_STATE_STREAM", "test:aircraft:state");
+        std::env::set_var(
+            "REDIS_SENTINEL_NODES",
+            "redis://sentinel-1:26379,redis://sentinel-2:26379",
+        );
+        std::env::set_var("REDIS_SENTINEL_MASTER", "gis");
+        std::env::set_var(
+            "REDIS_CLUSTER_NODES",
+            "redis://node-1:6379,redis://node-2:6379",
+        );
+        std::env::set_var("AIRCRAFT_IDENTIFIER_MAX_LENGTH", "64");
+        std::env::set_var("IDENTIFIER_EXTRA_CHARACTERS", "Zone::/");
+        std::env::set_var("IDENTIFIER_MAX_LENGTHS", "Zone:64");
+        std::env::set_var("TRACK_SIMPLIFY_EPSILON_METERS", "2.5");
+        std::env::set_var("BOOTSTRAP_SNAPSHOT", "/snapshot.json");
+        std::env::set_var("TRACK_RAW_WINDOW_SECONDS", "30");
+        std::env::set_var("FLIGHT_PATH_SIMPLIFY_EPSILON_METERS", "0.5");
+        std::env::set_var("ZONE_SIMPLIFY_EPSILON_METERS", "10.0");
+        std::env::set_var("ZONE_SIMPLIFY_MIN_VERTICES", "1000");
+        std::env::set_var("AIRCRAFT_STALE_SECONDS", "5");
+        std::env::set_var("AIRCRAFT_EXPIRED_SECONDS", "60");
+        std::env::set_var("PATH_WEIGHT_DISTANCE", "0.5");
+        std::env::set_var("PATH_WEIGHT_TIME", "2");
+        std::env::set_var("PATH_WEIGHT_GROUND_RISK", "3");
+        std::env::set_var("PATH_WEIGHT_NOISE", "4");
+        std::env::set_var("PATH_WEIGHT_ENERGY", "0.25");
+        std::env::set_var("AIRCRAFT_ALTITUDE_LIMITS", "Rotorcraft:0-120");
+        std::env::set_var("AIRCRAFT_PERFORMANCE_PROFILES", "Rotorcraft:30000@20");
+        std::env::set_var("FLIGHT_SEPARATION_MARGINS", "Rotorcraft:10/15");
+        std::env::set_var("REACHABILITY_DETOUR_FACTOR", "1.5");
+        std::env::set_var("MAINTENANCE_INTERVAL_SECONDS", "600");
+        std::env::set_var("FLIGHT_RETENTION_DAYS", "7");
+        std::env::set_var("FLIGHT_RETENTION_ARCHIVE", "false");
+        std::env::set_var("BEST_PATH_AUDIT", "true");
+        std::env::set_var("BEST_PATH_AUDIT_RETENTION_DAYS", "365");
+        std::env::set_var("HISTORY_RETENTION_DAYS", "7");
+        std::env::set_var("AIRCRAFT_SESSION_EXPIRY_SECONDS", "600");
+        std::env::set_var("REDIS_AIRSPACE_STREAM", "test:airspace");
+        std::env::set_var("ZONE_NOTICE_LEAD_SECONDS", "300");
+        std::env::set_var("ZONE_NOTICE_INTERVAL_SECONDS", "30");
+        std::env::set_var("ADSB_SBS_ADDRESS", "dump1090:30003");
+        std::env::set_var("ADSB_BEAST_ADDRESS", "dump1090:30005");
+        std::env::set_var("REMOTE_ID_UDP_ADDRESS", "0.0.0.0:4000");
+        std::env::set_var("AIRCRAFT_PARTITION_CELL_DEGREES", "30");
+        std::env::set_var("TELEMETRY_MAX_SPEED_JUMP_MPS", "25");
+        std::env::set_var("TELEMETRY_MAX_POSITION_JUMP_METERS", "5000");
+        std::env::set_var("TELEMETRY_ALTITUDE_CEILING_METERS", "3000");
+        std::env::set_var("TELEMETRY_CLOCK_SKEW_SECONDS", "2");
+        std::env::set_var("TELEMETRY_BYPASS_IDENTIFIERS", "TEST-1,TEST-2");
+        std::env::set_var("DEDUPE_POSITION_EPSILON_METERS", "1.5");
+        std::env::set_var("DEDUPE_VELOCITY_EPSILON_MPS", "0.5");
+        std::env::set_var("DEDUPE_HEARTBEAT_SECONDS", "3");
+        std::env::set_var("REDIS_QUEUE_LATENCY_WARN_MS", "250");
+        std::env::set_var("COORDINATE_DEGREE_DECIMALS", "6");
+        std::env::set_var("COORDINATE_ALTITUDE_DECIMALS", "1");
+        std::env::set_var("VERTIPORT_CACHE_TTL_SECONDS", "30");
+        std::env::set_var("CONFIG_RELOAD_FILE", "/etc/svc-gis/reload.env");
 
         let config = Config::try_from_env();
         assert!(config.is_ok());
         let config = config.unwrap();
 
         assert_eq!(config.docker_port_grpc, 6789);
+        assert!(config.grpc_query_only);
+        assert!(config.grpc_reflection);
         assert_eq!(config.log_config, String::from("config_file.yaml"));
+        assert_eq!(
+            config.pg_standby.and_then(|pg| pg.host),
+            Some(String::from("standby"))
+        );
+        assert_eq!(config.pg_health_check_interval_seconds, 10);
         assert_eq!(
             config.redis.url,
             Some(String::from("redis://test_redis:6379"))
         );
         assert!(config.redis.pool.is_some());
+        assert_eq!(
+            config.redis_sentinel_nodes,
+            String::from("redis://sentinel-1:26379,redis://sentinel-2:26379")
+        );
+        assert_eq!(config.redis_sentinel_master, String::from("gis"));
+        assert_eq!(
+            config.redis_cluster_nodes,
+            String::from("redis://node-1:6379,redis://node-2:6379")
+        );
+        assert_eq!(
+            config.redis_aircraft_state_stream,
+            String::from("test:aircraft:state")
+        );
+        assert_eq!(config.aircraft_identifier_max_length, 64);
+        assert_eq!(config.identifier_extra_characters, String::from("Zone::/"));
+        assert_eq!(config.identifier_max_lengths, String::from("Zone:64"));
+        assert_eq!(config.track_simplify_epsilon_meters, 2.5);
+        assert_eq!(config.bootstrap_snapshot, String::from("/snapshot.json"));
+        assert_eq!(config.track_raw_window_seconds, 30);
+        assert_eq!(config.flight_path_simplify_epsilon_meters, 0.5);
+        assert_eq!(config.zone_simplify_epsilon_meters, 10.0);
+        assert_eq!(config.zone_simplify_min_vertices, 1000);
+        assert_eq!(config.aircraft_stale_seconds, 5);
+        assert_eq!(config.aircraft_expired_seconds, 60);
+        assert_eq!(config.path_weight_distance, 0.5);
+        assert_eq!(config.path_weight_time, 2.0);
+        assert_eq!(config.path_weight_ground_risk, 3.0);
+        assert_eq!(config.path_weight_noise, 4.0);
+        assert_eq!(config.path_weight_energy, 0.25);
+        assert_eq!(
+            config.aircraft_altitude_limits,
+            String::from("Rotorcraft:0-120")
+        );
+        assert_eq!(
+            config.aircraft_performance_profiles,
+            String::from("Rotorcraft:30000@20")
+        );
+        assert_eq!(
+            config.flight_separation_margins,
+            String::from("Rotorcraft:10/15")
+        );
+        assert_eq!(config.reachability_detour_factor, 1.5);
+        assert_eq!(config.maintenance_interval_seconds, 600);
+        assert_eq!(config.flight_retention_days, 7);
+        assert!(!config.flight_retention_archive);
+        assert!(config.best_path_audit);
+        assert_eq!(config.best_path_audit_retention_days, 365);
+        assert_eq!(config.history_retention_days, 7);
+        assert_eq!(config.aircraft_session_expiry_seconds, 600);
+        assert_eq!(config.redis_airspace_stream, String::from("test:airspace"));
+        assert_eq!(config.zone_notice_lead_seconds, 300);
+        assert_eq!(config.zone_notice_interval_seconds, 30);
+        assert_eq!(config.adsb_sbs_address, String::from("dump1090:30003"));
+        assert_eq!(config.adsb_beast_address, String::from("dump1090:30005"));
+        assert_eq!(config.remote_id_udp_address, String::from("0.0.0.0:4000"));
+        assert_eq!(config.aircraft_partition_cell_degrees, 30);
+        assert_eq!(config.telemetry_max_speed_jump_mps, 25.0);
+        assert_eq!(config.telemetry_max_position_jump_meters, 5000.0);
+        assert_eq!(config.telemetry_altitude_ceiling_meters, 3000.0);
+        assert_eq!(config.telemetry_clock_skew_seconds, 2);
+        assert_eq!(
+            config.telemetry_bypass_identifiers,
+            String::from("TEST-1,TEST-2")
+        );
+        assert_eq!(config.dedupe_position_epsilon_meters, 1.5);
+        assert_eq!(config.dedupe_velocity_epsilon_mps, 0.5);
+        assert_eq!(config.dedupe_heartbeat_seconds, 3);
+        assert_eq!(config.redis_queue_latency_warn_ms, 250);
+        assert_eq!(config.coordinate_degree_decimals, 6);
+        assert_eq!(config.coordinate_altitude_decimals, 1);
+        assert_eq!(config.vertiport_cache_ttl_seconds, 30);
+        assert_eq!(
+            config.config_reload_file,
+            String::from("/etc/svc-gis/reload.env")
+        );
+
+        ut_info!("Success.");
+    }
+
+    #[tokio::test]
+    async fn test_config_from_env_and_file() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("Start.");
+
+        let path = std::env::temp_dir().join("svc-gis-test-reload.env");
+        let variables = [
+            "# tunables",
+            "DEDUPE_HEARTBEAT_SECONDS=4",
+            "PG__DBNAME=gis",
+            "REDIS__URL=redis://reload:6379",
+            "DB_CA_CERT=ca.crt",
+            "DB_CLIENT_CERT=client.crt",
+            "DB_CLIENT_KEY=client.key",
+        ];
+        std::fs::write(&path, variables.join("\n")).unwrap();
+
+        let config = Config::try_from_env_and_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(config.dedupe_heartbeat_seconds, 4);
+        assert_eq!(config.pg.dbname, Some(String::from("gis")));
+        assert_eq!(config.redis.url, Some(String::from("redis://reload:6379")));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(Config::try_from_env_and_file(path.to_str().unwrap()).is_err());
 
         ut_info!("Success.");
     }
+
+    #[test]
+    fn ut_parse_variables() {
+        let variables = parse_variables(
+            "# comment\n\n  FLIGHT_RETENTION_DAYS = 7\nTELEMETRY_BYPASS_IDENTIFIERS=\"TEST-1,TEST-2\"\nINVALID\n",
+        );
+
+        assert_eq!(
+            variables,
+            vec![
+                (String::from("FLIGHT_RETENTION_DAYS"), String::from("7")),
+                (
+                    String::from("TELEMETRY_BYPASS_IDENTIFIERS"),
+                    String::from("TEST-1,TEST-2")
+                ),
+            ]
+        );
+    }
 }
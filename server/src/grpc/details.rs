@@ -0,0 +1,92 @@
+//! Error details attached to gRPC statuses
+//!
+//! Rejected fields are sent as a `google.rpc.BadRequest` in the
+//!  `grpc-status-details-bin` trailer, wrapped in a `google.rpc.Status`,
+//!  which is how the standard error detail decoders expect them.
+
+use super::server::grpc_server::{BadRequest, FieldViolation};
+use prost::Message;
+use tonic::codegen::Bytes;
+use tonic::{Code, Status};
+
+/// Type URL of a packed `google.rpc.BadRequest`
+pub const BAD_REQUEST_TYPE_URL: &str = "type.googleapis.com/google.rpc.BadRequest";
+
+/// Envelope of the status details, as `google.rpc.Status`
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    /// The status code
+    #[prost(int32, tag = "1")]
+    code: i32,
+
+    /// The status message
+    #[prost(string, tag = "2")]
+    message: String,
+
+    /// The packed details
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<prost_types::Any>,
+}
+
+/// Builds an INVALID_ARGUMENT status listing the rejected fields
+pub fn bad_request(message: &str, field_violations: Vec<FieldViolation>) -> Status {
+    let details = RpcStatus {
+        code: Code::InvalidArgument as i32,
+        message: message.to_string(),
+        details: vec![prost_types::Any {
+            type_url: BAD_REQUEST_TYPE_URL.to_string(),
+            value: BadRequest { field_violations }.encode_to_vec(),
+        }],
+    };
+
+    Status::with_details(
+        Code::InvalidArgument,
+        message,
+        Bytes::from(details.encode_to_vec()),
+    )
+}
+
+/// Returns a status listing the rejected fields, if any were rejected
+#[allow(clippy::result_large_err)] // the handlers return the status as-is
+pub fn check_violations(message: &str, violations: Vec<FieldViolation>) -> Result<(), Status> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    grpc_warn!("{message}: {} rejected fields.", violations.len());
+    Err(bad_request(message, violations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_bad_request() {
+        let violation = FieldViolation {
+            field: "zones[1].vertices[3]".to_string(),
+            description: "zone 'NFZ_B': Invalid location provided.".to_string(),
+        };
+
+        let status = bad_request("invalid zones", vec![violation.clone()]);
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "invalid zones");
+
+        let details = RpcStatus::decode(status.details()).unwrap();
+        assert_eq!(details.code, Code::InvalidArgument as i32);
+        assert_eq!(details.details.len(), 1);
+        assert_eq!(details.details[0].type_url, BAD_REQUEST_TYPE_URL);
+
+        let bad_request = BadRequest::decode(details.details[0].value.as_slice()).unwrap();
+        assert_eq!(bad_request.field_violations, vec![violation]);
+    }
+
+    #[test]
+    fn ut_check_violations() {
+        assert!(check_violations("invalid zones", vec![]).is_ok());
+
+        let status =
+            check_violations("invalid zones", vec![FieldViolation::default()]).unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,156 @@
+//! Information about this service, so that clients can build requests
+//!  within the limits it enforces
+
+use super::server::grpc_server::{DatabaseInfo, QueueLatency, ServiceInfoResponse, ServiceLimits};
+use crate::cache::latency;
+use crate::postgis::{aircraft, best_path, capabilities, routing, rules, utils, waypoint, zone};
+
+/// Optional features and whether this build was compiled with them
+const FEATURES: [(&str, bool); 7] = [
+    ("adsb", cfg!(feature = "adsb")),
+    ("export", cfg!(feature = "export")),
+    ("remote_id", cfg!(feature = "remote_id")),
+    ("mock", cfg!(feature = "mock")),
+    ("stub_backends", cfg!(feature = "stub_backends")),
+    ("stub_server", cfg!(feature = "stub_server")),
+    ("vendored-openssl", cfg!(feature = "vendored-openssl")),
+];
+
+/// Gets the optional features this build was compiled with
+pub fn features() -> Vec<String> {
+    FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// Gets the limits in effect, including those set from the configuration
+pub fn limits() -> ServiceLimits {
+    let thresholds = aircraft::get_quality_thresholds();
+    let rules = rules::get_rules();
+
+    ServiceLimits {
+        max_path_count: best_path::MAX_PATH_COUNT_LIMIT as u32,
+        min_path_node_count: best_path::MIN_PATH_NODE_COUNT_LIMIT as u32,
+        max_path_node_count: best_path::MAX_PATH_NODE_COUNT_LIMIT as u32,
+        default_path_node_count: best_path::DEFAULT_PATH_NODE_COUNT as u32,
+        max_flight_distance_meters: routing::MAX_FLIGHT_DISTANCE_METERS,
+        flight_levels_meters: routing::FLIGHT_LEVELS.to_vec(),
+        flight_separation_meters: best_path::FLIGHT_SEPARATION_METERS as f32,
+        max_distance_matrix_node_count: best_path::MAX_DISTANCE_MATRIX_NODE_COUNT as u32,
+        min_polygon_vertices: utils::MIN_NUM_POLYGON_VERTICES as u32,
+        max_waypoint_buffer_meters: zone::MAX_WAYPOINT_BUFFER_METERS,
+        default_page_size: utils::DEFAULT_PAGE_SIZE,
+        max_page_size: utils::MAX_PAGE_SIZE,
+        aircraft_identifier_max_length: aircraft::get_identifier_max_length(),
+        aircraft_stale_seconds: thresholds.stale_seconds,
+        aircraft_expired_seconds: thresholds.expired_seconds,
+        telemetry_altitude_ceiling_meters: rules.altitude_ceiling_meters,
+        telemetry_clock_skew_seconds: rules.clock_skew_seconds,
+        max_cluster_zoom: waypoint::MAX_CLUSTER_ZOOM,
+        max_window_offset_minutes: best_path::MAX_WINDOW_OFFSET_MINUTES,
+        max_arrival_window_minutes: best_path::MAX_ARRIVAL_WINDOW_MINUTES,
+    }
+}
+
+/// Gets the database versions detected at startup, if checked
+pub fn database() -> Option<DatabaseInfo> {
+    capabilities::get_capabilities().map(|capabilities| DatabaseInfo {
+        postgres_version: capabilities.postgres_version.clone(),
+        postgis_version: capabilities
+            .postgis_version()
+            .unwrap_or_default()
+            .to_string(),
+        sfcgal_version: capabilities
+            .sfcgal_version()
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+/// Gets the latency histograms of the consumed Redis queues
+pub fn queue_latencies() -> Vec<QueueLatency> {
+    latency::histograms()
+        .into_iter()
+        .map(|(queue, histogram)| QueueLatency {
+            queue,
+            bucket_bounds_ms: latency::LATENCY_BUCKETS_MS.to_vec(),
+            counts: histogram.counts.to_vec(),
+            p95_ms: histogram.percentile(95.0).unwrap_or_default(),
+            max_ms: histogram.max_ms,
+        })
+        .collect()
+}
+
+/// Gets the version, features, schema, limits, database, queue
+///  latencies and interface version of this service
+pub fn service_info() -> ServiceInfoResponse {
+    ServiceInfoResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: features(),
+        schema: crate::postgis::get_schema().to_string(),
+        limits: Some(limits()),
+        database: database(),
+        queue_latencies: queue_latencies(),
+        interface_version: crate::types::INTERFACE_VERSION.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_features() {
+        let features = features();
+        assert_eq!(
+            features.contains(&"mock".to_string()),
+            cfg!(feature = "mock")
+        );
+        assert_eq!(
+            features.contains(&"adsb".to_string()),
+            cfg!(feature = "adsb")
+        );
+    }
+
+    #[test]
+    fn ut_service_info() {
+        let info = service_info();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.schema, crate::postgis::get_schema());
+        assert_eq!(info.interface_version, crate::types::INTERFACE_VERSION);
+
+        // not checked without a database
+        assert!(info.database.is_none());
+
+        let limits = info.limits.unwrap();
+        assert!(limits.min_path_node_count <= limits.default_path_node_count);
+        assert!(limits.default_path_node_count <= limits.max_path_node_count);
+        assert!(limits.default_page_size <= limits.max_page_size);
+        assert!(limits.aircraft_stale_seconds <= limits.aircraft_expired_seconds);
+        assert_eq!(limits.flight_levels_meters, routing::FLIGHT_LEVELS.to_vec());
+    }
+
+    #[test]
+    fn ut_queue_latencies() {
+        let queue = "test:info:latency";
+        let now = lib_common::time::Utc::now();
+        let item = crate::types::AircraftId {
+            identifier: Some("TEST-1".to_string()),
+            session_id: None,
+            aircraft_type: crate::types::AircraftType::Undeclared,
+            timestamp_network: now - lib_common::time::Duration::try_milliseconds(75).unwrap(),
+            timestamp_asset: None,
+            session_ended: false,
+        };
+        latency::record(queue, &[item], now);
+
+        let latencies = queue_latencies();
+        let latency = latencies.iter().find(|l| l.queue == queue).unwrap();
+        assert_eq!(latency.bucket_bounds_ms.len() + 1, latency.counts.len());
+        assert_eq!(latency.counts.iter().sum::<u64>(), 1);
+        assert_eq!(latency.p95_ms, 75);
+        assert_eq!(latency.max_ms, 75);
+    }
+}
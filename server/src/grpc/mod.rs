@@ -3,4 +3,6 @@
 
 #[macro_use]
 pub mod macros;
+pub mod details;
+pub mod info;
 pub mod server;
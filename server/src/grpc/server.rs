@@ -4,34 +4,105 @@
 pub mod grpc_server {
     #![allow(unused_qualifications, missing_docs)]
     tonic::include_proto!("grpc");
+
+    /// Encoded descriptors of the services, served through gRPC reflection
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("grpc_descriptor");
 }
 
+#[cfg(not(feature = "stub_server"))]
+use super::details::check_violations;
 use crate::postgis::utils::distance_meters;
 use crate::postgis::{best_path::PathError, *};
 use crate::shutdown_signal;
-pub use grpc_server::rpc_service_server::{RpcService, RpcServiceServer};
+use crate::types::INTERFACE_VERSION;
+pub use grpc_server::ingest_service_server::{IngestService, IngestServiceServer};
+pub use grpc_server::query_service_server::{QueryService, QueryServiceServer};
 use grpc_server::{ReadyRequest, ReadyResponse};
 use lib_common::time::{DateTime, Utc};
 use postgis::ewkb::PointZ;
+use prost::Message;
 use std::fmt::Debug;
 use std::net::SocketAddr;
 use tonic::transport::Server;
-use tonic::{Request, Response, Status};
+use tonic::{Code, Request, Response, Status, Streaming};
 
 /// struct to implement the gRPC server functions
 #[derive(Debug, Copy, Clone)]
 pub struct ServerImpl {}
 
+/// Converts a best path error into a [`Status`]
+///
+/// When no path could be found, the reason and the flights that blocked
+///  the most candidates are attached to the status details as an encoded
+///  [`grpc_server::PathFailure`].
+fn best_path_status(failure: best_path::SearchFailure) -> Status {
+    let best_path::SearchFailure { error, blockers } = failure;
+    let reason = match error {
+        PostgisError::BestPath(e) => e.failure_reason(),
+        _ => None,
+    };
+
+    let Some(reason) = reason else {
+        return Status::internal(error.to_string());
+    };
+
+    let details = grpc_server::PathFailure {
+        reason: reason as i32,
+        blockers,
+    }
+    .encode_to_vec();
+
+    Status::with_details(Code::NotFound, error.to_string(), details.into())
+}
+
+/// Parses a `grpc-timeout` header value, digits followed by a unit
+fn parse_grpc_timeout(value: &str) -> Option<std::time::Duration> {
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        "H" => Some(std::time::Duration::from_secs(amount * 3600)),
+        "M" => Some(std::time::Duration::from_secs(amount * 60)),
+        "S" => Some(std::time::Duration::from_secs(amount)),
+        "m" => Some(std::time::Duration::from_millis(amount)),
+        "u" => Some(std::time::Duration::from_micros(amount)),
+        "n" => Some(std::time::Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Deadline of a call, from the timeout set by the caller if any
+fn request_deadline<T>(request: &Request<T>) -> Option<DateTime<Utc>> {
+    let value = request.metadata().get("grpc-timeout")?.to_str().ok()?;
+    let Some(timeout) = parse_grpc_timeout(value) else {
+        grpc_warn!("invalid grpc-timeout header: {value}");
+        return None;
+    };
+
+    let timeout = lib_common::time::Duration::from_std(timeout).ok()?;
+    Utc::now().checked_add_signed(timeout)
+}
+
 #[cfg(not(feature = "stub_server"))]
 #[tonic::async_trait]
-impl RpcService for ServerImpl {
+impl IngestService for ServerImpl {
     /// Returns ready:true when service is available
     async fn is_ready(
         &self,
         _request: Request<ReadyRequest>,
     ) -> Result<Response<ReadyResponse>, Status> {
         grpc_debug!("entry.");
-        let response = ReadyResponse { ready: true };
+        let response = ReadyResponse {
+            ready: crate::postgis::bootstrap::is_ready(),
+            interface_version: INTERFACE_VERSION.to_string(),
+        };
         Ok(Response::new(response))
     }
 
@@ -41,8 +112,24 @@ impl RpcService for ServerImpl {
     ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
         grpc_debug!("entry.");
 
+        let request = request.into_inner();
+        if request.validate_only {
+            let validations = vertiport::validate_vertiports(request.vertiports)
+                .await
+                .map_err(|e| {
+                    grpc_error!("error validating vertiports: {}", e);
+                    Status::internal(e.to_string())
+                })?;
+
+            return Ok(Response::new(grpc_server::UpdateResponse {
+                updated: false,
+                validations,
+            }));
+        }
+
         // Update nodes in PostGIS
-        let vertiports = request.into_inner().vertiports;
+        let vertiports = request.vertiports;
+        check_violations("invalid vertiports", vertiport::violations(&vertiports))?;
         vertiport::update_vertiports(vertiports)
             .await
             .map_err(|e| {
@@ -50,7 +137,30 @@ impl RpcService for ServerImpl {
                 Status::internal(e.to_string())
             })?;
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn set_vertiport_status(
+        &self,
+        request: Request<grpc_server::SetVertiportStatusRequest>,
+    ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
+        grpc_debug!("entry.");
+
+        let request = request.into_inner();
+        vertiport::set_vertiport_status(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("error setting vertiport status: {}", e);
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
     }
 
     async fn update_waypoints(
@@ -61,12 +171,32 @@ impl RpcService for ServerImpl {
 
         // Update nodes in PostGIS
         let waypoints = request.into_inner().waypoints;
+        check_violations("invalid waypoints", waypoint::violations(&waypoints))?;
         waypoint::update_waypoints(waypoints).await.map_err(|e| {
             grpc_error!("error updating nodes: {}", e);
             Status::internal(e.to_string())
         })?;
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn import_waypoints(
+        &self,
+        request: Request<grpc_server::ImportWaypointsRequest>,
+    ) -> Result<Response<grpc_server::ImportWaypointsSummary>, Status> {
+        grpc_debug!("entry.");
+
+        let summary = published::import_waypoints(request.into_inner())
+            .await
+            .map_err(|e| {
+                grpc_error!("error importing waypoints: {}", e);
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(summary))
     }
 
     async fn update_zones(
@@ -75,14 +205,108 @@ impl RpcService for ServerImpl {
     ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
         grpc_debug!("entry.");
 
+        let request = request.into_inner();
+        if request.validate_only {
+            let validations = zone::validate_zones(request.zones).await.map_err(|e| {
+                grpc_error!("error validating zones: {}", e);
+                Status::internal(e.to_string())
+            })?;
+
+            return Ok(Response::new(grpc_server::UpdateResponse {
+                updated: false,
+                validations,
+            }));
+        }
+
         // Update nodes in PostGIS
-        let zones = request.into_inner().zones;
+        let zones = request.zones;
+        check_violations("invalid zones", zone::violations(&zones))?;
         zone::update_zones(zones).await.map_err(|e| {
             grpc_error!("error updating zones: {}", e);
             Status::internal(e.to_string())
         })?;
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn update_noise_areas(
+        &self,
+        request: Request<grpc_server::UpdateNoiseAreasRequest>,
+    ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
+        grpc_debug!("entry.");
+
+        let areas = request.into_inner().areas;
+        check_violations("invalid noise areas", noise::violations(&areas))?;
+        noise::update_noise_areas(areas).await.map_err(|e| {
+            grpc_error!("error updating noise areas: {}", e);
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn request_zone_authorization(
+        &self,
+        request: Request<grpc_server::ZoneAuthorizationRequest>,
+    ) -> Result<Response<grpc_server::ZoneAuthorization>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let authorization = authorization::request_zone_authorization(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("error requesting zone authorization: {e}");
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(authorization))
+    }
+
+    async fn update_zones_stream(
+        &self,
+        request: Request<Streaming<grpc_server::UpdateZonesRequest>>,
+    ) -> Result<Response<grpc_server::UpdateZonesSummary>, Status> {
+        grpc_debug!("entry.");
+
+        let mut stream = request.into_inner();
+        let mut import = zone::ZoneStream::default();
+        while let Some(chunk) = stream.message().await? {
+            import.push(chunk.zones).await.map_err(|e| {
+                grpc_error!("error updating zones: {}", e);
+                Status::internal(e.to_string())
+            })?;
+        }
+
+        let summary = import.finish().await.map_err(|e| {
+            grpc_error!("error updating zones: {}", e);
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(summary))
+    }
+
+    async fn update_zone_attributes(
+        &self,
+        request: Request<grpc_server::UpdateZoneAttributesRequest>,
+    ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
+        grpc_debug!("entry.");
+
+        let request = request.into_inner();
+        zone::update_zone_attributes(request).await.map_err(|e| {
+            grpc_error!("error updating zone attributes: {}", e);
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
     }
 
     async fn update_flight_path(
@@ -93,12 +317,102 @@ impl RpcService for ServerImpl {
 
         // Update nodes in PostGIS
         let request = request.into_inner();
+        check_violations("invalid flight path", flight::violations(&request))?;
         flight::update_flight_path(request).await.map_err(|e| {
             grpc_error!("error updating flight path: {}", e);
             Status::internal(e.to_string())
         })?;
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn reserve_volume(
+        &self,
+        request: Request<grpc_server::ReserveVolumeRequest>,
+    ) -> Result<Response<grpc_server::VolumeReservation>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let reservation = reservation::reserve_volume(request).await.map_err(|e| {
+            grpc_error!("error reserving volume: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(reservation))
+    }
+
+    async fn release_reservation(
+        &self,
+        request: Request<grpc_server::ReleaseReservationRequest>,
+    ) -> Result<Response<grpc_server::VolumeReservation>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let reservation = reservation::release_reservation(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("error releasing volume reservation: {e}");
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(reservation))
+    }
+}
+
+#[cfg(not(feature = "stub_server"))]
+#[tonic::async_trait]
+impl QueryService for ServerImpl {
+    /// Returns ready:true when service is available
+    async fn is_ready(
+        &self,
+        _request: Request<ReadyRequest>,
+    ) -> Result<Response<ReadyResponse>, Status> {
+        grpc_debug!("entry.");
+        let response = ReadyResponse {
+            ready: crate::postgis::bootstrap::is_ready(),
+            interface_version: INTERFACE_VERSION.to_string(),
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn list_authorizations(
+        &self,
+        request: Request<grpc_server::ListAuthorizationsRequest>,
+    ) -> Result<Response<grpc_server::ListAuthorizationsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = authorization::list_authorizations(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("error listing zone authorizations: {e}");
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn preview_zone_waypoints(
+        &self,
+        request: Request<grpc_server::PreviewZoneWaypointsRequest>,
+    ) -> Result<Response<grpc_server::PreviewZoneWaypointsResponse>, Status> {
+        grpc_debug!("entry.");
+
+        let zone = request.into_inner().zone.ok_or_else(|| {
+            Status::invalid_argument("zone is required for preview_zone_waypoints")
+        })?;
+
+        let waypoints = zone::preview_zone_waypoints(zone).await.map_err(|e| {
+            grpc_error!("error previewing zone waypoints: {}", e);
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(grpc_server::PreviewZoneWaypointsResponse {
+            waypoints,
+        }))
     }
 
     async fn best_path(
@@ -106,14 +420,15 @@ impl RpcService for ServerImpl {
         request: Request<grpc_server::BestPathRequest>,
     ) -> Result<Response<grpc_server::BestPathResponse>, Status> {
         grpc_debug!("entry.");
+        let deadline = request_deadline(&request);
         let request = request.into_inner();
 
-        let paths = best_path::best_path(request).await.map_err(|e| {
+        let response = best_path::best_path(request, deadline).await.map_err(|e| {
             grpc_error!("error getting best path: {e}");
-            Status::internal(e.to_string())
+            best_path_status(e)
         })?;
 
-        Ok(Response::new(grpc_server::BestPathResponse { paths }))
+        Ok(Response::new(response))
     }
 
     async fn check_intersection(
@@ -135,6 +450,24 @@ impl RpcService for ServerImpl {
             .ok_or_else(|| Status::invalid_argument("time_end is required for check_intersection"))?
             .into();
 
+        let priority = grpc_server::FlightPriority::try_from(request.priority)
+            .map_err(|_| Status::invalid_argument("invalid priority for check_intersection"))?;
+
+        let aircraft_type = match request.aircraft_type {
+            None => crate::types::AircraftType::Undeclared,
+            Some(aircraft_type) => {
+                num_traits::FromPrimitive::from_i32(aircraft_type).ok_or_else(|| {
+                    Status::invalid_argument("invalid aircraft type for check_intersection")
+                })?
+            }
+        };
+
+        let entrant =
+            authorization::Entrant::new(request.aircraft_identifier, request.flight_identifier)
+                .map_err(|_| {
+                    Status::invalid_argument("invalid aircraft or flight for check_intersection")
+                })?;
+
         let pool = DEADPOOL_POSTGIS.get().ok_or_else(|| {
             grpc_error!("could not get psql pool.");
             Status::internal("could not get psql pool")
@@ -145,67 +478,301 @@ impl RpcService for ServerImpl {
             Status::internal(e.to_string())
         })?;
 
-        let points: Vec<PointZ> = request
-            .path
-            .into_iter()
-            .map(|p| {
-                PointZ::new(
-                    p.latitude,
-                    p.longitude,
-                    p.altitude_meters as f64,
-                    Some(DEFAULT_SRID),
-                )
-            })
-            .collect();
-
-        let distance = points
-            .windows(2)
-            .fold(0.0, |acc, pair| acc + distance_meters(&pair[0], &pair[1]));
+        let points: Vec<PointZ> = request
+            .path
+            .into_iter()
+            .map(|p| {
+                PointZ::new(
+                    p.latitude,
+                    p.longitude,
+                    p.altitude_meters as f64,
+                    Some(DEFAULT_SRID),
+                )
+            })
+            .collect();
+
+        let distance = points
+            .windows(2)
+            .fold(0.0, |acc, pair| acc + distance_meters(&pair[0], &pair[1]));
+
+        let (intersects, advisories) = match best_path::intersection_checks(
+            &client,
+            points,
+            distance,
+            time_start,
+            time_end,
+            &request.origin_identifier,
+            &request.target_identifier,
+            &entrant,
+            request.include_simulated,
+            priority,
+            aircraft_type,
+        )
+        .await
+        {
+            Ok(advisories) => (false, advisories),
+            Err(PostgisError::BestPath(PathError::ZoneIntersection)) => (true, vec![]),
+            Err(PostgisError::BestPath(PathError::FlightPlanIntersection)) => (true, vec![]),
+            Err(PostgisError::BestPath(PathError::ReservationIntersection)) => (true, vec![]),
+            Err(_) => {
+                grpc_error!("error checking intersection.");
+                return Err(Status::internal("error checking intersection"));
+            }
+        };
+
+        Ok(Response::new(grpc_server::CheckIntersectionResponse {
+            intersects,
+            advisories,
+        }))
+    }
+
+    async fn suggest_deconfliction(
+        &self,
+        request: Request<grpc_server::SuggestDeconflictionRequest>,
+    ) -> Result<Response<grpc_server::SuggestDeconflictionResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = deconfliction::suggest_deconfliction(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("error suggesting deconfliction: {e}");
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn distance_matrix(
+        &self,
+        request: Request<grpc_server::DistanceMatrixRequest>,
+    ) -> Result<Response<grpc_server::DistanceMatrixResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let entries = best_path::distance_matrix(request).await.map_err(|e| {
+            grpc_error!("error getting distance matrix: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(grpc_server::DistanceMatrixResponse {
+            entries,
+        }))
+    }
+
+    async fn check_volume(
+        &self,
+        request: Request<grpc_server::CheckVolumeRequest>,
+    ) -> Result<Response<grpc_server::CheckVolumeResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = volume::check_volume(request).await.map_err(|e| {
+            grpc_error!("error checking volume: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn find_zone_gaps(
+        &self,
+        request: Request<grpc_server::FindZoneGapsRequest>,
+    ) -> Result<Response<grpc_server::FindZoneGapsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = gaps::find_zone_gaps(request).await.map_err(|e| {
+            grpc_error!("error finding zone gaps: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_zone_constraints(
+        &self,
+        request: Request<grpc_server::GetZoneConstraintsRequest>,
+    ) -> Result<Response<grpc_server::GetZoneConstraintsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = constraints::get_zone_constraints(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("error getting zone constraints: {e}");
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_best_path_audits(
+        &self,
+        request: Request<grpc_server::GetBestPathAuditsRequest>,
+    ) -> Result<Response<grpc_server::GetBestPathAuditsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = audit::get_best_path_audits(request).await.map_err(|e| {
+            grpc_error!("error getting best path audits: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_flights(
+        &self,
+        request: Request<grpc_server::GetFlightsRequest>,
+    ) -> Result<Response<grpc_server::GetFlightsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = flight::get_flights(request).await.map_err(|e| {
+            grpc_error!("error getting flights: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_zones(
+        &self,
+        request: Request<grpc_server::GetZonesRequest>,
+    ) -> Result<Response<grpc_server::GetZonesResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = zone::get_zones(request).await.map_err(|e| {
+            grpc_error!("error getting zones: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_zones_at_point(
+        &self,
+        request: Request<grpc_server::GetZonesAtPointRequest>,
+    ) -> Result<Response<grpc_server::GetZonesAtPointResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = zone::get_zones_at_point(request).await.map_err(|e| {
+            grpc_error!("error getting zones at point: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_waypoints(
+        &self,
+        request: Request<grpc_server::GetWaypointsRequest>,
+    ) -> Result<Response<grpc_server::GetWaypointsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = waypoint::get_waypoints(request).await.map_err(|e| {
+            grpc_error!("error getting waypoints: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn sync_airspace(
+        &self,
+        request: Request<grpc_server::SyncAirspaceRequest>,
+    ) -> Result<Response<grpc_server::SyncAirspaceResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = sync::sync_airspace(request).await.map_err(|e| {
+            grpc_error!("error syncing airspace: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_vertiports(
+        &self,
+        request: Request<grpc_server::GetVertiportsRequest>,
+    ) -> Result<Response<grpc_server::GetVertiportsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = vertiport::get_vertiports(request).await.map_err(|e| {
+            grpc_error!("error getting vertiports: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_service_info(
+        &self,
+        _request: Request<grpc_server::ServiceInfoRequest>,
+    ) -> Result<Response<grpc_server::ServiceInfoResponse>, Status> {
+        grpc_debug!("entry.");
+        Ok(Response::new(super::info::service_info()))
+    }
+
+    async fn get_statistics(
+        &self,
+        request: Request<grpc_server::GetStatisticsRequest>,
+    ) -> Result<Response<grpc_server::GetStatisticsResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
+
+        let response = statistics::get_statistics(request).await.map_err(|e| {
+            grpc_error!("error getting statistics: {e}");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+    async fn check_reachability(
+        &self,
+        request: Request<grpc_server::CheckReachabilityRequest>,
+    ) -> Result<Response<grpc_server::CheckReachabilityResponse>, Status> {
+        grpc_debug!("entry.");
+        let request = request.into_inner();
 
-        let intersects = match best_path::intersection_checks(
-            &client,
-            points,
-            distance,
-            time_start,
-            time_end,
-            &request.origin_identifier,
-            &request.target_identifier,
-        )
-        .await
-        {
-            Ok(()) => false,
-            Err(PostgisError::BestPath(PathError::ZoneIntersection)) => true,
-            Err(PostgisError::BestPath(PathError::FlightPlanIntersection)) => true,
-            Err(_) => {
-                grpc_error!("error checking intersection.");
-                return Err(Status::internal("error checking intersection"));
-            }
-        };
+        let response = reachability::check_reachability(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("error checking reachability: {e}");
+                Status::internal(e.to_string())
+            })?;
 
-        Ok(Response::new(grpc_server::CheckIntersectionResponse {
-            intersects,
-        }))
+        Ok(Response::new(response))
     }
 
-    async fn get_flights(
+    async fn get_snapshot(
         &self,
-        request: Request<grpc_server::GetFlightsRequest>,
-    ) -> Result<Response<grpc_server::GetFlightsResponse>, Status> {
+        request: Request<grpc_server::GetSnapshotRequest>,
+    ) -> Result<Response<grpc_server::GetSnapshotResponse>, Status> {
         grpc_debug!("entry.");
         let request = request.into_inner();
 
-        let flights = flight::get_flights(request).await.map_err(|e| {
-            grpc_error!("error getting flights: {e}");
+        let response = snapshot::get_snapshot(request).await.map_err(|e| {
+            grpc_error!("error getting snapshot: {e}");
             Status::internal(e.to_string())
         })?;
 
-        let response = grpc_server::GetFlightsResponse { flights };
         Ok(Response::new(response))
     }
 }
 
 /// Starts the grpc servers for this microservice using the provided configuration
 ///
+/// The ingest and query services share the port. With
+///  [`grpc_query_only`](crate::config::Config::grpc_query_only) set, only the
+///  query service is served.
+///
 /// # Example:
 /// ```
 /// use svc_gis::grpc::server::grpc_server;
@@ -235,14 +802,44 @@ pub async fn grpc_server(
     let imp = ServerImpl {};
     let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
     health_reporter
-        .set_serving::<RpcServiceServer<ServerImpl>>()
+        .set_serving::<QueryServiceServer<ServerImpl>>()
         .await;
 
+    // Query-only replicas don't serve writes
+    let ingest_service = if config.grpc_query_only {
+        grpc_info!("Query-only mode, the ingest service is not served.");
+        None
+    } else {
+        health_reporter
+            .set_serving::<IngestServiceServer<ServerImpl>>()
+            .await;
+
+        Some(IngestServiceServer::new(imp))
+    };
+
+    // Lets tools such as grpcurl list and call the services without the proto
+    let reflection_service = if config.grpc_reflection {
+        match tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(grpc_server::FILE_DESCRIPTOR_SET)
+            .build()
+        {
+            Ok(service) => Some(service),
+            Err(e) => {
+                grpc_error!("Could not build the gRPC reflection service: {}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
     //start server
     grpc_info!("Starting gRPC services on: {}.", full_grpc_addr);
     match Server::builder()
         .add_service(health_service)
-        .add_service(RpcServiceServer::new(imp))
+        .add_optional_service(reflection_service)
+        .add_service(QueryServiceServer::new(imp))
+        .add_optional_service(ingest_service)
         .serve_with_shutdown(full_grpc_addr, shutdown_signal("grpc", shutdown_rx))
         .await
     {
@@ -255,13 +852,16 @@ pub async fn grpc_server(
 
 #[cfg(feature = "stub_server")]
 #[tonic::async_trait]
-impl RpcService for ServerImpl {
+impl IngestService for ServerImpl {
     async fn is_ready(
         &self,
         _request: Request<ReadyRequest>,
     ) -> Result<Response<ReadyResponse>, Status> {
         grpc_warn!("(MOCK) entry.");
-        let response = ReadyResponse { ready: true };
+        let response = ReadyResponse {
+            ready: true,
+            interface_version: INTERFACE_VERSION.to_string(),
+        };
         Ok(Response::new(response))
     }
 
@@ -271,7 +871,22 @@ impl RpcService for ServerImpl {
     ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
         grpc_warn!("(MOCK) entry.");
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn set_vertiport_status(
+        &self,
+        _request: Request<grpc_server::SetVertiportStatusRequest>,
+    ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
     }
 
     async fn update_waypoints(
@@ -280,7 +895,19 @@ impl RpcService for ServerImpl {
     ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
         grpc_warn!("(MOCK) entry.");
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn import_waypoints(
+        &self,
+        _request: Request<grpc_server::ImportWaypointsRequest>,
+    ) -> Result<Response<grpc_server::ImportWaypointsSummary>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::ImportWaypointsSummary::default()))
     }
 
     async fn update_zones(
@@ -289,7 +916,61 @@ impl RpcService for ServerImpl {
     ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
         grpc_warn!("(MOCK) entry.");
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn update_noise_areas(
+        &self,
+        _request: Request<grpc_server::UpdateNoiseAreasRequest>,
+    ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn request_zone_authorization(
+        &self,
+        request: Request<grpc_server::ZoneAuthorizationRequest>,
+    ) -> Result<Response<grpc_server::ZoneAuthorization>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+
+        Ok(Response::new(grpc_server::ZoneAuthorization {
+            identifier: lib_common::uuid::Uuid::new_v4().to_string(),
+            zone_identifier: request.zone_identifier,
+            aircraft_identifier: request.aircraft_identifier,
+            flight_identifier: request.flight_identifier,
+            time_start: request.time_start,
+            time_end: request.time_end,
+            reference: request.reference,
+        }))
+    }
+
+    async fn update_zones_stream(
+        &self,
+        _request: Request<Streaming<grpc_server::UpdateZonesRequest>>,
+    ) -> Result<Response<grpc_server::UpdateZonesSummary>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::UpdateZonesSummary::default()))
+    }
+
+    async fn update_zone_attributes(
+        &self,
+        _request: Request<grpc_server::UpdateZoneAttributesRequest>,
+    ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
     }
 
     async fn update_flight_path(
@@ -298,7 +979,64 @@ impl RpcService for ServerImpl {
     ) -> Result<Response<grpc_server::UpdateResponse>, Status> {
         grpc_debug!("(MOCK) entry.");
 
-        Ok(Response::new(grpc_server::UpdateResponse { updated: true }))
+        Ok(Response::new(grpc_server::UpdateResponse {
+            updated: true,
+            validations: vec![],
+        }))
+    }
+
+    async fn reserve_volume(
+        &self,
+        _request: Request<grpc_server::ReserveVolumeRequest>,
+    ) -> Result<Response<grpc_server::VolumeReservation>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        Ok(Response::new(grpc_server::VolumeReservation::default()))
+    }
+
+    async fn release_reservation(
+        &self,
+        _request: Request<grpc_server::ReleaseReservationRequest>,
+    ) -> Result<Response<grpc_server::VolumeReservation>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        Ok(Response::new(grpc_server::VolumeReservation::default()))
+    }
+}
+
+#[cfg(feature = "stub_server")]
+#[tonic::async_trait]
+impl QueryService for ServerImpl {
+    async fn is_ready(
+        &self,
+        _request: Request<ReadyRequest>,
+    ) -> Result<Response<ReadyResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let response = ReadyResponse {
+            ready: true,
+            interface_version: INTERFACE_VERSION.to_string(),
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn list_authorizations(
+        &self,
+        _request: Request<grpc_server::ListAuthorizationsRequest>,
+    ) -> Result<Response<grpc_server::ListAuthorizationsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(
+            grpc_server::ListAuthorizationsResponse::default(),
+        ))
+    }
+
+    async fn preview_zone_waypoints(
+        &self,
+        _request: Request<grpc_server::PreviewZoneWaypointsRequest>,
+    ) -> Result<Response<grpc_server::PreviewZoneWaypointsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(
+            grpc_server::PreviewZoneWaypointsResponse::default(),
+        ))
     }
 
     async fn best_path(
@@ -306,13 +1044,14 @@ impl RpcService for ServerImpl {
         request: Request<grpc_server::BestPathRequest>,
     ) -> Result<Response<grpc_server::BestPathResponse>, Status> {
         grpc_warn!("(MOCK) entry.");
+        let deadline = request_deadline(&request);
         let request = request.into_inner();
-        let paths = best_path::best_path(request).await.map_err(|e| {
+        let response = best_path::best_path(request, deadline).await.map_err(|e| {
             grpc_error!("(MOCK) error getting best path.");
-            Status::internal(e.to_string())
+            best_path_status(e)
         })?;
 
-        Ok(Response::new(grpc_server::BestPathResponse { paths }))
+        Ok(Response::new(response))
     }
 
     async fn check_intersection(
@@ -334,6 +1073,24 @@ impl RpcService for ServerImpl {
             .ok_or_else(|| Status::invalid_argument("time_end is required for check_intersection"))?
             .into();
 
+        let priority = grpc_server::FlightPriority::try_from(request.priority)
+            .map_err(|_| Status::invalid_argument("invalid priority for check_intersection"))?;
+
+        let aircraft_type = match request.aircraft_type {
+            None => crate::types::AircraftType::Undeclared,
+            Some(aircraft_type) => {
+                num_traits::FromPrimitive::from_i32(aircraft_type).ok_or_else(|| {
+                    Status::invalid_argument("invalid aircraft type for check_intersection")
+                })?
+            }
+        };
+
+        let entrant =
+            authorization::Entrant::new(request.aircraft_identifier, request.flight_identifier)
+                .map_err(|_| {
+                    Status::invalid_argument("invalid aircraft or flight for check_intersection")
+                })?;
+
         let pool = DEADPOOL_POSTGIS.get().ok_or_else(|| {
             grpc_error!("(MOCK) could not get psql pool.");
             Status::internal("could not get psql pool")
@@ -364,7 +1121,7 @@ impl RpcService for ServerImpl {
             .windows(2)
             .fold(0.0, |acc, pair| acc + distance_meters(&pair[0], &pair[1]));
 
-        let intersects = match best_path::intersection_checks(
+        let (intersects, advisories) = match best_path::intersection_checks(
             &client,
             points,
             distance,
@@ -372,12 +1129,17 @@ impl RpcService for ServerImpl {
             time_end,
             &request.origin_identifier,
             &request.target_identifier,
+            &entrant,
+            request.include_simulated,
+            priority,
+            aircraft_type,
         )
         .await
         {
-            Ok(()) => false,
-            Err(PostgisError::BestPath(PathError::ZoneIntersection)) => true,
-            Err(PostgisError::BestPath(PathError::FlightPlanIntersection)) => true,
+            Ok(advisories) => (false, advisories),
+            Err(PostgisError::BestPath(PathError::ZoneIntersection)) => (true, vec![]),
+            Err(PostgisError::BestPath(PathError::FlightPlanIntersection)) => (true, vec![]),
+            Err(PostgisError::BestPath(PathError::ReservationIntersection)) => (true, vec![]),
             Err(_) => {
                 grpc_error!("(MOCK) error checking intersection.");
                 return Err(Status::internal("error checking intersection"));
@@ -386,9 +1148,87 @@ impl RpcService for ServerImpl {
 
         Ok(Response::new(grpc_server::CheckIntersectionResponse {
             intersects,
+            advisories,
+        }))
+    }
+
+    async fn suggest_deconfliction(
+        &self,
+        request: Request<grpc_server::SuggestDeconflictionRequest>,
+    ) -> Result<Response<grpc_server::SuggestDeconflictionResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+        let response = deconfliction::suggest_deconfliction(request)
+            .await
+            .map_err(|e| {
+                grpc_error!("(MOCK) error suggesting deconfliction.");
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn distance_matrix(
+        &self,
+        request: Request<grpc_server::DistanceMatrixRequest>,
+    ) -> Result<Response<grpc_server::DistanceMatrixResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+        let entries = best_path::distance_matrix(request).await.map_err(|e| {
+            grpc_error!("(MOCK) error getting distance matrix.");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(grpc_server::DistanceMatrixResponse {
+            entries,
         }))
     }
 
+    async fn check_volume(
+        &self,
+        request: Request<grpc_server::CheckVolumeRequest>,
+    ) -> Result<Response<grpc_server::CheckVolumeResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+        let response = volume::check_volume(request).await.map_err(|e| {
+            grpc_error!("(MOCK) error checking volume.");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn find_zone_gaps(
+        &self,
+        _request: Request<grpc_server::FindZoneGapsRequest>,
+    ) -> Result<Response<grpc_server::FindZoneGapsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::FindZoneGapsResponse::default()))
+    }
+
+    async fn get_zone_constraints(
+        &self,
+        _request: Request<grpc_server::GetZoneConstraintsRequest>,
+    ) -> Result<Response<grpc_server::GetZoneConstraintsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(
+            grpc_server::GetZoneConstraintsResponse::default(),
+        ))
+    }
+
+    async fn get_best_path_audits(
+        &self,
+        _request: Request<grpc_server::GetBestPathAuditsRequest>,
+    ) -> Result<Response<grpc_server::GetBestPathAuditsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(
+            grpc_server::GetBestPathAuditsResponse::default(),
+        ))
+    }
+
     async fn get_flights(
         &self,
         request: Request<grpc_server::GetFlightsRequest>,
@@ -396,14 +1236,118 @@ impl RpcService for ServerImpl {
         grpc_warn!("(MOCK) entry.");
         let request = request.into_inner();
 
-        let flights = flight::get_flights(request).await.map_err(|e| {
+        let response = flight::get_flights(request).await.map_err(|e| {
             grpc_error!("(MOCK) error getting flights.");
             Status::internal(e.to_string())
         })?;
 
-        let response = grpc_server::GetFlightsResponse { flights };
         Ok(Response::new(response))
     }
+
+    async fn get_zones(
+        &self,
+        request: Request<grpc_server::GetZonesRequest>,
+    ) -> Result<Response<grpc_server::GetZonesResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+
+        let response = zone::get_zones(request).await.map_err(|e| {
+            grpc_error!("(MOCK) error getting zones.");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_zones_at_point(
+        &self,
+        _request: Request<grpc_server::GetZonesAtPointRequest>,
+    ) -> Result<Response<grpc_server::GetZonesAtPointResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(
+            grpc_server::GetZonesAtPointResponse::default(),
+        ))
+    }
+
+    async fn get_waypoints(
+        &self,
+        request: Request<grpc_server::GetWaypointsRequest>,
+    ) -> Result<Response<grpc_server::GetWaypointsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+
+        let response = waypoint::get_waypoints(request).await.map_err(|e| {
+            grpc_error!("(MOCK) error getting waypoints.");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn sync_airspace(
+        &self,
+        request: Request<grpc_server::SyncAirspaceRequest>,
+    ) -> Result<Response<grpc_server::SyncAirspaceResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+
+        Ok(Response::new(grpc_server::SyncAirspaceResponse {
+            cursor: request.cursor,
+            ..Default::default()
+        }))
+    }
+
+    async fn get_vertiports(
+        &self,
+        request: Request<grpc_server::GetVertiportsRequest>,
+    ) -> Result<Response<grpc_server::GetVertiportsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        let request = request.into_inner();
+
+        let response = vertiport::get_vertiports(request).await.map_err(|e| {
+            grpc_error!("(MOCK) error getting vertiports.");
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_service_info(
+        &self,
+        _request: Request<grpc_server::ServiceInfoRequest>,
+    ) -> Result<Response<grpc_server::ServiceInfoResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+        Ok(Response::new(super::info::service_info()))
+    }
+
+    async fn get_statistics(
+        &self,
+        _request: Request<grpc_server::GetStatisticsRequest>,
+    ) -> Result<Response<grpc_server::GetStatisticsResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::GetStatisticsResponse::default()))
+    }
+    async fn check_reachability(
+        &self,
+        _request: Request<grpc_server::CheckReachabilityRequest>,
+    ) -> Result<Response<grpc_server::CheckReachabilityResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(
+            grpc_server::CheckReachabilityResponse::default(),
+        ))
+    }
+
+    async fn get_snapshot(
+        &self,
+        _request: Request<grpc_server::GetSnapshotRequest>,
+    ) -> Result<Response<grpc_server::GetSnapshotResponse>, Status> {
+        grpc_warn!("(MOCK) entry.");
+
+        Ok(Response::new(grpc_server::GetSnapshotResponse::default()))
+    }
 }
 
 #[cfg(test)]
@@ -413,10 +1357,101 @@ mod tests {
     #[tokio::test]
     async fn test_grpc_server_is_ready() {
         let imp = ServerImpl {};
-        let result = imp.is_ready(Request::new(ReadyRequest {})).await;
+        let result = IngestService::is_ready(&imp, Request::new(ReadyRequest {})).await;
         assert!(result.is_ok());
         let result: ReadyResponse = result.unwrap().into_inner();
         assert!(result.ready);
+        assert_eq!(result.interface_version, INTERFACE_VERSION);
+
+        let result = QueryService::is_ready(&imp, Request::new(ReadyRequest {})).await;
+        assert!(result.unwrap().into_inner().ready);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_server_get_service_info() {
+        let imp = ServerImpl {};
+        let result = imp
+            .get_service_info(Request::new(grpc_server::ServiceInfoRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(result.version, env!("CARGO_PKG_VERSION"));
+        assert!(result.limits.is_some());
+    }
+
+    #[test]
+    fn test_best_path_status() {
+        let status =
+            best_path_status(PostgisError::BestPath(PathError::AllCandidatesConflicted).into());
+        assert_eq!(status.code(), Code::NotFound);
+        let details = grpc_server::PathFailure::decode(status.details()).unwrap();
+        assert_eq!(
+            details.reason(),
+            grpc_server::PathFailureReason::AllCandidatesConflicted
+        );
+        assert!(details.blockers.is_empty());
+
+        let blocker = grpc_server::PathBlocker {
+            flight_identifier: "FLIGHT-1".to_string(),
+            aircraft_identifier: "AIRCRAFT-1".to_string(),
+            blocked_count: 3,
+        };
+        let status = best_path_status(best_path::SearchFailure {
+            error: PostgisError::BestPath(PathError::AllCandidatesConflicted),
+            blockers: vec![blocker.clone()],
+        });
+        let details = grpc_server::PathFailure::decode(status.details()).unwrap();
+        assert_eq!(details.blockers, vec![blocker]);
+
+        let status = best_path_status(PostgisError::BestPath(PathError::InvalidStartNode).into());
+        assert_eq!(status.code(), Code::Internal);
+        assert!(status.details().is_empty());
+
+        let status = best_path_status(PostgisError::Psql(PsqlError::Client).into());
+        assert_eq!(status.code(), Code::Internal);
+    }
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        use std::time::Duration;
+        assert_eq!(parse_grpc_timeout("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_grpc_timeout("3M"), Some(Duration::from_secs(180)));
+        assert_eq!(parse_grpc_timeout("5S"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_grpc_timeout("10u"), Some(Duration::from_micros(10)));
+        assert_eq!(
+            parse_grpc_timeout("99999999n"),
+            Some(Duration::from_nanos(99999999))
+        );
+
+        assert_eq!(parse_grpc_timeout(""), None);
+        assert_eq!(parse_grpc_timeout("m"), None);
+        assert_eq!(parse_grpc_timeout("5s"), None);
+        assert_eq!(parse_grpc_timeout("-5S"), None);
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+    }
+
+    #[test]
+    fn test_request_deadline() {
+        let request = Request::new(());
+        assert!(request_deadline(&request).is_none());
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("grpc-timeout", "500m".parse().unwrap());
+        let deadline = request_deadline(&request).unwrap();
+        assert!(deadline > Utc::now());
+        assert!(
+            deadline <= Utc::now() + lib_common::time::Duration::try_milliseconds(500).unwrap()
+        );
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("grpc-timeout", "soon".parse().unwrap());
+        assert!(request_deadline(&request).is_none());
     }
 
     #[tokio::test]
@@ -0,0 +1,448 @@
+//! Decoding of ADS-B extended squitter messages
+//!
+//! Only the 112-bit messages (DF17, and DF18 with CF 0) carrying aircraft
+//!  identification, airborne position and ground velocity are decoded.
+//!  Aircraft are identified by the hex string of their ICAO address.
+//!
+//! Positions are broadcast in Compact Position Reporting (CPR) format, which
+//!  takes an even and an odd frame received within
+//!  [`CPR_MAX_FRAME_INTERVAL_SECONDS`] of each other to resolve globally.
+
+use super::{Report, METERS_PER_FOOT, MPS_PER_KNOT};
+use crate::types::{AircraftId, AircraftPosition, AircraftType, AircraftVelocity, Position};
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Length of an extended squitter message in bytes
+pub const MESSAGE_LENGTH: usize = 14;
+
+/// Max time between the even and odd frames of a CPR position
+pub const CPR_MAX_FRAME_INTERVAL_SECONDS: i64 = 10;
+
+/// Number of aircraft with pending CPR frames above which old frames are dropped
+const MAX_PENDING_AIRCRAFT: usize = 1000;
+
+/// Number of latitude zones between the equator and a pole
+const CPR_LATITUDE_ZONES: f64 = 15.0;
+
+/// Scale of the 17-bit CPR coordinates
+const CPR_SCALE: f64 = 131072.0;
+
+/// Generator polynomial of the Mode S parity
+const CRC_GENERATOR: u32 = 0xFFF409;
+
+/// Characters of the 6-bit aircraft identification charset
+const CALLSIGN_CHARSET: &[u8; 64] =
+    b"#ABCDEFGHIJKLMNOPQRSTUVWXYZ##### ###############0123456789######";
+
+/// Computes the Mode S parity of the data bits of a message
+fn parity(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0;
+    for byte in data {
+        crc ^= (*byte as u32) << 16;
+        for _ in 0..8 {
+            crc = if crc & 0x800000 != 0 {
+                (crc << 1) ^ CRC_GENERATOR
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc & 0xFFFFFF
+}
+
+/// Verifies the parity of an extended squitter message
+pub fn crc_ok(message: &[u8]) -> bool {
+    if message.len() != MESSAGE_LENGTH {
+        return false;
+    }
+
+    let received = ((message[11] as u32) << 16) | ((message[12] as u32) << 8) | message[13] as u32;
+    parity(&message[..11]) == received
+}
+
+/// Reads `length` bits of the message starting at bit `start`,
+///  counting the first bit of the message as bit 1
+fn bits(message: &[u8], start: usize, length: usize) -> u64 {
+    (start..start + length).fold(0, |value, bit| {
+        let byte = message[(bit - 1) / 8];
+        let set = (byte >> (7 - (bit - 1) % 8)) & 1;
+        (value << 1) | set as u64
+    })
+}
+
+/// Gets the aircraft type of an emitter category
+fn aircraft_type(type_code: u8, category: u8) -> AircraftType {
+    match (type_code, category) {
+        (2, 4..=7) => AircraftType::Groundobstacle,
+        (3, 1) => AircraftType::Glider,
+        (3, 2) => AircraftType::Airship,
+        (3, 4) => AircraftType::Unpowered,
+        (3, 6) => AircraftType::Other,
+        (3, 7) => AircraftType::Rocket,
+        (4, 1..=6) => AircraftType::Aeroplane,
+        (4, 7) => AircraftType::Rotorcraft,
+        _ => AircraftType::Undeclared,
+    }
+}
+
+/// Decodes the callsign of an identification message
+fn callsign(message: &[u8]) -> Option<String> {
+    let callsign = (0..8)
+        .map(|index| CALLSIGN_CHARSET[bits(message, 41 + index * 6, 6) as usize] as char)
+        .collect::<String>()
+        .replace('#', "")
+        .trim()
+        .to_string();
+
+    (!callsign.is_empty()).then_some(callsign)
+}
+
+/// Decodes the barometric altitude of a position message in feet
+fn altitude_feet(message: &[u8]) -> Option<f64> {
+    let raw = bits(message, 41, 12);
+
+    // Gillham coded altitudes are not supported
+    let q_bit = (raw >> 4) & 1;
+    if raw == 0 || q_bit == 0 {
+        return None;
+    }
+
+    let n = ((raw >> 5) << 4) | (raw & 0xF);
+    Some(n as f64 * 25.0 - 1000.0)
+}
+
+/// An even or odd CPR frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CprFrame {
+    /// The CPR latitude, from 0 to 1
+    pub latitude: f64,
+
+    /// The CPR longitude, from 0 to 1
+    pub longitude: f64,
+
+    /// The barometric altitude in feet
+    pub altitude_feet: f64,
+
+    /// When the frame was received
+    pub received: DateTime<Utc>,
+}
+
+/// The latest CPR frames of an aircraft
+#[derive(Debug, Default, Copy, Clone)]
+struct CprFrames {
+    even: Option<CprFrame>,
+    odd: Option<CprFrame>,
+}
+
+/// Number of longitude zones at a latitude
+fn longitude_zones(latitude: f64) -> i64 {
+    let latitude = latitude.abs();
+    if latitude == 0.0 {
+        return 59;
+    } else if latitude == 87.0 {
+        return 2;
+    } else if latitude > 87.0 {
+        return 1;
+    }
+
+    let a = 1.0 - (PI / (2.0 * CPR_LATITUDE_ZONES)).cos();
+    let b = (PI / 180.0 * latitude).cos().powi(2);
+    (2.0 * PI / (1.0 - a / b).acos()).floor() as i64
+}
+
+/// Resolves the position from an even and an odd frame, using the latest
+///  of the two as the position of the aircraft.
+///  Returns the latitude and longitude in degrees.
+pub fn cpr_global(even: &CprFrame, odd: &CprFrame) -> Option<(f64, f64)> {
+    let delta_even = 360.0 / 60.0;
+    let delta_odd = 360.0 / 59.0;
+
+    let j = (59.0 * even.latitude - 60.0 * odd.latitude + 0.5).floor();
+    let wrap = |latitude: f64| {
+        if latitude >= 270.0 {
+            latitude - 360.0
+        } else {
+            latitude
+        }
+    };
+
+    let latitude_even = wrap(delta_even * (j.rem_euclid(60.0) + even.latitude));
+    let latitude_odd = wrap(delta_odd * (j.rem_euclid(59.0) + odd.latitude));
+    if !(-90.0..=90.0).contains(&latitude_even) || !(-90.0..=90.0).contains(&latitude_odd) {
+        return None;
+    }
+
+    // Frames from different longitude zones can't be combined
+    let zones = longitude_zones(latitude_even);
+    if zones != longitude_zones(latitude_odd) {
+        return None;
+    }
+
+    let m = (even.longitude * (zones - 1) as f64 - odd.longitude * zones as f64 + 0.5).floor();
+    let (latitude, n, longitude_cpr) = if even.received >= odd.received {
+        (latitude_even, zones.max(1), even.longitude)
+    } else {
+        (latitude_odd, (zones - 1).max(1), odd.longitude)
+    };
+
+    let n = n as f64;
+    let mut longitude = (360.0 / n) * (m.rem_euclid(n) + longitude_cpr);
+    if longitude >= 180.0 {
+        longitude -= 360.0;
+    }
+
+    Some((latitude, longitude))
+}
+
+/// Decodes the ground velocity of a velocity message
+fn velocity(identifier: &str, message: &[u8], now: DateTime<Utc>) -> Option<AircraftVelocity> {
+    // Airspeed subtypes don't carry the ground velocity
+    let subtype = bits(message, 38, 3);
+    let factor = match subtype {
+        1 => 1.0,
+        2 => 4.0,
+        _ => return None,
+    };
+
+    let raw_east = bits(message, 47, 10);
+    let raw_north = bits(message, 58, 10);
+    if raw_east == 0 || raw_north == 0 {
+        return None;
+    }
+
+    let sign = |negative: u64| if negative == 1 { -1.0 } else { 1.0 };
+    let east = sign(bits(message, 46, 1)) * (raw_east - 1) as f64 * factor;
+    let north = sign(bits(message, 57, 1)) * (raw_north - 1) as f64 * factor;
+    let speed_knots = east.hypot(north);
+    let track = east.atan2(north).to_degrees().rem_euclid(360.0);
+
+    // Vertical rate in feet per minute, zero if unavailable
+    let raw_vertical = bits(message, 70, 9);
+    let vertical_fpm = match raw_vertical {
+        0 => 0.0,
+        raw => sign(bits(message, 69, 1)) * (raw - 1) as f64 * 64.0,
+    };
+
+    Some(AircraftVelocity {
+        identifier: identifier.to_string(),
+        velocity_horizontal_ground_mps: (speed_knots * MPS_PER_KNOT).into(),
+        velocity_horizontal_air_mps: None,
+        velocity_vertical_mps: (vertical_fpm * METERS_PER_FOOT / 60.0).into(),
+        track_angle_degrees: track.into(),
+        timestamp_network: now,
+        timestamp_asset: None,
+    })
+}
+
+/// Decodes extended squitter messages, keeping the CPR frames of each
+///  aircraft until their position can be resolved
+#[derive(Debug, Default)]
+pub struct AdsbDecoder {
+    frames: HashMap<String, CprFrames>,
+}
+
+impl AdsbDecoder {
+    /// Decodes a 112-bit message, returning nothing for messages
+    ///  that are corrupt, unsupported or still missing a CPR frame
+    pub fn decode_message(&mut self, message: &[u8], now: DateTime<Utc>) -> Option<Report> {
+        if !crc_ok(message) {
+            return None;
+        }
+
+        // DF18 with a non-zero CF holds TIS-B and ADS-R formats
+        let downlink_format = bits(message, 1, 5);
+        let capability = bits(message, 6, 3);
+        if downlink_format != 17 && !(downlink_format == 18 && capability == 0) {
+            return None;
+        }
+
+        let identifier = format!("{:06X}", bits(message, 9, 24));
+        let type_code = bits(message, 33, 5) as u8;
+        match type_code {
+            1..=4 => Some(Report::Id(AircraftId {
+                identifier: Some(identifier),
+                session_id: callsign(message),
+                aircraft_type: aircraft_type(type_code, bits(message, 38, 3) as u8),
+                timestamp_network: now,
+                timestamp_asset: None,
+                session_ended: false,
+            })),
+            9..=18 => self.position(identifier, message, now),
+            19 => velocity(&identifier, message, now).map(Report::Velocity),
+            _ => None,
+        }
+    }
+
+    /// Stores the CPR frame of a position message and resolves the
+    ///  position if a recent frame of the other kind is known
+    fn position(
+        &mut self,
+        identifier: String,
+        message: &[u8],
+        now: DateTime<Utc>,
+    ) -> Option<Report> {
+        let frame = CprFrame {
+            latitude: bits(message, 55, 17) as f64 / CPR_SCALE,
+            longitude: bits(message, 72, 17) as f64 / CPR_SCALE,
+            altitude_feet: altitude_feet(message)?,
+            received: now,
+        };
+
+        self.prune(now);
+        let frames = self.frames.entry(identifier.clone()).or_default();
+        if bits(message, 54, 1) == 0 {
+            frames.even = Some(frame);
+        } else {
+            frames.odd = Some(frame);
+        }
+
+        let (Some(even), Some(odd)) = (frames.even, frames.odd) else {
+            return None;
+        };
+
+        let interval = (even.received - odd.received).abs();
+        if interval > Duration::seconds(CPR_MAX_FRAME_INTERVAL_SECONDS) {
+            return None;
+        }
+
+        let (latitude, longitude) = cpr_global(&even, &odd)?;
+        Some(Report::Position(AircraftPosition {
+            identifier,
+            position: Position {
+                longitude: longitude.into(),
+                latitude: latitude.into(),
+                altitude_meters: (frame.altitude_feet * METERS_PER_FOOT).into(),
+            },
+            timestamp_network: now,
+            timestamp_asset: None,
+        }))
+    }
+
+    /// Drops the frames that are too old to resolve a position
+    fn prune(&mut self, now: DateTime<Utc>) {
+        if self.frames.len() < MAX_PENDING_AIRCRAFT {
+            return;
+        }
+
+        let cutoff = now - Duration::seconds(CPR_MAX_FRAME_INTERVAL_SECONDS);
+        self.frames.retain(|_, frames| {
+            [frames.even, frames.odd]
+                .iter()
+                .flatten()
+                .any(|frame| frame.received >= cutoff)
+        });
+    }
+}
+
+/// Decodes a hex string into bytes
+#[cfg(test)]
+pub(crate) fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_crc_ok() {
+        let message = from_hex("8D4840D6202CC371C32CE0576098");
+        assert!(crc_ok(&message));
+
+        let mut corrupt = message.clone();
+        corrupt[5] ^= 0x01;
+        assert!(!crc_ok(&corrupt));
+        assert!(!crc_ok(&message[..7]));
+    }
+
+    #[test]
+    fn ut_decode_identification() {
+        let mut decoder = AdsbDecoder::default();
+        let message = from_hex("8D4840D6202CC371C32CE0576098");
+        let Some(Report::Id(id)) = decoder.decode_message(&message, Utc::now()) else {
+            panic!("expected an identification");
+        };
+
+        assert_eq!(id.identifier, Some("4840D6".to_string()));
+        assert_eq!(id.session_id, Some("KLM1023".to_string()));
+        assert_eq!(id.aircraft_type, AircraftType::Undeclared);
+    }
+
+    #[test]
+    fn ut_decode_position() {
+        let mut decoder = AdsbDecoder::default();
+        let now = Utc::now();
+        let odd = from_hex("8D40621D58C386435CC412692AD6");
+        let even = from_hex("8D40621D58C382D690C8AC2863A7");
+
+        // A single frame can't be resolved
+        assert!(decoder.decode_message(&odd, now).is_none());
+
+        let Some(Report::Position(position)) =
+            decoder.decode_message(&even, now + Duration::seconds(1))
+        else {
+            panic!("expected a position");
+        };
+
+        assert_eq!(position.identifier, "40621D");
+        assert!((position.position.latitude.0 - 52.2572).abs() < 1e-4);
+        assert!((position.position.longitude.0 - 3.91937).abs() < 1e-4);
+        assert!((position.position.altitude_meters.0 - 38000.0 * METERS_PER_FOOT).abs() < 1e-6);
+
+        // Frames too far apart are not combined
+        let mut decoder = AdsbDecoder::default();
+        assert!(decoder.decode_message(&odd, now).is_none());
+        assert!(decoder
+            .decode_message(
+                &even,
+                now + Duration::seconds(CPR_MAX_FRAME_INTERVAL_SECONDS + 1)
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn ut_decode_velocity() {
+        let mut decoder = AdsbDecoder::default();
+        let message = from_hex("8D485020994409940838175B284F");
+        let Some(Report::Velocity(velocity)) = decoder.decode_message(&message, Utc::now()) else {
+            panic!("expected a velocity");
+        };
+
+        assert_eq!(velocity.identifier, "485020");
+        assert!((velocity.velocity_horizontal_ground_mps.0 - 159.20 * MPS_PER_KNOT).abs() < 0.01);
+        assert!((velocity.track_angle_degrees.0 - 182.88).abs() < 0.01);
+        assert!((velocity.velocity_vertical_mps.0 + 832.0 * METERS_PER_FOOT / 60.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ut_decode_unsupported() {
+        let mut decoder = AdsbDecoder::default();
+
+        // DF11 all-call reply, wrong length
+        assert!(decoder
+            .decode_message(&from_hex("5D4840D6A1B2C3"), Utc::now())
+            .is_none());
+    }
+
+    #[test]
+    fn ut_longitude_zones() {
+        assert_eq!(longitude_zones(0.0), 59);
+        assert_eq!(longitude_zones(52.0), 36);
+        assert_eq!(longitude_zones(-87.0), 2);
+        assert_eq!(longitude_zones(89.0), 1);
+    }
+
+    #[test]
+    fn ut_aircraft_type() {
+        assert_eq!(aircraft_type(4, 3), AircraftType::Aeroplane);
+        assert_eq!(aircraft_type(4, 7), AircraftType::Rotorcraft);
+        assert_eq!(aircraft_type(3, 1), AircraftType::Glider);
+        assert_eq!(aircraft_type(4, 0), AircraftType::Undeclared);
+    }
+}
@@ -0,0 +1,188 @@
+//! Decoding of the Beast binary format served by ADS-B receivers
+//!
+//! Each frame starts with the 0x1a escape byte and a type byte, followed by
+//!  a 6-byte timestamp, a signal level byte and the Mode S message. Escape
+//!  bytes within a frame are doubled.
+
+use super::adsb::{AdsbDecoder, MESSAGE_LENGTH};
+use super::{Decoder, Report};
+use lib_common::time::{DateTime, Utc};
+
+/// Starts each frame, doubled when part of the frame data
+const ESCAPE: u8 = 0x1a;
+
+/// Length of the timestamp and signal level preceding the message
+const HEADER_LENGTH: usize = 7;
+
+/// Max number of bytes kept while waiting for the rest of a frame
+const MAX_BUFFER_LENGTH: usize = 4096;
+
+/// Gets the length of the frame data following the type byte
+fn data_length(kind: u8) -> Option<usize> {
+    let message_length = match kind {
+        // Mode A/C
+        b'1' => 2,
+        // Mode S short
+        b'2' => 7,
+        // Mode S long
+        b'3' => MESSAGE_LENGTH,
+        _ => return None,
+    };
+
+    Some(HEADER_LENGTH + message_length)
+}
+
+/// Splits a byte stream into the Mode S messages of its frames
+#[derive(Debug, Default)]
+pub struct Framer {
+    buffer: Vec<u8>,
+}
+
+impl Framer {
+    /// Adds received bytes, returning the messages of the frames completed
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut messages = vec![];
+        let mut start = 0;
+        loop {
+            let Some(offset) = self.buffer[start..].iter().position(|b| *b == ESCAPE) else {
+                start = self.buffer.len();
+                break;
+            };
+
+            let sync = start + offset;
+            let Some(kind) = self.buffer.get(sync + 1) else {
+                start = sync;
+                break;
+            };
+
+            // Not the start of a frame, search from the next byte
+            let Some(length) = data_length(*kind) else {
+                start = sync + 1;
+                continue;
+            };
+
+            let mut data = Vec::with_capacity(length);
+            let mut index = sync + 2;
+            let mut corrupt = false;
+            while data.len() < length && index < self.buffer.len() {
+                let byte = self.buffer[index];
+                if byte != ESCAPE {
+                    data.push(byte);
+                    index += 1;
+                    continue;
+                }
+
+                match self.buffer.get(index + 1) {
+                    Some(&ESCAPE) => {
+                        data.push(ESCAPE);
+                        index += 2;
+                    }
+                    // A lone escape byte starts the next frame
+                    Some(_) => {
+                        corrupt = true;
+                        break;
+                    }
+                    None => break,
+                }
+            }
+
+            if corrupt {
+                start = index;
+                continue;
+            }
+
+            if data.len() < length {
+                start = sync;
+                break;
+            }
+
+            messages.push(data.split_off(HEADER_LENGTH));
+            start = index;
+        }
+
+        self.buffer.drain(..start);
+        if self.buffer.len() > MAX_BUFFER_LENGTH {
+            ingest_warn!("dropping {} bytes of incomplete frames.", self.buffer.len());
+            self.buffer.clear();
+        }
+
+        messages
+    }
+}
+
+/// Decodes the extended squitters of a Beast stream
+#[derive(Debug, Default)]
+pub struct BeastDecoder {
+    framer: Framer,
+    adsb: AdsbDecoder,
+}
+
+impl Decoder for BeastDecoder {
+    fn decode(&mut self, bytes: &[u8], now: DateTime<Utc>) -> Vec<Report> {
+        self.framer
+            .push(bytes)
+            .into_iter()
+            .filter(|message| message.len() == MESSAGE_LENGTH)
+            .filter_map(|message| self.adsb.decode_message(&message, now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::adsb::from_hex;
+    use super::*;
+
+    /// Wraps a message in a Beast frame, escaping the escape bytes
+    fn frame(kind: u8, message: &[u8]) -> Vec<u8> {
+        let mut frame = vec![ESCAPE, kind];
+        for byte in [0, 0, 0, 0, ESCAPE, 1, 0x80].iter().chain(message) {
+            frame.push(*byte);
+            if *byte == ESCAPE {
+                frame.push(ESCAPE);
+            }
+        }
+
+        frame
+    }
+
+    #[test]
+    fn ut_framer() {
+        let long = from_hex("8D4840D6202CC371C32CE0576098");
+        let short = from_hex("5D4840D61A2B3C");
+
+        let mut stream = vec![0xFF, 0x00];
+        stream.extend(frame(b'3', &long));
+        stream.extend(frame(b'2', &short));
+        stream.extend(frame(b'1', &[0x12, 0x34]));
+
+        let mut framer = Framer::default();
+        let messages = framer.push(&stream);
+        assert_eq!(messages, vec![long.clone(), short, vec![0x12, 0x34]]);
+        assert!(framer.buffer.is_empty());
+
+        // Frames split across reads
+        let stream = frame(b'3', &long);
+        let (first, second) = stream.split_at(10);
+        assert!(framer.push(first).is_empty());
+        assert_eq!(framer.push(second), vec![long.clone()]);
+
+        // A truncated frame is dropped at the start of the next frame
+        let mut stream = frame(b'3', &long)[..12].to_vec();
+        stream.extend(frame(b'3', &long));
+        assert_eq!(framer.push(&stream), vec![long]);
+    }
+
+    #[test]
+    fn ut_beast_decoder() {
+        let mut decoder = BeastDecoder::default();
+        let mut stream = frame(b'3', &from_hex("8D4840D6202CC371C32CE0576098"));
+        stream.extend(frame(b'2', &from_hex("5D4840D61A2B3C")));
+
+        let reports = decoder.decode(&stream, Utc::now());
+        assert_eq!(reports.len(), 1);
+        assert!(matches!(reports[0], Report::Id(_)));
+    }
+}
@@ -0,0 +1,3 @@
+//! log macro's for ingest logging
+use lib_common::log_macros;
+log_macros!("ingest");
@@ -0,0 +1,291 @@
+//! Ingestion of aircraft broadcasts
+//!
+//! Aircraft that don't report to the network can still be tracked through
+//!  their ADS-B or Remote ID broadcasts. The adapters in this module decode
+//!  the broadcasts picked up by local receivers and push the results onto
+//!  the aircraft queues, where the consumers process them like the reports
+//!  of any other aircraft.
+
+#[macro_use]
+pub mod macros;
+
+#[cfg(feature = "adsb")]
+pub mod adsb;
+#[cfg(feature = "adsb")]
+pub mod beast;
+#[cfg(feature = "remote_id")]
+pub mod remote_id;
+#[cfg(feature = "adsb")]
+pub mod sbs;
+
+use crate::cache::pool::{CacheError, RedisPool};
+use crate::types::{
+    AircraftId, AircraftPosition, AircraftVelocity, REDIS_KEY_AIRCRAFT_ID,
+    REDIS_KEY_AIRCRAFT_POSITION, REDIS_KEY_AIRCRAFT_VELOCITY,
+};
+use lib_common::time::{DateTime, Utc};
+use tokio::task::JoinHandle;
+
+/// Meters in a foot
+pub const METERS_PER_FOOT: f64 = 0.3048;
+
+/// Meters per second in a knot
+pub const MPS_PER_KNOT: f64 = 1852.0 / 3600.0;
+
+/// Time to wait before reconnecting to a receiver
+const RECONNECT_DELAY_MS: u64 = 5000;
+
+/// Size of the buffer receiving data from a receiver
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// An item decoded from an aircraft broadcast
+#[derive(Debug, Clone)]
+pub enum Report {
+    /// Identification of an aircraft
+    Id(AircraftId),
+
+    /// Position of an aircraft
+    Position(AircraftPosition),
+
+    /// Velocity of an aircraft
+    Velocity(AircraftVelocity),
+}
+
+/// Decodes the data received from a receiver into reports
+pub trait Decoder {
+    /// Decodes the received bytes, keeping incomplete messages until
+    ///  the rest of their bytes are received
+    fn decode(&mut self, bytes: &[u8], now: DateTime<Utc>) -> Vec<Report>;
+}
+
+/// Reports sorted by the queue they are pushed to
+#[derive(Debug, Default)]
+struct Batch {
+    ids: Vec<AircraftId>,
+    positions: Vec<AircraftPosition>,
+    velocities: Vec<AircraftVelocity>,
+}
+
+impl From<Vec<Report>> for Batch {
+    fn from(reports: Vec<Report>) -> Self {
+        let mut batch = Batch::default();
+        for report in reports {
+            match report {
+                Report::Id(item) => batch.ids.push(item),
+                Report::Position(item) => batch.positions.push(item),
+                Report::Velocity(item) => batch.velocities.push(item),
+            }
+        }
+
+        batch
+    }
+}
+
+/// The aircraft queues decoded reports are pushed to
+#[derive(Debug, Clone)]
+pub struct Queues {
+    id: RedisPool,
+    position: RedisPool,
+    velocity: RedisPool,
+}
+
+impl Queues {
+    /// Connects to the aircraft queues
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn new(config: &crate::config::Config) -> Result<Self, ()> {
+        Ok(Queues {
+            id: RedisPool::new(config, REDIS_KEY_AIRCRAFT_ID).await?,
+            position: RedisPool::new(config, REDIS_KEY_AIRCRAFT_POSITION).await?,
+            velocity: RedisPool::new(config, REDIS_KEY_AIRCRAFT_VELOCITY).await?,
+        })
+    }
+
+    /// Pushes reports onto their queues
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) needs redis backend to integration test
+    pub async fn push(&self, reports: Vec<Report>) -> Result<(), CacheError> {
+        let batch = Batch::from(reports);
+        self.id.push(&batch.ids).await?;
+        self.position.push(&batch.positions).await?;
+        self.velocity.push(&batch.velocities).await
+    }
+}
+
+/// Reads a TCP stream from a receiver, reconnecting when it closes
+#[cfg(feature = "adsb")]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs a receiver to integration test
+async fn run_tcp<D>(address: String, mut decoder: D, queues: Queues)
+where
+    D: Decoder,
+{
+    use tokio::io::AsyncReadExt;
+
+    let delay = tokio::time::Duration::from_millis(RECONNECT_DELAY_MS);
+    let mut buffer = [0u8; READ_BUFFER_SIZE];
+    loop {
+        let mut stream = match tokio::net::TcpStream::connect(&address).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                ingest_warn!("could not connect to receiver at {address}: {e}");
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+        };
+
+        ingest_info!("connected to receiver at {address}.");
+        loop {
+            let count = match stream.read(&mut buffer).await {
+                Ok(0) => {
+                    ingest_warn!("receiver at {address} closed the connection.");
+                    break;
+                }
+                Ok(count) => count,
+                Err(e) => {
+                    ingest_warn!("could not read from receiver at {address}: {e}");
+                    break;
+                }
+            };
+
+            let reports = decoder.decode(&buffer[..count], Utc::now());
+            if let Err(e) = queues.push(reports).await {
+                ingest_error!("could not push reports from {address}: {e}");
+            }
+        }
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Receives datagrams on a UDP socket, each holding whole messages
+#[cfg(feature = "remote_id")]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs a receiver to integration test
+async fn run_udp<D>(address: String, mut decoder: D, queues: Queues)
+where
+    D: Decoder,
+{
+    let delay = tokio::time::Duration::from_millis(RECONNECT_DELAY_MS);
+    let mut buffer = [0u8; READ_BUFFER_SIZE];
+    loop {
+        let socket = match tokio::net::UdpSocket::bind(&address).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                ingest_error!("could not bind to {address}: {e}");
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+        };
+
+        ingest_info!("listening on {address}.");
+        loop {
+            let count = match socket.recv(&mut buffer).await {
+                Ok(count) => count,
+                Err(e) => {
+                    ingest_warn!("could not receive on {address}: {e}");
+                    break;
+                }
+            };
+
+            let reports = decoder.decode(&buffer[..count], Utc::now());
+            if let Err(e) = queues.push(reports).await {
+                ingest_error!("could not push reports from {address}: {e}");
+            }
+        }
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Starts an adapter for each configured source
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs redis backend and receivers to integration test
+pub async fn start(config: &crate::config::Config) -> Result<Vec<JoinHandle<()>>, ()> {
+    let queues = Queues::new(config).await.map_err(|_| {
+        ingest_error!("could not connect to the aircraft queues.");
+    })?;
+
+    #[allow(unused_mut)]
+    let mut handles = vec![];
+
+    #[cfg(feature = "adsb")]
+    {
+        if !config.adsb_sbs_address.is_empty() {
+            handles.push(tokio::spawn(run_tcp(
+                config.adsb_sbs_address.clone(),
+                sbs::SbsDecoder::default(),
+                queues.clone(),
+            )));
+        }
+
+        if !config.adsb_beast_address.is_empty() {
+            handles.push(tokio::spawn(run_tcp(
+                config.adsb_beast_address.clone(),
+                beast::BeastDecoder::default(),
+                queues.clone(),
+            )));
+        }
+    }
+
+    #[cfg(feature = "remote_id")]
+    {
+        if !config.remote_id_udp_address.is_empty() {
+            handles.push(tokio::spawn(run_udp(
+                config.remote_id_udp_address.clone(),
+                remote_id::RemoteIdDecoder,
+                queues.clone(),
+            )));
+        }
+    }
+
+    ingest_info!("started {} adapter(s).", handles.len());
+    Ok(handles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AircraftType, Position};
+
+    #[test]
+    fn ut_batch_from_reports() {
+        let now = Utc::now();
+        let reports = vec![
+            Report::Id(AircraftId {
+                identifier: Some("A1B2C3".to_string()),
+                session_id: None,
+                aircraft_type: AircraftType::Aeroplane,
+                timestamp_network: now,
+                timestamp_asset: None,
+                session_ended: false,
+            }),
+            Report::Position(AircraftPosition {
+                identifier: "A1B2C3".to_string(),
+                position: Position {
+                    longitude: 3.9.into(),
+                    latitude: 52.2.into(),
+                    altitude_meters: 100.0.into(),
+                },
+                timestamp_network: now,
+                timestamp_asset: None,
+            }),
+            Report::Position(AircraftPosition {
+                identifier: "A1B2C4".to_string(),
+                position: Position {
+                    longitude: 3.8.into(),
+                    latitude: 52.1.into(),
+                    altitude_meters: 90.0.into(),
+                },
+                timestamp_network: now,
+                timestamp_asset: None,
+            }),
+        ];
+
+        let batch = Batch::from(reports);
+        assert_eq!(batch.ids.len(), 1);
+        assert_eq!(batch.positions.len(), 2);
+        assert!(batch.velocities.is_empty());
+        assert_eq!(batch.positions[1].identifier, "A1B2C4");
+    }
+}
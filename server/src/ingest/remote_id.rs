@@ -0,0 +1,405 @@
+//! Decoding of ASTM F3411 Remote ID broadcasts
+//!
+//! Receivers forward the 25-byte messages they pick up over Bluetooth or
+//!  Wi-Fi, one datagram per broadcast. Location messages don't carry the
+//!  identity of the aircraft, so they are only decoded along with a Basic ID
+//!  message from the same datagram, as sent in a message pack.
+
+use super::{Decoder, Report};
+use crate::types::{AircraftId, AircraftPosition, AircraftType, AircraftVelocity, Position};
+use lib_common::time::{DateTime, Duration, TimeZone, Utc};
+use num_traits::FromPrimitive;
+
+/// Length of a Remote ID message in bytes
+pub const MESSAGE_LENGTH: usize = 25;
+
+/// Length of the UAS ID field of a Basic ID message
+const UAS_ID_LENGTH: usize = 20;
+
+/// Length of a UTM-assigned UUID
+const UUID_LENGTH: usize = 16;
+
+/// Header length of a message pack, before its messages
+const PACK_HEADER_LENGTH: usize = 3;
+
+/// Remote ID message types
+mod message_type {
+    pub const BASIC_ID: u8 = 0x0;
+    pub const LOCATION: u8 = 0x1;
+    pub const MESSAGE_PACK: u8 = 0xF;
+}
+
+/// Remote ID identification types, by order of preference
+mod id_type {
+    pub const SERIAL_NUMBER: u8 = 1;
+    pub const CAA_REGISTRATION: u8 = 2;
+    pub const UTM_UUID: u8 = 3;
+    pub const SPECIFIC_SESSION: u8 = 4;
+}
+
+/// Encoded values of unknown location fields
+mod unknown {
+    pub const TRACK: u16 = 361;
+    pub const SPEED: u8 = 255;
+    pub const VERTICAL_SPEED: i8 = 63;
+    pub const ALTITUDE: u16 = 0;
+    pub const TIMESTAMP: u16 = 0xFFFF;
+}
+
+/// The identity of the aircraft broadcasting a Basic ID message
+#[derive(Debug, Clone, PartialEq)]
+struct BasicId {
+    id_type: u8,
+    identifier: String,
+    aircraft_type: AircraftType,
+}
+
+/// The content of a Location message
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Location {
+    latitude: f64,
+    longitude: f64,
+    altitude_meters: f64,
+    track_degrees: Option<f64>,
+    speed_mps: Option<f64>,
+    vertical_speed_mps: f64,
+    tenths_since_hour: Option<u16>,
+}
+
+/// Reads a little-endian u16 at the given offset
+fn u16_at(message: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([message[offset], message[offset + 1]])
+}
+
+/// Reads a little-endian i32 at the given offset
+fn i32_at(message: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes([
+        message[offset],
+        message[offset + 1],
+        message[offset + 2],
+        message[offset + 3],
+    ])
+}
+
+/// Decodes a Basic ID message
+fn basic_id(message: &[u8]) -> Option<BasicId> {
+    let id_type = message[1] >> 4;
+    let aircraft_type = AircraftType::from_u8(message[1] & 0x0F)?;
+    let field = &message[2..2 + UAS_ID_LENGTH];
+    let identifier = match id_type {
+        id_type::UTM_UUID => field[..UUID_LENGTH]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>(),
+        id_type::SERIAL_NUMBER | id_type::CAA_REGISTRATION | id_type::SPECIFIC_SESSION => {
+            let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).trim().to_string()
+        }
+        _ => return None,
+    };
+
+    (!identifier.is_empty()).then_some(BasicId {
+        id_type,
+        identifier,
+        aircraft_type,
+    })
+}
+
+/// Decodes an encoded altitude, `None` if unknown
+fn altitude(raw: u16) -> Option<f64> {
+    (raw != unknown::ALTITUDE).then_some(raw as f64 * 0.5 - 1000.0)
+}
+
+/// Decodes a Location message
+fn location(message: &[u8]) -> Option<Location> {
+    let flags = message[1];
+    let latitude = i32_at(message, 5) as f64 * 1e-7;
+    let longitude = i32_at(message, 9) as f64 * 1e-7;
+    if (latitude == 0.0 && longitude == 0.0)
+        || !(-90.0..=90.0).contains(&latitude)
+        || !(-180.0..=180.0).contains(&longitude)
+    {
+        return None;
+    }
+
+    // Prefer the geodetic altitude over the pressure altitude
+    let altitude_meters = altitude(u16_at(message, 15)).or(altitude(u16_at(message, 13)))?;
+
+    let track = message[2] as u16 + if flags & 0x02 != 0 { 180 } else { 0 };
+    let track_degrees = (track < unknown::TRACK).then_some(track as f64);
+
+    let speed = message[3];
+    let speed_mps = match (speed, flags & 0x01) {
+        (unknown::SPEED, _) => None,
+        (speed, 0) => Some(speed as f64 * 0.25),
+        (speed, _) => Some(speed as f64 * 0.75 + 255.0 * 0.25),
+    };
+
+    let vertical_speed = message[4] as i8;
+    let vertical_speed_mps = match vertical_speed {
+        unknown::VERTICAL_SPEED => 0.0,
+        speed => speed as f64 * 0.5,
+    };
+
+    let timestamp = u16_at(message, 21);
+    let tenths_since_hour = (timestamp != unknown::TIMESTAMP).then_some(timestamp);
+
+    Some(Location {
+        latitude,
+        longitude,
+        altitude_meters,
+        track_degrees,
+        speed_mps,
+        vertical_speed_mps,
+        tenths_since_hour,
+    })
+}
+
+/// Resolves a timestamp in tenths of seconds since the hour, assuming
+///  the message was sent less than an hour before it was received
+fn asset_timestamp(tenths_since_hour: u16, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let seconds = now.timestamp();
+    let hour = Utc
+        .timestamp_opt(seconds - seconds.rem_euclid(3600), 0)
+        .single()?;
+    let timestamp = hour + Duration::try_milliseconds(tenths_since_hour as i64 * 100)?;
+    if timestamp > now {
+        Some(timestamp - Duration::try_hours(1)?)
+    } else {
+        Some(timestamp)
+    }
+}
+
+/// Splits a datagram into its messages, unpacking message packs
+fn messages(datagram: &[u8]) -> Vec<&[u8]> {
+    let Some(header) = datagram.first() else {
+        return vec![];
+    };
+
+    if header >> 4 != message_type::MESSAGE_PACK {
+        return datagram.chunks_exact(MESSAGE_LENGTH).collect();
+    }
+
+    let (Some(&size), Some(&count)) = (datagram.get(1), datagram.get(2)) else {
+        return vec![];
+    };
+
+    if size as usize != MESSAGE_LENGTH {
+        ingest_warn!("unsupported message pack size {size}.");
+        return vec![];
+    }
+
+    datagram[PACK_HEADER_LENGTH..]
+        .chunks_exact(MESSAGE_LENGTH)
+        .take(count as usize)
+        .collect()
+}
+
+/// Decodes the messages of a single Remote ID broadcast
+pub fn decode_datagram(datagram: &[u8], now: DateTime<Utc>) -> Vec<Report> {
+    let mut ids = vec![];
+    let mut locations = vec![];
+    for message in messages(datagram) {
+        match message[0] >> 4 {
+            message_type::BASIC_ID => ids.extend(basic_id(message)),
+            message_type::LOCATION => locations.extend(location(message)),
+            _ => (),
+        }
+    }
+
+    let Some(id) = ids.into_iter().min_by_key(|id| id.id_type) else {
+        return vec![];
+    };
+
+    let mut reports = vec![Report::Id(AircraftId {
+        identifier: Some(id.identifier.clone()),
+        session_id: None,
+        aircraft_type: id.aircraft_type,
+        timestamp_network: now,
+        timestamp_asset: None,
+        session_ended: false,
+    })];
+
+    for location in locations {
+        let timestamp_asset = location
+            .tenths_since_hour
+            .and_then(|tenths| asset_timestamp(tenths, now));
+
+        reports.push(Report::Position(AircraftPosition {
+            identifier: id.identifier.clone(),
+            position: Position {
+                longitude: location.longitude.into(),
+                latitude: location.latitude.into(),
+                altitude_meters: location.altitude_meters.into(),
+            },
+            timestamp_network: now,
+            timestamp_asset,
+        }));
+
+        let (Some(speed_mps), Some(track_degrees)) = (location.speed_mps, location.track_degrees)
+        else {
+            continue;
+        };
+
+        reports.push(Report::Velocity(AircraftVelocity {
+            identifier: id.identifier.clone(),
+            velocity_horizontal_ground_mps: speed_mps.into(),
+            velocity_horizontal_air_mps: None,
+            velocity_vertical_mps: location.vertical_speed_mps.into(),
+            track_angle_degrees: track_degrees.into(),
+            timestamp_network: now,
+            timestamp_asset,
+        }));
+    }
+
+    reports
+}
+
+/// Decodes Remote ID datagrams
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RemoteIdDecoder;
+
+impl Decoder for RemoteIdDecoder {
+    fn decode(&mut self, bytes: &[u8], now: DateTime<Utc>) -> Vec<Report> {
+        decode_datagram(bytes, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn basic_id_message(id_type: u8, ua_type: u8, uas_id: &[u8]) -> Vec<u8> {
+        let mut message = vec![0u8; MESSAGE_LENGTH];
+        message[0] = (message_type::BASIC_ID << 4) | 0x2;
+        message[1] = (id_type << 4) | ua_type;
+        message[2..2 + uas_id.len()].copy_from_slice(uas_id);
+        message
+    }
+
+    fn location_message(latitude: f64, longitude: f64, altitude_meters: f64) -> Vec<u8> {
+        let encoded_altitude = ((altitude_meters + 1000.0) / 0.5) as u16;
+        let mut message = vec![0u8; MESSAGE_LENGTH];
+        message[0] = (message_type::LOCATION << 4) | 0x2;
+
+        // Airborne, track of 200 degrees
+        message[1] = 0x20 | 0x02;
+        message[2] = 20;
+        // 10 m/s
+        message[3] = 40;
+        // 1.5 m/s climb
+        message[4] = 3;
+        message[5..9].copy_from_slice(&((latitude * 1e7) as i32).to_le_bytes());
+        message[9..13].copy_from_slice(&((longitude * 1e7) as i32).to_le_bytes());
+        message[13..15].copy_from_slice(&encoded_altitude.to_le_bytes());
+        message[15..17].copy_from_slice(&encoded_altitude.to_le_bytes());
+        // 12:34.5 past the hour
+        message[21..23].copy_from_slice(&7545u16.to_le_bytes());
+        message
+    }
+
+    fn pack(messages: &[Vec<u8>]) -> Vec<u8> {
+        let mut datagram = vec![
+            (message_type::MESSAGE_PACK << 4) | 0x2,
+            MESSAGE_LENGTH as u8,
+            messages.len() as u8,
+        ];
+        messages.iter().for_each(|m| datagram.extend(m));
+        datagram
+    }
+
+    #[test]
+    fn ut_decode_pack() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 40, 0).unwrap();
+        let datagram = pack(&[
+            basic_id_message(id_type::CAA_REGISTRATION, 2, b"N-12345"),
+            basic_id_message(id_type::SERIAL_NUMBER, 2, b"1596F123456789"),
+            location_message(52.5, 13.4, 120.0),
+        ]);
+
+        let reports = decode_datagram(&datagram, now);
+        assert_eq!(reports.len(), 3);
+
+        let Report::Id(id) = &reports[0] else {
+            panic!("expected an identification");
+        };
+        assert_eq!(id.identifier, Some("1596F123456789".to_string()));
+        assert_eq!(id.aircraft_type, AircraftType::Rotorcraft);
+
+        let Report::Position(position) = &reports[1] else {
+            panic!("expected a position");
+        };
+        assert_eq!(position.identifier, "1596F123456789");
+        assert!((position.position.latitude.0 - 52.5).abs() < 1e-6);
+        assert!((position.position.longitude.0 - 13.4).abs() < 1e-6);
+        assert_eq!(position.position.altitude_meters.0, 120.0);
+        assert_eq!(
+            position.timestamp_asset,
+            Some(
+                Utc.with_ymd_and_hms(2024, 3, 1, 12, 12, 34).unwrap() + Duration::milliseconds(500)
+            )
+        );
+
+        let Report::Velocity(velocity) = &reports[2] else {
+            panic!("expected a velocity");
+        };
+        assert_eq!(velocity.track_angle_degrees.0, 200.0);
+        assert_eq!(velocity.velocity_horizontal_ground_mps.0, 10.0);
+        assert_eq!(velocity.velocity_vertical_mps.0, 1.5);
+    }
+
+    #[test]
+    fn ut_decode_unlinked_location() {
+        let datagram = location_message(52.5, 13.4, 120.0);
+        assert!(decode_datagram(&datagram, Utc::now()).is_empty());
+        assert!(decode_datagram(&[], Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn ut_basic_id() {
+        let uuid = (0..16).collect::<Vec<u8>>();
+        let id = basic_id(&basic_id_message(id_type::UTM_UUID, 0, &uuid)).unwrap();
+        assert_eq!(id.identifier, "000102030405060708090a0b0c0d0e0f");
+        assert_eq!(id.aircraft_type, AircraftType::Undeclared);
+
+        // No identification
+        assert!(basic_id(&basic_id_message(0, 2, b"1596F123456789")).is_none());
+        assert!(basic_id(&basic_id_message(id_type::SERIAL_NUMBER, 2, b"")).is_none());
+    }
+
+    #[test]
+    fn ut_location_unknown_fields() {
+        let mut message = location_message(52.5, 13.4, 120.0);
+        message[2] = 181;
+        message[3] = unknown::SPEED;
+        message[4] = unknown::VERTICAL_SPEED as u8;
+        message[21..23].copy_from_slice(&unknown::TIMESTAMP.to_le_bytes());
+        let decoded = location(&message).unwrap();
+        assert_eq!(decoded.track_degrees, None);
+        assert_eq!(decoded.speed_mps, None);
+        assert_eq!(decoded.vertical_speed_mps, 0.0);
+        assert_eq!(decoded.tenths_since_hour, None);
+
+        // Falls back to the pressure altitude
+        message[15..17].copy_from_slice(&unknown::ALTITUDE.to_le_bytes());
+        assert_eq!(location(&message).unwrap().altitude_meters, 120.0);
+
+        // Unknown position
+        assert!(location(&location_message(0.0, 0.0, 120.0)).is_none());
+    }
+
+    #[test]
+    fn ut_asset_timestamp() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 30).unwrap();
+        assert_eq!(
+            asset_timestamp(200, now),
+            Some(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 20).unwrap())
+        );
+
+        // Sent before the hour
+        assert_eq!(
+            asset_timestamp(35990, now),
+            Some(Utc.with_ymd_and_hms(2024, 3, 1, 11, 59, 59).unwrap())
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Decoding of the SBS (BaseStation) text format served by ADS-B receivers
+//!
+//! Each line is a comma-separated message. Only the transmission messages
+//!  with identification (MSG,1), airborne position (MSG,3) and airborne
+//!  velocity (MSG,4) are decoded. Receivers report times in their local
+//!  time zone, so the asset timestamp is left empty.
+
+use super::{Decoder, Report, METERS_PER_FOOT, MPS_PER_KNOT};
+use crate::types::{AircraftId, AircraftPosition, AircraftType, AircraftVelocity, Position};
+use lib_common::time::{DateTime, Utc};
+
+/// Max number of bytes kept while waiting for the end of a line
+const MAX_LINE_LENGTH: usize = 1024;
+
+/// Indices of the fields of a message
+mod field {
+    pub const MESSAGE_TYPE: usize = 0;
+    pub const TRANSMISSION_TYPE: usize = 1;
+    pub const HEX_IDENT: usize = 4;
+    pub const CALLSIGN: usize = 10;
+    pub const ALTITUDE: usize = 11;
+    pub const GROUND_SPEED: usize = 12;
+    pub const TRACK: usize = 13;
+    pub const LATITUDE: usize = 14;
+    pub const LONGITUDE: usize = 15;
+    pub const VERTICAL_RATE: usize = 16;
+}
+
+/// Parses a numeric field, `None` if empty or invalid
+fn number(fields: &[&str], index: usize) -> Option<f64> {
+    fields.get(index)?.trim().parse::<f64>().ok()
+}
+
+/// Decodes a single message
+pub fn decode_line(line: &str, now: DateTime<Utc>) -> Option<Report> {
+    let fields = line.trim().split(',').collect::<Vec<&str>>();
+    if fields.get(field::MESSAGE_TYPE) != Some(&"MSG") {
+        return None;
+    }
+
+    let identifier = fields.get(field::HEX_IDENT)?.trim().to_uppercase();
+    if identifier.is_empty() {
+        return None;
+    }
+
+    match *fields.get(field::TRANSMISSION_TYPE)? {
+        "1" => {
+            let callsign = fields.get(field::CALLSIGN)?.trim();
+            Some(Report::Id(AircraftId {
+                identifier: Some(identifier),
+                session_id: (!callsign.is_empty()).then(|| callsign.to_string()),
+                aircraft_type: AircraftType::Undeclared,
+                timestamp_network: now,
+                timestamp_asset: None,
+                session_ended: false,
+            }))
+        }
+        "3" => Some(Report::Position(AircraftPosition {
+            identifier,
+            position: Position {
+                longitude: number(&fields, field::LONGITUDE)?.into(),
+                latitude: number(&fields, field::LATITUDE)?.into(),
+                altitude_meters: (number(&fields, field::ALTITUDE)? * METERS_PER_FOOT).into(),
+            },
+            timestamp_network: now,
+            timestamp_asset: None,
+        })),
+        "4" => {
+            // Vertical rate in feet per minute
+            let vertical_rate = number(&fields, field::VERTICAL_RATE).unwrap_or(0.0);
+            Some(Report::Velocity(AircraftVelocity {
+                identifier,
+                velocity_horizontal_ground_mps: (number(&fields, field::GROUND_SPEED)?
+                    * MPS_PER_KNOT)
+                    .into(),
+                velocity_horizontal_air_mps: None,
+                velocity_vertical_mps: (vertical_rate * METERS_PER_FOOT / 60.0).into(),
+                track_angle_degrees: number(&fields, field::TRACK)?.into(),
+                timestamp_network: now,
+                timestamp_asset: None,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Decodes the lines of an SBS stream
+#[derive(Debug, Default)]
+pub struct SbsDecoder {
+    buffer: Vec<u8>,
+}
+
+impl Decoder for SbsDecoder {
+    fn decode(&mut self, bytes: &[u8], now: DateTime<Utc>) -> Vec<Report> {
+        self.buffer.extend_from_slice(bytes);
+        let Some(end) = self.buffer.iter().rposition(|b| *b == b'\n') else {
+            if self.buffer.len() > MAX_LINE_LENGTH {
+                ingest_warn!("dropping {} bytes without a line end.", self.buffer.len());
+                self.buffer.clear();
+            }
+
+            return vec![];
+        };
+
+        let lines = self.buffer.drain(..=end).collect::<Vec<u8>>();
+        String::from_utf8_lossy(&lines)
+            .lines()
+            .filter_map(|line| decode_line(line, now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTIFICATION: &str =
+        "MSG,1,1,1,4840d6,1,2024/03/01,12:00:00.000,2024/03/01,12:00:00.000,KLM1023 ,,,,,,,,,,,0";
+    const POSITION: &str = "MSG,3,1,1,40621D,1,2024/03/01,12:00:00.000,2024/03/01,12:00:00.000,,38000,,,52.25720,3.91937,,,0,0,0,0";
+    const VELOCITY: &str = "MSG,4,1,1,485020,1,2024/03/01,12:00:00.000,2024/03/01,12:00:00.000,,,159.2,182.9,,,-832,,,,,0";
+
+    #[test]
+    fn ut_decode_line() {
+        let now = Utc::now();
+        let Some(Report::Id(id)) = decode_line(IDENTIFICATION, now) else {
+            panic!("expected an identification");
+        };
+        assert_eq!(id.identifier, Some("4840D6".to_string()));
+        assert_eq!(id.session_id, Some("KLM1023".to_string()));
+
+        let Some(Report::Position(position)) = decode_line(POSITION, now) else {
+            panic!("expected a position");
+        };
+        assert_eq!(position.identifier, "40621D");
+        assert_eq!(position.position.latitude.0, 52.2572);
+        assert_eq!(position.position.longitude.0, 3.91937);
+        assert_eq!(
+            position.position.altitude_meters.0,
+            38000.0 * METERS_PER_FOOT
+        );
+
+        let Some(Report::Velocity(velocity)) = decode_line(VELOCITY, now) else {
+            panic!("expected a velocity");
+        };
+        assert_eq!(velocity.identifier, "485020");
+        assert_eq!(velocity.track_angle_degrees.0, 182.9);
+        assert_eq!(
+            velocity.velocity_horizontal_ground_mps.0,
+            159.2 * MPS_PER_KNOT
+        );
+
+        // Surface positions and other message types are ignored
+        assert!(decode_line("MSG,2,1,1,40621D,1,,,,,,0,10,90,52.0,3.9,,,,,,1", now).is_none());
+        assert!(decode_line("STA,,1,1,40621D,1,,,,,RM", now).is_none());
+
+        // Missing coordinates
+        assert!(decode_line("MSG,3,1,1,40621D,1,,,,,,38000,,,,,,,0,0,0,0", now).is_none());
+    }
+
+    #[test]
+    fn ut_sbs_decoder() {
+        let mut decoder = SbsDecoder::default();
+        let stream = format!("{IDENTIFICATION}\r\n{POSITION}\r\n{VELOCITY}");
+        let (first, second) = stream.split_at(100);
+
+        let now = Utc::now();
+        assert_eq!(decoder.decode(first.as_bytes(), now).len(), 1);
+        assert_eq!(decoder.decode(second.as_bytes(), now).len(), 1);
+
+        // The last line is complete once its line end is received
+        assert_eq!(decoder.decode(b"\r\n", now).len(), 1);
+        assert!(decoder.buffer.is_empty());
+    }
+}
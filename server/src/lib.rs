@@ -7,13 +7,24 @@ pub mod test_util;
 pub mod cache;
 pub mod config;
 pub mod grpc;
+#[cfg(any(feature = "adsb", feature = "remote_id"))]
+pub mod ingest;
 pub mod postgis;
+pub mod reload;
 
 /// Types used with svc-gis Redis queues
 pub mod types {
     include!("../../common/types.rs");
 }
 
+/// Canned airspace scenarios shared by the tests and examples
+#[cfg(any(test, feature = "mock"))]
+pub mod fixtures {
+    use crate::grpc::server::grpc_server::*;
+    use crate::types::AircraftType;
+    include!("../../common/fixtures.rs");
+}
+
 pub use crate::config::Config;
 
 /// Tokio signal handler that will wait for a user to press CTRL+C.
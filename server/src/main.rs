@@ -1,25 +1,56 @@
 //! Main function starting the server and initializing dependencies.
 
 use crate::types::{
-    AircraftId, AircraftPosition, AircraftVelocity, REDIS_KEY_AIRCRAFT_ID,
-    REDIS_KEY_AIRCRAFT_POSITION, REDIS_KEY_AIRCRAFT_VELOCITY,
+    AircraftId, AircraftPosition, AircraftVelocity, ZoneUpdate, REDIS_KEY_AIRCRAFT_ID,
+    REDIS_KEY_AIRCRAFT_POSITION, REDIS_KEY_AIRCRAFT_TRACK, REDIS_KEY_AIRCRAFT_VELOCITY,
+    REDIS_KEY_ZONE,
 };
+use cache::pool::RedisPool;
 use cache::Consumer;
+use clap::Parser;
 use lib_common::logger::load_logger_config_from_file;
 use log::info;
-use svc_gis::cache::IsConsumer;
+use svc_gis::cache::{IsConsumer, IsReliableConsumer};
 use svc_gis::*;
 use tokio::task::JoinHandle;
 
+/// Connects the cache of recent aircraft tracks, written by the position
+///  consumer and read when getting flights
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) needs running backend, integration tests
+async fn start_track_cache(config: &Config) -> Result<(), ()> {
+    let track_cache = RedisPool::new(config, REDIS_KEY_AIRCRAFT_TRACK).await?;
+    cache::TRACK_CACHE.set(track_cache).map_err(|_| {
+        log::error!("(start_track_cache) could not set TRACK_CACHE.");
+    })
+}
+
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (Rnever) needs running backend, integration tests, these spin up threads
 async fn start_redis_consumers(config: &Config) -> Result<Vec<JoinHandle<Result<(), ()>>>, ()> {
     //
     // Aircraft
     //
-    let mut id_consumer = Consumer::new(config, REDIS_KEY_AIRCRAFT_ID, 500).await?;
-    let mut position_consumer = Consumer::new(config, REDIS_KEY_AIRCRAFT_POSITION, 100).await?;
-    let mut velocity_consumer = Consumer::new(config, REDIS_KEY_AIRCRAFT_VELOCITY, 100).await?;
+    let state_stream = &config.redis_aircraft_state_stream;
+    let mut id_consumer = Consumer::new(config, REDIS_KEY_AIRCRAFT_ID, 500)
+        .await?
+        .with_publisher(config, state_stream)
+        .await?;
+
+    let mut position_consumer = Consumer::new(config, REDIS_KEY_AIRCRAFT_POSITION, 100)
+        .await?
+        .with_publisher(config, state_stream)
+        .await?;
+
+    let mut velocity_consumer = Consumer::new(config, REDIS_KEY_AIRCRAFT_VELOCITY, 100)
+        .await?
+        .with_publisher(config, state_stream)
+        .await?;
+
+    //
+    // Zones
+    //
+    let mut zone_consumer = Consumer::new(config, REDIS_KEY_ZONE, 1000).await?;
 
     let handles = vec![
         tokio::spawn(
@@ -31,16 +62,80 @@ async fn start_redis_consumers(config: &Config) -> Result<Vec<JoinHandle<Result<
         tokio::spawn(async move {
             <Consumer as IsConsumer<AircraftVelocity>>::begin(&mut velocity_consumer).await
         }),
+        tokio::spawn(async move {
+            <Consumer as IsReliableConsumer<ZoneUpdate>>::begin_reliable(&mut zone_consumer).await
+        }),
     ];
 
     Ok(handles)
 }
 
+/// Command line arguments
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Run a synthetic scenario against the backends in a temporary schema,
+    ///  report whether it passed and exit
+    #[arg(long)]
+    verify: bool,
+
+    /// Run a one-off command instead of the server
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// One-off commands
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Push the aircraft reports of a JSONL dump onto the Redis queues at
+    ///  their recorded pace and exit
+    ReplayTelemetry {
+        /// Dump to replay, one report per line
+        #[arg(long)]
+        input: std::path::PathBuf,
+
+        /// Factor of the recorded pace, zero to send as fast as possible
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+
+        /// Send the reports with their recorded timestamps instead of
+        ///  moving them to the time they are sent
+        #[arg(long)]
+        original_timestamps: bool,
+    },
+
+    /// Write the flights overlapping a time range to a file and exit
+    #[cfg(feature = "export")]
+    ExportFlights {
+        /// Start of the time range (RFC 3339)
+        #[arg(long)]
+        from: lib_common::time::DateTime<lib_common::time::Utc>,
+
+        /// End of the time range (RFC 3339)
+        #[arg(long)]
+        to: lib_common::time::DateTime<lib_common::time::Utc>,
+
+        /// File to write
+        #[arg(long)]
+        output: std::path::PathBuf,
+
+        /// Format of the file
+        #[arg(long, value_enum, default_value_t = postgis::export::Format::Parquet)]
+        format: postgis::export::Format,
+
+        /// Also export the flights moved to the archive table
+        #[arg(long)]
+        include_archived: bool,
+    },
+}
+
 /// Main entry point: starts gRPC Server on specified address and port
 #[tokio::main]
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (Rnever) main entry point of the application
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
     // Will use default config settings if no environment vars are found.
     let config = Config::try_from_env()
         .map_err(|e| format!("Failed to load configuration from environment: {}", e))?;
@@ -51,10 +146,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await
         .or_else(|e| Ok::<(), String>(log::error!("(main) {}", e)))?;
 
+    // Replays only need the Redis queues
+    if let Some(Command::ReplayTelemetry {
+        input,
+        speed,
+        original_timestamps,
+    }) = &cli.command
+    {
+        let result = cache::replay::replay(&config, input, *speed, *original_timestamps).await;
+        log::logger().flush();
+        return result.map(|_| ()).map_err(Into::into);
+    }
+
     info!("(main) Server startup.");
 
     // Create pool from PostgreSQL environment variables
-    let pool = postgis::pool::create_pool(config.clone()).map_err(|e| {
+    let pool = postgis::pool::FailoverPool::new(config.clone()).map_err(|e| {
         let error = format!("Could not create pool: {:?}", e);
         log::error!("(main) {error}");
         error
@@ -66,15 +173,137 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         error
     })?;
 
-    postgis::psql_init().await?;
+    postgis::utils::set_precision(postgis::utils::Precision {
+        degree_decimals: config.coordinate_degree_decimals,
+        altitude_decimals: config.coordinate_altitude_decimals,
+    })?;
+    postgis::aircraft::set_identifier_max_length(config.aircraft_identifier_max_length)?;
+    postgis::identifier::set_overrides(postgis::identifier::Overrides {
+        extra_characters: postgis::identifier::parse_extra_characters(
+            &config.identifier_extra_characters,
+        )?,
+        max_lengths: postgis::identifier::parse_max_lengths(&config.identifier_max_lengths)?,
+    })?;
+    postgis::partition::set_cell_degrees(config.aircraft_partition_cell_degrees)?;
+    postgis::vertiport::set_cache_ttl_seconds(config.vertiport_cache_ttl_seconds);
+
+    // Settings that can be reloaded later without a restart
+    let tunables = reload::Tunables::try_from(&config)?;
+    tunables.apply()?;
 
-    // Start the Redis consumers
-    let handles = start_redis_consumers(&config).await.map_err(|_| {
-        let error = "Could not start Redis consumers.";
+    // Fail fast if the database lacks an extension the tables depend on
+    postgis::capabilities::check().await?;
+
+    // One-shot readiness check, leaves the production tables untouched
+    if cli.verify {
+        postgis::set_schema(&postgis::self_test::schema_name())?;
+        let result = postgis::self_test::run().await;
+        log::logger().flush();
+        return result.map_err(Into::into);
+    }
+
+    #[cfg(feature = "export")]
+    if let Some(Command::ExportFlights {
+        from,
+        to,
+        output,
+        format,
+        include_archived,
+    }) = cli.command
+    {
+        let result =
+            postgis::export::export_flights(from, to, include_archived, &output, format).await;
+        log::logger().flush();
+        return result.map(|_| ()).map_err(Into::into);
+    }
+
+    // Query-only replicas leave the tables and every writer to a primary
+    //  instance, so that they can run against a read-only database
+    let query_only = config.grpc_query_only;
+    if query_only {
+        info!("(main) Query-only mode, nothing is written to the database.");
+    } else {
+        postgis::psql_init().await?;
+    }
+
+    postgis::statements::verify().await?;
+
+    // Import assets into an empty database, reporting not ready until done
+    if query_only && !config.bootstrap_snapshot.is_empty() {
+        log::warn!("(main) bootstrap snapshot ignored in query-only mode.");
+    } else if !config.bootstrap_snapshot.is_empty() {
+        postgis::bootstrap::begin();
+        let path = config.bootstrap_snapshot.clone();
+        tokio::spawn(async move {
+            if let Err(e) = postgis::bootstrap::run(&path).await {
+                log::error!("(main) bootstrap failed, server stays not ready: {e}");
+            }
+        });
+    }
+
+    // Fail over to the standby database if the primary stops accepting writes
+    let monitor = (!query_only).then(|| {
+        tokio::spawn(postgis::pool::monitor(std::time::Duration::from_secs(
+            config.pg_health_check_interval_seconds,
+        )))
+    });
+
+    // Archive or delete flights past their retention period
+    let maintenance = (!query_only).then(|| tokio::spawn(postgis::maintenance::run()));
+
+    // Reload the tunable settings on SIGHUP or when the reload file changes
+    let reloader = tokio::spawn(reload::watch(tunables, config.config_reload_file.clone()));
+
+    // Announce zones about to activate or expire
+    let zone_notices = if config.zone_notice_lead_seconds > 0 && !query_only {
+        let publisher = RedisPool::new(&config, &config.redis_airspace_stream)
+            .await
+            .map_err(|_| {
+                let error = "Could not create airspace stream publisher.";
+                log::error!("(main) {error}");
+                error
+            })?;
+
+        Some(tokio::spawn(postgis::zone_events::run(
+            postgis::zone_events::Settings {
+                interval: std::time::Duration::from_secs(config.zone_notice_interval_seconds),
+                lead_seconds: config.zone_notice_lead_seconds,
+            },
+            publisher,
+        )))
+    } else {
+        None
+    };
+
+    start_track_cache(&config).await.map_err(|_| {
+        let error = "Could not connect the aircraft track cache.";
         log::error!("(main) {error}");
         error
     })?;
 
+    // Start the Redis consumers
+    let handles = if query_only {
+        vec![]
+    } else {
+        start_redis_consumers(&config).await.map_err(|_| {
+            let error = "Could not start Redis consumers.";
+            log::error!("(main) {error}");
+            error
+        })?
+    };
+
+    // Decode the broadcasts of aircraft that don't report to the network
+    #[cfg(any(feature = "adsb", feature = "remote_id"))]
+    let adapters = if query_only {
+        vec![]
+    } else {
+        ingest::start(&config).await.map_err(|_| {
+            let error = "Could not start ingestion adapters.";
+            log::error!("(main) {error}");
+            error
+        })?
+    };
+
     // Start GRPC Server
     tokio::spawn(grpc::server::grpc_server(config, None)).await?;
 
@@ -85,6 +314,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Abort all Redis consumers
     handles.iter().for_each(|handle| handle.abort());
+    #[cfg(any(feature = "adsb", feature = "remote_id"))]
+    adapters.iter().for_each(|handle| handle.abort());
+    monitor.iter().for_each(|handle| handle.abort());
+    maintenance.iter().for_each(|handle| handle.abort());
+    reloader.abort();
+    if let Some(zone_notices) = zone_notices {
+        zone_notices.abort();
+    }
 
     Ok(())
 }
@@ -1,19 +1,44 @@
 //! This module contains functions for updating aircraft in the PostGIS database.
 
-use super::{psql_transaction, PostgisError, DEFAULT_SRID, PSQL_SCHEMA};
-
-use crate::cache::{Consumer, Processor};
-use lib_common::time::{DateTime, Utc};
+use super::dedupe::{self, Velocity};
+use super::identifier::{self, Entity, IdentifierError};
+use super::partition::{self, UNPLACED_REGION};
+use super::rules;
+use super::statements::Statement;
+use super::{psql_transaction, OnceCell, PostgisError, DEFAULT_SRID};
+
+use crate::cache::{pool::RedisPool, Consumer, Processor};
+use crate::grpc::server::grpc_server::DataQuality;
+use crate::reload::Tunable;
+use lib_common::time::{DateTime, Duration, Utc};
 use postgis::ewkb::PointZ;
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
 use tonic::async_trait;
 
 use crate::types::{
-    AircraftId, AircraftPosition, AircraftType, AircraftVelocity, OperationalStatus,
+    AircraftId, AircraftPosition, AircraftState, AircraftType, AircraftVelocity, Degrees,
+    MetersPerSecond, OperationalStatus, Position,
 };
 
-/// Allowed characters in a identifier
-pub const IDENTIFIER_REGEX: &str = r"^[\-0-9A-Za-z_\.]{1,255}$";
+/// Default max length of aircraft identifiers and session IDs
+pub const DEFAULT_IDENTIFIER_MAX_LENGTH: u32 = 255;
+
+/// Default age of the last report after which an aircraft's session is
+///  released
+pub const DEFAULT_SESSION_EXPIRY_SECONDS: u32 = 60 * 60;
+
+/// Default age of a position after which it is reported as stale
+pub const DEFAULT_STALE_SECONDS: u32 = 10;
+
+/// Default age of a position after which it is reported as expired
+pub const DEFAULT_EXPIRED_SECONDS: u32 = 120;
+
+/// Max length of aircraft identifiers and session IDs, set once at startup
+static IDENTIFIER_MAX_LENGTH: OnceCell<u32> = OnceCell::new();
+
+/// Position quality thresholds
+static QUALITY_THRESHOLDS: Tunable<QualityThresholds> = Tunable::new();
 
 /// Possible errors with aircraft requests
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -24,9 +49,27 @@ pub enum AircraftError {
     /// Invalid Time Provided
     Time,
 
+    /// Invalid Velocity Provided
+    Velocity,
+
     /// Invalid Identifier
     Identifier,
 
+    /// Identifier exceeds the max length
+    IdentifierLength,
+
+    /// Invalid position quality thresholds
+    QualityThresholds,
+
+    /// Invalid partitioning of the aircraft table
+    Partitioning,
+
+    /// Invalid telemetry validation rules
+    Rules,
+
+    /// Invalid duplicate suppression settings
+    Dedupe,
+
     /// No Aircraft
     NoAircraft,
 
@@ -42,7 +85,17 @@ impl Display for AircraftError {
         match self {
             AircraftError::Location => write!(f, "Invalid location provided."),
             AircraftError::Time => write!(f, "Invalid time provided."),
+            AircraftError::Velocity => write!(f, "Invalid velocity provided."),
             AircraftError::Identifier => write!(f, "Invalid identifier(s) provided."),
+            AircraftError::IdentifierLength => {
+                write!(f, "Identifier(s) exceed the maximum length.")
+            }
+            AircraftError::QualityThresholds => {
+                write!(f, "Invalid position quality thresholds.")
+            }
+            AircraftError::Partitioning => write!(f, "Invalid aircraft table partitioning."),
+            AircraftError::Rules => write!(f, "Invalid telemetry validation rules."),
+            AircraftError::Dedupe => write!(f, "Invalid duplicate suppression settings."),
             AircraftError::NoAircraft => write!(f, "No aircraft provided."),
             AircraftError::Client => write!(f, "Could not get backend client."),
             AircraftError::DBError => write!(f, "Unknown backend error."),
@@ -52,16 +105,108 @@ impl Display for AircraftError {
 
 /// Gets the name of this module's table
 pub(super) fn get_table_name() -> &'static str {
-    static FULL_NAME: &str = const_format::formatcp!(r#""{PSQL_SCHEMA}"."aircraft""#,);
-    FULL_NAME
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."aircraft""#, super::get_schema()))
+}
+
+/// Sets the max length of aircraft identifiers and session IDs
+///  Must be called before [`psql_init`] to affect the table schema
+pub fn set_identifier_max_length(max_length: u32) -> Result<(), PostgisError> {
+    if max_length == 0 {
+        postgis_error!("identifier max length must be greater than zero.");
+        return Err(PostgisError::Aircraft(AircraftError::IdentifierLength));
+    }
+
+    IDENTIFIER_MAX_LENGTH.set(max_length).map_err(|_| {
+        postgis_error!("identifier max length was already set.");
+        PostgisError::Aircraft(AircraftError::IdentifierLength)
+    })
+}
+
+/// Gets the max length of aircraft identifiers and session IDs
+pub fn get_identifier_max_length() -> u32 {
+    *IDENTIFIER_MAX_LENGTH
+        .get()
+        .unwrap_or(&DEFAULT_IDENTIFIER_MAX_LENGTH)
+}
+
+/// Ages of a position at which its quality degrades, in seconds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct QualityThresholds {
+    /// Positions older than this are stale
+    pub stale_seconds: u32,
+
+    /// Positions older than this are expired
+    pub expired_seconds: u32,
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        QualityThresholds {
+            stale_seconds: DEFAULT_STALE_SECONDS,
+            expired_seconds: DEFAULT_EXPIRED_SECONDS,
+        }
+    }
+}
+
+/// Checks that positions become stale before they expire
+pub fn check_quality_thresholds(thresholds: &QualityThresholds) -> Result<(), PostgisError> {
+    if thresholds.stale_seconds > thresholds.expired_seconds {
+        postgis_error!(
+            "stale threshold ({}s) must not exceed the expired threshold ({}s).",
+            thresholds.stale_seconds,
+            thresholds.expired_seconds
+        );
+        return Err(PostgisError::Aircraft(AircraftError::QualityThresholds));
+    }
+
+    Ok(())
+}
+
+/// Sets the position quality thresholds, replacing any set before
+pub fn set_quality_thresholds(thresholds: QualityThresholds) -> Result<(), PostgisError> {
+    check_quality_thresholds(&thresholds)?;
+    QUALITY_THRESHOLDS.set(thresholds);
+    Ok(())
+}
+
+/// Gets the position quality thresholds
+pub fn get_quality_thresholds() -> QualityThresholds {
+    QUALITY_THRESHOLDS.get().unwrap_or_default()
+}
+
+/// Seconds elapsed between the last position update and now. Updates
+///  timestamped in the future count as fresh.
+pub fn get_staleness_seconds(last_position_update: DateTime<Utc>, now: DateTime<Utc>) -> f32 {
+    let milliseconds = (now - last_position_update).num_milliseconds().max(0);
+    milliseconds as f32 / 1000.0
+}
+
+/// Grades a position by its age
+pub fn get_data_quality(staleness_seconds: f32, thresholds: &QualityThresholds) -> DataQuality {
+    if staleness_seconds > thresholds.expired_seconds as f32 {
+        DataQuality::Expired
+    } else if staleness_seconds > thresholds.stale_seconds as f32 {
+        DataQuality::Stale
+    } else {
+        DataQuality::Fresh
+    }
+}
+
+/// Verifies that an aircraft identifier or session ID is valid
+fn check_entity_identifier(entity: Entity, identifier: &str) -> Result<(), PostgisError> {
+    identifier::check_identifier(entity, identifier).map_err(|e| {
+        postgis_error!("invalid {:?} identifier {:?}: {e}", entity, identifier);
+        match e {
+            IdentifierError::Length => PostgisError::Aircraft(AircraftError::IdentifierLength),
+            _ => PostgisError::Aircraft(AircraftError::Identifier),
+        }
+    })
 }
 
 /// Verifies that a identifier is valid
 pub fn check_identifier(identifier: &str) -> Result<(), PostgisError> {
-    super::utils::check_string(identifier, IDENTIFIER_REGEX).map_err(|e| {
-        postgis_error!("invalid identifier: {e}");
-        PostgisError::Aircraft(AircraftError::Identifier)
-    })
+    check_entity_identifier(Entity::Aircraft, identifier)
 }
 
 /// Initializes the PostGIS database for aircraft.
@@ -71,30 +216,52 @@ pub async fn psql_init() -> Result<(), PostgisError> {
     // Create Aircraft Table
     let type_enum_name = "aircrafttype";
     let status_enum_name = "opstatus";
-    let statements = vec![
+    let max_length = get_identifier_max_length();
+    let table_name = get_table_name();
+    let columns = format!(
+        r#""identifier" VARCHAR({max_length}) NOT NULL,
+            "session_id" VARCHAR({max_length}),
+            "aircraft_type" {type_enum_name} NOT NULL DEFAULT '{type_enum_default}',
+            "velocity_horizontal_ground_mps" FLOAT(4),
+            "velocity_horizontal_air_mps" FLOAT(4),
+            "velocity_vertical_mps" FLOAT(4),
+            "track_angle_degrees" FLOAT(4),
+            "geom" GEOMETRY(POINTZ, {DEFAULT_SRID}),
+            "last_identifier_update" TIMESTAMPTZ,
+            "last_position_update" TIMESTAMPTZ,
+            "last_velocity_update" TIMESTAMPTZ,
+            "simulated" BOOLEAN DEFAULT FALSE,
+            "op_status" {status_enum_name} NOT NULL DEFAULT '{status_enum_default}',
+            "region" INTEGER NOT NULL DEFAULT {UNPLACED_REGION}"#,
+        type_enum_default = AircraftType::Undeclared,
+        status_enum_default = OperationalStatus::Undeclared
+    );
+
+    let mut statements = vec![
         super::psql_enum_declaration::<AircraftType>(type_enum_name),
         super::psql_enum_declaration::<OperationalStatus>(status_enum_name),
+    ];
+
+    statements.extend(partition::psql_statements(
+        table_name,
+        &columns,
+        partition::get_grid(),
+    ));
+
+    // Tables created by earlier versions used VARCHAR(20)
+    statements.extend(
+        super::psql_widen_varchar("aircraft", &["identifier", "session_id"], max_length).await?,
+    );
+
+    statements.extend([
         format!(
-            r#"CREATE TABLE IF NOT EXISTS {table_name} (
-                "identifier" VARCHAR(20) UNIQUE PRIMARY KEY,
-                "session_id" VARCHAR(20) UNIQUE,
-                "aircraft_type" {type_enum_name} NOT NULL DEFAULT '{type_enum_default}',
-                "velocity_horizontal_ground_mps" FLOAT(4),
-                "velocity_horizontal_air_mps" FLOAT(4),
-                "velocity_vertical_mps" FLOAT(4),
-                "track_angle_degrees" FLOAT(4),
-                "geom" GEOMETRY(POINTZ, {DEFAULT_SRID}),
-                "last_identifier_update" TIMESTAMPTZ,
-                "last_position_update" TIMESTAMPTZ,
-                "last_velocity_update" TIMESTAMPTZ,
-                "simulated" BOOLEAN DEFAULT FALSE,
-                "op_status" {status_enum_name} NOT NULL DEFAULT '{status_enum_default}'
-            );"#,
-            table_name = get_table_name(),
-            type_enum_default = AircraftType::Undeclared.to_string(),
-            status_enum_default = OperationalStatus::Undeclared.to_string()
+            r#"ALTER TABLE {table_name}
+                ADD COLUMN IF NOT EXISTS "region" INTEGER NOT NULL DEFAULT {UNPLACED_REGION};"#
         ),
-    ];
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "aircraft_geom_idx" ON {table_name} USING GIST ("geom");"#
+        ),
+    ]);
 
     psql_transaction(statements).await
 }
@@ -108,7 +275,11 @@ impl Processor<AircraftId> for Consumer {
 
         #[cfg(not(tarpaulin_include))]
         // no_coverage: (R5) needs psql backend to test
-        update_aircraft_id(items).await.map_err(|_| ())
+        let identifiers = update_aircraft_id(items).await.map_err(|_| ())?;
+
+        #[cfg(not(tarpaulin_include))]
+        // no_coverage: (R5) needs psql and redis backends to test
+        publish_aircraft_state(&self.publisher, identifiers).await
     }
 }
 
@@ -119,9 +290,19 @@ impl Processor<AircraftPosition> for Consumer {
             return Ok(());
         }
 
+        let items = valid_position_messages(items, &Utc::now());
+
+        #[cfg(not(tarpaulin_include))]
+        // no_coverage: (R5) needs redis backend to test
+        mirror_aircraft_track(&items).await;
+
         #[cfg(not(tarpaulin_include))]
         // no_coverage: (R5) needs psql backend to test
-        update_aircraft_position(items).await.map_err(|_| ())
+        let identifiers = update_aircraft_position(items).await.map_err(|_| ())?;
+
+        #[cfg(not(tarpaulin_include))]
+        // no_coverage: (R5) needs psql and redis backends to test
+        publish_aircraft_state(&self.publisher, identifiers).await
     }
 }
 
@@ -134,7 +315,11 @@ impl Processor<AircraftVelocity> for Consumer {
 
         #[cfg(not(tarpaulin_include))]
         // no_coverage: (R5) needs psql backend to test
-        update_aircraft_velocity(items).await.map_err(|_| ())
+        let identifiers = update_aircraft_velocity(items).await.map_err(|_| ())?;
+
+        #[cfg(not(tarpaulin_include))]
+        // no_coverage: (R5) needs psql and redis backends to test
+        publish_aircraft_state(&self.publisher, identifiers).await
     }
 }
 
@@ -156,10 +341,7 @@ fn validate_identification(
     }
 
     if let Some(identifier) = session_id {
-        super::flight::check_flight_identifier(identifier).map_err(|e| {
-            postgis_error!("invalid session_id {:?}: {e}", identifier);
-            PostgisError::Aircraft(AircraftError::Identifier)
-        })?;
+        check_entity_identifier(Entity::Flight, identifier)?;
     }
 
     Ok(())
@@ -169,24 +351,286 @@ fn validate_identification(
 fn validate_id_message(item: &AircraftId, now: &DateTime<Utc>) -> Result<(), PostgisError> {
     validate_identification(&item.identifier, &item.session_id)?;
 
-    if item.timestamp_network > *now {
-        postgis_error!(
-            "could not validate timestamp_network (in future): {}",
-            item.timestamp_network
-        );
+    let identifier = item
+        .identifier
+        .as_ref()
+        .or(item.session_id.as_ref())
+        .map_or("", String::as_str);
+
+    rules::get_rules()
+        .check_timestamp(identifier, item.timestamp_network, *now)
+        .map_err(rules::reject)
+}
+
+/// Gets the session ID to store for the aircraft after this message
+fn get_next_session_id(item: &AircraftId) -> Option<&String> {
+    match item.session_ended {
+        true => None,
+        false => item.session_id.as_ref(),
+    }
+}
+
+/// Gets the session-only identifier to merge into the identified aircraft
+///
+/// Aircraft that report positions before identifying themselves are stored
+///  under their session ID. Once a message links that session to an
+///  identifier, the two rows describe the same aircraft.
+fn get_merge_pair(item: &AircraftId) -> Option<(&String, &String)> {
+    if item.session_ended {
+        return None;
+    }
+
+    match (&item.session_id, &item.identifier) {
+        (Some(session_id), Some(identifier)) if session_id != identifier => {
+            Some((session_id, identifier))
+        }
+        _ => None,
+    }
+}
+
+/// The last accepted report of each aircraft, with its network timestamp
+type LastReports<T> = HashMap<String, (T, DateTime<Utc>)>;
+
+/// Whether a report follows the previous one of its aircraft closely enough
+///  to be compared with it. A report after the previous one expired starts
+///  a new track.
+fn is_consecutive(previous: DateTime<Utc>, next: DateTime<Utc>) -> bool {
+    let expired_seconds = get_quality_thresholds().expired_seconds as i64;
+    let gap = next - previous;
+    gap >= Duration::zero() && gap.num_seconds() <= expired_seconds
+}
+
+/// Keeps the reports that pass a rule comparing them with the previous
+///  report of their aircraft, in order. Each accepted report becomes the
+///  previous report of the next.
+fn plausible_reports<R, T>(
+    reports: Vec<R>,
+    mut last: LastReports<T>,
+    report: impl Fn(&R) -> (&str, T, DateTime<Utc>),
+    check: impl Fn(&str, &T, &T) -> Result<(), rules::Rule>,
+) -> Vec<R> {
+    reports
+        .into_iter()
+        .filter(|item| {
+            let (identifier, value, timestamp) = report(item);
+            if let Some((previous, previous_timestamp)) = last.get(identifier) {
+                if is_consecutive(*previous_timestamp, timestamp) {
+                    if let Err(rule) = check(identifier, previous, &value) {
+                        rules::reject(rule);
+                        return false;
+                    }
+                }
+            }
+
+            last.insert(identifier.to_string(), (value, timestamp));
+            true
+        })
+        .collect()
+}
+
+/// Drops the reports that only advance the timestamp of the last written
+///  report of their aircraft, in order. Each written report becomes the last
+///  written report of the next.
+fn changed_reports<R, T>(
+    reports: Vec<R>,
+    mut last: LastReports<T>,
+    report: impl Fn(&R) -> (&str, T, DateTime<Utc>),
+    is_duplicate: impl Fn((&T, DateTime<Utc>), (&T, DateTime<Utc>)) -> bool,
+) -> Vec<R> {
+    let total = reports.len();
+    let changed: Vec<R> = reports
+        .into_iter()
+        .filter(|item| {
+            let (identifier, value, timestamp) = report(item);
+            if let Some((previous, previous_timestamp)) = last.get(identifier) {
+                if is_duplicate((previous, *previous_timestamp), (&value, timestamp)) {
+                    return false;
+                }
+            }
+
+            last.insert(identifier.to_string(), (value, timestamp));
+            true
+        })
+        .collect();
+
+    dedupe::record_suppressed((total - changed.len()) as u64);
+    changed
+}
+
+/// The position of a report, to compare with the position of the next
+fn position_report(craft: &AircraftPosition) -> (&str, Position, DateTime<Utc>) {
+    (
+        craft.identifier.as_str(),
+        craft.position,
+        craft.timestamp_network,
+    )
+}
+
+/// The velocity of a report, to compare with the velocity of the next
+fn velocity_report(craft: &AircraftVelocity) -> (&str, Velocity, DateTime<Utc>) {
+    let velocity = Velocity {
+        horizontal_ground_mps: craft.velocity_horizontal_ground_mps.0,
+        vertical_mps: craft.velocity_vertical_mps.0,
+        track_angle_degrees: craft.track_angle_degrees.0,
+    };
+
+    (craft.identifier.as_str(), velocity, craft.timestamp_network)
+}
+
+/// Gets the stored state of the provided aircraft, to compare new
+///  reports with
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql backend to test
+async fn get_last_state(
+    transaction: &deadpool_postgres::Transaction<'_>,
+    identifiers: &[&str],
+) -> Result<Vec<AircraftState>, PostgisError> {
+    let stmt = transaction
+        .prepare_cached(&Statement::GetAircraftState.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
+    transaction
+        .query(&stmt, &[&identifiers])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute query: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?
+        .into_iter()
+        .map(process_state_row)
+        .collect::<Result<Vec<AircraftState>, _>>()
+        .map_err(|e| {
+            postgis_error!("could not get aircraft state: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })
+}
+
+/// Gets the stored position of the provided aircraft
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql backend to test
+async fn get_last_positions(
+    transaction: &deadpool_postgres::Transaction<'_>,
+    aircraft: &[AircraftPosition],
+) -> Result<LastReports<Position>, PostgisError> {
+    let identifiers: Vec<&str> = aircraft
+        .iter()
+        .map(|craft| craft.identifier.as_str())
+        .collect();
+
+    Ok(get_last_state(transaction, &identifiers)
+        .await?
+        .into_iter()
+        .filter_map(|state| {
+            let last = state.position.zip(state.last_position_update)?;
+            Some((state.identifier, last))
+        })
+        .collect())
+}
+
+/// Gets the stored velocity of the provided aircraft
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql backend to test
+async fn get_last_velocities(
+    transaction: &deadpool_postgres::Transaction<'_>,
+    aircraft: &[AircraftVelocity],
+) -> Result<LastReports<Velocity>, PostgisError> {
+    let identifiers: Vec<&str> = aircraft
+        .iter()
+        .map(|craft| craft.identifier.as_str())
+        .collect();
+
+    Ok(get_last_state(transaction, &identifiers)
+        .await?
+        .into_iter()
+        .filter_map(|state| {
+            let velocity = Velocity {
+                horizontal_ground_mps: state.velocity_horizontal_ground_mps?.0,
+                vertical_mps: state.velocity_vertical_mps.map_or(0.0, |v| v.0),
+                track_angle_degrees: state.track_angle_degrees.map_or(0.0, |v| v.0),
+            };
+
+            Some((state.identifier, (velocity, state.last_velocity_update?)))
+        })
+        .collect())
+}
 
-        return Err(PostgisError::Aircraft(AircraftError::Time));
+/// Locks the provided aircraft identifiers until the transaction ends
+///
+/// The partitioned table can't enforce unique identifiers across its
+///  partitions, so two transactions inserting the same new aircraft would
+///  each add a row. Upserts to the unpartitioned table rely on its primary
+///  key instead and skip the lock.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql backend to test
+async fn lock_aircraft(
+    transaction: &deadpool_postgres::Transaction<'_>,
+    identifiers: Vec<&str>,
+) -> Result<(), PostgisError> {
+    if partition::get_grid().is_none() {
+        return Ok(());
     }
 
+    let stmt = transaction
+        .prepare_cached(&Statement::LockAircraft.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
+    transaction
+        .execute(&stmt, &[&identifiers])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not lock aircraft: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
     Ok(())
 }
 
+/// Releases the sessions of aircraft that haven't reported since `cutoff`,
+///  so that they can be reused. Returns the number of sessions released.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql backend to test
+pub async fn expire_sessions(cutoff: DateTime<Utc>) -> Result<u64, PostgisError> {
+    let client = crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Aircraft(AircraftError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Aircraft(AircraftError::Client)
+        })?;
+
+    let stmt = client
+        .prepare_cached(&Statement::ExpireAircraftSessions.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
+    client.execute(&stmt, &[&cutoff]).await.map_err(|e| {
+        postgis_error!("could not expire stale sessions: {}", e);
+        PostgisError::Aircraft(AircraftError::DBError)
+    })
+}
+
 /// Pulls queued aircraft id messages from Redis Queue
 /// Updates aircraft in the PostGIS database.
 /// Confirms with Redis Queue that item was processed.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) needs psql backend to test
-pub async fn update_aircraft_id(aircraft: Vec<AircraftId>) -> Result<(), PostgisError> {
+pub async fn update_aircraft_id(aircraft: Vec<AircraftId>) -> Result<Vec<String>, PostgisError> {
     postgis_debug!("entry.");
 
     let now = Utc::now();
@@ -216,23 +660,35 @@ pub async fn update_aircraft_id(aircraft: Vec<AircraftId>) -> Result<(), Postgis
         PostgisError::Aircraft(AircraftError::DBError)
     })?;
 
+    let identifiers = aircraft
+        .iter()
+        .flat_map(|craft| [&craft.identifier, &craft.session_id])
+        .flatten()
+        .map(String::as_str)
+        .collect();
+
+    lock_aircraft(&transaction, identifiers).await?;
+
+    // A session can only belong to one aircraft at a time
+    //  Ended sessions are released from every aircraft
+    let release_stmt = transaction
+        .prepare_cached(&Statement::ReleaseAircraftSession.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
     let stmt = transaction
-        .prepare_cached(&format!(
-            r#"
-        INSERT INTO {table_name} (
-            "identifier",
-            "session_id",
-            "aircraft_type",
-            "last_identifier_update"
-        )
-        VALUES ($1, $2, $3, $4)
-        ON CONFLICT ("identifier") DO UPDATE
-            SET "session_id" = EXCLUDED."session_id",
-                "aircraft_type" = EXCLUDED."aircraft_type",
-                "last_identifier_update" = EXCLUDED."last_identifier_update";
-        "#,
-            table_name = get_table_name()
-        ))
+        .prepare_cached(&Statement::UpsertAircraftIdentifier.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
+    let merge_stmt = transaction
+        .prepare_cached(&Statement::MergeAircraftSession.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
@@ -240,12 +696,39 @@ pub async fn update_aircraft_id(aircraft: Vec<AircraftId>) -> Result<(), Postgis
         })?;
 
     for craft in &aircraft {
+        if let Some(session_id) = &craft.session_id {
+            let owner = match craft.session_ended {
+                true => None,
+                false => craft.identifier.as_ref(),
+            };
+
+            let released = transaction
+                .execute(&release_stmt, &[session_id, &owner])
+                .await
+                .map_err(|e| {
+                    postgis_error!("could not release session: {}", e);
+                    PostgisError::Aircraft(AircraftError::DBError)
+                })?;
+
+            if released > 0 && !craft.session_ended {
+                postgis_info!(
+                    "session {session_id} moved to aircraft {:?}.",
+                    craft.identifier
+                );
+            }
+        }
+
+        // Session-only messages have no aircraft row to update
+        if craft.identifier.is_none() {
+            continue;
+        }
+
         transaction
             .execute(
                 &stmt,
                 &[
                     &craft.identifier,
-                    &craft.session_id,
+                    &get_next_session_id(craft),
                     &craft.aircraft_type,
                     &craft.timestamp_network,
                 ],
@@ -255,6 +738,27 @@ pub async fn update_aircraft_id(aircraft: Vec<AircraftId>) -> Result<(), Postgis
                 postgis_error!("could not execute transaction: {}", e);
                 PostgisError::Aircraft(AircraftError::DBError)
             })?;
+
+        // Move the session-only row and its flights onto this aircraft
+        let Some((session_id, identifier)) = get_merge_pair(craft) else {
+            continue;
+        };
+
+        let row = transaction
+            .query_one(&merge_stmt, &[session_id, identifier])
+            .await
+            .map_err(|e| {
+                postgis_error!("could not merge session {session_id}: {}", e);
+                PostgisError::Aircraft(AircraftError::DBError)
+            })?;
+
+        let merged: i64 = row.try_get("merged").unwrap_or_default();
+        let flights: i64 = row.try_get("flights").unwrap_or_default();
+        if merged > 0 {
+            postgis_info!(
+                "merged aircraft {session_id} into {identifier}, {flights} flight(s) reassigned."
+            );
+        }
     }
 
     transaction.commit().await.map_err(|e| {
@@ -263,7 +767,10 @@ pub async fn update_aircraft_id(aircraft: Vec<AircraftId>) -> Result<(), Postgis
     })?;
 
     postgis_debug!("success.");
-    Ok(())
+    Ok(aircraft
+        .into_iter()
+        .filter_map(|craft| craft.identifier)
+        .collect())
 }
 
 /// Validates the provided aircraft position.
@@ -271,43 +778,63 @@ fn validate_position_message(
     item: &AircraftPosition,
     now: &DateTime<Utc>,
 ) -> Result<(), PostgisError> {
-    if item.position.latitude < -90.0 || item.position.latitude > 90.0 {
+    if item.position.latitude.0 < -90.0 || item.position.latitude.0 > 90.0 {
         postgis_error!("could not validate latitude: {}", item.position.latitude);
         return Err(PostgisError::Aircraft(AircraftError::Location));
     }
 
-    if item.position.longitude < -180.0 || item.position.longitude > 180.0 {
+    if item.position.longitude.0 < -180.0 || item.position.longitude.0 > 180.0 {
         postgis_error!("could not validate longitude: {}", item.position.longitude);
 
         return Err(PostgisError::Aircraft(AircraftError::Location));
     }
 
-    if item.timestamp_network > *now {
-        postgis_error!(
-            "could not validate timestamp_network (in future): {}",
-            item.timestamp_network
-        );
+    check_identifier(&item.identifier)?;
 
-        return Err(PostgisError::Aircraft(AircraftError::Time));
-    }
+    let rules = rules::get_rules();
+    rules
+        .check_timestamp(&item.identifier, item.timestamp_network, *now)
+        .map_err(rules::reject)?;
 
-    check_identifier(&item.identifier)?;
+    rules
+        .check_altitude(&item.identifier, &item.position)
+        .map_err(rules::reject)
+}
 
-    Ok(())
+/// Keeps the aircraft positions that pass validation.
+fn valid_position_messages(
+    aircraft: Vec<AircraftPosition>,
+    now: &DateTime<Utc>,
+) -> Vec<AircraftPosition> {
+    aircraft
+        .into_iter()
+        .filter(|item| validate_position_message(item, now).is_ok())
+        .collect()
+}
+
+/// Mirrors aircraft positions into the Redis track cache, if one is configured.
+///  The PostGIS update goes ahead even if this fails.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs redis backend to test
+async fn mirror_aircraft_track(aircraft: &[AircraftPosition]) {
+    let Some(cache) = crate::cache::TRACK_CACHE.get() else {
+        return;
+    };
+
+    if let Err(e) = cache.push_track(aircraft).await {
+        postgis_error!("could not cache aircraft track: {}", e);
+    }
 }
 
 /// Updates aircraft position in the PostGIS database.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) needs psql backend to test
-pub async fn update_aircraft_position(aircraft: Vec<AircraftPosition>) -> Result<(), PostgisError> {
+pub async fn update_aircraft_position(
+    aircraft: Vec<AircraftPosition>,
+) -> Result<Vec<String>, PostgisError> {
     postgis_debug!("entry.");
 
-    let now = Utc::now();
-    let aircraft: Vec<AircraftPosition> = aircraft
-        .into_iter()
-        .filter(|item| validate_position_message(item, &now).is_ok())
-        .collect();
-
+    let aircraft = valid_position_messages(aircraft, &Utc::now());
     if aircraft.is_empty() {
         return Err(PostgisError::Aircraft(AircraftError::NoAircraft));
     }
@@ -327,32 +854,51 @@ pub async fn update_aircraft_position(aircraft: Vec<AircraftPosition>) -> Result
         PostgisError::Aircraft(AircraftError::DBError)
     })?;
 
+    let identifiers = aircraft
+        .iter()
+        .map(|craft| craft.identifier.as_str())
+        .collect();
+
+    lock_aircraft(&transaction, identifiers).await?;
+
+    // Compares the reports with the stored state of their aircraft
+    let rules = rules::get_rules();
+    let dedupe = dedupe::get_settings();
+    let mut aircraft = aircraft;
+    if rules.max_position_jump_meters > 0.0 || dedupe.positions_enabled() {
+        let last = get_last_positions(&transaction, &aircraft).await?;
+        aircraft = plausible_reports(
+            aircraft,
+            last.clone(),
+            position_report,
+            |identifier, previous, next| rules.check_position_jump(identifier, previous, next),
+        );
+
+        aircraft = changed_reports(aircraft, last, position_report, |stored, next| {
+            dedupe.is_duplicate_position(stored, next)
+        });
+    }
+
     let stmt = transaction
-        .prepare_cached(&format!(
-            r#"
-        INSERT INTO {table_name} (
-            "identifier",
-            "geom",
-            "last_position_update"
-        )
-        VALUES ($1, $2, $3)
-        ON CONFLICT ("identifier") DO UPDATE
-            SET "geom" = EXCLUDED."geom",
-                "last_position_update" = EXCLUDED."last_position_update";
-        "#,
-            table_name = get_table_name()
-        ))
+        .prepare_cached(&Statement::UpsertAircraftPosition.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
             PostgisError::Aircraft(AircraftError::DBError)
         })?;
 
+    // Routes each row to the partition of its new position
+    let grid = partition::get_grid();
     for craft in &aircraft {
         let geom = PointZ::from(craft.position);
+        let region =
+            partition::get_region(grid, craft.position.longitude.0, craft.position.latitude.0);
 
         transaction
-            .execute(&stmt, &[&craft.identifier, &geom, &craft.timestamp_network])
+            .execute(
+                &stmt,
+                &[&craft.identifier, &geom, &craft.timestamp_network, &region],
+            )
             .await
             .map_err(|e| {
                 postgis_error!("could not execute transaction: {}", e);
@@ -366,7 +912,7 @@ pub async fn update_aircraft_position(aircraft: Vec<AircraftPosition>) -> Result
     })?;
 
     postgis_debug!("success.");
-    Ok(())
+    Ok(aircraft.into_iter().map(|craft| craft.identifier).collect())
 }
 
 /// Validates the provided aircraft velocity
@@ -376,22 +922,17 @@ fn validate_velocity_message(
 ) -> Result<(), PostgisError> {
     check_identifier(&item.identifier)?;
 
-    if item.timestamp_network > *now {
-        postgis_error!(
-            "could not validate timestamp_network (in future): {}",
-            item.timestamp_network
-        );
-
-        return Err(PostgisError::Aircraft(AircraftError::Time));
-    }
-
-    Ok(())
+    rules::get_rules()
+        .check_timestamp(&item.identifier, item.timestamp_network, *now)
+        .map_err(rules::reject)
 }
 
 /// Updates aircraft velocity in the PostGIS database.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) needs psql backend to test
-pub async fn update_aircraft_velocity(aircraft: Vec<AircraftVelocity>) -> Result<(), PostgisError> {
+pub async fn update_aircraft_velocity(
+    aircraft: Vec<AircraftVelocity>,
+) -> Result<Vec<String>, PostgisError> {
     postgis_debug!("entry.");
 
     let now = Utc::now();
@@ -419,24 +960,35 @@ pub async fn update_aircraft_velocity(aircraft: Vec<AircraftVelocity>) -> Result
         PostgisError::Aircraft(AircraftError::DBError)
     })?;
 
+    let identifiers = aircraft
+        .iter()
+        .map(|craft| craft.identifier.as_str())
+        .collect();
+
+    lock_aircraft(&transaction, identifiers).await?;
+
+    // Compares the reports with the stored state of their aircraft
+    let rules = rules::get_rules();
+    let dedupe = dedupe::get_settings();
+    let mut aircraft = aircraft;
+    if rules.max_speed_jump_mps > 0.0 || dedupe.velocities_enabled() {
+        let last = get_last_velocities(&transaction, &aircraft).await?;
+        aircraft = plausible_reports(
+            aircraft,
+            last.clone(),
+            velocity_report,
+            |identifier, previous, next| {
+                rules.check_speed_jump(identifier, previous.speed_mps(), next.speed_mps())
+            },
+        );
+
+        aircraft = changed_reports(aircraft, last, velocity_report, |stored, next| {
+            dedupe.is_duplicate_velocity(stored, next)
+        });
+    }
+
     let stmt = transaction
-        .prepare_cached(&format!(
-            r#"
-        INSERT INTO {table_name} (
-            "identifier",
-            "velocity_horizontal_ground_mps",
-            "velocity_vertical_mps",
-            "track_angle_degrees",
-            "last_velocity_update"
-        ) VALUES (
-            $1, $2, $3, $4, $5
-        ) ON CONFLICT ("identifier") DO UPDATE
-            SET "velocity_horizontal_ground_mps" = EXCLUDED."velocity_horizontal_ground_mps",
-                "velocity_vertical_mps" = EXCLUDED."velocity_vertical_mps",
-                "track_angle_degrees" = EXCLUDED."track_angle_degrees",
-                "last_velocity_update" = EXCLUDED."last_velocity_update";"#,
-            table_name = get_table_name()
-        ))
+        .prepare_cached(&Statement::UpsertAircraftVelocity.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
@@ -449,9 +1001,9 @@ pub async fn update_aircraft_velocity(aircraft: Vec<AircraftVelocity>) -> Result
                 &stmt,
                 &[
                     &craft.identifier,
-                    &craft.velocity_horizontal_ground_mps,
-                    &craft.velocity_vertical_mps,
-                    &craft.track_angle_degrees,
+                    &craft.velocity_horizontal_ground_mps.as_f32(),
+                    &craft.velocity_vertical_mps.as_f32(),
+                    &craft.track_angle_degrees.as_f32(),
                     &craft.timestamp_network,
                 ],
             )
@@ -468,18 +1020,203 @@ pub async fn update_aircraft_velocity(aircraft: Vec<AircraftVelocity>) -> Result
     })?;
 
     postgis_debug!("success.");
+    Ok(aircraft.into_iter().map(|craft| craft.identifier).collect())
+}
+
+/// Converts an aircraft row into the merged aircraft state
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test, no way to create a Row without querying it
+fn process_state_row(row: tokio_postgres::Row) -> Result<AircraftState, tokio_postgres::Error> {
+    let geom: Option<PointZ> = row.try_get("geom")?;
+    let simulated: Option<bool> = row.try_get("simulated")?;
+    let velocity_horizontal_ground_mps: Option<f32> =
+        row.try_get("velocity_horizontal_ground_mps")?;
+    let velocity_vertical_mps: Option<f32> = row.try_get("velocity_vertical_mps")?;
+    let track_angle_degrees: Option<f32> = row.try_get("track_angle_degrees")?;
+
+    Ok(AircraftState {
+        identifier: row.try_get("identifier")?,
+        session_id: row.try_get("session_id")?,
+        aircraft_type: row.try_get("aircraft_type")?,
+        op_status: row.try_get("op_status")?,
+        simulated: simulated.unwrap_or(false),
+        position: geom.map(Position::from),
+        velocity_horizontal_ground_mps: velocity_horizontal_ground_mps.map(MetersPerSecond::from),
+        velocity_vertical_mps: velocity_vertical_mps.map(MetersPerSecond::from),
+        track_angle_degrees: track_angle_degrees.map(Degrees::from),
+        last_identifier_update: row.try_get("last_identifier_update")?,
+        last_position_update: row.try_get("last_position_update")?,
+        last_velocity_update: row.try_get("last_velocity_update")?,
+    })
+}
+
+/// Gets the merged state of the provided aircraft
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql backend to test
+pub async fn get_aircraft_state(
+    identifiers: &[String],
+) -> Result<Vec<AircraftState>, PostgisError> {
+    let pool = crate::postgis::DEADPOOL_POSTGIS.get().ok_or_else(|| {
+        postgis_error!("could not get psql pool.");
+        PostgisError::Aircraft(AircraftError::Client)
+    })?;
+
+    let client = pool.get().await.map_err(|e| {
+        postgis_error!("could not get client from psql connection pool: {}", e);
+        PostgisError::Aircraft(AircraftError::Client)
+    })?;
+
+    let stmt = client
+        .prepare_cached(&Statement::GetAircraftState.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
+    client
+        .query(&stmt, &[&identifiers])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute query: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?
+        .into_iter()
+        .map(process_state_row)
+        .collect::<Result<Vec<AircraftState>, _>>()
+        .map_err(|e| {
+            postgis_error!("could not get aircraft state: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })
+}
+
+/// A box of longitudes and latitudes, in degrees
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    /// Western edge
+    pub min_longitude: f64,
+
+    /// Southern edge
+    pub min_latitude: f64,
+
+    /// Eastern edge
+    pub max_longitude: f64,
+
+    /// Northern edge
+    pub max_latitude: f64,
+}
+
+/// Verifies that a box has valid and ordered edges
+fn validate_box(bbox: &BoundingBox) -> Result<(), PostgisError> {
+    let longitudes = -180.0..=180.0;
+    let latitudes = -90.0..=90.0;
+    if !longitudes.contains(&bbox.min_longitude)
+        || !longitudes.contains(&bbox.max_longitude)
+        || !latitudes.contains(&bbox.min_latitude)
+        || !latitudes.contains(&bbox.max_latitude)
+        || bbox.min_longitude > bbox.max_longitude
+        || bbox.min_latitude > bbox.max_latitude
+    {
+        postgis_error!("invalid bounding box: {:?}", bbox);
+        return Err(PostgisError::Aircraft(AircraftError::Location));
+    }
+
     Ok(())
 }
 
+/// Gets the state of the aircraft positioned within a box since a time
+///
+/// On a partitioned table only the partitions of the grid cells the box
+///  overlaps are scanned.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql backend to test
+pub async fn get_aircraft_in_box(
+    bbox: &BoundingBox,
+    since: DateTime<Utc>,
+) -> Result<Vec<AircraftState>, PostgisError> {
+    validate_box(bbox)?;
+
+    let regions = partition::get_grid().map(|grid| {
+        grid.regions_in_box(
+            bbox.min_longitude,
+            bbox.min_latitude,
+            bbox.max_longitude,
+            bbox.max_latitude,
+        )
+    });
+
+    let pool = crate::postgis::DEADPOOL_POSTGIS.get().ok_or_else(|| {
+        postgis_error!("could not get psql pool.");
+        PostgisError::Aircraft(AircraftError::Client)
+    })?;
+
+    let client = pool.get().await.map_err(|e| {
+        postgis_error!("could not get client from psql connection pool: {}", e);
+        PostgisError::Aircraft(AircraftError::Client)
+    })?;
+
+    let stmt = client
+        .prepare_cached(&Statement::GetAircraftInBox.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
+    client
+        .query(
+            &stmt,
+            &[
+                &bbox.min_longitude,
+                &bbox.min_latitude,
+                &bbox.max_longitude,
+                &bbox.max_latitude,
+                &since,
+                &regions,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute query: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?
+        .into_iter()
+        .map(process_state_row)
+        .collect::<Result<Vec<AircraftState>, _>>()
+        .map_err(|e| {
+            postgis_error!("could not get aircraft state: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })
+}
+
+/// Publishes the merged state of updated aircraft to a Redis stream
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs psql and redis backends to test
+async fn publish_aircraft_state(
+    publisher: &Option<RedisPool>,
+    identifiers: Vec<String>,
+) -> Result<(), ()> {
+    let Some(publisher) = publisher else {
+        return Ok(());
+    };
+
+    if identifiers.is_empty() {
+        return Ok(());
+    }
+
+    let states = get_aircraft_state(&identifiers).await.map_err(|e| {
+        postgis_error!("could not get aircraft state: {}", e);
+    })?;
+
+    publisher.push_stream(&states).await.map_err(|e| {
+        postgis_error!("could not publish aircraft state: {}", e);
+    })
+}
+
 /// Gets the geometry of an aircraft given its identifier.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) needs psql backend to test
 pub async fn get_aircraft_pointz(identifier: &str) -> Result<PointZ, PostgisError> {
-    let stmt = format!(
-        r#"SELECT "geom" FROM {table_name} WHERE "identifier" = $1;"#,
-        table_name = get_table_name()
-    );
-
     let pool = crate::postgis::DEADPOOL_POSTGIS.get().ok_or_else(|| {
         postgis_error!("could not get psql pool.");
         PostgisError::Aircraft(AircraftError::Client)
@@ -490,6 +1227,14 @@ pub async fn get_aircraft_pointz(identifier: &str) -> Result<PointZ, PostgisErro
         PostgisError::Aircraft(AircraftError::Client)
     })?;
 
+    let stmt = client
+        .prepare_cached(&Statement::GetAircraftGeom.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Aircraft(AircraftError::DBError)
+        })?;
+
     client
         .query_one(&stmt, &[&identifier])
         .await
@@ -510,7 +1255,7 @@ pub async fn get_aircraft_pointz(identifier: &str) -> Result<PointZ, PostgisErro
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Position;
+    use crate::types::Meters;
     use lib_common::time::Duration;
 
     #[tokio::test]
@@ -524,9 +1269,9 @@ mod tests {
             .map(|(label, latitude, longitude)| AircraftPosition {
                 identifier: label.to_string(),
                 position: Position {
-                    latitude: *latitude,
-                    longitude: *longitude,
-                    altitude_meters: 100.0,
+                    latitude: Degrees(*latitude),
+                    longitude: Degrees(*longitude),
+                    altitude_meters: Meters(100.0),
                 },
                 timestamp_network: Utc::now(),
                 timestamp_asset: None,
@@ -544,19 +1289,13 @@ mod tests {
         lib_common::logger::get_log_handle().await;
         ut_info!("start");
 
-        for label in &[
-            "NULL",
-            "Aircraft;",
-            "'Aircraft'",
-            "Aircraft \'",
-            &"X".repeat(1000),
-        ] {
+        for label in &["NULL", "Aircraft;", "'Aircraft'", "Aircraft \'"] {
             let position = AircraftPosition {
                 identifier: label.to_string(),
                 position: Position {
-                    latitude: 0.0,
-                    longitude: 0.0,
-                    altitude_meters: 100.0,
+                    latitude: Degrees(0.0),
+                    longitude: Degrees(0.0),
+                    altitude_meters: Meters(100.0),
                 },
                 timestamp_network: Utc::now(),
                 timestamp_asset: None,
@@ -565,10 +1304,10 @@ mod tests {
             let velocity = AircraftVelocity {
                 identifier: label.to_string(),
                 timestamp_network: Utc::now(),
-                velocity_horizontal_ground_mps: 0.0,
+                velocity_horizontal_ground_mps: MetersPerSecond(0.0),
                 velocity_horizontal_air_mps: None,
-                velocity_vertical_mps: 0.0,
-                track_angle_degrees: 0.0,
+                velocity_vertical_mps: MetersPerSecond(0.0),
+                track_angle_degrees: Degrees(0.0),
                 timestamp_asset: None,
             };
 
@@ -578,6 +1317,7 @@ mod tests {
                 timestamp_network: Utc::now(),
                 aircraft_type: AircraftType::Rotorcraft,
                 timestamp_asset: None,
+                session_ended: false,
             };
 
             let result = validate_position_message(&position, &Utc::now()).unwrap_err();
@@ -593,6 +1333,60 @@ mod tests {
         ut_info!("success");
     }
 
+    #[tokio::test]
+    async fn ut_aircraft_to_gis_identifier_too_long() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let mut label = "X".repeat(get_identifier_max_length() as usize + 1);
+        let position = AircraftPosition {
+            identifier: label.clone(),
+            position: Position {
+                latitude: Degrees(0.0),
+                longitude: Degrees(0.0),
+                altitude_meters: Meters(100.0),
+            },
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        };
+
+        let result = validate_position_message(&position, &Utc::now()).unwrap_err();
+        assert_eq!(
+            result,
+            PostgisError::Aircraft(AircraftError::IdentifierLength)
+        );
+
+        let id = AircraftId {
+            identifier: None,
+            session_id: Some(label.clone()),
+            timestamp_network: Utc::now(),
+            aircraft_type: AircraftType::Rotorcraft,
+            timestamp_asset: None,
+            session_ended: false,
+        };
+
+        let result = validate_id_message(&id, &Utc::now()).unwrap_err();
+        assert_eq!(
+            result,
+            PostgisError::Aircraft(AircraftError::IdentifierLength)
+        );
+
+        // At the limit is fine
+        label.pop();
+        check_identifier(&label).unwrap();
+
+        ut_info!("success");
+    }
+
+    #[test]
+    fn test_set_identifier_max_length_zero() {
+        let error = set_identifier_max_length(0).unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::Aircraft(AircraftError::IdentifierLength)
+        );
+    }
+
     #[tokio::test]
     async fn ut_aircraft_id_no_identifier() {
         lib_common::logger::get_log_handle().await;
@@ -604,6 +1398,7 @@ mod tests {
             timestamp_network: Utc::now(),
             aircraft_type: AircraftType::Rotorcraft,
             timestamp_asset: None,
+            session_ended: false,
         };
 
         let result = validate_id_message(&id, &Utc::now()).unwrap_err();
@@ -621,9 +1416,9 @@ mod tests {
         for coord in coords {
             let aircraft = AircraftPosition {
                 position: Position {
-                    latitude: coord.0,
-                    longitude: coord.1,
-                    altitude_meters: 100.0,
+                    latitude: Degrees(coord.0),
+                    longitude: Degrees(coord.1),
+                    altitude_meters: Meters(100.0),
                 },
                 identifier: "Aircraft".to_string(),
                 timestamp_network: Utc::now(),
@@ -646,9 +1441,9 @@ mod tests {
         let position = AircraftPosition {
             timestamp_network,
             position: Position {
-                latitude: 0.0,
-                longitude: 0.0,
-                altitude_meters: 0.0,
+                latitude: Degrees(0.0),
+                longitude: Degrees(0.0),
+                altitude_meters: Meters(0.0),
             },
             identifier: "Aircraft".to_string(),
             timestamp_asset: None,
@@ -657,10 +1452,10 @@ mod tests {
         let velocity = AircraftVelocity {
             timestamp_network,
             identifier: "Aircraft".to_string(),
-            velocity_horizontal_ground_mps: 0.0,
+            velocity_horizontal_ground_mps: MetersPerSecond(0.0),
             velocity_horizontal_air_mps: None,
-            velocity_vertical_mps: 0.0,
-            track_angle_degrees: 0.0,
+            velocity_vertical_mps: MetersPerSecond(0.0),
+            track_angle_degrees: Degrees(0.0),
             timestamp_asset: None,
         };
 
@@ -670,6 +1465,7 @@ mod tests {
             session_id: None,
             aircraft_type: AircraftType::Rotorcraft,
             timestamp_asset: None,
+            session_ended: false,
         };
 
         let result = validate_position_message(&position, &Utc::now()).unwrap_err();
@@ -691,10 +1487,34 @@ mod tests {
             "Invalid location provided."
         );
         assert_eq!(format!("{}", AircraftError::Time), "Invalid time provided.");
+        assert_eq!(
+            format!("{}", AircraftError::Velocity),
+            "Invalid velocity provided."
+        );
         assert_eq!(
             format!("{}", AircraftError::Identifier),
             "Invalid identifier(s) provided."
         );
+        assert_eq!(
+            format!("{}", AircraftError::IdentifierLength),
+            "Identifier(s) exceed the maximum length."
+        );
+        assert_eq!(
+            format!("{}", AircraftError::QualityThresholds),
+            "Invalid position quality thresholds."
+        );
+        assert_eq!(
+            format!("{}", AircraftError::Partitioning),
+            "Invalid aircraft table partitioning."
+        );
+        assert_eq!(
+            format!("{}", AircraftError::Rules),
+            "Invalid telemetry validation rules."
+        );
+        assert_eq!(
+            format!("{}", AircraftError::Dedupe),
+            "Invalid duplicate suppression settings."
+        );
         assert_eq!(
             format!("{}", AircraftError::Client),
             "Could not get backend client."
@@ -709,6 +1529,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ut_validate_box() {
+        let bbox = BoundingBox {
+            min_longitude: 4.7,
+            min_latitude: 52.2,
+            max_longitude: 5.1,
+            max_latitude: 52.5,
+        };
+        assert!(validate_box(&bbox).is_ok());
+
+        // Boxes across the antimeridian aren't supported
+        let invalid = BoundingBox {
+            min_longitude: 179.0,
+            max_longitude: -179.0,
+            ..bbox
+        };
+        assert_eq!(
+            validate_box(&invalid),
+            Err(PostgisError::Aircraft(AircraftError::Location))
+        );
+
+        let invalid = BoundingBox {
+            max_latitude: 91.0,
+            ..bbox
+        };
+        assert_eq!(
+            validate_box(&invalid),
+            Err(PostgisError::Aircraft(AircraftError::Location))
+        );
+    }
+
+    #[test]
+    fn test_get_staleness_seconds() {
+        let now = Utc::now();
+        let last_update = now - Duration::try_milliseconds(2500).unwrap();
+        assert_eq!(get_staleness_seconds(last_update, now), 2.5);
+
+        let future = now + Duration::try_seconds(5).unwrap();
+        assert_eq!(get_staleness_seconds(future, now), 0.0);
+    }
+
+    #[test]
+    fn test_get_data_quality() {
+        let thresholds = QualityThresholds {
+            stale_seconds: 10,
+            expired_seconds: 60,
+        };
+
+        assert_eq!(get_data_quality(0.0, &thresholds), DataQuality::Fresh);
+        assert_eq!(get_data_quality(10.0, &thresholds), DataQuality::Fresh);
+        assert_eq!(get_data_quality(10.5, &thresholds), DataQuality::Stale);
+        assert_eq!(get_data_quality(60.0, &thresholds), DataQuality::Stale);
+        assert_eq!(get_data_quality(61.0, &thresholds), DataQuality::Expired);
+    }
+
+    #[test]
+    fn test_set_quality_thresholds_invalid() {
+        let thresholds = QualityThresholds {
+            stale_seconds: 60,
+            expired_seconds: 10,
+        };
+
+        assert_eq!(
+            set_quality_thresholds(thresholds),
+            Err(PostgisError::Aircraft(AircraftError::QualityThresholds))
+        );
+        assert_eq!(get_quality_thresholds(), QualityThresholds::default());
+    }
+
     #[test]
     fn test_validate_identification() {
         validate_identification(
@@ -727,6 +1616,163 @@ mod tests {
         assert_eq!(error, PostgisError::Aircraft(AircraftError::Identifier));
     }
 
+    #[test]
+    fn test_get_next_session_id() {
+        let mut id = AircraftId {
+            identifier: Some("Aircraft".to_string()),
+            session_id: Some("AETH12345".to_string()),
+            timestamp_network: Utc::now(),
+            aircraft_type: AircraftType::Rotorcraft,
+            timestamp_asset: None,
+            session_ended: false,
+        };
+
+        assert_eq!(get_next_session_id(&id), Some(&"AETH12345".to_string()));
+
+        id.session_ended = true;
+        assert_eq!(get_next_session_id(&id), None);
+
+        id.session_id = None;
+        id.session_ended = false;
+        assert_eq!(get_next_session_id(&id), None);
+    }
+
+    #[test]
+    fn test_get_merge_pair() {
+        let mut id = AircraftId {
+            identifier: Some("Aircraft".to_string()),
+            session_id: Some("AETH12345".to_string()),
+            timestamp_network: Utc::now(),
+            aircraft_type: AircraftType::Rotorcraft,
+            timestamp_asset: None,
+            session_ended: false,
+        };
+
+        assert_eq!(
+            get_merge_pair(&id),
+            Some((&"AETH12345".to_string(), &"Aircraft".to_string()))
+        );
+
+        id.session_ended = true;
+        assert_eq!(get_merge_pair(&id), None);
+
+        id.session_ended = false;
+        id.identifier = Some("AETH12345".to_string());
+        assert_eq!(get_merge_pair(&id), None);
+
+        id.identifier = None;
+        assert_eq!(get_merge_pair(&id), None);
+    }
+
+    #[test]
+    fn ut_plausible_reports() {
+        let rules = rules::Rules {
+            max_speed_jump_mps: 10.0,
+            bypass: rules::bypass_list("TEST-1"),
+            ..Default::default()
+        };
+
+        let now = Utc::now();
+        let second = Duration::try_seconds(1).unwrap();
+        let expired = Duration::try_seconds(DEFAULT_EXPIRED_SECONDS as i64 + 1).unwrap();
+        let last: LastReports<f64> = [
+            ("AIRCRAFT-1".to_string(), (20.0, now - second)),
+            ("AIRCRAFT-2".to_string(), (20.0, now - expired)),
+        ]
+        .into_iter()
+        .collect();
+
+        let reports = vec![
+            ("AIRCRAFT-1", 25.0, now),
+            // compared with the report above rather than the stored one
+            ("AIRCRAFT-1", 34.0, now + second),
+            ("AIRCRAFT-1", 50.0, now + second * 2),
+            // the stored report expired
+            ("AIRCRAFT-2", 50.0, now),
+            ("AIRCRAFT-3", 50.0, now),
+            ("AIRCRAFT-3", 80.0, now + second),
+            ("TEST-1", 0.0, now),
+            ("TEST-1", 80.0, now + second),
+        ];
+
+        let accepted = plausible_reports(
+            reports,
+            last,
+            |(identifier, speed, timestamp)| (*identifier, *speed, *timestamp),
+            |identifier, previous, next| rules.check_speed_jump(identifier, *previous, *next),
+        );
+
+        let speeds: Vec<(&str, f64)> = accepted
+            .into_iter()
+            .map(|(identifier, speed, _)| (identifier, speed))
+            .collect();
+
+        assert_eq!(
+            speeds,
+            vec![
+                ("AIRCRAFT-1", 25.0),
+                ("AIRCRAFT-1", 34.0),
+                ("AIRCRAFT-2", 50.0),
+                ("AIRCRAFT-3", 50.0),
+                ("TEST-1", 0.0),
+                ("TEST-1", 80.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn ut_changed_reports() {
+        let settings = dedupe::Settings {
+            velocity_epsilon_mps: 0.5,
+            ..Default::default()
+        };
+
+        let now = Utc::now();
+        let second = Duration::try_seconds(1).unwrap();
+        let parked = |horizontal_ground_mps| Velocity {
+            horizontal_ground_mps,
+            vertical_mps: 0.0,
+            track_angle_degrees: 0.0,
+        };
+
+        let last: LastReports<Velocity> = [("AIRCRAFT-1".to_string(), (parked(0.0), now))]
+            .into_iter()
+            .collect();
+
+        let heartbeat = second * settings.heartbeat_seconds as i32;
+        let reports = vec![
+            ("AIRCRAFT-1", 0.1, now + second),
+            ("AIRCRAFT-1", 0.2, now + second * 2),
+            // the unchanged state is written once per heartbeat
+            ("AIRCRAFT-1", 0.2, now + heartbeat),
+            ("AIRCRAFT-1", 0.2, now + heartbeat + second),
+            ("AIRCRAFT-1", 5.0, now + heartbeat + second * 2),
+            ("AIRCRAFT-2", 0.0, now),
+            ("AIRCRAFT-2", 0.0, now + second),
+        ];
+
+        let written = changed_reports(
+            reports,
+            last,
+            |(identifier, speed, timestamp)| (*identifier, parked(*speed), *timestamp),
+            |stored, next| settings.is_duplicate_velocity(stored, next),
+        );
+
+        let written: Vec<(&str, f64)> = written
+            .into_iter()
+            .map(|(identifier, speed, _)| (identifier, speed))
+            .collect();
+
+        assert_eq!(
+            written,
+            vec![
+                ("AIRCRAFT-1", 0.2),
+                ("AIRCRAFT-1", 5.0),
+                ("AIRCRAFT-2", 0.0)
+            ]
+        );
+    }
+
     #[test]
     fn test_get_table_name() {
         assert_eq!(get_table_name(), r#""arrow"."aircraft""#);
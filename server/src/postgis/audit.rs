@@ -0,0 +1,429 @@
+//! Audit records of the best paths returned.
+//!
+//! Regulators may ask why a route was issued. While auditing is enabled,
+//!  each best path response is recorded with the parameters of its request,
+//!  the geometry of the best path, the number of candidates rejected for
+//!  conflicts, and the airspace change cursor at the time. The zones and
+//!  waypoints the search saw can then be rebuilt from the sync history up
+//!  to that cursor.
+//!
+//! Records are removed by the maintenance passes after their retention
+//!  period.
+
+use super::identifier::{check_identifier, Entity};
+use super::statements::Statement;
+use super::{OnceCell, PostgisError, DEFAULT_SRID};
+use crate::grpc::server::grpc_server::{
+    BestPathAudit, BestPathRequest, BestPathResponse, FlightPriority, GetBestPathAuditsRequest,
+    GetBestPathAuditsResponse, NodeType, PointZ as GrpcPointZ,
+};
+use crate::types::AircraftType;
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Timestamp, Utc};
+use lib_common::uuid::Uuid;
+use postgis::ewkb::{LineStringT, PointZ};
+use serde_json::{json, Value};
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio_postgres::Row;
+
+/// Default number of days best path records are kept
+pub const DEFAULT_RETENTION_DAYS: u32 = 90;
+
+/// Whether best path responses are recorded
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Possible errors with best path audits
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AuditError {
+    /// Invalid flight identifier
+    Identifier,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for AuditError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            AuditError::Identifier => write!(f, "Invalid flight identifier provided."),
+            AuditError::Client => write!(f, "Could not get backend client."),
+            AuditError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// Sets whether best path responses are recorded
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether best path responses are recorded
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Gets the name of this module's table
+pub(super) fn get_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."best_path_audits""#, super::get_schema()))
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Audit(AuditError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Audit(AuditError::Client)
+        })
+}
+
+/// Initialize the best path audits table in the PostGIS database
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn psql_init() -> Result<(), PostgisError> {
+    let max_length = super::aircraft::get_identifier_max_length();
+    let mut statements = vec![
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {table_name} (
+            "identifier" UUID UNIQUE NOT NULL PRIMARY KEY,
+            "flight_identifier" VARCHAR({max_length}),
+            "aircraft_identifier" VARCHAR({max_length}),
+            "request_parameters" JSONB NOT NULL,
+            "geom" GEOMETRY(LINESTRINGZ, {DEFAULT_SRID}) NOT NULL,
+            "rejected_count" INTEGER NOT NULL,
+            "airspace_cursor" BIGINT NOT NULL,
+            "created" TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );"#,
+            table_name = get_table_name()
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "best_path_audits_flight_idx" ON {table_name} ("flight_identifier", "created");"#,
+            table_name = get_table_name()
+        ),
+        // Finds the records past their retention period
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "best_path_audits_created_idx" ON {table_name} ("created");"#,
+            table_name = get_table_name()
+        ),
+    ];
+
+    // Tables created by earlier versions used VARCHAR(255)
+    statements.extend(
+        super::psql_widen_varchar(
+            "best_path_audits",
+            &["flight_identifier", "aircraft_identifier"],
+            max_length,
+        )
+        .await?,
+    );
+
+    super::psql_transaction(statements).await
+}
+
+/// The parameters of a best path request, as a JSON object. Enumerations
+///  are written by name, timestamps in RFC 3339.
+fn request_parameters(request: &BestPathRequest) -> Value {
+    let node_type = |value: i32| {
+        NodeType::try_from(value).map_or_else(|_| value.to_string(), |t| t.as_str_name().into())
+    };
+
+    let time = |timestamp: &Option<Timestamp>| {
+        timestamp
+            .clone()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+    };
+
+    json!({
+        "origin_identifier": request.origin_identifier,
+        "origin_type": node_type(request.origin_type),
+        "target_identifier": request.target_identifier,
+        "target_type": node_type(request.target_type),
+        "time_start": time(&request.time_start),
+        "time_end": time(&request.time_end),
+        "limit": request.limit,
+        "max_path_node_count": request.max_path_node_count,
+        "weights": request.weights.map(|w| json!({
+            "distance": w.distance,
+            "time": w.time,
+            "ground_risk": w.ground_risk,
+            "noise": w.noise,
+            "energy": w.energy,
+        })),
+        "include_simulated": request.include_simulated,
+        "priority": FlightPriority::try_from(request.priority)
+            .map_or_else(|_| request.priority.to_string(), |p| p.as_str_name().into()),
+        "max_window_offset_minutes": request.max_window_offset_minutes,
+        "aircraft_identifier": request.aircraft_identifier,
+        "flight_identifier": request.flight_identifier,
+        "operator_identifier": request.operator_identifier,
+        "arrival_window_minutes": request.arrival_window_minutes,
+        "aircraft_type": request
+            .aircraft_type
+            .map(|t| AircraftType::try_from(t).map_or_else(|_| t.to_string(), |t| t.to_string())),
+    })
+}
+
+/// The geometry of the best path of a response, `None` without paths
+fn best_path_geom(response: &BestPathResponse) -> Option<LineStringT<PointZ>> {
+    let path = response.paths.first()?;
+    let points = path
+        .path
+        .iter()
+        .filter_map(|node| node.geom.map(PointZ::from))
+        .collect();
+
+    Some(LineStringT {
+        points,
+        srid: Some(DEFAULT_SRID),
+    })
+}
+
+/// Records the best path of a response to a request
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn record(
+    request: &BestPathRequest,
+    response: &BestPathResponse,
+) -> Result<(), PostgisError> {
+    let Some(geom) = best_path_geom(response) else {
+        return Ok(());
+    };
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::InsertBestPathAudit.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Audit(AuditError::DBError)
+        })?;
+
+    client
+        .execute(
+            &stmt,
+            &[
+                &Uuid::new_v4(),
+                &request.flight_identifier,
+                &request.aircraft_identifier,
+                &request_parameters(request).to_string(),
+                &geom,
+                &(response.rejected_count as i32),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not insert best path audit: {}", e);
+            PostgisError::Audit(AuditError::DBError)
+        })?;
+
+    Ok(())
+}
+
+/// Converts a row of the best path audits table
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test, no way to create a Row without querying it
+fn process_audit_row(row: &Row) -> Result<BestPathAudit, tokio_postgres::Error> {
+    let identifier: Uuid = row.try_get("identifier")?;
+    let geom: LineStringT<PointZ> = row.try_get("geom")?;
+    let rejected_count: i32 = row.try_get("rejected_count")?;
+    let airspace_cursor: i64 = row.try_get("airspace_cursor")?;
+    let created: DateTime<Utc> = row.try_get("created")?;
+
+    Ok(BestPathAudit {
+        identifier: identifier.to_string(),
+        flight_identifier: row.try_get("flight_identifier")?,
+        aircraft_identifier: row.try_get("aircraft_identifier")?,
+        request_parameters: row.try_get("request_parameters")?,
+        path: geom.points.into_iter().map(GrpcPointZ::from).collect(),
+        rejected_count: rejected_count.max(0) as u32,
+        airspace_cursor: airspace_cursor.max(0) as u64,
+        created: Some(created.into()),
+    })
+}
+
+/// Gets the best paths recorded for a flight, newest first
+///  At most [`MAX_PAGE_SIZE`](super::utils::MAX_PAGE_SIZE) are returned.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn get_best_path_audits(
+    request: GetBestPathAuditsRequest,
+) -> Result<GetBestPathAuditsResponse, PostgisError> {
+    postgis_debug!("entry.");
+    check_identifier(Entity::Flight, &request.flight_identifier).map_err(|e| {
+        postgis_error!(
+            "invalid flight identifier {}: {}",
+            request.flight_identifier,
+            e
+        );
+        PostgisError::Audit(AuditError::Identifier)
+    })?;
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::GetBestPathAudits.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Audit(AuditError::DBError)
+        })?;
+
+    let audits = client
+        .query(
+            &stmt,
+            &[
+                &request.flight_identifier,
+                &(super::utils::MAX_PAGE_SIZE as i64),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get best path audits: {}", e);
+            PostgisError::Audit(AuditError::DBError)
+        })?
+        .iter()
+        .map(process_audit_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            postgis_error!("could not parse best path audit row: {}", e);
+            PostgisError::Audit(AuditError::DBError)
+        })?;
+
+    postgis_debug!("found {} best path audits.", audits.len());
+    Ok(GetBestPathAuditsResponse { audits })
+}
+
+/// Removes up to `limit` records created before the cutoff. Returns the
+///  number of records removed.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn purge_audits(cutoff: DateTime<Utc>, limit: i64) -> Result<u64, PostgisError> {
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::DeleteBestPathAudits.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Audit(AuditError::DBError)
+        })?;
+
+    client
+        .execute(&stmt, &[&cutoff, &limit])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not purge best path audits: {}", e);
+            PostgisError::Audit(AuditError::DBError)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::server::grpc_server::{CostWeights, Path, PathNode};
+
+    fn request() -> BestPathRequest {
+        BestPathRequest {
+            origin_identifier: "vertiport-a".to_string(),
+            target_identifier: "vertiport-b".to_string(),
+            origin_type: NodeType::Vertiport as i32,
+            target_type: NodeType::Vertiport as i32,
+            limit: 3,
+            weights: Some(CostWeights {
+                distance: 1.0,
+                time: 2.0,
+                ground_risk: 0.0,
+                noise: 0.0,
+                energy: 0.0,
+            }),
+            priority: FlightPriority::Emergency as i32,
+            flight_identifier: Some("FLIGHT-1".to_string()),
+            aircraft_type: Some(AircraftType::Rotorcraft as i32),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ut_request_parameters() {
+        let time_start = Utc::now();
+        let parameters = request_parameters(&BestPathRequest {
+            time_start: Some(time_start.into()),
+            ..request()
+        });
+
+        assert_eq!(parameters["origin_type"], "VERTIPORT");
+        assert_eq!(parameters["priority"], "EMERGENCY");
+        assert_eq!(
+            parameters["aircraft_type"],
+            AircraftType::Rotorcraft.to_string()
+        );
+        assert_eq!(parameters["limit"], 3);
+        assert_eq!(parameters["weights"]["time"], 2.0);
+        assert_eq!(parameters["time_start"], time_start.to_rfc3339());
+        assert_eq!(parameters["time_end"], Value::Null);
+        assert_eq!(parameters["flight_identifier"], "FLIGHT-1");
+
+        // unknown values are kept as numbers
+        let parameters = request_parameters(&BestPathRequest {
+            origin_type: 99,
+            ..request()
+        });
+        assert_eq!(parameters["origin_type"], "99");
+    }
+
+    #[test]
+    fn ut_best_path_geom() {
+        let node = |latitude: f64| PathNode {
+            geom: Some(GrpcPointZ {
+                latitude,
+                longitude: 4.91,
+                altitude_meters: 80.0,
+            }),
+            ..Default::default()
+        };
+
+        let mut response = BestPathResponse::default();
+        assert!(best_path_geom(&response).is_none());
+
+        response.paths = vec![
+            Path {
+                path: vec![node(52.37), node(52.38)],
+                ..Default::default()
+            },
+            Path {
+                path: vec![node(52.30)],
+                ..Default::default()
+            },
+        ];
+
+        let geom = best_path_geom(&response).unwrap();
+        assert_eq!(geom.srid, Some(DEFAULT_SRID));
+        assert_eq!(geom.points.len(), 2);
+        assert_eq!(geom.points[1].y, 52.38);
+        assert_eq!(geom.points[1].z, 80.0);
+    }
+
+    #[test]
+    fn test_audit_error_display() {
+        assert_eq!(
+            AuditError::Identifier.to_string(),
+            "Invalid flight identifier provided."
+        );
+        assert_eq!(
+            AuditError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(AuditError::DBError.to_string(), "Database error.");
+    }
+}
@@ -0,0 +1,489 @@
+//! Authorizations for aircraft and flights to enter zones.
+//!
+//! A zone normally blocks every path through it while it is active. An
+//!  authorization lets one aircraft or flight in during a time window, so
+//!  that paths checked for them pass through the zone as if it were clear.
+
+use super::identifier::{check_identifier, Entity};
+use super::statements::Statement;
+use super::{OnceCell, PostgisError};
+use crate::grpc::server::grpc_server::{
+    ListAuthorizationsRequest, ListAuthorizationsResponse,
+    ZoneAuthorization as ResponseZoneAuthorization, ZoneAuthorizationRequest,
+};
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Utc};
+use lib_common::uuid::Uuid;
+use std::fmt::{self, Display, Formatter};
+use tokio_postgres::Row;
+
+/// Max length of an approval reference
+pub const MAX_REFERENCE_LENGTH: usize = 255;
+
+/// Possible errors with zone authorization requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AuthorizationError {
+    /// Invalid or unknown zone
+    Zone,
+
+    /// Invalid aircraft, flight or operator identifier, or neither an
+    ///  aircraft nor a flight was provided
+    Entrant,
+
+    /// Invalid validity window
+    Time,
+
+    /// Invalid approval reference
+    Reference,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for AuthorizationError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            AuthorizationError::Zone => write!(f, "Invalid zone provided."),
+            AuthorizationError::Entrant => write!(f, "Invalid aircraft or flight provided."),
+            AuthorizationError::Time => write!(f, "Invalid time window provided."),
+            AuthorizationError::Reference => write!(f, "Invalid reference provided."),
+            AuthorizationError::Client => write!(f, "Could not get backend client."),
+            AuthorizationError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// The aircraft and flight a path is checked for, zones either of them is
+///  authorized to enter don't conflict with the path. Volumes reserved by
+///  the operator of the flight don't conflict with it either.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entrant {
+    /// Aircraft flying the path
+    pub aircraft_identifier: Option<String>,
+
+    /// Flight of the path
+    pub flight_identifier: Option<String>,
+
+    /// Operator of the flight
+    pub operator_identifier: Option<String>,
+}
+
+impl Entrant {
+    /// Validates the identifiers that are provided
+    pub fn new(
+        aircraft_identifier: Option<String>,
+        flight_identifier: Option<String>,
+    ) -> Result<Self, AuthorizationError> {
+        check_optional_identifier(Entity::Aircraft, &aircraft_identifier)?;
+        check_optional_identifier(Entity::Flight, &flight_identifier)?;
+
+        Ok(Entrant {
+            aircraft_identifier,
+            flight_identifier,
+            operator_identifier: None,
+        })
+    }
+
+    /// Sets the operator of the flight, validated if one is provided
+    pub fn with_operator(
+        self,
+        operator_identifier: Option<String>,
+    ) -> Result<Self, AuthorizationError> {
+        check_optional_identifier(Entity::Operator, &operator_identifier)?;
+
+        Ok(Entrant {
+            operator_identifier,
+            ..self
+        })
+    }
+
+    /// If neither an aircraft nor a flight is known
+    pub fn is_empty(&self) -> bool {
+        self.aircraft_identifier.is_none() && self.flight_identifier.is_none()
+    }
+}
+
+/// Checks an identifier if one is provided
+fn check_optional_identifier(
+    entity: Entity,
+    identifier: &Option<String>,
+) -> Result<(), AuthorizationError> {
+    let Some(identifier) = identifier else {
+        return Ok(());
+    };
+
+    check_identifier(entity, identifier).map_err(|e| {
+        postgis_error!("invalid {:?} identifier {}: {}", entity, identifier, e);
+        match entity {
+            Entity::Zone => AuthorizationError::Zone,
+            _ => AuthorizationError::Entrant,
+        }
+    })
+}
+
+/// A validated request to enter a zone
+#[derive(Debug, Clone)]
+struct ZoneAuthorization {
+    zone_identifier: String,
+    entrant: Entrant,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    reference: Option<String>,
+}
+
+impl TryFrom<ZoneAuthorizationRequest> for ZoneAuthorization {
+    type Error = AuthorizationError;
+
+    fn try_from(request: ZoneAuthorizationRequest) -> Result<Self, Self::Error> {
+        check_identifier(Entity::Zone, &request.zone_identifier).map_err(|e| {
+            postgis_error!("invalid zone identifier {}: {}", request.zone_identifier, e);
+            AuthorizationError::Zone
+        })?;
+
+        let entrant = Entrant::new(request.aircraft_identifier, request.flight_identifier)?;
+        if entrant.is_empty() {
+            postgis_error!("no aircraft or flight provided.");
+            return Err(AuthorizationError::Entrant);
+        }
+
+        let (Some(time_start), Some(time_end)) = (request.time_start, request.time_end) else {
+            postgis_error!("time_start and time_end are required.");
+            return Err(AuthorizationError::Time);
+        };
+
+        let time_start: DateTime<Utc> = time_start.into();
+        let time_end: DateTime<Utc> = time_end.into();
+        if time_end <= time_start {
+            postgis_error!(
+                "time_end {} is not after time_start {}.",
+                time_end,
+                time_start
+            );
+            return Err(AuthorizationError::Time);
+        }
+
+        if let Some(reference) = &request.reference {
+            if reference.trim().is_empty() || reference.len() > MAX_REFERENCE_LENGTH {
+                postgis_error!("invalid reference: {}", reference);
+                return Err(AuthorizationError::Reference);
+            }
+        }
+
+        Ok(ZoneAuthorization {
+            zone_identifier: request.zone_identifier,
+            entrant,
+            time_start,
+            time_end,
+            reference: request.reference,
+        })
+    }
+}
+
+/// Gets the name of this module's table
+pub(super) fn get_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."zone_authorizations""#, super::get_schema()))
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Authorization(AuthorizationError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Authorization(AuthorizationError::Client)
+        })
+}
+
+/// Initialize the zone authorizations table in the PostGIS database
+///  Authorizations are removed along with their zone.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn psql_init() -> Result<(), PostgisError> {
+    let max_length = super::aircraft::get_identifier_max_length();
+    let mut statements = vec![
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {table_name} (
+            "identifier" UUID UNIQUE NOT NULL PRIMARY KEY,
+            "zone_identifier" VARCHAR(255) NOT NULL
+                REFERENCES {zones_table_name} ("identifier")
+                ON UPDATE CASCADE ON DELETE CASCADE,
+            "aircraft_identifier" VARCHAR({max_length}),
+            "flight_identifier" VARCHAR({max_length}),
+            "time_start" TIMESTAMPTZ NOT NULL,
+            "time_end" TIMESTAMPTZ NOT NULL,
+            "reference" VARCHAR(255),
+            "created" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            CHECK ("aircraft_identifier" IS NOT NULL OR "flight_identifier" IS NOT NULL)
+        );"#,
+            table_name = get_table_name(),
+            zones_table_name = super::zone::get_table_name()
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "zone_authorizations_zone_idx" ON {table_name} ("zone_identifier", "time_start", "time_end");"#,
+            table_name = get_table_name()
+        ),
+    ];
+
+    // Tables created by earlier versions used VARCHAR(255)
+    statements.extend(
+        super::psql_widen_varchar(
+            "zone_authorizations",
+            &["aircraft_identifier", "flight_identifier"],
+            max_length,
+        )
+        .await?,
+    );
+
+    super::psql_transaction(statements).await
+}
+
+/// Converts a row of the zone authorizations table
+fn process_authorization_row(row: &Row) -> Result<ResponseZoneAuthorization, PostgisError> {
+    let parse = |e: tokio_postgres::Error| {
+        postgis_error!("could not parse zone authorization row: {}", e);
+        PostgisError::Authorization(AuthorizationError::DBError)
+    };
+
+    let identifier: Uuid = row.try_get("identifier").map_err(parse)?;
+    let time_start: DateTime<Utc> = row.try_get("time_start").map_err(parse)?;
+    let time_end: DateTime<Utc> = row.try_get("time_end").map_err(parse)?;
+
+    Ok(ResponseZoneAuthorization {
+        identifier: identifier.to_string(),
+        zone_identifier: row.try_get("zone_identifier").map_err(parse)?,
+        aircraft_identifier: row.try_get("aircraft_identifier").map_err(parse)?,
+        flight_identifier: row.try_get("flight_identifier").map_err(parse)?,
+        time_start: Some(time_start.into()),
+        time_end: Some(time_end.into()),
+        reference: row.try_get("reference").map_err(parse)?,
+    })
+}
+
+/// Grants an aircraft or flight entry to a zone during a time window
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn request_zone_authorization(
+    request: ZoneAuthorizationRequest,
+) -> Result<ResponseZoneAuthorization, PostgisError> {
+    postgis_debug!("entry.");
+    let authorization =
+        ZoneAuthorization::try_from(request).map_err(PostgisError::Authorization)?;
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::InsertZoneAuthorization.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Authorization(AuthorizationError::DBError)
+        })?;
+
+    let row = client
+        .query_opt(
+            &stmt,
+            &[
+                &Uuid::new_v4(),
+                &authorization.zone_identifier,
+                &authorization.entrant.aircraft_identifier,
+                &authorization.entrant.flight_identifier,
+                &authorization.time_start,
+                &authorization.time_end,
+                &authorization.reference,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not insert zone authorization: {}", e);
+            PostgisError::Authorization(AuthorizationError::DBError)
+        })?
+        .ok_or_else(|| {
+            postgis_error!("unknown zone: {}", authorization.zone_identifier);
+            PostgisError::Authorization(AuthorizationError::Zone)
+        })?;
+
+    let authorization = process_authorization_row(&row)?;
+    postgis_info!("granted zone authorization: {:?}", authorization);
+    Ok(authorization)
+}
+
+/// Lists zone authorizations, by start of their window
+///  At most [`MAX_PAGE_SIZE`](super::utils::MAX_PAGE_SIZE) are returned.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn list_authorizations(
+    request: ListAuthorizationsRequest,
+) -> Result<ListAuthorizationsResponse, PostgisError> {
+    postgis_debug!("entry.");
+    check_optional_identifier(Entity::Zone, &request.zone_identifier)
+        .map_err(PostgisError::Authorization)?;
+
+    let entrant = Entrant::new(request.aircraft_identifier, request.flight_identifier)
+        .map_err(PostgisError::Authorization)?;
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::GetZoneAuthorizations.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Authorization(AuthorizationError::DBError)
+        })?;
+
+    let authorizations = client
+        .query(
+            &stmt,
+            &[
+                &request.zone_identifier,
+                &entrant.aircraft_identifier,
+                &entrant.flight_identifier,
+                &request.include_expired,
+                &(super::utils::MAX_PAGE_SIZE as i64),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not list zone authorizations: {}", e);
+            PostgisError::Authorization(AuthorizationError::DBError)
+        })?
+        .iter()
+        .map(process_authorization_row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    postgis_debug!("found {} zone authorizations.", authorizations.len());
+    Ok(ListAuthorizationsResponse { authorizations })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Duration;
+
+    fn request() -> ZoneAuthorizationRequest {
+        let time_start = Utc::now();
+        ZoneAuthorizationRequest {
+            zone_identifier: "NFZ-1".to_string(),
+            aircraft_identifier: Some("N12345".to_string()),
+            flight_identifier: None,
+            time_start: Some(time_start.into()),
+            time_end: Some((time_start + Duration::try_hours(1).unwrap()).into()),
+            reference: Some("PERMIT-42".to_string()),
+        }
+    }
+
+    #[test]
+    fn ut_zone_authorization_try_from() {
+        let authorization = ZoneAuthorization::try_from(request()).unwrap();
+        assert_eq!(authorization.zone_identifier, "NFZ-1");
+        assert_eq!(
+            authorization.entrant.aircraft_identifier,
+            Some("N12345".to_string())
+        );
+        assert!(authorization.time_start < authorization.time_end);
+
+        let error = ZoneAuthorization::try_from(ZoneAuthorizationRequest {
+            zone_identifier: "".to_string(),
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, AuthorizationError::Zone);
+
+        let error = ZoneAuthorization::try_from(ZoneAuthorizationRequest {
+            aircraft_identifier: None,
+            flight_identifier: None,
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, AuthorizationError::Entrant);
+
+        let error = ZoneAuthorization::try_from(ZoneAuthorizationRequest {
+            time_end: request().time_start,
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, AuthorizationError::Time);
+
+        let error = ZoneAuthorization::try_from(ZoneAuthorizationRequest {
+            time_start: None,
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, AuthorizationError::Time);
+
+        for reference in [" ".to_string(), "A".repeat(MAX_REFERENCE_LENGTH + 1)] {
+            let error = ZoneAuthorization::try_from(ZoneAuthorizationRequest {
+                reference: Some(reference),
+                ..request()
+            })
+            .unwrap_err();
+            assert_eq!(error, AuthorizationError::Reference);
+        }
+    }
+
+    #[test]
+    fn ut_entrant_new() {
+        let entrant = Entrant::new(None, Some("FLIGHT-1".to_string())).unwrap();
+        assert!(!entrant.is_empty());
+        assert!(Entrant::default().is_empty());
+
+        let error = Entrant::new(Some("".to_string()), None).unwrap_err();
+        assert_eq!(error, AuthorizationError::Entrant);
+    }
+
+    #[test]
+    fn ut_entrant_with_operator() {
+        let entrant = Entrant::new(None, Some("FLIGHT-1".to_string()))
+            .unwrap()
+            .with_operator(Some("OPERATOR-1".to_string()))
+            .unwrap();
+        assert_eq!(entrant.operator_identifier, Some("OPERATOR-1".to_string()));
+        assert_eq!(entrant.flight_identifier, Some("FLIGHT-1".to_string()));
+
+        // an operator alone isn't an entrant of a zone
+        let entrant = Entrant::default()
+            .with_operator(Some("OPERATOR-1".to_string()))
+            .unwrap();
+        assert!(entrant.is_empty());
+
+        let error = Entrant::default()
+            .with_operator(Some("".to_string()))
+            .unwrap_err();
+        assert_eq!(error, AuthorizationError::Entrant);
+    }
+
+    #[test]
+    fn test_authorization_error_display() {
+        assert_eq!(
+            AuthorizationError::Zone.to_string(),
+            "Invalid zone provided."
+        );
+        assert_eq!(
+            AuthorizationError::Entrant.to_string(),
+            "Invalid aircraft or flight provided."
+        );
+        assert_eq!(
+            AuthorizationError::Time.to_string(),
+            "Invalid time window provided."
+        );
+        assert_eq!(
+            AuthorizationError::Reference.to_string(),
+            "Invalid reference provided."
+        );
+        assert_eq!(
+            AuthorizationError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(AuthorizationError::DBError.to_string(), "Database error.");
+    }
+}
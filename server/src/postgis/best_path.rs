@@ -1,112 +1,132 @@
 //! This module contains functions for routing between nodes.
+use super::authorization::Entrant;
+use super::identifier::{check_identifier, Entity};
+use super::noise::get_noise_exposure;
+use super::routing::{
+    a_star, get_altitude_limits, get_default_weights, get_separation_margins,
+    get_widest_separation_margins, AltitudeLimits, ConflictChecker, CostWeights, Exposure,
+    NodeProvider, PathNode, SearchLimits, FLIGHT_LEVELS, MAX_FLIGHT_DISTANCE_METERS,
+};
+use super::statements::Statement;
 use super::PostgisError;
 use super::DEFAULT_SRID;
 use crate::grpc::server::grpc_server::{
-    BestPathRequest, NodeType, Path as GrpcPath, PathNode as GrpcPathNode, PointZ as GrpcPointZ,
+    BestPathRequest, BestPathResponse, CostWeights as GrpcCostWeights, DistanceMatrixEntry,
+    DistanceMatrixRequest, FlightPriority, NodeType, Path as GrpcPath, PathBlocker,
+    PathCosts as GrpcPathCosts, PathFailureReason, PathNode as GrpcPathNode, PointZ as GrpcPointZ,
+    ReplanAdvisory, VertiportStatus,
 };
 use crate::postgis::aircraft::get_aircraft_pointz;
 use crate::postgis::flight::FlightError;
 use crate::postgis::utils::Segment;
-use crate::postgis::vertiport::get_vertiport_centroidz;
+use crate::postgis::vertiport::{get_vertiport_centroid, get_vertiport_centroidz, VertiportError};
+use crate::types::AircraftType;
 use lib_common::time::Duration;
 use lib_common::time::*;
 use num_traits::FromPrimitive;
 use postgis::ewkb::{LineStringT, PointZ};
-use std::collections::{BinaryHeap, VecDeque};
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tonic::async_trait;
 
 /// Look for waypoints within N meters when routing between two points
 ///  Saves computation time by doing shortest path on a smaller graph
 const WAYPOINT_RANGE_METERS: f32 = 10_000.0;
 
-/// Elevations to search for valid paths
-const FLIGHT_LEVELS: [f32; 3] = [40.0, 80.0, 120.0];
+/// Max number of nodes in best path (to circumvent no fly zones)
+pub const MAX_PATH_NODE_COUNT_LIMIT: usize = 10;
+
+/// Min number of nodes in best path (origin and target)
+pub const MIN_PATH_NODE_COUNT_LIMIT: usize = 2;
 
-/// Max distance a flight can travel
-const MAX_FLIGHT_DISTANCE_METERS: f32 = 300_000.;
+/// Default number of nodes in a short path (one waypoint)
+pub const DEFAULT_PATH_NODE_COUNT: usize = 3;
 
-/// Max number of nodes in best path (to circumvent no fly zones)
-const MAX_PATH_NODE_COUNT_LIMIT: usize = 5;
+/// Max number of blocking flights reported for a search
+pub const MAX_BLOCKER_COUNT: usize = 5;
+
+/// Straight-line distance covered per additional default path node
+const PATH_NODE_DISTANCE_METERS: f32 = 20_000.0;
 
 /// Max paths to return
-const MAX_PATH_COUNT_LIMIT: usize = 5;
+pub const MAX_PATH_COUNT_LIMIT: usize = 5;
 
 /// Best Path Time Limit
 ///  ~1 seconds per aircraft availability check
 ///  Prevent runaway calculation with impossible to reach target
 const BEST_PATH_TIME_LIMIT_MS: i64 = 1000;
 
-impl From<PointZ> for GrpcPointZ {
-    fn from(field: PointZ) -> Self {
-        Self {
-            longitude: field.x,
-            latitude: field.y,
-            altitude_meters: field.z as f32,
-        }
-    }
-}
+/// Time kept back from the caller's deadline to vet the found paths
+///  and send the response
+const DEADLINE_MARGIN_MS: i64 = 100;
 
-#[derive(Debug, Clone)]
-struct PathNode {
-    node_type: i32,
-    identifier: String,
-    geom: PointZ,
-}
+/// Max shift of a search window, in minutes
+pub const MAX_WINDOW_OFFSET_MINUTES: u32 = 120;
 
-impl PartialEq for PathNode {
-    fn eq(&self, other: &Self) -> bool {
-        self.identifier == other.identifier
-    }
-}
+/// Step between two shifted search windows, in minutes
+const WINDOW_OFFSET_STEP_MINUTES: u32 = 5;
 
-#[derive(Debug, Clone)]
-struct Path {
-    path: Vec<PathNode>,
-    distance_traversed_meters: f32,
-    distance_to_target_meters: f32,
-}
+/// Time limit for the searches in shifted windows, on top of the search
+///  in the requested window
+const WINDOW_SEARCH_TIME_LIMIT_MS: i64 = 5000;
 
-impl Path {
-    fn heuristic(&self) -> f32 {
-        self.distance_traversed_meters + self.distance_to_target_meters
-    }
-}
+/// Default margin around a path's estimated arrival within which flights
+///  arriving at the same vertiport are counted, in minutes
+pub const DEFAULT_ARRIVAL_WINDOW_MINUTES: u32 = 10;
 
-// Reverse the ordering so that the BinaryHeap is a min-heap
-impl Ord for Path {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let oh = other.heuristic();
-        let sh = self.heuristic();
-
-        if oh < sh {
-            std::cmp::Ordering::Less
-        } else if oh > sh {
-            std::cmp::Ordering::Greater
-        } else {
-            std::cmp::Ordering::Equal
-        }
-    }
-}
+/// Max margin around a path's estimated arrival, in minutes
+pub const MAX_ARRIVAL_WINDOW_MINUTES: u32 = 120;
+
+/// Max number of origins or targets in a distance matrix request
+pub const MAX_DISTANCE_MATRIX_NODE_COUNT: usize = 25;
+
+/// Paths closer than this distance to a filed flight intersect it, both
+///  horizontally and vertically, unless margins are configured for the
+///  aircraft type
+pub const FLIGHT_SEPARATION_METERS: f64 = 10.0;
+
+/// Max number of single-waypoint detours tried per blocked matrix pair
+const MAX_DETOUR_ATTEMPTS: usize = 5;
 
-impl PartialOrd for Path {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl From<GrpcCostWeights> for CostWeights {
+    fn from(weights: GrpcCostWeights) -> Self {
+        CostWeights {
+            distance: weights.distance,
+            time: weights.time,
+            ground_risk: weights.ground_risk,
+            noise: weights.noise,
+            energy: weights.energy,
+        }
     }
 }
 
-impl PartialEq for Path {
-    fn eq(&self, other: &Self) -> bool {
-        self.heuristic() == other.heuristic()
+impl From<PointZ> for GrpcPointZ {
+    fn from(field: PointZ) -> Self {
+        Self {
+            longitude: field.x,
+            latitude: field.y,
+            altitude_meters: field.z as f32,
+        }
     }
 }
 
-impl Eq for Path {}
-
 /// Possible errors with path requests
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PathError {
-    /// No path was found
-    NoPath,
+    /// No path was found, the direct route is blocked and there are
+    ///  no waypoints nearby to get around it
+    NoWaypointsNearby,
+
+    /// No path was found, every route exceeds the max flight distance
+    ExceededMaxDistance,
+
+    /// No path was found, every route intersects a zone or flight plan
+    AllCandidatesConflicted,
+
+    /// No path was found before the search time limit was reached
+    TimeBudgetExhausted,
 
     /// Invalid start node
     InvalidStartNode,
@@ -140,12 +160,61 @@ pub enum PathError {
 
     /// Flight Plan Intersection
     FlightPlanIntersection,
+
+    /// Volume Reservation Intersection
+    ReservationIntersection,
+
+    /// Invalid number of nodes
+    InvalidNodeCount,
+
+    /// Invalid path cost weights
+    InvalidWeights,
+
+    /// Invalid flight priority
+    InvalidPriority,
+
+    /// Invalid search window offset
+    InvalidWindowOffset,
+
+    /// Invalid arrival window
+    InvalidArrivalWindow,
+
+    /// Invalid aircraft type
+    InvalidAircraftType,
+
+    /// Invalid aircraft altitude limits configuration
+    InvalidAltitudeLimits,
+
+    /// Invalid flight separation margins configuration
+    InvalidSeparationMargins,
+
+    /// No path was found, no flight level is within the altitude limits
+    ///  of the aircraft type
+    OutsideAltitudeLimits,
+
+    /// No path was found, every route arrives while the target vertiport
+    ///  is outside its operating hours
+    OutsideOperatingHours,
 }
 
 impl Display for PathError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
-            PathError::NoPath => write!(f, "No path was found."),
+            PathError::NoWaypointsNearby => {
+                write!(f, "No path was found, no waypoints near blocked route.")
+            }
+            PathError::ExceededMaxDistance => {
+                write!(f, "No path was found, max flight distance exceeded.")
+            }
+            PathError::AllCandidatesConflicted => {
+                write!(
+                    f,
+                    "No path was found, all routes intersect zones or flights."
+                )
+            }
+            PathError::TimeBudgetExhausted => {
+                write!(f, "No path was found, search time limit reached.")
+            }
             PathError::InvalidStartNode => write!(f, "Invalid start node."),
             PathError::InvalidEndNode => write!(f, "Invalid end node."),
             PathError::InvalidStartTime => write!(f, "Invalid start time."),
@@ -157,10 +226,199 @@ impl Display for PathError {
             PathError::Internal => write!(f, "Internal error."),
             PathError::ZoneIntersection => write!(f, "Zone intersection error."),
             PathError::FlightPlanIntersection => write!(f, "Flight plan intersection error."),
+            PathError::ReservationIntersection => {
+                write!(f, "Volume reservation intersection error.")
+            }
+            PathError::InvalidNodeCount => write!(f, "Invalid number of nodes."),
+            PathError::InvalidWeights => write!(f, "Invalid path cost weights."),
+            PathError::InvalidPriority => write!(f, "Invalid flight priority."),
+            PathError::InvalidWindowOffset => write!(f, "Invalid search window offset."),
+            PathError::InvalidArrivalWindow => write!(f, "Invalid arrival window."),
+            PathError::InvalidAircraftType => write!(f, "Invalid aircraft type."),
+            PathError::InvalidAltitudeLimits => write!(f, "Invalid aircraft altitude limits."),
+            PathError::InvalidSeparationMargins => {
+                write!(f, "Invalid flight separation margins.")
+            }
+            PathError::OutsideAltitudeLimits => write!(
+                f,
+                "No path was found, no flight level within the aircraft altitude limits."
+            ),
+            PathError::OutsideOperatingHours => write!(
+                f,
+                "No path was found, all routes arrive outside the target operating hours."
+            ),
         }
     }
 }
 
+impl PathError {
+    /// The reason reported to clients when no path was found, or `None`
+    ///  if the error is not a failure to find a path
+    pub fn failure_reason(&self) -> Option<PathFailureReason> {
+        match self {
+            PathError::NoWaypointsNearby => Some(PathFailureReason::NoWaypointsNearby),
+            PathError::ExceededMaxDistance => Some(PathFailureReason::ExceededMaxDistance),
+            PathError::AllCandidatesConflicted => Some(PathFailureReason::AllCandidatesConflicted),
+            PathError::TimeBudgetExhausted => Some(PathFailureReason::TimeBudgetExhausted),
+            PathError::OutsideAltitudeLimits => Some(PathFailureReason::OutsideAltitudeLimits),
+            PathError::OutsideOperatingHours => Some(PathFailureReason::OutsideOperatingHours),
+            _ => None,
+        }
+    }
+
+    /// Whether another time window could clear the conflicts that caused
+    ///  this error
+    pub fn is_window_dependent(&self) -> bool {
+        matches!(
+            self,
+            PathError::NoWaypointsNearby
+                | PathError::AllCandidatesConflicted
+                | PathError::OutsideOperatingHours
+        )
+    }
+}
+
+/// Time budget of a window search, bounded by the caller's deadline if any
+fn search_budget(
+    deadline: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Duration, PathError> {
+    let (Some(limit), Some(margin)) = (
+        Duration::try_milliseconds(BEST_PATH_TIME_LIMIT_MS),
+        Duration::try_milliseconds(DEADLINE_MARGIN_MS),
+    ) else {
+        postgis_error!("could not get time limit for path calculation.");
+        return Err(PathError::Internal);
+    };
+
+    let Some(deadline) = deadline else {
+        return Ok(limit);
+    };
+
+    Ok(limit.min(deadline - now - margin).max(Duration::zero()))
+}
+
+/// Offsets of the shifted search windows, in minutes and closest first
+///  At equal distance the later window is tried first, the aircraft may
+///  not be ready any earlier.
+fn window_offsets(max_window_offset_minutes: u32) -> Vec<i64> {
+    (1..)
+        .map(|step| (step * WINDOW_OFFSET_STEP_MINUTES) as i64)
+        .take_while(|offset| *offset <= max_window_offset_minutes as i64)
+        .flat_map(|offset| [offset, -offset])
+        .collect()
+}
+
+/// A failed search, with the filed flights that blocked its candidates
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchFailure {
+    /// Why the search failed
+    pub error: PostgisError,
+
+    /// Flights that most often blocked candidate paths
+    pub blockers: Vec<PathBlocker>,
+}
+
+impl Display for SearchFailure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl From<PostgisError> for SearchFailure {
+    fn from(error: PostgisError) -> Self {
+        SearchFailure {
+            error,
+            blockers: vec![],
+        }
+    }
+}
+
+/// A filed flight that conflicts with a path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Blocker {
+    flight_identifier: String,
+    aircraft_identifier: String,
+}
+
+/// Counts the candidate paths each filed flight blocked during a search
+#[derive(Debug, Default)]
+struct BlockerTally {
+    counts: HashMap<Blocker, u32>,
+}
+
+impl BlockerTally {
+    /// Records a candidate path blocked by a flight
+    fn record(&mut self, blocker: Blocker) {
+        *self.counts.entry(blocker).or_default() += 1;
+    }
+
+    /// The flights that blocked the most candidates, most frequent first
+    ///  Ties are broken by flight identifier so results are stable.
+    fn top(&self, limit: usize) -> Vec<PathBlocker> {
+        let mut counts: Vec<(&Blocker, &u32)> = self.counts.iter().collect();
+        counts.sort_by(|a, b| {
+            b.1.cmp(a.1)
+                .then_with(|| a.0.flight_identifier.cmp(&b.0.flight_identifier))
+        });
+
+        counts
+            .into_iter()
+            .take(limit)
+            .map(|(blocker, count)| PathBlocker {
+                flight_identifier: blocker.flight_identifier.clone(),
+                aircraft_identifier: blocker.aircraft_identifier.clone(),
+                blocked_count: *count,
+            })
+            .collect()
+    }
+}
+
+/// Outcome of checking a path against the filed flights
+#[derive(Debug, Clone)]
+enum FlightConflicts {
+    /// No flight blocks the path, lower-priority flights in the way are
+    ///  listed to be re-planned
+    Clear(Vec<ReplanAdvisory>),
+
+    /// The first flight found to block the path
+    Blocked(Blocker),
+}
+
+/// Resolves the requested time window
+///  Defaults to the next 24 hours
+fn time_window(
+    time_start: Option<Timestamp>,
+    time_end: Option<Timestamp>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), PostgisError> {
+    let time_start: DateTime<Utc> = match time_start {
+        None => Utc::now(),
+        Some(time) => time.into(),
+    };
+
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) this will never fail
+    let delta = Duration::try_days(1).ok_or_else(|| {
+        postgis_error!("could not get time delta for 1 day.");
+        PostgisError::BestPath(PathError::InvalidTimeWindow)
+    })?;
+
+    let time_end: DateTime<Utc> = match time_end {
+        None => Utc::now() + delta,
+        Some(time) => time.into(),
+    };
+
+    if time_end < time_start {
+        return Err(PostgisError::BestPath(PathError::InvalidTimeWindow));
+    }
+
+    if time_end < Utc::now() {
+        return Err(PostgisError::BestPath(PathError::InvalidEndTime));
+    }
+
+    Ok((time_start, time_end))
+}
+
 #[derive(Debug)]
 struct PathRequest {
     origin_identifier: String,
@@ -170,6 +428,15 @@ struct PathRequest {
     time_start: DateTime<Utc>,
     time_end: DateTime<Utc>,
     limit: usize,
+    max_path_node_count: Option<usize>,
+    weights: CostWeights,
+    include_simulated: bool,
+    priority: FlightPriority,
+    max_window_offset_minutes: u32,
+    arrival_window_minutes: u32,
+    aircraft_type: AircraftType,
+    altitude_limits: Option<AltitudeLimits>,
+    entrant: Entrant,
 }
 
 impl TryFrom<BestPathRequest> for PathRequest {
@@ -191,6 +458,29 @@ impl TryFrom<BestPathRequest> for PathRequest {
             return Err(PostgisError::BestPath(PathError::InvalidLimit));
         }
 
+        let max_path_node_count = request
+            .max_path_node_count
+            .map(|count| count as usize)
+            .map(|count| {
+                if !(MIN_PATH_NODE_COUNT_LIMIT..=MAX_PATH_NODE_COUNT_LIMIT).contains(&count) {
+                    postgis_error!("invalid max number of path nodes: {count}");
+                    return Err(PostgisError::BestPath(PathError::InvalidNodeCount));
+                }
+
+                Ok(count)
+            })
+            .transpose()?;
+
+        let weights = request
+            .weights
+            .map(CostWeights::from)
+            .unwrap_or_else(get_default_weights);
+
+        if !weights.is_valid() {
+            postgis_error!("invalid path cost weights: {:?}", weights);
+            return Err(PostgisError::BestPath(PathError::InvalidWeights));
+        }
+
         let origin_type = FromPrimitive::from_i32(request.origin_type).ok_or_else(|| {
             postgis_error!("invalid start node type: {:?}", request.origin_type);
 
@@ -203,65 +493,85 @@ impl TryFrom<BestPathRequest> for PathRequest {
             PostgisError::BestPath(PathError::InvalidEndNode)
         })?;
 
-        let regex = match origin_type {
-            NodeType::Vertiport => crate::postgis::vertiport::IDENTIFIER_REGEX,
-            NodeType::Aircraft => crate::postgis::aircraft::IDENTIFIER_REGEX,
+        let priority = FromPrimitive::from_i32(request.priority).ok_or_else(|| {
+            postgis_error!("invalid flight priority: {:?}", request.priority);
+            PostgisError::BestPath(PathError::InvalidPriority)
+        })?;
+
+        let entity = match origin_type {
+            NodeType::Vertiport => Entity::Vertiport,
+            NodeType::Aircraft => Entity::Aircraft,
             _ => {
                 postgis_error!("invalid start node type: {:?}", origin_type);
                 return Err(PostgisError::BestPath(PathError::InvalidStartNode));
             }
         };
 
-        super::utils::check_string(&request.origin_identifier, regex).map_err(|_| {
+        check_identifier(entity, &request.origin_identifier).map_err(|e| {
             postgis_error!(
-                "invalid start node identifier: {:?}",
+                "invalid start node identifier {:?}: {e}",
                 request.origin_identifier
             );
 
             PostgisError::BestPath(PathError::InvalidStartNode)
         })?;
 
-        let regex = match target_type {
-            NodeType::Vertiport => crate::postgis::vertiport::IDENTIFIER_REGEX,
-            _ => {
-                postgis_error!("invalid end node type: {:?}", target_type);
-                return Err(PostgisError::BestPath(PathError::InvalidEndNode));
-            }
-        };
+        if target_type != NodeType::Vertiport {
+            postgis_error!("invalid end node type: {:?}", target_type);
+            return Err(PostgisError::BestPath(PathError::InvalidEndNode));
+        }
 
-        super::utils::check_string(&request.target_identifier, regex).map_err(|_| {
+        check_identifier(Entity::Vertiport, &request.target_identifier).map_err(|e| {
             postgis_error!(
-                "invalid end node identifier: {:?}",
+                "invalid end node identifier {:?}: {e}",
                 request.target_identifier
             );
 
             PostgisError::BestPath(PathError::InvalidEndNode)
         })?;
 
-        let time_start: DateTime<Utc> = match request.time_start {
-            None => Utc::now(),
-            Some(time) => time.into(),
-        };
+        let (time_start, time_end) = time_window(request.time_start, request.time_end)?;
 
-        #[cfg(not(tarpaulin_include))]
-        // no_coverage: (Rnever) this will never fail
-        let delta = Duration::try_days(1).ok_or_else(|| {
-            postgis_error!("could not get time delta for 1 day.");
-            PostgisError::BestPath(PathError::InvalidTimeWindow)
-        })?;
+        let max_window_offset_minutes = request.max_window_offset_minutes.unwrap_or(0);
+        if max_window_offset_minutes > MAX_WINDOW_OFFSET_MINUTES {
+            postgis_error!("invalid max window offset: {max_window_offset_minutes} minutes");
+            return Err(PostgisError::BestPath(PathError::InvalidWindowOffset));
+        }
+
+        let arrival_window_minutes = request
+            .arrival_window_minutes
+            .unwrap_or(DEFAULT_ARRIVAL_WINDOW_MINUTES);
+        if arrival_window_minutes > MAX_ARRIVAL_WINDOW_MINUTES {
+            postgis_error!("invalid arrival window: {arrival_window_minutes} minutes");
+            return Err(PostgisError::BestPath(PathError::InvalidArrivalWindow));
+        }
 
-        let time_end: DateTime<Utc> = match request.time_end {
-            None => Utc::now() + delta,
-            Some(time) => time.into(),
+        let aircraft_type = match request.aircraft_type {
+            None => AircraftType::Undeclared,
+            Some(aircraft_type) => FromPrimitive::from_i32(aircraft_type).ok_or_else(|| {
+                postgis_error!("invalid aircraft type: {aircraft_type}");
+                PostgisError::BestPath(PathError::InvalidAircraftType)
+            })?,
         };
 
-        if time_end < time_start {
-            return Err(PostgisError::BestPath(PathError::InvalidTimeWindow));
+        let altitude_limits = get_altitude_limits(aircraft_type);
+        if AltitudeLimits::flight_levels(altitude_limits).is_empty() {
+            postgis_error!(
+                "no flight level within the altitude limits of {aircraft_type}: {:?}",
+                altitude_limits
+            );
+            return Err(PostgisError::BestPath(PathError::OutsideAltitudeLimits));
         }
 
-        if time_end < Utc::now() {
-            return Err(PostgisError::BestPath(PathError::InvalidEndTime));
-        }
+        // An aircraft origin is the aircraft flying the path
+        let aircraft_identifier = match (request.aircraft_identifier, origin_type) {
+            (None, NodeType::Aircraft) => Some(request.origin_identifier.clone()),
+            (aircraft_identifier, _) => aircraft_identifier,
+        };
+
+        let entrant = Entrant::new(aircraft_identifier, request.flight_identifier)
+            .and_then(|entrant| entrant.with_operator(request.operator_identifier))
+            .map_err(PostgisError::Authorization)?;
 
         Ok(PathRequest {
             origin_identifier: request.origin_identifier,
@@ -271,36 +581,112 @@ impl TryFrom<BestPathRequest> for PathRequest {
             time_start,
             time_end,
             limit,
+            max_path_node_count,
+            weights,
+            include_simulated: request.include_simulated,
+            priority,
+            max_window_offset_minutes,
+            arrival_window_minutes,
+            aircraft_type,
+            altitude_limits,
+            entrant,
         })
     }
 }
 
-/// Checks if the path intersects with any no-fly zones or existing flights
+/// The times between which flights arriving at the target are counted
+///  for a path departing at `time_start` and flying for `time_seconds`
+fn arrival_window(
+    time_start: DateTime<Utc>,
+    time_seconds: f32,
+    window_minutes: u32,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let arrival = time_start + Duration::try_milliseconds((time_seconds * 1000.0) as i64)?;
+    let margin = Duration::try_minutes(window_minutes as i64)?;
+    Some((arrival - margin, arrival + margin))
+}
+
+/// Whether a path departing at `time_start` and flying for `time_seconds`
+///  arrives while the target vertiport is operating
+fn arrives_within(operating_hours: &[i32], time_start: DateTime<Utc>, time_seconds: f32) -> bool {
+    let Some(flight) = Duration::try_milliseconds((time_seconds * 1000.0) as i64) else {
+        return false;
+    };
+
+    let arrival = time_start + flight;
+    super::zone::schedule_overlaps(operating_hours, arrival, arrival)
+}
+
+/// Gets the default max number of nodes in a path given the straight-line
+///  distance between its origin and target
+///
+/// Short hops only need a waypoint or so to get around a zone, while long
+///  corridors may need to thread several zones.
+fn default_path_node_count(distance_meters: f32) -> usize {
+    let extra = (distance_meters.max(0.0) / PATH_NODE_DISTANCE_METERS) as usize;
+    (DEFAULT_PATH_NODE_COUNT + extra).min(MAX_PATH_NODE_COUNT_LIMIT)
+}
+
+#[derive(Debug)]
+struct MatrixRequest {
+    origin_identifiers: Vec<String>,
+    target_identifiers: Vec<String>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+}
+
+impl TryFrom<DistanceMatrixRequest> for MatrixRequest {
+    type Error = PostgisError;
+
+    fn try_from(request: DistanceMatrixRequest) -> Result<Self, Self::Error> {
+        for identifiers in [&request.origin_identifiers, &request.target_identifiers] {
+            if identifiers.is_empty() || identifiers.len() > MAX_DISTANCE_MATRIX_NODE_COUNT {
+                postgis_error!("invalid number of nodes: {}", identifiers.len());
+                return Err(PostgisError::BestPath(PathError::InvalidNodeCount));
+            }
+        }
+
+        for identifier in &request.origin_identifiers {
+            check_identifier(Entity::Vertiport, identifier).map_err(|e| {
+                postgis_error!("invalid start node identifier {:?}: {e}", identifier);
+                PostgisError::BestPath(PathError::InvalidStartNode)
+            })?;
+        }
+
+        for identifier in &request.target_identifiers {
+            check_identifier(Entity::Vertiport, identifier).map_err(|e| {
+                postgis_error!("invalid end node identifier {:?}: {e}", identifier);
+                PostgisError::BestPath(PathError::InvalidEndNode)
+            })?;
+        }
+
+        let (time_start, time_end) = time_window(request.time_start, request.time_end)?;
+
+        Ok(MatrixRequest {
+            origin_identifiers: request.origin_identifiers,
+            target_identifiers: request.target_identifiers,
+            time_start,
+            time_end,
+        })
+    }
+}
+
+/// Checks if the path intersects with any zones active during the time window
+///  Zones the entrant is authorized to enter for the whole window are skipped.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need to run with a real database
-pub async fn intersection_checks(
+pub(super) async fn zone_intersection_checks(
     client: &deadpool_postgres::Client,
-    points: Vec<PointZ>,
-    distance: f32,
+    geom: &LineStringT<PointZ>,
     time_start: DateTime<Utc>,
     time_end: DateTime<Utc>,
     origin_identifier: &str,
     target_identifier: &str,
+    entrant: &Entrant,
 ) -> Result<(), PostgisError> {
-    // TODO(R5): This is dependent on the aircraft type
-    //  Small drones can come closer to one another than large drones
-    //  or rideshare vehicles
-    const ALLOWABLE_DISTANCE_M: f64 = 10.0;
-
-    let geom = LineStringT {
-        points,
-        srid: Some(DEFAULT_SRID),
-    };
-
-    // Check if any of the zones overlap this path
     let zone_stmt = crate::postgis::zone::get_zone_intersection_stmt(client).await?;
-    if let Ok(row) = client
-        .query_one(
+    let zones = client
+        .query(
             &zone_stmt,
             &[
                 &geom,
@@ -308,19 +694,159 @@ pub async fn intersection_checks(
                 &time_end,
                 &origin_identifier,
                 &target_identifier,
+                &entrant.aircraft_identifier,
+                &entrant.flight_identifier,
             ],
         )
         .await
+        .map_err(|e| {
+            postgis_error!("could not query for zone intersections: {}", e);
+            PostgisError::BestPath(PathError::DBError)
+        })?;
+
+    // Recurring zones only block the path during their active windows
+    for row in zones {
+        let schedule: Vec<i32> = row.try_get("schedule").map_err(|e| {
+            postgis_error!("could not get zone schedule: {}", e);
+            PostgisError::BestPath(PathError::DBError)
+        })?;
+
+        if crate::postgis::zone::schedule_overlaps(&schedule, time_start, time_end) {
+            postgis_debug!("flight path intersects with no-fly zone: {:?}", row);
+            return Err(PostgisError::BestPath(PathError::ZoneIntersection));
+        }
+    }
+
+    Ok(())
+}
+
+/// If a filed flight of the given priority gives way to a flight of
+///  `priority`. Only strictly lower priorities give way.
+fn gives_way(flight_priority: i32, priority: FlightPriority) -> bool {
+    flight_priority < priority as i32
+}
+
+/// Checks if the path intersects with any no-fly zones, volume
+///  reservations or existing flights
+///  Simulated flights are only conflicts if `include_simulated` is set.
+///  Flights are kept apart by the separation margins of the aircraft type
+///  flying the path or of the filed flight, whichever are larger.
+///
+/// Flights of a strictly lower priority than `priority` don't block the
+///  path. They are returned as advisories instead, as they would need to
+///  be re-planned if this path is flown.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need to run with a real database
+#[allow(clippy::too_many_arguments)]
+pub async fn intersection_checks(
+    client: &deadpool_postgres::Client,
+    points: Vec<PointZ>,
+    distance: f32,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    origin_identifier: &str,
+    target_identifier: &str,
+    entrant: &Entrant,
+    include_simulated: bool,
+    priority: FlightPriority,
+    aircraft_type: AircraftType,
+) -> Result<Vec<ReplanAdvisory>, PostgisError> {
+    match flight_conflicts(
+        client,
+        points,
+        distance,
+        time_start,
+        time_end,
+        origin_identifier,
+        target_identifier,
+        entrant,
+        include_simulated,
+        priority,
+        aircraft_type,
+    )
+    .await?
+    {
+        FlightConflicts::Clear(advisories) => Ok(advisories),
+        FlightConflicts::Blocked(blocker) => {
+            postgis_debug!("path blocked by flight: {:?}", blocker);
+            Err(PostgisError::BestPath(PathError::FlightPlanIntersection))
+        }
+    }
+}
+
+/// Checks if the path intersects with any no-fly zones or volume
+///  reservations, and which filed flight blocks it if any
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need to run with a real database
+#[allow(clippy::too_many_arguments)]
+async fn flight_conflicts(
+    client: &deadpool_postgres::Client,
+    points: Vec<PointZ>,
+    distance: f32,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    origin_identifier: &str,
+    target_identifier: &str,
+    entrant: &Entrant,
+    include_simulated: bool,
+    priority: FlightPriority,
+    aircraft_type: AircraftType,
+) -> Result<FlightConflicts, PostgisError> {
+    let geom = LineStringT {
+        points,
+        srid: Some(DEFAULT_SRID),
+    };
+
+    // Check if any of the zones overlap this path
+    zone_intersection_checks(
+        client,
+        &geom,
+        time_start,
+        time_end,
+        origin_identifier,
+        target_identifier,
+        entrant,
+    )
+    .await?;
+
+    // Reserved volumes block the path like filed flights, whatever its
+    //  priority, unless the operator of the path holds them
+    if let Some(identifier) = super::reservation::path_reservation(
+        client,
+        &geom,
+        time_start,
+        time_end,
+        &entrant.operator_identifier,
+    )
+    .await
+    .map_err(|_| PostgisError::BestPath(PathError::DBError))?
     {
-        postgis_debug!("flight path intersects with no-fly zone: {:?}", row);
-        return Err(PostgisError::BestPath(PathError::ZoneIntersection));
+        postgis_debug!("flight path intersects with volume reservation: {identifier}");
+        return Err(PostgisError::BestPath(PathError::ReservationIntersection));
     }
+
     // Check if this conflicts with other flights' segments
+    //  The margins of the filed flights are only known once they are found.
+    //  Filed paths were simplified, so they may be off the submitted ones by
+    //  up to the simplification tolerance.
+    let path_epsilon_meters = super::simplify::get_settings().path_epsilon_meters;
+    let margins = get_separation_margins(aircraft_type);
+    let reach_meters = margins
+        .between(get_widest_separation_margins())
+        .widened(path_epsilon_meters)
+        .reach_meters();
+
     let flights_stmt = crate::postgis::flight::get_flight_intersection_stmt(client).await?;
     let result = client
         .query(
             &flights_stmt,
-            &[&geom, &ALLOWABLE_DISTANCE_M, &time_start, &time_end],
+            &[
+                &geom,
+                &reach_meters,
+                &time_start,
+                &time_end,
+                &include_simulated,
+            ],
         )
         .await
         .map_err(|e| {
@@ -333,7 +859,7 @@ pub async fn intersection_checks(
 
     if result.is_empty() {
         postgis_debug!("no flight path intersections.");
-        return Ok(());
+        return Ok(FlightConflicts::Clear(vec![]));
     }
 
     postgis_debug!(
@@ -341,15 +867,7 @@ pub async fn intersection_checks(
     );
 
     let stmt = client
-        .prepare_cached(
-            r#"
-            SELECT ("distance_to_path" < $3 OR "distance_to_path" IS NULL) as "conflict"
-            FROM ST_3DDistance(
-                ST_Transform($1, 4978),
-                ST_Transform($2, 4978)
-            ) as "distance_to_path"
-        "#,
-        )
+        .prepare_cached(&Statement::SegmentConflict.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
@@ -362,6 +880,7 @@ pub async fn intersection_checks(
         time_end,
     };
 
+    let mut advisories = vec![];
     for row in result {
         postgis_debug!("row: {:?}", row);
         let b_segment = Segment {
@@ -384,18 +903,54 @@ pub async fn intersection_checks(
             PostgisError::BestPath(PathError::DBError)
         })?;
 
+        let b_priority: i32 = row.try_get("priority").map_err(|e| {
+            postgis_debug!("{e}");
+            PostgisError::BestPath(PathError::DBError)
+        })?;
+
+        let flight_identifier: String = row.try_get("flight_identifier").map_err(|e| {
+            postgis_debug!("{e}");
+            PostgisError::BestPath(PathError::DBError)
+        })?;
+
+        let aircraft_identifier: String = row.try_get("aircraft_identifier").map_err(|e| {
+            postgis_debug!("{e}");
+            PostgisError::BestPath(PathError::DBError)
+        })?;
+
+        let b_aircraft_type: AircraftType = row.try_get("aircraft_type").map_err(|e| {
+            postgis_debug!("{e}");
+            PostgisError::BestPath(PathError::DBError)
+        })?;
+
         match crate::postgis::flight::intersection_check(
             client,
             &stmt,
-            ALLOWABLE_DISTANCE_M,
+            margins
+                .between(get_separation_margins(b_aircraft_type))
+                .widened(path_epsilon_meters),
             distance.max(b_distance as f32) / 2.0,
             a_segment.clone(),
             b_segment,
         )
         .await
         {
+            Err(PostgisError::FlightPath(FlightError::Intersection))
+                if gives_way(b_priority, priority) =>
+            {
+                let advisory = ReplanAdvisory {
+                    flight_identifier,
+                    aircraft_identifier,
+                    priority: b_priority,
+                };
+
+                advisories.push(advisory);
+            }
             Err(PostgisError::FlightPath(FlightError::Intersection)) => {
-                return Err(PostgisError::BestPath(PathError::FlightPlanIntersection));
+                return Ok(FlightConflicts::Blocked(Blocker {
+                    flight_identifier,
+                    aircraft_identifier,
+                }));
             }
             Err(PostgisError::FlightPath(_)) => {
                 return Err(PostgisError::BestPath(PathError::DBError));
@@ -404,177 +959,243 @@ pub async fn intersection_checks(
         }
     }
 
-    Ok(())
-}
+    if !advisories.is_empty() {
+        postgis_warn!(
+            "path conflicts with lower-priority flights that need re-planning: {:?}",
+            advisories
+        );
+    }
 
-/// Modified A* algorithm for finding the best path between two points
-///  Potentials are sorted by (distance to target + distance traversed)
-#[cfg(not(tarpaulin_include))]
-// no_coverage: (R5) need to run with a real database
-async fn mod_a_star(
-    origin_node: PathNode,
-    target_node: PathNode,
-    time_start: DateTime<Utc>,
-    time_end: DateTime<Utc>,
-    waypoints: Vec<super::waypoint::Waypoint>,
-    limit: usize,
-) -> Result<Vec<Path>, PostgisError> {
-    postgis_debug!("entry.");
-
-    // Using a binary heap to store potential paths
-    //  means potentials are sorted on insert with O(log n)
-    //  worst case time complexity
-    let mut potentials: BinaryHeap<Path> = BinaryHeap::new();
-    let mut completed: BinaryHeap<Path> = BinaryHeap::new();
-
-    // Get all possible waypoints, including at different
-    //  flight elevations
-    let mut path_points = waypoints
-        .into_iter()
-        .flat_map(|w| {
-            FLIGHT_LEVELS
-                .iter()
-                .map(|fl| PathNode {
-                    node_type: NodeType::Waypoint as i32,
-                    identifier: w.identifier.clone(),
-                    geom: PointZ {
-                        x: w.geom.x,
-                        y: w.geom.y,
-                        z: *fl as f64,
-                        srid: w.geom.srid,
-                    },
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<VecDeque<PathNode>>();
-
-    // Add the destination as a path point
-    path_points.push_front(target_node.clone());
-
-    // Add starting node
-    let starting_path = Path {
-        path: vec![origin_node.clone()],
-        distance_to_target_meters: super::utils::distance_meters(
-            &origin_node.geom,
-            &target_node.geom,
-        ),
-        distance_traversed_meters: 0.,
-    };
+    Ok(FlightConflicts::Clear(advisories))
+}
 
-    potentials.push(starting_path);
+/// Waypoints stored in PostGIS
+#[derive(Debug, Copy, Clone)]
+struct PostgisNodes<'a> {
+    /// Identifiers of the origin and target if both are vertiports, the
+    ///  waypoints between them are then served from memory if read recently
+    vertiports: Option<(&'a str, &'a str)>,
+}
 
-    let pool = crate::postgis::DEADPOOL_POSTGIS.get().ok_or_else(|| {
-        postgis_error!("could not get psql pool.");
-        PostgisError::BestPath(PathError::Client)
-    })?;
+#[async_trait]
+impl NodeProvider for PostgisNodes<'_> {
+    /// Get a subset of waypoints within N meters of the line between the origin and target
+    ///  This saves computation time by doing shortest path on a smaller graph
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need to run with a real database
+    async fn waypoints(
+        &self,
+        origin: &PointZ,
+        target: &PointZ,
+    ) -> Result<Vec<super::waypoint::Waypoint>, PostgisError> {
+        let line = postgis::ewkb::GeometryT::LineString(LineStringT {
+            points: vec![*origin, *target],
+            srid: Some(DEFAULT_SRID),
+        });
 
-    let client = pool.get().await.map_err(|e| {
-        postgis_error!("could not get client from psql connection pool: {}", e);
-        PostgisError::BestPath(PathError::Client)
-    })?;
+        let waypoints = match self.vertiports {
+            Some(vertiports) => {
+                super::vertiport::get_route_waypoints(vertiports, &line, WAYPOINT_RANGE_METERS)
+                    .await?
+            }
+            None => {
+                super::waypoint::get_waypoints_near_geometry(&line, WAYPOINT_RANGE_METERS).await?
+            }
+        };
 
-    // TODO(R5): Conditional approval zones
-    //  For now all zones are considered no-fly zones
-    //  So limit query to one result
+        postgis_info!("nearby waypoints: {:?}", waypoints);
+        Ok(waypoints)
+    }
+}
 
-    // Run until we have 'limit' paths or we run out of potentials
-    let time_limit = Duration::try_milliseconds(BEST_PATH_TIME_LIMIT_MS).ok_or_else(|| {
-        postgis_error!("could not get time limit for path calculation.");
-        PostgisError::BestPath(PathError::Internal)
-    })?;
+/// Zones and flight plans stored in PostGIS, during a time window
+struct PostgisConflicts<'a> {
+    client: deadpool_postgres::Client,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    origin_identifier: &'a str,
+    target_identifier: &'a str,
+    entrant: &'a Entrant,
+    include_simulated: bool,
+    priority: FlightPriority,
+    aircraft_type: AircraftType,
+    blockers: Mutex<BlockerTally>,
+}
 
-    let start_time = Utc::now();
-    while completed.len() < limit && !potentials.is_empty() {
-        if Utc::now() - start_time > time_limit {
-            postgis_warn!("max calculation time reached");
-            break;
+#[async_trait]
+impl ConflictChecker for PostgisConflicts<'_> {
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need to run with a real database
+    async fn conflicts(
+        &self,
+        points: Vec<PointZ>,
+        distance_meters: f32,
+    ) -> Result<bool, PostgisError> {
+        match flight_conflicts(
+            &self.client,
+            points,
+            distance_meters,
+            self.time_start,
+            self.time_end,
+            self.origin_identifier,
+            self.target_identifier,
+            self.entrant,
+            self.include_simulated,
+            self.priority,
+            self.aircraft_type,
+        )
+        .await
+        {
+            Ok(FlightConflicts::Clear(_)) => Ok(false),
+            Ok(FlightConflicts::Blocked(blocker)) => {
+                self.blockers
+                    .lock()
+                    .map_err(|e| {
+                        postgis_error!("could not lock blocker tally: {}", e);
+                        PostgisError::BestPath(PathError::Internal)
+                    })?
+                    .record(blocker);
+
+                Ok(true)
+            }
+            Err(PostgisError::BestPath(PathError::ZoneIntersection)) => Ok(true),
+            Err(PostgisError::BestPath(PathError::ReservationIntersection)) => Ok(true),
+            Err(e) => Err(e),
         }
+    }
 
-        let current = potentials.pop().ok_or_else(|| {
-            postgis_error!("no path found");
-            PostgisError::BestPath(PathError::NoPath)
-        })?;
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need to run with a real database
+    async fn exposure(&self, points: &[PointZ]) -> Result<Exposure, PostgisError> {
+        let noise_meters =
+            get_noise_exposure(&self.client, points, self.time_start, self.time_end).await?;
 
-        for p in path_points.iter() {
-            // Don't backtrack
-            if current.path.contains(p) {
-                continue;
+        // Ground risk is reserved, its weight is rejected unless zero
+        Ok(Exposure {
+            noise_meters,
+            ..Default::default()
+        })
+    }
+}
+
+impl PostgisConflicts<'_> {
+    /// The flights that blocked the most candidates so far
+    fn top_blockers(&self) -> Vec<PathBlocker> {
+        match self.blockers.lock() {
+            Ok(blockers) => blockers.top(MAX_BLOCKER_COUNT),
+            Err(e) => {
+                postgis_error!("could not lock blocker tally: {}", e);
+                vec![]
             }
+        }
+    }
 
-            let last = current.path.last().ok_or_else(|| {
-                postgis_error!("no last point found");
-                PostgisError::BestPath(PathError::NoPath)
+    /// Counts the filed flights arriving at the target vertiport between
+    ///  two times, other than the flight being routed
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need to run with a real database
+    async fn arrivals(
+        &self,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Result<u32, PostgisError> {
+        let stmt = self
+            .client
+            .prepare_cached(&super::statements::Statement::CountVertiportArrivals.sql())
+            .await
+            .map_err(|e| {
+                postgis_error!("could not prepare cached statement: {}", e);
+                PostgisError::BestPath(PathError::DBError)
             })?;
 
-            let distance_meters = super::utils::distance_meters(&last.geom, &p.geom);
-            let mut tmp = current.clone();
-            tmp.distance_traversed_meters += distance_meters;
+        let count: i64 = self
+            .client
+            .query_one(
+                &stmt,
+                &[
+                    &self.target_identifier,
+                    &window_start,
+                    &window_end,
+                    &self.include_simulated,
+                    &self.entrant.flight_identifier,
+                ],
+            )
+            .await
+            .and_then(|row| row.try_get("count"))
+            .map_err(|e| {
+                postgis_error!("could not count vertiport arrivals: {}", e);
+                PostgisError::BestPath(PathError::DBError)
+            })?;
 
-            // Don't allow flights to exceed max distance
-            if tmp.distance_traversed_meters > MAX_FLIGHT_DISTANCE_METERS {
-                continue;
-            }
+        Ok(count.clamp(0, u32::MAX as i64) as u32)
+    }
 
-            tmp.path.push(p.clone());
-            tmp.distance_to_target_meters =
-                super::utils::distance_meters(&p.geom, &target_node.geom);
-
-            // If the path has reached the target, shove it into the
-            //  potentials list and move on
-            if p.identifier != target_node.identifier {
-                // Limit the max number of nodes to prevent crazy winding paths
-                //  waypoints should only be used to get around a local no-fly zone, to
-                //  so the total path length should be 2 (origin and target) plus a limited
-                //  number of nodes needed to circumvent 1-2 no-fly zones
-                if tmp.path.len() < MAX_PATH_NODE_COUNT_LIMIT {
-                    potentials.push(tmp);
-                }
+    /// Lists the lower-priority flights that conflict with a found path
+    ///  Nothing gives way to a routine flight, so its paths are not checked.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need to run with a real database
+    async fn advisories(&self, path: &[PathNode]) -> Result<Vec<ReplanAdvisory>, PostgisError> {
+        if self.priority == FlightPriority::Routine {
+            return Ok(vec![]);
+        }
 
-                continue;
-            }
+        let points: Vec<PointZ> = path.iter().map(|node| node.geom).collect();
+        let distance = points.windows(2).fold(0.0, |acc, pair| {
+            acc + super::utils::distance_meters(&pair[0], &pair[1])
+        });
+
+        intersection_checks(
+            &self.client,
+            points,
+            distance,
+            self.time_start,
+            self.time_end,
+            self.origin_identifier,
+            self.target_identifier,
+            self.entrant,
+            self.include_simulated,
+            self.priority,
+            self.aircraft_type,
+        )
+        .await
+    }
+}
 
-            // If the path has reached the target, do final checks
-            //  to ensure flight safety
-
-            // Path 3D linestring for zone intersection check
-            let points = tmp.path.iter().map(|p| p.geom).collect::<Vec<PointZ>>();
-            match intersection_checks(
-                &client,
-                points,
-                tmp.distance_traversed_meters,
-                time_start,
-                time_end,
-                &origin_node.identifier,
-                &target_node.identifier,
-            )
-            .await
-            {
-                Ok(_) => (),
-                Err(PostgisError::BestPath(PathError::ZoneIntersection)) => {
-                    continue;
-                }
-                Err(PostgisError::BestPath(PathError::FlightPlanIntersection)) => {
-                    continue;
-                }
-                Err(e) => {
-                    postgis_error!("intersection checks failed: {}", e);
-                    return Err(e);
-                }
-            }
+/// Number of best path searches run since startup
+static SEARCHES: AtomicU64 = AtomicU64::new(0);
 
-            // Valid routes are pushed
-            completed.push(tmp);
-            if completed.len() >= limit {
-                break;
-            }
-        }
+/// Time spent in the best path searches since startup, in microseconds
+static SEARCH_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// Time spent in the best path searches since startup
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Latency {
+    /// Number of searches run, successful or not
+    pub count: u64,
+
+    /// Total time spent in the searches, in microseconds
+    pub total_micros: u64,
+}
+
+impl Latency {
+    /// Average time of a search in milliseconds, `None` before the first
+    pub fn average_ms(&self) -> Option<f32> {
+        (self.count > 0).then(|| self.total_micros as f32 / self.count as f32 / 1000.0)
     }
+}
 
-    let mut completed = completed.into_sorted_vec();
-    completed.reverse();
+/// Gets the time spent in the best path searches
+pub fn latency() -> Latency {
+    Latency {
+        count: SEARCHES.load(Ordering::Relaxed),
+        total_micros: SEARCH_MICROS.load(Ordering::Relaxed),
+    }
+}
 
-    postgis_debug!("completed paths: {:?}", completed);
-    Ok(completed)
+/// Adds a search to the latency counters
+fn record_latency(elapsed: std::time::Duration) {
+    SEARCHES.fetch_add(1, Ordering::Relaxed);
+    SEARCH_MICROS.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
 }
 
 /// The purpose of this initial search is to verify that a flight between two
@@ -585,14 +1206,50 @@ async fn mod_a_star(
 ///  of charge.
 ///
 /// No-Fly zones can extend flights, isolate aircraft, or disable vertiports entirely.
+///
+/// The search gives up by the caller's `deadline`, if any. The paths
+///  completed by then are returned with `truncated` set.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (Rnever) need running postgresql instance, not unit testable
-pub async fn best_path(request: BestPathRequest) -> Result<Vec<GrpcPath>, PostgisError> {
+pub async fn best_path(
+    request: BestPathRequest,
+    deadline: Option<DateTime<Utc>>,
+) -> Result<BestPathResponse, SearchFailure> {
     postgis_info!("request: {:?}", request);
-    let request = PathRequest::try_from(request)?;
+    let audited = super::audit::is_enabled().then(|| request.clone());
+    let started = std::time::Instant::now();
+    let result = search(PathRequest::try_from(request)?, deadline).await;
+    record_latency(started.elapsed());
+    let response = result?;
+
+    // The path is returned even if it couldn't be recorded
+    if let Some(request) = audited {
+        if let Err(e) = super::audit::record(&request, &response).await {
+            postgis_error!("could not record best path for audit: {e}");
+        }
+    }
+
+    Ok(response)
+}
 
+/// Searches for the best paths in the requested window, then in shifted
+///  windows if the request allows it
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance, not unit testable
+async fn search(
+    request: PathRequest,
+    deadline: Option<DateTime<Utc>>,
+) -> Result<BestPathResponse, SearchFailure> {
+    // Closed vertiports are not valid origins or targets
     let origin_geom = match request.origin_type {
-        NodeType::Vertiport => get_vertiport_centroidz(&request.origin_identifier).await?,
+        NodeType::Vertiport => get_vertiport_centroidz(&request.origin_identifier)
+            .await
+            .map_err(|e| match e {
+                PostgisError::Vertiport(VertiportError::Closed) => {
+                    PostgisError::BestPath(PathError::InvalidStartNode)
+                }
+                e => e,
+            })?,
         NodeType::Aircraft => get_aircraft_pointz(&request.origin_identifier).await?,
         _ => {
             postgis_error!(
@@ -600,82 +1257,449 @@ pub async fn best_path(request: BestPathRequest) -> Result<Vec<GrpcPath>, Postgi
                 request.origin_type,
                 request.target_type
             );
-            return Err(PostgisError::BestPath(PathError::InvalidStartNode));
+            return Err(PostgisError::BestPath(PathError::InvalidStartNode).into());
         }
     };
 
-    let target_geom = match request.target_type {
-        NodeType::Vertiport => get_vertiport_centroidz(&request.target_identifier).await?,
+    let target = match request.target_type {
+        NodeType::Vertiport => get_vertiport_centroid(&request.target_identifier).await?,
         _ => {
             postgis_error!(
                 "invalid node types: {:?} -> {:?}",
                 request.origin_type,
                 request.target_type
             );
-            return Err(PostgisError::BestPath(PathError::InvalidEndNode));
+            return Err(PostgisError::BestPath(PathError::InvalidEndNode).into());
         }
     };
 
-    // Get a subset of waypoints within N meters of the line between the origin and target
-    //  This saves computation time by doing shortest path on a smaller graph
-    let waypoints = crate::postgis::waypoint::get_waypoints_near_geometry(
-        &(postgis::ewkb::GeometryT::LineString(LineStringT {
-            points: vec![origin_geom, target_geom],
-            srid: Some(DEFAULT_SRID),
-        })),
-        WAYPOINT_RANGE_METERS,
-    )
-    .await?;
+    if target.status == VertiportStatus::Closed {
+        postgis_error!("vertiport '{}' is closed.", request.target_identifier);
+        return Err(PostgisError::BestPath(PathError::InvalidEndNode).into());
+    }
 
+    let target_geom = target.geom;
     postgis_info!("origin: {:?}", origin_geom);
     postgis_info!("target: {:?}", target_geom);
-    postgis_info!("nearby waypoints: {:?}", waypoints);
 
-    let origin_node = PathNode {
-        node_type: request.origin_type as i32,
-        identifier: request.origin_identifier,
-        geom: origin_geom,
-    };
+    let origin_node = PathNode {
+        node_type: request.origin_type as i32,
+        identifier: request.origin_identifier.clone(),
+        geom: origin_geom,
+    };
+
+    let target_node = PathNode {
+        node_type: request.target_type as i32,
+        identifier: request.target_identifier.clone(),
+        geom: target_geom,
+    };
+
+    let max_path_node_count = request.max_path_node_count.unwrap_or_else(|| {
+        default_path_node_count(super::utils::distance_meters(&origin_geom, &target_geom))
+    });
+    postgis_info!("max path node count: {}", max_path_node_count);
+
+    let failure = match search_window(
+        &request,
+        &origin_node,
+        &target_node,
+        &target.operating_hours,
+        max_path_node_count,
+        request.time_start,
+        request.time_end,
+        deadline,
+    )
+    .await
+    {
+        Ok(response) => return Ok(response),
+        Err(failure) => failure,
+    };
+
+    let window_dependent = match failure.error {
+        PostgisError::BestPath(e) => e.is_window_dependent(),
+        _ => false,
+    };
+
+    if !window_dependent || request.max_window_offset_minutes == 0 {
+        return Err(failure);
+    }
+
+    let window_deadline = Utc::now()
+        + Duration::try_milliseconds(WINDOW_SEARCH_TIME_LIMIT_MS).ok_or_else(|| {
+            postgis_error!("could not get time limit for shifted windows.");
+            PostgisError::BestPath(PathError::Internal)
+        })?;
+
+    let window_deadline = deadline.map_or(window_deadline, |d| d.min(window_deadline));
+    for offset in window_offsets(request.max_window_offset_minutes) {
+        if Utc::now() > window_deadline {
+            postgis_warn!("time limit reached before offset {offset} minutes.");
+            break;
+        }
+
+        let offset = Duration::try_minutes(offset).ok_or_else(|| {
+            postgis_error!("could not get window offset of {offset} minutes.");
+            PostgisError::BestPath(PathError::Internal)
+        })?;
+
+        let time_start = request.time_start + offset;
+        let time_end = request.time_end + offset;
+
+        // Departures can't be moved into the past
+        if time_start < Utc::now() {
+            continue;
+        }
+
+        postgis_debug!("searching shifted window {time_start} to {time_end}.");
+        match search_window(
+            &request,
+            &origin_node,
+            &target_node,
+            &target.operating_hours,
+            max_path_node_count,
+            time_start,
+            time_end,
+            deadline,
+        )
+        .await
+        {
+            Ok(response) => {
+                postgis_info!("found paths in window shifted by {offset}.");
+                return Ok(response);
+            }
+            Err(SearchFailure {
+                error: PostgisError::BestPath(e),
+                ..
+            }) if e.is_window_dependent() => continue,
+            Err(failure) => return Err(failure),
+        }
+    }
+
+    // Report why the requested window itself failed
+    Err(failure)
+}
+
+/// Searches for the best paths between two nodes within one time window
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance, not unit testable
+#[allow(clippy::too_many_arguments)]
+async fn search_window(
+    request: &PathRequest,
+    origin_node: &PathNode,
+    target_node: &PathNode,
+    operating_hours: &[i32],
+    max_path_node_count: usize,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    deadline: Option<DateTime<Utc>>,
+) -> Result<BestPathResponse, SearchFailure> {
+    let time_budget = search_budget(deadline, Utc::now()).map_err(PostgisError::BestPath)?;
+
+    let pool = crate::postgis::DEADPOOL_POSTGIS.get().ok_or_else(|| {
+        postgis_error!("could not get psql pool.");
+        PostgisError::BestPath(PathError::Client)
+    })?;
+
+    let client = pool.get().await.map_err(|e| {
+        postgis_error!("could not get client from psql connection pool: {}", e);
+        PostgisError::BestPath(PathError::Client)
+    })?;
+
+    let conflicts = PostgisConflicts {
+        client,
+        time_start,
+        time_end,
+        origin_identifier: &origin_node.identifier,
+        target_identifier: &target_node.identifier,
+        entrant: &request.entrant,
+        include_simulated: request.include_simulated,
+        priority: request.priority,
+        aircraft_type: request.aircraft_type,
+        blockers: Mutex::default(),
+    };
+
+    let limits = SearchLimits {
+        path_count: request.limit,
+        max_path_node_count,
+        time_budget,
+        altitude_limits: request.altitude_limits,
+    };
+
+    let nodes = PostgisNodes {
+        vertiports: (request.origin_type == NodeType::Vertiport).then_some((
+            origin_node.identifier.as_str(),
+            target_node.identifier.as_str(),
+        )),
+    };
+
+    let result = a_star(
+        origin_node.clone(),
+        target_node.clone(),
+        &nodes,
+        &conflicts,
+        limits,
+        request.weights,
+    )
+    .await
+    .map_err(|error| SearchFailure {
+        error,
+        blockers: conflicts.top_blockers(),
+    })?;
+
+    if result.truncated {
+        postgis_warn!(
+            "search stopped after {time_budget}, returning {} paths.",
+            result.paths.len()
+        );
+    }
+
+    // Paths are planned to depart at the start of the window
+    let found = result.paths.len();
+    let arriving = result
+        .paths
+        .into_iter()
+        .filter(|path| arrives_within(operating_hours, time_start, path.costs.time_seconds))
+        .collect::<Vec<_>>();
+
+    if found > 0 && arriving.is_empty() {
+        postgis_warn!(
+            "all {found} paths arrive outside the operating hours of '{}'.",
+            target_node.identifier
+        );
+
+        return Err(SearchFailure {
+            error: PostgisError::BestPath(PathError::OutsideOperatingHours),
+            blockers: conflicts.top_blockers(),
+        });
+    }
+
+    let mut paths = Vec::with_capacity(arriving.len());
+    for path in arriving {
+        let advisories = conflicts.advisories(&path.path).await?;
+        let (window_start, window_end) = arrival_window(
+            time_start,
+            path.costs.time_seconds,
+            request.arrival_window_minutes,
+        )
+        .ok_or_else(|| {
+            postgis_error!("could not get arrival window of path.");
+            PostgisError::BestPath(PathError::Internal)
+        })?;
+
+        let arrival_congestion = conflicts.arrivals(window_start, window_end).await?;
+        paths.push(GrpcPath {
+            path: path
+                .path
+                .iter()
+                .enumerate()
+                .map(|(index, p)| GrpcPathNode {
+                    index: index as i32,
+                    node_type: p.node_type,
+                    identifier: p.identifier.clone(),
+                    geom: Some(p.geom.into()),
+                })
+                .collect(),
+            distance_meters: path.costs.distance_meters,
+            costs: Some(GrpcPathCosts {
+                distance_meters: path.costs.distance_meters,
+                time_seconds: path.costs.time_seconds,
+                ground_risk_meters: path.costs.ground_risk_meters,
+                noise_meters: path.costs.noise_meters,
+                energy_meters: path.costs.energy_meters,
+                total: path.total_cost(),
+            }),
+            advisories,
+            arrival_congestion,
+        });
+    }
+
+    Ok(BestPathResponse {
+        paths,
+        blockers: conflicts.top_blockers(),
+        time_start: Some(time_start.into()),
+        time_end: Some(time_end.into()),
+        truncated: result.truncated,
+        rejected_count: result.rejected as u32,
+    })
+}
+
+/// Lists paths through a single waypoint between two points, shortest first
+///  Waypoints are tried at each flight level, paths longer than the
+///  max flight distance are dropped
+fn detour_candidates(
+    origin: &PointZ,
+    target: &PointZ,
+    waypoints: &[super::waypoint::Waypoint],
+) -> Vec<(f32, Vec<PointZ>)> {
+    let mut candidates = waypoints
+        .iter()
+        .flat_map(|w| {
+            FLIGHT_LEVELS.iter().map(|fl| PointZ {
+                x: w.geom.x,
+                y: w.geom.y,
+                z: *fl as f64,
+                srid: w.geom.srid,
+            })
+        })
+        .map(|p| {
+            let distance = super::utils::distance_meters(origin, &p)
+                + super::utils::distance_meters(&p, target);
+
+            (distance, vec![*origin, p, *target])
+        })
+        .filter(|(distance, _)| *distance <= MAX_FLIGHT_DISTANCE_METERS)
+        .collect::<Vec<_>>();
+
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+    candidates
+}
+
+/// Estimates the routable distance between two vertiports
+///  Tries the direct path first, then a few single-waypoint detours
+///  Returns None if no clear path was found
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need to run with a real database
+async fn estimate_routable_distance(
+    client: &deadpool_postgres::Client,
+    origin: (&str, &PointZ),
+    target: (&str, &PointZ),
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Result<Option<f32>, PostgisError> {
+    let (origin_identifier, origin_geom) = origin;
+    let (target_identifier, target_geom) = target;
+
+    let direct = (
+        super::utils::distance_meters(origin_geom, target_geom),
+        vec![*origin_geom, *target_geom],
+    );
+
+    let mut candidates = vec![direct];
+    let nodes = PostgisNodes {
+        vertiports: Some((origin_identifier, target_identifier)),
+    };
+    let waypoints = nodes.waypoints(origin_geom, target_geom).await?;
+
+    candidates.extend(
+        detour_candidates(origin_geom, target_geom, &waypoints)
+            .into_iter()
+            .take(MAX_DETOUR_ATTEMPTS),
+    );
+
+    for (distance, points) in candidates {
+        if distance > MAX_FLIGHT_DISTANCE_METERS {
+            continue;
+        }
+
+        let geom = LineStringT {
+            points,
+            srid: Some(DEFAULT_SRID),
+        };
+
+        match zone_intersection_checks(
+            client,
+            &geom,
+            time_start,
+            time_end,
+            origin_identifier,
+            target_identifier,
+            &Entrant::default(),
+        )
+        .await
+        {
+            Ok(()) => return Ok(Some(distance)),
+            Err(PostgisError::BestPath(PathError::ZoneIntersection)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Computes distances between every origin and target vertiport
+///
+/// Routable distances are estimates: only the direct path and a few
+///  single-waypoint detours are checked against active zones. Existing
+///  flight plans are not considered, as they depend on the exact path
+///  and departure time chosen later by [`best_path`].
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance, not unit testable
+pub async fn distance_matrix(
+    request: DistanceMatrixRequest,
+) -> Result<Vec<DistanceMatrixEntry>, PostgisError> {
+    postgis_info!("request: {:?}", request);
+    let request = MatrixRequest::try_from(request)?;
+
+    // Closed vertiports can't be routed to or from
+    let mut geoms: HashMap<&str, Option<PointZ>> = HashMap::new();
+    for identifier in request
+        .origin_identifiers
+        .iter()
+        .chain(request.target_identifiers.iter())
+    {
+        if geoms.contains_key(identifier.as_str()) {
+            continue;
+        }
+
+        let geom = match get_vertiport_centroidz(identifier).await {
+            Ok(geom) => Some(geom),
+            Err(PostgisError::Vertiport(VertiportError::Closed)) => None,
+            Err(e) => return Err(e),
+        };
+
+        geoms.insert(identifier, geom);
+    }
+
+    let pool = crate::postgis::DEADPOOL_POSTGIS.get().ok_or_else(|| {
+        postgis_error!("could not get psql pool.");
+        PostgisError::BestPath(PathError::Client)
+    })?;
+
+    let client = pool.get().await.map_err(|e| {
+        postgis_error!("could not get client from psql connection pool: {}", e);
+        PostgisError::BestPath(PathError::Client)
+    })?;
 
-    let target_node = PathNode {
-        node_type: request.target_type as i32,
-        identifier: request.target_identifier,
-        geom: target_geom,
-    };
+    let mut entries = vec![];
+    for origin_identifier in &request.origin_identifiers {
+        for target_identifier in &request.target_identifiers {
+            let origin_geom = geoms.get(origin_identifier.as_str()).copied().flatten();
+            let target_geom = geoms.get(target_identifier.as_str()).copied().flatten();
+            let mut entry = DistanceMatrixEntry {
+                origin_identifier: origin_identifier.clone(),
+                target_identifier: target_identifier.clone(),
+                ..Default::default()
+            };
+
+            if let (Some(origin_geom), Some(target_geom)) = (origin_geom, target_geom) {
+                entry.straight_line_meters =
+                    super::utils::distance_meters(&origin_geom, &target_geom);
+
+                if origin_identifier != target_identifier {
+                    entry.routable_meters = estimate_routable_distance(
+                        &client,
+                        (origin_identifier, &origin_geom),
+                        (target_identifier, &target_geom),
+                        request.time_start,
+                        request.time_end,
+                    )
+                    .await?;
+                }
+            }
 
-    let result = mod_a_star(
-        origin_node,
-        target_node,
-        request.time_start,
-        request.time_end,
-        waypoints,
-        request.limit,
-    )
-    .await?;
+            entry.feasible = entry.routable_meters.is_some();
+            entries.push(entry);
+        }
+    }
 
-    Ok(result
-        .into_iter()
-        .map(|path| GrpcPath {
-            path: path
-                .path
-                .iter()
-                .enumerate()
-                .map(|(index, p)| GrpcPathNode {
-                    index: index as i32,
-                    node_type: p.node_type,
-                    identifier: p.identifier.clone(),
-                    geom: Some(p.geom.into()),
-                })
-                .collect(),
-            distance_meters: path.distance_traversed_meters,
-        })
-        .collect::<Vec<GrpcPath>>())
+    Ok(entries)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::grpc::server::grpc_server;
+    use crate::postgis::authorization::AuthorizationError;
     use lib_common::uuid::Uuid;
 
     #[test]
@@ -688,6 +1712,16 @@ mod tests {
             time_start: None,
             time_end: None,
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request);
@@ -704,6 +1738,16 @@ mod tests {
             time_start: None,
             time_end: None,
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request).unwrap_err();
@@ -720,6 +1764,16 @@ mod tests {
             time_start: None,
             time_end: None,
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request).unwrap_err();
@@ -740,6 +1794,16 @@ mod tests {
             time_start: Some(time_start),
             time_end: Some(time_end.clone()),
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request).unwrap_err();
@@ -754,6 +1818,16 @@ mod tests {
             time_start: None,
             time_end: Some(time_end),
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request).unwrap_err();
@@ -770,6 +1844,16 @@ mod tests {
             time_start: Some(time_start),
             time_end: None,
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request).unwrap_err();
@@ -791,6 +1875,16 @@ mod tests {
             time_start: Some(time_start),
             time_end: Some(time_end),
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request).unwrap_err();
@@ -812,6 +1906,16 @@ mod tests {
             time_start: Some(time_start),
             time_end: Some(time_end),
             limit: -1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         let result = PathRequest::try_from(request.clone()).unwrap_err();
@@ -827,32 +1931,92 @@ mod tests {
     }
 
     #[test]
-    fn ut_path_order() {
-        // End time (assumed) is before start time
-        let mut paths: BinaryHeap<Path> = BinaryHeap::new();
-
-        let path1 = Path {
-            path: vec![],
-            distance_traversed_meters: 2.,
-            distance_to_target_meters: 0.,
+    fn ut_blocker_tally() {
+        let blocker = |flight: &str| Blocker {
+            flight_identifier: flight.to_string(),
+            aircraft_identifier: format!("{flight}-CRAFT"),
         };
 
-        let path2 = Path {
-            path: vec![],
-            distance_traversed_meters: 1.,
-            distance_to_target_meters: 0.,
-        };
+        let mut tally = BlockerTally::default();
+        assert!(tally.top(MAX_BLOCKER_COUNT).is_empty());
+
+        tally.record(blocker("B"));
+        tally.record(blocker("A"));
+        tally.record(blocker("C"));
+        tally.record(blocker("C"));
+        tally.record(blocker("C"));
+        tally.record(blocker("A"));
+
+        let top = tally.top(MAX_BLOCKER_COUNT);
+        let flights: Vec<(&str, u32)> = top
+            .iter()
+            .map(|b| (b.flight_identifier.as_str(), b.blocked_count))
+            .collect();
+        assert_eq!(flights, vec![("C", 3), ("A", 2), ("B", 1)]);
+        assert_eq!(top[0].aircraft_identifier, "C-CRAFT");
+
+        let top = tally.top(1);
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].flight_identifier, "C");
+    }
 
-        paths.push(path1);
-        paths.push(path2);
+    #[test]
+    fn test_search_failure_from_error() {
+        let error = PostgisError::BestPath(PathError::AllCandidatesConflicted);
+        let failure = SearchFailure::from(error);
+        assert_eq!(failure.error, error);
+        assert!(failure.blockers.is_empty());
+        assert_eq!(failure.to_string(), error.to_string());
+    }
 
-        assert_eq!(paths.pop().unwrap().distance_traversed_meters, 1.);
-        assert_eq!(paths.pop().unwrap().distance_traversed_meters, 2.);
+    #[test]
+    fn test_path_failure_reason() {
+        assert_eq!(
+            PathError::NoWaypointsNearby.failure_reason(),
+            Some(PathFailureReason::NoWaypointsNearby)
+        );
+        assert_eq!(
+            PathError::ExceededMaxDistance.failure_reason(),
+            Some(PathFailureReason::ExceededMaxDistance)
+        );
+        assert_eq!(
+            PathError::AllCandidatesConflicted.failure_reason(),
+            Some(PathFailureReason::AllCandidatesConflicted)
+        );
+        assert_eq!(
+            PathError::TimeBudgetExhausted.failure_reason(),
+            Some(PathFailureReason::TimeBudgetExhausted)
+        );
+        assert_eq!(
+            PathError::OutsideAltitudeLimits.failure_reason(),
+            Some(PathFailureReason::OutsideAltitudeLimits)
+        );
+        assert_eq!(
+            PathError::OutsideOperatingHours.failure_reason(),
+            Some(PathFailureReason::OutsideOperatingHours)
+        );
+        assert_eq!(PathError::InvalidStartNode.failure_reason(), None);
+        assert_eq!(PathError::DBError.failure_reason(), None);
     }
 
     #[test]
     fn test_path_error_display() {
-        assert_eq!(format!("{}", PathError::NoPath), "No path was found.");
+        assert_eq!(
+            format!("{}", PathError::NoWaypointsNearby),
+            "No path was found, no waypoints near blocked route."
+        );
+        assert_eq!(
+            format!("{}", PathError::ExceededMaxDistance),
+            "No path was found, max flight distance exceeded."
+        );
+        assert_eq!(
+            format!("{}", PathError::AllCandidatesConflicted),
+            "No path was found, all routes intersect zones or flights."
+        );
+        assert_eq!(
+            format!("{}", PathError::TimeBudgetExhausted),
+            "No path was found, search time limit reached."
+        );
         assert_eq!(
             format!("{}", PathError::InvalidStartNode),
             "Invalid start node."
@@ -891,39 +2055,260 @@ mod tests {
             format!("{}", PathError::FlightPlanIntersection),
             "Flight plan intersection error."
         );
+        assert_eq!(
+            format!("{}", PathError::ReservationIntersection),
+            "Volume reservation intersection error."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidNodeCount),
+            "Invalid number of nodes."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidWeights),
+            "Invalid path cost weights."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidPriority),
+            "Invalid flight priority."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidWindowOffset),
+            "Invalid search window offset."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidArrivalWindow),
+            "Invalid arrival window."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidAircraftType),
+            "Invalid aircraft type."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidAltitudeLimits),
+            "Invalid aircraft altitude limits."
+        );
+        assert_eq!(
+            format!("{}", PathError::InvalidSeparationMargins),
+            "Invalid flight separation margins."
+        );
+        assert_eq!(
+            format!("{}", PathError::OutsideAltitudeLimits),
+            "No path was found, no flight level within the aircraft altitude limits."
+        );
+        assert_eq!(
+            format!("{}", PathError::OutsideOperatingHours),
+            "No path was found, all routes arrive outside the target operating hours."
+        );
     }
 
     #[test]
-    fn test_partial_eq_path_node() {
-        let node = PathNode {
-            node_type: 0,
-            identifier: "test".to_string(),
-            geom: PointZ {
-                x: 0.,
-                y: 0.,
-                z: 0.,
-                srid: None,
-            },
+    fn ut_arrival_window() {
+        let time_start = Utc::now();
+        let (start, end) = arrival_window(time_start, 600.0, 10).unwrap();
+        assert_eq!(start, time_start);
+        assert_eq!(end, time_start + Duration::try_minutes(20).unwrap());
+
+        // only the estimated arrival itself
+        let (start, end) = arrival_window(time_start, 90.5, 0).unwrap();
+        assert_eq!(start, end);
+        assert_eq!(
+            start,
+            time_start + Duration::try_milliseconds(90_500).unwrap()
+        );
+    }
+
+    #[test]
+    fn ut_arrives_within() {
+        // A Monday, 21:50
+        let time_start = Utc.with_ymd_and_hms(2024, 1, 1, 21, 50, 0).unwrap();
+
+        // Open on Mondays from 06:00 to 22:00
+        let hours = vec![6 * 60, 22 * 60];
+        assert!(arrives_within(&hours, time_start, 300.0));
+        assert!(!arrives_within(&hours, time_start, 900.0));
+
+        // open at all hours
+        assert!(arrives_within(&[], time_start, 900.0));
+    }
+
+    #[test]
+    fn ut_window_offsets() {
+        assert!(window_offsets(0).is_empty());
+        assert!(window_offsets(WINDOW_OFFSET_STEP_MINUTES - 1).is_empty());
+        assert_eq!(window_offsets(12), vec![5, -5, 10, -10]);
+
+        let offsets = window_offsets(MAX_WINDOW_OFFSET_MINUTES);
+        assert_eq!(
+            offsets.len(),
+            2 * (MAX_WINDOW_OFFSET_MINUTES / WINDOW_OFFSET_STEP_MINUTES) as usize
+        );
+        assert_eq!(offsets.last(), Some(&-(MAX_WINDOW_OFFSET_MINUTES as i64)));
+    }
+
+    #[test]
+    fn ut_search_budget() {
+        let now = Utc::now();
+        let limit = Duration::try_milliseconds(BEST_PATH_TIME_LIMIT_MS).unwrap();
+        assert_eq!(search_budget(None, now).unwrap(), limit);
+
+        // a distant deadline doesn't extend the search
+        let deadline = now + Duration::try_seconds(30).unwrap();
+        assert_eq!(search_budget(Some(deadline), now).unwrap(), limit);
+
+        // a close deadline shortens it, keeping a margin to respond
+        let deadline = now + Duration::try_milliseconds(500).unwrap();
+        assert_eq!(
+            search_budget(Some(deadline), now).unwrap(),
+            Duration::try_milliseconds(500 - DEADLINE_MARGIN_MS).unwrap()
+        );
+
+        // a passed deadline leaves no time
+        let deadline = now - Duration::try_seconds(1).unwrap();
+        assert_eq!(
+            search_budget(Some(deadline), now).unwrap(),
+            Duration::zero()
+        );
+    }
+
+    #[test]
+    fn test_is_window_dependent() {
+        assert!(PathError::AllCandidatesConflicted.is_window_dependent());
+        assert!(PathError::NoWaypointsNearby.is_window_dependent());
+        assert!(PathError::OutsideOperatingHours.is_window_dependent());
+        assert!(!PathError::ExceededMaxDistance.is_window_dependent());
+        assert!(!PathError::TimeBudgetExhausted.is_window_dependent());
+        assert!(!PathError::InvalidStartNode.is_window_dependent());
+    }
+
+    #[test]
+    fn ut_gives_way() {
+        let routine = FlightPriority::Routine as i32;
+        let emergency = FlightPriority::Emergency as i32;
+        assert!(gives_way(routine, FlightPriority::Urgent));
+        assert!(gives_way(routine, FlightPriority::Emergency));
+
+        // same priority conflicts
+        assert!(!gives_way(routine, FlightPriority::Routine));
+        assert!(!gives_way(emergency, FlightPriority::Emergency));
+
+        // higher priority conflicts
+        assert!(!gives_way(emergency, FlightPriority::Urgent));
+    }
+
+    #[test]
+    fn ut_matrix_request_valid() {
+        let request = DistanceMatrixRequest {
+            origin_identifiers: vec![Uuid::new_v4().to_string()],
+            target_identifiers: vec![Uuid::new_v4().to_string(), Uuid::new_v4().to_string()],
+            time_start: None,
+            time_end: None,
+        };
+
+        let result = MatrixRequest::try_from(request.clone()).unwrap();
+        assert_eq!(result.origin_identifiers, request.origin_identifiers);
+        assert_eq!(result.target_identifiers, request.target_identifiers);
+        assert!(result.time_start < result.time_end);
+    }
+
+    #[test]
+    fn ut_matrix_request_invalid_node_count() {
+        let request = DistanceMatrixRequest {
+            origin_identifiers: vec![],
+            target_identifiers: vec![Uuid::new_v4().to_string()],
+            time_start: None,
+            time_end: None,
+        };
+
+        let result = MatrixRequest::try_from(request).unwrap_err();
+        assert_eq!(result, PostgisError::BestPath(PathError::InvalidNodeCount));
+
+        let request = DistanceMatrixRequest {
+            origin_identifiers: vec![Uuid::new_v4().to_string()],
+            target_identifiers: (0..=MAX_DISTANCE_MATRIX_NODE_COUNT)
+                .map(|_| Uuid::new_v4().to_string())
+                .collect(),
+            time_start: None,
+            time_end: None,
+        };
+
+        let result = MatrixRequest::try_from(request).unwrap_err();
+        assert_eq!(result, PostgisError::BestPath(PathError::InvalidNodeCount));
+    }
+
+    #[test]
+    fn ut_matrix_request_invalid_identifiers() {
+        let request = DistanceMatrixRequest {
+            origin_identifiers: vec!["Invalid!".to_string()],
+            target_identifiers: vec![Uuid::new_v4().to_string()],
+            time_start: None,
+            time_end: None,
+        };
+
+        let result = MatrixRequest::try_from(request).unwrap_err();
+        assert_eq!(result, PostgisError::BestPath(PathError::InvalidStartNode));
+
+        let request = DistanceMatrixRequest {
+            origin_identifiers: vec![Uuid::new_v4().to_string()],
+            target_identifiers: vec!["Invalid!".to_string()],
+            time_start: None,
+            time_end: None,
         };
 
-        let other = PathNode {
-            identifier: "test2".to_string(),
-            ..node.clone()
+        let result = MatrixRequest::try_from(request).unwrap_err();
+        assert_eq!(result, PostgisError::BestPath(PathError::InvalidEndNode));
+    }
+
+    #[test]
+    fn ut_matrix_request_invalid_time_window() {
+        let request = DistanceMatrixRequest {
+            origin_identifiers: vec![Uuid::new_v4().to_string()],
+            target_identifiers: vec![Uuid::new_v4().to_string()],
+            time_start: Some((Utc::now() + Duration::try_hours(2).unwrap()).into()),
+            time_end: Some((Utc::now() + Duration::try_hours(1).unwrap()).into()),
         };
 
-        assert_ne!(node, other);
+        let result = MatrixRequest::try_from(request).unwrap_err();
+        assert_eq!(result, PostgisError::BestPath(PathError::InvalidTimeWindow));
+    }
+
+    #[test]
+    fn test_detour_candidates() {
+        let point = |x: f64, y: f64| PointZ {
+            x,
+            y,
+            z: 0.0,
+            srid: Some(DEFAULT_SRID),
+        };
 
-        let other = PathNode {
-            node_type: 1,
-            geom: PointZ {
-                x: 1.,
-                y: 1.,
-                z: 1.,
-                srid: None,
+        let origin = point(4.90, 52.37);
+        let target = point(4.94, 52.37);
+        let waypoint = |identifier: &str, x: f64, y: f64| super::super::waypoint::Waypoint {
+            identifier: identifier.to_string(),
+            geom: postgis::ewkb::Point {
+                x,
+                y,
+                srid: Some(DEFAULT_SRID),
             },
-            ..node.clone()
         };
-        assert_eq!(node, other);
+
+        let waypoints = vec![
+            waypoint("far", 4.92, 52.40),
+            waypoint("near", 4.92, 52.38),
+            // Beyond the max flight distance
+            waypoint("unreachable", 40.0, 0.0),
+        ];
+
+        let candidates = detour_candidates(&origin, &target, &waypoints);
+        assert_eq!(candidates.len(), 2 * FLIGHT_LEVELS.len());
+
+        // Shortest first, the near waypoint at the lowest flight level
+        assert!(candidates.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        let (_, points) = &candidates[0];
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0], origin);
+        assert_eq!(points[1].y, 52.38);
+        assert_eq!(points[1].z, FLIGHT_LEVELS[0] as f64);
+        assert_eq!(points[2], target);
     }
 
     #[test]
@@ -941,47 +2326,6 @@ mod tests {
         assert_eq!(grpc_pointz.altitude_meters, pointz.z as f32);
     }
 
-    #[test]
-    fn test_path_eq() {
-        let mut path = Path {
-            path: vec![],
-            distance_traversed_meters: 0.,
-            distance_to_target_meters: 0.,
-        };
-
-        let heuristic = path.heuristic();
-        assert_eq!(
-            heuristic,
-            path.distance_to_target_meters + path.distance_traversed_meters
-        );
-
-        path.distance_traversed_meters = 1.;
-        let heuristic = path.heuristic();
-        assert_eq!(
-            heuristic,
-            path.distance_to_target_meters + path.distance_traversed_meters
-        );
-
-        path.distance_to_target_meters = 2.;
-        let heuristic = path.heuristic();
-        assert_eq!(
-            heuristic,
-            path.distance_to_target_meters + path.distance_traversed_meters
-        );
-
-        let mut other = path.clone();
-        assert!(path.eq(&other));
-
-        other.distance_traversed_meters = 2.;
-        assert!(!path.eq(&other));
-
-        // ordering is reversed for the min heap, comparison is reversed
-        assert!(path > other);
-
-        path.distance_traversed_meters = 10.0;
-        assert!(path < other);
-    }
-
     #[test]
     fn test_try_from_path_request() {
         let now = Utc::now();
@@ -993,6 +2337,16 @@ mod tests {
             time_start: Some(now.into()),
             time_end: Some((now + Duration::try_hours(1).unwrap()).into()),
             limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
         };
 
         // valid request
@@ -1004,6 +2358,107 @@ mod tests {
         assert_eq!(result.limit, request.limit as usize);
         assert_eq!(result.time_start, now);
         assert_eq!(result.time_end, now + Duration::try_hours(1).unwrap());
+        assert!(!result.include_simulated);
+
+        // simulated flights as conflicts
+        let tmp = BestPathRequest {
+            include_simulated: true,
+            ..request.clone()
+        };
+        assert!(PathRequest::try_from(tmp).unwrap().include_simulated);
+
+        // priority
+        assert_eq!(result.priority, FlightPriority::Routine);
+        let tmp = BestPathRequest {
+            priority: FlightPriority::Emergency as i32,
+            ..request.clone()
+        };
+        let result = PathRequest::try_from(tmp).unwrap();
+        assert_eq!(result.priority, FlightPriority::Emergency);
+        let tmp = BestPathRequest {
+            priority: 100,
+            ..request.clone()
+        };
+        let error = PathRequest::try_from(tmp).unwrap_err();
+        assert_eq!(error, PostgisError::BestPath(PathError::InvalidPriority));
+
+        // window offset
+        assert_eq!(result.max_window_offset_minutes, 0);
+        let tmp = BestPathRequest {
+            max_window_offset_minutes: Some(MAX_WINDOW_OFFSET_MINUTES),
+            ..request.clone()
+        };
+        let result = PathRequest::try_from(tmp).unwrap();
+        assert_eq!(result.max_window_offset_minutes, MAX_WINDOW_OFFSET_MINUTES);
+        let tmp = BestPathRequest {
+            max_window_offset_minutes: Some(MAX_WINDOW_OFFSET_MINUTES + 1),
+            ..request.clone()
+        };
+        let error = PathRequest::try_from(tmp).unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::BestPath(PathError::InvalidWindowOffset)
+        );
+
+        // arrival window
+        assert_eq!(
+            result.arrival_window_minutes,
+            DEFAULT_ARRIVAL_WINDOW_MINUTES
+        );
+        let tmp = BestPathRequest {
+            arrival_window_minutes: Some(0),
+            ..request.clone()
+        };
+        let result = PathRequest::try_from(tmp).unwrap();
+        assert_eq!(result.arrival_window_minutes, 0);
+        let tmp = BestPathRequest {
+            arrival_window_minutes: Some(MAX_ARRIVAL_WINDOW_MINUTES + 1),
+            ..request.clone()
+        };
+        let error = PathRequest::try_from(tmp).unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::BestPath(PathError::InvalidArrivalWindow)
+        );
+
+        // aircraft type, without configured altitude limits
+        assert_eq!(result.altitude_limits, None);
+        let tmp = BestPathRequest {
+            aircraft_type: Some(AircraftType::Rotorcraft as i32),
+            ..request.clone()
+        };
+        let result = PathRequest::try_from(tmp).unwrap();
+        assert_eq!(result.altitude_limits, None);
+        let tmp = BestPathRequest {
+            aircraft_type: Some(1000),
+            ..request.clone()
+        };
+        let error = PathRequest::try_from(tmp).unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::BestPath(PathError::InvalidAircraftType)
+        );
+
+        // operator, its reservations don't block the path
+        assert_eq!(result.entrant.operator_identifier, None);
+        let tmp = BestPathRequest {
+            operator_identifier: Some("OPERATOR-1".to_string()),
+            ..request.clone()
+        };
+        let result = PathRequest::try_from(tmp).unwrap();
+        assert_eq!(
+            result.entrant.operator_identifier,
+            Some("OPERATOR-1".to_string())
+        );
+        let tmp = BestPathRequest {
+            operator_identifier: Some("".to_string()),
+            ..request.clone()
+        };
+        let error = PathRequest::try_from(tmp).unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::Authorization(AuthorizationError::Entrant)
+        );
 
         // invalid start node
         let tmp = BestPathRequest {
@@ -1068,5 +2523,92 @@ mod tests {
         };
         let error = PathRequest::try_from(tmp).unwrap_err();
         assert_eq!(error, PostgisError::BestPath(PathError::InvalidEndTime));
+
+        // max path node count
+        let tmp = BestPathRequest {
+            max_path_node_count: Some(MAX_PATH_NODE_COUNT_LIMIT as u32),
+            ..request.clone()
+        };
+        let result = PathRequest::try_from(tmp).unwrap();
+        assert_eq!(result.max_path_node_count, Some(MAX_PATH_NODE_COUNT_LIMIT));
+        assert_eq!(result.weights, get_default_weights());
+
+        // weights
+        let weights = GrpcCostWeights {
+            distance: 1.0,
+            time: 2.0,
+            ground_risk: 0.0,
+            noise: 3.0,
+            energy: 0.5,
+        };
+        let tmp = BestPathRequest {
+            weights: Some(weights),
+            ..request.clone()
+        };
+        let result = PathRequest::try_from(tmp).unwrap();
+        assert_eq!(result.weights, CostWeights::from(weights));
+
+        for weights in [
+            GrpcCostWeights::default(),
+            GrpcCostWeights {
+                time: -1.0,
+                ..weights
+            },
+            GrpcCostWeights {
+                noise: f32::NAN,
+                ..weights
+            },
+        ] {
+            let tmp = BestPathRequest {
+                weights: Some(weights),
+                ..request.clone()
+            };
+            let error = PathRequest::try_from(tmp).unwrap_err();
+            assert_eq!(error, PostgisError::BestPath(PathError::InvalidWeights));
+        }
+
+        for count in [
+            0,
+            MIN_PATH_NODE_COUNT_LIMIT as u32 - 1,
+            MAX_PATH_NODE_COUNT_LIMIT as u32 + 1,
+        ] {
+            let tmp = BestPathRequest {
+                max_path_node_count: Some(count),
+                ..request.clone()
+            };
+            let error = PathRequest::try_from(tmp).unwrap_err();
+            assert_eq!(error, PostgisError::BestPath(PathError::InvalidNodeCount));
+        }
+    }
+
+    #[test]
+    fn test_default_path_node_count() {
+        assert_eq!(default_path_node_count(0.0), DEFAULT_PATH_NODE_COUNT);
+        assert_eq!(default_path_node_count(-1.0), DEFAULT_PATH_NODE_COUNT);
+        assert_eq!(default_path_node_count(5_000.0), DEFAULT_PATH_NODE_COUNT);
+        assert_eq!(
+            default_path_node_count(PATH_NODE_DISTANCE_METERS * 2.5),
+            DEFAULT_PATH_NODE_COUNT + 2
+        );
+        assert_eq!(
+            default_path_node_count(MAX_FLIGHT_DISTANCE_METERS),
+            MAX_PATH_NODE_COUNT_LIMIT
+        );
+    }
+
+    #[test]
+    fn ut_latency() {
+        assert_eq!(Latency::default().average_ms(), None);
+        let latency = Latency {
+            count: 4,
+            total_micros: 10_000,
+        };
+        assert_eq!(latency.average_ms(), Some(2.5));
+
+        let before = super::latency();
+        record_latency(std::time::Duration::from_millis(3));
+        let after = super::latency();
+        assert_eq!(after.count, before.count + 1);
+        assert_eq!(after.total_micros, before.total_micros + 3_000);
     }
 }
@@ -0,0 +1,357 @@
+//! Imports vertiports, waypoints and zones from a snapshot file
+//!  when the database is empty.
+//!
+//! On a fresh database the server knows nothing until the other services
+//!  resend their assets. A snapshot exported from svc-storage can be
+//!  provided instead, which is imported at startup. The server reports that
+//!  it is not ready until the import has completed.
+
+use super::statements::Statement;
+use super::{vertiport, waypoint, zone};
+use crate::grpc::server::grpc_server::{
+    Coordinates, Vertiport as RequestVertiport, Waypoint as RequestWaypoint, Zone as RequestZone,
+};
+use crate::types::ZoneUpdate;
+use lib_common::time::Utc;
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set while an import is pending or has failed
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Possible errors importing a snapshot
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BootstrapError {
+    /// The snapshot file could not be read
+    Read,
+
+    /// The snapshot file is not valid
+    Parse,
+
+    /// Could not check the existing assets
+    DBError,
+
+    /// Could not insert the assets
+    Insert,
+}
+
+impl Display for BootstrapError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            BootstrapError::Read => write!(f, "Could not read the snapshot."),
+            BootstrapError::Parse => write!(f, "Could not parse the snapshot."),
+            BootstrapError::DBError => write!(f, "Unknown backend error."),
+            BootstrapError::Insert => write!(f, "Could not import the snapshot."),
+        }
+    }
+}
+
+impl std::error::Error for BootstrapError {}
+
+/// A vertex in a snapshot
+#[derive(Debug, Copy, Clone, Deserialize)]
+struct SnapshotVertex {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl From<SnapshotVertex> for Coordinates {
+    fn from(vertex: SnapshotVertex) -> Self {
+        Coordinates {
+            latitude: vertex.latitude,
+            longitude: vertex.longitude,
+        }
+    }
+}
+
+/// A vertiport in a snapshot
+#[derive(Debug, Clone, Deserialize)]
+struct SnapshotVertiport {
+    identifier: String,
+    vertices: Vec<SnapshotVertex>,
+    #[serde(default)]
+    altitude_meters: f32,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+/// A waypoint in a snapshot
+#[derive(Debug, Clone, Deserialize)]
+struct SnapshotWaypoint {
+    identifier: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Assets to import, in the format of the zone queue for zones
+#[derive(Debug, Clone, Default, Deserialize)]
+struct Snapshot {
+    #[serde(default)]
+    vertiports: Vec<SnapshotVertiport>,
+
+    #[serde(default)]
+    waypoints: Vec<SnapshotWaypoint>,
+
+    #[serde(default)]
+    zones: Vec<ZoneUpdate>,
+}
+
+/// The assets of a snapshot, as update requests
+#[derive(Debug, Clone, Default)]
+struct Assets {
+    vertiports: Vec<RequestVertiport>,
+    waypoints: Vec<RequestWaypoint>,
+    zones: Vec<RequestZone>,
+}
+
+/// Parses a snapshot. Zones marked as deleted are ignored.
+fn parse_snapshot(contents: &str) -> Result<Assets, BootstrapError> {
+    let snapshot: Snapshot = serde_json::from_str(contents).map_err(|e| {
+        postgis_error!("could not parse snapshot: {}", e);
+        BootstrapError::Parse
+    })?;
+
+    let timestamp_network = Some(Utc::now().into());
+    let vertiports = snapshot
+        .vertiports
+        .into_iter()
+        .map(|vertiport| RequestVertiport {
+            identifier: vertiport.identifier,
+            vertices: vertiport.vertices.into_iter().map(Into::into).collect(),
+            altitude_meters: vertiport.altitude_meters,
+            label: vertiport.label,
+            timestamp_network: timestamp_network.clone(),
+            status: None,
+            layers: None,
+            compact_vertices: None,
+            operating_hours: vec![],
+        })
+        .collect();
+
+    let waypoints = snapshot
+        .waypoints
+        .into_iter()
+        .map(|waypoint| RequestWaypoint {
+            identifier: waypoint.identifier,
+            location: Some(Coordinates {
+                latitude: waypoint.latitude,
+                longitude: waypoint.longitude,
+            }),
+        })
+        .collect();
+
+    let zones = snapshot
+        .zones
+        .into_iter()
+        .filter(|zone| !zone.deleted)
+        .map(RequestZone::from)
+        .collect();
+
+    Ok(Assets {
+        vertiports,
+        waypoints,
+        zones,
+    })
+}
+
+/// Checks if the database already holds vertiports, waypoints or zones
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+async fn has_assets() -> Result<bool, BootstrapError> {
+    let client = super::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            BootstrapError::DBError
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            BootstrapError::DBError
+        })?;
+
+    let stmt = client
+        .prepare_cached(&Statement::HasAssets.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            BootstrapError::DBError
+        })?;
+
+    client
+        .query_one(&stmt, &[])
+        .await
+        .and_then(|row| row.try_get("has_assets"))
+        .map_err(|e| {
+            postgis_error!("could not check existing assets: {}", e);
+            BootstrapError::DBError
+        })
+}
+
+/// Inserts the assets of a snapshot
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+async fn import(assets: Assets) -> Result<(), BootstrapError> {
+    postgis_info!(
+        "importing {} vertiports, {} waypoints and {} zones.",
+        assets.vertiports.len(),
+        assets.waypoints.len(),
+        assets.zones.len()
+    );
+
+    if !assets.vertiports.is_empty() {
+        vertiport::update_vertiports(assets.vertiports)
+            .await
+            .map_err(|e| {
+                postgis_error!("could not import vertiports: {}", e);
+                BootstrapError::Insert
+            })?;
+    }
+
+    if !assets.waypoints.is_empty() {
+        waypoint::update_waypoints(assets.waypoints)
+            .await
+            .map_err(|e| {
+                postgis_error!("could not import waypoints: {}", e);
+                BootstrapError::Insert
+            })?;
+    }
+
+    if !assets.zones.is_empty() {
+        zone::update_zones(assets.zones).await.map_err(|e| {
+            postgis_error!("could not import zones: {}", e);
+            BootstrapError::Insert
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Marks the server as not ready until [`run`] succeeds
+pub fn begin() {
+    PENDING.store(true, Ordering::SeqCst);
+}
+
+/// If the server is ready to serve requests
+pub fn is_ready() -> bool {
+    !PENDING.load(Ordering::SeqCst)
+}
+
+/// Imports the snapshot at the provided path if the database holds no
+///  assets yet. The server stays not ready if the import fails.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+pub async fn run(path: &str) -> Result<(), BootstrapError> {
+    if has_assets().await? {
+        postgis_info!("database already holds assets, skipping snapshot import.");
+        PENDING.store(false, Ordering::SeqCst);
+        return Ok(());
+    }
+
+    let contents = tokio::fs::read_to_string(path).await.map_err(|e| {
+        postgis_error!("could not read snapshot {path}: {}", e);
+        BootstrapError::Read
+    })?;
+
+    import(parse_snapshot(&contents)?).await?;
+
+    postgis_info!("snapshot {path} imported.");
+    PENDING.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_parse_snapshot() {
+        let contents = r#"{
+            "vertiports": [{
+                "identifier": "vertiport-1",
+                "vertices": [
+                    { "latitude": 52.374, "longitude": 4.915 },
+                    { "latitude": 52.375, "longitude": 4.915 },
+                    { "latitude": 52.375, "longitude": 4.916 },
+                    { "latitude": 52.374, "longitude": 4.915 }
+                ],
+                "label": "Vertiport 1"
+            }],
+            "waypoints": [
+                { "identifier": "waypoint-1", "latitude": 52.38, "longitude": 4.92 }
+            ],
+            "zones": [
+                {
+                    "identifier": "zone-1",
+                    "zone_type": 1,
+                    "vertices": [
+                        { "latitude": 52.39, "longitude": 4.93 },
+                        { "latitude": 52.40, "longitude": 4.93 },
+                        { "latitude": 52.40, "longitude": 4.94 },
+                        { "latitude": 52.39, "longitude": 4.93 }
+                    ],
+                    "altitude_meters_max": 500.0,
+                    "timestamp_network": "2024-01-01T00:00:00Z"
+                },
+                {
+                    "identifier": "zone-2",
+                    "deleted": true,
+                    "timestamp_network": "2024-01-01T00:00:00Z"
+                }
+            ]
+        }"#;
+
+        let assets = parse_snapshot(contents).unwrap();
+        assert_eq!(assets.vertiports.len(), 1);
+        assert_eq!(assets.vertiports[0].vertices.len(), 4);
+        assert_eq!(assets.vertiports[0].label, Some("Vertiport 1".to_string()));
+        assert!(assets.vertiports[0].timestamp_network.is_some());
+        assert!(vertiport::Vertiport::try_from(assets.vertiports[0].clone()).is_ok());
+
+        assert_eq!(assets.waypoints.len(), 1);
+        assert_eq!(assets.waypoints[0].location.unwrap().latitude, 52.38);
+
+        assert_eq!(assets.zones.len(), 1);
+        assert_eq!(assets.zones[0].identifier, "zone-1");
+        assert!(zone::Zone::try_from(assets.zones[0].clone()).is_ok());
+    }
+
+    #[test]
+    fn ut_parse_snapshot_empty() {
+        let assets = parse_snapshot("{}").unwrap();
+        assert!(assets.vertiports.is_empty());
+        assert!(assets.waypoints.is_empty());
+        assert!(assets.zones.is_empty());
+    }
+
+    #[test]
+    fn ut_parse_snapshot_invalid() {
+        let error = parse_snapshot(r#"{ "waypoints": [{ "identifier": "w" }] }"#).unwrap_err();
+        assert_eq!(error, BootstrapError::Parse);
+
+        let error = parse_snapshot("not json").unwrap_err();
+        assert_eq!(error, BootstrapError::Parse);
+    }
+
+    #[test]
+    fn test_bootstrap_error_display() {
+        assert_eq!(
+            BootstrapError::Read.to_string(),
+            "Could not read the snapshot."
+        );
+        assert_eq!(
+            BootstrapError::Parse.to_string(),
+            "Could not parse the snapshot."
+        );
+        assert_eq!(
+            BootstrapError::DBError.to_string(),
+            "Unknown backend error."
+        );
+        assert_eq!(
+            BootstrapError::Insert.to_string(),
+            "Could not import the snapshot."
+        );
+    }
+}
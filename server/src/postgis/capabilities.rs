@@ -0,0 +1,374 @@
+//! Checks of the database features this service depends on.
+//!
+//! Flight segments are split with `ST_DumpSegments`, added in PostGIS 3.2.
+//!  The server checks the database at startup so that a missing extension
+//!  is reported before any table is created, rather than as a failed
+//!  statement later on.
+//!
+//! Zone volumes are extruded with `ST_Extrude` into polyhedral surfaces,
+//!  which requires the `postgis_sfcgal` extension. Some managed databases
+//!  don't offer it, in which case zones are kept as footprints and checked
+//!  against their altitude bounds instead (see [`ZoneVolumes`]).
+
+use super::PostgisError;
+use deadpool_postgres::Object;
+use once_cell::sync::OnceCell;
+use std::fmt::{self, Display, Formatter};
+
+/// Minimum PostgreSQL version, as reported by `server_version_num`
+pub const MIN_POSTGRES_VERSION_NUM: i32 = 120000;
+
+/// Minimum PostGIS version, as (major, minor)
+pub const MIN_POSTGIS_VERSION: (u32, u32) = (3, 2);
+
+/// Extension providing the PostGIS types and functions
+pub const POSTGIS_EXTENSION: &str = "postgis";
+
+/// Extension providing the 3D functions of PostGIS
+pub const SFCGAL_EXTENSION: &str = "postgis_sfcgal";
+
+/// Capabilities detected at startup
+static CAPABILITIES: OnceCell<Capabilities> = OnceCell::new();
+
+/// Possible errors with the database capabilities
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CapabilityError {
+    /// PostgreSQL is older than [`MIN_POSTGRES_VERSION_NUM`]
+    PostgresVersion,
+
+    /// The postgis extension is not installed
+    Postgis,
+
+    /// PostGIS is older than [`MIN_POSTGIS_VERSION`]
+    PostgisVersion,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for CapabilityError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CapabilityError::PostgresVersion => write!(
+                f,
+                "PostgreSQL {} or later is required.",
+                MIN_POSTGRES_VERSION_NUM / 10000
+            ),
+            CapabilityError::Postgis => write!(
+                f,
+                "The {POSTGIS_EXTENSION} extension is not installed, run CREATE EXTENSION {POSTGIS_EXTENSION}; in the database."
+            ),
+            CapabilityError::PostgisVersion => write!(
+                f,
+                "PostGIS {}.{} or later is required, run ALTER EXTENSION {POSTGIS_EXTENSION} UPDATE; after upgrading the package.",
+                MIN_POSTGIS_VERSION.0, MIN_POSTGIS_VERSION.1
+            ),
+            CapabilityError::Client => write!(f, "Could not get backend client."),
+            CapabilityError::DBError => write!(f, "Unknown backend error."),
+        }
+    }
+}
+
+/// How zone volumes are stored and checked against flight paths
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ZoneVolumes {
+    /// Footprints extruded into polyhedral surfaces, requires SFCGAL
+    Extruded,
+
+    /// Footprints only, paths are checked against the altitude bounds
+    Footprint,
+}
+
+impl Display for ZoneVolumes {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ZoneVolumes::Extruded => write!(f, "extruded"),
+            ZoneVolumes::Footprint => write!(f, "footprint"),
+        }
+    }
+}
+
+/// Versions of the database and of its installed extensions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Capabilities {
+    /// PostgreSQL version, such as "16.2"
+    pub postgres_version: String,
+
+    /// PostgreSQL version as a number, such as 160002
+    pub postgres_version_num: i32,
+
+    /// Installed extensions and their versions
+    pub extensions: Vec<(String, String)>,
+}
+
+impl Capabilities {
+    /// Gets the version of an installed extension
+    pub fn extension_version(&self, name: &str) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find(|(extension, _)| extension == name)
+            .map(|(_, version)| version.as_str())
+    }
+
+    /// Gets the PostGIS version, if installed
+    pub fn postgis_version(&self) -> Option<&str> {
+        self.extension_version(POSTGIS_EXTENSION)
+    }
+
+    /// Gets the postgis_sfcgal version, if installed
+    pub fn sfcgal_version(&self) -> Option<&str> {
+        self.extension_version(SFCGAL_EXTENSION)
+    }
+
+    /// Gets the zone volume representation these capabilities allow
+    pub fn zone_volumes(&self) -> ZoneVolumes {
+        match self.sfcgal_version() {
+            Some(_) => ZoneVolumes::Extruded,
+            None => ZoneVolumes::Footprint,
+        }
+    }
+
+    /// Checks the versions against the requirements of this service
+    pub fn validate(&self) -> Result<(), CapabilityError> {
+        if self.postgres_version_num < MIN_POSTGRES_VERSION_NUM {
+            postgis_error!(
+                "found PostgreSQL {} ({}).",
+                self.postgres_version,
+                self.postgres_version_num
+            );
+            return Err(CapabilityError::PostgresVersion);
+        }
+
+        let Some(postgis_version) = self.postgis_version() else {
+            return Err(CapabilityError::Postgis);
+        };
+
+        match parse_version(postgis_version) {
+            Some(version) if version >= MIN_POSTGIS_VERSION => (),
+            _ => {
+                postgis_error!("found PostGIS {}.", postgis_version);
+                return Err(CapabilityError::PostgisVersion);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the major and minor numbers of an extension version,
+///  such as "3.4.2" or "3.5.0alpha1"
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    let number = |part: Option<&str>| {
+        let digits: String = part?.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse::<u32>().ok()
+    };
+
+    Some((number(parts.next())?, number(parts.next())?))
+}
+
+/// Gets the capabilities detected at startup, if checked
+pub fn get_capabilities() -> Option<&'static Capabilities> {
+    CAPABILITIES.get()
+}
+
+/// Gets the zone volume representation in use. Zones are extruded until
+///  the capabilities have been checked.
+pub fn zone_volumes() -> ZoneVolumes {
+    get_capabilities().map_or(ZoneVolumes::Extruded, Capabilities::zone_volumes)
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Capability(CapabilityError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Capability(CapabilityError::Client)
+        })
+}
+
+/// Queries the database version and installed extensions
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test
+async fn detect() -> Result<Capabilities, PostgisError> {
+    let client = get_client().await?;
+    let error = |e: tokio_postgres::Error| {
+        postgis_error!("could not query database capabilities: {}", e);
+        PostgisError::Capability(CapabilityError::DBError)
+    };
+
+    let row = client
+        .query_one(
+            r#"SELECT
+                current_setting('server_version') AS "server_version",
+                current_setting('server_version_num')::INTEGER AS "server_version_num";"#,
+            &[],
+        )
+        .await
+        .map_err(error)?;
+
+    let extensions = client
+        .query(
+            r#"SELECT "extname"::TEXT, "extversion" FROM pg_extension ORDER BY "extname";"#,
+            &[],
+        )
+        .await
+        .map_err(error)?
+        .iter()
+        .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+        .collect::<Result<Vec<(String, String)>, tokio_postgres::Error>>()
+        .map_err(error)?;
+
+    Ok(Capabilities {
+        postgres_version: row.try_get("server_version").map_err(error)?,
+        postgres_version_num: row.try_get("server_version_num").map_err(error)?,
+        extensions,
+    })
+}
+
+/// Detects the database capabilities, records them and fails if the
+///  database lacks a requirement of this service
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test
+pub async fn check() -> Result<&'static Capabilities, PostgisError> {
+    postgis_debug!("entry.");
+    let capabilities = detect().await?;
+    postgis_info!(
+        "detected PostgreSQL {}, PostGIS {}, SFCGAL {}.",
+        capabilities.postgres_version,
+        capabilities.postgis_version().unwrap_or("(none)"),
+        capabilities.sfcgal_version().unwrap_or("(none)")
+    );
+
+    capabilities.validate().map_err(|e| {
+        postgis_error!("{}", e);
+        PostgisError::Capability(e)
+    })?;
+
+    if capabilities.zone_volumes() == ZoneVolumes::Footprint {
+        postgis_warn!(
+            "{SFCGAL_EXTENSION} is not installed, zones are checked by footprint and altitude bounds."
+        );
+    }
+
+    Ok(CAPABILITIES.get_or_init(|| capabilities))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capabilities() -> Capabilities {
+        Capabilities {
+            postgres_version: "16.2".to_string(),
+            postgres_version_num: 160002,
+            extensions: vec![
+                ("plpgsql".to_string(), "1.0".to_string()),
+                (POSTGIS_EXTENSION.to_string(), "3.4.2".to_string()),
+                (SFCGAL_EXTENSION.to_string(), "3.4.2".to_string()),
+            ],
+        }
+    }
+
+    #[test]
+    fn ut_parse_version() {
+        assert_eq!(parse_version("3.4.2"), Some((3, 4)));
+        assert_eq!(parse_version("3.5.0alpha1"), Some((3, 5)));
+        assert_eq!(parse_version("3.10"), Some((3, 10)));
+        assert_eq!(parse_version("3"), None);
+        assert_eq!(parse_version("dev"), None);
+    }
+
+    #[test]
+    fn ut_extension_version() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.postgis_version(), Some("3.4.2"));
+        assert_eq!(capabilities.sfcgal_version(), Some("3.4.2"));
+        assert_eq!(capabilities.extension_version("pgrouting"), None);
+    }
+
+    #[test]
+    fn ut_validate() {
+        assert_eq!(capabilities().validate(), Ok(()));
+
+        let result = Capabilities {
+            postgres_version: "11.22".to_string(),
+            postgres_version_num: 110022,
+            ..capabilities()
+        }
+        .validate();
+        assert_eq!(result, Err(CapabilityError::PostgresVersion));
+
+        let without = |name: &str| {
+            let mut capabilities = capabilities();
+            capabilities
+                .extensions
+                .retain(|(extension, _)| extension != name);
+            capabilities.validate()
+        };
+        assert_eq!(without(POSTGIS_EXTENSION), Err(CapabilityError::Postgis));
+
+        // falls back to footprints
+        assert_eq!(without(SFCGAL_EXTENSION), Ok(()));
+
+        let mut old = capabilities();
+        old.extensions[1].1 = "3.1.4".to_string();
+        assert_eq!(old.validate(), Err(CapabilityError::PostgisVersion));
+    }
+
+    #[test]
+    fn ut_zone_volumes() {
+        let mut capabilities = capabilities();
+        assert_eq!(capabilities.zone_volumes(), ZoneVolumes::Extruded);
+
+        capabilities
+            .extensions
+            .retain(|(extension, _)| extension != SFCGAL_EXTENSION);
+        assert_eq!(capabilities.zone_volumes(), ZoneVolumes::Footprint);
+
+        // unchecked
+        assert_eq!(zone_volumes(), ZoneVolumes::Extruded);
+    }
+
+    #[test]
+    fn test_zone_volumes_display() {
+        assert_eq!(ZoneVolumes::Extruded.to_string(), "extruded");
+        assert_eq!(ZoneVolumes::Footprint.to_string(), "footprint");
+    }
+
+    #[test]
+    fn test_capability_error_display() {
+        assert_eq!(
+            CapabilityError::PostgresVersion.to_string(),
+            "PostgreSQL 12 or later is required."
+        );
+        assert_eq!(
+            CapabilityError::Postgis.to_string(),
+            "The postgis extension is not installed, run CREATE EXTENSION postgis; in the database."
+        );
+        assert_eq!(
+            CapabilityError::PostgisVersion.to_string(),
+            "PostGIS 3.2 or later is required, run ALTER EXTENSION postgis UPDATE; after upgrading the package."
+        );
+        assert_eq!(
+            CapabilityError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(
+            CapabilityError::DBError.to_string(),
+            "Unknown backend error."
+        );
+    }
+}
@@ -0,0 +1,602 @@
+//! Reports the zones constraining a volume or path.
+//!
+//! Several zones can overlap a volume or path with different types,
+//!  validity periods, schedules and authorizations. This module lists all
+//!  of them with their effect, most severe first, so that operators can
+//!  tell which one governs a routing decision.
+
+use super::authorization::Entrant;
+use super::statements::Statement;
+use super::utils::{polygon_from_vertices_z, validate_pointz};
+use super::{PostgisError, DEFAULT_SRID};
+use crate::grpc::server::grpc_server::{
+    ConstraintEffect, GetZoneConstraintsRequest, GetZoneConstraintsResponse, ZoneConstraint,
+    ZoneType,
+};
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Utc};
+use postgis::ewkb::{LineStringZ, PointZ, PolygonZ};
+use std::fmt::{self, Display, Formatter};
+
+/// Possible errors with zone constraint requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConstraintError {
+    /// Invalid volume or path, or both provided
+    Location,
+
+    /// Invalid Altitude Range
+    Altitude,
+
+    /// Invalid Time Provided
+    Time,
+
+    /// Invalid aircraft or flight identifier
+    Identifier,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for ConstraintError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ConstraintError::Location => write!(f, "Invalid volume or path provided."),
+            ConstraintError::Altitude => write!(f, "Invalid altitude range provided."),
+            ConstraintError::Time => write!(f, "Invalid time provided."),
+            ConstraintError::Identifier => write!(f, "Invalid identifier provided."),
+            ConstraintError::Client => write!(f, "Could not get backend client."),
+            ConstraintError::DBError => write!(f, "Unknown backend error."),
+        }
+    }
+}
+
+/// The airspace the constraints are reported for
+#[derive(Debug)]
+enum Shape {
+    /// A polygon extended between two altitudes
+    Volume {
+        geom: PolygonZ,
+        altitude_meters_min: f32,
+        altitude_meters_max: f32,
+    },
+
+    /// A path through the provided points
+    Path(LineStringZ),
+}
+
+/// A volume or path during a time window, flown by an entrant
+#[derive(Debug)]
+struct ConstraintQuery {
+    shape: Shape,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    entrant: Entrant,
+}
+
+impl TryFrom<GetZoneConstraintsRequest> for ConstraintQuery {
+    type Error = ConstraintError;
+
+    fn try_from(request: GetZoneConstraintsRequest) -> Result<Self, Self::Error> {
+        let shape = match (request.vertices.is_empty(), request.path.is_empty()) {
+            (false, true) => {
+                if request.altitude_meters_min > request.altitude_meters_max {
+                    postgis_error!(
+                        "altitude_meters_min ({}) > altitude_meters_max ({}).",
+                        request.altitude_meters_min,
+                        request.altitude_meters_max
+                    );
+                    return Err(ConstraintError::Altitude);
+                }
+
+                let geom = polygon_from_vertices_z(&request.vertices, request.altitude_meters_min)
+                    .map_err(|e| {
+                        postgis_error!("invalid volume polygon: {}", e);
+                        ConstraintError::Location
+                    })?;
+
+                Shape::Volume {
+                    geom,
+                    altitude_meters_min: request.altitude_meters_min,
+                    altitude_meters_max: request.altitude_meters_max,
+                }
+            }
+            (true, false) => {
+                let points: Vec<PointZ> = request.path.into_iter().map(Into::into).collect();
+                if points.len() < 2 || points.iter().any(|p| validate_pointz(p).is_err()) {
+                    postgis_error!("path needs at least two valid points: {:?}", points);
+                    return Err(ConstraintError::Location);
+                }
+
+                Shape::Path(LineStringZ {
+                    points,
+                    srid: Some(DEFAULT_SRID),
+                })
+            }
+            _ => {
+                postgis_error!("exactly one of a volume or a path must be provided.");
+                return Err(ConstraintError::Location);
+            }
+        };
+
+        let time_start: DateTime<Utc> = request
+            .time_start
+            .ok_or_else(|| {
+                postgis_error!("time_start is required.");
+                ConstraintError::Time
+            })?
+            .into();
+
+        let time_end: DateTime<Utc> = request
+            .time_end
+            .ok_or_else(|| {
+                postgis_error!("time_end is required.");
+                ConstraintError::Time
+            })?
+            .into();
+
+        if time_end < time_start {
+            postgis_error!("time_end ({time_end}) is before time_start ({time_start}).");
+            return Err(ConstraintError::Time);
+        }
+
+        let entrant = Entrant::new(request.aircraft_identifier, request.flight_identifier)
+            .map_err(|_| ConstraintError::Identifier)?;
+
+        Ok(ConstraintQuery {
+            shape,
+            time_start,
+            time_end,
+            entrant,
+        })
+    }
+}
+
+/// A zone crossed by the volume or path, as stored
+#[derive(Debug, Clone)]
+struct CrossedZone {
+    identifier: String,
+    zone_type: ZoneType,
+    altitude_meters_min: f32,
+    altitude_meters_max: f32,
+    time_start: Option<DateTime<Utc>>,
+    time_end: Option<DateTime<Utc>>,
+    schedule: Vec<i32>,
+    authority: Option<String>,
+    authorized: bool,
+}
+
+impl CrossedZone {
+    /// The effect of the zone on the query and the part of the time
+    ///  window during which the zone is valid
+    fn constraint(self, query: &ConstraintQuery) -> ZoneConstraint {
+        let effect =
+            if !super::zone::schedule_overlaps(&self.schedule, query.time_start, query.time_end) {
+                ConstraintEffect::ConstraintUnscheduled
+            } else if self.authorized {
+                ConstraintEffect::ConstraintAuthorized
+            } else {
+                ConstraintEffect::ConstraintBlocking
+            };
+
+        let time_start = self
+            .time_start
+            .map_or(query.time_start, |t| t.max(query.time_start));
+        let time_end = self
+            .time_end
+            .map_or(query.time_end, |t| t.min(query.time_end));
+
+        ZoneConstraint {
+            zone_identifier: self.identifier,
+            zone_type: self.zone_type as i32,
+            effect: effect as i32,
+            altitude_meters_min: self.altitude_meters_min,
+            altitude_meters_max: self.altitude_meters_max,
+            time_start: Some(time_start.into()),
+            time_end: Some(time_end.into()),
+            authority: self.authority,
+        }
+    }
+}
+
+/// Orders constraints by severity, then by the time they take effect
+///  Blocking zones come first, then zones the entrant is authorized to
+///  enter, then zones that are off schedule during the time window.
+fn sort_constraints(constraints: &mut [ZoneConstraint]) {
+    constraints.sort_by(|a, b| {
+        let start = |c: &ZoneConstraint| c.time_start.clone().map(DateTime::<Utc>::from);
+        a.effect
+            .cmp(&b.effect)
+            .then_with(|| start(a).cmp(&start(b)))
+            .then_with(|| a.zone_identifier.cmp(&b.zone_identifier))
+    });
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Constraint(ConstraintError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Constraint(ConstraintError::Client)
+        })
+}
+
+/// Converts a zone row into a crossed zone
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test, no way to create a Row without querying it
+fn process_zone_row(row: &tokio_postgres::Row) -> Result<CrossedZone, tokio_postgres::Error> {
+    Ok(CrossedZone {
+        identifier: row.try_get("identifier")?,
+        zone_type: row.try_get("zone_type")?,
+        altitude_meters_min: row.try_get("altitude_meters_min")?,
+        altitude_meters_max: row.try_get("altitude_meters_max")?,
+        time_start: row.try_get("time_start")?,
+        time_end: row.try_get("time_end")?,
+        schedule: row.try_get("schedule")?,
+        authority: row.try_get("authority")?,
+        authorized: row.try_get("authorized")?,
+    })
+}
+
+/// Gets the zones crossed by a volume or path during a time window, with
+///  their effect, most severe first. The first blocking zone, if any,
+///  governs the volume or path.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test
+pub async fn get_zone_constraints(
+    request: GetZoneConstraintsRequest,
+) -> Result<GetZoneConstraintsResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let query = ConstraintQuery::try_from(request).map_err(PostgisError::Constraint)?;
+    let client = get_client().await?;
+
+    let statement = match query.shape {
+        Shape::Volume { .. } => Statement::ZoneVolumeConstraints,
+        Shape::Path(_) => Statement::ZonePathConstraints,
+    };
+
+    let stmt = client.prepare_cached(&statement.sql()).await.map_err(|e| {
+        postgis_error!("could not prepare cached statement: {}", e);
+        PostgisError::Constraint(ConstraintError::DBError)
+    })?;
+
+    let entrant = &query.entrant;
+    let result = match &query.shape {
+        Shape::Volume {
+            geom,
+            altitude_meters_min,
+            altitude_meters_max,
+        } => {
+            client
+                .query(
+                    &stmt,
+                    &[
+                        geom,
+                        altitude_meters_min,
+                        altitude_meters_max,
+                        &query.time_start,
+                        &query.time_end,
+                        &entrant.aircraft_identifier,
+                        &entrant.flight_identifier,
+                    ],
+                )
+                .await
+        }
+        Shape::Path(geom) => {
+            client
+                .query(
+                    &stmt,
+                    &[
+                        geom,
+                        &query.time_start,
+                        &query.time_end,
+                        &entrant.aircraft_identifier,
+                        &entrant.flight_identifier,
+                    ],
+                )
+                .await
+        }
+    };
+
+    let rows = result.map_err(|e| {
+        postgis_error!("could not query for zone constraints: {}", e);
+        PostgisError::Constraint(ConstraintError::DBError)
+    })?;
+
+    let mut constraints = rows
+        .iter()
+        .map(|row| process_zone_row(row).map(|zone| zone.constraint(&query)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            postgis_error!("could not get zone data: {}", e);
+            PostgisError::Constraint(ConstraintError::DBError)
+        })?;
+
+    sort_constraints(&mut constraints);
+    postgis_debug!("found {} zone constraints.", constraints.len());
+    Ok(GetZoneConstraintsResponse { constraints })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::server::grpc_server::{Coordinates, PointZ as GrpcPointZ};
+    use lib_common::time::Duration;
+
+    fn volume_request() -> GetZoneConstraintsRequest {
+        let vertex = |latitude: f64, longitude: f64| Coordinates {
+            latitude,
+            longitude,
+        };
+
+        GetZoneConstraintsRequest {
+            vertices: vec![
+                vertex(52.374, 4.915),
+                vertex(52.376, 4.916),
+                vertex(52.374, 4.917),
+                vertex(52.374, 4.915),
+            ],
+            altitude_meters_min: 20.0,
+            altitude_meters_max: 120.0,
+            path: vec![],
+            time_start: Some(Utc::now().into()),
+            time_end: Some((Utc::now() + Duration::try_hours(1).unwrap()).into()),
+            aircraft_identifier: None,
+            flight_identifier: None,
+        }
+    }
+
+    fn path() -> Vec<GrpcPointZ> {
+        let point = |latitude: f64, longitude: f64| GrpcPointZ {
+            latitude,
+            longitude,
+            altitude_meters: 80.0,
+        };
+
+        vec![point(52.374, 4.915), point(52.376, 4.917)]
+    }
+
+    fn zone(identifier: &str) -> CrossedZone {
+        CrossedZone {
+            identifier: identifier.to_string(),
+            zone_type: ZoneType::Restriction,
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 500.0,
+            time_start: None,
+            time_end: None,
+            schedule: vec![],
+            authority: None,
+            authorized: false,
+        }
+    }
+
+    #[test]
+    fn ut_query_volume() {
+        let query = ConstraintQuery::try_from(volume_request()).unwrap();
+        let Shape::Volume {
+            geom,
+            altitude_meters_min,
+            altitude_meters_max,
+        } = query.shape
+        else {
+            panic!("expected a volume");
+        };
+
+        assert_eq!(geom.rings[0].points.len(), 4);
+        assert_eq!((altitude_meters_min, altitude_meters_max), (20.0, 120.0));
+        assert!(query.entrant.is_empty());
+
+        let error = ConstraintQuery::try_from(GetZoneConstraintsRequest {
+            altitude_meters_min: 200.0,
+            ..volume_request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ConstraintError::Altitude);
+    }
+
+    #[test]
+    fn ut_query_path() {
+        let query = ConstraintQuery::try_from(GetZoneConstraintsRequest {
+            vertices: vec![],
+            path: path(),
+            ..volume_request()
+        })
+        .unwrap();
+        let Shape::Path(line) = query.shape else {
+            panic!("expected a path");
+        };
+        assert_eq!(line.points.len(), 2);
+        assert_eq!(line.points[0].z, 80.0);
+
+        let mut short = path();
+        short.pop();
+        let error = ConstraintQuery::try_from(GetZoneConstraintsRequest {
+            vertices: vec![],
+            path: short,
+            ..volume_request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ConstraintError::Location);
+    }
+
+    #[test]
+    fn ut_query_invalid() {
+        // both a volume and a path
+        let error = ConstraintQuery::try_from(GetZoneConstraintsRequest {
+            path: path(),
+            ..volume_request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ConstraintError::Location);
+
+        // neither
+        let error = ConstraintQuery::try_from(GetZoneConstraintsRequest {
+            vertices: vec![],
+            ..volume_request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ConstraintError::Location);
+
+        let error = ConstraintQuery::try_from(GetZoneConstraintsRequest {
+            time_start: None,
+            ..volume_request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ConstraintError::Time);
+
+        let mut request = volume_request();
+        std::mem::swap(&mut request.time_start, &mut request.time_end);
+        let error = ConstraintQuery::try_from(request).unwrap_err();
+        assert_eq!(error, ConstraintError::Time);
+
+        let error = ConstraintQuery::try_from(GetZoneConstraintsRequest {
+            aircraft_identifier: Some("".to_string()),
+            ..volume_request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ConstraintError::Identifier);
+    }
+
+    #[test]
+    fn ut_constraint_effect() {
+        let query = ConstraintQuery::try_from(volume_request()).unwrap();
+
+        let constraint = zone("NFZ-1").constraint(&query);
+        assert_eq!(constraint.effect(), ConstraintEffect::ConstraintBlocking);
+        assert_eq!(constraint.zone_type(), ZoneType::Restriction);
+
+        let constraint = CrossedZone {
+            authorized: true,
+            ..zone("NFZ-2")
+        }
+        .constraint(&query);
+        assert_eq!(constraint.effect(), ConstraintEffect::ConstraintAuthorized);
+
+        // a weekly window far from the time window is off schedule, even
+        //  if the entrant is authorized
+        let schedule = (0..7)
+            .flat_map(|day| {
+                let start = day * 24 * 60;
+                let now = query.time_start;
+                let minute = lib_common::time::Datelike::weekday(&now).num_days_from_monday()
+                    as i32
+                    * 24
+                    * 60
+                    + lib_common::time::Timelike::hour(&now) as i32 * 60;
+
+                // a one minute window twelve hours away from now on each day
+                let offset = (minute + 12 * 60) % (24 * 60);
+                [start + offset, start + offset + 1]
+            })
+            .collect();
+
+        let constraint = CrossedZone {
+            schedule,
+            authorized: true,
+            ..zone("NFZ-3")
+        }
+        .constraint(&query);
+        assert_eq!(constraint.effect(), ConstraintEffect::ConstraintUnscheduled);
+    }
+
+    #[test]
+    fn ut_constraint_time_window() {
+        let query = ConstraintQuery::try_from(volume_request()).unwrap();
+
+        // unbounded zones cover the whole time window
+        let constraint = zone("NFZ-1").constraint(&query);
+        assert_eq!(
+            constraint.time_start.map(DateTime::<Utc>::from),
+            Some(query.time_start)
+        );
+        assert_eq!(
+            constraint.time_end.map(DateTime::<Utc>::from),
+            Some(query.time_end)
+        );
+
+        // zones starting late or ending early only cover part of it
+        let time_start = query.time_start + Duration::try_minutes(10).unwrap();
+        let time_end = query.time_start + Duration::try_minutes(20).unwrap();
+        let constraint = CrossedZone {
+            time_start: Some(time_start),
+            time_end: Some(time_end),
+            ..zone("NFZ-2")
+        }
+        .constraint(&query);
+        assert_eq!(
+            constraint.time_start.map(DateTime::<Utc>::from),
+            Some(time_start)
+        );
+        assert_eq!(
+            constraint.time_end.map(DateTime::<Utc>::from),
+            Some(time_end)
+        );
+    }
+
+    #[test]
+    fn ut_sort_constraints() {
+        let query = ConstraintQuery::try_from(volume_request()).unwrap();
+        let later = query.time_start + Duration::try_minutes(30).unwrap();
+
+        let mut constraints = vec![
+            CrossedZone {
+                authorized: true,
+                ..zone("A-AUTHORIZED")
+            }
+            .constraint(&query),
+            CrossedZone {
+                time_start: Some(later),
+                ..zone("B-LATER")
+            }
+            .constraint(&query),
+            zone("C-NOW").constraint(&query),
+            zone("B-NOW").constraint(&query),
+        ];
+
+        sort_constraints(&mut constraints);
+        let identifiers: Vec<&str> = constraints
+            .iter()
+            .map(|c| c.zone_identifier.as_str())
+            .collect();
+        assert_eq!(
+            identifiers,
+            vec!["B-NOW", "C-NOW", "B-LATER", "A-AUTHORIZED"]
+        );
+    }
+
+    #[test]
+    fn test_constraint_error_display() {
+        assert_eq!(
+            ConstraintError::Location.to_string(),
+            "Invalid volume or path provided."
+        );
+        assert_eq!(
+            ConstraintError::Altitude.to_string(),
+            "Invalid altitude range provided."
+        );
+        assert_eq!(ConstraintError::Time.to_string(), "Invalid time provided.");
+        assert_eq!(
+            ConstraintError::Identifier.to_string(),
+            "Invalid identifier provided."
+        );
+        assert_eq!(
+            ConstraintError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(
+            ConstraintError::DBError.to_string(),
+            "Unknown backend error."
+        );
+    }
+}
@@ -0,0 +1,653 @@
+//! Suggests changes that clear the conflicts of a path.
+//!
+//! Candidate changes come from a small grid of departure delays, altitude
+//!  changes and lateral offsets. They are tried from the least to the most
+//!  disruptive, and the first one that clears every zone and flight
+//!  conflict is suggested.
+
+use super::authorization::Entrant;
+use super::best_path::{intersection_checks, PathError};
+use super::utils::{distance_meters, validate_pointz};
+use super::PostgisError;
+use crate::grpc::server::grpc_server::{
+    Deconfliction, FlightPriority, PointZ as GrpcPointZ, SuggestDeconflictionRequest,
+    SuggestDeconflictionResponse,
+};
+use crate::types::AircraftType;
+use deadpool_postgres::Object;
+use geo::{point, GeodesicBearing, GeodesicDestination};
+use lib_common::time::{DateTime, Duration, Utc};
+use num_traits::FromPrimitive;
+use postgis::ewkb::PointZ;
+use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+
+/// Departure delays tried, in minutes
+const DELAYS_MINUTES: [u32; 5] = [5, 10, 15, 30, 60];
+
+/// Altitude changes tried, in meters
+const ALTITUDE_CHANGES_METERS: [f32; 4] = [30.0, -30.0, 60.0, -60.0];
+
+/// Lateral offsets tried, in meters to the right of the direction of travel
+const LATERAL_OFFSETS_METERS: [f32; 4] = [100.0, -100.0, 250.0, -250.0];
+
+/// Deconfliction search time limit
+///  Each candidate costs a round of intersection queries
+const SEARCH_TIME_LIMIT_MS: i64 = 2000;
+
+/// Possible errors with deconfliction requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DeconflictionError {
+    /// Invalid Location
+    Location,
+
+    /// Invalid Time Provided
+    Time,
+
+    /// Invalid flight priority
+    Priority,
+
+    /// Invalid aircraft or flight identifier
+    Entrant,
+
+    /// Invalid aircraft type
+    AircraftType,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for DeconflictionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            DeconflictionError::Location => write!(f, "Invalid location provided."),
+            DeconflictionError::Time => write!(f, "Invalid time provided."),
+            DeconflictionError::Priority => write!(f, "Invalid flight priority provided."),
+            DeconflictionError::Entrant => write!(f, "Invalid aircraft or flight provided."),
+            DeconflictionError::AircraftType => write!(f, "Invalid aircraft type provided."),
+            DeconflictionError::Client => write!(f, "Could not get backend client."),
+            DeconflictionError::DBError => write!(f, "Unknown backend error."),
+        }
+    }
+}
+
+/// A path to deconflict, during a time window
+#[derive(Debug)]
+struct ConflictingPath {
+    origin_identifier: String,
+    target_identifier: String,
+    points: Vec<PointZ>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    include_simulated: bool,
+    priority: FlightPriority,
+    aircraft_type: AircraftType,
+    entrant: Entrant,
+}
+
+impl TryFrom<SuggestDeconflictionRequest> for ConflictingPath {
+    type Error = DeconflictionError;
+
+    fn try_from(request: SuggestDeconflictionRequest) -> Result<Self, Self::Error> {
+        let points: Vec<PointZ> = request.path.into_iter().map(PointZ::from).collect();
+        if points.len() < 2 {
+            postgis_error!("path needs at least two points: {:?}", points);
+            return Err(DeconflictionError::Location);
+        }
+
+        for point in &points {
+            validate_pointz(point).map_err(|e| {
+                postgis_error!("invalid path point {:?}: {}", point, e);
+                DeconflictionError::Location
+            })?;
+        }
+
+        let time_start: DateTime<Utc> = request
+            .time_start
+            .ok_or_else(|| {
+                postgis_error!("time_start is required.");
+                DeconflictionError::Time
+            })?
+            .into();
+
+        let time_end: DateTime<Utc> = request
+            .time_end
+            .ok_or_else(|| {
+                postgis_error!("time_end is required.");
+                DeconflictionError::Time
+            })?
+            .into();
+
+        if time_end < time_start {
+            postgis_error!("time_end ({time_end}) is before time_start ({time_start}).");
+            return Err(DeconflictionError::Time);
+        }
+
+        let priority = FromPrimitive::from_i32(request.priority).ok_or_else(|| {
+            postgis_error!("invalid flight priority: {:?}", request.priority);
+            DeconflictionError::Priority
+        })?;
+
+        let aircraft_type = match request.aircraft_type {
+            None => AircraftType::Undeclared,
+            Some(aircraft_type) => FromPrimitive::from_i32(aircraft_type).ok_or_else(|| {
+                postgis_error!("invalid aircraft type: {aircraft_type}");
+                DeconflictionError::AircraftType
+            })?,
+        };
+
+        let entrant = Entrant::new(request.aircraft_identifier, request.flight_identifier)
+            .map_err(|_| DeconflictionError::Entrant)?;
+
+        Ok(ConflictingPath {
+            origin_identifier: request.origin_identifier,
+            target_identifier: request.target_identifier,
+            points,
+            time_start,
+            time_end,
+            include_simulated: request.include_simulated,
+            priority,
+            aircraft_type,
+            entrant,
+        })
+    }
+}
+
+/// A change to a path, from the parameter grid
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+struct Candidate {
+    delay_minutes: u32,
+    altitude_change_meters: f32,
+    lateral_offset_meters: f32,
+}
+
+impl Candidate {
+    /// How much this change disrupts the flight, each parameter relative
+    ///  to the largest value tried for it
+    fn disruption(&self) -> f32 {
+        self.delay_minutes as f32 / DELAYS_MINUTES[DELAYS_MINUTES.len() - 1] as f32
+            + self.altitude_change_meters.abs() / 60.0
+            + self.lateral_offset_meters.abs() / 250.0
+    }
+}
+
+/// A clear candidate, with its path and time window
+#[derive(Debug)]
+struct Suggestion {
+    candidate: Candidate,
+    points: Vec<PointZ>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+}
+
+/// Lists the parameter grid, least disruptive first
+///  The unchanged path comes first.
+fn candidates() -> Vec<Candidate> {
+    let delays = std::iter::once(0).chain(DELAYS_MINUTES);
+    let mut candidates: Vec<Candidate> = delays
+        .flat_map(|delay_minutes| {
+            std::iter::once(0.0)
+                .chain(ALTITUDE_CHANGES_METERS)
+                .flat_map(move |altitude_change_meters| {
+                    std::iter::once(0.0).chain(LATERAL_OFFSETS_METERS).map(
+                        move |lateral_offset_meters| Candidate {
+                            delay_minutes,
+                            altitude_change_meters,
+                            lateral_offset_meters,
+                        },
+                    )
+                })
+        })
+        .collect();
+
+    // stable, so equally disruptive changes keep the grid order
+    candidates.sort_by(|a, b| a.disruption().total_cmp(&b.disruption()));
+    candidates
+}
+
+/// Moves the en-route nodes of a path, the origin and target stay in place.
+///  A direct path gets a midpoint so that it can be moved at all.
+///
+/// Returns `None` if a node would be moved below the ground.
+fn reshape(points: &[PointZ], candidate: &Candidate) -> Option<Vec<PointZ>> {
+    if candidate.altitude_change_meters == 0.0 && candidate.lateral_offset_meters == 0.0 {
+        return Some(points.to_vec());
+    }
+
+    let first = points.first()?;
+    let last = points.last()?;
+    let mut points = points.to_vec();
+    if points.len() == 2 {
+        let midpoint = PointZ::new(
+            (first.x + last.x) / 2.0,
+            (first.y + last.y) / 2.0,
+            first.z.max(last.z),
+            first.srid,
+        );
+
+        points.insert(1, midpoint);
+    }
+
+    // a negative offset is to the left
+    let course = point!(x: first.x, y: first.y).geodesic_bearing(point!(x: last.x, y: last.y));
+    let bearing = if candidate.lateral_offset_meters < 0.0 {
+        course - 90.0
+    } else {
+        course + 90.0
+    };
+
+    let count = points.len();
+    for point in points[1..count - 1].iter_mut() {
+        if candidate.lateral_offset_meters != 0.0 {
+            let moved = point!(x: point.x, y: point.y)
+                .geodesic_destination(bearing, candidate.lateral_offset_meters.abs() as f64);
+
+            point.x = moved.x();
+            point.y = moved.y();
+        }
+
+        point.z += candidate.altitude_change_meters as f64;
+        if point.z < 0.0 {
+            return None;
+        }
+    }
+
+    Some(points)
+}
+
+/// Tries the candidates in order until one clears all conflicts, or the
+///  time budget runs out. `conflicts` reports if a path conflicts with
+///  anything during a time window.
+async fn search<F, Fut>(
+    path: &ConflictingPath,
+    time_budget: Duration,
+    conflicts: F,
+) -> Result<Option<Suggestion>, PostgisError>
+where
+    F: Fn(Vec<PointZ>, DateTime<Utc>, DateTime<Utc>) -> Fut,
+    Fut: Future<Output = Result<bool, PostgisError>>,
+{
+    let deadline = Utc::now() + time_budget;
+    for candidate in candidates() {
+        let Some(points) = reshape(&path.points, &candidate) else {
+            continue;
+        };
+
+        let delay = Duration::try_minutes(candidate.delay_minutes as i64).ok_or_else(|| {
+            postgis_error!(
+                "could not get delay of {} minutes.",
+                candidate.delay_minutes
+            );
+            PostgisError::BestPath(PathError::Internal)
+        })?;
+
+        let time_start = path.time_start + delay;
+        let time_end = path.time_end + delay;
+        if !conflicts(points.clone(), time_start, time_end).await? {
+            return Ok(Some(Suggestion {
+                candidate,
+                points,
+                time_start,
+                time_end,
+            }));
+        }
+
+        if Utc::now() > deadline {
+            postgis_info!("deconfliction search time limit reached.");
+            break;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Deconfliction(DeconflictionError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Deconfliction(DeconflictionError::Client)
+        })
+}
+
+/// Checks a path against the zones and filed flights in the database
+///  Lower-priority flights don't count as conflicts.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn path_conflicts(
+    client: &Object,
+    path: &ConflictingPath,
+    points: Vec<PointZ>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Result<bool, PostgisError> {
+    let distance = points
+        .windows(2)
+        .fold(0.0, |acc, pair| acc + distance_meters(&pair[0], &pair[1]));
+
+    match intersection_checks(
+        client,
+        points,
+        distance,
+        time_start,
+        time_end,
+        &path.origin_identifier,
+        &path.target_identifier,
+        &path.entrant,
+        path.include_simulated,
+        path.priority,
+        path.aircraft_type,
+    )
+    .await
+    {
+        Ok(_) => Ok(false),
+        Err(PostgisError::BestPath(PathError::ZoneIntersection)) => Ok(true),
+        Err(PostgisError::BestPath(PathError::FlightPlanIntersection)) => Ok(true),
+        Err(PostgisError::BestPath(PathError::ReservationIntersection)) => Ok(true),
+        Err(e) => {
+            postgis_error!("could not check path conflicts: {e}");
+            Err(PostgisError::Deconfliction(DeconflictionError::DBError))
+        }
+    }
+}
+
+/// Suggests the least disruptive change to a path that clears all of its
+///  conflicts. No suggestion is made if the path is already clear, or if
+///  nothing in the parameter grid clears it.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn suggest_deconfliction(
+    request: SuggestDeconflictionRequest,
+) -> Result<SuggestDeconflictionResponse, PostgisError> {
+    let path = ConflictingPath::try_from(request).map_err(PostgisError::Deconfliction)?;
+    let time_budget = Duration::try_milliseconds(SEARCH_TIME_LIMIT_MS).ok_or_else(|| {
+        postgis_error!("could not get time limit for deconfliction search.");
+        PostgisError::Deconfliction(DeconflictionError::DBError)
+    })?;
+
+    let client = get_client().await?;
+    let result = search(&path, time_budget, |points, time_start, time_end| {
+        path_conflicts(&client, &path, points, time_start, time_end)
+    })
+    .await?;
+
+    let Some(suggestion) = result else {
+        postgis_info!("no change in the parameter grid clears the path.");
+        return Ok(SuggestDeconflictionResponse {
+            intersects: true,
+            suggestion: None,
+        });
+    };
+
+    let candidate = suggestion.candidate;
+    if candidate == Candidate::default() {
+        postgis_debug!("path has no conflicts.");
+        return Ok(SuggestDeconflictionResponse {
+            intersects: false,
+            suggestion: None,
+        });
+    }
+
+    postgis_info!("suggesting {:?}", candidate);
+    Ok(SuggestDeconflictionResponse {
+        intersects: true,
+        suggestion: Some(Deconfliction {
+            delay_minutes: candidate.delay_minutes,
+            altitude_change_meters: candidate.altitude_change_meters,
+            lateral_offset_meters: candidate.lateral_offset_meters,
+            path: suggestion
+                .points
+                .into_iter()
+                .map(GrpcPointZ::from)
+                .collect(),
+            time_start: Some(suggestion.time_start.into()),
+            time_end: Some(suggestion.time_end.into()),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgis::DEFAULT_SRID;
+
+    fn request() -> SuggestDeconflictionRequest {
+        let point = |latitude: f64, longitude: f64| GrpcPointZ {
+            latitude,
+            longitude,
+            altitude_meters: 0.0,
+        };
+
+        SuggestDeconflictionRequest {
+            origin_identifier: "vertiport-1".to_string(),
+            target_identifier: "vertiport-2".to_string(),
+            path: vec![point(52.374, 4.915), point(52.384, 4.915)],
+            time_start: Some(Utc::now().into()),
+            time_end: Some((Utc::now() + Duration::try_minutes(20).unwrap()).into()),
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            aircraft_type: None,
+        }
+    }
+
+    fn points() -> Vec<PointZ> {
+        vec![
+            PointZ::new(4.915, 52.374, 0.0, Some(DEFAULT_SRID)),
+            PointZ::new(4.915, 52.384, 0.0, Some(DEFAULT_SRID)),
+        ]
+    }
+
+    #[test]
+    fn ut_conflicting_path_valid() {
+        let path = ConflictingPath::try_from(request()).unwrap();
+        assert_eq!(path.points.len(), 2);
+        assert_eq!(path.priority, FlightPriority::Routine);
+        assert_eq!(path.aircraft_type, AircraftType::Undeclared);
+        assert!(path.time_start < path.time_end);
+    }
+
+    #[test]
+    fn ut_conflicting_path_invalid() {
+        let mut request = request();
+        request.path.pop();
+        let error = ConflictingPath::try_from(request).unwrap_err();
+        assert_eq!(error, DeconflictionError::Location);
+
+        let mut request = self::request();
+        request.path[0].latitude = 91.0;
+        let error = ConflictingPath::try_from(request).unwrap_err();
+        assert_eq!(error, DeconflictionError::Location);
+
+        let mut request = self::request();
+        request.time_start = None;
+        let error = ConflictingPath::try_from(request).unwrap_err();
+        assert_eq!(error, DeconflictionError::Time);
+
+        let mut request = self::request();
+        std::mem::swap(&mut request.time_start, &mut request.time_end);
+        let error = ConflictingPath::try_from(request).unwrap_err();
+        assert_eq!(error, DeconflictionError::Time);
+
+        let mut request = self::request();
+        request.priority = 100;
+        let error = ConflictingPath::try_from(request).unwrap_err();
+        assert_eq!(error, DeconflictionError::Priority);
+
+        let mut request = self::request();
+        request.aircraft_identifier = Some("".to_string());
+        let error = ConflictingPath::try_from(request).unwrap_err();
+        assert_eq!(error, DeconflictionError::Entrant);
+
+        let mut request = self::request();
+        request.aircraft_type = Some(1000);
+        let error = ConflictingPath::try_from(request).unwrap_err();
+        assert_eq!(error, DeconflictionError::AircraftType);
+    }
+
+    #[test]
+    fn ut_candidates() {
+        let candidates = candidates();
+        assert_eq!(candidates.len(), 6 * 5 * 5);
+        assert_eq!(candidates[0], Candidate::default());
+
+        // shortest delay is the least disruptive change
+        assert_eq!(
+            candidates[1],
+            Candidate {
+                delay_minutes: 5,
+                ..Default::default()
+            }
+        );
+
+        for pair in candidates.windows(2) {
+            assert!(pair[0].disruption() <= pair[1].disruption());
+        }
+    }
+
+    #[test]
+    fn ut_reshape() {
+        let points = points();
+
+        // no change
+        let result = reshape(&points, &Candidate::default()).unwrap();
+        assert_eq!(result, points);
+
+        // climb adds a midpoint
+        let candidate = Candidate {
+            altitude_change_meters: 30.0,
+            ..Default::default()
+        };
+        let result = reshape(&points, &candidate).unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0], points[0]);
+        assert_eq!(result[2], points[1]);
+        assert_eq!(result[1].z, 30.0);
+
+        // heading north, so right is east
+        let candidate = Candidate {
+            lateral_offset_meters: 100.0,
+            ..Default::default()
+        };
+        let result = reshape(&points, &candidate).unwrap();
+        let midpoint = PointZ::new(4.915, 52.379, 0.0, Some(DEFAULT_SRID));
+        assert!(result[1].x > midpoint.x);
+        assert!((distance_meters(&result[1], &midpoint) - 100.0).abs() < 1.0);
+
+        let candidate = Candidate {
+            lateral_offset_meters: -100.0,
+            ..Default::default()
+        };
+        let result = reshape(&points, &candidate).unwrap();
+        assert!(result[1].x < midpoint.x);
+
+        // below the ground
+        let candidate = Candidate {
+            altitude_change_meters: -30.0,
+            ..Default::default()
+        };
+        assert!(reshape(&points, &candidate).is_none());
+    }
+
+    #[tokio::test]
+    async fn ut_search() {
+        let path = ConflictingPath::try_from(request()).unwrap();
+        let budget = Duration::try_seconds(10).unwrap();
+
+        // already clear
+        let result = search(&path, budget, |_, _, _| async { Ok(false) })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.candidate, Candidate::default());
+
+        // cleared by a delay
+        let cleared = path.time_start + Duration::try_minutes(15).unwrap();
+        let result = search(&path, budget, |_, time_start, _| async move {
+            Ok(time_start < cleared)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(result.candidate.delay_minutes, 15);
+        assert_eq!(result.points, path.points);
+        assert_eq!(result.time_start, cleared);
+        assert_eq!(
+            result.time_end - result.time_start,
+            path.time_end - path.time_start
+        );
+
+        // cleared by climbing
+        let result = search(&path, budget, |points, _, _| async move {
+            Ok(points.iter().all(|p| p.z < 50.0))
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(result.candidate.altitude_change_meters, 60.0);
+        assert_eq!(result.candidate.delay_minutes, 0);
+        assert_eq!(result.points[1].z, 60.0);
+
+        // never cleared
+        let result = search(&path, budget, |_, _, _| async { Ok(true) })
+            .await
+            .unwrap();
+        assert!(result.is_none());
+
+        // errors are passed on
+        let error = search(&path, budget, |_, _, _| async {
+            Err(PostgisError::Deconfliction(DeconflictionError::DBError))
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::Deconfliction(DeconflictionError::DBError)
+        );
+    }
+
+    #[test]
+    fn test_deconfliction_error_display() {
+        assert_eq!(
+            format!("{}", DeconflictionError::Location),
+            "Invalid location provided."
+        );
+        assert_eq!(
+            format!("{}", DeconflictionError::Time),
+            "Invalid time provided."
+        );
+        assert_eq!(
+            format!("{}", DeconflictionError::Priority),
+            "Invalid flight priority provided."
+        );
+        assert_eq!(
+            format!("{}", DeconflictionError::Entrant),
+            "Invalid aircraft or flight provided."
+        );
+        assert_eq!(
+            format!("{}", DeconflictionError::AircraftType),
+            "Invalid aircraft type provided."
+        );
+        assert_eq!(
+            format!("{}", DeconflictionError::Client),
+            "Could not get backend client."
+        );
+        assert_eq!(
+            format!("{}", DeconflictionError::DBError),
+            "Unknown backend error."
+        );
+    }
+}
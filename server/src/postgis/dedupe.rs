@@ -0,0 +1,321 @@
+//! Suppression of duplicate aircraft reports.
+//!
+//! Parked aircraft keep reporting the same position and velocity every
+//!  second. A report within the configured epsilon of the stored state of
+//!  its aircraft only advances the timestamp, so its upsert is skipped.
+//!  The stored state is still rewritten once per heartbeat to keep the
+//!  aircraft from being reported as stale.
+
+use super::aircraft::{get_quality_thresholds, AircraftError, QualityThresholds};
+use super::PostgisError;
+use crate::reload::Tunable;
+use crate::types::Position;
+use lib_common::time::{DateTime, Duration, Utc};
+use postgis::ewkb::PointZ;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default seconds between writes of an unchanged aircraft state
+pub const DEFAULT_HEARTBEAT_SECONDS: u32 = 5;
+
+/// Duplicate suppression settings
+static SETTINGS: Tunable<Settings> = Tunable::new();
+
+/// Number of reports whose upsert was skipped
+static SUPPRESSED: AtomicU64 = AtomicU64::new(0);
+
+/// The velocity of an aircraft
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Velocity {
+    /// Horizontal speed relative to the ground
+    pub horizontal_ground_mps: f64,
+
+    /// Vertical speed
+    pub vertical_mps: f64,
+
+    /// Angle of the horizontal velocity with respect to true north
+    pub track_angle_degrees: f64,
+}
+
+impl Velocity {
+    /// Magnitude of the velocity
+    pub fn speed_mps(&self) -> f64 {
+        self.horizontal_ground_mps.hypot(self.vertical_mps)
+    }
+
+    /// Magnitude of the difference between two velocities. The track angle
+    ///  of a hovering or parked aircraft is noise, so comparing the vectors
+    ///  ignores it at low speeds.
+    pub fn difference_mps(&self, other: &Velocity) -> f64 {
+        let (east, north) = self.horizontal_components();
+        let (other_east, other_north) = other.horizontal_components();
+        let vertical = self.vertical_mps - other.vertical_mps;
+
+        ((east - other_east).powi(2) + (north - other_north).powi(2) + vertical.powi(2)).sqrt()
+    }
+
+    /// East and north components of the horizontal velocity
+    fn horizontal_components(&self) -> (f64, f64) {
+        let (sin, cos) = self.track_angle_degrees.to_radians().sin_cos();
+        (
+            self.horizontal_ground_mps * sin,
+            self.horizontal_ground_mps * cos,
+        )
+    }
+}
+
+/// Duplicate suppression settings
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Settings {
+    /// Positions within this distance of the stored position are
+    ///  duplicates, zero to disable
+    pub position_epsilon_meters: f32,
+
+    /// Velocities within this difference of the stored velocity are
+    ///  duplicates, zero to disable
+    pub velocity_epsilon_mps: f32,
+
+    /// Time between writes of an unchanged aircraft state
+    pub heartbeat_seconds: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            position_epsilon_meters: 0.0,
+            velocity_epsilon_mps: 0.0,
+            heartbeat_seconds: DEFAULT_HEARTBEAT_SECONDS,
+        }
+    }
+}
+
+impl Settings {
+    /// Whether a report written at `stored` can be repeated by the
+    ///  report at `next` without a write
+    fn within_heartbeat(&self, stored: DateTime<Utc>, next: DateTime<Utc>) -> bool {
+        let gap = next - stored;
+        gap >= Duration::zero() && gap.num_seconds() < self.heartbeat_seconds as i64
+    }
+
+    /// Whether positions are compared at all
+    pub fn positions_enabled(&self) -> bool {
+        self.position_epsilon_meters > 0.0
+    }
+
+    /// Whether velocities are compared at all
+    pub fn velocities_enabled(&self) -> bool {
+        self.velocity_epsilon_mps > 0.0
+    }
+
+    /// Whether a position only advances the timestamp of the stored one
+    pub fn is_duplicate_position(
+        &self,
+        stored: (&Position, DateTime<Utc>),
+        next: (&Position, DateTime<Utc>),
+    ) -> bool {
+        if !self.positions_enabled() || !self.within_heartbeat(stored.1, next.1) {
+            return false;
+        }
+
+        let distance =
+            super::utils::distance_meters(&PointZ::from(*stored.0), &PointZ::from(*next.0));
+        distance <= self.position_epsilon_meters
+    }
+
+    /// Whether a velocity only advances the timestamp of the stored one
+    pub fn is_duplicate_velocity(
+        &self,
+        stored: (&Velocity, DateTime<Utc>),
+        next: (&Velocity, DateTime<Utc>),
+    ) -> bool {
+        if !self.velocities_enabled() || !self.within_heartbeat(stored.1, next.1) {
+            return false;
+        }
+
+        stored.0.difference_mps(next.0) <= self.velocity_epsilon_mps as f64
+    }
+}
+
+/// Checks the duplicate suppression settings against the position quality
+///  thresholds they will be used with
+pub fn check_settings(
+    settings: &Settings,
+    thresholds: &QualityThresholds,
+) -> Result<(), PostgisError> {
+    let epsilons = [
+        settings.position_epsilon_meters,
+        settings.velocity_epsilon_mps,
+    ];
+
+    if epsilons
+        .iter()
+        .any(|epsilon| !epsilon.is_finite() || *epsilon < 0.0)
+    {
+        postgis_error!(
+            "duplicate epsilons must be positive, or zero to disable: {:?}",
+            settings
+        );
+        return Err(PostgisError::Aircraft(AircraftError::Dedupe));
+    }
+
+    // Suppressed aircraft must not be reported stale between heartbeats
+    let stale_seconds = thresholds.stale_seconds;
+    if settings.heartbeat_seconds == 0 || settings.heartbeat_seconds > stale_seconds {
+        postgis_error!(
+            "heartbeat ({}s) must be between 1s and the stale threshold ({}s).",
+            settings.heartbeat_seconds,
+            stale_seconds
+        );
+        return Err(PostgisError::Aircraft(AircraftError::Dedupe));
+    }
+
+    Ok(())
+}
+
+/// Sets the duplicate suppression settings, replacing any set before
+///  Must be called after the position quality thresholds are set
+pub fn set_settings(settings: Settings) -> Result<(), PostgisError> {
+    check_settings(&settings, &get_quality_thresholds())?;
+    SETTINGS.set(settings);
+    Ok(())
+}
+
+/// Gets the duplicate suppression settings
+pub fn get_settings() -> Settings {
+    SETTINGS.get().unwrap_or_default()
+}
+
+/// Counts the reports whose upsert was skipped
+pub fn record_suppressed(count: u64) {
+    if count > 0 {
+        let total = SUPPRESSED.fetch_add(count, Ordering::Relaxed) + count;
+        postgis_debug!("suppressed {count} duplicate report(s), {total} in total.");
+    }
+}
+
+/// Gets the number of reports whose upsert was skipped since startup
+pub fn suppressed() -> u64 {
+    SUPPRESSED.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            position_epsilon_meters: 1.0,
+            velocity_epsilon_mps: 0.5,
+            heartbeat_seconds: 5,
+        }
+    }
+
+    fn velocity(horizontal_ground_mps: f64, track_angle_degrees: f64) -> Velocity {
+        Velocity {
+            horizontal_ground_mps,
+            vertical_mps: 0.0,
+            track_angle_degrees,
+        }
+    }
+
+    #[test]
+    fn ut_velocity() {
+        let velocity = Velocity {
+            horizontal_ground_mps: 3.0,
+            vertical_mps: -4.0,
+            track_angle_degrees: 90.0,
+        };
+        assert_eq!(velocity.speed_mps(), 5.0);
+
+        // the track of a parked aircraft doesn't matter
+        let parked = self::velocity(0.1, 0.0);
+        assert!(parked.difference_mps(&self::velocity(0.1, 180.0)) <= 0.2 + f64::EPSILON);
+
+        // the track of a moving aircraft does
+        let moving = self::velocity(10.0, 0.0);
+        assert!((moving.difference_mps(&self::velocity(10.0, 180.0)) - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ut_is_duplicate_position() {
+        let settings = settings();
+        let now = Utc::now();
+        let later = now + Duration::try_seconds(4).unwrap();
+        let heartbeat = now + Duration::try_seconds(5).unwrap();
+
+        let stored = Position {
+            longitude: 4.9.into(),
+            latitude: 52.3.into(),
+            altitude_meters: 0.0.into(),
+        };
+
+        // ~0.5 meters north
+        let jitter = Position {
+            latitude: 52.300_004_5.into(),
+            ..stored
+        };
+
+        // ~11 meters north
+        let moved = Position {
+            latitude: 52.3001.into(),
+            ..stored
+        };
+
+        assert!(settings.is_duplicate_position((&stored, now), (&jitter, later)));
+        assert!(!settings.is_duplicate_position((&stored, now), (&moved, later)));
+        assert!(!settings.is_duplicate_position((&stored, now), (&jitter, heartbeat)));
+
+        // out of order reports are written as before
+        assert!(!settings.is_duplicate_position((&stored, later), (&jitter, now)));
+        assert!(!Settings::default().is_duplicate_position((&stored, now), (&stored, later)));
+    }
+
+    #[test]
+    fn ut_is_duplicate_velocity() {
+        let settings = settings();
+        let now = Utc::now();
+        let later = now + Duration::try_seconds(1).unwrap();
+
+        let stored = velocity(0.0, 0.0);
+        assert!(settings.is_duplicate_velocity((&stored, now), (&velocity(0.3, 270.0), later)));
+        assert!(!settings.is_duplicate_velocity((&stored, now), (&velocity(1.0, 270.0), later)));
+        assert!(!Settings::default().is_duplicate_velocity((&stored, now), (&stored, later)));
+    }
+
+    #[test]
+    fn ut_set_settings_invalid() {
+        let invalid = Settings {
+            position_epsilon_meters: -1.0,
+            ..settings()
+        };
+        assert_eq!(
+            set_settings(invalid),
+            Err(PostgisError::Aircraft(AircraftError::Dedupe))
+        );
+
+        let invalid = Settings {
+            heartbeat_seconds: 0,
+            ..settings()
+        };
+        assert_eq!(
+            set_settings(invalid),
+            Err(PostgisError::Aircraft(AircraftError::Dedupe))
+        );
+
+        // would be reported stale before the next heartbeat
+        let invalid = Settings {
+            heartbeat_seconds: get_quality_thresholds().stale_seconds + 1,
+            ..settings()
+        };
+        assert_eq!(
+            set_settings(invalid),
+            Err(PostgisError::Aircraft(AircraftError::Dedupe))
+        );
+    }
+
+    #[test]
+    fn ut_record_suppressed() {
+        let before = suppressed();
+        record_suppressed(3);
+        assert!(suppressed() >= before + 3);
+    }
+}
@@ -0,0 +1,425 @@
+//! Bulk export of flights, run with `export-flights`.
+//!
+//! Writes the flights overlapping a time range to an Arrow IPC or Parquet
+//!  file, one row per flight with the path as WKB. Flights are read from the
+//!  database as a stream and written in record batches, so the export doesn't
+//!  hold the whole range in memory. Archived flights can be included.
+
+use super::statements::Statement;
+use crate::types::AircraftType;
+use arrow::array::{ArrayRef, BinaryBuilder, BooleanBuilder, Int32Builder, StringBuilder};
+use arrow::array::{RecordBatch, TimestampMicrosecondBuilder};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::ipc::writer::FileWriter;
+use futures::{pin_mut, TryStreamExt};
+use lib_common::time::{DateTime, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Number of flights per record batch
+const BATCH_SIZE: usize = 10_000;
+
+/// Extension type marking a binary column as WKB geometries
+const GEOARROW_WKB: &str = "geoarrow.wkb";
+
+/// Possible failures of an export
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExportError {
+    /// The time range ends before it starts
+    TimeRange,
+
+    /// Could not get a database client
+    Client,
+
+    /// Could not query the flights
+    DBError,
+
+    /// Could not create or write the output file
+    File,
+
+    /// Could not encode the flights
+    Encode,
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ExportError::TimeRange => write!(f, "The time range ends before it starts."),
+            ExportError::Client => write!(f, "Could not get backend client."),
+            ExportError::DBError => write!(f, "Database error."),
+            ExportError::File => write!(f, "Could not write the output file."),
+            ExportError::Encode => write!(f, "Could not encode flights."),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// File formats of an export
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Arrow IPC file
+    Arrow,
+
+    /// Parquet file, compressed with Snappy
+    Parquet,
+}
+
+/// A flight as written to the export
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlightRecord {
+    /// Unique identifier of the flight
+    pub flight_identifier: String,
+
+    /// Identifier of the aircraft flying it
+    pub aircraft_identifier: String,
+
+    /// The type of aircraft
+    pub aircraft_type: AircraftType,
+
+    /// If this is a simulated flight
+    pub simulated: bool,
+
+    /// Right-of-way, as a `FlightPriority` value
+    pub priority: i32,
+
+    /// Scheduled start of the flight
+    pub time_start: Option<DateTime<Utc>>,
+
+    /// Scheduled end of the flight
+    pub time_end: Option<DateTime<Utc>>,
+
+    /// The path as WKB, with altitudes
+    pub path: Option<Vec<u8>>,
+
+    /// If the flight was read from the archive table
+    pub archived: bool,
+}
+
+/// Schema of the exported flights
+pub fn schema() -> SchemaRef {
+    let timestamp = DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+    let path = Field::new("path", DataType::Binary, true).with_metadata(HashMap::from([(
+        "ARROW:extension:name".to_string(),
+        GEOARROW_WKB.to_string(),
+    )]));
+
+    Arc::new(Schema::new(vec![
+        Field::new("flight_identifier", DataType::Utf8, false),
+        Field::new("aircraft_identifier", DataType::Utf8, false),
+        Field::new("aircraft_type", DataType::Utf8, false),
+        Field::new("simulated", DataType::Boolean, false),
+        Field::new("priority", DataType::Int32, false),
+        Field::new("time_start", timestamp.clone(), true),
+        Field::new("time_end", timestamp, true),
+        path,
+        Field::new("archived", DataType::Boolean, false),
+    ]))
+}
+
+/// Converts flights into a record batch of the export schema
+pub fn record_batch(flights: &[FlightRecord]) -> Result<RecordBatch, ExportError> {
+    let mut flight_identifier = StringBuilder::new();
+    let mut aircraft_identifier = StringBuilder::new();
+    let mut aircraft_type = StringBuilder::new();
+    let mut simulated = BooleanBuilder::new();
+    let mut priority = Int32Builder::new();
+    let mut time_start = TimestampMicrosecondBuilder::new().with_timezone("UTC");
+    let mut time_end = TimestampMicrosecondBuilder::new().with_timezone("UTC");
+    let mut path = BinaryBuilder::new();
+    let mut archived = BooleanBuilder::new();
+
+    for flight in flights {
+        flight_identifier.append_value(&flight.flight_identifier);
+        aircraft_identifier.append_value(&flight.aircraft_identifier);
+        aircraft_type.append_value(flight.aircraft_type.to_string());
+        simulated.append_value(flight.simulated);
+        priority.append_value(flight.priority);
+        time_start.append_option(flight.time_start.map(|t| t.timestamp_micros()));
+        time_end.append_option(flight.time_end.map(|t| t.timestamp_micros()));
+        path.append_option(flight.path.as_deref());
+        archived.append_value(flight.archived);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(flight_identifier.finish()),
+        Arc::new(aircraft_identifier.finish()),
+        Arc::new(aircraft_type.finish()),
+        Arc::new(simulated.finish()),
+        Arc::new(priority.finish()),
+        Arc::new(time_start.finish()),
+        Arc::new(time_end.finish()),
+        Arc::new(path.finish()),
+        Arc::new(archived.finish()),
+    ];
+
+    RecordBatch::try_new(schema(), columns).map_err(|e| {
+        postgis_error!("could not build record batch: {}", e);
+        ExportError::Encode
+    })
+}
+
+/// Writes record batches to a file in one of the export formats
+enum Writer {
+    Arrow(FileWriter<File>),
+    Parquet(ArrowWriter<File>),
+}
+
+impl Writer {
+    /// Creates the output file and writes the header of the format
+    fn create(path: &Path, format: Format) -> Result<Self, ExportError> {
+        let file = File::create(path).map_err(|e| {
+            postgis_error!("could not create {}: {}", path.display(), e);
+            ExportError::File
+        })?;
+
+        let writer = match format {
+            Format::Arrow => FileWriter::try_new(file, &schema()).map(Writer::Arrow),
+            Format::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+
+                ArrowWriter::try_new(file, schema(), Some(properties))
+                    .map(Writer::Parquet)
+                    .map_err(Into::into)
+            }
+        };
+
+        writer.map_err(|e| {
+            postgis_error!("could not start {:?} file: {}", format, e);
+            ExportError::File
+        })
+    }
+
+    /// Appends a record batch
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), ExportError> {
+        let result = match self {
+            Writer::Arrow(writer) => writer.write(batch),
+            Writer::Parquet(writer) => writer.write(batch).map_err(Into::into),
+        };
+
+        result.map_err(|e| {
+            postgis_error!("could not write record batch: {}", e);
+            ExportError::File
+        })
+    }
+
+    /// Writes the footer of the format
+    fn finish(self) -> Result<(), ExportError> {
+        let result = match self {
+            Writer::Arrow(mut writer) => writer.finish(),
+            Writer::Parquet(writer) => writer.close().map(|_| ()).map_err(Into::into),
+        };
+
+        result.map_err(|e| {
+            postgis_error!("could not finish file: {}", e);
+            ExportError::File
+        })
+    }
+}
+
+/// Converts a row of the export statement
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_row(row: tokio_postgres::Row) -> Result<FlightRecord, tokio_postgres::Error> {
+    Ok(FlightRecord {
+        flight_identifier: row.try_get("flight_identifier")?,
+        aircraft_identifier: row.try_get("aircraft_identifier")?,
+        aircraft_type: row.try_get("aircraft_type")?,
+        simulated: row.try_get("simulated")?,
+        priority: row.try_get("priority")?,
+        time_start: row.try_get("time_start")?,
+        time_end: row.try_get("time_end")?,
+        path: row.try_get("path")?,
+        archived: row.try_get("archived")?,
+    })
+}
+
+/// Writes the flights overlapping a time range to a file.
+///  Returns the number of flights written.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+pub async fn export_flights(
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    include_archived: bool,
+    path: &Path,
+    format: Format,
+) -> Result<usize, ExportError> {
+    if time_end < time_start {
+        postgis_error!("time range ends before it starts.");
+        return Err(ExportError::TimeRange);
+    }
+
+    let client = crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            ExportError::Client
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            ExportError::Client
+        })?;
+
+    let stmt = client
+        .prepare_cached(&Statement::ExportFlights.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            ExportError::DBError
+        })?;
+
+    let params: [&(dyn tokio_postgres::types::ToSql + Sync); 3] =
+        [&time_start, &time_end, &include_archived];
+    let rows = client.query_raw(&stmt, params).await.map_err(|e| {
+        postgis_error!("could not query flights: {}", e);
+        ExportError::DBError
+    })?;
+    pin_mut!(rows);
+
+    let mut writer = Writer::create(path, format)?;
+    let mut flights = Vec::with_capacity(BATCH_SIZE);
+    let mut count = 0;
+    while let Some(row) = rows.try_next().await.map_err(|e| {
+        postgis_error!("could not read flights: {}", e);
+        ExportError::DBError
+    })? {
+        flights.push(process_row(row).map_err(|e| {
+            postgis_error!("could not get flight data: {}", e);
+            ExportError::DBError
+        })?);
+
+        if flights.len() == BATCH_SIZE {
+            writer.write(&record_batch(&flights)?)?;
+            count += flights.len();
+            flights.clear();
+        }
+    }
+
+    if !flights.is_empty() {
+        writer.write(&record_batch(&flights)?)?;
+        count += flights.len();
+    }
+
+    writer.finish()?;
+    postgis_info!("exported {} flight(s) to {}.", count, path.display());
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::ipc::reader::FileReader;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn flights() -> Vec<FlightRecord> {
+        let now = Utc::now();
+        vec![
+            FlightRecord {
+                flight_identifier: "FLIGHT-1".to_string(),
+                aircraft_identifier: "AIRCRAFT-1".to_string(),
+                aircraft_type: AircraftType::Rotorcraft,
+                simulated: false,
+                priority: 0,
+                time_start: Some(now),
+                time_end: Some(now),
+                path: Some(vec![1, 2, 3]),
+                archived: false,
+            },
+            FlightRecord {
+                flight_identifier: "FLIGHT-2".to_string(),
+                aircraft_identifier: "AIRCRAFT-2".to_string(),
+                aircraft_type: AircraftType::Aeroplane,
+                simulated: true,
+                priority: 2,
+                time_start: None,
+                time_end: None,
+                path: None,
+                archived: true,
+            },
+        ]
+    }
+
+    /// Writes the test flights to a temporary file
+    fn write_file(format: Format) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "svc_gis_export_{}_{:?}",
+            std::process::id(),
+            format
+        ));
+
+        let mut writer = Writer::create(&path, format).unwrap();
+        writer.write(&record_batch(&flights()).unwrap()).unwrap();
+        writer.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn ut_export_error_display() {
+        let error = ExportError::TimeRange;
+        assert_eq!(error.to_string(), "The time range ends before it starts.");
+
+        let error = ExportError::Client;
+        assert_eq!(error.to_string(), "Could not get backend client.");
+
+        let error = ExportError::DBError;
+        assert_eq!(error.to_string(), "Database error.");
+
+        let error = ExportError::File;
+        assert_eq!(error.to_string(), "Could not write the output file.");
+
+        let error = ExportError::Encode;
+        assert_eq!(error.to_string(), "Could not encode flights.");
+    }
+
+    #[test]
+    fn ut_record_batch() {
+        let batch = record_batch(&flights()).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), schema());
+
+        let path = batch.column_by_name("path").unwrap();
+        assert_eq!(path.null_count(), 1);
+
+        let field = schema().field_with_name("path").unwrap().clone();
+        assert_eq!(
+            field.metadata().get("ARROW:extension:name"),
+            Some(&GEOARROW_WKB.to_string())
+        );
+
+        assert_eq!(record_batch(&[]).unwrap().num_rows(), 0);
+    }
+
+    #[test]
+    fn ut_write_arrow() {
+        let path = write_file(Format::Arrow);
+        let reader = FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(rows, 2);
+    }
+
+    #[test]
+    fn ut_write_parquet() {
+        let path = write_file(Format::Parquet);
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(rows, 2);
+    }
+}
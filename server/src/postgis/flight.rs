@@ -1,23 +1,28 @@
 //! This module contains functions for updating aircraft flight paths in the PostGIS database.
 
-use super::{psql_transaction, PostgisError, DEFAULT_SRID, PSQL_SCHEMA};
+use super::identifier::{self, Entity};
+use super::statements::{Ordering, Statement};
+use super::{aircraft, psql_transaction, OnceCell, PostgisError, DEFAULT_SRID};
 use crate::grpc::server::grpc_server::{
-    AircraftState, Flight, GetFlightsRequest, PointZ as GrpcPointZ, TimePosition,
-    UpdateFlightPathRequest,
+    AircraftState, Coordinates, DataQuality, FieldViolation, Flight, FlightPriority,
+    GetFlightsRequest, GetFlightsResponse, OrderBy, PointZ as GrpcPointZ, TimePosition,
+    UpdateFlightPathRequest, ZoneProximity, ZoneType,
 };
+use crate::postgis::routing::SeparationMargins;
+use crate::postgis::utils::Pagination;
 use crate::postgis::utils::Segment;
-use crate::postgis::utils::StringError;
+use crate::postgis::utils::{field_violation, GeometryError, InvalidVertex, PolygonError};
+use crate::types::AircraftPosition;
 use crate::types::AircraftType;
 use crate::types::OperationalStatus;
 use deadpool_postgres::Object;
+use futures::future::join_all;
 use lib_common::time::{DateTime, Utc};
 use num_traits::FromPrimitive;
-use postgis::ewkb::{LineStringT, Point, PointZ};
+use postgis::ewkb::{LineStringT, PointZ, PolygonZ};
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
 
-/// Allowed characters in a identifier
-pub const FLIGHT_IDENTIFIER_REGEX: &str = r"^[\-0-9A-Za-z_\.]{1,255}$";
-
 /// Max length of each flight segment in meters
 pub const MAX_FLIGHT_SEGMENT_LENGTH_METERS: f32 = 40.0;
 
@@ -50,6 +55,18 @@ pub enum FlightError {
 
     /// Intersection of flight segments
     Intersection,
+
+    /// Invalid Altitude Range
+    Altitude,
+
+    /// Invalid pagination options
+    Pagination,
+
+    /// Invalid zone proximity distance
+    ZoneProximity,
+
+    /// Invalid flight priority
+    Priority,
 }
 
 impl Display for FlightError {
@@ -64,19 +81,24 @@ impl Display for FlightError {
             FlightError::DBError => write!(f, "Unknown backend error."),
             FlightError::Segments => write!(f, "Could not segmentize path."),
             FlightError::Intersection => write!(f, "Flight paths intersect."),
+            FlightError::Altitude => write!(f, "Invalid altitude range provided."),
+            FlightError::Pagination => write!(f, "Invalid pagination provided."),
+            FlightError::ZoneProximity => write!(f, "Invalid zone proximity provided."),
+            FlightError::Priority => write!(f, "Invalid flight priority provided."),
         }
     }
 }
 
 /// Gets the name of the flights table
-fn get_flights_table_name() -> &'static str {
-    static FULL_NAME: &str = const_format::formatcp!(r#""{PSQL_SCHEMA}"."flights""#,);
-    FULL_NAME
+pub(super) fn get_flights_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."flights""#, super::get_schema()))
 }
 
-/// Verifies that a identifier is valid
-pub fn check_flight_identifier(identifier: &str) -> Result<(), StringError> {
-    super::utils::check_string(identifier, FLIGHT_IDENTIFIER_REGEX)
+/// Gets the name of the table holding the flights removed by the retention policy
+pub(super) fn get_archive_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."flights_archive""#, super::get_schema()))
 }
 
 /// Initializes the PostGIS database for aircraft.
@@ -85,12 +107,21 @@ pub fn check_flight_identifier(identifier: &str) -> Result<(), StringError> {
 pub async fn psql_init() -> Result<(), PostgisError> {
     // Create Aircraft Table
     let enum_name = "aircrafttype";
-    let statements = vec![
+    let max_length = aircraft::get_identifier_max_length();
+    let identifier_columns = ["flight_identifier", "aircraft_identifier"];
+
+    // Tables created by earlier versions used VARCHAR(20)
+    let widen = [
+        super::psql_widen_varchar("flights", &identifier_columns, max_length).await?,
+        super::psql_widen_varchar("flights_archive", &identifier_columns, max_length).await?,
+    ];
+
+    let mut statements = vec![
         // super::psql_enum_declaration::<AircraftType>(enum_name), // should already exist
         format!(
             r#"CREATE TABLE IF NOT EXISTS {table_name} (
-                "flight_identifier" VARCHAR(20) UNIQUE PRIMARY KEY NOT NULL,
-                "aircraft_identifier" VARCHAR(20) NOT NULL,
+                "flight_identifier" VARCHAR({max_length}) UNIQUE PRIMARY KEY NOT NULL,
+                "aircraft_identifier" VARCHAR({max_length}) NOT NULL,
                 "aircraft_type" {enum_name} NOT NULL DEFAULT '{aircraft_type}',
                 "simulated" BOOLEAN NOT NULL DEFAULT FALSE,
                 "geom" GEOMETRY(LINESTRINGZ, {DEFAULT_SRID}), -- full path
@@ -101,19 +132,116 @@ pub async fn psql_init() -> Result<(), PostgisError> {
             table_name = get_flights_table_name(),
             aircraft_type = AircraftType::Undeclared.to_string()
         ),
+        // The path in ECEF coordinates (meters) and its length, kept up to
+        //  date on insert so that intersection checks don't transform every row
+        format!(
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "geom_ecef" GEOMETRY(LINESTRINGZ, 4978)
+                GENERATED ALWAYS AS (ST_Transform("geom", 4978)) STORED,
+            ADD COLUMN IF NOT EXISTS "length_meters" FLOAT8
+                GENERATED ALWAYS AS (ST_3DLength(ST_Transform("geom", 4978))) STORED;"#,
+            table_name = get_flights_table_name()
+        ),
+        // Right-of-way, as a FlightPriority value
         format!(
-            r#"CREATE INDEX IF NOT EXISTS "flights_geom_idx" ON {table_name} USING GIST (ST_Transform("geom", 4978));"#,
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "priority" INTEGER NOT NULL DEFAULT 0;"#,
+            table_name = get_flights_table_name()
+        ),
+        // Number of points of the submitted path, before simplification
+        format!(
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "original_point_count" INTEGER;"#,
+            table_name = get_flights_table_name()
+        ),
+        // Superseded by the index on "geom_ecef"
+        format!(
+            r#"DROP INDEX IF EXISTS "{schema}"."flights_geom_idx";"#,
+            schema = super::get_schema()
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "flights_geom_ecef_idx" ON {table_name} USING GIST ("geom_ecef" gist_geometry_ops_nd);"#,
             table_name = get_flights_table_name()
         ),
         format!(
             r#"CREATE INDEX IF NOT EXISTS "flights_isa_idx" ON {table_name} USING GIST ("isa");"#,
             table_name = get_flights_table_name()
         ),
+        // Finds the flights past their retention period
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "flights_time_end_idx" ON {table_name} ("time_end");"#,
+            table_name = get_flights_table_name()
+        ),
+        // Cold storage, without the indexes and derived columns of the live table
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {archive_table_name} (
+                "flight_identifier" VARCHAR({max_length}) NOT NULL,
+                "aircraft_identifier" VARCHAR({max_length}) NOT NULL,
+                "aircraft_type" {enum_name} NOT NULL,
+                "simulated" BOOLEAN NOT NULL,
+                "geom" GEOMETRY(LINESTRINGZ, {DEFAULT_SRID}),
+                "isa" GEOMETRY NOT NULL,
+                "time_start" TIMESTAMPTZ,
+                "time_end" TIMESTAMPTZ,
+                "archived_at" TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            );"#,
+            archive_table_name = get_archive_table_name(),
+        ),
+        format!(
+            r#"ALTER TABLE {archive_table_name}
+            ADD COLUMN IF NOT EXISTS "priority" INTEGER NOT NULL DEFAULT 0,
+            ADD COLUMN IF NOT EXISTS "original_point_count" INTEGER;"#,
+            archive_table_name = get_archive_table_name(),
+        ),
     ];
 
+    statements.extend(widen.into_iter().flatten());
     psql_transaction(statements).await
 }
 
+/// Removes up to `limit` flights that ended before the cutoff, copying
+///  them to the archive table first if `archive` is set. Returns the number
+///  of flights removed.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn purge_flights(
+    cutoff: DateTime<Utc>,
+    limit: i64,
+    archive: bool,
+) -> Result<u64, PostgisError> {
+    let client = crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::FlightPath(FlightError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::FlightPath(FlightError::Client)
+        })?;
+
+    let statement = if archive {
+        Statement::ArchiveFlights
+    } else {
+        Statement::DeleteFlights
+    };
+
+    let stmt = client.prepare_cached(&statement.sql()).await.map_err(|e| {
+        postgis_error!("could not prepare cached statement: {}", e);
+        PostgisError::FlightPath(FlightError::DBError)
+    })?;
+
+    client
+        .execute(&stmt, &[&cutoff, &limit])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not purge flights: {}", e);
+            PostgisError::FlightPath(FlightError::DBError)
+        })
+}
+
 /// Validates the provided aircraft identification.
 fn validate_flight_identifier(id: &Option<String>) -> Result<(), PostgisError> {
     let identifier = id.as_ref().ok_or_else(|| {
@@ -121,7 +249,7 @@ fn validate_flight_identifier(id: &Option<String>) -> Result<(), PostgisError> {
         PostgisError::FlightPath(FlightError::Label)
     })?;
 
-    check_flight_identifier(identifier).map_err(|e| {
+    identifier::check_identifier(Entity::Flight, identifier).map_err(|e| {
         postgis_error!("invalid identifier {}: {}", identifier, e);
         PostgisError::FlightPath(FlightError::Label)
     })?;
@@ -129,6 +257,71 @@ fn validate_flight_identifier(id: &Option<String>) -> Result<(), PostgisError> {
     Ok(())
 }
 
+/// Index of the first vertex of a path outside of the valid range of
+///  latitude and longitude
+fn out_of_bounds_vertex(path: &[GrpcPointZ]) -> Option<usize> {
+    path.iter().position(|point| {
+        !(-90.0..=90.0).contains(&point.latitude) || !(-180.0..=180.0).contains(&point.longitude)
+    })
+}
+
+/// Gets the fields of a flight path update that would be rejected, and why
+pub fn violations(flight: &UpdateFlightPathRequest) -> Vec<FieldViolation> {
+    let identifier = flight.flight_identifier.as_deref().unwrap_or_default();
+    let mut violations = vec![];
+    let mut reject = |field: &str, error: FlightError| {
+        violations.push(field_violation(
+            field.to_string(),
+            "flight",
+            identifier,
+            error,
+        ));
+    };
+
+    if validate_flight_identifier(&flight.flight_identifier).is_err() {
+        reject("flight_identifier", FlightError::Label);
+    }
+
+    if flight.timestamp_start.is_none() {
+        reject("timestamp_start", FlightError::Time);
+    }
+
+    if flight.timestamp_end.is_none() {
+        reject("timestamp_end", FlightError::Time);
+    }
+
+    if <AircraftType as FromPrimitive>::from_i32(flight.aircraft_type).is_none() {
+        reject("aircraft_type", FlightError::AircraftType);
+    }
+
+    if FlightPriority::try_from(flight.priority).is_err() {
+        reject("priority", FlightError::Priority);
+    }
+
+    if let Some(index) = out_of_bounds_vertex(&flight.path) {
+        let point = &flight.path[index];
+        let error = GeometryError {
+            reason: PolygonError::OutOfBounds,
+            vertex: Some(InvalidVertex {
+                index,
+                coordinates: Coordinates {
+                    latitude: point.latitude,
+                    longitude: point.longitude,
+                },
+            }),
+        };
+
+        violations.push(field_violation(
+            format!("path[{index}]"),
+            "flight",
+            identifier,
+            error,
+        ));
+    }
+
+    violations
+}
+
 /// Pulls queued flight path messages from Redis Queue (from svc-scheduler)
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need psql backend to test
@@ -163,28 +356,10 @@ pub async fn update_flight_path(flight: UpdateFlightPathRequest) -> Result<(), P
             PostgisError::FlightPath(FlightError::AircraftType)
         })?;
 
-    let flights_insertion_stmt: String = format!(
-        r#"INSERT INTO {table_name} (
-            "flight_identifier",
-            "aircraft_identifier",
-            "aircraft_type",
-            "simulated",
-            "time_start",
-            "time_end",
-            "geom",
-            "isa"
-        )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, ST_Envelope($7))
-        ON CONFLICT ("flight_identifier") DO UPDATE
-            SET "aircraft_identifier" = EXCLUDED."aircraft_identifier",
-                "aircraft_type" = EXCLUDED."aircraft_type",
-                "simulated" = EXCLUDED."simulated",
-                "geom" = EXCLUDED."geom",
-                "isa" = EXCLUDED."isa",
-                "time_start" = EXCLUDED."time_start",
-                "time_end" = EXCLUDED."time_end";"#,
-        table_name = get_flights_table_name()
-    );
+    let priority: FlightPriority = FromPrimitive::from_i32(flight.priority).ok_or_else(|| {
+        postgis_error!("invalid flight priority provided.");
+        PostgisError::FlightPath(FlightError::Priority)
+    })?;
 
     let mut client = crate::postgis::DEADPOOL_POSTGIS
         .get()
@@ -204,6 +379,11 @@ pub async fn update_flight_path(flight: UpdateFlightPathRequest) -> Result<(), P
         PostgisError::FlightPath(FlightError::Client)
     })?;
 
+    if let Some(index) = out_of_bounds_vertex(&flight.path) {
+        postgis_error!("path vertex {} is out of bounds.", index);
+        return Err(PostgisError::FlightPath(FlightError::Location));
+    }
+
     let points = flight
         .path
         .clone()
@@ -215,6 +395,20 @@ pub async fn update_flight_path(flight: UpdateFlightPathRequest) -> Result<(), P
             PostgisError::FlightPath(FlightError::Location)
         })?;
 
+    // Dense paths slow down every intersection check against this flight
+    let original_point_count = points.len() as i32;
+    let points =
+        super::simplify::simplify_path(points, super::simplify::get_settings().path_epsilon_meters);
+
+    if points.len() < original_point_count as usize {
+        postgis_debug!(
+            "simplified path of flight {:?} from {} to {} points.",
+            flight.flight_identifier,
+            original_point_count,
+            points.len()
+        );
+    }
+
     // Subdivide the path into segments by length
     let geom = LineStringT {
         points,
@@ -223,9 +417,17 @@ pub async fn update_flight_path(flight: UpdateFlightPathRequest) -> Result<(), P
 
     // postgis_debug!("found segments: {:?}", segments);
 
+    let stmt = transaction
+        .prepare_cached(&Statement::UpsertFlight.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::FlightPath(FlightError::DBError)
+        })?;
+
     transaction
         .execute(
-            &flights_insertion_stmt,
+            &stmt,
             &[
                 &flight.flight_identifier,
                 &flight.aircraft_identifier,
@@ -234,6 +436,8 @@ pub async fn update_flight_path(flight: UpdateFlightPathRequest) -> Result<(), P
                 &timestamp_start,
                 &timestamp_end,
                 &geom,
+                &(priority as i32),
+                &original_point_count,
             ],
         )
         .await
@@ -258,29 +462,23 @@ pub async fn get_flight_intersection_stmt(
     client: &Object,
 ) -> Result<tokio_postgres::Statement, PostgisError> {
     client
-        .prepare_cached(&format!(
-            r#"
-            SELECT
-                "flight_identifier",
-                "aircraft_identifier",
-                "geom",
-                "time_start",
-                "time_end",
-                ST_3DLength(ST_Transform("geom", 4978)) as "distance",
-                "distance_to_path"
-            FROM {flights_table_name},
-                ST_3DDistance(
-                    ST_Transform("geom", 4978),
-                    ST_Transform($1, 4978)
-                ) as "distance_to_path"
-            WHERE
-                ("distance_to_path" < $2 OR "distance_to_path" IS NULL)
-                AND ("time_start" <= $4 OR "time_start" IS NULL) -- easy checks first
-                AND ("time_end" >= $3 OR "time_end" IS NULL)
-                AND "simulated" = FALSE
-        "#,
-            flights_table_name = get_flights_table_name(),
-        ))
+        .prepare_cached(&Statement::FlightPathIntersection.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::FlightPath(FlightError::DBError)
+        })
+}
+
+/// Prepares a statement that gets filed flights passing through a volume
+///  The volume is a polygon extended between two altitudes
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test
+pub async fn get_flight_volume_intersection_stmt(
+    client: &Object,
+) -> Result<tokio_postgres::Statement, PostgisError> {
+    client
+        .prepare_cached(&Statement::FlightVolumeIntersection.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
@@ -290,12 +488,13 @@ pub async fn get_flight_intersection_stmt(
 
 /// Splits intersecting flight paths into smaller segments to check for intersections
 ///  on a higher resolution
+///  Segments intersect if they are closer than both separation margins.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need psql backend to test
 pub async fn intersection_check(
     client: &deadpool_postgres::Client,
     stmt: &tokio_postgres::Statement,
-    allowable_distance: f64,
+    margins: SeparationMargins,
     segment_length: f32,
     a_segment: Segment,
     b_segment: Segment,
@@ -304,8 +503,8 @@ pub async fn intersection_check(
     let mut pairs: Vec<(Segment, Segment, f32)> = vec![(a_segment, b_segment, segment_length)];
 
     while let Some((a_segment, b_segment, segment_length)) = pairs.pop() {
-        if (segment_length as f64) < allowable_distance {
-            postgis_debug!("intersection < {allowable_distance} m found.");
+        if (segment_length as f64) < margins.resolution_meters() {
+            postgis_debug!("intersection within {:?} found.", margins);
             return Err(PostgisError::FlightPath(FlightError::Intersection));
         }
 
@@ -343,7 +542,15 @@ pub async fn intersection_check(
                 }
 
                 let conflict: bool = client
-                    .query_one(stmt, &[&a.geom, &b.geom, &allowable_distance])
+                    .query_one(
+                        stmt,
+                        &[
+                            &a.geom,
+                            &b.geom,
+                            &margins.horizontal_meters,
+                            &margins.vertical_meters,
+                        ],
+                    )
                     .await
                     .map_err(|e| {
                         postgis_error!(
@@ -370,14 +577,21 @@ pub async fn intersection_check(
     Ok(())
 }
 
+/// The latest known telemetry of an aircraft
+#[derive(Debug, Clone)]
+struct AircraftTelemetry {
+    identifier: Option<String>,
+    session_id: Option<String>,
+    position: TimePosition,
+    state: AircraftState,
+}
+
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need psql backend to test, no way to create a Row without querying it
 //  from a postgres instance
 fn process_row(
     row: tokio_postgres::Row,
-    base: &Flight,
-) -> Result<Flight, tokio_postgres::error::Error> {
-    let mut flight = base.clone();
+) -> Result<AircraftTelemetry, tokio_postgres::error::Error> {
     let identifier: Option<String> = row.try_get("identifier")?;
     let session_id: Option<String> = row.try_get("session_id")?;
     let geom: PointZ = row.try_get("geom")?;
@@ -387,42 +601,334 @@ fn process_row(
     let last_position_update: DateTime<Utc> = row.try_get("last_position_update")?;
     let status: OperationalStatus = row.try_get("op_status")?;
 
-    flight.session_id = session_id;
-    flight.aircraft_id = identifier;
-    flight.positions.push(TimePosition {
-        position: Some(GrpcPointZ {
-            latitude: geom.y,
-            longitude: geom.x,
-            altitude_meters: geom.z as f32,
-        }),
-        timestamp: Some(last_position_update.into()),
-    });
-
-    let state = AircraftState {
-        timestamp: Some(last_position_update.into()),
-        ground_speed_mps: velocity_horizontal_ground_mps,
-        vertical_speed_mps: velocity_vertical_mps,
-        track_angle_degrees,
-        position: Some(GrpcPointZ {
-            latitude: geom.y,
-            longitude: geom.x,
-            altitude_meters: geom.z as f32,
-        }),
-        status: status as i32,
+    let position = GrpcPointZ {
+        latitude: geom.y,
+        longitude: geom.x,
+        altitude_meters: geom.z as f32,
+    };
+
+    let staleness_seconds = aircraft::get_staleness_seconds(last_position_update, Utc::now());
+    let quality =
+        aircraft::get_data_quality(staleness_seconds, &aircraft::get_quality_thresholds());
+
+    Ok(AircraftTelemetry {
+        identifier,
+        session_id,
+        position: TimePosition {
+            position: Some(position),
+            timestamp: Some(last_position_update.into()),
+        },
+        state: AircraftState {
+            timestamp: Some(last_position_update.into()),
+            ground_speed_mps: velocity_horizontal_ground_mps,
+            vertical_speed_mps: velocity_vertical_mps,
+            track_angle_degrees,
+            position: Some(position),
+            status: status as i32,
+            staleness_seconds,
+            quality: quality as i32,
+        },
+    })
+}
+
+/// If the latest position of a flight's aircraft is expired
+fn is_expired(flight: &Flight) -> bool {
+    flight
+        .state
+        .as_ref()
+        .is_some_and(|state| state.quality == DataQuality::Expired as i32)
+}
+
+/// Pairs each flight with the telemetry of its aircraft, preferring a match
+///  on the session ID over one on the aircraft identifier. Flights without
+///  any aircraft telemetry are dropped.
+fn match_telemetry(flights: Vec<Flight>, telemetry: &[AircraftTelemetry]) -> Vec<Flight> {
+    let mut by_session: HashMap<&str, &AircraftTelemetry> = HashMap::new();
+    let mut by_identifier: HashMap<&str, &AircraftTelemetry> = HashMap::new();
+    for item in telemetry {
+        if let Some(session_id) = &item.session_id {
+            by_session.entry(session_id).or_insert(item);
+        }
+
+        if let Some(identifier) = &item.identifier {
+            by_identifier.entry(identifier).or_insert(item);
+        }
+    }
+
+    flights
+        .into_iter()
+        .filter_map(|mut flight| {
+            let item = flight
+                .session_id
+                .as_deref()
+                .and_then(|session_id| by_session.get(session_id))
+                .or_else(|| {
+                    flight
+                        .aircraft_id
+                        .as_deref()
+                        .and_then(|identifier| by_identifier.get(identifier))
+                })?;
+
+            flight.session_id = item.session_id.clone();
+            flight.aircraft_id = item.identifier.clone();
+            flight.positions.push(item.position.clone());
+            flight.state = Some(item.state.clone());
+            Some(flight)
+        })
+        .collect()
+}
+
+/// Fraction of a flight path completed at a time, if the flight is active
+///  then. The fraction of the path at the live position of the aircraft is
+///  preferred to the fraction of the scheduled time elapsed.
+fn flight_progress(
+    time_start: Option<DateTime<Utc>>,
+    time_end: Option<DateTime<Utc>>,
+    path_fraction: Option<f64>,
+    now: DateTime<Utc>,
+) -> Option<f32> {
+    let (time_start, time_end) = (time_start?, time_end?);
+    if now < time_start || now > time_end {
+        return None;
+    }
+
+    if let Some(fraction) = path_fraction {
+        return Some(fraction.clamp(0.0, 1.0) as f32);
+    }
+
+    let duration = (time_end - time_start).num_milliseconds();
+    if duration <= 0 {
+        return Some(1.0);
+    }
+
+    Some(((now - time_start).num_milliseconds() as f64 / duration as f64) as f32)
+}
+
+/// Converts cached aircraft positions into flight positions
+fn track_positions(track: Vec<AircraftPosition>) -> Vec<TimePosition> {
+    track
+        .into_iter()
+        .map(|item| TimePosition {
+            position: Some(GrpcPointZ {
+                latitude: item.position.latitude.0,
+                longitude: item.position.longitude.0,
+                altitude_meters: item.position.altitude_meters.as_f32(),
+            }),
+            timestamp: Some(item.timestamp_network.into()),
+        })
+        .collect()
+}
+
+/// Gets the recent track of an aircraft from the Redis track cache,
+///  simplified except for its most recent positions.
+///  Returns `None` if the cache is unavailable or holds no positions,
+///  in which case the last position in PostGIS is used instead.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs redis backend to test
+async fn get_cached_track(
+    aircraft_id: &Option<String>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Option<Vec<TimePosition>> {
+    let identifier = aircraft_id.as_ref()?;
+    let cache = crate::cache::TRACK_CACHE.get()?;
+    let track = cache
+        .get_track(identifier, time_start, time_end)
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get cached track for aircraft {identifier}: {e}");
+        })
+        .ok()?;
+
+    if track.is_empty() {
+        return None;
+    }
+
+    let track = super::simplify::simplify_track(track, &super::simplify::get_settings());
+    Some(track_positions(track))
+}
+
+/// Builds the search window for a [`GetFlightsRequest`]
+///  An arbitrary polygon takes precedence over the rectangular window
+fn get_flights_window(request: &GetFlightsRequest) -> Result<PolygonZ, FlightError> {
+    let vertices = if request.window_vertices.is_empty() {
+        let corner = |longitude: f64, latitude: f64| Coordinates {
+            latitude,
+            longitude,
+        };
+
+        vec![
+            corner(request.window_min_x, request.window_min_y),
+            corner(request.window_max_x, request.window_min_y),
+            corner(request.window_max_x, request.window_max_y),
+            corner(request.window_min_x, request.window_max_y),
+            corner(request.window_min_x, request.window_min_y),
+        ]
+    } else {
+        request.window_vertices.clone()
     };
 
-    flight.state = Some(state);
+    super::utils::polygon_from_vertices_z(&vertices, 0.0).map_err(|e| {
+        postgis_error!("invalid window provided: {}", e);
+        FlightError::Location
+    })
+}
+
+/// Validates the optional altitude band of a [`GetFlightsRequest`]
+fn get_flights_altitude(
+    request: &GetFlightsRequest,
+) -> Result<(Option<f64>, Option<f64>), FlightError> {
+    let min = request.altitude_meters_min.map(|a| a as f64);
+    let max = request.altitude_meters_max.map(|a| a as f64);
+
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            postgis_error!("altitude_meters_min ({min}) > altitude_meters_max ({max}).");
+            return Err(FlightError::Altitude);
+        }
+    }
+
+    Ok((min, max))
+}
+
+/// Validates the optional zone proximity of a [`GetFlightsRequest`]
+fn get_flights_zone_proximity(request: &GetFlightsRequest) -> Result<Option<f64>, FlightError> {
+    match request.zone_proximity_meters {
+        None => Ok(None),
+        Some(meters) if meters.is_finite() && meters > 0.0 => Ok(Some(meters as f64)),
+        Some(meters) => {
+            postgis_error!("zone_proximity_meters must be greater than zero: {meters}");
+            Err(FlightError::ZoneProximity)
+        }
+    }
+}
+
+/// An active zone near the last position of an aircraft
+#[derive(Debug, Clone, PartialEq)]
+struct NearbyZone {
+    aircraft_identifier: String,
+    zone_identifier: String,
+    schedule: Vec<i32>,
+    distance_meters: f64,
+}
+
+/// Keeps the nearest zone of each aircraft within the proximity distance,
+///  ignoring zones whose schedule is inactive at the provided time
+fn nearest_zones(
+    zones: Vec<NearbyZone>,
+    proximity_meters: f64,
+    now: DateTime<Utc>,
+) -> HashMap<String, ZoneProximity> {
+    let mut nearest: HashMap<String, ZoneProximity> = HashMap::new();
+    let zones = zones
+        .into_iter()
+        .filter(|zone| zone.distance_meters <= proximity_meters)
+        .filter(|zone| super::zone::schedule_overlaps(&zone.schedule, now, now));
+
+    for zone in zones {
+        let distance_meters = zone.distance_meters as f32;
+        match nearest.get(&zone.aircraft_identifier) {
+            Some(current) if current.distance_meters <= distance_meters => continue,
+            _ => nearest.insert(
+                zone.aircraft_identifier,
+                ZoneProximity {
+                    zone_identifier: zone.zone_identifier,
+                    distance_meters,
+                },
+            ),
+        };
+    }
+
+    nearest
+}
+
+/// Gets the active restriction zones within the proximity distance
+///  of the provided aircraft
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test
+async fn get_nearby_zones(
+    client: &Object,
+    aircraft_ids: &[&str],
+    proximity_meters: f64,
+    now: DateTime<Utc>,
+) -> Result<Vec<NearbyZone>, FlightError> {
+    let stmt = client
+        .prepare_cached(&Statement::GetNearbyZones.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            FlightError::DBError
+        })?;
+
+    client
+        .query(
+            &stmt,
+            &[
+                &aircraft_ids,
+                &proximity_meters,
+                &now,
+                &ZoneType::Restriction,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute transaction: {}", e);
+            FlightError::DBError
+        })?
+        .iter()
+        .map(|row| {
+            Ok(NearbyZone {
+                aircraft_identifier: row.try_get("aircraft_identifier")?,
+                zone_identifier: row.try_get("zone_identifier")?,
+                schedule: row.try_get("schedule")?,
+                distance_meters: row.try_get("distance_meters")?,
+            })
+        })
+        .collect::<Result<Vec<NearbyZone>, tokio_postgres::error::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not get nearby zone data: {}", e);
+            FlightError::DBError
+        })
+}
+
+/// Gets the column used to order flights
+pub(super) fn get_order_column(order_by: OrderBy) -> &'static str {
+    match order_by {
+        OrderBy::Identifier => r#""aircraft"."identifier""#,
+        OrderBy::LastUpdated => r#""aircraft"."last_position_update""#,
+    }
+}
 
-    Ok(flight)
+/// Gets the column used to order filed flights
+///  Flights planned the soonest come first when ordering by last update
+pub(super) fn get_planned_order_column(order_by: OrderBy) -> &'static str {
+    match order_by {
+        OrderBy::Identifier => r#""flights"."flight_identifier""#,
+        OrderBy::LastUpdated => r#""flights"."time_start""#,
+    }
 }
 
 /// Get flights and their aircraft that intersect with the provided geometry
 ///  and time range.
+///
+/// With `planned_only`, only filed flights are returned, without any
+///  aircraft telemetry, track or nearby zone.
+///
+/// Simulated aircraft and flights are left out unless `include_simulated`
+///  is set.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need psql backend to test
-pub async fn get_flights(request: GetFlightsRequest) -> Result<Vec<Flight>, FlightError> {
+pub async fn get_flights(request: GetFlightsRequest) -> Result<GetFlightsResponse, FlightError> {
     postgis_debug!("entry.");
 
+    let window = get_flights_window(&request)?;
+    let (altitude_min, altitude_max) = get_flights_altitude(&request)?;
+    let zone_proximity = get_flights_zone_proximity(&request)?;
+    let page = Pagination::try_from(request.page).map_err(|e| {
+        postgis_error!("invalid pagination: {}", e);
+        FlightError::Pagination
+    })?;
+
     let time_start = request.time_start.ok_or_else(|| {
         postgis_error!("time_start is required.");
         FlightError::Time
@@ -435,21 +941,6 @@ pub async fn get_flights(request: GetFlightsRequest) -> Result<Vec<Flight>, Flig
 
     let time_start: DateTime<Utc> = time_start.into();
     let time_end: DateTime<Utc> = time_end.into();
-    let linestring = LineStringT {
-        points: vec![
-            Point {
-                x: request.window_min_x,
-                y: request.window_min_y,
-                srid: Some(DEFAULT_SRID),
-            },
-            Point {
-                x: request.window_max_x,
-                y: request.window_max_y,
-                srid: Some(DEFAULT_SRID),
-            },
-        ],
-        srid: Some(DEFAULT_SRID),
-    };
 
     let client = crate::postgis::DEADPOOL_POSTGIS
         .get()
@@ -468,45 +959,32 @@ pub async fn get_flights(request: GetFlightsRequest) -> Result<Vec<Flight>, Flig
     let aircraft_id_str = "aircraft_identifier";
     let aircraft_type_str = "aircraft_type";
     let simulated_str = "simulated";
-    let stmt = client
-        .prepare_cached(&format!(
-            r#"
-            SELECT 
-                "flights"."flight_identifier" as "{session_id_str}",
-                "aircraft"."identifier" as "{aircraft_id_str}",
-                "aircraft"."aircraft_type" as "{aircraft_type_str}",
-                "aircraft"."simulated" as "{simulated_str}"
-            FROM {aircraft_table_name} as "aircraft"
-            LEFT JOIN {flights_table_name} as "flights"
-                ON (
-                    "flights"."aircraft_identifier" = "aircraft"."identifier"
-                    OR "flights"."flight_identifier" = "aircraft"."session_id"
-                )
-            WHERE 
-                (
-                    -- get grounded aircraft without a scheduled flight
-                    ST_Intersects(ST_Envelope($1), "aircraft"."geom")
-                    AND "aircraft"."last_position_update" >= $2
-                    AND "aircraft"."last_position_update" <= $3
-                ) OR (
-                    -- flights that intersect this window
-                    "flights"."geom" IS NOT NULL
-                    AND ST_Intersects(ST_Envelope($1), "flights"."geom")
-                    AND "flights"."time_end" >= $2
-                    AND "flights"."time_start" <= $3
-                );
-            "#,
-            flights_table_name = get_flights_table_name(),
-            aircraft_table_name = super::aircraft::get_table_name(),
-        ))
-        .await
-        .map_err(|e| {
-            postgis_error!("could not prepare cached statement: {}", e);
-            FlightError::DBError
-        })?;
+    let ordering = Ordering::from(&page);
+    let statement = match request.planned_only {
+        true => Statement::GetPlannedFlights(ordering),
+        false => Statement::GetFlights(ordering),
+    };
 
-    let mut flights = client
-        .query(&stmt, &[&linestring, &time_start, &time_end])
+    let stmt = client.prepare_cached(&statement.sql()).await.map_err(|e| {
+        postgis_error!("could not prepare cached statement: {}", e);
+        FlightError::DBError
+    })?;
+
+    let now = Utc::now();
+    let flights = client
+        .query(
+            &stmt,
+            &[
+                &window,
+                &time_start,
+                &time_end,
+                &altitude_min,
+                &altitude_max,
+                &(page.limit as i64),
+                &(page.offset as i64),
+                &request.include_simulated,
+            ],
+        )
         .await
         .map_err(|e| {
             postgis_error!("could not execute transaction: {}", e);
@@ -518,6 +996,9 @@ pub async fn get_flights(request: GetFlightsRequest) -> Result<Vec<Flight>, Flig
             let aircraft_id: Option<String> = row.try_get(aircraft_id_str)?;
             let aircraft_type: AircraftType = row.try_get(aircraft_type_str)?;
             let simulated: bool = row.try_get(simulated_str)?;
+            let time_start: Option<DateTime<Utc>> = row.try_get("time_start")?;
+            let time_end: Option<DateTime<Utc>> = row.try_get("time_end")?;
+            let path_fraction: Option<f64> = row.try_get("path_fraction")?;
 
             Ok(Flight {
                 session_id,
@@ -526,6 +1007,10 @@ pub async fn get_flights(request: GetFlightsRequest) -> Result<Vec<Flight>, Flig
                 positions: vec![],
                 state: None,
                 aircraft_type: aircraft_type as i32,
+                nearest_zone: None,
+                progress: flight_progress(time_start, time_end, path_fraction, now),
+                time_start: time_start.map(Into::into),
+                time_end: time_end.map(Into::into),
             })
         })
         .collect::<Result<Vec<Flight>, tokio_postgres::error::Error>>()
@@ -535,66 +1020,90 @@ pub async fn get_flights(request: GetFlightsRequest) -> Result<Vec<Flight>, Flig
         })?;
 
     postgis_debug!("found {} flights.", flights.len());
+    let next_offset = page.next_offset(flights.len());
+    if request.planned_only {
+        return Ok(GetFlightsResponse {
+            flights,
+            next_offset,
+        });
+    }
 
-    // TODO(R5): Change this to use Redis 60s telemetry storage to acquire
-    //  telemetry information
+    // The latest aircraft state comes from PostGIS, the position history
+    //  from the Redis track cache when one is available
     let stmt = client
-        .prepare_cached(&format!(
-            r#"SELECT
-                    "identifier",
-                    "session_id",
-                    "geom",
-                    "velocity_horizontal_ground_mps",
-                    "velocity_vertical_mps",
-                    "track_angle_degrees",
-                    "last_position_update",
-                    "op_status"
-                FROM {table_name} 
-                WHERE
-                    "session_id" = $1 
-                    OR "identifier" = $2 
-                LIMIT 1;
-        "#,
-            table_name = super::aircraft::get_table_name(),
-        ))
+        .prepare_cached(&Statement::GetAircraftTelemetry.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
             FlightError::DBError
         })?;
 
-    let mut result: Vec<Flight> = vec![];
-    for flight in &mut flights {
-        let rows = client
-            .query(&stmt, &[&flight.session_id, &flight.aircraft_id])
-            .await;
-
-        let rows = match rows {
-            Ok(rows) => rows,
-            Err(e) => {
-                postgis_error!("could not execute transaction: {}", e);
-                continue;
-            }
-        };
+    let session_ids: Vec<&str> = flights
+        .iter()
+        .filter_map(|flight| flight.session_id.as_deref())
+        .collect();
 
-        let flight_it = rows.into_iter().filter_map(|row| {
-            process_row(row, flight)
+    let aircraft_ids: Vec<&str> = flights
+        .iter()
+        .filter_map(|flight| flight.aircraft_id.as_deref())
+        .collect();
+
+    let telemetry: Vec<AircraftTelemetry> = client
+        .query(&stmt, &[&session_ids, &aircraft_ids])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute transaction: {}", e);
+            FlightError::DBError
+        })?
+        .into_iter()
+        .filter_map(|row| {
+            process_row(row)
                 .map_err(|e| {
                     postgis_error!("could not get position data for row: {e}");
                 })
                 .ok()
-        });
-
-        // 'extend' can take an iterator argument
-        result.extend(flight_it);
+        })
+        .collect();
+
+    let mut flights = match_telemetry(flights, &telemetry);
+    if let Some(proximity_meters) = zone_proximity {
+        // Expired positions are too old to predict a zone conflict from
+        let now = Utc::now();
+        let aircraft_ids: Vec<&str> = flights
+            .iter()
+            .filter(|flight| !is_expired(flight))
+            .filter_map(|flight| flight.aircraft_id.as_deref())
+            .collect();
+
+        let zones = get_nearby_zones(&client, &aircraft_ids, proximity_meters, now).await?;
+        let mut nearest = nearest_zones(zones, proximity_meters, now);
+        for flight in flights.iter_mut() {
+            flight.nearest_zone = flight
+                .aircraft_id
+                .as_ref()
+                .and_then(|identifier| nearest.remove(identifier));
+        }
     }
 
-    Ok(result)
+    let result = join_all(flights.into_iter().map(|mut flight| async move {
+        if let Some(track) = get_cached_track(&flight.aircraft_id, time_start, time_end).await {
+            flight.positions = track;
+        }
+
+        flight
+    }))
+    .await;
+
+    Ok(GetFlightsResponse {
+        flights: result,
+        next_offset,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{Degrees, Meters};
     use lib_common::time::{Duration, Utc};
 
     #[tokio::test]
@@ -610,11 +1119,19 @@ mod tests {
             timestamp_start: Some(Utc::now().into()),
             timestamp_end: Some((Utc::now() + Duration::try_hours(1).unwrap()).into()),
             path: vec![],
+            priority: FlightPriority::Emergency as i32,
         };
 
-        let result = update_flight_path(item).await.unwrap_err();
+        let result = update_flight_path(item.clone()).await.unwrap_err();
         assert_eq!(result, PostgisError::FlightPath(FlightError::DBError));
 
+        let item = UpdateFlightPathRequest {
+            priority: 100,
+            ..item
+        };
+        let result = update_flight_path(item).await.unwrap_err();
+        assert_eq!(result, PostgisError::FlightPath(FlightError::Priority));
+
         ut_info!("success");
     }
 
@@ -647,6 +1164,373 @@ mod tests {
             FlightError::Intersection.to_string(),
             "Flight paths intersect."
         );
+        assert_eq!(
+            FlightError::Altitude.to_string(),
+            "Invalid altitude range provided."
+        );
+        assert_eq!(
+            FlightError::Pagination.to_string(),
+            "Invalid pagination provided."
+        );
+        assert_eq!(
+            FlightError::ZoneProximity.to_string(),
+            "Invalid zone proximity provided."
+        );
+        assert_eq!(
+            FlightError::Priority.to_string(),
+            "Invalid flight priority provided."
+        );
+    }
+
+    #[test]
+    fn test_get_order_column() {
+        assert_eq!(
+            get_order_column(OrderBy::Identifier),
+            r#""aircraft"."identifier""#
+        );
+        assert_eq!(
+            get_order_column(OrderBy::LastUpdated),
+            r#""aircraft"."last_position_update""#
+        );
+        assert_eq!(
+            get_planned_order_column(OrderBy::LastUpdated),
+            r#""flights"."time_start""#
+        );
+    }
+
+    #[test]
+    fn test_track_positions() {
+        let timestamp_network = Utc::now();
+        let track = vec![AircraftPosition {
+            identifier: "N12345".to_string(),
+            position: crate::types::Position {
+                longitude: Degrees(4.9),
+                latitude: Degrees(52.3),
+                altitude_meters: Meters(120.0),
+            },
+            timestamp_network,
+            timestamp_asset: None,
+        }];
+
+        let positions = track_positions(track);
+        assert_eq!(
+            positions,
+            vec![TimePosition {
+                position: Some(GrpcPointZ {
+                    latitude: 52.3,
+                    longitude: 4.9,
+                    altitude_meters: 120.0,
+                }),
+                timestamp: Some(timestamp_network.into()),
+            }]
+        );
+        assert!(track_positions(vec![]).is_empty());
+    }
+
+    #[test]
+    fn ut_flight_progress() {
+        let now = Utc::now();
+        let time_start = Some(now - Duration::try_minutes(15).unwrap());
+        let time_end = Some(now + Duration::try_minutes(45).unwrap());
+
+        assert_eq!(flight_progress(time_start, time_end, None, now), Some(0.25));
+        assert_eq!(
+            flight_progress(time_start, time_end, Some(0.5), now),
+            Some(0.5)
+        );
+        assert_eq!(
+            flight_progress(time_start, time_end, Some(1.2), now),
+            Some(1.0)
+        );
+
+        // Not active
+        let later = now + Duration::try_hours(1).unwrap();
+        assert_eq!(
+            flight_progress(time_start, time_end, Some(0.5), later),
+            None
+        );
+        assert_eq!(flight_progress(None, time_end, None, now), None);
+        assert_eq!(flight_progress(time_start, None, None, now), None);
+
+        // Zero length schedule
+        assert_eq!(flight_progress(Some(now), Some(now), None, now), Some(1.0));
+    }
+
+    #[test]
+    fn test_match_telemetry() {
+        let flight = |session_id: Option<&str>, aircraft_id: Option<&str>| Flight {
+            session_id: session_id.map(String::from),
+            aircraft_id: aircraft_id.map(String::from),
+            simulated: false,
+            positions: vec![],
+            state: None,
+            aircraft_type: AircraftType::Rotorcraft as i32,
+            nearest_zone: None,
+            time_start: None,
+            time_end: None,
+            progress: None,
+        };
+
+        let telemetry = |identifier: &str, session_id: Option<&str>, altitude_meters: f32| {
+            let position = GrpcPointZ {
+                latitude: 52.3,
+                longitude: 4.9,
+                altitude_meters,
+            };
+
+            AircraftTelemetry {
+                identifier: Some(identifier.to_string()),
+                session_id: session_id.map(String::from),
+                position: TimePosition {
+                    position: Some(position),
+                    timestamp: None,
+                },
+                state: AircraftState {
+                    position: Some(position),
+                    ..Default::default()
+                },
+            }
+        };
+
+        let telemetry = vec![
+            telemetry("A1", Some("F1"), 10.0),
+            telemetry("A2", None, 20.0),
+            telemetry("A3", Some("F3"), 30.0),
+        ];
+
+        let flights = vec![
+            // matched on session ID, even with another aircraft identifier
+            flight(Some("F1"), Some("A3")),
+            // no session, matched on aircraft identifier
+            flight(None, Some("A2")),
+            // unknown session, falls back to aircraft identifier
+            flight(Some("F9"), Some("A3")),
+            // no telemetry at all
+            flight(Some("F8"), Some("A8")),
+        ];
+
+        let result = match_telemetry(flights, &telemetry);
+        let summary: Vec<(Option<String>, Option<String>, usize, Option<f32>)> = result
+            .iter()
+            .map(|flight| {
+                (
+                    flight.session_id.clone(),
+                    flight.aircraft_id.clone(),
+                    flight.positions.len(),
+                    flight
+                        .state
+                        .as_ref()
+                        .and_then(|state| state.position)
+                        .map(|position| position.altitude_meters),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Some("F1".to_string()),
+                    Some("A1".to_string()),
+                    1,
+                    Some(10.0)
+                ),
+                (None, Some("A2".to_string()), 1, Some(20.0)),
+                (
+                    Some("F3".to_string()),
+                    Some("A3".to_string()),
+                    1,
+                    Some(30.0)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_flights_window() {
+        let mut request = GetFlightsRequest {
+            window_min_x: 4.915,
+            window_min_y: 52.374,
+            window_max_x: 4.917,
+            window_max_y: 52.376,
+            ..Default::default()
+        };
+
+        // rectangular window
+        let window = get_flights_window(&request).unwrap();
+        assert_eq!(window.rings[0].points.len(), 5);
+        assert_eq!(window.rings[0].points[2].x, request.window_max_x);
+        assert_eq!(window.rings[0].points[2].y, request.window_max_y);
+
+        // arbitrary polygon takes precedence
+        request.window_vertices = vec![
+            Coordinates {
+                latitude: 52.374,
+                longitude: 4.915,
+            },
+            Coordinates {
+                latitude: 52.376,
+                longitude: 4.916,
+            },
+            Coordinates {
+                latitude: 52.374,
+                longitude: 4.917,
+            },
+            Coordinates {
+                latitude: 52.374,
+                longitude: 4.915,
+            },
+        ];
+        let window = get_flights_window(&request).unwrap();
+        assert_eq!(window.rings[0].points.len(), 4);
+        assert_eq!(window.rings[0].points[1].x, 4.916);
+
+        // open polygon
+        request.window_vertices.pop();
+        let error = get_flights_window(&request).unwrap_err();
+        assert_eq!(error, FlightError::Location);
+
+        // out of bounds rectangle
+        let request = GetFlightsRequest {
+            window_max_y: 91.0,
+            ..Default::default()
+        };
+        let error = get_flights_window(&request).unwrap_err();
+        assert_eq!(error, FlightError::Location);
+    }
+
+    #[test]
+    fn test_get_flights_altitude() {
+        let mut request = GetFlightsRequest::default();
+        assert_eq!(get_flights_altitude(&request).unwrap(), (None, None));
+
+        request.altitude_meters_min = Some(10.0);
+        assert_eq!(get_flights_altitude(&request).unwrap(), (Some(10.0), None));
+
+        request.altitude_meters_max = Some(100.0);
+        assert_eq!(
+            get_flights_altitude(&request).unwrap(),
+            (Some(10.0), Some(100.0))
+        );
+
+        request.altitude_meters_min = Some(101.0);
+        let error = get_flights_altitude(&request).unwrap_err();
+        assert_eq!(error, FlightError::Altitude);
+    }
+
+    #[test]
+    fn test_get_flights_zone_proximity() {
+        let mut request = GetFlightsRequest::default();
+        assert_eq!(get_flights_zone_proximity(&request).unwrap(), None);
+
+        request.zone_proximity_meters = Some(500.0);
+        assert_eq!(get_flights_zone_proximity(&request).unwrap(), Some(500.0));
+
+        for meters in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            request.zone_proximity_meters = Some(meters);
+            let error = get_flights_zone_proximity(&request).unwrap_err();
+            assert_eq!(error, FlightError::ZoneProximity);
+        }
+    }
+
+    #[test]
+    fn test_nearest_zones() {
+        let zone = |aircraft: &str, zone: &str, distance_meters: f64| NearbyZone {
+            aircraft_identifier: aircraft.to_string(),
+            zone_identifier: zone.to_string(),
+            schedule: vec![],
+            distance_meters,
+        };
+
+        let now = Utc::now();
+        let mut inactive = zone("A1", "Z0", 10.0);
+        inactive.schedule = vec![0, 1];
+        // an inactive schedule relative to the current minute of the week
+        if crate::postgis::zone::schedule_overlaps(&inactive.schedule, now, now) {
+            inactive.schedule = vec![2, 3];
+        }
+
+        let zones = vec![
+            inactive,
+            zone("A1", "Z2", 200.0),
+            zone("A1", "Z1", 100.0),
+            zone("A2", "Z3", 600.0),
+            zone("A3", "Z1", 0.0),
+        ];
+
+        let result = nearest_zones(zones, 500.0, now);
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result.get("A1"),
+            Some(&ZoneProximity {
+                zone_identifier: "Z1".to_string(),
+                distance_meters: 100.0,
+            })
+        );
+        assert!(result.get("A2").is_none());
+        assert_eq!(result.get("A3").unwrap().distance_meters, 0.0);
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let flight = |quality: Option<DataQuality>| Flight {
+            state: quality.map(|quality| AircraftState {
+                quality: quality as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert!(!is_expired(&flight(None)));
+        assert!(!is_expired(&flight(Some(DataQuality::Fresh))));
+        assert!(!is_expired(&flight(Some(DataQuality::Stale))));
+        assert!(is_expired(&flight(Some(DataQuality::Expired))));
+    }
+
+    #[test]
+    fn ut_violations() {
+        let flight = UpdateFlightPathRequest {
+            flight_identifier: Some("test".to_string()),
+            aircraft_identifier: Some("test".to_string()),
+            aircraft_type: AircraftType::Aeroplane as i32,
+            simulated: false,
+            timestamp_start: Some(Utc::now().into()),
+            timestamp_end: Some((Utc::now() + Duration::try_hours(1).unwrap()).into()),
+            path: vec![
+                GrpcPointZ {
+                    latitude: 52.3745905,
+                    longitude: 4.9160036,
+                    altitude_meters: 100.0,
+                },
+                GrpcPointZ {
+                    latitude: 52.3749819,
+                    longitude: 180.5,
+                    altitude_meters: 100.0,
+                },
+            ],
+            priority: FlightPriority::Routine as i32,
+        };
+
+        let rejected = violations(&flight);
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].field, "path[1]");
+        assert!(rejected[0]
+            .description
+            .ends_with("Vertex 1: (52.3749819, 180.5)."));
+
+        let flight = UpdateFlightPathRequest {
+            flight_identifier: None,
+            timestamp_end: None,
+            priority: 100,
+            path: vec![],
+            ..flight
+        };
+
+        let fields: Vec<String> = violations(&flight).into_iter().map(|v| v.field).collect();
+        assert_eq!(
+            fields,
+            vec!["flight_identifier", "timestamp_end", "priority"]
+        );
     }
 
     #[test]
@@ -0,0 +1,299 @@
+//! Analysis of the gaps left between zones.
+//!
+//! Two zones sharing an altitude band and separated by less than the width
+//!  an aircraft needs leave a passage that routes may try to thread. This
+//!  module finds such pairs in an area, for review of the airspace design.
+
+use super::utils::MAX_PAGE_SIZE;
+use super::PostgisError;
+use crate::grpc::server::grpc_server::{
+    Coordinates, FindZoneGapsRequest, FindZoneGapsResponse, ZoneGap,
+};
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Utc};
+use postgis::ewkb::{LineString, PolygonZ};
+use std::fmt::{self, Display, Formatter};
+
+/// Max gap width that can be searched for, in meters
+pub const MAX_GAP_WIDTH_METERS: f32 = 10_000.0;
+
+/// Possible errors with zone gap requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GapError {
+    /// Invalid Location
+    Location,
+
+    /// Invalid gap width
+    Width,
+
+    /// Invalid Time Provided
+    Time,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for GapError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GapError::Location => write!(f, "Invalid location provided."),
+            GapError::Width => write!(f, "Invalid gap width provided."),
+            GapError::Time => write!(f, "Invalid time provided."),
+            GapError::Client => write!(f, "Could not get backend client."),
+            GapError::DBError => write!(f, "Unknown backend error."),
+        }
+    }
+}
+
+/// An area searched for narrow gaps between zones during a time window
+#[derive(Debug)]
+struct GapQuery {
+    geom: PolygonZ,
+    min_width_meters: f32,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+}
+
+impl TryFrom<FindZoneGapsRequest> for GapQuery {
+    type Error = GapError;
+
+    fn try_from(request: FindZoneGapsRequest) -> Result<Self, Self::Error> {
+        let geom = super::utils::polygon_from_vertices_z(&request.vertices, 0.0).map_err(|e| {
+            postgis_error!("invalid area polygon: {}", e);
+            GapError::Location
+        })?;
+
+        if !request.min_width_meters.is_finite()
+            || request.min_width_meters <= 0.0
+            || request.min_width_meters > MAX_GAP_WIDTH_METERS
+        {
+            postgis_error!(
+                "min_width_meters must be positive and at most {MAX_GAP_WIDTH_METERS}: {}",
+                request.min_width_meters
+            );
+            return Err(GapError::Width);
+        }
+
+        let time_start: DateTime<Utc> = request.time_start.map_or_else(Utc::now, Into::into);
+        let time_end: DateTime<Utc> = request.time_end.map_or(time_start, Into::into);
+        if time_end < time_start {
+            postgis_error!("time_end ({time_end}) is before time_start ({time_start}).");
+            return Err(GapError::Time);
+        }
+
+        Ok(GapQuery {
+            geom,
+            min_width_meters: request.min_width_meters,
+            time_start,
+            time_end,
+        })
+    }
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Gap(GapError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Gap(GapError::Client)
+        })
+}
+
+/// Converts the points of a line into the GRPC type
+fn line_coordinates(line: &LineString) -> Vec<Coordinates> {
+    line.points
+        .iter()
+        .map(|p| Coordinates {
+            latitude: p.y,
+            longitude: p.x,
+        })
+        .collect()
+}
+
+/// Converts a zone gap row into the GRPC type
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test, no way to create a Row without querying it
+fn process_gap_row(row: &tokio_postgres::Row) -> Result<ZoneGap, tokio_postgres::Error> {
+    let width_meters: f64 = row.try_get("width_meters")?;
+    let crossing: LineString = row.try_get("crossing")?;
+    let outline: Option<LineString> = row.try_get("outline")?;
+    let crossing = line_coordinates(&crossing);
+
+    Ok(ZoneGap {
+        zone_identifier_a: row.try_get("identifier_a")?,
+        zone_identifier_b: row.try_get("identifier_b")?,
+        width_meters: width_meters as f32,
+        closest_a: crossing.first().copied(),
+        closest_b: crossing.last().copied(),
+        vertices: outline.as_ref().map(line_coordinates).unwrap_or_default(),
+        altitude_meters_min: row.try_get("altitude_meters_min")?,
+        altitude_meters_max: row.try_get("altitude_meters_max")?,
+    })
+}
+
+/// Gets the pairs of zones in an area, active during the time window and
+///  sharing an altitude band, that are separated by less than the
+///  requested width. At most [`MAX_PAGE_SIZE`] of the narrowest pairs
+///  are checked against the zone schedules.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test
+pub async fn find_zone_gaps(
+    request: FindZoneGapsRequest,
+) -> Result<FindZoneGapsResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let query = GapQuery::try_from(request).map_err(PostgisError::Gap)?;
+    let client = get_client().await?;
+
+    let stmt = client
+        .prepare_cached(&super::statements::Statement::GetZoneGaps.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Gap(GapError::DBError)
+        })?;
+
+    let rows = client
+        .query(
+            &stmt,
+            &[
+                &query.geom,
+                &(query.min_width_meters as f64),
+                &query.time_start,
+                &query.time_end,
+                &(MAX_PAGE_SIZE as i64),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not query for zone gaps: {}", e);
+            PostgisError::Gap(GapError::DBError)
+        })?;
+
+    let mut gaps = vec![];
+    for row in &rows {
+        let parse = |e: tokio_postgres::Error| {
+            postgis_error!("could not get zone gap data: {}", e);
+            PostgisError::Gap(GapError::DBError)
+        };
+
+        let schedule_a: Vec<i32> = row.try_get("schedule_a").map_err(parse)?;
+        let schedule_b: Vec<i32> = row.try_get("schedule_b").map_err(parse)?;
+        let active = |schedule: &[i32]| {
+            super::zone::schedule_overlaps(schedule, query.time_start, query.time_end)
+        };
+
+        if active(&schedule_a) && active(&schedule_b) {
+            gaps.push(process_gap_row(row).map_err(parse)?);
+        }
+    }
+
+    postgis_debug!("found {} zone gaps.", gaps.len());
+    Ok(FindZoneGapsResponse { gaps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::rectangle;
+    use lib_common::time::Duration;
+
+    fn request() -> FindZoneGapsRequest {
+        FindZoneGapsRequest {
+            vertices: rectangle((4.91, 52.37), (4.92, 52.38)),
+            min_width_meters: 150.0,
+            time_start: Some(Utc::now().into()),
+            time_end: Some((Utc::now() + Duration::try_hours(1).unwrap()).into()),
+        }
+    }
+
+    #[test]
+    fn ut_gap_query_valid() {
+        let query = GapQuery::try_from(request()).unwrap();
+        assert_eq!(query.min_width_meters, 150.0);
+        assert_eq!(query.geom.rings[0].points.len(), 5);
+        assert!(query.time_start < query.time_end);
+
+        // defaults to now
+        let query = GapQuery::try_from(FindZoneGapsRequest {
+            time_start: None,
+            time_end: None,
+            ..request()
+        })
+        .unwrap();
+        assert_eq!(query.time_start, query.time_end);
+    }
+
+    #[test]
+    fn ut_gap_query_invalid() {
+        let error = GapQuery::try_from(FindZoneGapsRequest {
+            vertices: vec![],
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, GapError::Location);
+
+        for min_width_meters in [0.0, -1.0, f32::NAN, MAX_GAP_WIDTH_METERS + 1.0] {
+            let error = GapQuery::try_from(FindZoneGapsRequest {
+                min_width_meters,
+                ..request()
+            })
+            .unwrap_err();
+            assert_eq!(error, GapError::Width);
+        }
+
+        let error = GapQuery::try_from(FindZoneGapsRequest {
+            time_end: Some((Utc::now() - Duration::try_hours(1).unwrap()).into()),
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, GapError::Time);
+    }
+
+    #[test]
+    fn ut_line_coordinates() {
+        let line = LineString {
+            points: vec![
+                postgis::ewkb::Point {
+                    x: 4.91,
+                    y: 52.37,
+                    srid: None,
+                },
+                postgis::ewkb::Point {
+                    x: 4.92,
+                    y: 52.38,
+                    srid: None,
+                },
+            ],
+            srid: None,
+        };
+
+        let coordinates = line_coordinates(&line);
+        assert_eq!(coordinates.len(), 2);
+        assert_eq!(coordinates[0].latitude, 52.37);
+        assert_eq!(coordinates[1].longitude, 4.92);
+    }
+
+    #[test]
+    fn test_gap_error_display() {
+        assert_eq!(GapError::Location.to_string(), "Invalid location provided.");
+        assert_eq!(GapError::Width.to_string(), "Invalid gap width provided.");
+        assert_eq!(GapError::Time.to_string(), "Invalid time provided.");
+        assert_eq!(
+            GapError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(GapError::DBError.to_string(), "Unknown backend error.");
+    }
+}
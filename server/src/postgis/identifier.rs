@@ -0,0 +1,392 @@
+//! Identifier policy shared by all entities
+//!
+//! Every identifier is checked against the charset and max length of the
+//!  entity it names, and may not contain "null". The max lengths match the
+//!  columns the identifiers are stored in, so an identifier that passes
+//!  validation is never truncated or rejected by the database.
+//!
+//! Deployments may add characters to the charset of an entity, such as the
+//!  colons of URNs, and shorten its max length (see [`Overrides`]).
+//!  Identifiers only reach the database as bound statement parameters, never
+//!  as SQL text, so a wider charset doesn't need escaping.
+
+use super::aircraft;
+use super::utils::{check_string, StringError};
+use super::OnceCell;
+use std::fmt::{self, Display, Formatter};
+
+/// Characters allowed in every identifier, as a regex character class body
+const BASE_CHARACTERS: &str = r"\-0-9A-Za-z_\.";
+
+/// Characters a deployment may add to the charset of an entity
+///  None of them is special in a regex character class, nor separates the
+///  entries of the policy settings.
+pub const EXTRA_CHARACTERS: &str = ":/#@+~";
+
+/// Max length of vertiport, waypoint, zone, noise area and operator identifiers
+pub const ASSET_IDENTIFIER_MAX_LENGTH: u32 = 255;
+
+/// Deployment changes to the identifier policies, set once at startup
+static OVERRIDES: OnceCell<Overrides> = OnceCell::new();
+
+/// The kinds of entities named by an identifier
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum::EnumString)]
+pub enum Entity {
+    /// CAA-assigned aircraft identifiers
+    Aircraft,
+
+    /// Flight identifiers, also used as aircraft session IDs
+    Flight,
+
+    /// Vertiport identifiers
+    Vertiport,
+
+    /// Waypoint identifiers
+    Waypoint,
+
+    /// Zone identifiers
+    Zone,
+
+    /// Noise-sensitive area identifiers
+    NoiseArea,
+
+    /// Operator identifiers, holding volume reservations
+    Operator,
+}
+
+/// The constraints on the identifiers of an entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    /// Max number of characters
+    pub max_length: u32,
+
+    /// Regex matching the allowed characters
+    pub charset: String,
+}
+
+/// Deployment changes to the default identifier policies
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overrides {
+    /// Characters allowed on top of the default charset, per entity
+    pub extra_characters: Vec<(Entity, String)>,
+
+    /// Max lengths shorter than the stored columns, per entity
+    pub max_lengths: Vec<(Entity, u32)>,
+}
+
+impl Entity {
+    /// Gets the length of the columns identifiers of this entity are stored in
+    pub fn column_length(self) -> u32 {
+        match self {
+            // Session IDs are stored next to the aircraft identifiers
+            Entity::Aircraft | Entity::Flight => aircraft::get_identifier_max_length(),
+            Entity::Vertiport
+            | Entity::Waypoint
+            | Entity::Zone
+            | Entity::NoiseArea
+            | Entity::Operator => ASSET_IDENTIFIER_MAX_LENGTH,
+        }
+    }
+
+    /// Gets the identifier policy of this entity
+    pub fn policy(self) -> Policy {
+        match OVERRIDES.get() {
+            Some(overrides) => self.policy_with(overrides),
+            None => self.policy_with(&Overrides::default()),
+        }
+    }
+
+    /// Gets the identifier policy of this entity with the provided overrides
+    fn policy_with(self, overrides: &Overrides) -> Policy {
+        let max_length = overrides
+            .max_lengths
+            .iter()
+            .find(|(entity, _)| *entity == self)
+            .map(|(_, max_length)| *max_length)
+            .unwrap_or_else(|| self.column_length());
+
+        let extra = overrides
+            .extra_characters
+            .iter()
+            .find(|(entity, _)| *entity == self)
+            .map(|(_, characters)| characters.as_str())
+            .unwrap_or_default();
+
+        Policy {
+            max_length,
+            charset: format!("^[{BASE_CHARACTERS}{extra}]+$"),
+        }
+    }
+}
+
+/// Splits a policy setting into entities and their values, such as
+///  `Zone:64,Waypoint:32`
+fn parse_entries(text: &str) -> Result<Vec<(Entity, &str)>, IdentifierError> {
+    let mut entries: Vec<(Entity, &str)> = vec![];
+    for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        // the values may hold colons, the entity names don't
+        let Some((entity, value)) = entry.split_once(':') else {
+            postgis_error!("invalid identifier policy: {entry}");
+            return Err(IdentifierError::Policy);
+        };
+
+        let entity: Entity = entity.trim().parse().map_err(|_| {
+            postgis_error!("invalid identifier policy entity: {entry}");
+            IdentifierError::Policy
+        })?;
+
+        if entries.iter().any(|(e, _)| *e == entity) {
+            postgis_error!("duplicate identifier policy for {entity:?}.");
+            return Err(IdentifierError::Policy);
+        }
+
+        entries.push((entity, value));
+    }
+
+    Ok(entries)
+}
+
+/// Parses the characters added to the charset of each entity, such as
+///  `Zone::/,Aircraft:#` (colons and slashes in zone identifiers, hashes in
+///  aircraft identifiers). Only [`EXTRA_CHARACTERS`] may be added.
+pub fn parse_extra_characters(text: &str) -> Result<Vec<(Entity, String)>, IdentifierError> {
+    parse_entries(text)?
+        .into_iter()
+        .map(|(entity, characters)| {
+            if characters.is_empty() || !characters.chars().all(|c| EXTRA_CHARACTERS.contains(c))
+            {
+                postgis_error!(
+                    "identifier characters of {entity:?} must be among '{EXTRA_CHARACTERS}', got '{characters}'."
+                );
+                return Err(IdentifierError::Policy);
+            }
+
+            Ok((entity, characters.to_string()))
+        })
+        .collect()
+}
+
+/// Parses the max length of the identifiers of each entity, such as
+///  `Zone:64,Waypoint:32`. A max length may not exceed the column the
+///  identifiers are stored in.
+pub fn parse_max_lengths(text: &str) -> Result<Vec<(Entity, u32)>, IdentifierError> {
+    parse_entries(text)?
+        .into_iter()
+        .map(|(entity, max_length)| {
+            let max_length: u32 = max_length.trim().parse().map_err(|_| {
+                postgis_error!("invalid identifier max length of {entity:?}: {max_length}");
+                IdentifierError::Policy
+            })?;
+
+            if max_length == 0 || max_length > entity.column_length() {
+                postgis_error!(
+                    "identifier max length of {entity:?} must be between 1 and {}, got {max_length}.",
+                    entity.column_length()
+                );
+                return Err(IdentifierError::Policy);
+            }
+
+            Ok((entity, max_length))
+        })
+        .collect()
+}
+
+/// Sets the deployment changes to the identifier policies
+///  Must be called after [`aircraft::set_identifier_max_length`], as the
+///  aircraft and flight max lengths are checked against it.
+pub fn set_overrides(overrides: Overrides) -> Result<(), IdentifierError> {
+    OVERRIDES.set(overrides).map_err(|_| {
+        postgis_error!("identifier policies were already set.");
+        IdentifierError::Policy
+    })
+}
+
+/// Reasons an identifier is rejected
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IdentifierError {
+    /// The identifier is empty
+    Empty,
+
+    /// The identifier exceeds the max length of its entity
+    Length,
+
+    /// The identifier holds characters outside of the charset of its entity
+    Charset,
+
+    /// The identifier contains a forbidden substring
+    Forbidden,
+
+    /// The deployment identifier policies are invalid
+    Policy,
+}
+
+impl Display for IdentifierError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            IdentifierError::Empty => write!(f, "Identifier is empty."),
+            IdentifierError::Length => write!(f, "Identifier exceeds the maximum length."),
+            IdentifierError::Charset => write!(f, "Identifier contains invalid characters."),
+            IdentifierError::Forbidden => write!(f, "Identifier contains 'null'."),
+            IdentifierError::Policy => write!(f, "Invalid identifier policy provided."),
+        }
+    }
+}
+
+impl std::error::Error for IdentifierError {}
+
+/// Verifies that an identifier follows the policy of its entity
+pub fn check_identifier(entity: Entity, identifier: &str) -> Result<(), IdentifierError> {
+    let policy = entity.policy();
+    if identifier.is_empty() {
+        return Err(IdentifierError::Empty);
+    }
+
+    if identifier.chars().count() > policy.max_length as usize {
+        return Err(IdentifierError::Length);
+    }
+
+    check_string(identifier, &policy.charset).map_err(|e| match e {
+        StringError::ContainsForbidden => IdentifierError::Forbidden,
+        StringError::Regex | StringError::Mismatch => IdentifierError::Charset,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_check_identifier() {
+        for entity in [
+            Entity::Aircraft,
+            Entity::Flight,
+            Entity::Vertiport,
+            Entity::Waypoint,
+            Entity::Zone,
+            Entity::NoiseArea,
+            Entity::Operator,
+        ] {
+            assert!(check_identifier(entity, "N-12345_a.b").is_ok());
+            assert_eq!(check_identifier(entity, ""), Err(IdentifierError::Empty));
+            assert_eq!(
+                check_identifier(entity, "N 12345"),
+                Err(IdentifierError::Charset)
+            );
+            assert_eq!(
+                check_identifier(entity, "NuLL-1"),
+                Err(IdentifierError::Forbidden)
+            );
+
+            let max_length = entity.policy().max_length as usize;
+            assert!(check_identifier(entity, &"a".repeat(max_length)).is_ok());
+            assert_eq!(
+                check_identifier(entity, &"a".repeat(max_length + 1)),
+                Err(IdentifierError::Length)
+            );
+        }
+    }
+
+    #[test]
+    fn ut_policy() {
+        assert_eq!(
+            Entity::Zone.policy().max_length,
+            ASSET_IDENTIFIER_MAX_LENGTH
+        );
+        assert_eq!(
+            Entity::Flight.policy().max_length,
+            aircraft::get_identifier_max_length()
+        );
+        assert_eq!(Entity::Aircraft.policy(), Entity::Flight.policy());
+    }
+
+    #[test]
+    fn ut_policy_with() {
+        let overrides = Overrides {
+            extra_characters: vec![(Entity::Zone, ":/".to_string())],
+            max_lengths: vec![(Entity::Zone, 32)],
+        };
+
+        let policy = Entity::Zone.policy_with(&overrides);
+        assert_eq!(policy.max_length, 32);
+        assert!(check_string("urn:faa:nfz/KDCA-1", &policy.charset).is_ok());
+        assert!(check_string("urn:faa:nfz KDCA", &policy.charset).is_err());
+
+        // other entities keep the defaults
+        let policy = Entity::Vertiport.policy_with(&overrides);
+        assert_eq!(policy, Entity::Vertiport.policy_with(&Overrides::default()));
+        assert!(check_string("urn:faa:VP-1", &policy.charset).is_err());
+
+        let policy = Entity::Aircraft.policy_with(&Overrides {
+            extra_characters: vec![(Entity::Aircraft, EXTRA_CHARACTERS.to_string())],
+            ..Default::default()
+        });
+        assert!(check_string("N123:a/b#c@d+e~f", &policy.charset).is_ok());
+        assert!(check_string("N123]", &policy.charset).is_err());
+    }
+
+    #[test]
+    fn ut_parse_extra_characters() {
+        assert_eq!(parse_extra_characters("").unwrap(), vec![]);
+        assert_eq!(
+            parse_extra_characters("Zone::/, NoiseArea:#").unwrap(),
+            vec![
+                (Entity::Zone, ":/".to_string()),
+                (Entity::NoiseArea, "#".to_string())
+            ]
+        );
+
+        for text in [
+            "Zone",
+            "Zone:",
+            "Zone: ",
+            "Zone:;",
+            "Zone:]",
+            "zone::",
+            "Zone::,Zone:/",
+        ] {
+            assert_eq!(
+                parse_extra_characters(text),
+                Err(IdentifierError::Policy),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn ut_parse_max_lengths() {
+        assert_eq!(
+            parse_max_lengths("Zone:64,Waypoint: 32").unwrap(),
+            vec![(Entity::Zone, 64), (Entity::Waypoint, 32)]
+        );
+
+        let too_long = format!("Flight:{}", Entity::Flight.column_length() + 1);
+        for text in ["Zone:0", "Zone:-1", "Zone:256", "Zone:a", too_long.as_str()] {
+            assert_eq!(
+                parse_max_lengths(text),
+                Err(IdentifierError::Policy),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_identifier_error_display() {
+        assert_eq!(IdentifierError::Empty.to_string(), "Identifier is empty.");
+        assert_eq!(
+            IdentifierError::Length.to_string(),
+            "Identifier exceeds the maximum length."
+        );
+        assert_eq!(
+            IdentifierError::Charset.to_string(),
+            "Identifier contains invalid characters."
+        );
+        assert_eq!(
+            IdentifierError::Forbidden.to_string(),
+            "Identifier contains 'null'."
+        );
+        assert_eq!(
+            IdentifierError::Policy.to_string(),
+            "Invalid identifier policy provided."
+        );
+    }
+}
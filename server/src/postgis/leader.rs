@@ -0,0 +1,163 @@
+//! Leader election between replicas of this service.
+//!
+//! Periodic tasks such as flight maintenance and zone notices should run on
+//!  exactly one replica. Each task holds a PostgreSQL session-level advisory
+//!  lock on a dedicated connection while it leads. The lock is released by
+//!  the database when that session ends, so if the leading replica dies or
+//!  loses its connection another replica acquires the lock on its next pass.
+
+use deadpool_postgres::Object;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Max time allowed to acquire or confirm a lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Namespace of the advisory lock keys of this service, "GIS" in ASCII
+pub(super) const LOCK_NAMESPACE: i64 = 0x0047_4953 << 32;
+
+/// Periodic tasks that must run on a single replica
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Task {
+    /// Archiving and deleting old flights
+    Maintenance,
+
+    /// Announcing upcoming zone changes
+    ZoneEvents,
+}
+
+impl Task {
+    /// Key of the advisory lock guarding this task
+    pub fn lock_key(&self) -> i64 {
+        LOCK_NAMESPACE
+            | match self {
+                Task::Maintenance => 1,
+                Task::ZoneEvents => 2,
+            }
+    }
+}
+
+impl Display for Task {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Task::Maintenance => write!(f, "maintenance"),
+            Task::ZoneEvents => write!(f, "zone events"),
+        }
+    }
+}
+
+/// Leadership of a task by this replica
+#[derive(Debug)]
+pub struct Leadership {
+    /// The task to lead
+    task: Task,
+
+    /// Session holding the advisory lock while this replica leads
+    session: Option<Object>,
+}
+
+impl Leadership {
+    /// Creates a leadership that isn't held yet
+    pub fn new(task: Task) -> Self {
+        Leadership {
+            task,
+            session: None,
+        }
+    }
+
+    /// If this replica led the task at the last check
+    pub fn is_leader(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Confirms the lock is still held, or tries to acquire it.
+    ///  Returns if this replica leads the task.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) need running postgresql instance
+    pub async fn check(&mut self) -> bool {
+        match self.session.take() {
+            Some(session) => {
+                match tokio::time::timeout(LOCK_TIMEOUT, session.query_one("SELECT 1;", &[])).await
+                {
+                    Ok(Ok(_)) => self.session = Some(session),
+                    _ => {
+                        postgis_warn!(
+                            "lost the {} lock, another replica may take over.",
+                            self.task
+                        );
+
+                        // Close the session rather than recycling it, so the lock
+                        //  can't outlive it in the pool
+                        drop(Object::take(session));
+                    }
+                }
+            }
+            None => {
+                if let Some(session) = self.acquire().await {
+                    postgis_info!("acquired the {} lock, this replica now leads.", self.task);
+                    self.session = Some(session);
+                }
+            }
+        }
+
+        self.is_leader()
+    }
+
+    /// Gets a dedicated session holding the advisory lock of the task,
+    ///  if no other replica holds it
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) need running postgresql instance
+    async fn acquire(&self) -> Option<Object> {
+        let pool = super::DEADPOOL_POSTGIS.get().or_else(|| {
+            postgis_error!("could not get psql pool.");
+            None
+        })?;
+
+        let attempt = async {
+            let session = pool.get().await.map_err(|e| e.to_string())?;
+            let acquired = session
+                .query_one("SELECT pg_try_advisory_lock($1);", &[&self.task.lock_key()])
+                .await
+                .map_err(|e| e.to_string())?
+                .try_get::<_, bool>(0)
+                .map_err(|e| e.to_string())?;
+
+            Ok::<_, String>(acquired.then_some(session))
+        };
+
+        match tokio::time::timeout(LOCK_TIMEOUT, attempt).await {
+            Ok(Ok(session)) => session,
+            Ok(Err(e)) => {
+                postgis_warn!("could not try the {} lock: {e}", self.task);
+                None
+            }
+            Err(_) => {
+                postgis_warn!("timed out trying the {} lock.", self.task);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_lock_keys_distinct() {
+        assert_ne!(Task::Maintenance.lock_key(), Task::ZoneEvents.lock_key());
+        assert_eq!(Task::Maintenance.lock_key() >> 32, 0x0047_4953);
+    }
+
+    #[test]
+    fn ut_leadership_new() {
+        let leadership = Leadership::new(Task::Maintenance);
+        assert!(!leadership.is_leader());
+    }
+
+    #[test]
+    fn test_task_display() {
+        assert_eq!(Task::Maintenance.to_string(), "maintenance");
+        assert_eq!(Task::ZoneEvents.to_string(), "zone events");
+    }
+}
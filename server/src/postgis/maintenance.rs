@@ -0,0 +1,344 @@
+//! Periodic upkeep of the database.
+//!
+//! Sessions of aircraft that stopped reporting are released so that their
+//!  IDs can be reused.
+//! Completed flights are kept for a retention period, then moved to the
+//!  flights archive table (or deleted outright) so that the time filters of
+//!  the intersection queries only scan recent flights. Best path audit
+//!  records and the zone and aircraft history are deleted after their own
+//!  retention periods. Each pass removes rows in bounded batches to keep
+//!  the locks it takes short.
+
+use super::leader::{Leadership, Task};
+use super::{aircraft, audit, flight, snapshot};
+use crate::reload::Tunable;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default number of days completed flights are kept
+pub const DEFAULT_FLIGHT_RETENTION_DAYS: u32 = 30;
+
+/// Default seconds between maintenance passes
+pub const DEFAULT_INTERVAL_SECONDS: u64 = 3600;
+
+/// Max number of flights, audit records or history rows removed per
+///  statement
+const PURGE_BATCH_SIZE: i64 = 1000;
+
+/// Maintenance settings
+static SETTINGS: Tunable<Settings> = Tunable::new();
+
+/// Number of maintenance passes that completed
+static PASSES: AtomicU64 = AtomicU64::new(0);
+
+/// Number of flights moved to the archive table
+static FLIGHTS_ARCHIVED: AtomicU64 = AtomicU64::new(0);
+
+/// Number of flights deleted without being archived
+static FLIGHTS_DELETED: AtomicU64 = AtomicU64::new(0);
+
+/// Maintenance settings
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Settings {
+    /// Time between maintenance passes
+    pub interval: std::time::Duration,
+
+    /// Flights that ended more than this many days ago are removed
+    ///  Zero keeps flights forever
+    pub flight_retention_days: u32,
+
+    /// Copy removed flights to the archive table instead of dropping them
+    pub archive_flights: bool,
+
+    /// Best path audit records older than this many days are removed
+    ///  Zero keeps them forever
+    pub audit_retention_days: u32,
+
+    /// Zone versions replaced and aircraft positions reported more than
+    ///  this many days ago are removed. Zero keeps them forever
+    pub history_retention_days: u32,
+
+    /// Sessions of aircraft that haven't reported in this many seconds are
+    ///  released. Zero keeps them until ended
+    pub session_expiry_seconds: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            interval: std::time::Duration::from_secs(DEFAULT_INTERVAL_SECONDS),
+            flight_retention_days: DEFAULT_FLIGHT_RETENTION_DAYS,
+            archive_flights: true,
+            audit_retention_days: audit::DEFAULT_RETENTION_DAYS,
+            history_retention_days: snapshot::DEFAULT_HISTORY_RETENTION_DAYS,
+            session_expiry_seconds: aircraft::DEFAULT_SESSION_EXPIRY_SECONDS,
+        }
+    }
+}
+
+/// Sets the maintenance settings, replacing any set before. A new interval
+///  takes effect after the pending pass.
+pub fn set_settings(settings: Settings) {
+    SETTINGS.set(settings);
+}
+
+/// Gets the maintenance settings
+pub fn get_settings() -> Settings {
+    SETTINGS.get().unwrap_or_default()
+}
+
+/// Counters of the work done by maintenance passes since startup
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Metrics {
+    /// Number of maintenance passes that completed
+    pub passes: u64,
+
+    /// Number of flights moved to the archive table
+    pub flights_archived: u64,
+
+    /// Number of flights deleted without being archived
+    pub flights_deleted: u64,
+}
+
+/// Gets the counters of the work done by maintenance passes
+pub fn metrics() -> Metrics {
+    Metrics {
+        passes: PASSES.load(Ordering::Relaxed),
+        flights_archived: FLIGHTS_ARCHIVED.load(Ordering::Relaxed),
+        flights_deleted: FLIGHTS_DELETED.load(Ordering::Relaxed),
+    }
+}
+
+/// Adds purged flights to the counters
+fn record_purge(count: u64, archived: bool) {
+    let counter = if archived {
+        &FLIGHTS_ARCHIVED
+    } else {
+        &FLIGHTS_DELETED
+    };
+
+    counter.fetch_add(count, Ordering::Relaxed);
+}
+
+/// Rows older than this time are past their retention period, `None` if
+///  they are kept forever
+pub(super) fn retention_cutoff(now: DateTime<Utc>, retention_days: u32) -> Option<DateTime<Utc>> {
+    if retention_days == 0 {
+        return None;
+    }
+
+    Duration::try_days(retention_days as i64).and_then(|days| now.checked_sub_signed(days))
+}
+
+/// Aircraft that last reported before this time have their session
+///  released, `None` if sessions don't expire
+fn session_cutoff(now: DateTime<Utc>, expiry_seconds: u32) -> Option<DateTime<Utc>> {
+    if expiry_seconds == 0 {
+        return None;
+    }
+
+    Duration::try_seconds(expiry_seconds as i64).and_then(|seconds| now.checked_sub_signed(seconds))
+}
+
+/// Releases the sessions of aircraft that stopped reporting. Returns the
+///  number of sessions released.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn expire_sessions(settings: &Settings) -> Result<u64, super::PostgisError> {
+    let Some(cutoff) = session_cutoff(Utc::now(), settings.session_expiry_seconds) else {
+        return Ok(0);
+    };
+
+    aircraft::expire_sessions(cutoff).await
+}
+
+/// Removes the flights past their retention period, a batch at a time.
+///  Returns the number of flights removed.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn purge_flights(settings: &Settings) -> Result<u64, super::PostgisError> {
+    let Some(cutoff) = retention_cutoff(Utc::now(), settings.flight_retention_days) else {
+        return Ok(0);
+    };
+
+    let mut total = 0;
+    loop {
+        let count =
+            flight::purge_flights(cutoff, PURGE_BATCH_SIZE, settings.archive_flights).await?;
+
+        record_purge(count, settings.archive_flights);
+        total += count;
+        if count < PURGE_BATCH_SIZE as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+/// Removes the best path audit records past their retention period, a
+///  batch at a time. Returns the number of records removed.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn purge_audits(settings: &Settings) -> Result<u64, super::PostgisError> {
+    let Some(cutoff) = retention_cutoff(Utc::now(), settings.audit_retention_days) else {
+        return Ok(0);
+    };
+
+    let mut total = 0;
+    loop {
+        let count = audit::purge_audits(cutoff, PURGE_BATCH_SIZE).await?;
+        total += count;
+        if count < PURGE_BATCH_SIZE as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+/// Removes the zone and aircraft history past its retention period, a
+///  batch at a time. Returns the number of rows removed.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn purge_history(settings: &Settings) -> Result<u64, super::PostgisError> {
+    let Some(cutoff) = retention_cutoff(Utc::now(), settings.history_retention_days) else {
+        return Ok(0);
+    };
+
+    let mut total = 0;
+    loop {
+        let count = snapshot::purge_history(cutoff, PURGE_BATCH_SIZE).await?;
+        total += count;
+        if count < PURGE_BATCH_SIZE as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+/// Runs a maintenance pass at each interval, on the replica leading
+///  maintenance. The settings are read again before each pass.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+pub async fn run() {
+    let mut leadership = Leadership::new(Task::Maintenance);
+    let mut period = get_settings().interval;
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let settings = get_settings();
+        if settings.interval != period {
+            postgis_info!("maintenance interval changed to {:?}.", settings.interval);
+            period = settings.interval;
+            interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        }
+
+        if !leadership.check().await {
+            continue;
+        }
+
+        // Sessions are released independently of the retention work, a
+        //  failure here doesn't hold back the purges
+        let sessions_expired = match expire_sessions(&settings).await {
+            Ok(0) => true,
+            Ok(count) => {
+                postgis_info!(
+                    "released {count} session(s) of aircraft quiet for {}s.",
+                    settings.session_expiry_seconds
+                );
+                true
+            }
+            Err(e) => {
+                postgis_error!("could not expire sessions: {e}");
+                false
+            }
+        };
+
+        match purge_flights(&settings).await {
+            Ok(0) => (),
+            Ok(count) => {
+                let action = if settings.archive_flights {
+                    "archived"
+                } else {
+                    "deleted"
+                };
+
+                postgis_info!(
+                    "{action} {count} flight(s) older than {} day(s); totals: {:?}",
+                    settings.flight_retention_days,
+                    metrics()
+                );
+            }
+            Err(e) => {
+                postgis_error!("could not purge flights: {e}");
+                continue;
+            }
+        }
+
+        match purge_audits(&settings).await {
+            Ok(0) => (),
+            Ok(count) => postgis_info!(
+                "deleted {count} best path audit(s) older than {} day(s).",
+                settings.audit_retention_days
+            ),
+            Err(e) => {
+                postgis_error!("could not purge best path audits: {e}");
+                continue;
+            }
+        }
+
+        match purge_history(&settings).await {
+            Ok(0) => (),
+            Ok(count) => postgis_info!(
+                "deleted {count} history row(s) older than {} day(s).",
+                settings.history_retention_days
+            ),
+            Err(e) => {
+                postgis_error!("could not purge history: {e}");
+                continue;
+            }
+        }
+
+        if sessions_expired {
+            PASSES.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_retention_cutoff() {
+        let now = Utc::now();
+        assert_eq!(retention_cutoff(now, 0), None);
+        assert_eq!(
+            retention_cutoff(now, 30),
+            Some(now - Duration::try_days(30).unwrap())
+        );
+
+        // out of range
+        assert_eq!(retention_cutoff(now, u32::MAX), None);
+    }
+
+    #[test]
+    fn ut_session_cutoff() {
+        let now = Utc::now();
+        assert_eq!(session_cutoff(now, 0), None);
+        assert_eq!(
+            session_cutoff(now, 3600),
+            Some(now - Duration::try_seconds(3600).unwrap())
+        );
+    }
+
+    #[test]
+    fn ut_record_purge() {
+        let before = metrics();
+        record_purge(3, true);
+        record_purge(2, false);
+        record_purge(0, false);
+
+        let after = metrics();
+        assert!(after.flights_archived >= before.flights_archived + 3);
+        assert!(after.flights_deleted >= before.flights_deleted + 2);
+    }
+}
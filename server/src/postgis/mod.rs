@@ -5,23 +5,57 @@ use strum::IntoEnumIterator;
 #[macro_use]
 pub mod macros;
 pub mod aircraft;
+pub mod audit;
+pub mod authorization;
 pub mod best_path;
+pub mod bootstrap;
+pub mod capabilities;
+pub mod constraints;
+pub mod deconfliction;
+pub mod dedupe;
+#[cfg(feature = "export")]
+pub mod export;
 pub mod flight;
+pub mod gaps;
+pub mod identifier;
+pub mod leader;
+pub mod maintenance;
+pub mod noise;
+pub mod partition;
 pub mod pool;
+pub mod published;
+pub mod reachability;
+pub mod reservation;
+pub mod routing;
+pub mod rules;
+pub mod self_test;
+pub mod simplify;
+pub mod snapshot;
+pub mod statements;
+pub mod statistics;
+pub mod sync;
 pub mod utils;
 pub mod vertiport;
+pub mod volume;
 pub mod waypoint;
 pub mod zone;
+pub mod zone_events;
 
 pub use once_cell::sync::OnceCell;
 use std::fmt::{self, Display, Formatter};
 
 /// Global pool for PostgreSQL connections
-pub static DEADPOOL_POSTGIS: OnceCell<deadpool_postgres::Pool> = OnceCell::new();
+pub static DEADPOOL_POSTGIS: OnceCell<pool::FailoverPool> = OnceCell::new();
 
 /// PostgreSQL schema for all tables
 pub const PSQL_SCHEMA: &str = "arrow";
 
+/// Regex for valid schema names
+pub const SCHEMA_REGEX: &str = r"^[a-z_][a-z0-9_]{0,62}$";
+
+/// Schema used in place of [`PSQL_SCHEMA`], set once at startup
+static SCHEMA: OnceCell<String> = OnceCell::new();
+
 /// Default Spatial Reference Identifier
 /// WGS84 with Z axis: <https://spatialreference.org/ref/epsg/4326/>
 pub const DEFAULT_SRID: i32 = 4326;
@@ -49,6 +83,51 @@ pub enum PostgisError {
 
     /// FlightPath Error
     FlightPath(flight::FlightError),
+
+    /// Volume Error
+    Volume(volume::VolumeError),
+
+    /// Noise Area Error
+    Noise(noise::NoiseError),
+
+    /// Deconfliction Error
+    Deconfliction(deconfliction::DeconflictionError),
+
+    /// Zone Authorization Error
+    Authorization(authorization::AuthorizationError),
+
+    /// Airspace Sync Error
+    Sync(sync::SyncError),
+
+    /// Zone Gap Error
+    Gap(gaps::GapError),
+
+    /// Database Capability Error
+    Capability(capabilities::CapabilityError),
+
+    /// Zone Constraint Error
+    Constraint(constraints::ConstraintError),
+
+    /// Best Path Audit Error
+    Audit(audit::AuditError),
+
+    /// Published Waypoint Import Error
+    Published(published::PublishedError),
+
+    /// Statistics Error
+    Statistics(statistics::StatisticsError),
+
+    /// Reachability Error
+    Reachability(reachability::ReachabilityError),
+
+    /// Snapshot Error
+    Snapshot(snapshot::SnapshotError),
+
+    /// Path Segment Error
+    Segment(utils::SegmentError),
+
+    /// Volume Reservation Error
+    Reservation(reservation::ReservationError),
 }
 
 impl std::error::Error for PostgisError {}
@@ -63,6 +142,21 @@ impl Display for PostgisError {
             PostgisError::Zone(e) => write!(f, "Zone Error: {}", e),
             PostgisError::BestPath(e) => write!(f, "BestPath Error: {}", e),
             PostgisError::FlightPath(e) => write!(f, "FlightPath Error: {}", e),
+            PostgisError::Volume(e) => write!(f, "Volume Error: {}", e),
+            PostgisError::Noise(e) => write!(f, "Noise Area Error: {}", e),
+            PostgisError::Deconfliction(e) => write!(f, "Deconfliction Error: {}", e),
+            PostgisError::Authorization(e) => write!(f, "Zone Authorization Error: {}", e),
+            PostgisError::Sync(e) => write!(f, "Airspace Sync Error: {}", e),
+            PostgisError::Gap(e) => write!(f, "Zone Gap Error: {}", e),
+            PostgisError::Capability(e) => write!(f, "Database Capability Error: {}", e),
+            PostgisError::Constraint(e) => write!(f, "Zone Constraint Error: {}", e),
+            PostgisError::Audit(e) => write!(f, "Best Path Audit Error: {}", e),
+            PostgisError::Published(e) => write!(f, "Published Waypoint Import Error: {}", e),
+            PostgisError::Statistics(e) => write!(f, "Statistics Error: {}", e),
+            PostgisError::Reachability(e) => write!(f, "Reachability Error: {}", e),
+            PostgisError::Snapshot(e) => write!(f, "Snapshot Error: {}", e),
+            PostgisError::Segment(e) => write!(f, "Path Segment Error: {}", e),
+            PostgisError::Reservation(e) => write!(f, "Volume Reservation Error: {}", e),
         }
     }
 }
@@ -84,6 +178,15 @@ pub enum PsqlError {
 
     /// Error on commit
     Commit,
+
+    /// Error on statement preparation
+    Prepare,
+
+    /// Invalid schema
+    Schema,
+
+    /// Existing column wider than configured
+    ColumnWidth,
 }
 
 impl Display for PsqlError {
@@ -94,12 +197,34 @@ impl Display for PsqlError {
             PsqlError::Execute => write!(f, "Error on execution"),
             PsqlError::Rollback => write!(f, "Error on rollback"),
             PsqlError::Commit => write!(f, "Error on commit"),
+            PsqlError::Prepare => write!(f, "Error on preparation"),
+            PsqlError::Schema => write!(f, "Invalid schema"),
+            PsqlError::ColumnWidth => write!(f, "Existing column wider than configured"),
         }
     }
 }
 
 impl std::error::Error for PsqlError {}
 
+/// Overrides the schema of all tables
+///  Must be called before any table is used
+pub fn set_schema(schema: &str) -> Result<(), PostgisError> {
+    utils::check_string(schema, SCHEMA_REGEX).map_err(|e| {
+        postgis_error!("invalid schema '{}': {}", schema, e);
+        PostgisError::Psql(PsqlError::Schema)
+    })?;
+
+    SCHEMA.set(schema.to_string()).map_err(|_| {
+        postgis_error!("schema was already set.");
+        PostgisError::Psql(PsqlError::Schema)
+    })
+}
+
+/// Gets the schema of all tables
+pub fn get_schema() -> &'static str {
+    SCHEMA.get().map(String::as_str).unwrap_or(PSQL_SCHEMA)
+}
+
 /// Executes a transaction with multiple statements on the provided pool
 ///  with rollback if any of the statements fail to execute.
 #[cfg(not(tarpaulin_include))]
@@ -167,6 +292,106 @@ where
     declaration
 }
 
+/// Gets the statement widening the VARCHAR columns of a table to
+///  `max_length` characters, given their current widths, or `None` if all
+///  are wide enough.
+/// Columns are never narrowed: existing values could fail the cast, so a
+///  column wider than `max_length` is an error.
+pub fn widen_varchar_statement(
+    table_name: &str,
+    widths: &[(String, Option<i32>)],
+    max_length: u32,
+) -> Result<Option<String>, PostgisError> {
+    let mut alterations = vec![];
+    for (column, width) in widths {
+        let Some(width) = width else {
+            // unbounded VARCHAR or TEXT, nothing to widen
+            continue;
+        };
+
+        let width = u32::try_from(*width).unwrap_or(0);
+        if width > max_length {
+            postgis_error!(
+                "column {table_name}.\"{column}\" is VARCHAR({width}), wider than the configured {max_length}; columns are never narrowed."
+            );
+            return Err(PostgisError::Psql(PsqlError::ColumnWidth));
+        }
+
+        if width < max_length {
+            alterations.push(format!(
+                r#"ALTER COLUMN "{column}" TYPE VARCHAR({max_length})"#
+            ));
+        }
+    }
+
+    if alterations.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(format!(
+        "ALTER TABLE {table_name} {};",
+        alterations.join(", ")
+    )))
+}
+
+/// Gets the statement widening the VARCHAR columns of a table created by
+///  an earlier version with a smaller `max_length`, or `None` if the table
+///  doesn't exist yet or is wide enough.
+/// Altering the type locks the table (and every partition) exclusively,
+///  so it's only done when the configured length grows.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+pub async fn psql_widen_varchar(
+    table: &str,
+    columns: &[&str],
+    max_length: u32,
+) -> Result<Option<String>, PostgisError> {
+    let pool = DEADPOOL_POSTGIS.get().ok_or_else(|| {
+        postgis_error!("could not get psql pool.");
+        PostgisError::Psql(PsqlError::Connection)
+    })?;
+
+    let client = pool.get().await.map_err(|e| {
+        postgis_error!("could not get client from psql connection pool: {}", e);
+        PostgisError::Psql(PsqlError::Client)
+    })?;
+
+    let columns = columns
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+    let widths = client
+        .query(
+            r#"SELECT
+                "column_name"::TEXT,
+                "character_maximum_length"::INTEGER
+            FROM "information_schema"."columns"
+            WHERE "table_schema" = $1
+                AND "table_name" = $2
+                AND "column_name" = ANY($3)
+            ORDER BY "column_name";"#,
+            &[&get_schema(), &table, &columns],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get the widths of {table} columns: {}", e);
+            PostgisError::Psql(PsqlError::Execute)
+        })?
+        .iter()
+        .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+        .collect::<Result<Vec<(String, Option<i32>)>, tokio_postgres::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not read the widths of {table} columns: {}", e);
+            PostgisError::Psql(PsqlError::Execute)
+        })?;
+
+    widen_varchar_statement(
+        &format!(r#""{}"."{table}""#, get_schema()),
+        &widths,
+        max_length,
+    )
+}
+
 /// Initializes the PostgreSQL database with the required tables and enums
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (Rnever) need running postgresql instance, not unit testable
@@ -175,7 +400,13 @@ pub async fn psql_init() -> Result<(), Box<dyn std::error::Error>> {
     vertiport::psql_init().await?;
     aircraft::psql_init().await?;
     waypoint::psql_init().await?;
+    sync::psql_init().await?;
     flight::psql_init().await?;
+    noise::psql_init().await?;
+    authorization::psql_init().await?;
+    reservation::psql_init().await?;
+    audit::psql_init().await?;
+    snapshot::psql_init().await?;
 
     Ok(())
 }
@@ -224,6 +455,117 @@ mod tests {
             error.to_string(),
             format!("FlightPath Error: {}", flight::FlightError::Time)
         );
+
+        let error = PostgisError::Volume(volume::VolumeError::Altitude);
+        assert_eq!(
+            error.to_string(),
+            format!("Volume Error: {}", volume::VolumeError::Altitude)
+        );
+
+        let error = PostgisError::Noise(noise::NoiseError::Altitude);
+        assert_eq!(
+            error.to_string(),
+            format!("Noise Area Error: {}", noise::NoiseError::Altitude)
+        );
+
+        let error = PostgisError::Deconfliction(deconfliction::DeconflictionError::Time);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Deconfliction Error: {}",
+                deconfliction::DeconflictionError::Time
+            )
+        );
+
+        let error = PostgisError::Authorization(authorization::AuthorizationError::Zone);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Zone Authorization Error: {}",
+                authorization::AuthorizationError::Zone
+            )
+        );
+
+        let error = PostgisError::Sync(sync::SyncError::Cursor);
+        assert_eq!(
+            error.to_string(),
+            format!("Airspace Sync Error: {}", sync::SyncError::Cursor)
+        );
+
+        let error = PostgisError::Gap(gaps::GapError::Width);
+        assert_eq!(
+            error.to_string(),
+            format!("Zone Gap Error: {}", gaps::GapError::Width)
+        );
+
+        let error = PostgisError::Capability(capabilities::CapabilityError::Postgis);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Database Capability Error: {}",
+                capabilities::CapabilityError::Postgis
+            )
+        );
+
+        let error = PostgisError::Constraint(constraints::ConstraintError::Location);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Zone Constraint Error: {}",
+                constraints::ConstraintError::Location
+            )
+        );
+
+        let error = PostgisError::Audit(audit::AuditError::Identifier);
+        assert_eq!(
+            error.to_string(),
+            format!("Best Path Audit Error: {}", audit::AuditError::Identifier)
+        );
+
+        let error = PostgisError::Published(published::PublishedError::Format);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Published Waypoint Import Error: {}",
+                published::PublishedError::Format
+            )
+        );
+
+        let error = PostgisError::Statistics(statistics::StatisticsError::Window);
+        assert_eq!(
+            error.to_string(),
+            format!("Statistics Error: {}", statistics::StatisticsError::Window)
+        );
+
+        let error = PostgisError::Reachability(reachability::ReachabilityError::Profile);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Reachability Error: {}",
+                reachability::ReachabilityError::Profile
+            )
+        );
+
+        let error = PostgisError::Snapshot(snapshot::SnapshotError::Time);
+        assert_eq!(
+            error.to_string(),
+            format!("Snapshot Error: {}", snapshot::SnapshotError::Time)
+        );
+
+        let error = PostgisError::Segment(utils::SegmentError::TimeWindow);
+        assert_eq!(
+            error.to_string(),
+            format!("Path Segment Error: {}", utils::SegmentError::TimeWindow)
+        );
+
+        let error = PostgisError::Reservation(reservation::ReservationError::Conflict);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Volume Reservation Error: {}",
+                reservation::ReservationError::Conflict
+            )
+        );
     }
 
     #[test]
@@ -242,6 +584,61 @@ mod tests {
 
         let error = PsqlError::Commit;
         assert_eq!(error.to_string(), "Error on commit");
+
+        let error = PsqlError::Prepare;
+        assert_eq!(error.to_string(), "Error on preparation");
+
+        let error = PsqlError::Schema;
+        assert_eq!(error.to_string(), "Invalid schema");
+
+        let error = PsqlError::ColumnWidth;
+        assert_eq!(error.to_string(), "Existing column wider than configured");
+    }
+
+    #[test]
+    fn test_widen_varchar_statement() {
+        let table = r#""arrow"."aircraft""#;
+        let widths = |a: Option<i32>, b: Option<i32>| {
+            vec![("identifier".to_string(), a), ("session_id".to_string(), b)]
+        };
+
+        // new table or already as wide
+        assert_eq!(widen_varchar_statement(table, &[], 255).unwrap(), None);
+        assert_eq!(
+            widen_varchar_statement(table, &widths(Some(255), None), 255).unwrap(),
+            None
+        );
+
+        // tables of earlier versions
+        assert_eq!(
+            widen_varchar_statement(table, &widths(Some(20), Some(255)), 255).unwrap(),
+            Some(
+                r#"ALTER TABLE "arrow"."aircraft" ALTER COLUMN "identifier" TYPE VARCHAR(255);"#
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            widen_varchar_statement(table, &widths(Some(20), Some(20)), 255).unwrap(),
+            Some(
+                r#"ALTER TABLE "arrow"."aircraft" ALTER COLUMN "identifier" TYPE VARCHAR(255), ALTER COLUMN "session_id" TYPE VARCHAR(255);"#
+                    .to_string()
+            )
+        );
+
+        // lowered below existing columns
+        let error = widen_varchar_statement(table, &widths(Some(255), Some(20)), 100).unwrap_err();
+        assert_eq!(error, PostgisError::Psql(PsqlError::ColumnWidth));
+    }
+
+    #[test]
+    fn test_set_schema_invalid() {
+        let error = set_schema(r#"arrow"; DROP TABLE "zones"#).unwrap_err();
+        assert_eq!(error, PostgisError::Psql(PsqlError::Schema));
+
+        let error = set_schema("Arrow").unwrap_err();
+        assert_eq!(error, PostgisError::Psql(PsqlError::Schema));
+
+        assert_eq!(get_schema(), PSQL_SCHEMA);
     }
 
     #[test]
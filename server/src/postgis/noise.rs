@@ -0,0 +1,480 @@
+//! Noise-sensitive areas, where low overflights are limited during quiet hours.
+//!
+//! Unlike zones, noise areas don't block a path. Flying through one below
+//!  its altitude floor while its quiet hours are in effect adds to the noise
+//!  cost of the path, so that [`best_path`](super::best_path) prefers routes
+//!  around the area when the noise objective is weighted.
+
+use super::identifier::{check_identifier, Entity};
+use super::statements::Statement;
+use super::utils::{field_violation, vertices_field};
+use super::zone::schedule_from_windows;
+use super::{OnceCell, PostgisError, DEFAULT_SRID};
+use crate::grpc::server::grpc_server::FieldViolation;
+use crate::grpc::server::grpc_server::NoiseArea as RequestNoiseArea;
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Utc};
+use postgis::ewkb::{LineStringT, PointZ, PolygonZ};
+use std::fmt::{self, Display, Formatter};
+
+/// Possible errors with noise area requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NoiseError {
+    /// No noise areas were provided
+    NoAreas,
+
+    /// Invalid identifier
+    Identifier,
+
+    /// Invalid area vertices
+    Location,
+
+    /// Invalid altitude floor
+    Altitude,
+
+    /// Invalid quiet hours
+    QuietHours,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for NoiseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            NoiseError::NoAreas => write!(f, "No noise areas were provided."),
+            NoiseError::Identifier => write!(f, "Invalid identifier provided."),
+            NoiseError::Location => write!(f, "Invalid vertices provided."),
+            NoiseError::Altitude => write!(f, "Invalid altitude floor provided."),
+            NoiseError::QuietHours => write!(f, "Invalid quiet hours provided."),
+            NoiseError::Client => write!(f, "Could not get backend client."),
+            NoiseError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// A noise-sensitive area
+#[derive(Debug, Clone)]
+pub struct NoiseArea {
+    /// Unique identifier
+    pub identifier: String,
+
+    /// Outline of the area, at the altitude floor
+    pub geom: PolygonZ,
+
+    /// Flights below this altitude count as crossing the area
+    pub altitude_meters_floor: f32,
+
+    /// Quiet hours as [start, end) pairs of minutes into the week
+    pub quiet_hours: Vec<i32>,
+}
+
+impl TryFrom<RequestNoiseArea> for NoiseArea {
+    type Error = NoiseError;
+
+    fn try_from(area: RequestNoiseArea) -> Result<Self, Self::Error> {
+        check_identifier(Entity::NoiseArea, &area.identifier).map_err(|e| {
+            postgis_error!("Invalid identifier: {}; {}", area.identifier, e);
+            NoiseError::Identifier
+        })?;
+
+        if !area.altitude_meters_floor.is_finite() || area.altitude_meters_floor < 0.0 {
+            postgis_error!("Invalid altitude floor: {}", area.altitude_meters_floor);
+            return Err(NoiseError::Altitude);
+        }
+
+        let geom =
+            super::utils::polygon_from_vertices_z(&area.vertices, area.altitude_meters_floor)
+                .map_err(|e| {
+                    postgis_error!("Error converting noise area polygon: {}", e.to_string());
+                    NoiseError::Location
+                })?;
+
+        let quiet_hours = schedule_from_windows(&area.quiet_hours).map_err(|_| {
+            postgis_error!("Invalid quiet hours: {:?}", area.quiet_hours);
+            NoiseError::QuietHours
+        })?;
+
+        Ok(NoiseArea {
+            identifier: area.identifier,
+            geom,
+            altitude_meters_floor: area.altitude_meters_floor,
+            quiet_hours,
+        })
+    }
+}
+
+/// Describes why a noise area of an update was rejected
+fn area_violation(index: usize, area: &RequestNoiseArea, error: NoiseError) -> FieldViolation {
+    let field = |name: &str| format!("areas[{index}].{name}");
+    let violation =
+        |name: &str| field_violation(field(name), "noise area", &area.identifier, error);
+    match error {
+        NoiseError::Identifier => violation("identifier"),
+        NoiseError::Altitude => violation("altitude_meters_floor"),
+        NoiseError::QuietHours => violation("quiet_hours"),
+        NoiseError::Location => {
+            super::utils::polygon_from_vertices_z(&area.vertices, area.altitude_meters_floor)
+                .err()
+                .map(|e| {
+                    field_violation(
+                        vertices_field(&field("vertices"), &e),
+                        "noise area",
+                        &area.identifier,
+                        e,
+                    )
+                })
+                .unwrap_or_else(|| violation("vertices"))
+        }
+        _ => field_violation(
+            format!("areas[{index}]"),
+            "noise area",
+            &area.identifier,
+            error,
+        ),
+    }
+}
+
+/// Gets the noise areas of an update that would be rejected, and why
+pub fn violations(areas: &[RequestNoiseArea]) -> Vec<FieldViolation> {
+    areas
+        .iter()
+        .enumerate()
+        .filter_map(|(index, area)| {
+            NoiseArea::try_from(area.clone())
+                .err()
+                .map(|e| area_violation(index, area, e))
+        })
+        .collect()
+}
+
+/// Gets the name of this module's table
+pub(super) fn get_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."noise_areas""#, super::get_schema()))
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Noise(NoiseError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Noise(NoiseError::Client)
+        })
+}
+
+/// Initialize the noise areas table in the PostGIS database
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn psql_init() -> Result<(), PostgisError> {
+    let statements = vec![
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {table_name} (
+            "identifier" VARCHAR(255) UNIQUE NOT NULL PRIMARY KEY,
+            "geom" GEOMETRY(POLYGON, {DEFAULT_SRID}) NOT NULL,
+            "altitude_meters_floor" FLOAT(4) NOT NULL,
+            "quiet_hours" INTEGER[] NOT NULL DEFAULT '{{}}',
+            "last_updated" TIMESTAMPTZ
+        );"#,
+            table_name = get_table_name()
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "noise_areas_geom_idx" ON {table_name} USING GIST ("geom");"#,
+            table_name = get_table_name()
+        ),
+    ];
+
+    super::psql_transaction(statements).await
+}
+
+/// Inserts or updates noise areas in the PostGIS database
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn update_noise_areas(areas: Vec<RequestNoiseArea>) -> Result<(), PostgisError> {
+    postgis_debug!("entry.");
+    if areas.is_empty() {
+        postgis_error!("no noise areas provided.");
+        return Err(PostgisError::Noise(NoiseError::NoAreas));
+    }
+
+    let areas: Vec<NoiseArea> = areas
+        .into_iter()
+        .map(NoiseArea::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(PostgisError::Noise)?;
+
+    let mut client = get_client().await?;
+    let transaction = client.transaction().await.map_err(|e| {
+        postgis_error!("could not create transaction: {}", e);
+        PostgisError::Noise(NoiseError::DBError)
+    })?;
+
+    let stmt = transaction
+        .prepare_cached(&Statement::UpsertNoiseArea.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Noise(NoiseError::DBError)
+        })?;
+
+    for area in &areas {
+        transaction
+            .execute(
+                &stmt,
+                &[
+                    &area.identifier,
+                    &area.geom,
+                    &area.altitude_meters_floor,
+                    &area.quiet_hours,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                postgis_error!("could not execute transaction: {}", e);
+                PostgisError::Noise(NoiseError::DBError)
+            })?;
+    }
+
+    transaction.commit().await.map_err(|e| {
+        postgis_error!("could not commit transaction: {}", e);
+        PostgisError::Noise(NoiseError::DBError)
+    })?;
+
+    postgis_debug!("success.");
+    Ok(())
+}
+
+/// Length of path flown through noise areas whose quiet hours fall within
+///  the time window, given the quiet hours of each area crossed and the
+///  distance flown through it
+fn quiet_crossing_meters(
+    crossings: &[(Vec<i32>, f64)],
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> f32 {
+    crossings
+        .iter()
+        .filter(|(quiet_hours, _)| {
+            super::zone::schedule_overlaps(quiet_hours, time_start, time_end)
+        })
+        .map(|(_, meters)| *meters as f32)
+        .sum()
+}
+
+/// Gets the distance a path flies through noise areas, below their
+///  altitude floor, during their quiet hours
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub(super) async fn get_noise_exposure(
+    client: &Object,
+    points: &[PointZ],
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Result<f32, PostgisError> {
+    let geom = LineStringT {
+        points: points.to_vec(),
+        srid: Some(DEFAULT_SRID),
+    };
+
+    let stmt = client
+        .prepare_cached(&Statement::GetNoiseCrossings.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Noise(NoiseError::DBError)
+        })?;
+
+    let crossings = client
+        .query(&stmt, &[&geom])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute query: {}", e);
+            PostgisError::Noise(NoiseError::DBError)
+        })?
+        .into_iter()
+        .map(|row| {
+            Ok((
+                row.try_get::<_, Vec<i32>>("quiet_hours")?,
+                row.try_get::<_, f64>("crossing_meters")?,
+            ))
+        })
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not parse noise crossings: {}", e);
+            PostgisError::Noise(NoiseError::DBError)
+        })?;
+
+    Ok(quiet_crossing_meters(&crossings, time_start, time_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::server::grpc_server::{ActiveWindow, Coordinates, Weekday};
+    use lib_common::time::Duration;
+
+    fn vertices() -> Vec<Coordinates> {
+        [(52.37, 4.90), (52.37, 4.92), (52.38, 4.92), (52.37, 4.90)]
+            .iter()
+            .map(|&(latitude, longitude)| Coordinates {
+                latitude,
+                longitude,
+            })
+            .collect()
+    }
+
+    fn request() -> RequestNoiseArea {
+        RequestNoiseArea {
+            identifier: "residential-1".to_string(),
+            vertices: vertices(),
+            altitude_meters_floor: 100.0,
+            quiet_hours: vec![ActiveWindow {
+                days: vec![Weekday::Monday as i32],
+                start_minute: 22 * 60,
+                end_minute: 6 * 60,
+            }],
+        }
+    }
+
+    #[test]
+    fn ut_noise_area_try_from() {
+        let area = NoiseArea::try_from(request()).unwrap();
+        assert_eq!(area.identifier, "residential-1");
+        assert_eq!(area.altitude_meters_floor, 100.0);
+        assert_eq!(area.geom.rings[0].points.len(), 4);
+        assert_eq!(area.quiet_hours, vec![22 * 60, 30 * 60]);
+
+        let error = NoiseArea::try_from(RequestNoiseArea {
+            identifier: "".to_string(),
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, NoiseError::Identifier);
+
+        let error = NoiseArea::try_from(RequestNoiseArea {
+            vertices: vertices()[..3].to_vec(),
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, NoiseError::Location);
+
+        for altitude_meters_floor in [-1.0, f32::NAN] {
+            let error = NoiseArea::try_from(RequestNoiseArea {
+                altitude_meters_floor,
+                ..request()
+            })
+            .unwrap_err();
+            assert_eq!(error, NoiseError::Altitude);
+        }
+
+        let error = NoiseArea::try_from(RequestNoiseArea {
+            quiet_hours: vec![ActiveWindow {
+                days: vec![],
+                start_minute: 0,
+                end_minute: 60,
+            }],
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, NoiseError::QuietHours);
+    }
+
+    #[test]
+    fn ut_violations() {
+        let areas = vec![
+            request(),
+            RequestNoiseArea {
+                identifier: "residential-2".to_string(),
+                vertices: vertices()[..3].to_vec(),
+                ..request()
+            },
+            RequestNoiseArea {
+                identifier: "residential-3".to_string(),
+                altitude_meters_floor: -1.0,
+                ..request()
+            },
+        ];
+
+        let violations = violations(&areas);
+        assert_eq!(
+            violations,
+            vec![
+                FieldViolation {
+                    field: "areas[1].vertices".to_string(),
+                    description: format!(
+                        "noise area 'residential-2': {}",
+                        crate::postgis::utils::PolygonError::VertexCount
+                    ),
+                },
+                FieldViolation {
+                    field: "areas[2].altitude_meters_floor".to_string(),
+                    description: format!("noise area 'residential-3': {}", NoiseError::Altitude),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ut_quiet_crossing_meters() {
+        let monday_night = request().quiet_hours;
+        let nightly = schedule_from_windows(&monday_night).unwrap();
+        let crossings = vec![(nightly, 100.0), (vec![], 50.0)];
+
+        // 2023-10-02 is a Monday
+        let night = DateTime::parse_from_rfc3339("2023-10-02T23:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let time_end = night + Duration::try_minutes(30).unwrap();
+        assert_eq!(quiet_crossing_meters(&crossings, night, time_end), 150.0);
+
+        // only the area that is always quiet counts at noon
+        let noon = DateTime::parse_from_rfc3339("2023-10-02T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let time_end = noon + Duration::try_minutes(30).unwrap();
+        assert_eq!(quiet_crossing_meters(&crossings, noon, time_end), 50.0);
+
+        assert_eq!(quiet_crossing_meters(&[], night, time_end), 0.0);
+    }
+
+    #[test]
+    fn test_noise_error_display() {
+        assert_eq!(
+            NoiseError::NoAreas.to_string(),
+            "No noise areas were provided."
+        );
+        assert_eq!(
+            NoiseError::Identifier.to_string(),
+            "Invalid identifier provided."
+        );
+        assert_eq!(
+            NoiseError::Location.to_string(),
+            "Invalid vertices provided."
+        );
+        assert_eq!(
+            NoiseError::Altitude.to_string(),
+            "Invalid altitude floor provided."
+        );
+        assert_eq!(
+            NoiseError::QuietHours.to_string(),
+            "Invalid quiet hours provided."
+        );
+        assert_eq!(
+            NoiseError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(NoiseError::DBError.to_string(), "Database error.");
+    }
+}
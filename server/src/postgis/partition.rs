@@ -0,0 +1,286 @@
+//! Spatial partitioning of the aircraft table
+//!
+//! With tens of thousands of aircraft reporting, a single aircraft table and
+//!  its indices become the write hotspot. The table can instead be split
+//!  into declarative partitions, one per cell of a latitude/longitude grid.
+//!  Each position upsert carries the cell of the new position, so PostgreSQL
+//!  routes the row to its partition (moving it across partitions when the
+//!  aircraft crosses a cell border), and queries restricted to a bounding box
+//!  only scan the partitions of the cells it overlaps.
+//!
+//! Aircraft that have not reported a position yet are kept in the default
+//!  partition, under [`UNPLACED_REGION`].
+//!
+//! The layout is chosen when the table is created. [`psql_statements`] fails
+//!  the boot if the existing table doesn't match the configured layout,
+//!  since switching layouts requires migrating the table.
+
+use super::aircraft::AircraftError;
+use super::{OnceCell, PostgisError};
+
+/// Default size of a grid cell in degrees, zero to leave the table unpartitioned
+pub const DEFAULT_CELL_DEGREES: u32 = 0;
+
+/// Smallest supported cell size, bounding the number of partitions
+pub const MIN_CELL_DEGREES: u32 = 10;
+
+/// Region of aircraft without a position
+pub const UNPLACED_REGION: i32 = -1;
+
+/// Size of a grid cell in degrees, set once at startup
+static CELL_DEGREES: OnceCell<u32> = OnceCell::new();
+
+/// A latitude/longitude grid of square cells
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Grid {
+    cell_degrees: u32,
+}
+
+impl Grid {
+    /// Creates a grid of cells of the provided size, which must divide
+    ///  180 degrees and be at least [`MIN_CELL_DEGREES`]
+    pub fn new(cell_degrees: u32) -> Option<Self> {
+        if !(MIN_CELL_DEGREES..=180).contains(&cell_degrees) || 180 % cell_degrees != 0 {
+            return None;
+        }
+
+        Some(Grid { cell_degrees })
+    }
+
+    /// Number of cells along a parallel
+    fn columns(&self) -> i32 {
+        (360 / self.cell_degrees) as i32
+    }
+
+    /// Number of cells along a meridian
+    fn rows(&self) -> i32 {
+        (180 / self.cell_degrees) as i32
+    }
+
+    /// Number of regions, each with its own partition
+    pub fn count(&self) -> i32 {
+        self.columns() * self.rows()
+    }
+
+    /// Column of a longitude, the antimeridian belongs to the last column
+    fn column(&self, longitude: f64) -> i32 {
+        let column = ((longitude + 180.0) / self.cell_degrees as f64).floor() as i32;
+        column.clamp(0, self.columns() - 1)
+    }
+
+    /// Row of a latitude, the north pole belongs to the last row
+    fn row(&self, latitude: f64) -> i32 {
+        let row = ((latitude + 90.0) / self.cell_degrees as f64).floor() as i32;
+        row.clamp(0, self.rows() - 1)
+    }
+
+    /// Gets the region of a position
+    pub fn region(&self, longitude: f64, latitude: f64) -> i32 {
+        self.row(latitude) * self.columns() + self.column(longitude)
+    }
+
+    /// Gets the regions overlapping a bounding box
+    pub fn regions_in_box(
+        &self,
+        min_longitude: f64,
+        min_latitude: f64,
+        max_longitude: f64,
+        max_latitude: f64,
+    ) -> Vec<i32> {
+        let columns = self.column(min_longitude)..=self.column(max_longitude);
+        (self.row(min_latitude)..=self.row(max_latitude))
+            .flat_map(|row| {
+                columns
+                    .clone()
+                    .map(move |column| row * self.columns() + column)
+            })
+            .collect()
+    }
+
+    /// The table comment recording the layout
+    fn comment(&self) -> String {
+        format!("partitioned by {} degree cells", self.cell_degrees)
+    }
+}
+
+/// Sets the size of a grid cell, enabling the partitioning
+///  Must be called before [`psql_init`](super::aircraft::psql_init)
+pub fn set_cell_degrees(cell_degrees: u32) -> Result<(), PostgisError> {
+    if cell_degrees != 0 && Grid::new(cell_degrees).is_none() {
+        postgis_error!(
+            "cell size ({cell_degrees}) must divide 180 degrees and be at least {MIN_CELL_DEGREES}."
+        );
+        return Err(PostgisError::Aircraft(AircraftError::Partitioning));
+    }
+
+    CELL_DEGREES.set(cell_degrees).map_err(|_| {
+        postgis_error!("cell size was already set.");
+        PostgisError::Aircraft(AircraftError::Partitioning)
+    })
+}
+
+/// Gets the grid the aircraft table is partitioned by, if any
+pub fn get_grid() -> Option<Grid> {
+    Grid::new(*CELL_DEGREES.get().unwrap_or(&DEFAULT_CELL_DEGREES))
+}
+
+/// Gets the region a position is stored under
+pub fn get_region(grid: Option<Grid>, longitude: f64, latitude: f64) -> i32 {
+    grid.map_or(UNPLACED_REGION, |grid| grid.region(longitude, latitude))
+}
+
+/// Gets the name of the partition of a region
+fn get_partition_name(region: Option<i32>) -> String {
+    let suffix = match region {
+        Some(region) => format!("r{region}"),
+        None => "unplaced".to_string(),
+    };
+
+    format!(r#""{}"."aircraft_{suffix}""#, super::get_schema())
+}
+
+/// Statements creating the aircraft table with the provided columns, and
+///  the partitions of the grid if any, then checking that the table has
+///  the expected layout
+pub fn psql_statements(table_name: &str, columns: &str, grid: Option<Grid>) -> Vec<String> {
+    let Some(grid) = grid else {
+        return vec![
+            format!(
+                r#"CREATE TABLE IF NOT EXISTS {table_name} (
+                    {columns},
+                    PRIMARY KEY ("identifier"),
+                    UNIQUE ("session_id")
+                );"#
+            ),
+            layout_check(table_name, None),
+        ];
+    };
+
+    let comment = grid.comment();
+    let mut statements = vec![format!(
+        r#"DO $$
+        BEGIN
+            IF to_regclass('{table_name}') IS NULL THEN
+                CREATE TABLE {table_name} (
+                    {columns},
+                    PRIMARY KEY ("identifier", "region"),
+                    UNIQUE ("session_id", "region")
+                ) PARTITION BY LIST ("region");
+                COMMENT ON TABLE {table_name} IS '{comment}';
+            END IF;
+        END $$;"#
+    )];
+
+    statements.push(layout_check(table_name, Some(grid)));
+    statements.push(format!(
+        r#"CREATE TABLE IF NOT EXISTS {partition_name}
+            PARTITION OF {table_name} DEFAULT;"#,
+        partition_name = get_partition_name(None)
+    ));
+
+    statements.extend((0..grid.count()).map(|region| {
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {partition_name}
+                PARTITION OF {table_name} FOR VALUES IN ({region});"#,
+            partition_name = get_partition_name(Some(region))
+        )
+    }));
+
+    statements
+}
+
+/// A statement failing if the table isn't laid out as configured
+fn layout_check(table_name: &str, grid: Option<Grid>) -> String {
+    let partitioned = grid.is_some();
+    let comment = match grid {
+        Some(grid) => format!("'{}'", grid.comment()),
+        None => "NULL".to_string(),
+    };
+
+    format!(
+        r#"DO $$
+        BEGIN
+            IF EXISTS (
+                SELECT 1 FROM pg_partitioned_table
+                WHERE "partrelid" = '{table_name}'::regclass
+            ) <> {partitioned} THEN
+                RAISE EXCEPTION 'table {table_name} must be migrated to partitioned = {partitioned}';
+            END IF;
+
+            IF {partitioned} AND obj_description('{table_name}'::regclass, 'pg_class')
+                IS DISTINCT FROM {comment} THEN
+                RAISE EXCEPTION 'table {table_name} is not partitioned by the configured cells';
+            END IF;
+        END $$;"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_grid_new() {
+        assert!(Grid::new(0).is_none());
+        assert!(Grid::new(5).is_none());
+        assert!(Grid::new(25).is_none());
+        assert!(Grid::new(360).is_none());
+        assert_eq!(Grid::new(30).unwrap().count(), 12 * 6);
+        assert_eq!(Grid::new(180).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn ut_grid_region() {
+        let grid = Grid::new(30).unwrap();
+        assert_eq!(grid.region(-180.0, -90.0), 0);
+        assert_eq!(grid.region(180.0, 90.0), grid.count() - 1);
+        assert_eq!(grid.region(-179.0, -59.0), 12);
+
+        // Amsterdam and Berlin share a cell, Sydney doesn't
+        assert_eq!(grid.region(4.9, 52.37), grid.region(13.4, 52.52));
+        assert_ne!(grid.region(4.9, 52.37), grid.region(151.2, -33.87));
+
+        assert_eq!(get_region(None, 4.9, 52.37), UNPLACED_REGION);
+        assert_eq!(get_region(Some(grid), 4.9, 52.37), grid.region(4.9, 52.37));
+    }
+
+    #[test]
+    fn ut_grid_regions_in_box() {
+        let grid = Grid::new(30).unwrap();
+
+        // A city-sized box only needs the partition of its cell
+        let regions = grid.regions_in_box(4.7, 52.2, 5.1, 52.5);
+        assert_eq!(regions, vec![grid.region(4.9, 52.37)]);
+
+        // A box over a cell corner needs the four cells around it
+        let mut regions = grid.regions_in_box(-1.0, 29.0, 1.0, 31.0);
+        regions.sort();
+        let mut expected = vec![
+            grid.region(-1.0, 29.0),
+            grid.region(1.0, 29.0),
+            grid.region(-1.0, 31.0),
+            grid.region(1.0, 31.0),
+        ];
+        expected.sort();
+        assert_eq!(regions, expected);
+    }
+
+    #[test]
+    fn ut_psql_statements() {
+        let table_name = r#""arrow"."aircraft""#;
+        let statements = psql_statements(table_name, r#""identifier" TEXT"#, None);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].contains(r#"PRIMARY KEY ("identifier")"#));
+        assert!(statements[1].contains("<> false"));
+
+        let grid = Grid::new(30).unwrap();
+        let statements = psql_statements(table_name, r#""identifier" TEXT"#, Some(grid));
+        assert_eq!(statements.len(), 3 + grid.count() as usize);
+        assert!(statements[0].contains(r#"PARTITION BY LIST ("region")"#));
+        assert!(statements[1].contains("'partitioned by 30 degree cells'"));
+        assert!(statements[2].contains(r#""arrow"."aircraft_unplaced""#));
+        assert!(statements[2].contains("DEFAULT"));
+        assert!(statements[3].contains(r#""arrow"."aircraft_r0""#));
+        assert!(statements[3].contains("FOR VALUES IN (0)"));
+    }
+}
@@ -1,7 +1,10 @@
 //! Secure connections to the PostGIS database
 //!
+//! A standby endpoint can be configured next to the primary. The active
+//!  endpoint is health-checked periodically, and when it stops accepting
+//!  writes the pool is rebuilt against the next endpoint that does.
 
-use deadpool_postgres::{ManagerConfig, Pool, RecyclingMethod, Runtime};
+use deadpool_postgres::{ManagerConfig, Object, Pool, RecyclingMethod, Runtime};
 use native_tls::{Certificate, Identity, TlsConnector};
 use postgres_native_tls::MakeTlsConnector;
 // use tokio_postgres::tls::MakeTlsConnect;
@@ -9,6 +12,11 @@ use postgres_native_tls::MakeTlsConnector;
 use crate::config::Config;
 use std::fmt::{self, Display, Formatter};
 use std::fs;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Max time allowed for a health check of an endpoint
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
 
 /// Errors that can occur when creating a connection pool
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -33,6 +41,9 @@ pub enum PoolError {
 
     /// Unable to create pool connection
     Connection,
+
+    /// None of the configured endpoints accepts writes
+    NoHealthyEndpoint,
 }
 
 impl Display for PoolError {
@@ -45,6 +56,7 @@ impl Display for PoolError {
             PoolError::Identity => write!(f, "unable to create identity"),
             PoolError::Builder => write!(f, "unable to build connector"),
             PoolError::Connection => write!(f, "unable to create pool connection"),
+            PoolError::NoHealthyEndpoint => write!(f, "no healthy database endpoint"),
         }
     }
 }
@@ -126,6 +138,184 @@ pub fn create_pool(mut config: Config) -> Result<Pool, PoolError> {
         })
 }
 
+/// The pool currently in use and the endpoint it connects to
+#[derive(Debug)]
+struct ActivePool {
+    endpoint: usize,
+    pool: Pool,
+}
+
+/// A connection pool to the primary PostGIS endpoint, rebuilt against
+///  the standby endpoint when the primary fails its health check
+#[derive(Debug)]
+pub struct FailoverPool {
+    /// Configuration of each endpoint, the primary first
+    endpoints: Vec<Config>,
+
+    /// The pool in use
+    active: RwLock<ActivePool>,
+}
+
+impl FailoverPool {
+    /// Creates a pool to the primary endpoint. No connection is made until
+    ///  the first client is requested.
+    pub fn new(config: Config) -> Result<Self, PoolError> {
+        let endpoints = endpoint_configs(config);
+        let pool = create_pool(endpoints[0].clone())?;
+
+        Ok(FailoverPool {
+            endpoints,
+            active: RwLock::new(ActivePool { endpoint: 0, pool }),
+        })
+    }
+
+    /// Creates a pool to a database accepting plain connections, such as
+    ///  the throwaway containers of the integration tests. The standby
+    ///  endpoint is ignored.
+    #[cfg(feature = "integration_test")]
+    pub fn without_tls(mut config: Config) -> Result<Self, PoolError> {
+        config.pg.manager = Some(ManagerConfig {
+            recycling_method: RecyclingMethod::Fast,
+        });
+
+        let pool = config
+            .pg
+            .create_pool(Some(Runtime::Tokio1), tokio_postgres::NoTls)
+            .map_err(|e| {
+                postgis_error!("(without_tls) unable to create pool connection: {}", e);
+                PoolError::Connection
+            })?;
+
+        Ok(FailoverPool {
+            endpoints: vec![config],
+            active: RwLock::new(ActivePool { endpoint: 0, pool }),
+        })
+    }
+
+    /// Gets a client from the active pool
+    pub async fn get(&self) -> Result<Object, deadpool_postgres::PoolError> {
+        let pool = self.active.read().await.pool.clone();
+        pool.get().await
+    }
+
+    /// Index of the active endpoint, 0 being the primary
+    pub async fn endpoint(&self) -> usize {
+        self.active.read().await.endpoint
+    }
+
+    /// Checks that the endpoint behind a pool accepts writes
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) need running postgresql instance
+    async fn is_healthy(pool: &Pool) -> bool {
+        let check = async {
+            let client = pool.get().await.map_err(|e| e.to_string())?;
+            let row = client
+                .query_one("SELECT pg_is_in_recovery();", &[])
+                .await
+                .map_err(|e| e.to_string())?;
+
+            row.try_get::<_, bool>(0).map_err(|e| e.to_string())
+        };
+
+        match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check).await {
+            Ok(Ok(in_recovery)) => !in_recovery,
+            Ok(Err(e)) => {
+                postgis_warn!("health check failed: {e}");
+                false
+            }
+            Err(_) => {
+                postgis_warn!("health check timed out.");
+                false
+            }
+        }
+    }
+
+    /// Checks the active endpoint, failing over to the next healthy one
+    ///  if it doesn't accept writes
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) need running postgresql instance
+    pub async fn check(&self) -> Result<(), PoolError> {
+        let (endpoint, pool) = {
+            let active = self.active.read().await;
+            (active.endpoint, active.pool.clone())
+        };
+
+        if FailoverPool::is_healthy(&pool).await {
+            return Ok(());
+        }
+
+        postgis_error!("database endpoint {endpoint} is unhealthy, failing over.");
+        for candidate in failover_order(endpoint, self.endpoints.len()) {
+            let Ok(pool) = create_pool(self.endpoints[candidate].clone()) else {
+                continue;
+            };
+
+            if !FailoverPool::is_healthy(&pool).await {
+                postgis_warn!("database endpoint {candidate} is unhealthy.");
+                continue;
+            }
+
+            let mut active = self.active.write().await;
+            active.pool.close();
+            *active = ActivePool {
+                endpoint: candidate,
+                pool,
+            };
+
+            postgis_info!("failed over from database endpoint {endpoint} to {candidate}.");
+            return Ok(());
+        }
+
+        Err(PoolError::NoHealthyEndpoint)
+    }
+}
+
+/// The configuration of each endpoint, with the standby after the primary.
+///  Connection settings not given for the standby are those of the primary.
+fn endpoint_configs(config: Config) -> Vec<Config> {
+    let standby = config.pg_standby.clone().map(|mut pg| {
+        let primary = &config.pg;
+        pg.user = pg.user.or_else(|| primary.user.clone());
+        pg.password = pg.password.or_else(|| primary.password.clone());
+        pg.dbname = pg.dbname.or_else(|| primary.dbname.clone());
+        pg.port = pg.port.or(primary.port);
+        pg.ssl_mode = pg.ssl_mode.or(primary.ssl_mode);
+        pg.pool = pg.pool.or(primary.pool);
+
+        Config {
+            pg,
+            ..config.clone()
+        }
+    });
+
+    std::iter::once(config).chain(standby).collect()
+}
+
+/// The endpoints to try when the active one fails, ending with a rebuilt
+///  pool to the active endpoint itself
+fn failover_order(active: usize, count: usize) -> Vec<usize> {
+    (1..=count).map(|i| (active + i) % count).collect()
+}
+
+/// Periodically checks the health of [`DEADPOOL_POSTGIS`](super::DEADPOOL_POSTGIS)
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+pub async fn monitor(interval: Duration) {
+    let Some(pool) = super::DEADPOOL_POSTGIS.get() else {
+        postgis_error!("could not get psql pool.");
+        return;
+    };
+
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if let Err(e) = pool.check().await {
+            postgis_error!("{e}");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,6 +346,60 @@ mod tests {
         // assert_eq!(error, PoolError::AuthorityPem);
     }
 
+    #[test]
+    fn test_failover_pool_invalid_db_ca_cert() {
+        let mut config = Config::new();
+        config.db_ca_cert = "/".to_string(); // invalid
+        let Err(error) = FailoverPool::new(config) else {
+            panic!("expected an error");
+        };
+
+        assert_eq!(error, PoolError::AuthorityCertificate);
+    }
+
+    #[test]
+    fn test_endpoint_configs() {
+        let mut config = Config::new();
+        config.pg.host = Some("primary".to_string());
+        config.pg.dbname = Some("gis".to_string());
+        config.pg.port = Some(5432);
+        assert_eq!(endpoint_configs(config.clone()).len(), 1);
+
+        let mut standby = deadpool_postgres::Config::new();
+        standby.host = Some("standby".to_string());
+        standby.port = Some(5433);
+        config.pg_standby = Some(standby);
+
+        let endpoints = endpoint_configs(config);
+        let hosts: Vec<(Option<String>, Option<String>, Option<u16>)> = endpoints
+            .into_iter()
+            .map(|c| (c.pg.host, c.pg.dbname, c.pg.port))
+            .collect();
+
+        assert_eq!(
+            hosts,
+            vec![
+                (
+                    Some("primary".to_string()),
+                    Some("gis".to_string()),
+                    Some(5432)
+                ),
+                (
+                    Some("standby".to_string()),
+                    Some("gis".to_string()),
+                    Some(5433)
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failover_order() {
+        assert_eq!(failover_order(0, 1), vec![0]);
+        assert_eq!(failover_order(0, 2), vec![1, 0]);
+        assert_eq!(failover_order(1, 2), vec![0, 1]);
+    }
+
     #[test]
     fn test_pool_error_display() {
         assert_eq!(
@@ -177,5 +421,9 @@ mod tests {
             PoolError::Connection.to_string(),
             "unable to create pool connection"
         );
+        assert_eq!(
+            PoolError::NoHealthyEndpoint.to_string(),
+            "no healthy database endpoint"
+        );
     }
 }
@@ -0,0 +1,448 @@
+//! Imports published waypoints from route network exports.
+//!
+//! Reporting points and waypoints published by openAIP or in DAFIF are
+//!  recognized by pilots and controllers, unlike the waypoints generated
+//!  around zones. Imported waypoints are tagged with their source, and an
+//!  import replaces the waypoints previously imported from the same source.
+
+use super::statements::Statement;
+use super::waypoint::Waypoint;
+use super::PostgisError;
+use crate::grpc::server::grpc_server::{
+    Coordinates, ImportWaypointsRequest, ImportWaypointsSummary, Waypoint as RequestWaypoint,
+    WaypointSource,
+};
+use deadpool_postgres::Object;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+/// Possible errors importing published waypoints
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PublishedError {
+    /// Unknown waypoint source
+    Source,
+
+    /// The export is not in the format of its source
+    Format,
+
+    /// No waypoint in the export could be read
+    NoWaypoints,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for PublishedError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PublishedError::Source => write!(f, "Invalid waypoint source provided."),
+            PublishedError::Format => write!(f, "Invalid export format."),
+            PublishedError::NoWaypoints => write!(f, "No waypoints could be read."),
+            PublishedError::Client => write!(f, "Could not get backend client."),
+            PublishedError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// A reporting point of an openAIP export
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAipPoint {
+    #[serde(rename = "_id")]
+    id: String,
+    geometry: OpenAipGeometry,
+}
+
+/// A GeoJSON point, as longitude, latitude and an optional elevation
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAipGeometry {
+    coordinates: Vec<f64>,
+}
+
+/// The waypoints read from an export
+#[derive(Debug, Clone, Default)]
+struct Parsed {
+    waypoints: Vec<Waypoint>,
+
+    /// Records that could not be read
+    skipped: u32,
+}
+
+impl Parsed {
+    /// Adds a record, skipping it if it is invalid or a duplicate
+    fn push(&mut self, seen: &mut HashSet<String>, waypoint: Option<RequestWaypoint>) {
+        let Some(waypoint) = waypoint.and_then(|w| Waypoint::try_from(w).ok()) else {
+            self.skipped += 1;
+            return;
+        };
+
+        if !seen.insert(waypoint.identifier.clone()) {
+            postgis_warn!("duplicate waypoint {}.", waypoint.identifier);
+            self.skipped += 1;
+            return;
+        }
+
+        self.waypoints.push(waypoint);
+    }
+}
+
+/// Builds an identifier from the parts of a published name, replacing the
+///  characters identifiers can't hold
+fn identifier(source: WaypointSource, parts: &[&str]) -> String {
+    let mut identifier = source.as_str_name().to_string();
+    for part in parts {
+        identifier.push('-');
+        identifier.extend(part.trim().chars().map(|c| match c {
+            '0'..='9' | 'A'..='Z' | 'a'..='z' | '_' | '.' => c,
+            _ => '_',
+        }));
+    }
+
+    identifier
+}
+
+/// Parses an openAIP reporting points export, either a page of the API
+///  with its points in `items` or a bare list of points
+fn parse_openaip(data: &[u8]) -> Result<Parsed, PublishedError> {
+    let items = match serde_json::from_slice(data) {
+        Ok(Value::Array(items)) => items,
+        Ok(Value::Object(mut page)) => match page.remove("items") {
+            Some(Value::Array(items)) => items,
+            _ => {
+                postgis_error!("openAIP export has no items.");
+                return Err(PublishedError::Format);
+            }
+        },
+        Ok(_) => {
+            postgis_error!("openAIP export is not a list of points.");
+            return Err(PublishedError::Format);
+        }
+        Err(e) => {
+            postgis_error!("could not parse openAIP export: {}", e);
+            return Err(PublishedError::Format);
+        }
+    };
+
+    let mut parsed = Parsed::default();
+    let mut seen = HashSet::new();
+    for item in items {
+        let waypoint = serde_json::from_value::<OpenAipPoint>(item)
+            .map_err(|e| postgis_warn!("could not read openAIP point: {}", e))
+            .ok()
+            .and_then(|point| match point.geometry.coordinates[..] {
+                [longitude, latitude, ..] => Some(RequestWaypoint {
+                    identifier: identifier(WaypointSource::Openaip, &[&point.id]),
+                    location: Some(Coordinates {
+                        latitude,
+                        longitude,
+                    }),
+                }),
+                _ => {
+                    postgis_warn!("openAIP point {} has no longitude and latitude.", point.id);
+                    None
+                }
+            });
+
+        parsed.push(&mut seen, waypoint);
+    }
+
+    Ok(parsed)
+}
+
+/// Parses a DAFIF waypoints file. Columns are found by the names of the
+///  header row; the WGS 84 decimal degrees are used.
+fn parse_dafif(data: &[u8]) -> Result<Parsed, PublishedError> {
+    let text = std::str::from_utf8(data).map_err(|e| {
+        postgis_error!("DAFIF export is not text: {}", e);
+        PublishedError::Format
+    })?;
+
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or_else(|| {
+            postgis_error!("DAFIF export is empty.");
+            PublishedError::Format
+        })?
+        .split('\t')
+        .map(str::trim)
+        .collect();
+
+    let column = |name: &str| {
+        header.iter().position(|c| *c == name).ok_or_else(|| {
+            postgis_error!("DAFIF export has no {} column.", name);
+            PublishedError::Format
+        })
+    };
+
+    let ident = column("WPT_IDENT")?;
+    let country = column("COUNTRY")?;
+    let latitude = column("WGS_DLAT")?;
+    let longitude = column("WGS_DLONG")?;
+
+    let mut parsed = Parsed::default();
+    let mut seen = HashSet::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let field = |index: usize| fields.get(index).copied().filter(|f| !f.is_empty());
+        let degrees = |index: usize| field(index).and_then(|f| f.parse::<f64>().ok());
+
+        let waypoint = match (
+            field(ident),
+            field(country),
+            degrees(latitude),
+            degrees(longitude),
+        ) {
+            (Some(ident), Some(country), Some(latitude), Some(longitude)) => {
+                Some(RequestWaypoint {
+                    identifier: identifier(WaypointSource::Dafif, &[country, ident]),
+                    location: Some(Coordinates {
+                        latitude,
+                        longitude,
+                    }),
+                })
+            }
+            _ => {
+                postgis_warn!("could not read DAFIF record: {}", line);
+                None
+            }
+        };
+
+        parsed.push(&mut seen, waypoint);
+    }
+
+    Ok(parsed)
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Published(PublishedError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Published(PublishedError::Client)
+        })
+}
+
+/// Imports the waypoints of an export, replacing those previously imported
+///  from the same source
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn import_waypoints(
+    request: ImportWaypointsRequest,
+) -> Result<ImportWaypointsSummary, PostgisError> {
+    postgis_debug!("entry.");
+    let source = WaypointSource::try_from(request.source).map_err(|_| {
+        postgis_error!("invalid waypoint source: {}", request.source);
+        PostgisError::Published(PublishedError::Source)
+    })?;
+
+    let parsed = match source {
+        WaypointSource::Openaip => parse_openaip(&request.data),
+        WaypointSource::Dafif => parse_dafif(&request.data),
+    }
+    .map_err(PostgisError::Published)?;
+
+    // An empty import would remove every waypoint of the source
+    if parsed.waypoints.is_empty() {
+        postgis_error!("no waypoints could be read from the export.");
+        return Err(PostgisError::Published(PublishedError::NoWaypoints));
+    }
+
+    let db_error = |e: tokio_postgres::Error| {
+        postgis_error!("could not import waypoints: {}", e);
+        PostgisError::Published(PublishedError::DBError)
+    };
+
+    let mut client = get_client().await?;
+    let transaction = client.transaction().await.map_err(db_error)?;
+    let upsert = transaction
+        .prepare_cached(&Statement::UpsertPublishedWaypoint.sql())
+        .await
+        .map_err(db_error)?;
+
+    let source_name = source.as_str_name();
+    for waypoint in &parsed.waypoints {
+        transaction
+            .execute(
+                &upsert,
+                &[&waypoint.identifier, &waypoint.geom, &source_name],
+            )
+            .await
+            .map_err(db_error)?;
+    }
+
+    let identifiers: Vec<&str> = parsed
+        .waypoints
+        .iter()
+        .map(|waypoint| waypoint.identifier.as_str())
+        .collect();
+
+    let delete = transaction
+        .prepare_cached(&Statement::DeleteStalePublishedWaypoints.sql())
+        .await
+        .map_err(db_error)?;
+
+    let removed = transaction
+        .execute(&delete, &[&source_name, &identifiers])
+        .await
+        .map_err(db_error)?;
+
+    transaction.commit().await.map_err(db_error)?;
+    super::vertiport::invalidate_route_waypoints();
+
+    postgis_info!(
+        "imported {} {} waypoints; removed {}, skipped {}.",
+        parsed.waypoints.len(),
+        source_name,
+        removed,
+        parsed.skipped
+    );
+
+    Ok(ImportWaypointsSummary {
+        imported: parsed.waypoints.len() as u32,
+        removed: removed as u32,
+        skipped: parsed.skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identifiers(parsed: &Parsed) -> Vec<&str> {
+        parsed
+            .waypoints
+            .iter()
+            .map(|waypoint| waypoint.identifier.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn ut_identifier() {
+        assert_eq!(
+            identifier(WaypointSource::Dafif, &["US", " KA/01 "]),
+            "DAFIF-US-KA_01"
+        );
+        assert_eq!(
+            identifier(WaypointSource::Openaip, &["62615b3d"]),
+            "OPENAIP-62615b3d"
+        );
+    }
+
+    #[test]
+    fn ut_parse_openaip() {
+        let point_with = |id: &str, coordinates: Vec<f64>| {
+            serde_json::json!({
+                "_id": id,
+                "name": "ECHO",
+                "country": "DE",
+                "compulsory": true,
+                "geometry": { "type": "Point", "coordinates": coordinates },
+            })
+        };
+        let point =
+            |id: &str, longitude: f64, latitude: f64| point_with(id, vec![longitude, latitude]);
+
+        let items = vec![
+            point("a1", 11.5, 48.1),
+            point("a2", 11.6, 48.2),
+            // Duplicate
+            point("a1", 11.5, 48.1),
+            // Out of bounds
+            point("a3", 11.6, 98.2),
+            serde_json::json!({ "_id": "a4", "name": "NO GEOMETRY" }),
+            // With elevation
+            point_with("a5", vec![11.7, 48.3, 520.0]),
+            // Missing latitude
+            point_with("a6", vec![11.8]),
+        ];
+
+        let page = serde_json::json!({ "limit": 100, "totalCount": 7, "items": items });
+        let parsed = parse_openaip(page.to_string().as_bytes()).unwrap();
+        assert_eq!(
+            identifiers(&parsed),
+            vec!["OPENAIP-a1", "OPENAIP-a2", "OPENAIP-a5"]
+        );
+        assert_eq!(parsed.skipped, 4);
+        assert_eq!(parsed.waypoints[1].geom.x, 11.6);
+        assert_eq!(parsed.waypoints[1].geom.y, 48.2);
+        assert_eq!(parsed.waypoints[2].geom.x, 11.7);
+        assert_eq!(parsed.waypoints[2].geom.y, 48.3);
+
+        let list = serde_json::Value::Array(items);
+        let from_list = parse_openaip(list.to_string().as_bytes()).unwrap();
+        assert_eq!(identifiers(&from_list), identifiers(&parsed));
+
+        for data in ["", "{}", "42", r#"{"items": 1}"#] {
+            assert_eq!(
+                parse_openaip(data.as_bytes()).unwrap_err(),
+                PublishedError::Format
+            );
+        }
+    }
+
+    #[test]
+    fn ut_parse_dafif() {
+        let data = [
+            "WPT_IDENT\tCOUNTRY\tSTATE\tTYPE\tWGS_LAT\tWGS_DLAT\tWGS_LONG\tWGS_DLONG",
+            "ABBOT\tUS\t06\tR\tN34261200\t34.436667\tW118223000\t-118.375000",
+            "",
+            "ALPHA\tCA\t\tR\tN49000000\t49.0\tW123000000\t-123.0",
+            // Missing coordinates
+            "BRAVO\tCA\t\tR\t\t\t\t",
+            // Duplicate
+            "ABBOT\tUS\t06\tR\tN34261200\t34.436667\tW118223000\t-118.375000",
+        ]
+        .join("\n");
+
+        let parsed = parse_dafif(data.as_bytes()).unwrap();
+        assert_eq!(
+            identifiers(&parsed),
+            vec!["DAFIF-US-ABBOT", "DAFIF-CA-ALPHA"]
+        );
+        assert_eq!(parsed.skipped, 2);
+        assert_eq!(parsed.waypoints[0].geom.y, 34.436667);
+        assert_eq!(parsed.waypoints[0].geom.x, -118.375);
+
+        assert_eq!(parse_dafif(b"").unwrap_err(), PublishedError::Format);
+        assert_eq!(
+            parse_dafif(b"WPT_IDENT\tCOUNTRY\nABBOT\tUS").unwrap_err(),
+            PublishedError::Format
+        );
+        assert_eq!(
+            parse_dafif(&[0xff, 0xfe]).unwrap_err(),
+            PublishedError::Format
+        );
+    }
+
+    #[test]
+    fn test_published_error_display() {
+        assert_eq!(
+            PublishedError::Source.to_string(),
+            "Invalid waypoint source provided."
+        );
+        assert_eq!(PublishedError::Format.to_string(), "Invalid export format.");
+        assert_eq!(
+            PublishedError::NoWaypoints.to_string(),
+            "No waypoints could be read."
+        );
+        assert_eq!(
+            PublishedError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(PublishedError::DBError.to_string(), "Database error.");
+    }
+}
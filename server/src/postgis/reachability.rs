@@ -0,0 +1,526 @@
+//! Quick checks of whether an aircraft in flight can still reach a vertiport.
+//!
+//! No path is searched. The straight line from the last reported position
+//!  of the aircraft to the vertiport is stretched by a detour factor and
+//!  compared with the range of the aircraft, taken from the performance
+//!  profile of its type. The straight line is screened against the zones
+//!  active until the estimated arrival; a blocked line needs a detour, which
+//!  lowers the confidence of the answer.
+
+use super::aircraft::{
+    get_aircraft_state, get_data_quality, get_quality_thresholds, get_staleness_seconds,
+};
+use super::authorization::Entrant;
+use super::best_path::{zone_intersection_checks, PathError};
+use super::identifier::{check_identifier, Entity};
+use super::vertiport::{get_vertiport_centroidz, VertiportError};
+use super::PostgisError;
+use super::DEFAULT_SRID;
+use crate::grpc::server::grpc_server::{
+    CheckReachabilityRequest, CheckReachabilityResponse, DataQuality, ReachabilityConfidence,
+};
+use crate::reload::Tunable;
+use crate::types::AircraftType;
+use lib_common::time::{Duration, Utc};
+use postgis::ewkb::{LineStringT, PointZ};
+use std::fmt::{self, Display, Formatter};
+
+/// Default ratio of the flown distance to the straight-line distance
+pub const DEFAULT_DETOUR_FACTOR: f32 = 1.3;
+
+/// Reachability settings
+static SETTINGS: Tunable<Settings> = Tunable::new();
+
+/// Possible errors checking reachability
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReachabilityError {
+    /// Invalid aircraft identifier
+    Aircraft,
+
+    /// Invalid vertiport identifier
+    Vertiport,
+
+    /// Invalid remaining range
+    Range,
+
+    /// Invalid performance profiles or detour factor
+    Settings,
+
+    /// No performance profile for the aircraft type
+    Profile,
+
+    /// No recent position for the aircraft
+    Position,
+
+    /// Could not get client
+    Client,
+}
+
+impl Display for ReachabilityError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ReachabilityError::Aircraft => write!(f, "Invalid aircraft identifier provided."),
+            ReachabilityError::Vertiport => write!(f, "Invalid vertiport identifier provided."),
+            ReachabilityError::Range => write!(f, "Invalid remaining range provided."),
+            ReachabilityError::Settings => write!(f, "Invalid reachability settings."),
+            ReachabilityError::Profile => {
+                write!(f, "No performance profile for the aircraft type.")
+            }
+            ReachabilityError::Position => write!(f, "No recent position for the aircraft."),
+            ReachabilityError::Client => write!(f, "Could not get backend client."),
+        }
+    }
+}
+
+/// Performance of an aircraft type
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PerformanceProfile {
+    /// Distance the aircraft can fly on a full charge
+    pub range_meters: f32,
+
+    /// Ground speed the aircraft cruises at
+    pub cruise_speed_mps: f32,
+}
+
+/// Reachability settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Performance of each aircraft type
+    pub profiles: Vec<(AircraftType, PerformanceProfile)>,
+
+    /// Ratio of the flown distance to the straight-line distance
+    pub detour_factor: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            profiles: vec![],
+            detour_factor: DEFAULT_DETOUR_FACTOR,
+        }
+    }
+}
+
+impl Settings {
+    /// Gets the performance profile of an aircraft type, if it has one
+    pub fn profile(&self, aircraft_type: AircraftType) -> Option<PerformanceProfile> {
+        self.profiles
+            .iter()
+            .find(|(t, _)| *t == aircraft_type)
+            .map(|(_, profile)| *profile)
+    }
+}
+
+/// Parses the performance of each aircraft type, as a comma-separated
+///  list of `TYPE:RANGE@SPEED` entries such as `Rotorcraft:30000@20`, with
+///  the range in meters and the cruise speed in meters per second
+pub fn parse_profiles(text: &str) -> Result<Vec<(AircraftType, PerformanceProfile)>, PostgisError> {
+    let invalid = |entry: &str| {
+        postgis_error!("invalid aircraft performance profile: {entry}");
+        PostgisError::Reachability(ReachabilityError::Settings)
+    };
+
+    let mut profiles: Vec<(AircraftType, PerformanceProfile)> = vec![];
+    for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (aircraft_type, performance) = entry.split_once(':').ok_or_else(|| invalid(entry))?;
+        let (range, speed) = performance.split_once('@').ok_or_else(|| invalid(entry))?;
+        let aircraft_type: AircraftType =
+            aircraft_type.trim().parse().map_err(|_| invalid(entry))?;
+        let profile = PerformanceProfile {
+            range_meters: range.trim().parse().map_err(|_| invalid(entry))?,
+            cruise_speed_mps: speed.trim().parse().map_err(|_| invalid(entry))?,
+        };
+
+        if !profile.range_meters.is_finite()
+            || !profile.cruise_speed_mps.is_finite()
+            || profile.range_meters <= 0.0
+            || profile.cruise_speed_mps <= 0.0
+            || profiles.iter().any(|(t, _)| *t == aircraft_type)
+        {
+            return Err(invalid(entry));
+        }
+
+        profiles.push((aircraft_type, profile));
+    }
+
+    Ok(profiles)
+}
+
+/// Checks the reachability settings
+pub fn check_settings(settings: &Settings) -> Result<(), PostgisError> {
+    if !settings.detour_factor.is_finite() || settings.detour_factor < 1.0 {
+        postgis_error!(
+            "detour factor must be at least 1, got {}.",
+            settings.detour_factor
+        );
+        return Err(PostgisError::Reachability(ReachabilityError::Settings));
+    }
+
+    Ok(())
+}
+
+/// Sets the reachability settings, replacing any set before
+pub fn set_settings(settings: Settings) -> Result<(), PostgisError> {
+    check_settings(&settings)?;
+    SETTINGS.set(settings);
+    Ok(())
+}
+
+/// Gets the reachability settings
+pub fn get_settings() -> Settings {
+    SETTINGS.get().unwrap_or_default()
+}
+
+/// Answers whether the target can be reached, and how confidently
+///
+/// A straight line out of range can't be flown whatever the airspace. A
+///  clear straight line within range is reachable, with little margin if
+///  the detour allowance doesn't fit. A blocked straight line relies on the
+///  detour allowance to fit.
+fn assess(
+    straight_line_meters: f32,
+    estimated_meters: f32,
+    range_meters: f32,
+    direct_blocked: bool,
+) -> (bool, ReachabilityConfidence) {
+    if straight_line_meters > range_meters {
+        return (false, ReachabilityConfidence::High);
+    }
+
+    match (estimated_meters <= range_meters, direct_blocked) {
+        (true, false) => (true, ReachabilityConfidence::High),
+        (true, true) | (false, false) => (true, ReachabilityConfidence::Medium),
+        (false, true) => (false, ReachabilityConfidence::Low),
+    }
+}
+
+/// An answer from a stale position is one level less confident
+fn degrade(confidence: ReachabilityConfidence) -> ReachabilityConfidence {
+    match confidence {
+        ReachabilityConfidence::High => ReachabilityConfidence::Medium,
+        _ => ReachabilityConfidence::Low,
+    }
+}
+
+/// Validates the request
+fn validate(request: &CheckReachabilityRequest) -> Result<(), ReachabilityError> {
+    check_identifier(Entity::Aircraft, &request.aircraft_identifier).map_err(|e| {
+        postgis_error!(
+            "invalid aircraft identifier {:?}: {e}",
+            request.aircraft_identifier
+        );
+        ReachabilityError::Aircraft
+    })?;
+
+    check_identifier(Entity::Vertiport, &request.target_identifier).map_err(|e| {
+        postgis_error!(
+            "invalid vertiport identifier {:?}: {e}",
+            request.target_identifier
+        );
+        ReachabilityError::Vertiport
+    })?;
+
+    if let Some(range) = request.remaining_range_meters {
+        if !range.is_finite() || range < 0.0 {
+            postgis_error!("invalid remaining range: {range}");
+            return Err(ReachabilityError::Range);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if an aircraft can still reach a vertiport from its last
+///  reported position
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn check_reachability(
+    request: CheckReachabilityRequest,
+) -> Result<CheckReachabilityResponse, PostgisError> {
+    postgis_debug!("request: {:?}", request);
+    validate(&request).map_err(PostgisError::Reachability)?;
+
+    let state = get_aircraft_state(std::slice::from_ref(&request.aircraft_identifier))
+        .await?
+        .pop()
+        .ok_or_else(|| {
+            postgis_error!("aircraft '{}' not found.", request.aircraft_identifier);
+            PostgisError::Reachability(ReachabilityError::Aircraft)
+        })?;
+
+    let now = Utc::now();
+    let (position, last_update) =
+        state
+            .position
+            .zip(state.last_position_update)
+            .ok_or_else(|| {
+                postgis_error!("aircraft '{}' has no position.", state.identifier);
+                PostgisError::Reachability(ReachabilityError::Position)
+            })?;
+
+    let quality = get_data_quality(
+        get_staleness_seconds(last_update, now),
+        &get_quality_thresholds(),
+    );
+
+    if quality == DataQuality::Expired {
+        postgis_error!("position of aircraft '{}' expired.", state.identifier);
+        return Err(PostgisError::Reachability(ReachabilityError::Position));
+    }
+
+    let settings = get_settings();
+    let profile = settings.profile(state.aircraft_type).ok_or_else(|| {
+        postgis_error!(
+            "no performance profile for aircraft type {:?}.",
+            state.aircraft_type
+        );
+        PostgisError::Reachability(ReachabilityError::Profile)
+    })?;
+
+    let range_meters = request
+        .remaining_range_meters
+        .unwrap_or(profile.range_meters);
+
+    // A closed vertiport can't be landed at, whatever the distance
+    let target = match get_vertiport_centroidz(&request.target_identifier).await {
+        Ok(target) => target,
+        Err(PostgisError::Vertiport(VertiportError::Closed)) => {
+            return Ok(CheckReachabilityResponse {
+                reachable: false,
+                confidence: ReachabilityConfidence::High as i32,
+                range_meters,
+                ..Default::default()
+            })
+        }
+        Err(e) => return Err(e),
+    };
+
+    let origin = PointZ::from(position);
+    let straight_line_meters = super::utils::distance_meters(&origin, &target);
+    let estimated_meters = straight_line_meters * settings.detour_factor;
+    let flight_seconds = (estimated_meters / profile.cruise_speed_mps) as i64;
+    let estimated_arrival = now + Duration::try_seconds(flight_seconds).unwrap_or_default();
+
+    let pool = crate::postgis::DEADPOOL_POSTGIS.get().ok_or_else(|| {
+        postgis_error!("could not get psql pool.");
+        PostgisError::Reachability(ReachabilityError::Client)
+    })?;
+
+    let client = pool.get().await.map_err(|e| {
+        postgis_error!("could not get client from psql connection pool: {}", e);
+        PostgisError::Reachability(ReachabilityError::Client)
+    })?;
+
+    let geom = LineStringT {
+        points: vec![origin, target],
+        srid: Some(DEFAULT_SRID),
+    };
+
+    let entrant = Entrant {
+        aircraft_identifier: Some(state.identifier.clone()),
+        flight_identifier: state.session_id.clone(),
+        operator_identifier: None,
+    };
+
+    let direct_blocked = match zone_intersection_checks(
+        &client,
+        &geom,
+        now,
+        estimated_arrival,
+        &state.identifier,
+        &request.target_identifier,
+        &entrant,
+    )
+    .await
+    {
+        Ok(()) => false,
+        Err(PostgisError::BestPath(PathError::ZoneIntersection)) => true,
+        Err(e) => return Err(e),
+    };
+
+    let (reachable, mut confidence) = assess(
+        straight_line_meters,
+        estimated_meters,
+        range_meters,
+        direct_blocked,
+    );
+
+    if quality == DataQuality::Stale {
+        confidence = degrade(confidence);
+    }
+
+    Ok(CheckReachabilityResponse {
+        reachable,
+        confidence: confidence as i32,
+        straight_line_meters,
+        estimated_meters,
+        range_meters,
+        direct_blocked,
+        estimated_arrival: reachable.then(|| estimated_arrival.into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_parse_profiles() {
+        assert_eq!(parse_profiles("").unwrap(), vec![]);
+        assert_eq!(
+            parse_profiles("Rotorcraft:30000@20, Hybridlift: 120000 @ 50").unwrap(),
+            vec![
+                (
+                    AircraftType::Rotorcraft,
+                    PerformanceProfile {
+                        range_meters: 30000.0,
+                        cruise_speed_mps: 20.0
+                    }
+                ),
+                (
+                    AircraftType::Hybridlift,
+                    PerformanceProfile {
+                        range_meters: 120000.0,
+                        cruise_speed_mps: 50.0
+                    }
+                )
+            ]
+        );
+
+        for text in [
+            "Rotorcraft",
+            "Rotorcraft:30000",
+            "Unknown:30000@20",
+            "Rotorcraft:0@20",
+            "Rotorcraft:30000@-1",
+            "Rotorcraft:30000@20,Rotorcraft:1000@5",
+        ] {
+            assert_eq!(
+                parse_profiles(text),
+                Err(PostgisError::Reachability(ReachabilityError::Settings))
+            );
+        }
+    }
+
+    #[test]
+    fn ut_check_settings() {
+        assert!(check_settings(&Settings::default()).is_ok());
+
+        for detour_factor in [0.9, f32::NAN] {
+            let settings = Settings {
+                detour_factor,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                check_settings(&settings),
+                Err(PostgisError::Reachability(ReachabilityError::Settings))
+            );
+        }
+    }
+
+    #[test]
+    fn ut_assess() {
+        // out of range even in a straight line
+        assert_eq!(
+            assess(1100.0, 1430.0, 1000.0, false),
+            (false, ReachabilityConfidence::High)
+        );
+
+        // in range with the detour allowance
+        assert_eq!(
+            assess(500.0, 650.0, 1000.0, false),
+            (true, ReachabilityConfidence::High)
+        );
+        assert_eq!(
+            assess(500.0, 650.0, 1000.0, true),
+            (true, ReachabilityConfidence::Medium)
+        );
+
+        // in range in a straight line only
+        assert_eq!(
+            assess(900.0, 1170.0, 1000.0, false),
+            (true, ReachabilityConfidence::Medium)
+        );
+        assert_eq!(
+            assess(900.0, 1170.0, 1000.0, true),
+            (false, ReachabilityConfidence::Low)
+        );
+    }
+
+    #[test]
+    fn ut_degrade() {
+        assert_eq!(
+            degrade(ReachabilityConfidence::High),
+            ReachabilityConfidence::Medium
+        );
+        assert_eq!(
+            degrade(ReachabilityConfidence::Medium),
+            ReachabilityConfidence::Low
+        );
+        assert_eq!(
+            degrade(ReachabilityConfidence::Low),
+            ReachabilityConfidence::Low
+        );
+    }
+
+    #[test]
+    fn ut_validate() {
+        let request = CheckReachabilityRequest {
+            aircraft_identifier: "Marauder".to_string(),
+            target_identifier: "vertiport-1".to_string(),
+            remaining_range_meters: Some(5000.0),
+        };
+        assert!(validate(&request).is_ok());
+
+        let invalid = CheckReachabilityRequest {
+            aircraft_identifier: "".to_string(),
+            ..request.clone()
+        };
+        assert_eq!(validate(&invalid), Err(ReachabilityError::Aircraft));
+
+        let invalid = CheckReachabilityRequest {
+            target_identifier: "".to_string(),
+            ..request.clone()
+        };
+        assert_eq!(validate(&invalid), Err(ReachabilityError::Vertiport));
+
+        let invalid = CheckReachabilityRequest {
+            remaining_range_meters: Some(-1.0),
+            ..request
+        };
+        assert_eq!(validate(&invalid), Err(ReachabilityError::Range));
+    }
+
+    #[test]
+    fn test_reachability_error_display() {
+        assert_eq!(
+            ReachabilityError::Aircraft.to_string(),
+            "Invalid aircraft identifier provided."
+        );
+        assert_eq!(
+            ReachabilityError::Vertiport.to_string(),
+            "Invalid vertiport identifier provided."
+        );
+        assert_eq!(
+            ReachabilityError::Range.to_string(),
+            "Invalid remaining range provided."
+        );
+        assert_eq!(
+            ReachabilityError::Settings.to_string(),
+            "Invalid reachability settings."
+        );
+        assert_eq!(
+            ReachabilityError::Profile.to_string(),
+            "No performance profile for the aircraft type."
+        );
+        assert_eq!(
+            ReachabilityError::Position.to_string(),
+            "No recent position for the aircraft."
+        );
+        assert_eq!(
+            ReachabilityError::Client.to_string(),
+            "Could not get backend client."
+        );
+    }
+}
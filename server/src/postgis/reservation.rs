@@ -0,0 +1,603 @@
+//! Volumes reserved by operators for aerial work.
+//!
+//! Some operations need a volume rather than a path, such as surveys or
+//!  inspections. A reservation holds a polygon extended between two
+//!  altitudes during a time window for one operator. Unlike a zone it is
+//!  not a regulatory restriction: it conflicts with the paths of other
+//!  planners like a filed flight does, and with other reservations of the
+//!  same volume. The operator may release it before its window ends, the
+//!  volume is free from then on.
+
+use super::identifier::{check_identifier, Entity};
+use super::leader::LOCK_NAMESPACE;
+use super::statements::Statement;
+use super::{OnceCell, PostgisError, DEFAULT_SRID};
+use crate::grpc::server::grpc_server::{
+    ReleaseReservationRequest, ReserveVolumeRequest, VolumeReservation,
+};
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Utc};
+use lib_common::uuid::Uuid;
+use postgis::ewkb::{LineStringT, PointZ, PolygonZ};
+use std::fmt::{self, Display, Formatter};
+use tokio_postgres::Row;
+
+/// Max length of a work reference
+pub const MAX_REFERENCE_LENGTH: usize = 255;
+
+/// Key of the advisory lock serializing reservation inserts
+pub(super) const WRITE_LOCK_KEY: i64 = LOCK_NAMESPACE | 0x200;
+
+/// Possible errors with volume reservation requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReservationError {
+    /// Invalid operator identifier
+    Operator,
+
+    /// Invalid Location
+    Location,
+
+    /// Invalid Altitude Range
+    Altitude,
+
+    /// Invalid reservation window
+    Time,
+
+    /// Invalid work reference
+    Reference,
+
+    /// The volume is already reserved during the window
+    Conflict,
+
+    /// Unknown reservation, or already released or ended
+    Reservation,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for ReservationError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ReservationError::Operator => write!(f, "Invalid operator provided."),
+            ReservationError::Location => write!(f, "Invalid location provided."),
+            ReservationError::Altitude => write!(f, "Invalid altitude range provided."),
+            ReservationError::Time => write!(f, "Invalid time window provided."),
+            ReservationError::Reference => write!(f, "Invalid reference provided."),
+            ReservationError::Conflict => {
+                write!(f, "The volume is already reserved during the time window.")
+            }
+            ReservationError::Reservation => {
+                write!(f, "Unknown reservation, or no longer active.")
+            }
+            ReservationError::Client => write!(f, "Could not get backend client."),
+            ReservationError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// A validated request to reserve a volume
+#[derive(Debug, Clone)]
+struct Reservation {
+    operator_identifier: String,
+    geom: PolygonZ,
+    altitude_meters_min: f32,
+    altitude_meters_max: f32,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    reference: Option<String>,
+}
+
+impl TryFrom<ReserveVolumeRequest> for Reservation {
+    type Error = ReservationError;
+
+    fn try_from(request: ReserveVolumeRequest) -> Result<Self, Self::Error> {
+        check_operator(&request.operator_identifier)?;
+
+        let geom =
+            super::utils::polygon_from_vertices_z(&request.vertices, request.altitude_meters_min)
+                .map_err(|e| {
+                postgis_error!("invalid reservation polygon: {}", e);
+                ReservationError::Location
+            })?;
+
+        if !request.altitude_meters_min.is_finite()
+            || !request.altitude_meters_max.is_finite()
+            || request.altitude_meters_min >= request.altitude_meters_max
+        {
+            postgis_error!(
+                "invalid altitude range: {} to {} meters.",
+                request.altitude_meters_min,
+                request.altitude_meters_max
+            );
+            return Err(ReservationError::Altitude);
+        }
+
+        let (Some(time_start), Some(time_end)) = (request.time_start, request.time_end) else {
+            postgis_error!("time_start and time_end are required.");
+            return Err(ReservationError::Time);
+        };
+
+        let time_start: DateTime<Utc> = time_start.into();
+        let time_end: DateTime<Utc> = time_end.into();
+        if time_end <= time_start {
+            postgis_error!(
+                "time_end {} is not after time_start {}.",
+                time_end,
+                time_start
+            );
+            return Err(ReservationError::Time);
+        }
+
+        if time_end <= Utc::now() {
+            postgis_error!("time_end {} has already passed.", time_end);
+            return Err(ReservationError::Time);
+        }
+
+        if let Some(reference) = &request.reference {
+            if reference.trim().is_empty() || reference.len() > MAX_REFERENCE_LENGTH {
+                postgis_error!("invalid reference: {}", reference);
+                return Err(ReservationError::Reference);
+            }
+        }
+
+        Ok(Reservation {
+            operator_identifier: request.operator_identifier,
+            geom,
+            altitude_meters_min: request.altitude_meters_min,
+            altitude_meters_max: request.altitude_meters_max,
+            time_start,
+            time_end,
+            reference: request.reference,
+        })
+    }
+}
+
+/// Checks an operator identifier
+fn check_operator(identifier: &str) -> Result<(), ReservationError> {
+    check_identifier(Entity::Operator, identifier).map_err(|e| {
+        postgis_error!("invalid operator identifier {}: {}", identifier, e);
+        ReservationError::Operator
+    })
+}
+
+/// Gets the name of this module's table
+pub(super) fn get_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."volume_reservations""#, super::get_schema()))
+}
+
+/// SQL condition matching the reservations whose footprint overlaps a 2D
+///  geometry. Footprints sharing only an edge don't overlap, so adjacent
+///  volumes may be reserved side by side.
+pub(super) fn footprint_overlap_sql(geom: &str) -> String {
+    format!(r#"ST_Intersects("footprint", {geom}) AND NOT ST_Touches("footprint", {geom})"#)
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Reservation(ReservationError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Reservation(ReservationError::Client)
+        })
+}
+
+/// Initialize the volume reservations table in the PostGIS database
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn psql_init() -> Result<(), PostgisError> {
+    let statements = vec![
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {table_name} (
+            "identifier" UUID UNIQUE NOT NULL PRIMARY KEY,
+            "operator_identifier" VARCHAR(255) NOT NULL,
+            "footprint" GEOMETRY(POLYGON, {DEFAULT_SRID}) NOT NULL,
+            "altitude_meters_min" FLOAT(4) NOT NULL,
+            "altitude_meters_max" FLOAT(4) NOT NULL,
+            "time_start" TIMESTAMPTZ NOT NULL,
+            "time_end" TIMESTAMPTZ NOT NULL,
+            "reference" VARCHAR(255),
+            "released" TIMESTAMPTZ,
+            "created" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            CHECK ("altitude_meters_min" < "altitude_meters_max"),
+            CHECK ("time_start" < "time_end")
+        );"#,
+            table_name = get_table_name()
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "volume_reservations_footprint_idx" ON {table_name} USING GIST ("footprint");"#,
+            table_name = get_table_name()
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "volume_reservations_time_idx" ON {table_name} ("time_start", "time_end");"#,
+            table_name = get_table_name()
+        ),
+    ];
+
+    super::psql_transaction(statements).await
+}
+
+/// Converts a row of the volume reservations table
+fn process_reservation_row(row: &Row) -> Result<VolumeReservation, PostgisError> {
+    let parse = |e: tokio_postgres::Error| {
+        postgis_error!("could not parse volume reservation row: {}", e);
+        PostgisError::Reservation(ReservationError::DBError)
+    };
+
+    let identifier: Uuid = row.try_get("identifier").map_err(parse)?;
+    let time_start: DateTime<Utc> = row.try_get("time_start").map_err(parse)?;
+    let time_end: DateTime<Utc> = row.try_get("time_end").map_err(parse)?;
+    let released: Option<DateTime<Utc>> = row.try_get("released").map_err(parse)?;
+
+    Ok(VolumeReservation {
+        identifier: identifier.to_string(),
+        operator_identifier: row.try_get("operator_identifier").map_err(parse)?,
+        altitude_meters_min: row.try_get("altitude_meters_min").map_err(parse)?,
+        altitude_meters_max: row.try_get("altitude_meters_max").map_err(parse)?,
+        time_start: Some(time_start.into()),
+        time_end: Some(time_end.into()),
+        reference: row.try_get("reference").map_err(parse)?,
+        released: released.map(Into::into),
+    })
+}
+
+/// Reserves a volume for an operator during a time window, unless another
+///  reservation overlaps it
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn reserve_volume(
+    request: ReserveVolumeRequest,
+) -> Result<VolumeReservation, PostgisError> {
+    postgis_debug!("entry.");
+    let reservation = Reservation::try_from(request).map_err(PostgisError::Reservation)?;
+
+    let mut client = get_client().await?;
+    let transaction = client.transaction().await.map_err(|e| {
+        postgis_error!("could not create transaction: {}", e);
+        PostgisError::Reservation(ReservationError::DBError)
+    })?;
+
+    let lock_stmt = transaction
+        .prepare_cached(&Statement::LockVolumeReservations.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?;
+
+    let stmt = transaction
+        .prepare_cached(&Statement::InsertVolumeReservation.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?;
+
+    // held until commit, the insert then sees reservations committed meanwhile
+    transaction.execute(&lock_stmt, &[]).await.map_err(|e| {
+        postgis_error!("could not lock volume reservations: {}", e);
+        PostgisError::Reservation(ReservationError::DBError)
+    })?;
+
+    let row = transaction
+        .query_opt(
+            &stmt,
+            &[
+                &Uuid::new_v4(),
+                &reservation.operator_identifier,
+                &reservation.geom,
+                &reservation.altitude_meters_min,
+                &reservation.altitude_meters_max,
+                &reservation.time_start,
+                &reservation.time_end,
+                &reservation.reference,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not insert volume reservation: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?
+        .ok_or_else(|| {
+            postgis_error!(
+                "volume already reserved between {} and {}.",
+                reservation.time_start,
+                reservation.time_end
+            );
+            PostgisError::Reservation(ReservationError::Conflict)
+        })?;
+
+    let reservation = process_reservation_row(&row)?;
+    transaction.commit().await.map_err(|e| {
+        postgis_error!("could not commit transaction: {}", e);
+        PostgisError::Reservation(ReservationError::DBError)
+    })?;
+
+    postgis_info!("reserved volume: {:?}", reservation);
+    Ok(reservation)
+}
+
+/// Releases a reservation of an operator before its window ends
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn release_reservation(
+    request: ReleaseReservationRequest,
+) -> Result<VolumeReservation, PostgisError> {
+    postgis_debug!("entry.");
+    check_operator(&request.operator_identifier).map_err(PostgisError::Reservation)?;
+    let identifier = Uuid::parse_str(&request.identifier).map_err(|e| {
+        postgis_error!(
+            "invalid reservation identifier {}: {}",
+            request.identifier,
+            e
+        );
+        PostgisError::Reservation(ReservationError::Reservation)
+    })?;
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::ReleaseVolumeReservation.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?;
+
+    let row = client
+        .query_opt(&stmt, &[&identifier, &request.operator_identifier])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not release volume reservation: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?
+        .ok_or_else(|| {
+            postgis_error!(
+                "no active reservation {} of operator {}.",
+                identifier,
+                request.operator_identifier
+            );
+            PostgisError::Reservation(ReservationError::Reservation)
+        })?;
+
+    let reservation = process_reservation_row(&row)?;
+    postgis_info!("released volume reservation: {:?}", reservation);
+    Ok(reservation)
+}
+
+/// Gets the identifier of a reservation crossed by the path during the
+///  time window, if any. The reservations of `operator_identifier` are
+///  skipped, an operator may fly through its own volumes.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub(super) async fn path_reservation(
+    client: &Object,
+    geom: &LineStringT<PointZ>,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    operator_identifier: &Option<String>,
+) -> Result<Option<String>, PostgisError> {
+    let stmt = client
+        .prepare_cached(&Statement::ReservationPathIntersection.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?;
+
+    let Some(row) = client
+        .query_opt(&stmt, &[&geom, &time_start, &time_end, operator_identifier])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not query for reservation intersections: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?
+    else {
+        return Ok(None);
+    };
+
+    let identifier: Uuid = row.try_get("identifier").map_err(|e| {
+        postgis_error!("could not get reservation identifier: {}", e);
+        PostgisError::Reservation(ReservationError::DBError)
+    })?;
+
+    Ok(Some(identifier.to_string()))
+}
+
+/// Gets the identifiers of the reservations overlapping a volume during
+///  the time window
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub(super) async fn volume_reservations(
+    client: &Object,
+    geom: &PolygonZ,
+    altitude_meters_min: f32,
+    altitude_meters_max: f32,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> Result<Vec<String>, PostgisError> {
+    let stmt = client
+        .prepare_cached(&Statement::ReservationVolumeIntersection.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?;
+
+    client
+        .query(
+            &stmt,
+            &[
+                &geom,
+                &altitude_meters_min,
+                &altitude_meters_max,
+                &time_start,
+                &time_end,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not query for reservation intersections: {}", e);
+            PostgisError::Reservation(ReservationError::DBError)
+        })?
+        .iter()
+        .map(|row| {
+            row.try_get::<_, Uuid>("identifier")
+                .map(|identifier| identifier.to_string())
+                .map_err(|e| {
+                    postgis_error!("could not get reservation identifier: {}", e);
+                    PostgisError::Reservation(ReservationError::DBError)
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::rectangle;
+    use lib_common::time::Duration;
+
+    fn request() -> ReserveVolumeRequest {
+        let time_start = Utc::now();
+        ReserveVolumeRequest {
+            operator_identifier: "OPERATOR-1".to_string(),
+            vertices: rectangle((4.915, 52.374), (4.917, 52.376)),
+            altitude_meters_min: 20.0,
+            altitude_meters_max: 120.0,
+            time_start: Some(time_start.into()),
+            time_end: Some((time_start + Duration::try_hours(1).unwrap()).into()),
+            reference: Some("SURVEY-7".to_string()),
+        }
+    }
+
+    #[test]
+    fn ut_reservation_try_from() {
+        let reservation = Reservation::try_from(request()).unwrap();
+        assert_eq!(reservation.operator_identifier, "OPERATOR-1");
+        assert_eq!(reservation.altitude_meters_min, 20.0);
+        assert_eq!(reservation.altitude_meters_max, 120.0);
+        assert!(reservation.time_start < reservation.time_end);
+
+        let error = Reservation::try_from(ReserveVolumeRequest {
+            operator_identifier: "".to_string(),
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ReservationError::Operator);
+
+        let mut open = request();
+        open.vertices.pop();
+        let error = Reservation::try_from(open).unwrap_err();
+        assert_eq!(error, ReservationError::Location);
+
+        for (min, max) in [(120.0, 20.0), (20.0, 20.0), (20.0, f32::INFINITY)] {
+            let error = Reservation::try_from(ReserveVolumeRequest {
+                altitude_meters_min: min,
+                altitude_meters_max: max,
+                ..request()
+            })
+            .unwrap_err();
+            assert_eq!(error, ReservationError::Altitude);
+        }
+
+        let error = Reservation::try_from(ReserveVolumeRequest {
+            time_end: request().time_start,
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ReservationError::Time);
+
+        let error = Reservation::try_from(ReserveVolumeRequest {
+            time_start: None,
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ReservationError::Time);
+
+        // already over
+        let time_start = Utc::now() - Duration::try_hours(2).unwrap();
+        let error = Reservation::try_from(ReserveVolumeRequest {
+            time_start: Some(time_start.into()),
+            time_end: Some((time_start + Duration::try_hours(1).unwrap()).into()),
+            ..request()
+        })
+        .unwrap_err();
+        assert_eq!(error, ReservationError::Time);
+
+        for reference in [" ".to_string(), "A".repeat(MAX_REFERENCE_LENGTH + 1)] {
+            let error = Reservation::try_from(ReserveVolumeRequest {
+                reference: Some(reference),
+                ..request()
+            })
+            .unwrap_err();
+            assert_eq!(error, ReservationError::Reference);
+        }
+    }
+
+    #[test]
+    fn ut_footprint_overlap_sql() {
+        let sql = footprint_overlap_sql("$1");
+        assert_eq!(
+            sql,
+            r#"ST_Intersects("footprint", $1) AND NOT ST_Touches("footprint", $1)"#
+        );
+    }
+
+    #[test]
+    fn ut_write_lock_key() {
+        assert_eq!(WRITE_LOCK_KEY >> 32, LOCK_NAMESPACE >> 32);
+        assert!(Statement::LockVolumeReservations
+            .sql()
+            .contains(&format!("pg_advisory_xact_lock({WRITE_LOCK_KEY})")));
+    }
+
+    #[test]
+    fn test_reservation_error_display() {
+        assert_eq!(
+            ReservationError::Operator.to_string(),
+            "Invalid operator provided."
+        );
+        assert_eq!(
+            ReservationError::Location.to_string(),
+            "Invalid location provided."
+        );
+        assert_eq!(
+            ReservationError::Altitude.to_string(),
+            "Invalid altitude range provided."
+        );
+        assert_eq!(
+            ReservationError::Time.to_string(),
+            "Invalid time window provided."
+        );
+        assert_eq!(
+            ReservationError::Reference.to_string(),
+            "Invalid reference provided."
+        );
+        assert_eq!(
+            ReservationError::Conflict.to_string(),
+            "The volume is already reserved during the time window."
+        );
+        assert_eq!(
+            ReservationError::Reservation.to_string(),
+            "Unknown reservation, or no longer active."
+        );
+        assert_eq!(
+            ReservationError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(ReservationError::DBError.to_string(), "Database error.");
+    }
+}
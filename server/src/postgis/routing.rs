@@ -0,0 +1,1703 @@
+//! Modified A* search for paths between two nodes.
+//!
+//! The search only deals with geometry. Candidate waypoints come from a
+//!  [`NodeProvider`] and finished paths are vetted by a [`ConflictChecker`],
+//!  so the ranking and limits can be exercised without a database. The
+//!  PostGIS-backed implementations live in [`best_path`](super::best_path).
+//!
+//! Paths are ranked by a weighted sum of their [`PathCosts`]. Distance, time
+//!  and energy are estimated from the geometry of each leg as the search
+//!  goes. Ground risk and noise depend on what a path flies over and are
+//!  only measured once a path reaches the target.
+
+use super::best_path::{PathError, FLIGHT_SEPARATION_METERS};
+use super::waypoint::Waypoint;
+use super::PostgisError;
+use crate::grpc::server::grpc_server::NodeType;
+use crate::reload::Tunable;
+use crate::types::AircraftType;
+use lib_common::time::{Duration, Utc};
+use postgis::ewkb::PointZ;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::{Add, AddAssign};
+use tonic::async_trait;
+
+/// Elevations to search for valid paths
+pub const FLIGHT_LEVELS: [f32; 3] = [40.0, 80.0, 120.0];
+
+/// Max distance a flight can travel
+pub const MAX_FLIGHT_DISTANCE_METERS: f32 = 300_000.;
+
+/// Horizontal speed assumed when estimating flight times
+pub const CRUISE_SPEED_METERS_PER_SECOND: f32 = 30.0;
+
+/// Climb and descent rate assumed when estimating flight times
+pub const VERTICAL_SPEED_METERS_PER_SECOND: f32 = 3.0;
+
+/// Meters of level flight worth of energy spent per meter climbed
+///  Descents are assumed to recover nothing
+pub const CLIMB_ENERGY_FACTOR: f32 = 10.0;
+
+/// Default weights of the path objectives
+static DEFAULT_WEIGHTS: Tunable<CostWeights> = Tunable::new();
+
+/// Altitude limits of each aircraft type
+static ALTITUDE_LIMITS: Tunable<Vec<(AircraftType, AltitudeLimits)>> = Tunable::new();
+
+/// Separation margins of each aircraft type
+static SEPARATION_MARGINS: Tunable<Vec<(AircraftType, SeparationMargins)>> = Tunable::new();
+
+/// A node of a path
+#[derive(Debug, Clone)]
+pub struct PathNode {
+    /// [`NodeType`] of the node
+    pub node_type: i32,
+
+    /// Identifier of the vertiport, aircraft or waypoint
+    pub identifier: String,
+
+    /// Location of the node
+    pub geom: PointZ,
+}
+
+impl PartialEq for PathNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.identifier == other.identifier
+    }
+}
+
+/// Cost of a path per objective, before weighting
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PathCosts {
+    /// Distance flown
+    pub distance_meters: f32,
+
+    /// Estimated flight time
+    pub time_seconds: f32,
+
+    /// Distance flown over populated ground
+    pub ground_risk_meters: f32,
+
+    /// Distance flown through noise-sensitive areas
+    pub noise_meters: f32,
+
+    /// Estimated energy, as the distance of level flight using as much
+    pub energy_meters: f32,
+}
+
+impl PathCosts {
+    /// Distance, time and energy of a straight leg between two points
+    ///
+    /// The horizontal and vertical parts of the leg are flown one after
+    ///  the other, so these never exceed the costs of any other route
+    ///  between the same points.
+    pub fn leg(from: &PointZ, to: &PointZ) -> Self {
+        let distance_meters = super::utils::distance_meters(from, to);
+        let climb_meters = (to.z - from.z) as f32;
+        let horizontal_meters = (distance_meters.powi(2) - climb_meters.powi(2))
+            .max(0.)
+            .sqrt();
+
+        PathCosts {
+            distance_meters,
+            time_seconds: horizontal_meters / CRUISE_SPEED_METERS_PER_SECOND
+                + climb_meters.abs() / VERTICAL_SPEED_METERS_PER_SECOND,
+            energy_meters: horizontal_meters + climb_meters.max(0.) * CLIMB_ENERGY_FACTOR,
+            ..Default::default()
+        }
+    }
+}
+
+impl Add for PathCosts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        PathCosts {
+            distance_meters: self.distance_meters + other.distance_meters,
+            time_seconds: self.time_seconds + other.time_seconds,
+            ground_risk_meters: self.ground_risk_meters + other.ground_risk_meters,
+            noise_meters: self.noise_meters + other.noise_meters,
+            energy_meters: self.energy_meters + other.energy_meters,
+        }
+    }
+}
+
+impl AddAssign for PathCosts {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// Weights of the objectives combined into the cost of a path
+///  Each weight scales the matching field of [`PathCosts`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CostWeights {
+    /// Weight per meter flown
+    pub distance: f32,
+
+    /// Weight per second of flight
+    pub time: f32,
+
+    /// Weight per meter flown over populated ground
+    ///  Reserved: no population density layer is loaded yet, so the
+    ///  distance is always zero and the weight must be too
+    pub ground_risk: f32,
+
+    /// Weight per meter flown through noise-sensitive areas
+    pub noise: f32,
+
+    /// Weight per meter of level-flight equivalent energy
+    pub energy: f32,
+}
+
+/// Ranks paths by distance, a meter flown through a noise-sensitive area
+///  during its quiet hours counting double
+impl Default for CostWeights {
+    fn default() -> Self {
+        CostWeights {
+            distance: 1.0,
+            time: 0.0,
+            ground_risk: 0.0,
+            noise: 1.0,
+            energy: 0.0,
+        }
+    }
+}
+
+impl CostWeights {
+    /// Weighted sum of the costs
+    pub fn total(&self, costs: &PathCosts) -> f32 {
+        self.distance * costs.distance_meters
+            + self.time * costs.time_seconds
+            + self.ground_risk * costs.ground_risk_meters
+            + self.noise * costs.noise_meters
+            + self.energy * costs.energy_meters
+    }
+
+    /// Weights must be finite and non-negative, with at least one
+    ///  objective to rank by. The reserved ground risk weight must be zero.
+    pub fn is_valid(&self) -> bool {
+        let weights = [
+            self.distance,
+            self.time,
+            self.ground_risk,
+            self.noise,
+            self.energy,
+        ];
+
+        self.ground_risk == 0.
+            && weights.iter().all(|w| w.is_finite() && *w >= 0.)
+            && weights.iter().any(|w| *w > 0.)
+    }
+}
+
+/// Checks the weights used when a request doesn't provide any
+pub fn check_default_weights(weights: &CostWeights) -> Result<(), PostgisError> {
+    if !weights.is_valid() {
+        postgis_error!("invalid default path cost weights: {:?}", weights);
+        return Err(PostgisError::BestPath(PathError::InvalidWeights));
+    }
+
+    Ok(())
+}
+
+/// Sets the weights used when a request doesn't provide any, replacing any
+///  set before
+pub fn set_default_weights(weights: CostWeights) -> Result<(), PostgisError> {
+    check_default_weights(&weights)?;
+    DEFAULT_WEIGHTS.set(weights);
+    Ok(())
+}
+
+/// Gets the weights used when a request doesn't provide any
+pub fn get_default_weights() -> CostWeights {
+    DEFAULT_WEIGHTS.get().unwrap_or_default()
+}
+
+/// Altitudes an aircraft type may cruise at
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AltitudeLimits {
+    /// Lowest altitude, in meters
+    pub floor_meters: f32,
+
+    /// Highest altitude, in meters
+    pub ceiling_meters: f32,
+}
+
+impl AltitudeLimits {
+    /// The flight levels within the limits, or all of them without limits
+    pub fn flight_levels(limits: Option<AltitudeLimits>) -> Vec<f32> {
+        FLIGHT_LEVELS
+            .iter()
+            .copied()
+            .filter(|level| {
+                limits.is_none_or(|l| (l.floor_meters..=l.ceiling_meters).contains(level))
+            })
+            .collect()
+    }
+}
+
+/// Parses altitude limits per aircraft type, such as
+///  `Rotorcraft:0-120,Hybridlift:60-300`
+pub fn parse_altitude_limits(
+    text: &str,
+) -> Result<Vec<(AircraftType, AltitudeLimits)>, PostgisError> {
+    let invalid = |entry: &str| {
+        postgis_error!("invalid aircraft altitude limits: {entry}");
+        PostgisError::BestPath(PathError::InvalidAltitudeLimits)
+    };
+
+    let mut table: Vec<(AircraftType, AltitudeLimits)> = vec![];
+    for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (aircraft_type, range) = entry.split_once(':').ok_or_else(|| invalid(entry))?;
+        let (floor, ceiling) = range.split_once('-').ok_or_else(|| invalid(entry))?;
+        let aircraft_type: AircraftType =
+            aircraft_type.trim().parse().map_err(|_| invalid(entry))?;
+        let limits = AltitudeLimits {
+            floor_meters: floor.trim().parse().map_err(|_| invalid(entry))?,
+            ceiling_meters: ceiling.trim().parse().map_err(|_| invalid(entry))?,
+        };
+
+        if !limits.floor_meters.is_finite()
+            || !limits.ceiling_meters.is_finite()
+            || limits.floor_meters < 0.0
+            || limits.floor_meters > limits.ceiling_meters
+            || table.iter().any(|(t, _)| *t == aircraft_type)
+        {
+            return Err(invalid(entry));
+        }
+
+        table.push((aircraft_type, limits));
+    }
+
+    Ok(table)
+}
+
+/// Sets the altitude limits of each aircraft type, replacing any set before
+pub fn set_altitude_limits(table: Vec<(AircraftType, AltitudeLimits)>) {
+    ALTITUDE_LIMITS.set(table);
+}
+
+/// Gets the altitude limits of an aircraft type, if it has any
+pub fn get_altitude_limits(aircraft_type: AircraftType) -> Option<AltitudeLimits> {
+    ALTITUDE_LIMITS
+        .get()?
+        .into_iter()
+        .find(|(t, _)| *t == aircraft_type)
+        .map(|(_, limits)| limits)
+}
+
+/// Distances a flight keeps from other flights
+///  A path conflicts with a flight if it comes closer than the horizontal
+///  margin on the ground and closer than the vertical margin in altitude.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SeparationMargins {
+    /// Horizontal separation, in meters
+    pub horizontal_meters: f64,
+
+    /// Vertical separation, in meters
+    pub vertical_meters: f64,
+}
+
+impl Default for SeparationMargins {
+    fn default() -> Self {
+        SeparationMargins {
+            horizontal_meters: FLIGHT_SEPARATION_METERS,
+            vertical_meters: FLIGHT_SEPARATION_METERS,
+        }
+    }
+}
+
+impl SeparationMargins {
+    /// The margins kept between two flights, the larger of each
+    pub fn between(self, other: SeparationMargins) -> SeparationMargins {
+        SeparationMargins {
+            horizontal_meters: self.horizontal_meters.max(other.horizontal_meters),
+            vertical_meters: self.vertical_meters.max(other.vertical_meters),
+        }
+    }
+
+    /// The margins grown by a distance on each axis, such as the tolerance
+    ///  a filed path was simplified with
+    pub fn widened(self, meters: f64) -> SeparationMargins {
+        SeparationMargins {
+            horizontal_meters: self.horizontal_meters + meters,
+            vertical_meters: self.vertical_meters + meters,
+        }
+    }
+
+    /// Segment length under which a conflict between two segments is
+    ///  considered found
+    pub fn resolution_meters(&self) -> f64 {
+        self.horizontal_meters.min(self.vertical_meters)
+    }
+
+    /// Straight-line distance within which the margins may be breached
+    pub fn reach_meters(&self) -> f64 {
+        self.horizontal_meters.hypot(self.vertical_meters)
+    }
+}
+
+/// Parses separation margins per aircraft type, as horizontal and vertical
+///  meters, such as `Rotorcraft:10/15,Aeroplane:50/30`
+pub fn parse_separation_margins(
+    text: &str,
+) -> Result<Vec<(AircraftType, SeparationMargins)>, PostgisError> {
+    let invalid = |entry: &str| {
+        postgis_error!("invalid flight separation margins: {entry}");
+        PostgisError::BestPath(PathError::InvalidSeparationMargins)
+    };
+
+    let mut table: Vec<(AircraftType, SeparationMargins)> = vec![];
+    for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (aircraft_type, margins) = entry.split_once(':').ok_or_else(|| invalid(entry))?;
+        let (horizontal, vertical) = margins.split_once('/').ok_or_else(|| invalid(entry))?;
+        let aircraft_type: AircraftType =
+            aircraft_type.trim().parse().map_err(|_| invalid(entry))?;
+        let margins = SeparationMargins {
+            horizontal_meters: horizontal.trim().parse().map_err(|_| invalid(entry))?,
+            vertical_meters: vertical.trim().parse().map_err(|_| invalid(entry))?,
+        };
+
+        if !margins.horizontal_meters.is_finite()
+            || !margins.vertical_meters.is_finite()
+            || margins.horizontal_meters <= 0.0
+            || margins.vertical_meters <= 0.0
+            || table.iter().any(|(t, _)| *t == aircraft_type)
+        {
+            return Err(invalid(entry));
+        }
+
+        table.push((aircraft_type, margins));
+    }
+
+    Ok(table)
+}
+
+/// Sets the separation margins of each aircraft type, replacing any set before
+pub fn set_separation_margins(table: Vec<(AircraftType, SeparationMargins)>) {
+    SEPARATION_MARGINS.set(table);
+}
+
+/// Gets the separation margins of an aircraft type, the default margins
+///  if none are set for it
+pub fn get_separation_margins(aircraft_type: AircraftType) -> SeparationMargins {
+    SEPARATION_MARGINS
+        .get()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|(t, _)| *t == aircraft_type)
+        .map(|(_, margins)| margins)
+        .unwrap_or_default()
+}
+
+/// The largest margins of any aircraft type, to find the flights that may
+///  conflict with a path before their types are known
+pub fn get_widest_separation_margins() -> SeparationMargins {
+    SEPARATION_MARGINS
+        .get()
+        .unwrap_or_default()
+        .into_iter()
+        .fold(SeparationMargins::default(), |acc, (_, margins)| {
+            acc.between(margins)
+        })
+}
+
+/// Distances a path flies over areas it should keep away from
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Exposure {
+    /// Distance flown over populated ground
+    pub ground_risk_meters: f32,
+
+    /// Distance flown through noise-sensitive areas
+    pub noise_meters: f32,
+}
+
+/// A path from the origin, complete or not
+#[derive(Debug, Clone)]
+pub struct Path {
+    /// Nodes visited so far, starting with the origin
+    pub path: Vec<PathNode>,
+
+    /// Costs of the path so far
+    pub costs: PathCosts,
+
+    /// Costs of a straight leg from the last node to the target, a lower
+    ///  bound on the costs of the rest of the path
+    pub costs_to_target: PathCosts,
+
+    /// Weights the path is ranked by
+    pub weights: CostWeights,
+}
+
+impl Path {
+    fn heuristic(&self) -> f32 {
+        self.weights.total(&(self.costs + self.costs_to_target))
+    }
+
+    /// Weighted cost of the path so far
+    pub fn total_cost(&self) -> f32 {
+        self.weights.total(&self.costs)
+    }
+}
+
+// Reverse the ordering so that the BinaryHeap is a min-heap
+impl Ord for Path {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let oh = other.heuristic();
+        let sh = self.heuristic();
+
+        if oh < sh {
+            std::cmp::Ordering::Less
+        } else if oh > sh {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    }
+}
+
+impl PartialOrd for Path {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.heuristic() == other.heuristic()
+    }
+}
+
+impl Eq for Path {}
+
+/// Tally of why candidate paths were discarded during a search
+#[derive(Debug, Default, Copy, Clone)]
+struct SearchOutcome {
+    /// Number of waypoints available to the search
+    waypoint_count: usize,
+
+    /// Candidates dropped for exceeding the max flight distance
+    distance_exceeded: usize,
+
+    /// Candidates reaching the target that intersected a zone or flight plan
+    conflicted: usize,
+
+    /// Whether the search stopped at the time limit
+    timed_out: bool,
+}
+
+impl SearchOutcome {
+    /// Why a search that completed no paths came up empty
+    fn no_path_reason(&self) -> PathError {
+        if self.timed_out {
+            PathError::TimeBudgetExhausted
+        } else if self.waypoint_count == 0 && self.distance_exceeded == 0 {
+            PathError::NoWaypointsNearby
+        } else if self.conflicted > 0 {
+            PathError::AllCandidatesConflicted
+        } else {
+            PathError::ExceededMaxDistance
+        }
+    }
+}
+
+/// Bounds on a search
+#[derive(Debug, Copy, Clone)]
+pub struct SearchLimits {
+    /// Number of paths to return
+    pub path_count: usize,
+
+    /// Max number of nodes in a path, including the origin and target
+    pub max_path_node_count: usize,
+
+    /// Time after which the search gives up
+    pub time_budget: Duration,
+
+    /// Altitudes waypoints may be flown at, any flight level if `None`
+    pub altitude_limits: Option<AltitudeLimits>,
+}
+
+/// Paths found by a search
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Best paths, cheapest first
+    pub paths: Vec<Path>,
+
+    /// If the search stopped at its time budget, cheaper paths may exist
+    pub truncated: bool,
+
+    /// Candidates reaching the target that intersected a zone or flight plan
+    pub rejected: usize,
+}
+
+/// Source of the waypoints a path can go through
+#[async_trait]
+pub trait NodeProvider {
+    /// Waypoints that may be used to route between the origin and target
+    async fn waypoints(
+        &self,
+        origin: &PointZ,
+        target: &PointZ,
+    ) -> Result<Vec<Waypoint>, PostgisError>;
+}
+
+/// Vets paths that reach the target
+#[async_trait]
+pub trait ConflictChecker {
+    /// Whether the path crosses a zone or another flight
+    async fn conflicts(
+        &self,
+        points: Vec<PointZ>,
+        distance_meters: f32,
+    ) -> Result<bool, PostgisError>;
+
+    /// How far the path flies over populated ground and through
+    ///  noise-sensitive areas, nothing unless a checker knows of such areas
+    async fn exposure(&self, _points: &[PointZ]) -> Result<Exposure, PostgisError> {
+        Ok(Exposure::default())
+    }
+}
+
+/// Expands waypoints to a path node at each of the given flight levels
+fn waypoint_nodes(waypoints: Vec<Waypoint>, flight_levels: &[f32]) -> VecDeque<PathNode> {
+    waypoints
+        .into_iter()
+        .flat_map(|w| {
+            flight_levels
+                .iter()
+                .map(|fl| PathNode {
+                    node_type: NodeType::Waypoint as i32,
+                    identifier: w.identifier.clone(),
+                    geom: PointZ {
+                        x: w.geom.x,
+                        y: w.geom.y,
+                        z: *fl as f64,
+                        srid: w.geom.srid,
+                    },
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Modified A* algorithm for finding the best paths between two points
+///  Potentials are sorted by the weighted (costs traversed + costs to target)
+pub async fn a_star(
+    origin_node: PathNode,
+    target_node: PathNode,
+    nodes: &(impl NodeProvider + Sync),
+    checker: &(impl ConflictChecker + Sync),
+    limits: SearchLimits,
+    weights: CostWeights,
+) -> Result<SearchResult, PostgisError> {
+    postgis_debug!("entry.");
+    let start_time = Utc::now();
+    let mut outcome = SearchOutcome::default();
+
+    // The direct route is vetted first. When it is clear and has no
+    //  exposure, no detour can be cheaper, so short open hops skip the
+    //  waypoint search when a single path is requested.
+    let direct = direct_path(&origin_node, &target_node, checker, weights, &mut outcome).await?;
+    if let Some(path) = direct.as_ref().filter(|path| {
+        limits.path_count == 1
+            && path.costs.ground_risk_meters <= 0.
+            && path.costs.noise_meters <= 0.
+    }) {
+        postgis_debug!("direct route is clear, skipping the waypoint search.");
+        return Ok(SearchResult {
+            paths: vec![path.clone()],
+            truncated: false,
+            rejected: 0,
+        });
+    }
+
+    let waypoints = nodes
+        .waypoints(&origin_node.geom, &target_node.geom)
+        .await?;
+
+    // Using a binary heap to store potential paths
+    //  means potentials are sorted on insert with O(log n)
+    //  worst case time complexity
+    let mut potentials: BinaryHeap<Path> = BinaryHeap::new();
+    let mut completed: BinaryHeap<Path> = BinaryHeap::new();
+    outcome.waypoint_count = waypoints.len();
+
+    // Get all possible waypoints, including at different
+    //  flight elevations the aircraft may fly at
+    let flight_levels = AltitudeLimits::flight_levels(limits.altitude_limits);
+    let mut path_points = waypoint_nodes(waypoints, &flight_levels);
+
+    // Add the destination as a path point
+    path_points.push_front(target_node.clone());
+
+    // Add starting node
+    let starting_path = Path {
+        path: vec![origin_node.clone()],
+        costs: PathCosts::default(),
+        costs_to_target: PathCosts::leg(&origin_node.geom, &target_node.geom),
+        weights,
+    };
+
+    potentials.push(starting_path);
+    potentials.extend(direct);
+
+    // TODO(R5): Conditional approval zones
+    //  For now all zones are considered no-fly zones
+
+    // Run until we have 'limit' paths or we run out of potentials
+    while completed.len() < limits.path_count && !potentials.is_empty() {
+        if Utc::now() - start_time > limits.time_budget {
+            postgis_warn!("max calculation time reached");
+            outcome.timed_out = true;
+
+            // Keep the vetted paths still waiting for their turn
+            completed.extend(
+                potentials
+                    .drain()
+                    .filter(|path| path.path.last() == Some(&target_node)),
+            );
+            break;
+        }
+
+        let current = potentials.pop().ok_or_else(|| {
+            postgis_error!("no potential path found");
+            PostgisError::BestPath(PathError::Internal)
+        })?;
+
+        // Paths that reached the target were vetted when they got there.
+        //  Their exposure can make them costlier than partial paths, so they
+        //  only count as completed once nothing cheaper is left to expand.
+        if current.path.last() == Some(&target_node) {
+            completed.push(current);
+            continue;
+        }
+
+        for p in path_points.iter() {
+            // Don't backtrack
+            if current.path.contains(p) {
+                continue;
+            }
+
+            // The direct route was vetted before the search
+            if current.path.len() == 1 && p == &target_node {
+                continue;
+            }
+
+            let last = current.path.last().ok_or_else(|| {
+                postgis_error!("no last point found");
+                PostgisError::BestPath(PathError::Internal)
+            })?;
+
+            let mut tmp = current.clone();
+            tmp.costs += PathCosts::leg(&last.geom, &p.geom);
+
+            // Don't allow flights to exceed max distance
+            if tmp.costs.distance_meters > MAX_FLIGHT_DISTANCE_METERS {
+                outcome.distance_exceeded += 1;
+                continue;
+            }
+
+            tmp.path.push(p.clone());
+            tmp.costs_to_target = PathCosts::leg(&p.geom, &target_node.geom);
+
+            // If the path has reached the target, shove it into the
+            //  potentials list and move on
+            if p.identifier != target_node.identifier {
+                // Limit the max number of nodes to prevent crazy winding paths
+                //  waypoints should only be used to get around a local no-fly zone, to
+                //  so the total path length should be 2 (origin and target) plus a limited
+                //  number of nodes needed to circumvent 1-2 no-fly zones
+                if tmp.path.len() < limits.max_path_node_count {
+                    potentials.push(tmp);
+                }
+
+                continue;
+            }
+
+            // If the path has reached the target, do final checks
+            //  to ensure flight safety
+            let points = tmp.path.iter().map(|p| p.geom).collect::<Vec<PointZ>>();
+            if checker
+                .conflicts(points.clone(), tmp.costs.distance_meters)
+                .await
+                .map_err(|e| {
+                    postgis_error!("intersection checks failed: {}", e);
+                    e
+                })?
+            {
+                outcome.conflicted += 1;
+                continue;
+            }
+
+            let exposure = checker.exposure(&points).await.map_err(|e| {
+                postgis_error!("exposure checks failed: {}", e);
+                e
+            })?;
+
+            tmp.costs.ground_risk_meters = exposure.ground_risk_meters;
+            tmp.costs.noise_meters = exposure.noise_meters;
+
+            // Valid routes are ranked with the partial paths
+            potentials.push(tmp);
+        }
+    }
+
+    if completed.is_empty() {
+        let reason = outcome.no_path_reason();
+        postgis_warn!("no path found ({:?}): {}", outcome, reason);
+        return Err(PostgisError::BestPath(reason));
+    }
+
+    let mut completed = completed.into_sorted_vec();
+    completed.reverse();
+    completed.truncate(limits.path_count);
+
+    postgis_debug!("completed paths: {:?}", completed);
+    Ok(SearchResult {
+        paths: completed,
+        truncated: outcome.timed_out,
+        rejected: outcome.conflicted,
+    })
+}
+
+/// Vets the direct route from the origin to the target, `None` if it is
+///  too long or conflicts
+async fn direct_path(
+    origin_node: &PathNode,
+    target_node: &PathNode,
+    checker: &(impl ConflictChecker + Sync),
+    weights: CostWeights,
+    outcome: &mut SearchOutcome,
+) -> Result<Option<Path>, PostgisError> {
+    let mut costs = PathCosts::leg(&origin_node.geom, &target_node.geom);
+    if costs.distance_meters > MAX_FLIGHT_DISTANCE_METERS {
+        outcome.distance_exceeded += 1;
+        return Ok(None);
+    }
+
+    let points = vec![origin_node.geom, target_node.geom];
+    if checker
+        .conflicts(points.clone(), costs.distance_meters)
+        .await
+        .map_err(|e| {
+            postgis_error!("intersection checks failed: {}", e);
+            e
+        })?
+    {
+        outcome.conflicted += 1;
+        return Ok(None);
+    }
+
+    let exposure = checker.exposure(&points).await.map_err(|e| {
+        postgis_error!("exposure checks failed: {}", e);
+        e
+    })?;
+
+    costs.ground_risk_meters = exposure.ground_risk_meters;
+    costs.noise_meters = exposure.noise_meters;
+    Ok(Some(Path {
+        path: vec![origin_node.clone(), target_node.clone()],
+        costs,
+        costs_to_target: PathCosts::default(),
+        weights,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgis::DEFAULT_SRID;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves a fixed list of waypoints
+    struct InMemoryNodes(Vec<Waypoint>);
+
+    #[async_trait]
+    impl NodeProvider for InMemoryNodes {
+        async fn waypoints(
+            &self,
+            _origin: &PointZ,
+            _target: &PointZ,
+        ) -> Result<Vec<Waypoint>, PostgisError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Blocks the direct route, or every route, and counts the checks
+    struct InMemoryConflicts {
+        block_direct: bool,
+        block_all: bool,
+        delay: std::time::Duration,
+        checks: AtomicUsize,
+    }
+
+    impl InMemoryConflicts {
+        fn new(block_direct: bool, block_all: bool) -> Self {
+            InMemoryConflicts {
+                block_direct,
+                block_all,
+                delay: std::time::Duration::ZERO,
+                checks: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ConflictChecker for InMemoryConflicts {
+        async fn conflicts(
+            &self,
+            points: Vec<PointZ>,
+            _distance_meters: f32,
+        ) -> Result<bool, PostgisError> {
+            self.checks.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            Ok(self.block_all || (self.block_direct && points.len() == 2))
+        }
+    }
+
+    /// Reports the direct route as crossing a noise-sensitive area
+    struct NoisyDirect;
+
+    #[async_trait]
+    impl ConflictChecker for NoisyDirect {
+        async fn conflicts(
+            &self,
+            _points: Vec<PointZ>,
+            _distance_meters: f32,
+        ) -> Result<bool, PostgisError> {
+            Ok(false)
+        }
+
+        async fn exposure(&self, points: &[PointZ]) -> Result<Exposure, PostgisError> {
+            let noise_meters = if points.len() == 2 { 10_000. } else { 0. };
+            Ok(Exposure {
+                noise_meters,
+                ..Default::default()
+            })
+        }
+    }
+
+    fn node(identifier: &str, node_type: NodeType, x: f64, y: f64) -> PathNode {
+        PathNode {
+            node_type: node_type as i32,
+            identifier: identifier.to_string(),
+            geom: PointZ {
+                x,
+                y,
+                z: 0.0,
+                srid: Some(DEFAULT_SRID),
+            },
+        }
+    }
+
+    fn waypoint(identifier: &str, x: f64, y: f64) -> Waypoint {
+        Waypoint {
+            identifier: identifier.to_string(),
+            geom: postgis::ewkb::Point {
+                x,
+                y,
+                srid: Some(DEFAULT_SRID),
+            },
+        }
+    }
+
+    fn origin() -> PathNode {
+        node("origin", NodeType::Vertiport, 4.90, 52.37)
+    }
+
+    fn target() -> PathNode {
+        node("target", NodeType::Vertiport, 4.94, 52.37)
+    }
+
+    fn waypoints() -> InMemoryNodes {
+        InMemoryNodes(vec![
+            waypoint("far", 4.92, 52.40),
+            waypoint("near", 4.92, 52.38),
+        ])
+    }
+
+    fn limits(path_count: usize, max_path_node_count: usize) -> SearchLimits {
+        SearchLimits {
+            path_count,
+            max_path_node_count,
+            time_budget: Duration::try_seconds(10).unwrap(),
+            altitude_limits: None,
+        }
+    }
+
+    fn path(distance_traversed_meters: f32, distance_to_target_meters: f32) -> Path {
+        Path {
+            path: vec![],
+            costs: PathCosts {
+                distance_meters: distance_traversed_meters,
+                ..Default::default()
+            },
+            costs_to_target: PathCosts {
+                distance_meters: distance_to_target_meters,
+                ..Default::default()
+            },
+            weights: CostWeights::default(),
+        }
+    }
+
+    fn identifiers(path: &Path) -> Vec<&str> {
+        path.path.iter().map(|n| n.identifier.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_direct() {
+        let checker = InMemoryConflicts::new(false, false);
+        let result = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits(1, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.truncated);
+        assert_eq!(result.rejected, 0);
+        let paths = result.paths;
+        assert_eq!(paths.len(), 1);
+        assert_eq!(identifiers(&paths[0]), vec!["origin", "target"]);
+        assert_eq!(checker.checks.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_direct_fast_path() {
+        /// Fails any waypoint lookup
+        struct NoNodes;
+
+        #[async_trait]
+        impl NodeProvider for NoNodes {
+            async fn waypoints(
+                &self,
+                _origin: &PointZ,
+                _target: &PointZ,
+            ) -> Result<Vec<Waypoint>, PostgisError> {
+                Err(PostgisError::BestPath(PathError::Internal))
+            }
+        }
+
+        // a clear direct route is returned without looking up waypoints
+        let checker = InMemoryConflicts::new(false, false);
+        let result = a_star(
+            origin(),
+            target(),
+            &NoNodes,
+            &checker,
+            limits(1, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(identifiers(&result.paths[0]), vec!["origin", "target"]);
+        assert_eq!(checker.checks.load(Ordering::SeqCst), 1);
+
+        // alternatives still need the waypoints
+        let error = a_star(
+            origin(),
+            target(),
+            &NoNodes,
+            &checker,
+            limits(2, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, PostgisError::BestPath(PathError::Internal));
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_ranking() {
+        let checker = InMemoryConflicts::new(false, false);
+        let paths = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits(3, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap()
+        .paths;
+
+        assert_eq!(paths.len(), 3);
+        assert!(paths
+            .windows(2)
+            .all(|pair| pair[0].costs.distance_meters <= pair[1].costs.distance_meters));
+        assert_eq!(identifiers(&paths[0]), vec!["origin", "target"]);
+
+        // the near waypoint at the lowest flight level is the shortest detour
+        assert_eq!(identifiers(&paths[1]), vec!["origin", "near", "target"]);
+        assert_eq!(paths[1].path[1].geom.z, FLIGHT_LEVELS[0] as f64);
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_weights() {
+        // noise is ignored with no weight
+        let weights = CostWeights {
+            noise: 0.,
+            ..Default::default()
+        };
+        let paths = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &NoisyDirect,
+            limits(1, 3),
+            weights,
+        )
+        .await
+        .unwrap()
+        .paths;
+        assert_eq!(identifiers(&paths[0]), vec!["origin", "target"]);
+        assert_eq!(paths[0].costs.noise_meters, 10_000.);
+
+        // avoiding noise is worth a detour, even when a single path is
+        //  requested and the direct route reaches the target first
+        let paths = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &NoisyDirect,
+            limits(1, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap()
+        .paths;
+        assert_eq!(identifiers(&paths[0]), vec!["origin", "near", "target"]);
+        assert_eq!(paths[0].costs.noise_meters, 0.);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].total_cost(), paths[0].costs.distance_meters);
+        assert!(paths[0].costs.time_seconds > 0.);
+        assert!(paths[0].costs.energy_meters > paths[0].costs.distance_meters);
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_detour() {
+        let checker = InMemoryConflicts::new(true, false);
+        let result = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits(1, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap();
+
+        // the direct route was rejected
+        assert_eq!(result.rejected, 1);
+        let paths = result.paths;
+        assert_eq!(identifiers(&paths[0]), vec!["origin", "near", "target"]);
+        assert_eq!(paths[0].path[1].node_type, NodeType::Waypoint as i32);
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_node_limit() {
+        // only the origin and target, the blocked direct route is all there is
+        let checker = InMemoryConflicts::new(true, false);
+        let error = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits(1, 2),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::BestPath(PathError::AllCandidatesConflicted)
+        );
+
+        // no path uses more than the allowed nodes
+        let checker = InMemoryConflicts::new(false, false);
+        let paths = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits(5, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap()
+        .paths;
+        assert!(paths.iter().all(|p| p.path.len() <= 3));
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_no_waypoints() {
+        let checker = InMemoryConflicts::new(true, false);
+        let nodes = InMemoryNodes(vec![]);
+        let error = a_star(
+            origin(),
+            target(),
+            &nodes,
+            &checker,
+            limits(1, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, PostgisError::BestPath(PathError::NoWaypointsNearby));
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_all_conflicted() {
+        let checker = InMemoryConflicts::new(false, true);
+        let error = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits(1, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::BestPath(PathError::AllCandidatesConflicted)
+        );
+
+        // the direct route and each waypoint at each flight level
+        assert_eq!(
+            checker.checks.load(Ordering::SeqCst),
+            1 + 2 * FLIGHT_LEVELS.len()
+        );
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_altitude_limits() {
+        let checker = InMemoryConflicts::new(true, false);
+        let limits = SearchLimits {
+            altitude_limits: Some(AltitudeLimits {
+                floor_meters: 0.0,
+                ceiling_meters: 80.0,
+            }),
+            ..limits(5, 3)
+        };
+
+        let result = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits,
+            CostWeights::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.paths.is_empty());
+        for path in &result.paths {
+            assert!(path.path[1..path.path.len() - 1]
+                .iter()
+                .all(|node| node.geom.z <= 80.0));
+        }
+
+        // the direct route and each waypoint at the two lowest levels
+        assert_eq!(checker.checks.load(Ordering::SeqCst), 1 + 2 * 2);
+    }
+
+    #[test]
+    fn ut_altitude_limits() {
+        assert_eq!(AltitudeLimits::flight_levels(None), FLIGHT_LEVELS.to_vec());
+
+        let limits = AltitudeLimits {
+            floor_meters: 60.0,
+            ceiling_meters: 300.0,
+        };
+        assert_eq!(
+            AltitudeLimits::flight_levels(Some(limits)),
+            vec![80.0, 120.0]
+        );
+
+        let limits = AltitudeLimits {
+            floor_meters: 130.0,
+            ceiling_meters: 300.0,
+        };
+        assert!(AltitudeLimits::flight_levels(Some(limits)).is_empty());
+    }
+
+    #[test]
+    fn ut_parse_altitude_limits() {
+        assert!(parse_altitude_limits("").unwrap().is_empty());
+
+        let table = parse_altitude_limits(" Rotorcraft:0-120, Hybridlift:60.5-300 ").unwrap();
+        assert_eq!(
+            table,
+            vec![
+                (
+                    AircraftType::Rotorcraft,
+                    AltitudeLimits {
+                        floor_meters: 0.0,
+                        ceiling_meters: 120.0,
+                    }
+                ),
+                (
+                    AircraftType::Hybridlift,
+                    AltitudeLimits {
+                        floor_meters: 60.5,
+                        ceiling_meters: 300.0,
+                    }
+                ),
+            ]
+        );
+
+        for invalid in [
+            "Rotorcraft",
+            "Rotorcraft:120",
+            "Spaceship:0-120",
+            "Rotorcraft:a-120",
+            "Rotorcraft:120-0",
+            "Rotorcraft:0-inf",
+            "Rotorcraft:0-120,Rotorcraft:0-60",
+        ] {
+            assert_eq!(
+                parse_altitude_limits(invalid).unwrap_err(),
+                PostgisError::BestPath(PathError::InvalidAltitudeLimits)
+            );
+        }
+
+        // none set
+        assert_eq!(get_altitude_limits(AircraftType::Rotorcraft), None);
+    }
+
+    #[test]
+    fn ut_parse_separation_margins() {
+        assert!(parse_separation_margins("").unwrap().is_empty());
+
+        let table = parse_separation_margins(" Rotorcraft:10/15, Aeroplane:50.5/30 ").unwrap();
+        assert_eq!(
+            table,
+            vec![
+                (
+                    AircraftType::Rotorcraft,
+                    SeparationMargins {
+                        horizontal_meters: 10.0,
+                        vertical_meters: 15.0,
+                    }
+                ),
+                (
+                    AircraftType::Aeroplane,
+                    SeparationMargins {
+                        horizontal_meters: 50.5,
+                        vertical_meters: 30.0,
+                    }
+                ),
+            ]
+        );
+
+        for invalid in [
+            "Rotorcraft",
+            "Rotorcraft:10",
+            "Spaceship:10/15",
+            "Rotorcraft:a/15",
+            "Rotorcraft:0/15",
+            "Rotorcraft:10/-1",
+            "Rotorcraft:10/inf",
+            "Rotorcraft:10/15,Rotorcraft:5/5",
+        ] {
+            assert_eq!(
+                parse_separation_margins(invalid).unwrap_err(),
+                PostgisError::BestPath(PathError::InvalidSeparationMargins)
+            );
+        }
+
+        // none set
+        assert_eq!(
+            get_separation_margins(AircraftType::Rotorcraft),
+            SeparationMargins::default()
+        );
+        assert_eq!(
+            get_widest_separation_margins(),
+            SeparationMargins::default()
+        );
+    }
+
+    #[test]
+    fn ut_separation_margins() {
+        let a = SeparationMargins {
+            horizontal_meters: 10.0,
+            vertical_meters: 30.0,
+        };
+        let b = SeparationMargins {
+            horizontal_meters: 40.0,
+            vertical_meters: 5.0,
+        };
+
+        assert_eq!(
+            a.between(b),
+            SeparationMargins {
+                horizontal_meters: 40.0,
+                vertical_meters: 30.0,
+            }
+        );
+        assert_eq!(a.between(b), b.between(a));
+        assert_eq!(
+            a.widened(1.5),
+            SeparationMargins {
+                horizontal_meters: 11.5,
+                vertical_meters: 31.5,
+            }
+        );
+        assert_eq!(a.widened(0.0), a);
+        assert_eq!(a.resolution_meters(), 10.0);
+        assert_eq!(b.resolution_meters(), 5.0);
+        assert_eq!(
+            SeparationMargins {
+                horizontal_meters: 30.0,
+                vertical_meters: 40.0,
+            }
+            .reach_meters(),
+            50.0
+        );
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_max_distance() {
+        let checker = InMemoryConflicts::new(false, false);
+        let target = node("target", NodeType::Vertiport, 40.0, 0.0);
+        let error = a_star(
+            origin(),
+            target,
+            &waypoints(),
+            &checker,
+            limits(1, 3),
+            CostWeights::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::BestPath(PathError::ExceededMaxDistance)
+        );
+        assert_eq!(checker.checks.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn ut_a_star_time_budget() {
+        let checker = InMemoryConflicts {
+            delay: std::time::Duration::from_millis(20),
+            ..InMemoryConflicts::new(true, false)
+        };
+
+        let limits = SearchLimits {
+            time_budget: Duration::try_milliseconds(10).unwrap(),
+            ..limits(1, 3)
+        };
+
+        let error = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits,
+            CostWeights::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error,
+            PostgisError::BestPath(PathError::TimeBudgetExhausted)
+        );
+        assert_eq!(checker.checks.load(Ordering::SeqCst), 1);
+
+        // a path vetted before the time ran out is still returned
+        //  (a single clear direct route would skip the search entirely)
+        let checker = InMemoryConflicts {
+            delay: std::time::Duration::from_millis(20),
+            ..InMemoryConflicts::new(false, false)
+        };
+
+        let limits = SearchLimits {
+            path_count: 2,
+            ..limits
+        };
+
+        let result = a_star(
+            origin(),
+            target(),
+            &waypoints(),
+            &checker,
+            limits,
+            CostWeights::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(identifiers(&result.paths[0]), vec!["origin", "target"]);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn ut_path_order() {
+        // End time (assumed) is before start time
+        let mut paths: BinaryHeap<Path> = BinaryHeap::new();
+
+        paths.push(path(2., 0.));
+        paths.push(path(1., 0.));
+
+        assert_eq!(paths.pop().unwrap().costs.distance_meters, 1.);
+        assert_eq!(paths.pop().unwrap().costs.distance_meters, 2.);
+    }
+
+    #[test]
+    fn ut_path_order_geodesic() {
+        // Two candidate legs of the same angular length from the equator.
+        //  A spherical (haversine) heuristic ties these, the ellipsoid
+        //  geodesic used by PostGIS ranks the meridian leg shorter.
+        let origin = PointZ {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            srid: Some(DEFAULT_SRID),
+        };
+
+        let node = |identifier: &str, x: f64, y: f64| PathNode {
+            node_type: NodeType::Waypoint as i32,
+            identifier: identifier.to_string(),
+            geom: PointZ { x, y, ..origin },
+        };
+
+        let north = node("north", 0.0, 0.5);
+        let east = node("east", 0.5, 0.0);
+
+        let mut paths: BinaryHeap<Path> = BinaryHeap::new();
+        for _ in 0..10 {
+            for n in [&east, &north] {
+                paths.push(Path {
+                    path: vec![n.clone()],
+                    ..path(super::super::utils::distance_meters(&origin, &n.geom), 0.)
+                });
+            }
+        }
+
+        // The ranking must be the same on every insertion
+        for _ in 0..10 {
+            assert_eq!(paths.pop().unwrap().path[0], north);
+        }
+
+        for _ in 0..10 {
+            assert_eq!(paths.pop().unwrap().path[0], east);
+        }
+    }
+
+    #[test]
+    fn test_no_path_reason() {
+        let outcome = SearchOutcome {
+            waypoint_count: 3,
+            distance_exceeded: 2,
+            conflicted: 4,
+            timed_out: true,
+        };
+        assert_eq!(outcome.no_path_reason(), PathError::TimeBudgetExhausted);
+
+        let outcome = SearchOutcome {
+            timed_out: false,
+            ..outcome
+        };
+        assert_eq!(outcome.no_path_reason(), PathError::AllCandidatesConflicted);
+
+        let outcome = SearchOutcome {
+            conflicted: 0,
+            ..outcome
+        };
+        assert_eq!(outcome.no_path_reason(), PathError::ExceededMaxDistance);
+
+        // direct route blocked, nothing to route around it with
+        let outcome = SearchOutcome {
+            waypoint_count: 0,
+            distance_exceeded: 0,
+            conflicted: 1,
+            timed_out: false,
+        };
+        assert_eq!(outcome.no_path_reason(), PathError::NoWaypointsNearby);
+
+        // direct route too long, even without waypoints
+        let outcome = SearchOutcome {
+            distance_exceeded: 1,
+            conflicted: 0,
+            ..outcome
+        };
+        assert_eq!(outcome.no_path_reason(), PathError::ExceededMaxDistance);
+    }
+
+    #[test]
+    fn test_partial_eq_path_node() {
+        let node = PathNode {
+            node_type: 0,
+            identifier: "test".to_string(),
+            geom: PointZ {
+                x: 0.,
+                y: 0.,
+                z: 0.,
+                srid: None,
+            },
+        };
+
+        let other = PathNode {
+            identifier: "test2".to_string(),
+            ..node.clone()
+        };
+
+        assert_ne!(node, other);
+
+        let other = PathNode {
+            node_type: 1,
+            geom: PointZ {
+                x: 1.,
+                y: 1.,
+                z: 1.,
+                srid: None,
+            },
+            ..node.clone()
+        };
+        assert_eq!(node, other);
+    }
+
+    #[test]
+    fn test_path_eq() {
+        let mut path = path(0., 0.);
+
+        let heuristic = path.heuristic();
+        assert_eq!(
+            heuristic,
+            path.costs_to_target.distance_meters + path.costs.distance_meters
+        );
+
+        path.costs.distance_meters = 1.;
+        let heuristic = path.heuristic();
+        assert_eq!(
+            heuristic,
+            path.costs_to_target.distance_meters + path.costs.distance_meters
+        );
+
+        path.costs_to_target.distance_meters = 2.;
+        let heuristic = path.heuristic();
+        assert_eq!(
+            heuristic,
+            path.costs_to_target.distance_meters + path.costs.distance_meters
+        );
+
+        let mut other = path.clone();
+        assert!(path.eq(&other));
+
+        other.costs.distance_meters = 2.;
+        assert!(!path.eq(&other));
+
+        // ordering is reversed for the min heap, comparison is reversed
+        assert!(path > other);
+
+        path.costs.distance_meters = 10.0;
+        assert!(path < other);
+
+        // objectives without weight don't affect the ranking
+        other.costs.time_seconds = 1000.;
+        assert!(path < other);
+
+        other.weights.time = 1.;
+        assert!(path > other);
+        assert_eq!(other.total_cost(), 1002.);
+    }
+
+    #[test]
+    fn ut_path_costs_leg() {
+        let origin = origin().geom;
+
+        let climb = PathCosts::leg(&origin, &PointZ { z: 30., ..origin });
+        assert_eq!(climb.distance_meters, 30.);
+        assert_eq!(climb.time_seconds, 30. / VERTICAL_SPEED_METERS_PER_SECOND);
+        assert_eq!(climb.energy_meters, 30. * CLIMB_ENERGY_FACTOR);
+        assert_eq!(climb.ground_risk_meters, 0.);
+        assert_eq!(climb.noise_meters, 0.);
+
+        // descents cost time, but no energy
+        let descent = PathCosts::leg(&PointZ { z: 30., ..origin }, &origin);
+        assert_eq!(descent.time_seconds, climb.time_seconds);
+        assert_eq!(descent.energy_meters, 0.);
+
+        let level = PathCosts::leg(&origin, &target().geom);
+        assert!(level.distance_meters > 0.);
+        assert_eq!(level.energy_meters, level.distance_meters);
+        assert_eq!(
+            level.time_seconds,
+            level.distance_meters / CRUISE_SPEED_METERS_PER_SECOND
+        );
+
+        let total = climb + level;
+        assert_eq!(
+            total.distance_meters,
+            climb.distance_meters + level.distance_meters
+        );
+        assert_eq!(
+            total.energy_meters,
+            climb.energy_meters + level.energy_meters
+        );
+    }
+
+    #[test]
+    fn ut_cost_weights() {
+        let costs = PathCosts {
+            distance_meters: 1.,
+            time_seconds: 2.,
+            ground_risk_meters: 3.,
+            noise_meters: 4.,
+            energy_meters: 5.,
+        };
+
+        assert_eq!(CostWeights::default().total(&costs), 5.);
+
+        let weights = CostWeights {
+            distance: 1.,
+            time: 1.,
+            ground_risk: 1.,
+            noise: 1.,
+            energy: 1.,
+        };
+        assert_eq!(weights.total(&costs), 15.);
+        assert!(CostWeights {
+            ground_risk: 0.,
+            ..weights
+        }
+        .is_valid());
+        assert!(CostWeights::default().is_valid());
+
+        for invalid in [
+            CostWeights {
+                distance: 0.,
+                noise: 0.,
+                ..Default::default()
+            },
+            CostWeights {
+                time: -1.,
+                ..Default::default()
+            },
+            CostWeights {
+                noise: f32::NAN,
+                ..Default::default()
+            },
+            CostWeights {
+                energy: f32::INFINITY,
+                ..Default::default()
+            },
+            // reserved
+            weights,
+        ] {
+            assert!(!invalid.is_valid());
+        }
+
+        assert_eq!(get_default_weights(), CostWeights::default());
+    }
+}
@@ -0,0 +1,406 @@
+//! Validation rules for aircraft telemetry, configured at startup and
+//!  replaced when the configuration is reloaded.
+//!
+//! Reports that are malformed (coordinates out of bounds, invalid
+//!  identifiers) are always rejected. On top of that, these rules reject
+//!  reports that are well-formed but implausible: an aircraft teleporting
+//!  between two updates, a sudden change of speed, an altitude above the
+//!  ceiling of the airspace, or a timestamp too far in the future for
+//!  clock differences to explain. Rules set to zero are disabled, except
+//!  for the clock skew which defaults to none.
+//!
+//! Aircraft on the bypass list, such as test aircraft, skip these rules.
+
+use super::aircraft::AircraftError;
+use super::PostgisError;
+use crate::reload::Tunable;
+use crate::types::Position;
+use lib_common::time::{DateTime, Duration, Utc};
+use postgis::ewkb::PointZ;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Rules applied to aircraft reports
+static RULES: Tunable<Arc<Rules>> = Tunable::new();
+
+/// Number of velocities rejected for a speed jump
+static SPEED_JUMP_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of positions rejected for a position jump
+static POSITION_JUMP_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of positions rejected above the altitude ceiling
+static ALTITUDE_CEILING_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of reports rejected for a timestamp in the future
+static CLOCK_SKEW_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+
+/// A validation rule
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Speed changed too much between two velocity updates
+    SpeedJump,
+
+    /// Position moved too far between two position updates
+    PositionJump,
+
+    /// Position is above the altitude ceiling
+    AltitudeCeiling,
+
+    /// Report is timestamped too far in the future
+    ClockSkew,
+}
+
+impl Rule {
+    /// Counter of the reports rejected by this rule
+    fn counter(&self) -> &'static AtomicU64 {
+        match self {
+            Rule::SpeedJump => &SPEED_JUMP_REJECTIONS,
+            Rule::PositionJump => &POSITION_JUMP_REJECTIONS,
+            Rule::AltitudeCeiling => &ALTITUDE_CEILING_REJECTIONS,
+            Rule::ClockSkew => &CLOCK_SKEW_REJECTIONS,
+        }
+    }
+
+    /// Error returned for reports rejected by this rule
+    fn error(&self) -> AircraftError {
+        match self {
+            Rule::SpeedJump => AircraftError::Velocity,
+            Rule::PositionJump | Rule::AltitudeCeiling => AircraftError::Location,
+            Rule::ClockSkew => AircraftError::Time,
+        }
+    }
+}
+
+/// Counters of the reports rejected by each rule since startup
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Rejections {
+    /// Velocities rejected for a speed jump
+    pub speed_jump: u64,
+
+    /// Positions rejected for a position jump
+    pub position_jump: u64,
+
+    /// Positions rejected above the altitude ceiling
+    pub altitude_ceiling: u64,
+
+    /// Reports rejected for a timestamp in the future
+    pub clock_skew: u64,
+}
+
+/// Gets the counters of the reports rejected by each rule
+pub fn rejections() -> Rejections {
+    Rejections {
+        speed_jump: SPEED_JUMP_REJECTIONS.load(Ordering::Relaxed),
+        position_jump: POSITION_JUMP_REJECTIONS.load(Ordering::Relaxed),
+        altitude_ceiling: ALTITUDE_CEILING_REJECTIONS.load(Ordering::Relaxed),
+        clock_skew: CLOCK_SKEW_REJECTIONS.load(Ordering::Relaxed),
+    }
+}
+
+/// Counts a report rejected by a rule, returning the error to report
+pub fn reject(rule: Rule) -> PostgisError {
+    rule.counter().fetch_add(1, Ordering::Relaxed);
+    postgis_debug!("rejections: {:?}", rejections());
+    PostgisError::Aircraft(rule.error())
+}
+
+/// Validation rules applied to aircraft reports
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rules {
+    /// Max change of speed between two velocity updates, zero to disable
+    pub max_speed_jump_mps: f32,
+
+    /// Max distance between two position updates, zero to disable
+    pub max_position_jump_meters: f32,
+
+    /// Max altitude of a position, zero to disable
+    pub altitude_ceiling_meters: f32,
+
+    /// How far in the future a report may be timestamped
+    pub clock_skew_seconds: u32,
+
+    /// Identifiers of the aircraft exempt from these rules
+    pub bypass: HashSet<String>,
+}
+
+impl Rules {
+    /// Whether an aircraft is exempt from the rules
+    fn bypassed(&self, identifier: &str) -> bool {
+        self.bypass.contains(identifier)
+    }
+
+    /// Rejects reports timestamped further in the future than the clock skew
+    pub fn check_timestamp(
+        &self,
+        identifier: &str,
+        timestamp: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<(), Rule> {
+        if self.bypassed(identifier) {
+            return Ok(());
+        }
+
+        let skew = Duration::try_seconds(self.clock_skew_seconds as i64).unwrap_or_default();
+        if timestamp > now + skew {
+            postgis_error!("{identifier} timestamp is in the future: {timestamp}");
+            return Err(Rule::ClockSkew);
+        }
+
+        Ok(())
+    }
+
+    /// Rejects positions above the altitude ceiling
+    pub fn check_altitude(&self, identifier: &str, position: &Position) -> Result<(), Rule> {
+        if self.bypassed(identifier) || self.altitude_ceiling_meters <= 0.0 {
+            return Ok(());
+        }
+
+        if position.altitude_meters.0 > self.altitude_ceiling_meters as f64 {
+            postgis_error!(
+                "{identifier} altitude is above the ceiling: {}",
+                position.altitude_meters
+            );
+            return Err(Rule::AltitudeCeiling);
+        }
+
+        Ok(())
+    }
+
+    /// Rejects a position too far from the previous position of the aircraft
+    pub fn check_position_jump(
+        &self,
+        identifier: &str,
+        previous: &Position,
+        next: &Position,
+    ) -> Result<(), Rule> {
+        if self.bypassed(identifier) || self.max_position_jump_meters <= 0.0 {
+            return Ok(());
+        }
+
+        let distance =
+            super::utils::distance_meters(&PointZ::from(*previous), &PointZ::from(*next));
+
+        if distance > self.max_position_jump_meters {
+            postgis_error!("{identifier} position jumped {distance} meters.");
+            return Err(Rule::PositionJump);
+        }
+
+        Ok(())
+    }
+
+    /// Rejects a speed too different from the previous speed of the aircraft,
+    ///  speeds combining the horizontal and vertical velocities
+    pub fn check_speed_jump(
+        &self,
+        identifier: &str,
+        previous_mps: f64,
+        next_mps: f64,
+    ) -> Result<(), Rule> {
+        if self.bypassed(identifier) || self.max_speed_jump_mps <= 0.0 {
+            return Ok(());
+        }
+
+        let jump = (next_mps - previous_mps).abs();
+        if jump > self.max_speed_jump_mps as f64 {
+            postgis_error!("{identifier} speed jumped {jump} m/s.");
+            return Err(Rule::SpeedJump);
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the rule limits are positive, or zero to disable them
+pub fn check_rules(rules: &Rules) -> Result<(), PostgisError> {
+    let limits = [
+        rules.max_speed_jump_mps,
+        rules.max_position_jump_meters,
+        rules.altitude_ceiling_meters,
+    ];
+
+    if limits
+        .iter()
+        .any(|limit| !limit.is_finite() || *limit < 0.0)
+    {
+        postgis_error!(
+            "rule limits must be positive, or zero to disable: {:?}",
+            rules
+        );
+        return Err(PostgisError::Aircraft(AircraftError::Rules));
+    }
+
+    Ok(())
+}
+
+/// Sets the rules applied to aircraft reports, replacing any set before
+pub fn set_rules(rules: Rules) -> Result<(), PostgisError> {
+    check_rules(&rules)?;
+    RULES.set(Arc::new(rules));
+    Ok(())
+}
+
+/// Gets the rules applied to aircraft reports
+pub fn get_rules() -> Arc<Rules> {
+    RULES.get().unwrap_or_default()
+}
+
+/// Splits a comma-separated list of bypassed identifiers
+pub fn bypass_list(identifiers: &str) -> HashSet<String> {
+    identifiers
+        .split(',')
+        .map(str::trim)
+        .filter(|identifier| !identifier.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(longitude: f64, latitude: f64, altitude_meters: f64) -> Position {
+        Position {
+            longitude: longitude.into(),
+            latitude: latitude.into(),
+            altitude_meters: altitude_meters.into(),
+        }
+    }
+
+    fn rules() -> Rules {
+        Rules {
+            max_speed_jump_mps: 20.0,
+            max_position_jump_meters: 1000.0,
+            altitude_ceiling_meters: 500.0,
+            clock_skew_seconds: 2,
+            bypass: bypass_list("TEST-1, TEST-2,,"),
+        }
+    }
+
+    #[test]
+    fn ut_bypass_list() {
+        let bypass = bypass_list(" TEST-1,TEST-2 ,, ");
+        assert_eq!(bypass.len(), 2);
+        assert!(bypass.contains("TEST-1"));
+        assert!(bypass.contains("TEST-2"));
+        assert!(bypass_list("").is_empty());
+    }
+
+    #[test]
+    fn ut_check_timestamp() {
+        let rules = rules();
+        let now = Utc::now();
+        let skewed = now + Duration::try_seconds(1).unwrap();
+        let future = now + Duration::try_seconds(3).unwrap();
+
+        assert!(rules.check_timestamp("AIRCRAFT", now, now).is_ok());
+        assert!(rules.check_timestamp("AIRCRAFT", skewed, now).is_ok());
+        assert_eq!(
+            rules.check_timestamp("AIRCRAFT", future, now),
+            Err(Rule::ClockSkew)
+        );
+        assert!(rules.check_timestamp("TEST-1", future, now).is_ok());
+
+        // no skew allowed by default
+        let rules = Rules::default();
+        assert_eq!(
+            rules.check_timestamp("AIRCRAFT", skewed, now),
+            Err(Rule::ClockSkew)
+        );
+    }
+
+    #[test]
+    fn ut_check_altitude() {
+        let rules = rules();
+        assert!(rules
+            .check_altitude("AIRCRAFT", &position(4.9, 52.3, 500.0))
+            .is_ok());
+        assert_eq!(
+            rules.check_altitude("AIRCRAFT", &position(4.9, 52.3, 501.0)),
+            Err(Rule::AltitudeCeiling)
+        );
+        assert!(rules
+            .check_altitude("TEST-2", &position(4.9, 52.3, 501.0))
+            .is_ok());
+        assert!(Rules::default()
+            .check_altitude("AIRCRAFT", &position(4.9, 52.3, 10000.0))
+            .is_ok());
+    }
+
+    #[test]
+    fn ut_check_position_jump() {
+        let rules = rules();
+        let previous = position(4.9, 52.3, 100.0);
+
+        // ~700 meters north
+        let near = position(4.9, 52.3063, 100.0);
+        assert!(rules
+            .check_position_jump("AIRCRAFT", &previous, &near)
+            .is_ok());
+
+        // ~7 kilometers north
+        let far = position(4.9, 52.363, 100.0);
+        assert_eq!(
+            rules.check_position_jump("AIRCRAFT", &previous, &far),
+            Err(Rule::PositionJump)
+        );
+        assert!(rules.check_position_jump("TEST-1", &previous, &far).is_ok());
+        assert!(Rules::default()
+            .check_position_jump("AIRCRAFT", &previous, &far)
+            .is_ok());
+    }
+
+    #[test]
+    fn ut_check_speed_jump() {
+        let rules = rules();
+        assert!(rules.check_speed_jump("AIRCRAFT", 30.0, 50.0).is_ok());
+        assert!(rules.check_speed_jump("AIRCRAFT", 50.0, 30.0).is_ok());
+        assert_eq!(
+            rules.check_speed_jump("AIRCRAFT", 30.0, 50.5),
+            Err(Rule::SpeedJump)
+        );
+        assert!(rules.check_speed_jump("TEST-2", 30.0, 50.5).is_ok());
+        assert!(Rules::default()
+            .check_speed_jump("AIRCRAFT", 0.0, 300.0)
+            .is_ok());
+    }
+
+    #[test]
+    fn ut_reject() {
+        let before = rejections();
+        assert_eq!(
+            reject(Rule::AltitudeCeiling),
+            PostgisError::Aircraft(AircraftError::Location)
+        );
+        assert_eq!(
+            reject(Rule::SpeedJump),
+            PostgisError::Aircraft(AircraftError::Velocity)
+        );
+
+        let after = rejections();
+        assert!(after.altitude_ceiling > before.altitude_ceiling);
+        assert!(after.speed_jump > before.speed_jump);
+    }
+
+    #[test]
+    fn ut_set_rules_invalid() {
+        let invalid = Rules {
+            max_speed_jump_mps: -1.0,
+            ..rules()
+        };
+        assert_eq!(
+            set_rules(invalid),
+            Err(PostgisError::Aircraft(AircraftError::Rules))
+        );
+
+        let invalid = Rules {
+            altitude_ceiling_meters: f32::NAN,
+            ..rules()
+        };
+        assert_eq!(
+            set_rules(invalid),
+            Err(PostgisError::Aircraft(AircraftError::Rules))
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! Startup self-test, run with `--verify`.
+//!
+//! Exercises the PostGIS backend end to end in a throwaway schema: the tables
+//!  are created, every statement is prepared, a pair of vertiports and a zone
+//!  are inserted, a path is routed between the vertiports and checked against
+//!  the zone. The schema is dropped afterwards, whether the test passed or not.
+
+use super::{best_path, psql_transaction, vertiport, zone, PSQL_SCHEMA};
+use crate::grpc::server::grpc_server::{
+    BestPathRequest, Coordinates, FlightPriority, NodeType, Vertiport, Zone, ZoneType,
+};
+use lib_common::time::{Duration, Utc};
+use postgis::ewkb::PointZ;
+use std::fmt::{self, Display, Formatter};
+
+/// Prefix of the schema created for the self-test
+pub const SCHEMA_PREFIX: &str = "svc_gis_verify";
+
+/// Identifiers of the synthetic vertiports
+const VERTIPORT_IDENTIFIERS: [&str; 2] = ["verify-vertiport-a", "verify-vertiport-b"];
+
+/// Identifier of the synthetic zone
+const ZONE_IDENTIFIER: &str = "verify-zone";
+
+/// Possible failures of the self-test
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SelfTestError {
+    /// Could not create or drop the schema
+    Schema,
+
+    /// Could not create the tables or prepare the statements
+    Setup,
+
+    /// Could not insert the vertiports or the zone
+    Insert,
+
+    /// No path was found between the vertiports
+    BestPath,
+
+    /// The intersection checks did not give the expected result
+    Intersection,
+}
+
+impl Display for SelfTestError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SelfTestError::Schema => write!(f, "Could not create or drop the schema."),
+            SelfTestError::Setup => write!(f, "Could not initialize the schema."),
+            SelfTestError::Insert => write!(f, "Could not insert test data."),
+            SelfTestError::BestPath => write!(f, "No path found between vertiports."),
+            SelfTestError::Intersection => write!(f, "Unexpected intersection check result."),
+        }
+    }
+}
+
+impl std::error::Error for SelfTestError {}
+
+/// Name of the schema to run the self-test in, unique to this process
+pub fn schema_name() -> String {
+    format!("{SCHEMA_PREFIX}_{}", std::process::id())
+}
+
+/// A square around the provided point
+fn square(latitude: f64, longitude: f64, half_side: f64) -> Vec<Coordinates> {
+    let vertex = |latitude: f64, longitude: f64| Coordinates {
+        latitude,
+        longitude,
+    };
+
+    vec![
+        vertex(latitude - half_side, longitude - half_side),
+        vertex(latitude + half_side, longitude - half_side),
+        vertex(latitude + half_side, longitude + half_side),
+        vertex(latitude - half_side, longitude + half_side),
+        vertex(latitude - half_side, longitude - half_side),
+    ]
+}
+
+/// Two vertiports about a kilometer apart
+fn vertiports() -> Vec<Vertiport> {
+    [(52.3745, 4.9160), (52.3800, 4.9300)]
+        .into_iter()
+        .zip(VERTIPORT_IDENTIFIERS)
+        .map(|((latitude, longitude), identifier)| Vertiport {
+            identifier: identifier.to_string(),
+            vertices: square(latitude, longitude, 0.0005),
+            altitude_meters: 10.0,
+            label: Some(identifier.to_string()),
+            timestamp_network: Some(Utc::now().into()),
+            status: None,
+            layers: None,
+            compact_vertices: None,
+            operating_hours: vec![],
+        })
+        .collect()
+}
+
+/// A restricted zone north of the vertiports, clear of the direct route
+fn restricted_zone() -> Zone {
+    Zone {
+        identifier: ZONE_IDENTIFIER.to_string(),
+        zone_type: ZoneType::Restriction as i32,
+        vertices: square(52.3910, 4.9215, 0.0015),
+        altitude_meters_min: 0.0,
+        altitude_meters_max: 500.0,
+        ..Default::default()
+    }
+}
+
+/// A path crossing the restricted zone from south to north
+fn crossing_path() -> Vec<PointZ> {
+    [(52.3880, 4.9215), (52.3940, 4.9215)]
+        .into_iter()
+        .map(|(latitude, longitude)| {
+            PointZ::new(longitude, latitude, 100.0, Some(super::DEFAULT_SRID))
+        })
+        .collect()
+}
+
+/// Checks if a path intersects a zone or another flight
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+async fn intersects(points: Vec<PointZ>) -> Result<bool, SelfTestError> {
+    let client = super::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            SelfTestError::Intersection
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            SelfTestError::Intersection
+        })?;
+
+    let distance = points.windows(2).fold(0.0, |acc, pair| {
+        acc + super::utils::distance_meters(&pair[0], &pair[1])
+    });
+
+    let time_start = Utc::now();
+    let time_end = time_start + Duration::hours(1);
+    match best_path::intersection_checks(
+        &client,
+        points,
+        distance,
+        time_start,
+        time_end,
+        VERTIPORT_IDENTIFIERS[0],
+        VERTIPORT_IDENTIFIERS[1],
+        &super::authorization::Entrant::default(),
+        false,
+        FlightPriority::Routine,
+        crate::types::AircraftType::Undeclared,
+    )
+    .await
+    {
+        Ok(_) => Ok(false),
+        Err(super::PostgisError::BestPath(best_path::PathError::ZoneIntersection)) => Ok(true),
+        Err(super::PostgisError::BestPath(
+            best_path::PathError::FlightPlanIntersection
+            | best_path::PathError::ReservationIntersection,
+        )) => Ok(true),
+        Err(e) => {
+            postgis_error!("could not check intersection: {}", e);
+            Err(SelfTestError::Intersection)
+        }
+    }
+}
+
+/// Runs the synthetic scenario in the current schema
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+async fn scenario() -> Result<(), SelfTestError> {
+    super::psql_init().await.map_err(|e| {
+        postgis_error!("could not create tables: {}", e);
+        SelfTestError::Setup
+    })?;
+
+    super::statements::verify()
+        .await
+        .map_err(|_| SelfTestError::Setup)?;
+
+    postgis_info!("(1/4) tables created and statements prepared.");
+
+    vertiport::update_vertiports(vertiports())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not insert vertiports: {}", e);
+            SelfTestError::Insert
+        })?;
+
+    zone::update_zones(vec![restricted_zone()])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not insert zone: {}", e);
+            SelfTestError::Insert
+        })?;
+
+    postgis_info!("(2/4) vertiports and zone inserted.");
+
+    let time_start = Utc::now();
+    let paths = best_path::best_path(
+        BestPathRequest {
+            origin_identifier: VERTIPORT_IDENTIFIERS[0].to_string(),
+            target_identifier: VERTIPORT_IDENTIFIERS[1].to_string(),
+            origin_type: NodeType::Vertiport as i32,
+            target_type: NodeType::Vertiport as i32,
+            time_start: Some(time_start.into()),
+            time_end: Some((time_start + Duration::hours(1)).into()),
+            limit: 1,
+            max_path_node_count: None,
+            weights: None,
+            include_simulated: false,
+            priority: FlightPriority::Routine as i32,
+            max_window_offset_minutes: None,
+            aircraft_identifier: None,
+            flight_identifier: None,
+            arrival_window_minutes: None,
+            aircraft_type: None,
+            operator_identifier: None,
+        },
+        None,
+    )
+    .await
+    .map_err(|e| {
+        postgis_error!("could not get best path: {}", e);
+        SelfTestError::BestPath
+    })?
+    .paths;
+
+    let path = paths.into_iter().next().ok_or_else(|| {
+        postgis_error!("no path found.");
+        SelfTestError::BestPath
+    })?;
+
+    postgis_info!(
+        "(3/4) path found with {} nodes, {} meters.",
+        path.path.len(),
+        path.distance_meters
+    );
+
+    let points = path
+        .path
+        .into_iter()
+        .filter_map(|node| node.geom)
+        .map(PointZ::from)
+        .collect();
+
+    if intersects(points).await? {
+        postgis_error!("the best path intersects the zone.");
+        return Err(SelfTestError::Intersection);
+    }
+
+    if !intersects(crossing_path()).await? {
+        postgis_error!("a path through the zone was not flagged.");
+        return Err(SelfTestError::Intersection);
+    }
+
+    postgis_info!("(4/4) intersection checks passed.");
+    Ok(())
+}
+
+/// Runs the self-test in the schema set with [`set_schema`](super::set_schema),
+///  dropping the schema afterwards
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+pub async fn run() -> Result<(), SelfTestError> {
+    let schema = super::get_schema();
+    if schema == PSQL_SCHEMA {
+        postgis_error!("refusing to run the self-test in the '{PSQL_SCHEMA}' schema.");
+        return Err(SelfTestError::Schema);
+    }
+
+    postgis_info!("running self-test in schema '{schema}'.");
+    psql_transaction(vec![format!(r#"CREATE SCHEMA "{schema}";"#)])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not create schema '{schema}': {}", e);
+            SelfTestError::Schema
+        })?;
+
+    let result = scenario().await;
+    let cleanup = psql_transaction(vec![format!(r#"DROP SCHEMA "{schema}" CASCADE;"#)])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not drop schema '{schema}': {}", e);
+            SelfTestError::Schema
+        });
+
+    match result {
+        Ok(()) => postgis_info!("self-test passed."),
+        Err(e) => postgis_error!("self-test failed: {}", e),
+    }
+
+    result.and(cleanup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_schema_name() {
+        let schema = schema_name();
+        assert!(schema.starts_with(SCHEMA_PREFIX));
+        assert!(super::super::utils::check_string(&schema, super::super::SCHEMA_REGEX).is_ok());
+        assert_ne!(schema, PSQL_SCHEMA);
+    }
+
+    #[test]
+    fn ut_scenario_data_valid() {
+        for vertiport in vertiports() {
+            assert!(vertiport::Vertiport::try_from(vertiport).is_ok());
+        }
+
+        assert!(zone::Zone::try_from(restricted_zone()).is_ok());
+    }
+
+    #[test]
+    fn ut_crossing_path() {
+        // x is the longitude, the path runs south to north across the zone
+        let zone = restricted_zone();
+        let (min, max) = (&zone.vertices[0], &zone.vertices[2]);
+        let path = crossing_path();
+        for point in &path {
+            assert!(point.x > min.longitude && point.x < max.longitude);
+        }
+
+        assert!(path[0].y < min.latitude);
+        assert!(path[1].y > max.latitude);
+    }
+
+    #[test]
+    fn test_self_test_error_display() {
+        assert_eq!(
+            SelfTestError::Schema.to_string(),
+            "Could not create or drop the schema."
+        );
+        assert_eq!(
+            SelfTestError::Setup.to_string(),
+            "Could not initialize the schema."
+        );
+        assert_eq!(
+            SelfTestError::Insert.to_string(),
+            "Could not insert test data."
+        );
+        assert_eq!(
+            SelfTestError::BestPath.to_string(),
+            "No path found between vertiports."
+        );
+        assert_eq!(
+            SelfTestError::Intersection.to_string(),
+            "Unexpected intersection check result."
+        );
+    }
+}
@@ -0,0 +1,423 @@
+//! Track and flight path simplification
+//!
+//! Aircraft report their position about once per second, so a track of a few
+//!  minutes holds hundreds of nearly collinear points. The Douglas-Peucker
+//!  algorithm keeps only the points that deviate from the simplified line by
+//!  more than a tolerance, measured in three dimensions so that climbs and
+//!  descents along a straight ground track are preserved.
+//!
+//! Submitted flight paths are simplified the same way before they are
+//!  stored, with a tighter tolerance since they are checked for conflicts.
+//!
+//! Zones published with thousands of vertices can also be simplified when
+//!  their volumes are built. That is done by PostGIS, see the zone module.
+
+use crate::reload::Tunable;
+use crate::types::{AircraftPosition, Degrees, Meters, Position};
+use lib_common::time::Duration;
+use postgis::ewkb::PointZ;
+use std::fmt::{self, Display, Formatter};
+
+/// Default tolerance of the track simplification, in meters
+pub const DEFAULT_EPSILON_METERS: f64 = 5.0;
+
+/// Default length of the most recent portion of a track returned as is
+pub const DEFAULT_RAW_WINDOW_SECONDS: u32 = 10;
+
+/// Default tolerance of the flight path simplification, in meters
+pub const DEFAULT_PATH_EPSILON_METERS: f64 = 1.0;
+
+/// Default tolerance of the zone simplification, in meters. Zones are not
+///  simplified by default.
+pub const DEFAULT_ZONE_EPSILON_METERS: f64 = 0.0;
+
+/// Default number of vertices above which a zone is simplified
+pub const DEFAULT_ZONE_MIN_VERTICES: u32 = 500;
+
+/// Mean radius of the Earth, in meters
+pub(super) const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Track simplification settings
+static SETTINGS: Tunable<Settings> = Tunable::new();
+
+/// Possible errors configuring the track simplification
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SimplifyError {
+    /// The tolerance is negative or not a number
+    Epsilon,
+}
+
+impl Display for SimplifyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SimplifyError::Epsilon => write!(f, "Invalid simplification tolerance."),
+        }
+    }
+}
+
+impl std::error::Error for SimplifyError {}
+
+/// Track simplification settings
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Settings {
+    /// Max deviation of a dropped point from the simplified track, in meters.
+    ///  Zero disables the simplification.
+    pub epsilon_meters: f64,
+
+    /// The most recent positions within this many seconds are never dropped
+    pub raw_window_seconds: u32,
+
+    /// Max deviation of a dropped point from a simplified flight path, in
+    ///  meters. Zero stores flight paths as submitted. The separation
+    ///  margins checked against filed flights grow by this much.
+    pub path_epsilon_meters: f64,
+
+    /// Max deviation of a dropped vertex from a simplified zone footprint,
+    ///  in meters. Zero keeps the volumes of zones as published.
+    pub zone_epsilon_meters: f64,
+
+    /// Zones with at most this many vertices are not simplified
+    pub zone_min_vertices: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            epsilon_meters: DEFAULT_EPSILON_METERS,
+            raw_window_seconds: DEFAULT_RAW_WINDOW_SECONDS,
+            path_epsilon_meters: DEFAULT_PATH_EPSILON_METERS,
+            zone_epsilon_meters: DEFAULT_ZONE_EPSILON_METERS,
+            zone_min_vertices: DEFAULT_ZONE_MIN_VERTICES,
+        }
+    }
+}
+
+/// Checks that the simplification tolerances are zero or positive
+pub fn check_settings(settings: &Settings) -> Result<(), SimplifyError> {
+    for epsilon_meters in [
+        settings.epsilon_meters,
+        settings.path_epsilon_meters,
+        settings.zone_epsilon_meters,
+    ] {
+        if epsilon_meters.is_nan() || epsilon_meters < 0.0 {
+            postgis_error!(
+                "simplification tolerance must be zero or positive: {}",
+                epsilon_meters
+            );
+            return Err(SimplifyError::Epsilon);
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets the track simplification settings, replacing any set before
+pub fn set_settings(settings: Settings) -> Result<(), SimplifyError> {
+    check_settings(&settings)?;
+    SETTINGS.set(settings);
+    Ok(())
+}
+
+/// Gets the track simplification settings
+pub fn get_settings() -> Settings {
+    SETTINGS.get().unwrap_or_default()
+}
+
+/// Projects a position onto a plane tangent at the reference position,
+///  giving east, north and up offsets in meters
+fn local_offset(reference: &Position, position: &Position) -> [f64; 3] {
+    let latitude = reference.latitude.to_radians();
+    let east = (position.longitude - reference.longitude).to_radians()
+        * latitude.cos()
+        * EARTH_RADIUS_METERS;
+    let north = (position.latitude - reference.latitude).to_radians() * EARTH_RADIUS_METERS;
+    let up = (position.altitude_meters - reference.altitude_meters).0;
+
+    [east, north, up]
+}
+
+/// Distance in meters from a position to the segment between two others
+fn segment_distance_meters(position: &Position, start: &Position, end: &Position) -> f64 {
+    let p = local_offset(start, position);
+    let e = local_offset(start, end);
+    let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+
+    let length_squared = dot(e, e);
+    let t = if length_squared > 0.0 {
+        (dot(p, e) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let d = [p[0] - t * e[0], p[1] - t * e[1], p[2] - t * e[2]];
+    dot(d, d).sqrt()
+}
+
+/// Douglas-Peucker simplification of a sequence of positions
+///
+/// Returns the indices of the positions to keep, in order. The first and
+///  last positions are always kept.
+pub fn douglas_peucker(positions: &[Position], epsilon_meters: f64) -> Vec<usize> {
+    let len = positions.len();
+    if len < 3 || epsilon_meters <= 0.0 {
+        return (0..len).collect();
+    }
+
+    let mut keep = vec![false; len];
+    keep[0] = true;
+    keep[len - 1] = true;
+
+    let mut stack = vec![(0, len - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let farthest = (start + 1..end)
+            .map(|i| {
+                let distance =
+                    segment_distance_meters(&positions[i], &positions[start], &positions[end]);
+                (i, distance)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        let Some((index, distance)) = farthest else {
+            continue;
+        };
+
+        if distance > epsilon_meters {
+            keep[index] = true;
+            stack.push((start, index));
+            stack.push((index, end));
+        }
+    }
+
+    keep.iter()
+        .enumerate()
+        .filter_map(|(i, keep)| keep.then_some(i))
+        .collect()
+}
+
+/// Simplifies a track ordered from oldest to newest
+///
+/// Positions within [`Settings::raw_window_seconds`] of the newest one are
+///  returned as is, the older portion of the track is simplified.
+pub fn simplify_track(track: Vec<AircraftPosition>, settings: &Settings) -> Vec<AircraftPosition> {
+    let Some(newest) = track.last().map(|item| item.timestamp_network) else {
+        return track;
+    };
+
+    let window_start = newest - Duration::seconds(settings.raw_window_seconds as i64);
+
+    // the first raw position also closes the simplified portion,
+    //  the newest position is always within the window
+    let split = track
+        .iter()
+        .position(|item| item.timestamp_network >= window_start)
+        .unwrap_or(track.len() - 1);
+
+    let positions: Vec<Position> = track[..=split].iter().map(|item| item.position).collect();
+
+    let mut keep = vec![false; track.len()];
+    douglas_peucker(&positions, settings.epsilon_meters)
+        .into_iter()
+        .for_each(|i| keep[i] = true);
+
+    keep[split..].iter_mut().for_each(|keep| *keep = true);
+
+    track
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(item, keep)| keep.then_some(item))
+        .collect()
+}
+
+/// Simplifies a flight path, keeping its first and last points
+pub fn simplify_path(path: Vec<PointZ>, epsilon_meters: f64) -> Vec<PointZ> {
+    let positions: Vec<Position> = path
+        .iter()
+        .map(|point| Position {
+            longitude: Degrees(point.x),
+            latitude: Degrees(point.y),
+            altitude_meters: Meters(point.z),
+        })
+        .collect();
+
+    let mut keep = vec![false; path.len()];
+    douglas_peucker(&positions, epsilon_meters)
+        .into_iter()
+        .for_each(|i| keep[i] = true);
+
+    path.into_iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(point))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::{DateTime, Utc};
+
+    fn position(latitude: f64, longitude: f64, altitude_meters: f64) -> Position {
+        Position {
+            latitude: Degrees(latitude),
+            longitude: Degrees(longitude),
+            altitude_meters: Meters(altitude_meters),
+        }
+    }
+
+    fn track(positions: Vec<Position>, start: DateTime<Utc>) -> Vec<AircraftPosition> {
+        positions
+            .into_iter()
+            .enumerate()
+            .map(|(i, position)| AircraftPosition {
+                identifier: "N12345".to_string(),
+                position,
+                timestamp_network: start + Duration::try_seconds(i as i64).unwrap(),
+                timestamp_asset: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ut_douglas_peucker_straight_line() {
+        let positions: Vec<Position> = (0..100)
+            .map(|i| position(52.0 + i as f64 * 0.0001, 4.0, 100.0))
+            .collect();
+
+        assert_eq!(douglas_peucker(&positions, 1.0), vec![0, 99]);
+    }
+
+    #[test]
+    fn ut_douglas_peucker_corner() {
+        let positions: Vec<Position> = (0..10)
+            .map(|i| position(52.0 + i as f64 * 0.0001, 4.0, 100.0))
+            .chain((1..10).map(|i| position(52.0009, 4.0 + i as f64 * 0.0001, 100.0)))
+            .collect();
+
+        assert_eq!(douglas_peucker(&positions, 1.0), vec![0, 9, 18]);
+    }
+
+    #[test]
+    fn ut_douglas_peucker_altitude() {
+        // straight ground track with a climb in the middle
+        let positions: Vec<Position> = (0..21)
+            .map(|i| {
+                let altitude = 100.0 + (i as f64 - 10.0).max(0.0) * 10.0;
+                position(52.0 + i as f64 * 0.0001, 4.0, altitude)
+            })
+            .collect();
+
+        assert_eq!(douglas_peucker(&positions, 1.0), vec![0, 10, 20]);
+        assert_eq!(douglas_peucker(&positions, 1000.0), vec![0, 20]);
+    }
+
+    #[test]
+    fn ut_douglas_peucker_disabled() {
+        let positions: Vec<Position> = (0..5).map(|i| position(52.0, 4.0, i as f64)).collect();
+        assert_eq!(douglas_peucker(&positions, 0.0), vec![0, 1, 2, 3, 4]);
+        assert_eq!(douglas_peucker(&positions[..2], 1.0), vec![0, 1]);
+        assert!(douglas_peucker(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    fn ut_simplify_track_raw_window() {
+        let positions: Vec<Position> = (0..60)
+            .map(|i| position(52.0 + i as f64 * 0.0001, 4.0, 100.0))
+            .collect();
+
+        let settings = Settings {
+            epsilon_meters: 1.0,
+            raw_window_seconds: 10,
+            ..Default::default()
+        };
+
+        let start = Utc::now();
+        let result = simplify_track(track(positions, start), &settings);
+
+        // the start of the track, then the last 11 seconds untouched
+        assert_eq!(result.len(), 12);
+        assert_eq!(result[0].timestamp_network, start);
+        assert_eq!(
+            result[1].timestamp_network,
+            start + Duration::try_seconds(49).unwrap()
+        );
+        assert!(result
+            .windows(2)
+            .all(|pair| pair[0].timestamp_network < pair[1].timestamp_network));
+    }
+
+    #[test]
+    fn ut_simplify_track_short() {
+        let settings = Settings::default();
+        assert!(simplify_track(vec![], &settings).is_empty());
+
+        let result = simplify_track(
+            track(vec![position(52.0, 4.0, 100.0)], Utc::now()),
+            &settings,
+        );
+        assert_eq!(result.len(), 1);
+    }
+
+    #[test]
+    fn ut_simplify_path() {
+        let point = |x: f64, y: f64, z: f64| PointZ {
+            x,
+            y,
+            z,
+            srid: Some(4326),
+        };
+
+        // dense climb out, then a level leg
+        let path: Vec<PointZ> = (0..=50)
+            .map(|i| point(4.0, 52.0 + i as f64 * 0.0001, 10.0 + i as f64 * 2.0))
+            .chain((1..=50).map(|i| point(4.0 + i as f64 * 0.0001, 52.005, 110.0)))
+            .collect();
+
+        let result = simplify_path(path.clone(), 1.0);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0], path[0]);
+        assert_eq!(result[1], path[50]);
+        assert_eq!(result[2], path[100]);
+
+        assert_eq!(simplify_path(path.clone(), 0.0), path);
+        assert!(simplify_path(vec![], 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_set_settings_invalid() {
+        let settings = Settings {
+            epsilon_meters: -1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(set_settings(settings), Err(SimplifyError::Epsilon));
+
+        let settings = Settings {
+            epsilon_meters: f64::NAN,
+            ..Default::default()
+        };
+
+        assert_eq!(set_settings(settings), Err(SimplifyError::Epsilon));
+
+        let settings = Settings {
+            path_epsilon_meters: -1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(set_settings(settings), Err(SimplifyError::Epsilon));
+
+        let settings = Settings {
+            zone_epsilon_meters: -1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(set_settings(settings), Err(SimplifyError::Epsilon));
+        assert_eq!(get_settings(), Settings::default());
+    }
+
+    #[test]
+    fn test_simplify_error_display() {
+        assert_eq!(
+            SimplifyError::Epsilon.to_string(),
+            "Invalid simplification tolerance."
+        );
+    }
+}
@@ -0,0 +1,633 @@
+//! The airspace picture as it was at a past time, for replay and incident
+//!  investigation.
+//!
+//! Each change to a zone closes its current version in the zone history
+//!  table and opens a new one, and each position update of an aircraft is
+//!  copied to the aircraft history table, both by triggers. A snapshot
+//!  reads the zone versions valid at the requested time, the flights filed
+//!  over it (archived ones included) and the last position of each aircraft
+//!  before it. History older than the retention period is removed by the
+//!  maintenance passes, so snapshots are only served within that period.
+
+use super::statements::{Statement, ZONE_COLUMNS};
+use super::{maintenance, OnceCell, PostgisError, DEFAULT_SRID};
+use crate::grpc::server::grpc_server::{
+    AircraftState, AirspaceSnapshot, GetSnapshotRequest, GetSnapshotResponse, PointZ as GrpcPointZ,
+    SnapshotAircraft, SnapshotFlight, Zone,
+};
+use crate::postgis::aircraft::{self, QualityThresholds};
+use crate::types::{AircraftType, OperationalStatus};
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Duration, Utc};
+use postgis::ewkb::{LineStringT, PointZ};
+use prost::Message;
+use std::fmt::{self, Display, Formatter};
+
+/// Default number of days the zone and aircraft history is kept
+pub const DEFAULT_HISTORY_RETENTION_DAYS: u32 = 30;
+
+/// Possible errors getting a snapshot
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SnapshotError {
+    /// Invalid snapshot time
+    Time,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Time => write!(f, "Invalid time provided."),
+            SnapshotError::Client => write!(f, "Could not get backend client."),
+            SnapshotError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// Gets the name of the zone history table
+pub(super) fn get_zones_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."zones_history""#, super::get_schema()))
+}
+
+/// Gets the name of the aircraft history table
+pub(super) fn get_aircraft_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."aircraft_history""#, super::get_schema()))
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Snapshot(SnapshotError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Snapshot(SnapshotError::Client)
+        })
+}
+
+/// Initialize the history tables and the triggers filling them
+///  Must run after the zones and aircraft tables are created.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn psql_init() -> Result<(), PostgisError> {
+    let schema = super::get_schema();
+    let zones = super::zone::get_table_name();
+    let zones_history = get_zones_table_name();
+    let aircraft = super::aircraft::get_table_name();
+    let aircraft_history = get_aircraft_table_name();
+    let max_length = super::aircraft::get_identifier_max_length();
+
+    let statements = vec![
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {zones_history} (
+            "identifier" VARCHAR(255) NOT NULL,
+            "zone_type" zonetype NOT NULL,
+            "footprint" GEOMETRY(MULTIPOLYGON, {DEFAULT_SRID}) NOT NULL,
+            "altitude_meters_min" FLOAT(4) NOT NULL,
+            "altitude_meters_max" FLOAT(4) NOT NULL,
+            "time_start" TIMESTAMPTZ,
+            "time_end" TIMESTAMPTZ,
+            "schedule" INTEGER[] NOT NULL DEFAULT '{{}}',
+            "waypoint_buffer_meters" FLOAT(4),
+            "description" TEXT,
+            "authority" VARCHAR(255),
+            "contact" VARCHAR(255),
+            "source_url" VARCHAR(255),
+            "valid_from" TIMESTAMPTZ NOT NULL,
+            "valid_to" TIMESTAMPTZ
+        );"#
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "zones_history_valid_idx" ON {zones_history} ("valid_from", "valid_to");"#
+        ),
+        // Finds the open version of a zone
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "zones_history_open_idx" ON {zones_history} ("identifier") WHERE "valid_to" IS NULL;"#
+        ),
+        // A version is valid from the transaction that wrote it until the
+        //  transaction that replaced or deleted it
+        format!(
+            r#"CREATE OR REPLACE FUNCTION "{schema}"."zone_history"() RETURNS TRIGGER AS $$
+            BEGIN
+                IF TG_OP <> 'INSERT' THEN
+                    UPDATE {zones_history} SET "valid_to" = NOW()
+                    WHERE "identifier" = OLD."identifier" AND "valid_to" IS NULL;
+                END IF;
+
+                IF TG_OP <> 'DELETE' THEN
+                    INSERT INTO {zones_history} ({ZONE_COLUMNS}, "valid_from")
+                    SELECT {ZONE_COLUMNS}, NOW() FROM (SELECT NEW.*) AS "zone";
+                END IF;
+
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql;"#
+        ),
+        format!(r#"DROP TRIGGER IF EXISTS "zones_history" ON {zones};"#),
+        format!(
+            r#"CREATE TRIGGER "zones_history"
+            AFTER INSERT OR UPDATE OR DELETE ON {zones}
+            FOR EACH ROW EXECUTE FUNCTION "{schema}"."zone_history"();"#
+        ),
+        // Zones written before the history existed are valid from their
+        //  last update
+        format!(
+            r#"INSERT INTO {zones_history} ({ZONE_COLUMNS}, "valid_from")
+            SELECT {ZONE_COLUMNS}, COALESCE("last_updated", NOW()) FROM {zones} AS "zone"
+            WHERE NOT EXISTS (
+                SELECT 1 FROM {zones_history} AS "history"
+                WHERE "history"."identifier" = "zone"."identifier"
+                    AND "history"."valid_to" IS NULL
+            );"#
+        ),
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {aircraft_history} (
+            "identifier" VARCHAR({max_length}) NOT NULL,
+            "aircraft_type" aircrafttype NOT NULL,
+            "op_status" opstatus NOT NULL,
+            "simulated" BOOLEAN NOT NULL DEFAULT FALSE,
+            "geom" GEOMETRY(POINTZ, {DEFAULT_SRID}) NOT NULL,
+            "velocity_horizontal_ground_mps" FLOAT(4),
+            "velocity_vertical_mps" FLOAT(4),
+            "track_angle_degrees" FLOAT(4),
+            "last_position_update" TIMESTAMPTZ NOT NULL
+        );"#
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "aircraft_history_identifier_idx" ON {aircraft_history} ("identifier", "last_position_update");"#
+        ),
+        // Finds the positions past their retention period
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "aircraft_history_time_idx" ON {aircraft_history} ("last_position_update");"#
+        ),
+        format!(
+            r#"CREATE OR REPLACE FUNCTION "{schema}"."aircraft_history"() RETURNS TRIGGER AS $$
+            BEGIN
+                IF NEW."geom" IS NULL OR NEW."last_position_update" IS NULL THEN
+                    RETURN NULL;
+                END IF;
+
+                IF TG_OP = 'UPDATE'
+                    AND OLD."last_position_update" IS NOT DISTINCT FROM NEW."last_position_update" THEN
+                    RETURN NULL;
+                END IF;
+
+                INSERT INTO {aircraft_history} (
+                    "identifier",
+                    "aircraft_type",
+                    "op_status",
+                    "simulated",
+                    "geom",
+                    "velocity_horizontal_ground_mps",
+                    "velocity_vertical_mps",
+                    "track_angle_degrees",
+                    "last_position_update"
+                ) VALUES (
+                    NEW."identifier",
+                    NEW."aircraft_type",
+                    NEW."op_status",
+                    COALESCE(NEW."simulated", FALSE),
+                    NEW."geom",
+                    NEW."velocity_horizontal_ground_mps",
+                    NEW."velocity_vertical_mps",
+                    NEW."track_angle_degrees",
+                    NEW."last_position_update"
+                );
+
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql;"#
+        ),
+        format!(r#"DROP TRIGGER IF EXISTS "aircraft_history" ON {aircraft};"#),
+        format!(
+            r#"CREATE TRIGGER "aircraft_history"
+            AFTER INSERT OR UPDATE OF "geom", "last_position_update" ON {aircraft}
+            FOR EACH ROW EXECUTE FUNCTION "{schema}"."aircraft_history"();"#
+        ),
+    ];
+
+    super::psql_transaction(statements).await
+}
+
+/// Checks the time of a snapshot, which can't be in the future nor before
+///  the history retention period
+fn snapshot_time(
+    request: &GetSnapshotRequest,
+    now: DateTime<Utc>,
+    retention_days: u32,
+) -> Result<DateTime<Utc>, SnapshotError> {
+    let Some(time) = request.time.clone() else {
+        postgis_error!("time not provided.");
+        return Err(SnapshotError::Time);
+    };
+
+    let time: DateTime<Utc> = time.into();
+    if time > now {
+        postgis_error!("time {time} is in the future.");
+        return Err(SnapshotError::Time);
+    }
+
+    if maintenance::retention_cutoff(now, retention_days).is_some_and(|cutoff| time < cutoff) {
+        postgis_error!(
+            "time {time} is older than the history retention period of {retention_days} day(s)."
+        );
+        return Err(SnapshotError::Time);
+    }
+
+    Ok(time)
+}
+
+/// Converts a filed flight path into the GRPC type
+fn path_points(path: Option<LineStringT<PointZ>>) -> Vec<GrpcPointZ> {
+    path.map(|path| {
+        path.points
+            .iter()
+            .map(|p| GrpcPointZ {
+                latitude: p.y,
+                longitude: p.x,
+                altitude_meters: p.z as f32,
+            })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// The last known position of an aircraft before a snapshot
+#[derive(Debug, Clone)]
+struct HistoryPosition {
+    position: GrpcPointZ,
+    status: OperationalStatus,
+    ground_speed_mps: Option<f32>,
+    vertical_speed_mps: Option<f32>,
+    track_angle_degrees: Option<f32>,
+    last_position_update: DateTime<Utc>,
+}
+
+/// The state of an aircraft, aged relative to the snapshot time rather
+///  than to now
+fn snapshot_state(
+    history: HistoryPosition,
+    time: DateTime<Utc>,
+    thresholds: &QualityThresholds,
+) -> AircraftState {
+    let staleness_seconds = aircraft::get_staleness_seconds(history.last_position_update, time);
+    let quality = aircraft::get_data_quality(staleness_seconds, thresholds);
+
+    AircraftState {
+        timestamp: Some(history.last_position_update.into()),
+        status: history.status as i32,
+        position: Some(history.position),
+        track_angle_degrees: history.track_angle_degrees.unwrap_or_default(),
+        ground_speed_mps: history.ground_speed_mps.unwrap_or_default(),
+        vertical_speed_mps: history.vertical_speed_mps.unwrap_or_default(),
+        staleness_seconds,
+        quality: quality as i32,
+    }
+}
+
+/// Wraps a snapshot in a response, encoded as a bundle if requested
+fn into_response(snapshot: AirspaceSnapshot, bundle: bool) -> GetSnapshotResponse {
+    if !bundle {
+        return GetSnapshotResponse {
+            snapshot: Some(snapshot),
+            bundle: vec![],
+        };
+    }
+
+    GetSnapshotResponse {
+        snapshot: None,
+        bundle: snapshot.encode_to_vec(),
+    }
+}
+
+/// Converts a zone history row into the GRPC type, `None` if the zone
+///  schedule is not active at the snapshot time
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_zone_row(
+    row: tokio_postgres::Row,
+    time: DateTime<Utc>,
+) -> Result<Option<Zone>, tokio_postgres::Error> {
+    let schedule: Vec<i32> = row.try_get("schedule")?;
+    if !super::zone::schedule_overlaps(&schedule, time, time) {
+        return Ok(None);
+    }
+
+    super::zone::process_zone_row(row).map(Some)
+}
+
+/// Converts a flight row into the GRPC type
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_flight_row(row: tokio_postgres::Row) -> Result<SnapshotFlight, tokio_postgres::Error> {
+    let aircraft_type: AircraftType = row.try_get("aircraft_type")?;
+    let time_start: Option<DateTime<Utc>> = row.try_get("time_start")?;
+    let time_end: Option<DateTime<Utc>> = row.try_get("time_end")?;
+
+    Ok(SnapshotFlight {
+        flight_identifier: row.try_get("flight_identifier")?,
+        aircraft_identifier: row.try_get("aircraft_identifier")?,
+        aircraft_type: aircraft_type as i32,
+        simulated: row.try_get("simulated")?,
+        priority: row.try_get("priority")?,
+        path: path_points(row.try_get("geom")?),
+        time_start: time_start.map(|t| t.into()),
+        time_end: time_end.map(|t| t.into()),
+        archived: row.try_get("archived")?,
+    })
+}
+
+/// Converts an aircraft history row into the GRPC type
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_aircraft_row(
+    row: tokio_postgres::Row,
+    time: DateTime<Utc>,
+    thresholds: &QualityThresholds,
+) -> Result<SnapshotAircraft, tokio_postgres::Error> {
+    let aircraft_type: AircraftType = row.try_get("aircraft_type")?;
+    let geom: PointZ = row.try_get("geom")?;
+    let history = HistoryPosition {
+        position: GrpcPointZ {
+            latitude: geom.y,
+            longitude: geom.x,
+            altitude_meters: geom.z as f32,
+        },
+        status: row.try_get("op_status")?,
+        ground_speed_mps: row.try_get("velocity_horizontal_ground_mps")?,
+        vertical_speed_mps: row.try_get("velocity_vertical_mps")?,
+        track_angle_degrees: row.try_get("track_angle_degrees")?,
+        last_position_update: row.try_get("last_position_update")?,
+    };
+
+    Ok(SnapshotAircraft {
+        identifier: row.try_get("identifier")?,
+        aircraft_type: aircraft_type as i32,
+        simulated: row.try_get("simulated")?,
+        state: Some(snapshot_state(history, time, thresholds)),
+    })
+}
+
+/// Gets the airspace picture as it was at the requested time
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn get_snapshot(
+    request: GetSnapshotRequest,
+) -> Result<GetSnapshotResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let retention_days = maintenance::get_settings().history_retention_days;
+    let time =
+        snapshot_time(&request, Utc::now(), retention_days).map_err(PostgisError::Snapshot)?;
+
+    // positions expired by the snapshot time are left out
+    let thresholds = aircraft::get_quality_thresholds();
+    let seen_since = time - Duration::seconds(thresholds.expired_seconds as i64);
+
+    let client = get_client().await?;
+    let prepare = |statement: Statement| {
+        let client = &client;
+        async move {
+            client.prepare_cached(&statement.sql()).await.map_err(|e| {
+                postgis_error!("could not prepare cached statement: {}", e);
+                PostgisError::Snapshot(SnapshotError::DBError)
+            })
+        }
+    };
+
+    let zones_stmt = prepare(Statement::GetZonesAsOf).await?;
+    let flights_stmt = prepare(Statement::GetSnapshotFlights).await?;
+    let aircraft_stmt = prepare(Statement::GetAircraftAsOf).await?;
+
+    let zones = client
+        .query(&zones_stmt, &[&time])
+        .await
+        .and_then(|rows| {
+            rows.into_iter()
+                .filter_map(|row| process_zone_row(row, time).transpose())
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| {
+            postgis_error!("could not get zones: {}", e);
+            PostgisError::Snapshot(SnapshotError::DBError)
+        })?;
+
+    let flights = client
+        .query(&flights_stmt, &[&time])
+        .await
+        .and_then(|rows| {
+            rows.into_iter()
+                .map(process_flight_row)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| {
+            postgis_error!("could not get flights: {}", e);
+            PostgisError::Snapshot(SnapshotError::DBError)
+        })?;
+
+    let aircraft = client
+        .query(&aircraft_stmt, &[&time, &seen_since])
+        .await
+        .and_then(|rows| {
+            rows.into_iter()
+                .map(|row| process_aircraft_row(row, time, &thresholds))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| {
+            postgis_error!("could not get aircraft: {}", e);
+            PostgisError::Snapshot(SnapshotError::DBError)
+        })?;
+
+    postgis_debug!(
+        "{} zone(s), {} flight(s) and {} aircraft at {time}.",
+        zones.len(),
+        flights.len(),
+        aircraft.len()
+    );
+
+    let snapshot = AirspaceSnapshot {
+        time: Some(time.into()),
+        zones,
+        flights,
+        aircraft,
+    };
+
+    Ok(into_response(snapshot, request.bundle))
+}
+
+/// Deletes the zone versions replaced and the aircraft positions reported
+///  before the cutoff, up to `limit` rows of each. Returns the number of
+///  rows deleted.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn purge_history(cutoff: DateTime<Utc>, limit: i64) -> Result<u64, PostgisError> {
+    let client = get_client().await?;
+    let mut total = 0;
+    for statement in [
+        Statement::DeleteZoneHistory,
+        Statement::DeleteAircraftHistory,
+    ] {
+        let stmt = client.prepare_cached(&statement.sql()).await.map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Snapshot(SnapshotError::DBError)
+        })?;
+
+        total += client
+            .execute(&stmt, &[&cutoff, &limit])
+            .await
+            .map_err(|e| {
+                postgis_error!("could not purge history: {}", e);
+                PostgisError::Snapshot(SnapshotError::DBError)
+            })?;
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::server::grpc_server::DataQuality;
+
+    #[test]
+    fn ut_snapshot_time() {
+        let now = Utc::now();
+        let request = |time: Option<DateTime<Utc>>| GetSnapshotRequest {
+            time: time.map(|t| t.into()),
+            bundle: false,
+        };
+
+        let time = now - Duration::try_days(1).unwrap();
+        assert_eq!(snapshot_time(&request(Some(time)), now, 30), Ok(time));
+        assert_eq!(
+            snapshot_time(&request(None), now, 30),
+            Err(SnapshotError::Time)
+        );
+
+        let future = now + Duration::try_seconds(1).unwrap();
+        assert_eq!(
+            snapshot_time(&request(Some(future)), now, 30),
+            Err(SnapshotError::Time)
+        );
+
+        // older than the history
+        let time = now - Duration::try_days(31).unwrap();
+        assert_eq!(
+            snapshot_time(&request(Some(time)), now, 30),
+            Err(SnapshotError::Time)
+        );
+
+        // history kept forever
+        assert_eq!(snapshot_time(&request(Some(time)), now, 0), Ok(time));
+    }
+
+    #[test]
+    fn ut_path_points() {
+        assert!(path_points(None).is_empty());
+
+        let path = LineStringT {
+            points: vec![
+                PointZ::new(4.9160036, 52.3746, 100.0, Some(DEFAULT_SRID)),
+                PointZ::new(4.9167, 52.3749, 120.0, Some(DEFAULT_SRID)),
+            ],
+            srid: Some(DEFAULT_SRID),
+        };
+
+        assert_eq!(
+            path_points(Some(path)),
+            vec![
+                GrpcPointZ {
+                    latitude: 52.3746,
+                    longitude: 4.9160036,
+                    altitude_meters: 100.0,
+                },
+                GrpcPointZ {
+                    latitude: 52.3749,
+                    longitude: 4.9167,
+                    altitude_meters: 120.0,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn ut_snapshot_state() {
+        let time = Utc::now() - Duration::try_days(1).unwrap();
+        let thresholds = QualityThresholds::default();
+        let history = |seconds: i64| HistoryPosition {
+            position: GrpcPointZ::default(),
+            status: OperationalStatus::Undeclared,
+            ground_speed_mps: Some(12.0),
+            vertical_speed_mps: None,
+            track_angle_degrees: Some(90.0),
+            last_position_update: time - Duration::try_seconds(seconds).unwrap(),
+        };
+
+        // aged relative to the snapshot time, not to now
+        let state = snapshot_state(history(0), time, &thresholds);
+        assert_eq!(state.staleness_seconds, 0.0);
+        assert_eq!(state.quality, DataQuality::Fresh as i32);
+        assert_eq!(state.ground_speed_mps, 12.0);
+        assert_eq!(state.vertical_speed_mps, 0.0);
+        assert_eq!(state.track_angle_degrees, 90.0);
+        assert_eq!(state.timestamp, Some(time.into()));
+
+        let seconds = thresholds.stale_seconds as i64 + 1;
+        let state = snapshot_state(history(seconds), time, &thresholds);
+        assert_eq!(state.staleness_seconds, seconds as f32);
+        assert_eq!(state.quality, DataQuality::Stale as i32);
+    }
+
+    #[test]
+    fn ut_into_response() {
+        let snapshot = AirspaceSnapshot {
+            time: Some(Utc::now().into()),
+            flights: vec![SnapshotFlight {
+                flight_identifier: "FL1".to_string(),
+                aircraft_identifier: "Marauder".to_string(),
+                archived: true,
+                ..Default::default()
+            }],
+            aircraft: vec![SnapshotAircraft {
+                identifier: "Marauder".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let response = into_response(snapshot.clone(), false);
+        assert_eq!(response.snapshot, Some(snapshot.clone()));
+        assert!(response.bundle.is_empty());
+
+        // the bundle decodes back to the same snapshot
+        let response = into_response(snapshot.clone(), true);
+        assert_eq!(response.snapshot, None);
+        assert_eq!(
+            AirspaceSnapshot::decode(response.bundle.as_slice()).unwrap(),
+            snapshot
+        );
+    }
+
+    #[test]
+    fn test_snapshot_error_display() {
+        assert_eq!(SnapshotError::Time.to_string(), "Invalid time provided.");
+        assert_eq!(
+            SnapshotError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(SnapshotError::DBError.to_string(), "Database error.");
+    }
+}
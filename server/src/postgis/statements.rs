@@ -0,0 +1,2165 @@
+//! Registry of the SQL statements used at runtime.
+//!
+//! Every query the server issues against its own tables is declared here,
+//!  qualified with the table schema (see [`get_schema`](super::get_schema)).
+//!  Callers prepare them with `prepare_cached`, and [`verify`] prepares the
+//!  full set at startup so that a statement which no longer matches the
+//!  schema fails the boot instead of the first request that happens to use it.
+
+use super::utils::Pagination;
+use super::{
+    aircraft, audit, authorization, capabilities, flight, noise, partition, reservation, snapshot,
+    sync, vertiport, waypoint, zone,
+};
+use super::{PostgisError, PsqlError, DEFAULT_SRID};
+use crate::grpc::server::grpc_server::{OrderBy, VertiportStatus};
+
+/// The sort order of a paginated listing
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ordering {
+    /// Field to order the results by
+    pub order_by: OrderBy,
+
+    /// Sort the results in descending order
+    pub descending: bool,
+}
+
+impl From<&Pagination> for Ordering {
+    fn from(page: &Pagination) -> Self {
+        Ordering {
+            order_by: page.order_by,
+            descending: page.descending,
+        }
+    }
+}
+
+impl Ordering {
+    /// Every ordering a listing can be requested with
+    pub fn all() -> Vec<Ordering> {
+        [OrderBy::Identifier, OrderBy::LastUpdated]
+            .into_iter()
+            .flat_map(|order_by| {
+                [false, true].map(|descending| Ordering {
+                    order_by,
+                    descending,
+                })
+            })
+            .collect()
+    }
+
+    /// Builds an ORDER BY clause for the provided column
+    ///  The tiebreak columns keep the ordering stable across pages
+    pub fn order_clause(&self, column: &str, tiebreak: &[&str]) -> String {
+        let direction = if self.descending { "DESC" } else { "ASC" };
+        let columns = std::iter::once(column)
+            .chain(tiebreak.iter().copied())
+            .map(|c| format!("{c} {direction}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("ORDER BY {columns}")
+    }
+}
+
+/// Statements issued by the server at runtime
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Statement {
+    /// Release the sessions of aircraft that stopped reporting
+    ExpireAircraftSessions,
+
+    /// Release a session held by a different aircraft
+    ReleaseAircraftSession,
+
+    /// Serialize the upserts of a set of aircraft
+    LockAircraft,
+
+    /// Insert or update an aircraft identification
+    UpsertAircraftIdentifier,
+
+    /// Merge the row of a session-only aircraft into its identified aircraft
+    MergeAircraftSession,
+
+    /// Insert or update an aircraft position
+    UpsertAircraftPosition,
+
+    /// Insert or update an aircraft velocity
+    UpsertAircraftVelocity,
+
+    /// Get the full state of a set of aircraft
+    GetAircraftState,
+
+    /// Get the position of a single aircraft
+    GetAircraftGeom,
+
+    /// Get the state of the aircraft positioned within a box
+    GetAircraftInBox,
+
+    /// Check the clearance between two geometries
+    SegmentConflict,
+
+    /// Insert or update a flight path
+    UpsertFlight,
+
+    /// Move a batch of flights that ended before a cutoff to the archive
+    ArchiveFlights,
+
+    /// Delete a batch of flights that ended before a cutoff
+    DeleteFlights,
+
+    /// List the flights overlapping a time range, with their paths as WKB,
+    ///  optionally including the archived flights
+    ExportFlights,
+
+    /// Get the flights that pass near a path
+    FlightPathIntersection,
+
+    /// Get the flights that pass through a volume
+    FlightVolumeIntersection,
+
+    /// List flights and aircraft within a window
+    GetFlights(Ordering),
+
+    /// List filed flights within a window, without aircraft
+    GetPlannedFlights(Ordering),
+
+    /// Get the telemetry of aircraft by session or identifier
+    GetAircraftTelemetry,
+
+    /// Get the active zones of a type near the last position of aircraft
+    GetNearbyZones,
+
+    /// Split a path into segments of a maximum length
+    Segmentize,
+
+    /// Insert or update a vertiport and its zone
+    UpsertVertiport,
+
+    /// Update the operational status of a vertiport
+    UpdateVertiportStatus,
+
+    /// Get the centroid and status of a vertiport
+    GetVertiportCentroid,
+
+    /// List vertiports
+    GetVertiports(Ordering),
+
+    /// Insert or update a waypoint
+    UpsertWaypoint,
+
+    /// Insert or update a waypoint imported from a published source
+    UpsertPublishedWaypoint,
+
+    /// Delete the waypoints of a source that were not in its latest import
+    DeleteStalePublishedWaypoints,
+
+    /// Get the waypoints within a distance of a geometry
+    GetWaypointsNearGeometry,
+
+    /// List waypoints, which are only ordered by identifier
+    GetWaypoints {
+        /// Sort the results in descending order
+        descending: bool,
+    },
+
+    /// List clusters of waypoints snapped to the same grid cell, ordered by
+    ///  the lowest identifier in each cluster
+    GetWaypointClusters {
+        /// Sort the results in descending order
+        descending: bool,
+    },
+
+    /// Insert or update a zone
+    UpsertZone,
+
+    /// Insert or update a batch of zones, passed as one array per column
+    UpsertZones,
+
+    /// Get the footprint of a zone
+    GetZoneFootprint,
+
+    /// Get the zones containing a point at a time
+    GetZonesAtPoint,
+
+    /// Get the zones starting or ending within a time range
+    GetZoneTransitions,
+
+    /// Get the waypoints generated around a zone
+    GetZoneWaypoints,
+
+    /// Insert a zone waypoint unless it falls inside another zone
+    InsertZoneWaypoint,
+
+    /// Get the candidate waypoints of a zone that fall inside a zone
+    GetBlockedZoneWaypoints,
+
+    /// Delete the waypoints a zone no longer generates
+    DeleteStaleZoneWaypoints,
+
+    /// Record the rename of a zone waypoint
+    InsertWaypointRename,
+
+    /// Lock the attributes of a zone for update
+    LockZoneAttributes,
+
+    /// Update the attributes of a zone
+    UpdateZoneAttributes,
+
+    /// List zones
+    GetZones(Ordering),
+
+    /// Delete zones other than vertiport zones
+    DeleteZones,
+
+    /// Get the identifiers of zones with an invalid geometry
+    InvalidZoneGeometries,
+
+    /// Get the zones that intersect a path
+    ZonePathIntersection,
+
+    /// Get the zones that intersect a volume
+    ZoneVolumeIntersection,
+
+    /// Get the zones that intersect a volume, with their authorization
+    ZoneVolumeConstraints,
+
+    /// Get the zones that intersect a path, with their authorization
+    ZonePathConstraints,
+
+    /// Get the pairs of zones in an area separated by less than a width
+    GetZoneGaps,
+
+    /// Count the filed flights ending at a vertiport during a time window
+    CountVertiportArrivals,
+
+    /// Check if any vertiport, waypoint or zone is stored
+    HasAssets,
+
+    /// Insert or update a noise area
+    UpsertNoiseArea,
+
+    /// Get the distance a path flies through each noise area below its floor
+    GetNoiseCrossings,
+
+    /// Insert an authorization to enter a zone, if the zone exists
+    InsertZoneAuthorization,
+
+    /// List zone authorizations
+    GetZoneAuthorizations,
+
+    /// Get the zones changed after a sequence number
+    GetZoneChanges,
+
+    /// Get the waypoints changed after a sequence number
+    GetWaypointChanges,
+
+    /// Get the zones and waypoints removed after a sequence number
+    GetAirspaceTombstones,
+
+    /// Record a best path, with the current airspace change cursor
+    InsertBestPathAudit,
+
+    /// Get the best paths recorded for a flight, newest first
+    GetBestPathAudits,
+
+    /// Delete best path records created before a cutoff, a batch at a time
+    DeleteBestPathAudits,
+
+    /// Count the zones in their time bounds, by type and schedule
+    CountActiveZones,
+
+    /// Count the flights in progress, the aircraft seen since a cutoff and
+    ///  the waypoints
+    CountAssets,
+
+    /// Get the zone versions valid at a time, within their time bounds
+    GetZonesAsOf,
+
+    /// Get the flights filed over a time, archived ones included
+    GetSnapshotFlights,
+
+    /// Get the last position of each aircraft between a cutoff and a time
+    GetAircraftAsOf,
+
+    /// Delete zone versions replaced before a cutoff, a batch at a time
+    DeleteZoneHistory,
+
+    /// Delete aircraft positions reported before a cutoff, a batch at a time
+    DeleteAircraftHistory,
+
+    /// Serialize volume reservations until the end of the transaction
+    LockVolumeReservations,
+
+    /// Insert a volume reservation, unless it overlaps another reservation
+    InsertVolumeReservation,
+
+    /// Release a volume reservation of an operator
+    ReleaseVolumeReservation,
+
+    /// Get a volume reservation crossed by a path during a time window
+    ReservationPathIntersection,
+
+    /// Get the volume reservations overlapping a volume during a time window
+    ReservationVolumeIntersection,
+}
+
+/// Columns of a zone crossed by a volume or path, and whether the aircraft
+///  or flight is authorized to enter it for the whole time window
+fn constraint_columns(time_start: &str, time_end: &str, aircraft: &str, flight: &str) -> String {
+    format!(
+        r#""identifier",
+                    "zone_type",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "time_start",
+                    "time_end",
+                    "schedule",
+                    "authority",
+                    EXISTS (
+                        SELECT 1 FROM {authorizations_table_name} AS "auth"
+                        WHERE "auth"."zone_identifier" = "zone"."identifier"
+                            AND (
+                                "auth"."aircraft_identifier" = {aircraft}::VARCHAR
+                                OR "auth"."flight_identifier" = {flight}::VARCHAR
+                            )
+                            AND "auth"."time_start" <= {time_start}
+                            AND "auth"."time_end" >= {time_end}
+                    ) AS "authorized""#,
+        authorizations_table_name = authorization::get_table_name(),
+    )
+}
+
+/// Columns of a zone row, as read by the zones module
+///  pub(super) so that the zone history can copy them
+pub(super) const ZONE_COLUMNS: &str = r#""identifier",
+                    "zone_type",
+                    "footprint",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "time_start",
+                    "time_end",
+                    "schedule",
+                    "waypoint_buffer_meters",
+                    "description",
+                    "authority",
+                    "contact",
+                    "source_url""#;
+
+/// Columns of an aircraft state row
+const AIRCRAFT_STATE_COLUMNS: &str = r#""identifier",
+                    "session_id",
+                    "aircraft_type",
+                    "op_status",
+                    "simulated",
+                    "geom",
+                    "velocity_horizontal_ground_mps",
+                    "velocity_vertical_mps",
+                    "track_angle_degrees",
+                    "last_identifier_update",
+                    "last_position_update",
+                    "last_velocity_update""#;
+
+/// Columns of a volume reservation row
+const RESERVATION_COLUMNS: &str = r#""identifier",
+                    "operator_identifier",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "time_start",
+                    "time_end",
+                    "reference",
+                    "released""#;
+
+/// Inserts an aircraft or updates the provided columns, bound after the
+///  identifier. A partitioned table has no unique constraint on the
+///  identifier alone to resolve the conflict on, so the row is updated
+///  first and only inserted if missing (the rows are locked beforehand
+///  with [`Statement::LockAircraft`]).
+fn upsert_aircraft_sql(columns: &[&str], partitioned: bool) -> String {
+    let table_name = aircraft::get_table_name();
+    let names = columns
+        .iter()
+        .map(|column| format!(r#""{column}""#))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let params = (1..=columns.len() + 1)
+        .map(|i| format!("${i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if !partitioned {
+        let updates = columns
+            .iter()
+            .map(|column| format!(r#""{column}" = EXCLUDED."{column}""#))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        return format!(
+            r#"INSERT INTO {table_name} ("identifier", {names})
+            VALUES ({params})
+            ON CONFLICT ("identifier") DO UPDATE SET {updates};"#
+        );
+    }
+
+    let updates = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| format!(r#""{column}" = ${}"#, i + 2))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        r#"WITH "updated" AS (
+            UPDATE {table_name} SET {updates}
+            WHERE "identifier" = $1
+            RETURNING 1
+        )
+        INSERT INTO {table_name} ("identifier", {names})
+        SELECT {params}
+        WHERE NOT EXISTS (SELECT 1 FROM "updated");"#
+    )
+}
+
+impl Statement {
+    /// Every statement the server can issue, including each ordering of
+    ///  the paginated listings
+    pub fn all() -> Vec<Statement> {
+        let mut statements = vec![
+            Statement::ExpireAircraftSessions,
+            Statement::ReleaseAircraftSession,
+            Statement::LockAircraft,
+            Statement::UpsertAircraftIdentifier,
+            Statement::MergeAircraftSession,
+            Statement::UpsertAircraftPosition,
+            Statement::UpsertAircraftVelocity,
+            Statement::GetAircraftState,
+            Statement::GetAircraftGeom,
+            Statement::GetAircraftInBox,
+            Statement::SegmentConflict,
+            Statement::UpsertFlight,
+            Statement::ArchiveFlights,
+            Statement::DeleteFlights,
+            Statement::ExportFlights,
+            Statement::FlightPathIntersection,
+            Statement::FlightVolumeIntersection,
+            Statement::GetAircraftTelemetry,
+            Statement::GetNearbyZones,
+            Statement::Segmentize,
+            Statement::UpsertVertiport,
+            Statement::UpdateVertiportStatus,
+            Statement::GetVertiportCentroid,
+            Statement::UpsertWaypoint,
+            Statement::UpsertPublishedWaypoint,
+            Statement::DeleteStalePublishedWaypoints,
+            Statement::GetWaypointsNearGeometry,
+            Statement::UpsertZone,
+            Statement::UpsertZones,
+            Statement::GetZoneFootprint,
+            Statement::GetZonesAtPoint,
+            Statement::GetZoneTransitions,
+            Statement::GetZoneWaypoints,
+            Statement::InsertZoneWaypoint,
+            Statement::GetBlockedZoneWaypoints,
+            Statement::DeleteStaleZoneWaypoints,
+            Statement::InsertWaypointRename,
+            Statement::LockZoneAttributes,
+            Statement::UpdateZoneAttributes,
+            Statement::DeleteZones,
+            Statement::InvalidZoneGeometries,
+            Statement::ZonePathIntersection,
+            Statement::ZoneVolumeIntersection,
+            Statement::ZoneVolumeConstraints,
+            Statement::ZonePathConstraints,
+            Statement::GetZoneGaps,
+            Statement::CountVertiportArrivals,
+            Statement::HasAssets,
+            Statement::UpsertNoiseArea,
+            Statement::GetNoiseCrossings,
+            Statement::InsertZoneAuthorization,
+            Statement::GetZoneAuthorizations,
+            Statement::GetZoneChanges,
+            Statement::GetWaypointChanges,
+            Statement::GetAirspaceTombstones,
+            Statement::InsertBestPathAudit,
+            Statement::GetBestPathAudits,
+            Statement::DeleteBestPathAudits,
+            Statement::CountActiveZones,
+            Statement::CountAssets,
+            Statement::GetZonesAsOf,
+            Statement::GetSnapshotFlights,
+            Statement::GetAircraftAsOf,
+            Statement::DeleteZoneHistory,
+            Statement::DeleteAircraftHistory,
+            Statement::LockVolumeReservations,
+            Statement::InsertVolumeReservation,
+            Statement::ReleaseVolumeReservation,
+            Statement::ReservationPathIntersection,
+            Statement::ReservationVolumeIntersection,
+        ];
+
+        for ordering in Ordering::all() {
+            statements.push(Statement::GetFlights(ordering));
+            statements.push(Statement::GetPlannedFlights(ordering));
+            statements.push(Statement::GetVertiports(ordering));
+            statements.push(Statement::GetZones(ordering));
+        }
+
+        for descending in [false, true] {
+            statements.push(Statement::GetWaypoints { descending });
+            statements.push(Statement::GetWaypointClusters { descending });
+        }
+
+        statements
+    }
+
+    /// The SQL text of the statement
+    pub fn sql(&self) -> String {
+        match self {
+            Statement::ExpireAircraftSessions => format!(
+                r#"UPDATE {table_name}
+                SET "session_id" = NULL
+                WHERE "session_id" IS NOT NULL
+                    AND GREATEST(
+                        "last_identifier_update",
+                        "last_position_update",
+                        "last_velocity_update"
+                    ) < $1;"#,
+                table_name = aircraft::get_table_name()
+            ),
+            Statement::ReleaseAircraftSession => format!(
+                r#"UPDATE {table_name}
+                SET "session_id" = NULL
+                WHERE "session_id" = $1
+                    AND "identifier" IS DISTINCT FROM $2;"#,
+                table_name = aircraft::get_table_name()
+            ),
+            // keys are hashed in order so that concurrent batches can't deadlock
+            Statement::LockAircraft => r#"SELECT pg_advisory_xact_lock("key")
+                FROM (
+                    SELECT DISTINCT hashtext("identifier") AS "key"
+                    FROM unnest($1::TEXT[]) AS "identifier"
+                    ORDER BY "key"
+                ) AS "keys";"#
+                .to_string(),
+            Statement::UpsertAircraftIdentifier => upsert_aircraft_sql(
+                &["session_id", "aircraft_type", "last_identifier_update"],
+                partition::get_grid().is_some(),
+            ),
+            // the newest position and velocity of the two rows are kept
+            Statement::MergeAircraftSession => format!(
+                r#"WITH "duplicate" AS (
+                DELETE FROM {table_name}
+                WHERE "identifier" = $1
+                    AND "identifier" <> $2
+                RETURNING *
+            ), "merged" AS (
+                UPDATE {table_name} AS "a"
+                SET "geom" = CASE
+                        WHEN "d"."last_position_update" > "a"."last_position_update"
+                            OR "a"."last_position_update" IS NULL
+                        THEN "d"."geom" ELSE "a"."geom" END,
+                    "last_position_update" = GREATEST(
+                        "a"."last_position_update",
+                        "d"."last_position_update"
+                    ),
+                    "velocity_horizontal_ground_mps" = CASE
+                        WHEN "d"."last_velocity_update" > "a"."last_velocity_update"
+                            OR "a"."last_velocity_update" IS NULL
+                        THEN "d"."velocity_horizontal_ground_mps"
+                        ELSE "a"."velocity_horizontal_ground_mps" END,
+                    "velocity_horizontal_air_mps" = CASE
+                        WHEN "d"."last_velocity_update" > "a"."last_velocity_update"
+                            OR "a"."last_velocity_update" IS NULL
+                        THEN "d"."velocity_horizontal_air_mps"
+                        ELSE "a"."velocity_horizontal_air_mps" END,
+                    "velocity_vertical_mps" = CASE
+                        WHEN "d"."last_velocity_update" > "a"."last_velocity_update"
+                            OR "a"."last_velocity_update" IS NULL
+                        THEN "d"."velocity_vertical_mps"
+                        ELSE "a"."velocity_vertical_mps" END,
+                    "track_angle_degrees" = CASE
+                        WHEN "d"."last_velocity_update" > "a"."last_velocity_update"
+                            OR "a"."last_velocity_update" IS NULL
+                        THEN "d"."track_angle_degrees"
+                        ELSE "a"."track_angle_degrees" END,
+                    "last_velocity_update" = GREATEST(
+                        "a"."last_velocity_update",
+                        "d"."last_velocity_update"
+                    ),
+                    "region" = CASE
+                        WHEN "d"."last_position_update" > "a"."last_position_update"
+                            OR "a"."last_position_update" IS NULL
+                        THEN "d"."region" ELSE "a"."region" END,
+                    "simulated" = "a"."simulated" OR "d"."simulated"
+                FROM "duplicate" AS "d"
+                WHERE "a"."identifier" = $2
+                RETURNING "a"."identifier"
+            ), "flights" AS (
+                UPDATE {flights_table_name}
+                SET "aircraft_identifier" = $2
+                WHERE "aircraft_identifier" = $1
+                    AND EXISTS (SELECT 1 FROM "duplicate")
+                RETURNING "flight_identifier"
+            )
+            SELECT
+                (SELECT COUNT(*) FROM "merged") AS "merged",
+                (SELECT COUNT(*) FROM "flights") AS "flights";"#,
+                table_name = aircraft::get_table_name(),
+                flights_table_name = flight::get_flights_table_name()
+            ),
+            Statement::UpsertAircraftPosition => upsert_aircraft_sql(
+                &["geom", "last_position_update", "region"],
+                partition::get_grid().is_some(),
+            ),
+            Statement::UpsertAircraftVelocity => upsert_aircraft_sql(
+                &[
+                    "velocity_horizontal_ground_mps",
+                    "velocity_vertical_mps",
+                    "track_angle_degrees",
+                    "last_velocity_update",
+                ],
+                partition::get_grid().is_some(),
+            ),
+            Statement::GetAircraftState => format!(
+                r#"SELECT {AIRCRAFT_STATE_COLUMNS}
+                FROM {table_name}
+                WHERE "identifier" = ANY($1);
+            "#,
+                table_name = aircraft::get_table_name()
+            ),
+            Statement::GetAircraftGeom => format!(
+                r#"SELECT "geom" FROM {table_name} WHERE "identifier" = $1;"#,
+                table_name = aircraft::get_table_name()
+            ),
+            // the regions let the planner skip the partitions outside the box
+            Statement::GetAircraftInBox => format!(
+                r#"SELECT {AIRCRAFT_STATE_COLUMNS}
+                FROM {table_name}
+                WHERE "geom" && ST_MakeEnvelope($1, $2, $3, $4, {DEFAULT_SRID})
+                    AND "last_position_update" >= $5
+                    AND {region_filter};"#,
+                table_name = aircraft::get_table_name(),
+                region_filter = match partition::get_grid() {
+                    Some(_) => r#""region" = ANY($6::INTEGER[])"#,
+                    None => r#"($6::INTEGER[] IS NULL OR "region" = ANY($6))"#,
+                }
+            ),
+            Statement::SegmentConflict => r#"
+                SELECT COALESCE(
+                    ST_Distance(
+                        ST_Force2D("a")::GEOGRAPHY,
+                        ST_Force2D("b")::GEOGRAPHY
+                    ) < $3
+                    AND GREATEST(
+                        ST_ZMin("a") - ST_ZMax("b"),
+                        ST_ZMin("b") - ST_ZMax("a"),
+                        0
+                    ) < $4,
+                    TRUE
+                ) as "conflict"
+                FROM (SELECT $1::GEOMETRY as "a", $2::GEOMETRY as "b") as "segments"
+            "#
+            .to_string(),
+            Statement::UpsertFlight => format!(
+                r#"INSERT INTO {table_name} (
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "aircraft_type",
+                    "simulated",
+                    "time_start",
+                    "time_end",
+                    "geom",
+                    "isa",
+                    "priority",
+                    "original_point_count"
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, ST_Envelope($7), $8, $9)
+                ON CONFLICT ("flight_identifier") DO UPDATE
+                    SET "aircraft_identifier" = EXCLUDED."aircraft_identifier",
+                        "aircraft_type" = EXCLUDED."aircraft_type",
+                        "simulated" = EXCLUDED."simulated",
+                        "priority" = EXCLUDED."priority",
+                        "original_point_count" = EXCLUDED."original_point_count",
+                        "geom" = EXCLUDED."geom",
+                        "isa" = EXCLUDED."isa",
+                        "time_start" = EXCLUDED."time_start",
+                        "time_end" = EXCLUDED."time_end";"#,
+                table_name = flight::get_flights_table_name()
+            ),
+            Statement::ArchiveFlights => format!(
+                r#"WITH "purged" AS (
+                    DELETE FROM {table_name}
+                    WHERE "flight_identifier" IN (
+                        SELECT "flight_identifier"
+                        FROM {table_name}
+                        WHERE "time_end" < $1
+                        LIMIT $2
+                    )
+                    RETURNING
+                        "flight_identifier",
+                        "aircraft_identifier",
+                        "aircraft_type",
+                        "simulated",
+                        "geom",
+                        "isa",
+                        "time_start",
+                        "time_end",
+                        "priority",
+                        "original_point_count"
+                )
+                INSERT INTO {archive_table_name} (
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "aircraft_type",
+                    "simulated",
+                    "geom",
+                    "isa",
+                    "time_start",
+                    "time_end",
+                    "priority",
+                    "original_point_count"
+                )
+                SELECT * FROM "purged";"#,
+                table_name = flight::get_flights_table_name(),
+                archive_table_name = flight::get_archive_table_name()
+            ),
+            Statement::DeleteFlights => format!(
+                r#"DELETE FROM {table_name}
+                WHERE "flight_identifier" IN (
+                    SELECT "flight_identifier"
+                    FROM {table_name}
+                    WHERE "time_end" < $1
+                    LIMIT $2
+                );"#,
+                table_name = flight::get_flights_table_name()
+            ),
+            Statement::ExportFlights => format!(
+                r#"SELECT
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "aircraft_type",
+                    "simulated",
+                    "priority",
+                    "time_start",
+                    "time_end",
+                    ST_AsBinary("geom") AS "path",
+                    FALSE AS "archived"
+                FROM {table_name}
+                WHERE "time_end" >= $1 AND "time_start" <= $2
+                UNION ALL
+                SELECT
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "aircraft_type",
+                    "simulated",
+                    "priority",
+                    "time_start",
+                    "time_end",
+                    ST_AsBinary("geom") AS "path",
+                    TRUE AS "archived"
+                FROM {archive_table_name}
+                WHERE $3 AND "time_end" >= $1 AND "time_start" <= $2
+                ORDER BY "time_start", "flight_identifier";"#,
+                table_name = flight::get_flights_table_name(),
+                archive_table_name = flight::get_archive_table_name(),
+            ),
+            Statement::FlightPathIntersection => format!(
+                r#"
+                SELECT
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "aircraft_type",
+                    "priority",
+                    "geom",
+                    "time_start",
+                    "time_end",
+                    "length_meters" as "distance",
+                    ST_3DDistance("geom_ecef", "path") as "distance_to_path"
+                FROM {flights_table_name},
+                    ST_Transform($1, 4978) as "path"
+                WHERE
+                    ("time_start" <= $4 OR "time_start" IS NULL) -- easy checks first
+                    AND ("time_end" >= $3 OR "time_end" IS NULL)
+                    AND ("simulated" = FALSE OR $5)
+                    AND ("geom_ecef" IS NULL OR ST_3DDWithin("geom_ecef", "path", $2))
+            "#,
+                flights_table_name = flight::get_flights_table_name(),
+            ),
+            Statement::FlightVolumeIntersection => format!(
+                r#"
+                SELECT
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "time_start",
+                    "time_end"
+                FROM {flights_table_name}
+                WHERE
+                    ("time_start" <= $5 OR "time_start" IS NULL) -- easy checks first
+                    AND ("time_end" >= $4 OR "time_end" IS NULL)
+                    AND ("simulated" = FALSE OR $6)
+                    AND ST_ZMax("geom") >= $2
+                    AND ST_ZMin("geom") <= $3
+                    AND ST_Intersects($1::GEOMETRY(POLYGONZ, {DEFAULT_SRID}), "geom")
+            "#,
+                flights_table_name = flight::get_flights_table_name(),
+            ),
+            Statement::GetFlights(ordering) => format!(
+                r#"
+                SELECT 
+                    "flights"."flight_identifier" as "flight_identifier",
+                    "aircraft"."identifier" as "aircraft_identifier",
+                    "aircraft"."aircraft_type" as "aircraft_type",
+                    "aircraft"."simulated" as "simulated",
+                    "flights"."time_start" as "time_start",
+                    "flights"."time_end" as "time_end",
+                    CASE WHEN "flights"."flight_identifier" = "aircraft"."session_id" THEN
+                        ST_LineLocatePoint(
+                            ST_Force2D("flights"."geom"),
+                            ST_Force2D("aircraft"."geom")
+                        )
+                    END as "path_fraction"
+                FROM {aircraft_table_name} as "aircraft"
+                LEFT JOIN {flights_table_name} as "flights"
+                    ON (
+                        "flights"."aircraft_identifier" = "aircraft"."identifier"
+                        OR "flights"."flight_identifier" = "aircraft"."session_id"
+                    )
+                WHERE 
+                    ("aircraft"."simulated" = FALSE OR $8)
+                    AND ((
+                        -- get grounded aircraft without a scheduled flight
+                        ST_Intersects($1, "aircraft"."geom")
+                        AND "aircraft"."last_position_update" >= $2
+                        AND "aircraft"."last_position_update" <= $3
+                        AND ($4::FLOAT8 IS NULL OR ST_Z("aircraft"."geom") >= $4)
+                        AND ($5::FLOAT8 IS NULL OR ST_Z("aircraft"."geom") <= $5)
+                    ) OR (
+                        -- flights that intersect this window
+                        "flights"."geom" IS NOT NULL
+                        AND ("flights"."simulated" = FALSE OR $8)
+                        AND ST_Intersects($1, "flights"."geom")
+                        AND "flights"."time_end" >= $2
+                        AND "flights"."time_start" <= $3
+                        AND ($4::FLOAT8 IS NULL OR ST_ZMax("flights"."geom") >= $4)
+                        AND ($5::FLOAT8 IS NULL OR ST_ZMin("flights"."geom") <= $5)
+                    ))
+                {order}
+                LIMIT $6 OFFSET $7;
+                "#,
+                flights_table_name = flight::get_flights_table_name(),
+                aircraft_table_name = aircraft::get_table_name(),
+                order = ordering.order_clause(
+                    flight::get_order_column(ordering.order_by),
+                    &[
+                        r#""aircraft"."identifier""#,
+                        r#""flights"."flight_identifier""#
+                    ]
+                ),
+            ),
+            // same parameters as GetFlights, so the future can be queried
+            Statement::GetPlannedFlights(ordering) => format!(
+                r#"
+                SELECT
+                    "flights"."flight_identifier" as "flight_identifier",
+                    "flights"."aircraft_identifier" as "aircraft_identifier",
+                    "flights"."aircraft_type" as "aircraft_type",
+                    "flights"."simulated" as "simulated",
+                    "flights"."time_start" as "time_start",
+                    "flights"."time_end" as "time_end",
+                    NULL::FLOAT8 as "path_fraction"
+                FROM {flights_table_name} as "flights"
+                WHERE
+                    "flights"."geom" IS NOT NULL
+                    AND ("flights"."simulated" = FALSE OR $8)
+                    AND ST_Intersects($1, "flights"."geom")
+                    AND "flights"."time_end" >= $2
+                    AND "flights"."time_start" <= $3
+                    AND ($4::FLOAT8 IS NULL OR ST_ZMax("flights"."geom") >= $4)
+                    AND ($5::FLOAT8 IS NULL OR ST_ZMin("flights"."geom") <= $5)
+                {order}
+                LIMIT $6 OFFSET $7;
+                "#,
+                flights_table_name = flight::get_flights_table_name(),
+                order = ordering.order_clause(
+                    flight::get_planned_order_column(ordering.order_by),
+                    &[r#""flights"."flight_identifier""#]
+                ),
+            ),
+            Statement::GetAircraftTelemetry => format!(
+                r#"SELECT
+                        "identifier",
+                        "session_id",
+                        "geom",
+                        "velocity_horizontal_ground_mps",
+                        "velocity_vertical_mps",
+                        "track_angle_degrees",
+                        "last_position_update",
+                        "op_status"
+                    FROM {table_name} 
+                    WHERE
+                        "session_id" = ANY($1)
+                        OR "identifier" = ANY($2);
+            "#,
+                table_name = aircraft::get_table_name(),
+            ),
+            Statement::GetNearbyZones => format!(
+                r#"SELECT
+                        "aircraft"."identifier" AS "aircraft_identifier",
+                        "zones"."identifier" AS "zone_identifier",
+                        "zones"."schedule",
+                        SQRT(
+                            POWER(ST_Distance(
+                                "zones"."footprint"::geography,
+                                "aircraft"."geom"::geography
+                            ), 2)
+                            + POWER(GREATEST(
+                                "zones"."altitude_meters_min" - ST_Z("aircraft"."geom"),
+                                ST_Z("aircraft"."geom") - "zones"."altitude_meters_max",
+                                0
+                            ), 2)
+                        ) AS "distance_meters"
+                    FROM {aircraft_table_name}
+                    JOIN {zones_table_name} ON ST_DWithin(
+                        "zones"."footprint"::geography,
+                        "aircraft"."geom"::geography,
+                        $2
+                    )
+                    WHERE
+                        "aircraft"."identifier" = ANY($1)
+                        AND "aircraft"."geom" IS NOT NULL
+                        AND "zones"."zone_type" = $4
+                        AND ("zones"."time_start" <= $3 OR "zones"."time_start" IS NULL)
+                        AND ("zones"."time_end" >= $3 OR "zones"."time_end" IS NULL)
+                    ORDER BY "distance_meters";
+            "#,
+                aircraft_table_name = aircraft::get_table_name(),
+                zones_table_name = zone::get_table_name(),
+            ),
+            Statement::Segmentize => r#"WITH "segments" AS (
+                SELECT
+                    "geom",
+                    ST_3DLength(ST_Transform("geom", 4978)) AS "distance_m"
+                FROM ST_DumpSegments(
+                    (
+                        SELECT ST_Segmentize(
+                            $1::geography,
+                            $2::FLOAT
+                        )::geometry
+                    )
+                )
+            ) SELECT 
+                    ROW_NUMBER() OVER () AS "idx",
+                    "segments"."geom" AS "geom",
+                    "segments"."distance_m" AS "distance_m"
+                FROM "segments";
+            "#
+            .to_string(),
+            Statement::UpsertVertiport => format!(
+                r#"WITH "tmp" AS (
+                    INSERT INTO {zones_table_name} (
+                        "identifier",
+                        "geom",
+                        "footprint",
+                        "altitude_meters_min",
+                        "altitude_meters_max",
+                        "zone_type",
+                        "last_updated"
+                    ) VALUES (
+                        $1,
+                        ST_EXTRUDE(
+                            $2::GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+                            0,
+                            0,
+                            ($4::FLOAT(4) - $3::FLOAT(4))
+                        ),
+                        ST_Multi(ST_Force2D($2::GEOMETRY(POLYGONZ, {DEFAULT_SRID}))),
+                        $3,
+                        $4,
+                        $6,
+                        $7
+                    )
+                    ON CONFLICT ("identifier") DO UPDATE
+                    SET
+                        "geom" = EXCLUDED."geom",
+                        "footprint" = EXCLUDED."footprint",
+                        "zone_type" = EXCLUDED."zone_type"
+                    RETURNING "id"
+                ) INSERT INTO {vertiports_table_name} (
+                    "identifier",
+                    "zone_id",
+                    "geom",
+                    "label",
+                    "altitude_meters",
+                    "last_updated",
+                    "operational_status",
+                    "tlof",
+                    "fato",
+                    "safety_area",
+                    "approach_headings",
+                    "operating_hours"
+                ) VALUES (
+                    $1::VARCHAR,
+                    (SELECT "id" FROM "tmp"),
+                    $2::GEOMETRY,
+                    $5::VARCHAR,
+                    $3::FLOAT(4),
+                    $7::TIMESTAMPTZ,
+                    coalesce($8::vertiportstatus, '{status_enum_default}'),
+                    $9::GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+                    $10::GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+                    $11::GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+                    $12::FLOAT(4)[],
+                    $13::INTEGER[]
+                )
+                ON CONFLICT ("identifier") DO UPDATE
+                    SET
+                        "label" = coalesce($5, {vertiports_table_name}."label"),
+                        "zone_id" = EXCLUDED."zone_id",
+                        "geom" = EXCLUDED."geom",
+                        "altitude_meters" = EXCLUDED."altitude_meters",
+                        "last_updated" = EXCLUDED."last_updated",
+                        "operational_status" = coalesce(
+                            $8::vertiportstatus,
+                            {vertiports_table_name}."operational_status"
+                        ),
+                        "tlof" = EXCLUDED."tlof",
+                        "fato" = EXCLUDED."fato",
+                        "safety_area" = EXCLUDED."safety_area",
+                        "approach_headings" = EXCLUDED."approach_headings",
+                        "operating_hours" = EXCLUDED."operating_hours";"#,
+                vertiports_table_name = vertiport::get_table_name(),
+                zones_table_name = zone::get_table_name(),
+                status_enum_default = VertiportStatus::Operational,
+            ),
+            Statement::UpdateVertiportStatus => format!(
+                r#"UPDATE {table_name}
+                SET "operational_status" = $2
+                WHERE "identifier" = $1;"#,
+                table_name = vertiport::get_table_name()
+            ),
+            Statement::GetVertiportCentroid => format!(
+                r#"
+                SELECT ST_Force3DZ (
+                    ST_Centroid("geom"),
+                    "altitude_meters"
+                ),
+                "operational_status",
+                "operating_hours"
+                FROM {table_name}
+                WHERE "identifier" = $1;"#,
+                table_name = vertiport::get_table_name()
+            ),
+            Statement::GetVertiports(ordering) => format!(
+                r#"SELECT
+                    "identifier",
+                    "label",
+                    ST_Force2D(ST_ExteriorRing("geom")) AS "ring",
+                    "altitude_meters",
+                    "last_updated",
+                    "operational_status",
+                    ST_Force2D(ST_ExteriorRing("tlof")) AS "tlof_ring",
+                    ST_Force2D(ST_ExteriorRing("fato")) AS "fato_ring",
+                    ST_Force2D(ST_ExteriorRing("safety_area")) AS "safety_area_ring",
+                    "approach_headings",
+                    "operating_hours"
+                FROM {table_name}
+                {order}
+                LIMIT $1 OFFSET $2;
+            "#,
+                table_name = vertiport::get_table_name(),
+                order = ordering.order_clause(
+                    vertiport::get_order_column(ordering.order_by),
+                    &[r#""identifier""#]
+                ),
+            ),
+            Statement::UpsertWaypoint => format!(
+                r#"INSERT INTO {table_name} (
+                "identifier",
+                "geog"
+            )
+            VALUES ($1, $2::geography)
+            ON CONFLICT ("identifier")
+            DO UPDATE
+                SET "geog" = EXCLUDED."geog";
+            "#,
+                table_name = waypoint::get_table_name()
+            ),
+            Statement::UpsertPublishedWaypoint => format!(
+                r#"INSERT INTO {table_name} (
+                "identifier",
+                "geog",
+                "source"
+            )
+            VALUES ($1, $2::geography, $3)
+            ON CONFLICT ("identifier")
+            DO UPDATE
+                SET "geog" = EXCLUDED."geog",
+                "source" = EXCLUDED."source";
+            "#,
+                table_name = waypoint::get_table_name()
+            ),
+            Statement::DeleteStalePublishedWaypoints => format!(
+                r#"DELETE FROM {table_name}
+                WHERE "source" = $1
+                AND NOT ("identifier" = ANY($2));"#,
+                table_name = waypoint::get_table_name(),
+            ),
+            Statement::GetWaypointsNearGeometry => format!(
+                r#"SELECT
+                    "identifier",
+                    "geog"
+                FROM {table_name}
+                WHERE ST_DWithin(
+                    "geog",
+                    $1::geography, -- ignores Z-axis
+                    $2::FLOAT(4),
+                    false
+                );"#,
+                table_name = waypoint::get_table_name()
+            ),
+            Statement::GetWaypoints { descending } => format!(
+                r#"SELECT
+                    "identifier",
+                    "geog"
+                FROM {table_name}
+                {order}
+                LIMIT $1 OFFSET $2;
+            "#,
+                table_name = waypoint::get_table_name(),
+                order = Ordering {
+                    order_by: OrderBy::Identifier,
+                    descending: *descending,
+                }
+                .order_clause(r#""identifier""#, &[]),
+            ),
+            Statement::GetWaypointClusters { descending } => format!(
+                r#"SELECT
+                    ST_Centroid(ST_Collect("geog"::geometry)) AS "centroid",
+                    COUNT(*) AS "count",
+                    MIN("identifier") AS "identifier"
+                FROM {table_name}
+                GROUP BY ST_SnapToGrid("geog"::geometry, $1::FLOAT8)
+                {order}
+                LIMIT $2 OFFSET $3;
+            "#,
+                table_name = waypoint::get_table_name(),
+                order = Ordering {
+                    order_by: OrderBy::Identifier,
+                    descending: *descending,
+                }
+                .order_clause(r#"MIN("identifier")"#, &[]),
+            ),
+            Statement::UpsertZone => format!(
+                r#"INSERT INTO {table_name} (
+                "identifier",
+                "zone_type",
+                "geom",
+                "footprint",
+                "altitude_meters_min",
+                "altitude_meters_max",
+                "time_start",
+                "time_end",
+                "last_updated",
+                "schedule",
+                "waypoint_buffer_meters",
+                "description",
+                "authority",
+                "contact",
+                "source_url"
+            )
+            VALUES (
+                $1,
+                $2,
+                {extruded},
+                ST_Force2D($3::GEOMETRY(MULTIPOLYGONZ, {DEFAULT_SRID})),
+                $4,
+                $5,
+                $6,
+                $7,
+                NOW(),
+                $8,
+                $9,
+                $10,
+                $11,
+                $12,
+                $13
+            )
+            ON CONFLICT ("identifier") DO UPDATE
+                SET "geom" = EXCLUDED."geom",
+                "footprint" = EXCLUDED."footprint",
+                "altitude_meters_min" = EXCLUDED."altitude_meters_min",
+                "altitude_meters_max" = EXCLUDED."altitude_meters_max",
+                "time_start" = EXCLUDED."time_start",
+                "time_end" = EXCLUDED."time_end",
+                "schedule" = EXCLUDED."schedule",
+                "waypoint_buffer_meters" = EXCLUDED."waypoint_buffer_meters",
+                "description" = EXCLUDED."description",
+                "authority" = EXCLUDED."authority",
+                "contact" = EXCLUDED."contact",
+                "source_url" = EXCLUDED."source_url"
+            RETURNING ("xmax" = 0) AS "inserted";
+            "#,
+                table_name = zone::get_table_name(),
+                extruded = zone::zone_volume_sql(
+                    capabilities::zone_volumes(),
+                    &format!("$3::GEOMETRY(MULTIPOLYGONZ, {DEFAULT_SRID})"),
+                    "$4::FLOAT(4)",
+                    "$5::FLOAT(4)"
+                ),
+            ),
+            Statement::UpsertZones => format!(
+                r#"INSERT INTO {table_name} (
+                "identifier",
+                "zone_type",
+                "geom",
+                "footprint",
+                "altitude_meters_min",
+                "altitude_meters_max",
+                "time_start",
+                "time_end",
+                "last_updated",
+                "schedule",
+                "waypoint_buffer_meters",
+                "description",
+                "authority",
+                "contact",
+                "source_url"
+            )
+            SELECT
+                "z"."identifier",
+                "z"."zone_type",
+                {extruded},
+                ST_Force2D("z"."geom"::GEOMETRY(MULTIPOLYGONZ, {DEFAULT_SRID})),
+                "z"."altitude_meters_min",
+                "z"."altitude_meters_max",
+                "z"."time_start",
+                "z"."time_end",
+                NOW(),
+                "z"."schedule"::INTEGER[],
+                "z"."waypoint_buffer_meters",
+                "z"."description",
+                "z"."authority",
+                "z"."contact",
+                "z"."source_url"
+            FROM UNNEST(
+                $1::TEXT[],
+                $2::zonetype[],
+                $3::GEOMETRY[],
+                $4::FLOAT4[],
+                $5::FLOAT4[],
+                $6::TIMESTAMPTZ[],
+                $7::TIMESTAMPTZ[],
+                $8::TEXT[],
+                $9::FLOAT4[],
+                $10::TEXT[],
+                $11::TEXT[],
+                $12::TEXT[],
+                $13::TEXT[]
+            ) AS "z" (
+                "identifier",
+                "zone_type",
+                "geom",
+                "altitude_meters_min",
+                "altitude_meters_max",
+                "time_start",
+                "time_end",
+                "schedule",
+                "waypoint_buffer_meters",
+                "description",
+                "authority",
+                "contact",
+                "source_url"
+            )
+            ON CONFLICT ("identifier") DO UPDATE
+                SET "geom" = EXCLUDED."geom",
+                "footprint" = EXCLUDED."footprint",
+                "altitude_meters_min" = EXCLUDED."altitude_meters_min",
+                "altitude_meters_max" = EXCLUDED."altitude_meters_max",
+                "time_start" = EXCLUDED."time_start",
+                "time_end" = EXCLUDED."time_end",
+                "schedule" = EXCLUDED."schedule",
+                "waypoint_buffer_meters" = EXCLUDED."waypoint_buffer_meters",
+                "description" = EXCLUDED."description",
+                "authority" = EXCLUDED."authority",
+                "contact" = EXCLUDED."contact",
+                "source_url" = EXCLUDED."source_url";
+            "#,
+                table_name = zone::get_table_name(),
+                extruded = zone::zone_volume_sql(
+                    capabilities::zone_volumes(),
+                    &format!(r#""z"."geom"::GEOMETRY(MULTIPOLYGONZ, {DEFAULT_SRID})"#),
+                    r#""z"."altitude_meters_min""#,
+                    r#""z"."altitude_meters_max""#
+                ),
+            ),
+            Statement::GetZoneFootprint => format!(
+                r#"SELECT
+                    "z"."id",
+                    "z"."zone_type",
+                    "z"."waypoint_buffer_meters",
+                    "z"."footprint",
+                    ST_Force2D("v"."fato") AS "fato",
+                    "v"."approach_headings"
+                FROM {table_name} AS "z"
+                LEFT JOIN {vertiports_table_name} AS "v"
+                    ON "v"."zone_id" = "z"."id"
+                WHERE "z"."identifier" = $1;"#,
+                table_name = zone::get_table_name(),
+                vertiports_table_name = vertiport::get_table_name(),
+            ),
+            Statement::GetZoneWaypoints => format!(
+                r#"SELECT "identifier", "geog"::GEOMETRY AS "geom"
+                FROM {waypoints_table_name}
+                WHERE "zone_id" = $1;"#,
+                waypoints_table_name = waypoint::get_table_name(),
+            ),
+            Statement::InsertZoneWaypoint => format!(
+                r#"INSERT INTO {waypoints_table_name} (
+                    "identifier",
+                    "geog",
+                    "zone_id"
+                )
+                SELECT $1, $2::GEOGRAPHY, $3
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM {zones_table_name}
+                    WHERE ST_Intersects("footprint", $2::GEOMETRY)
+                )
+                ON CONFLICT ("identifier") DO UPDATE
+                    SET "geog" = EXCLUDED."geog",
+                    "zone_id" = EXCLUDED."zone_id";"#,
+                waypoints_table_name = waypoint::get_table_name(),
+                zones_table_name = zone::get_table_name(),
+            ),
+            // the previewed zone is checked in place of its stored version
+            Statement::GetBlockedZoneWaypoints => format!(
+                r#"SELECT ("p"."index" - 1)::INT4 AS "index"
+                FROM UNNEST($1::FLOAT8[], $2::FLOAT8[])
+                    WITH ORDINALITY AS "p"("x", "y", "index")
+                WHERE ST_Intersects(
+                        ST_Force2D($4::GEOMETRY(MULTIPOLYGONZ, {DEFAULT_SRID})),
+                        ST_SetSRID(ST_MakePoint("p"."x", "p"."y"), {DEFAULT_SRID})
+                    )
+                    OR EXISTS (
+                        SELECT 1 FROM {zones_table_name}
+                        WHERE "identifier" <> $3
+                        AND ST_Intersects(
+                            "footprint",
+                            ST_SetSRID(ST_MakePoint("p"."x", "p"."y"), {DEFAULT_SRID})
+                        )
+                    );"#,
+                zones_table_name = zone::get_table_name(),
+            ),
+            Statement::DeleteStaleZoneWaypoints => format!(
+                r#"DELETE FROM {waypoints_table_name}
+                WHERE "zone_id" = $1
+                AND NOT ("identifier" = ANY($2));"#,
+                waypoints_table_name = waypoint::get_table_name(),
+            ),
+            Statement::InsertWaypointRename => format!(
+                r#"INSERT INTO {renames_table_name} (
+                    "zone_id",
+                    "old_identifier",
+                    "new_identifier"
+                ) VALUES ($1, $2, $3);"#,
+                renames_table_name = waypoint::get_renames_table_name(),
+            ),
+            Statement::LockZoneAttributes => format!(
+                r#"SELECT
+                    "zone_type",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "time_start",
+                    "time_end",
+                    "schedule"
+                FROM {table_name}
+                WHERE "identifier" = $1
+                FOR UPDATE;"#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::UpdateZoneAttributes => format!(
+                r#"UPDATE {table_name}
+                SET "zone_type" = $2,
+                    "geom" = CASE
+                        WHEN "altitude_meters_min" = $3 AND "altitude_meters_max" = $4 THEN "geom"
+                        ELSE {extruded}
+                    END,
+                    "altitude_meters_min" = $3,
+                    "altitude_meters_max" = $4,
+                    "time_start" = $5,
+                    "time_end" = $6,
+                    "schedule" = $7,
+                    "last_updated" = NOW()
+                WHERE "identifier" = $1;"#,
+                table_name = zone::get_table_name(),
+                extruded = zone::zone_volume_sql(
+                    capabilities::zone_volumes(),
+                    r#""footprint""#,
+                    "$3::FLOAT(4)",
+                    "$4::FLOAT(4)"
+                ),
+            ),
+            Statement::GetZones(ordering) => format!(
+                r#"SELECT {ZONE_COLUMNS},
+                    (
+                        SELECT "operating_hours"
+                        FROM {vertiports_table_name} AS "ports"
+                        WHERE "ports"."zone_id" = {table_name}."id"
+                        LIMIT 1
+                    ) AS "port_operating_hours"
+                FROM {table_name}
+                {order}
+                LIMIT $1 OFFSET $2;
+            "#,
+                table_name = zone::get_table_name(),
+                vertiports_table_name = vertiport::get_table_name(),
+                order = ordering.order_clause(
+                    zone::get_order_column(ordering.order_by),
+                    &[r#""identifier""#]
+                ),
+            ),
+            Statement::GetZonesAtPoint => format!(
+                r#"SELECT
+                    "identifier",
+                    "zone_type",
+                    "footprint",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "time_start",
+                    "time_end",
+                    "schedule",
+                    "waypoint_buffer_meters",
+                    "description",
+                    "authority",
+                    "contact",
+                    "source_url"
+                FROM {table_name}
+                WHERE
+                    ST_3DIntersects(
+                        ST_Force3DZ("footprint", ST_Z($1::GEOMETRY(POINTZ, {DEFAULT_SRID}))),
+                        $1::GEOMETRY(POINTZ, {DEFAULT_SRID})
+                    )
+                    AND "altitude_meters_min" <= ST_Z($1::GEOMETRY(POINTZ, {DEFAULT_SRID}))
+                    AND "altitude_meters_max" >= ST_Z($1::GEOMETRY(POINTZ, {DEFAULT_SRID}))
+                    AND ("time_start" <= $2 OR "time_start" IS NULL)
+                    AND ("time_end" >= $2 OR "time_end" IS NULL)
+                ORDER BY "identifier";
+            "#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::GetZoneTransitions => format!(
+                r#"SELECT
+                    "identifier",
+                    "zone_type",
+                    "time_start",
+                    "time_end"
+                FROM {table_name}
+                WHERE ("time_start" > $1 AND "time_start" <= $2)
+                    OR ("time_end" > $1 AND "time_end" <= $2)
+                ORDER BY "identifier";
+            "#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::DeleteZones => format!(
+                r#"DELETE FROM {table_name}
+                WHERE "identifier" = ANY($1)
+                    AND "zone_type" <> $2;"#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::InvalidZoneGeometries => r#"SELECT "z"."identifier"
+                FROM UNNEST($1::TEXT[], $2::GEOMETRY[]) AS "z" ("identifier", "geom")
+                WHERE NOT ST_IsValid("z"."geom");"#
+                .to_string(),
+            Statement::ZonePathIntersection => format!(
+                r#"
+                SELECT
+                    "identifier",
+                    "geom",
+                    "zone_type",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "time_start",
+                    "time_end",
+                    "schedule"
+                FROM {table_name} AS "zone"
+                WHERE
+                    {intersects}
+                    AND ("time_start" <= $3 OR "time_start" IS NULL)
+                    AND ("time_end" >= $2 OR "time_end" IS NULL)
+                    AND "id" NOT IN (
+                        SELECT "zone_id" FROM {vertiports_table_name}
+                        WHERE "identifier" IN ($4, $5)
+                    )
+                    AND NOT EXISTS (
+                        SELECT 1 FROM {authorizations_table_name} AS "auth"
+                        WHERE "auth"."zone_identifier" = "zone"."identifier"
+                            AND (
+                                "auth"."aircraft_identifier" = $6::VARCHAR
+                                OR "auth"."flight_identifier" = $7::VARCHAR
+                            )
+                            AND "auth"."time_start" <= $2
+                            AND "auth"."time_end" >= $3
+                    );
+            "#,
+                table_name = zone::get_table_name(),
+                vertiports_table_name = vertiport::get_table_name(),
+                authorizations_table_name = authorization::get_table_name(),
+                intersects = zone::path_intersection_sql(
+                    capabilities::zone_volumes(),
+                    &format!("$1::GEOMETRY(LINESTRINGZ, {DEFAULT_SRID})")
+                ),
+            ),
+            Statement::ZoneVolumeIntersection => format!(
+                r#"
+                SELECT
+                    "identifier",
+                    "schedule"
+                FROM {table_name}
+                WHERE
+                    ST_Intersects("footprint", ST_Force2D($1::GEOMETRY(POLYGONZ, {DEFAULT_SRID})))
+                    AND "altitude_meters_min" <= $3
+                    AND "altitude_meters_max" >= $2
+                    AND ("time_start" <= $5 OR "time_start" IS NULL)
+                    AND ("time_end" >= $4 OR "time_end" IS NULL);
+            "#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::ZoneVolumeConstraints => format!(
+                r#"
+                SELECT
+                    {columns}
+                FROM {table_name} AS "zone"
+                WHERE
+                    ST_Intersects("footprint", ST_Force2D($1::GEOMETRY(POLYGONZ, {DEFAULT_SRID})))
+                    AND "altitude_meters_min" <= $3
+                    AND "altitude_meters_max" >= $2
+                    AND ("time_start" <= $5 OR "time_start" IS NULL)
+                    AND ("time_end" >= $4 OR "time_end" IS NULL);
+            "#,
+                table_name = zone::get_table_name(),
+                columns = constraint_columns("$4", "$5", "$6", "$7"),
+            ),
+            Statement::ZonePathConstraints => format!(
+                r#"
+                SELECT
+                    {columns}
+                FROM {table_name} AS "zone"
+                WHERE
+                    {intersects}
+                    AND ("time_start" <= $3 OR "time_start" IS NULL)
+                    AND ("time_end" >= $2 OR "time_end" IS NULL);
+            "#,
+                table_name = zone::get_table_name(),
+                columns = constraint_columns("$2", "$3", "$4", "$5"),
+                intersects = zone::path_intersection_sql(
+                    capabilities::zone_volumes(),
+                    &format!("$1::GEOMETRY(LINESTRINGZ, {DEFAULT_SRID})")
+                ),
+            ),
+            Statement::CountVertiportArrivals => format!(
+                r#"
+                SELECT COUNT(*) AS "count"
+                FROM {flights_table_name} AS "flight", {vertiports_table_name} AS "vertiport"
+                WHERE
+                    "vertiport"."identifier" = $1
+                    AND "flight"."time_end" BETWEEN $2 AND $3
+                    AND ("flight"."simulated" = FALSE OR $4)
+                    AND "flight"."flight_identifier" IS DISTINCT FROM $5::VARCHAR
+                    AND ST_Intersects(
+                        ST_Force2D(ST_EndPoint("flight"."geom")),
+                        ST_Force2D("vertiport"."geom")
+                    );
+            "#,
+                flights_table_name = flight::get_flights_table_name(),
+                vertiports_table_name = vertiport::get_table_name(),
+            ),
+            Statement::GetZoneGaps => format!(
+                r#"
+                WITH "candidates" AS (
+                    SELECT
+                        "identifier",
+                        "footprint",
+                        "altitude_meters_min",
+                        "altitude_meters_max",
+                        "schedule"
+                    FROM {table_name}
+                    WHERE
+                        ST_Intersects("footprint", ST_Force2D($1::GEOMETRY(POLYGONZ, {DEFAULT_SRID})))
+                        AND ("time_start" <= $4 OR "time_start" IS NULL)
+                        AND ("time_end" >= $3 OR "time_end" IS NULL)
+                ), "pairs" AS (
+                    SELECT
+                        "a"."identifier" AS "identifier_a",
+                        "a"."footprint" AS "footprint_a",
+                        "a"."schedule" AS "schedule_a",
+                        "b"."identifier" AS "identifier_b",
+                        "b"."footprint" AS "footprint_b",
+                        "b"."schedule" AS "schedule_b",
+                        GREATEST("a"."altitude_meters_min", "b"."altitude_meters_min") AS "altitude_meters_min",
+                        LEAST("a"."altitude_meters_max", "b"."altitude_meters_max") AS "altitude_meters_max",
+                        ST_Distance("a"."footprint"::geography, "b"."footprint"::geography) AS "width_meters"
+                    FROM "candidates" AS "a"
+                    JOIN "candidates" AS "b"
+                        ON "a"."identifier" < "b"."identifier"
+                    WHERE
+                        ST_DWithin("a"."footprint"::geography, "b"."footprint"::geography, $2::FLOAT8)
+                        AND NOT ST_Intersects("a"."footprint", "b"."footprint")
+                        AND "a"."altitude_meters_min" < "b"."altitude_meters_max"
+                        AND "b"."altitude_meters_min" < "a"."altitude_meters_max"
+                    ORDER BY "width_meters", "identifier_a", "identifier_b"
+                    LIMIT $5
+                )
+                SELECT
+                    "identifier_a",
+                    "schedule_a",
+                    "identifier_b",
+                    "schedule_b",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "width_meters",
+                    ST_ShortestLine("footprint_a", "footprint_b") AS "crossing",
+                    CASE WHEN GeometryType("gap"."hull") = 'POLYGON'
+                        THEN ST_ExteriorRing("gap"."hull")
+                    END AS "outline"
+                FROM "pairs"
+                CROSS JOIN LATERAL (
+                    SELECT ST_ConvexHull(ST_Difference(
+                        ST_Intersection(
+                            ST_Buffer("footprint_a"::geography, $2::FLOAT8)::geometry,
+                            ST_Buffer("footprint_b"::geography, $2::FLOAT8)::geometry
+                        ),
+                        ST_Union("footprint_a", "footprint_b")
+                    )) AS "hull"
+                ) AS "gap"
+                ORDER BY "width_meters", "identifier_a", "identifier_b";
+            "#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::HasAssets => format!(
+                r#"SELECT
+                    EXISTS (SELECT 1 FROM {vertiports_table_name})
+                    OR EXISTS (SELECT 1 FROM {waypoints_table_name})
+                    OR EXISTS (SELECT 1 FROM {zones_table_name})
+                    AS "has_assets";
+            "#,
+                vertiports_table_name = vertiport::get_table_name(),
+                waypoints_table_name = waypoint::get_table_name(),
+                zones_table_name = zone::get_table_name(),
+            ),
+            Statement::UpsertNoiseArea => format!(
+                r#"INSERT INTO {table_name} (
+                "identifier",
+                "geom",
+                "altitude_meters_floor",
+                "quiet_hours",
+                "last_updated"
+            )
+            VALUES (
+                $1,
+                ST_Force2D($2::GEOMETRY(POLYGONZ, {DEFAULT_SRID})),
+                $3,
+                $4,
+                NOW()
+            )
+            ON CONFLICT ("identifier") DO UPDATE
+                SET "geom" = EXCLUDED."geom",
+                "altitude_meters_floor" = EXCLUDED."altitude_meters_floor",
+                "quiet_hours" = EXCLUDED."quiet_hours",
+                "last_updated" = EXCLUDED."last_updated";
+            "#,
+                table_name = noise::get_table_name()
+            ),
+            // Each leg of the path counts as below the floor if either end is
+            Statement::GetNoiseCrossings => format!(
+                r#"SELECT
+                    "area"."identifier",
+                    "area"."quiet_hours",
+                    SUM(ST_Length(
+                        ST_Intersection(ST_Force2D("leg"."geom"), "area"."geom")::geography
+                    ))::FLOAT(8) AS "crossing_meters"
+                FROM {table_name} AS "area",
+                    (
+                        SELECT ST_MakeLine(
+                            ST_PointN($1::GEOMETRY(LINESTRINGZ, {DEFAULT_SRID}), "n"),
+                            ST_PointN($1::GEOMETRY(LINESTRINGZ, {DEFAULT_SRID}), "n" + 1)
+                        ) AS "geom"
+                        FROM generate_series(
+                            1,
+                            ST_NPoints($1::GEOMETRY(LINESTRINGZ, {DEFAULT_SRID})) - 1
+                        ) AS "n"
+                    ) AS "leg"
+                WHERE ST_Intersects(ST_Force2D("leg"."geom"), "area"."geom")
+                    AND LEAST(
+                        ST_Z(ST_StartPoint("leg"."geom")),
+                        ST_Z(ST_EndPoint("leg"."geom"))
+                    ) < "area"."altitude_meters_floor"
+                GROUP BY "area"."identifier", "area"."quiet_hours";
+            "#,
+                table_name = noise::get_table_name()
+            ),
+            Statement::InsertZoneAuthorization => format!(
+                r#"INSERT INTO {table_name} (
+                "identifier",
+                "zone_identifier",
+                "aircraft_identifier",
+                "flight_identifier",
+                "time_start",
+                "time_end",
+                "reference"
+            )
+            SELECT $1, "identifier", $3, $4, $5, $6, $7
+            FROM {zones_table_name}
+            WHERE "identifier" = $2
+            RETURNING
+                "identifier",
+                "zone_identifier",
+                "aircraft_identifier",
+                "flight_identifier",
+                "time_start",
+                "time_end",
+                "reference";
+            "#,
+                table_name = authorization::get_table_name(),
+                zones_table_name = zone::get_table_name()
+            ),
+            Statement::GetZoneAuthorizations => format!(
+                r#"SELECT
+                    "identifier",
+                    "zone_identifier",
+                    "aircraft_identifier",
+                    "flight_identifier",
+                    "time_start",
+                    "time_end",
+                    "reference"
+                FROM {table_name}
+                WHERE ($1::VARCHAR IS NULL OR "zone_identifier" = $1)
+                    AND ($2::VARCHAR IS NULL OR "aircraft_identifier" = $2)
+                    AND ($3::VARCHAR IS NULL OR "flight_identifier" = $3)
+                    AND ($4 OR "time_end" >= NOW())
+                ORDER BY "time_start", "identifier"
+                LIMIT $5;
+            "#,
+                table_name = authorization::get_table_name()
+            ),
+            Statement::GetZoneChanges => format!(
+                r#"SELECT {ZONE_COLUMNS}, "change_seq"
+                FROM {table_name}
+                WHERE "change_seq" > $1
+                ORDER BY "change_seq"
+                LIMIT $2;
+            "#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::GetWaypointChanges => format!(
+                r#"SELECT "identifier", "geog", "change_seq"
+                FROM {table_name}
+                WHERE "change_seq" > $1
+                ORDER BY "change_seq"
+                LIMIT $2;
+            "#,
+                table_name = waypoint::get_table_name()
+            ),
+            Statement::GetAirspaceTombstones => format!(
+                r#"SELECT "entity", "identifier", "change_seq"
+                FROM {table_name}
+                WHERE "change_seq" > $1
+                ORDER BY "change_seq"
+                LIMIT $2;
+            "#,
+                table_name = sync::get_tombstones_table_name()
+            ),
+            // a sequence that was never used reads as its start value
+            Statement::InsertBestPathAudit => format!(
+                r#"INSERT INTO {table_name} (
+                "identifier",
+                "flight_identifier",
+                "aircraft_identifier",
+                "request_parameters",
+                "geom",
+                "rejected_count",
+                "airspace_cursor"
+            )
+            SELECT $1, $2, $3, $4::TEXT::JSONB, $5, $6,
+                CASE WHEN "is_called" THEN "last_value" ELSE 0 END
+            FROM {sequence_name};
+            "#,
+                table_name = audit::get_table_name(),
+                sequence_name = sync::get_sequence_name()
+            ),
+            Statement::GetBestPathAudits => format!(
+                r#"SELECT
+                    "identifier",
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "request_parameters"::TEXT AS "request_parameters",
+                    "geom",
+                    "rejected_count",
+                    "airspace_cursor",
+                    "created"
+                FROM {table_name}
+                WHERE "flight_identifier" = $1
+                ORDER BY "created" DESC, "identifier"
+                LIMIT $2;
+            "#,
+                table_name = audit::get_table_name()
+            ),
+            Statement::DeleteBestPathAudits => format!(
+                r#"DELETE FROM {table_name}
+                WHERE "identifier" IN (
+                    SELECT "identifier"
+                    FROM {table_name}
+                    WHERE "created" < $1
+                    LIMIT $2
+                );"#,
+                table_name = audit::get_table_name()
+            ),
+            Statement::CountActiveZones => format!(
+                r#"SELECT "zone_type", "schedule", COUNT(*) AS "count"
+                FROM {table_name}
+                WHERE ("time_start" IS NULL OR "time_start" <= $1)
+                    AND ("time_end" IS NULL OR "time_end" >= $1)
+                GROUP BY "zone_type", "schedule";
+            "#,
+                table_name = zone::get_table_name()
+            ),
+            Statement::CountAssets => format!(
+                r#"SELECT
+                    (
+                        SELECT COUNT(*) FROM {flights_table_name}
+                        WHERE "simulated" = FALSE
+                            AND "time_start" <= $1
+                            AND "time_end" >= $1
+                    ) AS "flights_in_progress",
+                    (
+                        SELECT COUNT(*) FROM {aircraft_table_name}
+                        WHERE "last_position_update" >= $2
+                    ) AS "aircraft_seen",
+                    (SELECT COUNT(*) FROM {waypoints_table_name}) AS "waypoint_count";
+            "#,
+                flights_table_name = flight::get_flights_table_name(),
+                aircraft_table_name = aircraft::get_table_name(),
+                waypoints_table_name = waypoint::get_table_name()
+            ),
+            Statement::GetZonesAsOf => format!(
+                r#"SELECT {ZONE_COLUMNS}
+                FROM {table_name}
+                WHERE "valid_from" <= $1
+                    AND ("valid_to" IS NULL OR "valid_to" > $1)
+                    AND ("time_start" IS NULL OR "time_start" <= $1)
+                    AND ("time_end" IS NULL OR "time_end" >= $1)
+                ORDER BY "identifier";
+            "#,
+                table_name = snapshot::get_zones_table_name()
+            ),
+            Statement::GetSnapshotFlights => format!(
+                r#"SELECT
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "aircraft_type",
+                    "simulated",
+                    "priority",
+                    "time_start",
+                    "time_end",
+                    "geom",
+                    FALSE AS "archived"
+                FROM {table_name}
+                WHERE "time_start" <= $1 AND "time_end" >= $1
+                UNION ALL
+                SELECT
+                    "flight_identifier",
+                    "aircraft_identifier",
+                    "aircraft_type",
+                    "simulated",
+                    "priority",
+                    "time_start",
+                    "time_end",
+                    "geom",
+                    TRUE AS "archived"
+                FROM {archive_table_name}
+                WHERE "time_start" <= $1 AND "time_end" >= $1
+                ORDER BY "time_start", "flight_identifier";"#,
+                table_name = flight::get_flights_table_name(),
+                archive_table_name = flight::get_archive_table_name(),
+            ),
+            Statement::GetAircraftAsOf => format!(
+                r#"SELECT DISTINCT ON ("identifier")
+                    "identifier",
+                    "aircraft_type",
+                    "op_status",
+                    "simulated",
+                    "geom",
+                    "velocity_horizontal_ground_mps",
+                    "velocity_vertical_mps",
+                    "track_angle_degrees",
+                    "last_position_update"
+                FROM {table_name}
+                WHERE "last_position_update" <= $1 AND "last_position_update" >= $2
+                ORDER BY "identifier", "last_position_update" DESC;
+            "#,
+                table_name = snapshot::get_aircraft_table_name()
+            ),
+            Statement::DeleteZoneHistory => format!(
+                r#"DELETE FROM {table_name}
+                WHERE "ctid" = ANY(ARRAY(
+                    SELECT "ctid"
+                    FROM {table_name}
+                    WHERE "valid_to" < $1
+                    LIMIT $2
+                ));"#,
+                table_name = snapshot::get_zones_table_name()
+            ),
+            Statement::DeleteAircraftHistory => format!(
+                r#"DELETE FROM {table_name}
+                WHERE "ctid" = ANY(ARRAY(
+                    SELECT "ctid"
+                    FROM {table_name}
+                    WHERE "last_position_update" < $1
+                    LIMIT $2
+                ));"#,
+                table_name = snapshot::get_aircraft_table_name()
+            ),
+            // the overlap check of a concurrent insert can't see an uncommitted
+            //  reservation, so inserts take turns
+            Statement::LockVolumeReservations => format!(
+                "SELECT pg_advisory_xact_lock({});",
+                reservation::WRITE_LOCK_KEY
+            ),
+            Statement::InsertVolumeReservation => format!(
+                r#"INSERT INTO {table_name} (
+                    "identifier",
+                    "operator_identifier",
+                    "footprint",
+                    "altitude_meters_min",
+                    "altitude_meters_max",
+                    "time_start",
+                    "time_end",
+                    "reference"
+                )
+                SELECT $1, $2, ST_Force2D($3::GEOMETRY(POLYGONZ, {DEFAULT_SRID})), $4, $5, $6, $7, $8
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM {table_name}
+                    WHERE {overlaps}
+                        AND "altitude_meters_min" < $5::FLOAT(4)
+                        AND "altitude_meters_max" > $4::FLOAT(4)
+                        AND "time_start" < $7::TIMESTAMPTZ
+                        AND LEAST("time_end", "released") > $6::TIMESTAMPTZ
+                )
+                RETURNING {RESERVATION_COLUMNS};"#,
+                table_name = reservation::get_table_name(),
+                overlaps = reservation::footprint_overlap_sql(&format!(
+                    "ST_Force2D($3::GEOMETRY(POLYGONZ, {DEFAULT_SRID}))"
+                )),
+            ),
+            Statement::ReleaseVolumeReservation => format!(
+                r#"UPDATE {table_name}
+                SET "released" = NOW()
+                WHERE "identifier" = $1
+                    AND "operator_identifier" = $2
+                    AND "released" IS NULL
+                    AND "time_end" > NOW()
+                RETURNING {RESERVATION_COLUMNS};"#,
+                table_name = reservation::get_table_name()
+            ),
+            Statement::ReservationPathIntersection => format!(
+                r#"SELECT "identifier", "operator_identifier"
+                FROM {table_name}
+                WHERE "time_start" <= $3
+                    AND LEAST("time_end", "released") >= $2
+                    AND ($4::TEXT IS NULL OR "operator_identifier" <> $4::TEXT)
+                    AND {intersects}
+                LIMIT 1;"#,
+                table_name = reservation::get_table_name(),
+                intersects = zone::path_intersection_sql(
+                    capabilities::ZoneVolumes::Footprint,
+                    &format!("$1::GEOMETRY(LINESTRINGZ, {DEFAULT_SRID})")
+                ),
+            ),
+            Statement::ReservationVolumeIntersection => format!(
+                r#"SELECT "identifier"
+                FROM {table_name}
+                WHERE {overlaps}
+                    AND "altitude_meters_min" <= $3
+                    AND "altitude_meters_max" >= $2
+                    AND "time_start" <= $5
+                    AND LEAST("time_end", "released") >= $4
+                ORDER BY "time_start", "identifier";"#,
+                table_name = reservation::get_table_name(),
+                overlaps = reservation::footprint_overlap_sql(&format!(
+                    "ST_Force2D($1::GEOMETRY(POLYGONZ, {DEFAULT_SRID}))"
+                )),
+            ),
+        }
+    }
+}
+
+/// Prepares every registered statement against the database
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance
+pub async fn verify() -> Result<(), PostgisError> {
+    let pool = super::DEADPOOL_POSTGIS.get().ok_or_else(|| {
+        postgis_error!("could not get psql pool.");
+        PostgisError::Psql(PsqlError::Connection)
+    })?;
+
+    let client = pool.get().await.map_err(|e| {
+        postgis_error!("could not get client from psql connection pool: {}", e);
+        PostgisError::Psql(PsqlError::Client)
+    })?;
+
+    let mut failed = 0;
+    for statement in Statement::all() {
+        if let Err(e) = client.prepare_cached(&statement.sql()).await {
+            postgis_error!("could not prepare statement {:?}: {}", statement, e);
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        postgis_error!("{} statement(s) do not match the schema.", failed);
+        return Err(PostgisError::Psql(PsqlError::Prepare));
+    }
+
+    postgis_info!("prepared {} statements.", Statement::all().len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgis::PSQL_SCHEMA;
+
+    #[test]
+    fn ut_statements_schema_qualified() {
+        let schema = format!(r#""{PSQL_SCHEMA}"."#);
+        for statement in Statement::all() {
+            match statement {
+                // operate on parameters only
+                Statement::SegmentConflict
+                | Statement::Segmentize
+                | Statement::InvalidZoneGeometries
+                | Statement::LockAircraft
+                | Statement::LockVolumeReservations => continue,
+                _ => assert!(
+                    statement.sql().contains(&schema),
+                    "{:?} is not schema qualified",
+                    statement
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn ut_statements_all() {
+        let statements = Statement::all();
+        assert_eq!(statements.len(), 70 + 4 * Ordering::all().len() + 4);
+
+        for (i, statement) in statements.iter().enumerate() {
+            assert!(!statements[..i].contains(statement));
+        }
+    }
+
+    #[test]
+    fn ut_upsert_aircraft_sql() {
+        let columns = ["geom", "last_position_update", "region"];
+        let sql = upsert_aircraft_sql(&columns, false);
+        assert!(sql.contains(r#"("identifier", "geom", "last_position_update", "region")"#));
+        assert!(sql.contains("VALUES ($1, $2, $3, $4)"));
+        assert!(
+            sql.contains(r#"ON CONFLICT ("identifier") DO UPDATE SET "geom" = EXCLUDED."geom""#)
+        );
+
+        // no conflict target on a partitioned table
+        let sql = upsert_aircraft_sql(&columns, true);
+        assert!(!sql.contains("ON CONFLICT"));
+        assert!(sql.contains(r#"SET "geom" = $2, "last_position_update" = $3, "region" = $4"#));
+        assert!(sql.contains(r#"WHERE NOT EXISTS (SELECT 1 FROM "updated")"#));
+
+        // the tests run without a partitioning grid
+        let sql = Statement::GetAircraftInBox.sql();
+        assert!(sql.contains(r#"($6::INTEGER[] IS NULL OR "region" = ANY($6))"#));
+    }
+
+    #[test]
+    fn ut_statements_simulated_filter() {
+        let ordering = Ordering {
+            order_by: OrderBy::Identifier,
+            descending: false,
+        };
+
+        // simulated flights are opt-in everywhere
+        let sql = Statement::FlightPathIntersection.sql();
+        assert!(sql.contains(r#"("simulated" = FALSE OR $5)"#));
+
+        let sql = Statement::FlightVolumeIntersection.sql();
+        assert!(sql.contains(r#"("simulated" = FALSE OR $6)"#));
+
+        let sql = Statement::GetFlights(ordering).sql();
+        assert!(sql.contains(r#"("aircraft"."simulated" = FALSE OR $8)"#));
+        assert!(sql.contains(r#"("flights"."simulated" = FALSE OR $8)"#));
+
+        let sql = Statement::GetPlannedFlights(ordering).sql();
+        assert!(sql.contains(r#"("flights"."simulated" = FALSE OR $8)"#));
+    }
+
+    #[test]
+    fn ut_statements_bound_parameters() {
+        // request values are bound as parameters, the only literals are
+        //  constants of the statements themselves
+        let literals = [
+            format!("'{}'", VertiportStatus::Operational),
+            "'POLYGON'".to_string(),
+        ];
+
+        for statement in Statement::all() {
+            let sql = literals
+                .iter()
+                .fold(statement.sql(), |sql, literal| sql.replace(literal, ""));
+            assert!(!sql.contains('\''), "{:?} holds a literal", statement);
+        }
+    }
+
+    #[test]
+    fn ut_zone_path_intersection_endpoint_ports() {
+        // only the port zones of the origin and target vertiports are exempt,
+        //  not every zone sharing their identifiers
+        let sql = Statement::ZonePathIntersection.sql();
+        assert!(!sql.contains(r#""identifier" NOT IN ($4, $5)"#));
+        assert!(sql.contains(&format!(
+            r#"SELECT "zone_id" FROM {}"#,
+            vertiport::get_table_name()
+        )));
+        assert!(sql.contains(r#"WHERE "identifier" IN ($4, $5)"#));
+    }
+
+    #[test]
+    fn ut_statements_ordering() {
+        let ordering = Ordering {
+            order_by: OrderBy::LastUpdated,
+            descending: true,
+        };
+
+        let sql = Statement::GetZones(ordering).sql();
+        assert!(sql.contains(r#"ORDER BY "last_updated" DESC, "identifier" DESC"#));
+
+        let sql = Statement::GetFlights(ordering).sql();
+        assert!(sql.contains(
+            r#"ORDER BY "aircraft"."last_position_update" DESC, "aircraft"."identifier" DESC, "flights"."flight_identifier" DESC"#
+        ));
+
+        let sql = Statement::GetPlannedFlights(ordering).sql();
+        assert!(sql.contains(
+            r#"ORDER BY "flights"."time_start" DESC, "flights"."flight_identifier" DESC"#
+        ));
+
+        let sql = Statement::GetWaypoints { descending: false }.sql();
+        assert!(sql.contains(r#"ORDER BY "identifier" ASC"#));
+        assert_eq!(Ordering::all().len(), 4);
+    }
+
+    #[test]
+    fn ut_upsert_vertiport_status_cast() {
+        // an untyped parameter in coalesce() is inferred as text, which
+        //  can't be assigned to the enum column
+        let sql = Statement::UpsertVertiport.sql();
+        assert_eq!(sql.matches("$8::vertiportstatus").count(), 2);
+        assert_eq!(sql.matches("$8").count(), 2);
+    }
+}
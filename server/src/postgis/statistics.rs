@@ -0,0 +1,260 @@
+//! Counts and aggregates of the airspace and traffic, for dashboards.
+//!
+//! The counts are read from PostGIS when requested. The best path latency
+//!  is kept in memory by each instance (see [`best_path::latency`]), so it
+//!  only covers the searches served by the instance that answered.
+
+use super::best_path;
+use super::statements::Statement;
+use super::PostgisError;
+use crate::grpc::server::grpc_server::{
+    GetStatisticsRequest, GetStatisticsResponse, ZoneType, ZoneTypeCount,
+};
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use strum::IntoEnumIterator;
+
+/// Default window in which aircraft must have reported a position to be
+///  counted as seen, in minutes
+pub const DEFAULT_AIRCRAFT_WINDOW_MINUTES: u32 = 5;
+
+/// Max window in which aircraft are counted as seen, in minutes
+pub const MAX_AIRCRAFT_WINDOW_MINUTES: u32 = 24 * 60;
+
+/// Possible errors getting statistics
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StatisticsError {
+    /// Invalid aircraft window
+    Window,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for StatisticsError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            StatisticsError::Window => write!(f, "Invalid aircraft window provided."),
+            StatisticsError::Client => write!(f, "Could not get backend client."),
+            StatisticsError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Statistics(StatisticsError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Statistics(StatisticsError::Client)
+        })
+}
+
+/// The window in which aircraft are counted as seen
+fn aircraft_window(request: &GetStatisticsRequest) -> Result<Duration, StatisticsError> {
+    let minutes = request
+        .aircraft_window_minutes
+        .unwrap_or(DEFAULT_AIRCRAFT_WINDOW_MINUTES);
+
+    if minutes == 0 || minutes > MAX_AIRCRAFT_WINDOW_MINUTES {
+        postgis_error!(
+            "aircraft window must be between 1 and {} minutes, got {}.",
+            MAX_AIRCRAFT_WINDOW_MINUTES,
+            minutes
+        );
+        return Err(StatisticsError::Window);
+    }
+
+    Duration::try_minutes(minutes as i64).ok_or(StatisticsError::Window)
+}
+
+/// Counts the zones active at `now` for each zone type, from the number of
+///  zones in their time bounds sharing a type and schedule
+fn active_zone_counts(
+    groups: &[(ZoneType, Vec<i32>, i64)],
+    now: DateTime<Utc>,
+) -> Vec<ZoneTypeCount> {
+    let mut counts: HashMap<ZoneType, i64> = HashMap::new();
+    groups
+        .iter()
+        .filter(|(_, schedule, _)| super::zone::schedule_overlaps(schedule, now, now))
+        .for_each(|(zone_type, _, count)| *counts.entry(*zone_type).or_default() += count);
+
+    ZoneType::iter()
+        .map(|zone_type| ZoneTypeCount {
+            zone_type: zone_type.into(),
+            count: to_count(counts.get(&zone_type).copied().unwrap_or_default()),
+        })
+        .collect()
+}
+
+/// A database count as a response count
+fn to_count(count: i64) -> u32 {
+    u32::try_from(count.max(0)).unwrap_or(u32::MAX)
+}
+
+/// Gets the counts and aggregates of the airspace and traffic
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn get_statistics(
+    request: GetStatisticsRequest,
+) -> Result<GetStatisticsResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let window = aircraft_window(&request).map_err(PostgisError::Statistics)?;
+    let now = Utc::now();
+
+    let client = get_client().await?;
+    let zones_stmt = client
+        .prepare_cached(&Statement::CountActiveZones.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Statistics(StatisticsError::DBError)
+        })?;
+
+    let assets_stmt = client
+        .prepare_cached(&Statement::CountAssets.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Statistics(StatisticsError::DBError)
+        })?;
+
+    let groups = client
+        .query(&zones_stmt, &[&now])
+        .await
+        .and_then(|rows| {
+            rows.iter()
+                .map(|row| {
+                    Ok((
+                        row.try_get::<_, ZoneType>("zone_type")?,
+                        row.try_get::<_, Vec<i32>>("schedule")?,
+                        row.try_get::<_, i64>("count")?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        })
+        .map_err(|e| {
+            postgis_error!("could not count active zones: {}", e);
+            PostgisError::Statistics(StatisticsError::DBError)
+        })?;
+
+    let row = client
+        .query_one(&assets_stmt, &[&now, &(now - window)])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not count assets: {}", e);
+            PostgisError::Statistics(StatisticsError::DBError)
+        })?;
+
+    let count = |column: &str| -> Result<u32, PostgisError> {
+        row.try_get::<_, i64>(column).map(to_count).map_err(|e| {
+            postgis_error!("could not get {column}: {}", e);
+            PostgisError::Statistics(StatisticsError::DBError)
+        })
+    };
+
+    let latency = best_path::latency();
+    Ok(GetStatisticsResponse {
+        active_zones: active_zone_counts(&groups, now),
+        flights_in_progress: count("flights_in_progress")?,
+        aircraft_seen: count("aircraft_seen")?,
+        waypoint_count: count("waypoint_count")?,
+        best_path_count: latency.count,
+        best_path_average_latency_ms: latency.average_ms(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::TimeZone;
+
+    #[test]
+    fn ut_aircraft_window() {
+        let request = GetStatisticsRequest::default();
+        assert_eq!(
+            aircraft_window(&request).unwrap(),
+            Duration::try_minutes(DEFAULT_AIRCRAFT_WINDOW_MINUTES as i64).unwrap()
+        );
+
+        let request = GetStatisticsRequest {
+            aircraft_window_minutes: Some(60),
+        };
+        assert_eq!(
+            aircraft_window(&request).unwrap(),
+            Duration::try_hours(1).unwrap()
+        );
+
+        for minutes in [0, MAX_AIRCRAFT_WINDOW_MINUTES + 1] {
+            let request = GetStatisticsRequest {
+                aircraft_window_minutes: Some(minutes),
+            };
+            assert_eq!(aircraft_window(&request), Err(StatisticsError::Window));
+        }
+    }
+
+    #[test]
+    fn ut_active_zone_counts() {
+        // A Monday, 10:00
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+
+        // Zones active on Mondays from 09:00 to 11:00, and from 12:00 to 13:00
+        let morning = vec![9 * 60, 11 * 60];
+        let noon = vec![12 * 60, 13 * 60];
+        let groups = vec![
+            (ZoneType::Restriction, vec![], 3),
+            (ZoneType::Restriction, morning, 2),
+            (ZoneType::Restriction, noon, 4),
+        ];
+
+        let counts = active_zone_counts(&groups, now);
+        assert_eq!(
+            counts,
+            vec![
+                ZoneTypeCount {
+                    zone_type: ZoneType::Port as i32,
+                    count: 0
+                },
+                ZoneTypeCount {
+                    zone_type: ZoneType::Restriction as i32,
+                    count: 5
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn ut_to_count() {
+        assert_eq!(to_count(-1), 0);
+        assert_eq!(to_count(12), 12);
+        assert_eq!(to_count(i64::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn test_statistics_error_display() {
+        assert_eq!(
+            StatisticsError::Window.to_string(),
+            "Invalid aircraft window provided."
+        );
+        assert_eq!(
+            StatisticsError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(StatisticsError::DBError.to_string(), "Database error.");
+    }
+}
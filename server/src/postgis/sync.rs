@@ -0,0 +1,445 @@
+//! Incremental sync of zones and waypoints.
+//!
+//! Every insert or update of a zone or waypoint takes the next number of a
+//!  shared change sequence, and every removal leaves a tombstone numbered
+//!  from the same sequence. A client keeps the sequence number of the last
+//!  change it applied as a cursor and only asks for the changes after it.
+//!
+//! The triggers recording changes also take a transaction-level advisory
+//!  lock, so that writes to the airspace tables commit in sequence order. A
+//!  change can't become visible after a client has already synced past it.
+
+use super::leader::LOCK_NAMESPACE;
+use super::statements::Statement;
+use super::utils::MAX_PAGE_SIZE;
+use super::{OnceCell, PostgisError};
+use crate::grpc::server::grpc_server::{
+    AirspaceChange, AirspaceEntity, Coordinates, GeometryEncoding, SyncAirspaceRequest,
+    SyncAirspaceResponse, Waypoint,
+};
+use deadpool_postgres::Object;
+use std::fmt::{self, Display, Formatter};
+use tokio_postgres::Row;
+
+/// Key of the advisory lock serializing writes to the airspace tables
+const WRITE_LOCK_KEY: i64 = LOCK_NAMESPACE | 0x100;
+
+/// Possible errors with airspace sync requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SyncError {
+    /// Invalid cursor
+    Cursor,
+
+    /// Invalid limit
+    Limit,
+
+    /// Invalid geometry encoding
+    Encoding,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for SyncError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SyncError::Cursor => write!(f, "Invalid cursor provided."),
+            SyncError::Limit => write!(f, "Invalid limit provided."),
+            SyncError::Encoding => write!(f, "Invalid geometry encoding provided."),
+            SyncError::Client => write!(f, "Could not get backend client."),
+            SyncError::DBError => write!(f, "Database error."),
+        }
+    }
+}
+
+/// Gets the name of the change sequence shared by zones and waypoints
+/// pub(super) so that best path audits can record the current cursor
+pub(super) fn get_sequence_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."airspace_change_seq""#, super::get_schema()))
+}
+
+/// Gets the name of the table of removed zones and waypoints
+/// pub(super) so that tombstones can be listed by the statements module
+pub(super) fn get_tombstones_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."airspace_tombstones""#, super::get_schema()))
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Sync(SyncError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Sync(SyncError::Client)
+        })
+}
+
+/// Statements numbering the changes of an airspace table
+fn track_changes_statements(
+    table_name: &str,
+    trigger_prefix: &str,
+    entity: AirspaceEntity,
+) -> Vec<String> {
+    let schema = super::get_schema();
+    let sequence = get_sequence_name();
+    let entity = entity.as_str_name();
+
+    vec![
+        format!(
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "change_seq" BIGINT NOT NULL DEFAULT nextval('{sequence}');"#
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "{trigger_prefix}_change_seq_idx" ON {table_name} ("change_seq");"#
+        ),
+        format!(r#"DROP TRIGGER IF EXISTS "{trigger_prefix}_airspace_upsert" ON {table_name};"#),
+        format!(
+            r#"CREATE TRIGGER "{trigger_prefix}_airspace_upsert"
+            BEFORE INSERT OR UPDATE ON {table_name}
+            FOR EACH ROW EXECUTE FUNCTION "{schema}"."airspace_upsert"('{entity}');"#
+        ),
+        format!(r#"DROP TRIGGER IF EXISTS "{trigger_prefix}_airspace_delete" ON {table_name};"#),
+        format!(
+            r#"CREATE TRIGGER "{trigger_prefix}_airspace_delete"
+            AFTER DELETE ON {table_name}
+            FOR EACH ROW EXECUTE FUNCTION "{schema}"."airspace_delete"('{entity}');"#
+        ),
+    ]
+}
+
+/// Initialize the change sequence, tombstones table and triggers
+///  Must run after the zones and waypoints tables are created.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn psql_init() -> Result<(), PostgisError> {
+    let schema = super::get_schema();
+    let sequence = get_sequence_name();
+    let tombstones = get_tombstones_table_name();
+
+    let mut statements = vec![
+        format!(r#"CREATE SEQUENCE IF NOT EXISTS {sequence};"#),
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {tombstones} (
+            "entity" VARCHAR(32) NOT NULL,
+            "identifier" VARCHAR(255) NOT NULL,
+            "change_seq" BIGINT NOT NULL,
+            "deleted_at" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            PRIMARY KEY ("entity", "identifier")
+        );"#
+        ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "airspace_tombstones_change_seq_idx" ON {tombstones} ("change_seq");"#
+        ),
+        // A recreated object no longer has a tombstone
+        format!(
+            r#"CREATE OR REPLACE FUNCTION "{schema}"."airspace_upsert"() RETURNS TRIGGER AS $$
+            BEGIN
+                PERFORM pg_advisory_xact_lock({WRITE_LOCK_KEY});
+                NEW."change_seq" := nextval('{sequence}');
+                DELETE FROM {tombstones}
+                WHERE "entity" = TG_ARGV[0] AND "identifier" = NEW."identifier";
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql;"#
+        ),
+        format!(
+            r#"CREATE OR REPLACE FUNCTION "{schema}"."airspace_delete"() RETURNS TRIGGER AS $$
+            BEGIN
+                PERFORM pg_advisory_xact_lock({WRITE_LOCK_KEY});
+                INSERT INTO {tombstones} ("entity", "identifier", "change_seq")
+                VALUES (TG_ARGV[0], OLD."identifier", nextval('{sequence}'))
+                ON CONFLICT ("entity", "identifier") DO UPDATE
+                    SET "change_seq" = EXCLUDED."change_seq", "deleted_at" = NOW();
+                RETURN OLD;
+            END;
+            $$ LANGUAGE plpgsql;"#
+        ),
+    ];
+
+    statements.extend(track_changes_statements(
+        super::zone::get_table_name(),
+        "zones",
+        AirspaceEntity::Zone,
+    ));
+
+    statements.extend(track_changes_statements(
+        super::waypoint::get_table_name(),
+        "waypoints",
+        AirspaceEntity::Waypoint,
+    ));
+
+    super::psql_transaction(statements).await
+}
+
+/// Max number of changes returned for a requested limit
+fn sync_limit(limit: Option<u32>) -> Result<u32, SyncError> {
+    match limit {
+        None => Ok(MAX_PAGE_SIZE),
+        Some(0) => Err(SyncError::Limit),
+        Some(limit) => Ok(limit.min(MAX_PAGE_SIZE)),
+    }
+}
+
+/// Merges the changes of each table into one page in sequence order.
+///  Each list holds the first changes of its table after the cursor, so
+///  the first `limit` changes of the merged lists are the first overall.
+fn page_changes(mut changes: Vec<AirspaceChange>, cursor: u64, limit: u32) -> SyncAirspaceResponse {
+    changes.sort_by_key(|change| change.sequence);
+
+    let has_more = changes.len() > limit as usize;
+    changes.truncate(limit as usize);
+
+    SyncAirspaceResponse {
+        cursor: changes.last().map_or(cursor, |change| change.sequence),
+        changes,
+        has_more,
+    }
+}
+
+/// Reads the change sequence number of a row
+fn row_sequence(row: &Row) -> Result<u64, tokio_postgres::Error> {
+    // The sequence starts at 1
+    let sequence: i64 = row.try_get("change_seq")?;
+    Ok(sequence.max(0) as u64)
+}
+
+/// Converts a row of the changed zones
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_zone_change(
+    row: Row,
+    encoding: GeometryEncoding,
+) -> Result<AirspaceChange, tokio_postgres::Error> {
+    let sequence = row_sequence(&row)?;
+    let zone = super::zone::process_zone_row(row)?;
+    let zone = match encoding {
+        GeometryEncoding::Coordinates => zone,
+        GeometryEncoding::Compact => super::zone::compact_zone(zone),
+    };
+
+    Ok(AirspaceChange {
+        sequence,
+        entity: AirspaceEntity::Zone as i32,
+        identifier: zone.identifier.clone(),
+        deleted: false,
+        zone: Some(zone),
+        waypoint: None,
+    })
+}
+
+/// Converts a row of the changed waypoints
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_waypoint_change(row: Row) -> Result<AirspaceChange, tokio_postgres::Error> {
+    let geom: postgis::ewkb::Point = row.try_get("geog")?;
+    let identifier: String = row.try_get("identifier")?;
+
+    Ok(AirspaceChange {
+        sequence: row_sequence(&row)?,
+        entity: AirspaceEntity::Waypoint as i32,
+        identifier: identifier.clone(),
+        deleted: false,
+        zone: None,
+        waypoint: Some(Waypoint {
+            identifier,
+            location: Some(Coordinates {
+                latitude: geom.y,
+                longitude: geom.x,
+            }),
+        }),
+    })
+}
+
+/// Converts a row of the tombstones table
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_tombstone(row: Row) -> Result<AirspaceChange, PostgisError> {
+    let parse = |e: tokio_postgres::Error| {
+        postgis_error!("could not parse tombstone row: {}", e);
+        PostgisError::Sync(SyncError::DBError)
+    };
+
+    let entity: String = row.try_get("entity").map_err(parse)?;
+    let entity = AirspaceEntity::from_str_name(&entity).ok_or_else(|| {
+        postgis_error!("unknown tombstone entity: {}", entity);
+        PostgisError::Sync(SyncError::DBError)
+    })?;
+
+    Ok(AirspaceChange {
+        sequence: row_sequence(&row).map_err(parse)?,
+        entity: entity as i32,
+        identifier: row.try_get("identifier").map_err(parse)?,
+        deleted: true,
+        zone: None,
+        waypoint: None,
+    })
+}
+
+/// Gets the zone and waypoint changes after a cursor, oldest first
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn sync_airspace(
+    request: SyncAirspaceRequest,
+) -> Result<SyncAirspaceResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let cursor = i64::try_from(request.cursor).map_err(|_| {
+        postgis_error!("invalid cursor: {}", request.cursor);
+        PostgisError::Sync(SyncError::Cursor)
+    })?;
+
+    let limit = sync_limit(request.limit).map_err(|e| {
+        postgis_error!("invalid limit: {:?}", request.limit);
+        PostgisError::Sync(e)
+    })?;
+
+    let encoding = GeometryEncoding::try_from(request.encoding).map_err(|e| {
+        postgis_error!("invalid geometry encoding: {}", e);
+        PostgisError::Sync(SyncError::Encoding)
+    })?;
+
+    let client = get_client().await?;
+    let query = |statement: Statement| {
+        let client = &client;
+        async move {
+            let stmt = client.prepare_cached(&statement.sql()).await?;
+
+            // One more than the limit, to know if more changes follow
+            client.query(&stmt, &[&cursor, &(limit as i64 + 1)]).await
+        }
+    };
+
+    let db_error = |e: tokio_postgres::Error| {
+        postgis_error!("could not get airspace changes: {}", e);
+        PostgisError::Sync(SyncError::DBError)
+    };
+
+    let mut changes = query(Statement::GetZoneChanges)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|row| process_zone_change(row, encoding))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(db_error)?;
+
+    for row in query(Statement::GetWaypointChanges)
+        .await
+        .map_err(db_error)?
+    {
+        changes.push(process_waypoint_change(row).map_err(db_error)?);
+    }
+
+    for row in query(Statement::GetAirspaceTombstones)
+        .await
+        .map_err(db_error)?
+    {
+        changes.push(process_tombstone(row)?);
+    }
+
+    let response = page_changes(changes, request.cursor, limit);
+    postgis_debug!(
+        "found {} airspace changes after {}.",
+        response.changes.len(),
+        request.cursor
+    );
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(sequence: u64, entity: AirspaceEntity) -> AirspaceChange {
+        AirspaceChange {
+            sequence,
+            entity: entity as i32,
+            identifier: format!("{}-{sequence}", entity.as_str_name()),
+            deleted: false,
+            zone: None,
+            waypoint: None,
+        }
+    }
+
+    #[test]
+    fn ut_sync_limit() {
+        assert_eq!(sync_limit(None), Ok(MAX_PAGE_SIZE));
+        assert_eq!(sync_limit(Some(10)), Ok(10));
+        assert_eq!(sync_limit(Some(MAX_PAGE_SIZE + 1)), Ok(MAX_PAGE_SIZE));
+        assert_eq!(sync_limit(Some(0)), Err(SyncError::Limit));
+    }
+
+    #[test]
+    fn ut_page_changes() {
+        let changes = vec![
+            change(5, AirspaceEntity::Zone),
+            change(2, AirspaceEntity::Zone),
+            change(3, AirspaceEntity::Waypoint),
+            change(4, AirspaceEntity::Waypoint),
+        ];
+
+        let response = page_changes(changes.clone(), 1, 3);
+        let sequences: Vec<u64> = response.changes.iter().map(|c| c.sequence).collect();
+        assert_eq!(sequences, vec![2, 3, 4]);
+        assert_eq!(response.cursor, 4);
+        assert!(response.has_more);
+
+        let response = page_changes(changes, 1, 4);
+        assert_eq!(response.changes.len(), 4);
+        assert_eq!(response.cursor, 5);
+        assert!(!response.has_more);
+    }
+
+    #[test]
+    fn ut_page_changes_empty() {
+        // The cursor is kept when nothing changed
+        let response = page_changes(vec![], 42, 10);
+        assert!(response.changes.is_empty());
+        assert_eq!(response.cursor, 42);
+        assert!(!response.has_more);
+    }
+
+    #[test]
+    fn ut_track_changes_statements() {
+        let statements = track_changes_statements(
+            super::super::zone::get_table_name(),
+            "zones",
+            AirspaceEntity::Zone,
+        );
+
+        assert!(statements
+            .iter()
+            .any(|s| s.contains(r#""airspace_upsert"('AIRSPACE_ENTITY_ZONE')"#)));
+        assert!(statements
+            .iter()
+            .any(|s| s.contains(r#""airspace_delete"('AIRSPACE_ENTITY_ZONE')"#)));
+        assert!(statements[0].contains(get_sequence_name()));
+    }
+
+    #[test]
+    fn test_sync_error_display() {
+        assert_eq!(SyncError::Cursor.to_string(), "Invalid cursor provided.");
+        assert_eq!(SyncError::Limit.to_string(), "Invalid limit provided.");
+        assert_eq!(
+            SyncError::Encoding.to_string(),
+            "Invalid geometry encoding provided."
+        );
+        assert_eq!(
+            SyncError::Client.to_string(),
+            "Could not get backend client."
+        );
+        assert_eq!(SyncError::DBError.to_string(), "Database error.");
+    }
+}
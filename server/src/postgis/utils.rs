@@ -1,16 +1,21 @@
 //! Common functions for PostGIS operations
 
+use super::statements::Statement;
 use super::DEFAULT_SRID;
 use super::{PostgisError, PsqlError};
-use crate::grpc::server::grpc_server::{Coordinates, PointZ as GrpcPointZ};
-use crate::types::Position;
+use crate::grpc::server::grpc_server::{
+    CompactRing, Coordinates, FieldViolation, ItemValidation, OrderBy, Page, PointZ as GrpcPointZ,
+};
+use crate::types::{Degrees, Meters, Position};
 use deadpool_postgres::tokio_postgres::{types::ToSql, Row};
-use geo::algorithm::haversine_distance::HaversineDistance;
+use geo::algorithm::geodesic_distance::GeodesicDistance;
 use geo::point;
 use lib_common::time::{DateTime, Duration, Utc};
+use once_cell::sync::OnceCell;
 use postgis::ewkb::{LineStringT, LineStringZ, Point, PointZ, PolygonZ};
 use regex;
 use std::fmt::{self, Display, Formatter};
+use tonic::async_trait;
 
 /// A polygon must have at least three vertices (a triangle)
 /// A closed polygon has the first and last vertex equal
@@ -43,6 +48,52 @@ impl Display for PolygonError {
     }
 }
 
+/// A vertex at fault in a geometry conversion
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InvalidVertex {
+    /// Index of the vertex in the vertices provided
+    pub index: usize,
+
+    /// The vertex as provided
+    pub coordinates: Coordinates,
+}
+
+/// Errors converting vertices to a PostGIS geometry, with the vertex at
+///  fault when a single one is
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GeometryError {
+    /// Why the vertices were rejected
+    pub reason: PolygonError,
+
+    /// The first vertex at fault, `None` if the vertices are rejected as a whole
+    pub vertex: Option<InvalidVertex>,
+}
+
+impl From<PolygonError> for GeometryError {
+    fn from(reason: PolygonError) -> Self {
+        GeometryError {
+            reason,
+            vertex: None,
+        }
+    }
+}
+
+impl Display for GeometryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.vertex {
+            Some(vertex) => write!(
+                f,
+                "{} Vertex {}: ({}, {}).",
+                self.reason,
+                vertex.index,
+                vertex.coordinates.latitude,
+                vertex.coordinates.longitude
+            ),
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
 /// Errors converting a vertex to a PostGIS point
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PointError {
@@ -81,6 +132,205 @@ impl Display for StringError {
     }
 }
 
+/// Default decimal places kept in longitudes and latitudes, about a centimeter
+pub const DEFAULT_DEGREE_DECIMALS: u32 = 7;
+
+/// Default decimal places kept in altitudes in meters, a centimeter
+pub const DEFAULT_ALTITUDE_DECIMALS: u32 = 2;
+
+/// Max decimal places that can be kept, beyond the precision of an f64
+///  holding a longitude
+pub const MAX_DECIMALS: u32 = 12;
+
+/// Rounding of coordinates, set once at startup
+static PRECISION: OnceCell<Precision> = OnceCell::new();
+
+/// Errors configuring the coordinate precision
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PrecisionError {
+    /// More decimal places than [`MAX_DECIMALS`]
+    Decimals,
+
+    /// The precision was already set
+    AlreadySet,
+}
+
+impl Display for PrecisionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PrecisionError::Decimals => write!(f, "Invalid number of decimal places."),
+            PrecisionError::AlreadySet => write!(f, "Coordinate precision already set."),
+        }
+    }
+}
+
+impl std::error::Error for PrecisionError {}
+
+/// Rounding applied to coordinates as they are converted to geometries,
+///  so that stored geometries and comparisons don't depend on the excess
+///  precision of the reports
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Precision {
+    /// Decimal places kept in longitudes and latitudes
+    pub degree_decimals: u32,
+
+    /// Decimal places kept in altitudes, in meters
+    pub altitude_decimals: u32,
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Precision {
+            degree_decimals: DEFAULT_DEGREE_DECIMALS,
+            altitude_decimals: DEFAULT_ALTITUDE_DECIMALS,
+        }
+    }
+}
+
+impl Precision {
+    /// Rounds a longitude or latitude
+    pub fn degrees(&self, value: f64) -> f64 {
+        round_decimals(value, self.degree_decimals)
+    }
+
+    /// Rounds an altitude in meters
+    pub fn meters(&self, value: f64) -> f64 {
+        round_decimals(value, self.altitude_decimals)
+    }
+
+    /// Rounds the coordinates of a point
+    pub fn pointz(&self, point: PointZ) -> PointZ {
+        PointZ {
+            x: self.degrees(point.x),
+            y: self.degrees(point.y),
+            z: self.meters(point.z),
+            srid: point.srid,
+        }
+    }
+}
+
+/// Rounds a value to a number of decimal places
+fn round_decimals(value: f64, decimals: u32) -> f64 {
+    let scale = 10_f64.powi(decimals as i32);
+    (value * scale).round() / scale
+}
+
+/// Sets the coordinate precision, once at startup
+pub fn set_precision(precision: Precision) -> Result<(), PrecisionError> {
+    if precision.degree_decimals > MAX_DECIMALS || precision.altitude_decimals > MAX_DECIMALS {
+        postgis_error!(
+            "decimal places must be at most {MAX_DECIMALS}: {:?}",
+            precision
+        );
+        return Err(PrecisionError::Decimals);
+    }
+
+    PRECISION.set(precision).map_err(|_| {
+        postgis_error!("coordinate precision was already set.");
+        PrecisionError::AlreadySet
+    })
+}
+
+/// Gets the coordinate precision, the default if not set
+pub fn get_precision() -> Precision {
+    PRECISION.get().copied().unwrap_or_default()
+}
+
+/// Number of results returned by a paginated query if no limit is provided
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Max number of results returned by a paginated query
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Errors with the pagination options of a request
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PageError {
+    /// Invalid limit
+    Limit,
+
+    /// Invalid or unsupported order field
+    OrderBy,
+}
+
+impl Display for PageError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PageError::Limit => write!(f, "Invalid page limit."),
+            PageError::OrderBy => write!(f, "Invalid order field."),
+        }
+    }
+}
+
+/// Validated pagination options
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Pagination {
+    /// Max number of results to return
+    pub limit: u32,
+
+    /// Number of results to skip
+    pub offset: u32,
+
+    /// Field to order the results by
+    pub order_by: OrderBy,
+
+    /// Sort the results in descending order
+    pub descending: bool,
+}
+
+impl TryFrom<Option<Page>> for Pagination {
+    type Error = PageError;
+
+    fn try_from(page: Option<Page>) -> Result<Self, Self::Error> {
+        let page = page.unwrap_or_default();
+        let limit = match page.limit {
+            None => DEFAULT_PAGE_SIZE,
+            Some(0) => return Err(PageError::Limit),
+            Some(limit) => limit.min(MAX_PAGE_SIZE),
+        };
+
+        let order_by = OrderBy::try_from(page.order_by).map_err(|_| PageError::OrderBy)?;
+
+        Ok(Pagination {
+            limit,
+            offset: page.offset,
+            order_by,
+            descending: page.descending,
+        })
+    }
+}
+
+impl Pagination {
+    /// The offset of the next page, if the current page was full
+    pub fn next_offset(&self, count: usize) -> Option<u32> {
+        if count < self.limit as usize {
+            return None;
+        }
+
+        self.offset.checked_add(self.limit)
+    }
+}
+
+/// Integer units per degree in a compact ring, about a centimeter apart
+pub const COMPACT_RING_SCALE: f64 = 1e7;
+
+/// Encodes vertices as a compact ring of longitude and latitude deltas
+pub fn compact_ring(vertices: &[Coordinates]) -> CompactRing {
+    let mut previous = (0_i64, 0_i64);
+    let mut deltas = Vec::with_capacity(vertices.len() * 2);
+    for vertex in vertices {
+        let current = (
+            (vertex.longitude * COMPACT_RING_SCALE).round() as i64,
+            (vertex.latitude * COMPACT_RING_SCALE).round() as i64,
+        );
+
+        deltas.push(current.0 - previous.0);
+        deltas.push(current.1 - previous.1);
+        previous = current;
+    }
+
+    CompactRing { deltas }
+}
+
 /// Check if a provided string argument is valid
 pub fn check_string(string: &str, regex: &str) -> Result<(), StringError> {
     let re = regex::Regex::new(regex).map_err(|_| StringError::Regex)?;
@@ -97,11 +347,16 @@ pub fn check_string(string: &str, regex: &str) -> Result<(), StringError> {
 }
 
 /// Approximate the distance between these two points
+///
+/// The surface distance is the geodesic on the WGS84 ellipsoid (Karney's
+///  algorithm), which is what PostGIS computes for `geography` types. Using
+///  a spherical approximation here would rank paths differently from the
+///  PostGIS clearance checks.
 pub fn distance_meters(a: &PointZ, b: &PointZ) -> f32 {
     let p1 = point!(x: a.x, y: a.y);
     let p2 = point!(x: b.x, y: b.y);
 
-    let distance_meters = p1.haversine_distance(&p2);
+    let distance_meters = p1.geodesic_distance(&p2);
 
     // the Z coordinate is already in meters
     (distance_meters.powf(2.) + (a.z - b.z).powf(2.)).sqrt() as f32
@@ -118,34 +373,44 @@ pub fn validate_pointz(point: &PointZ) -> Result<(), PolygonError> {
 
 impl From<Position> for PointZ {
     fn from(position: Position) -> Self {
-        PointZ::new(
-            position.longitude,
-            position.latitude,
-            position.altitude_meters,
+        get_precision().pointz(PointZ::new(
+            position.longitude.0,
+            position.latitude.0,
+            position.altitude_meters.0,
             Some(DEFAULT_SRID),
-        )
+        ))
+    }
+}
+
+impl From<PointZ> for Position {
+    fn from(point: PointZ) -> Self {
+        Position {
+            longitude: Degrees(point.x),
+            latitude: Degrees(point.y),
+            altitude_meters: Meters(point.z),
+        }
     }
 }
 
 impl From<GrpcPointZ> for PointZ {
     fn from(position: GrpcPointZ) -> Self {
-        PointZ::new(
+        get_precision().pointz(PointZ::new(
             position.longitude,
             position.latitude,
             position.altitude_meters as f64,
             Some(DEFAULT_SRID),
-        )
+        ))
     }
 }
 
 impl From<Coordinates> for PointZ {
     fn from(position: Coordinates) -> Self {
-        PointZ::new(
+        get_precision().pointz(PointZ::new(
             position.longitude,
             position.latitude,
             0.0,
             Some(DEFAULT_SRID),
-        )
+        ))
     }
 }
 
@@ -156,32 +421,48 @@ impl From<Coordinates> for PointZ {
 pub fn polygon_from_vertices_z(
     vertices: &[Coordinates],
     altitude_meters: f32,
-) -> Result<PolygonZ, PolygonError> {
+) -> Result<PolygonZ, GeometryError> {
     let size = vertices.len();
 
     // Check that the zone has at least N vertices
     if size < MIN_NUM_POLYGON_VERTICES {
-        return Err(PolygonError::VertexCount);
+        return Err(PolygonError::VertexCount.into());
     }
 
-    // Must be a closed polygon
-    if vertices.first() != vertices.last() {
-        return Err(PolygonError::OpenPolygon);
+    // Must be a closed polygon, at the stored precision
+    let first = vertices.first().map(|v| PointZ::from(*v));
+    let last = vertices.last().map(|v| PointZ::from(*v));
+    if first != last {
+        return Err(GeometryError {
+            reason: PolygonError::OpenPolygon,
+            vertex: Some(InvalidVertex {
+                index: size - 1,
+                coordinates: vertices[size - 1],
+            }),
+        });
     }
 
+    let altitude_meters = get_precision().meters(altitude_meters as f64);
+
     // Each coordinate must fit within the valid range of latitude and longitude
-    if vertices.iter().any(|&pt| {
+    if let Some(index) = vertices.iter().position(|&pt| {
         validate_pointz(
             &(PointZ {
                 x: pt.longitude,
                 y: pt.latitude,
-                z: altitude_meters as f64,
+                z: altitude_meters,
                 srid: Some(DEFAULT_SRID),
             }),
         )
         .is_err()
     }) {
-        return Err(PolygonError::OutOfBounds);
+        return Err(GeometryError {
+            reason: PolygonError::OutOfBounds,
+            vertex: Some(InvalidVertex {
+                index,
+                coordinates: vertices[index],
+            }),
+        });
     }
 
     Ok(PolygonZ {
@@ -189,7 +470,7 @@ pub fn polygon_from_vertices_z(
             points: vertices
                 .iter()
                 .map(|vertex| PointZ {
-                    z: altitude_meters as f64,
+                    z: altitude_meters,
                     ..(*vertex).into()
                 })
                 .collect(),
@@ -199,6 +480,37 @@ pub fn polygon_from_vertices_z(
     })
 }
 
+/// A rejected field of a request, naming the entity it belongs to
+pub fn field_violation(
+    field: String,
+    entity: &str,
+    identifier: &str,
+    reason: impl Display,
+) -> FieldViolation {
+    FieldViolation {
+        field,
+        description: format!("{entity} '{identifier}': {reason}"),
+    }
+}
+
+/// The outcome of checking an item of a validate-only update
+pub fn item_validation(identifier: &str, violation: Option<FieldViolation>) -> ItemValidation {
+    ItemValidation {
+        identifier: identifier.to_string(),
+        valid: violation.is_none(),
+        violation,
+    }
+}
+
+/// Path of the field a geometry conversion failed at, the vertex at fault
+///  if there is one
+pub fn vertices_field(field: &str, error: &GeometryError) -> String {
+    match error.vertex {
+        Some(vertex) => format!("{field}[{}]", vertex.index),
+        None => field.to_string(),
+    }
+}
+
 /// Generate a PostGis 'Point' from a vertex
 /// Each vertex must be within the valid range of latitude and longitude
 pub fn point_from_vertex(vertex: &Coordinates) -> Result<Point, PointError> {
@@ -212,9 +524,10 @@ pub fn point_from_vertex(vertex: &Coordinates) -> Result<Point, PointError> {
         return Err(PointError::OutOfBounds);
     }
 
+    let precision = get_precision();
     Ok(Point {
-        x: vertex.longitude,
-        y: vertex.latitude,
+        x: precision.degrees(vertex.longitude),
+        y: precision.degrees(vertex.latitude),
         srid: Some(DEFAULT_SRID),
     })
 }
@@ -232,19 +545,43 @@ pub struct Segment {
     pub time_end: DateTime<Utc>,
 }
 
-#[derive(Debug)]
-struct ExpectedResult {
-    // The index of the segment
-    idx: i64,
+/// Shortest time window a path is divided over, in milliseconds. Shorter
+///  windows (including empty ones) are stretched to it.
+pub const MIN_SEGMENT_WINDOW_MS: i64 = 1;
+
+/// Errors dividing a path into timed segments
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SegmentError {
+    /// The time window ends before it starts
+    TimeWindow,
+
+    /// A segment time could not be represented
+    Duration,
+}
+
+impl Display for SegmentError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SegmentError::TimeWindow => write!(f, "The time window ends before it starts."),
+            SegmentError::Duration => write!(f, "Invalid segment duration."),
+        }
+    }
+}
+
+/// A piece of a path no longer than the requested length
+#[derive(Debug, Clone)]
+pub struct PathPiece {
+    /// The position of the piece along the path
+    pub idx: i64,
 
-    // The geometry of the segment
-    geom: LineStringZ,
+    /// The geometry of the piece
+    pub geom: LineStringZ,
 
-    // The distance of the segment in meters
-    distance_m: f64,
+    /// The length of the piece in meters
+    pub distance_m: f64,
 }
 
-impl TryFrom<Row> for ExpectedResult {
+impl TryFrom<Row> for PathPiece {
     type Error = PostgisError;
 
     #[cfg(not(tarpaulin_include))]
@@ -254,7 +591,7 @@ impl TryFrom<Row> for ExpectedResult {
         let geom: LineStringZ = row.get("geom");
         let distance_m: f64 = row.get("distance_m");
 
-        Ok(ExpectedResult {
+        Ok(PathPiece {
             idx,
             geom,
             distance_m,
@@ -262,112 +599,198 @@ impl TryFrom<Row> for ExpectedResult {
     }
 }
 
-/// Subdivides a path into time segments by length and time start/end
-#[cfg(not(tarpaulin_include))]
-// no_coverage: (Rnever) need running postgresql instance, not unit testable
-pub async fn segmentize(
-    geom: &LineStringT<PointZ>,
-    timestamp_start: DateTime<Utc>,
-    timestamp_end: DateTime<Utc>,
-    max_segment_len_meters: f32,
-) -> Result<Vec<Segment>, PostgisError> {
-    let stmt = r#"WITH "segments" AS (
-        SELECT
-            "geom",
-            ST_3DLength(ST_Transform("geom", 4978)) AS "distance_m"
-        FROM ST_DumpSegments(
-            (
-                SELECT ST_Segmentize(
-                    $1::geography,
-                    $2::FLOAT
-                )::geometry
-            )
-        )
-    ) SELECT 
-            ROW_NUMBER() OVER () AS "idx",
-            "segments"."geom" AS "geom",
-            "segments"."distance_m" AS "distance_m"
-        FROM "segments";
-    "#
-    .to_string();
-
-    let client = crate::postgis::DEADPOOL_POSTGIS
-        .get()
-        .ok_or_else(|| {
-            postgis_error!("could not get psql pool.");
-            PostgisError::Psql(PsqlError::Client)
-        })?
-        .get()
-        .await
-        .map_err(|e| {
-            postgis_error!("could not get client from psql connection pool: {}", e);
-            PostgisError::Psql(PsqlError::Client)
-        })?;
+/// Splits paths into pieces of a max length
+#[async_trait]
+pub trait PathSplitter {
+    /// The pieces of the path, in any order
+    async fn split(
+        &self,
+        geom: &LineStringT<PointZ>,
+        max_segment_len_meters: f32,
+    ) -> Result<Vec<PathPiece>, PostgisError>;
+}
 
-    let mut results = client
-        .query(&stmt, &[&geom, &(max_segment_len_meters as f64)])
-        .await
-        .map_err(|e| {
-            postgis_error!("could not execute query: {}", e);
+/// Splits paths with PostGIS
+#[derive(Debug, Copy, Clone)]
+pub struct PostgisSplitter;
 
-            PostgisError::Psql(PsqlError::Execute)
-        })?
-        .into_iter()
-        .map(ExpectedResult::try_from)
-        .collect::<Result<Vec<ExpectedResult>, PostgisError>>()?;
+#[async_trait]
+impl PathSplitter for PostgisSplitter {
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (Rnever) need running postgresql instance, not unit testable
+    async fn split(
+        &self,
+        geom: &LineStringT<PointZ>,
+        max_segment_len_meters: f32,
+    ) -> Result<Vec<PathPiece>, PostgisError> {
+        let client = crate::postgis::DEADPOOL_POSTGIS
+            .get()
+            .ok_or_else(|| {
+                postgis_error!("could not get psql pool.");
+                PostgisError::Psql(PsqlError::Client)
+            })?
+            .get()
+            .await
+            .map_err(|e| {
+                postgis_error!("could not get client from psql connection pool: {}", e);
+                PostgisError::Psql(PsqlError::Client)
+            })?;
+
+        let stmt = client
+            .prepare_cached(&Statement::Segmentize.sql())
+            .await
+            .map_err(|e| {
+                postgis_error!("could not prepare cached statement: {}", e);
+                PostgisError::Psql(PsqlError::Prepare)
+            })?;
+
+        client
+            .query(&stmt, &[&geom, &(max_segment_len_meters as f64)])
+            .await
+            .map_err(|e| {
+                postgis_error!("could not execute query: {}", e);
+
+                PostgisError::Psql(PsqlError::Execute)
+            })?
+            .into_iter()
+            .map(PathPiece::try_from)
+            .collect()
+    }
+}
 
-    results.sort_by(|a, b| a.idx.cmp(&b.idx));
+/// The length of the time window a path is divided over in milliseconds,
+///  at least [`MIN_SEGMENT_WINDOW_MS`]
+fn segment_window_ms(
+    timestamp_start: DateTime<Utc>,
+    timestamp_end: DateTime<Utc>,
+) -> Result<i64, SegmentError> {
+    if timestamp_end < timestamp_start {
+        postgis_error!("time window ends ({timestamp_end}) before it starts ({timestamp_start}).");
+        return Err(SegmentError::TimeWindow);
+    }
 
-    let mut cursor = timestamp_start;
-    let duration = timestamp_end - timestamp_start;
-    let velocity_m_s: f64 =
-        results.iter().map(|r| r.distance_m).sum::<f64>() / duration.num_seconds() as f64;
+    Ok((timestamp_end - timestamp_start)
+        .num_milliseconds()
+        .max(MIN_SEGMENT_WINDOW_MS))
+}
 
-    // TODO(R5): Checks for unreasonable speeds?
+/// Spreads a time window over the pieces of a path in proportion to their
+///  length, or evenly if the path has no length. The last segment ends
+///  with the window.
+fn timed_segments(
+    mut pieces: Vec<PathPiece>,
+    timestamp_start: DateTime<Utc>,
+    window_ms: i64,
+) -> Result<Vec<Segment>, SegmentError> {
+    pieces.sort_by(|a, b| a.idx.cmp(&b.idx));
+    let count = pieces.len();
+    let total_m: f64 = pieces.iter().map(|p| p.distance_m.max(0.0)).sum();
 
-    let results = results
+    let mut traversed_m = 0.0;
+    let mut cursor = timestamp_start;
+    pieces
         .into_iter()
-        .map(|r| {
-            let segment_duration_ms = (r.distance_m / velocity_m_s) * 1000.;
+        .enumerate()
+        .map(|(i, piece)| {
+            traversed_m += piece.distance_m.max(0.0);
+            let fraction = if total_m > 0.0 {
+                traversed_m / total_m
+            } else {
+                (i + 1) as f64 / count as f64
+            };
 
-            let time_delta =
-                Duration::try_milliseconds(segment_duration_ms as i64).ok_or_else(|| {
-                    postgis_error!(
-                        "could not create time delta from segment duration: {}",
-                        segment_duration_ms
-                    );
+            let offset_ms = if i + 1 == count {
+                window_ms
+            } else {
+                (window_ms as f64 * fraction) as i64
+            };
 
-                    PostgisError::Psql(PsqlError::Execute)
+            let time_end = Duration::try_milliseconds(offset_ms)
+                .and_then(|offset| timestamp_start.checked_add_signed(offset))
+                .ok_or_else(|| {
+                    postgis_error!("could not offset segment end by {offset_ms} ms.");
+                    SegmentError::Duration
                 })?;
 
             let segment = Segment {
-                geom: r.geom,
+                geom: piece.geom,
                 time_start: cursor,
-                time_end: cursor + time_delta,
+                time_end,
             };
 
-            cursor = segment.time_end;
-
+            cursor = time_end;
             Ok(segment)
         })
-        .collect::<Result<Vec<Segment>, PostgisError>>()
-        .map_err(|e| {
-            postgis_error!("could not create segment: {}", e);
-            PostgisError::Psql(PsqlError::Execute)
-        })?;
+        .collect()
+}
+
+/// Subdivides a path into time segments by length and time start/end
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql instance, not unit testable
+pub async fn segmentize(
+    geom: &LineStringT<PointZ>,
+    timestamp_start: DateTime<Utc>,
+    timestamp_end: DateTime<Utc>,
+    max_segment_len_meters: f32,
+) -> Result<Vec<Segment>, PostgisError> {
+    segmentize_with(
+        &PostgisSplitter,
+        geom,
+        timestamp_start,
+        timestamp_end,
+        max_segment_len_meters,
+    )
+    .await
+}
 
-    // postgis_debug!(
-    //     "found {} segments. craft velocity {} m/s.",
-    //     results.len(),
-    //     velocity_m_s
-    // );
+/// Subdivides a path into time segments with the provided splitter
+pub async fn segmentize_with(
+    splitter: &(impl PathSplitter + Sync),
+    geom: &LineStringT<PointZ>,
+    timestamp_start: DateTime<Utc>,
+    timestamp_end: DateTime<Utc>,
+    max_segment_len_meters: f32,
+) -> Result<Vec<Segment>, PostgisError> {
+    let window_ms =
+        segment_window_ms(timestamp_start, timestamp_end).map_err(PostgisError::Segment)?;
 
-    Ok(results)
+    let pieces = splitter.split(geom, max_segment_len_meters).await?;
+    timed_segments(pieces, timestamp_start, window_ms).map_err(PostgisError::Segment)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn ut_compact_ring() {
+        let vertices = vec![
+            Coordinates {
+                latitude: 52.3745905,
+                longitude: 4.9160036,
+            },
+            Coordinates {
+                latitude: 52.3746905,
+                longitude: 4.9160036,
+            },
+            Coordinates {
+                latitude: 52.3746905,
+                longitude: 4.9161036,
+            },
+            Coordinates {
+                latitude: 52.3745905,
+                longitude: 4.9160036,
+            },
+        ];
+
+        let ring = compact_ring(&vertices);
+        assert_eq!(
+            ring.deltas,
+            vec![49160036, 523745905, 0, 1000, 1000, 0, -1000, -1000]
+        );
+
+        assert!(compact_ring(&[]).deltas.is_empty());
+    }
     use rand::{thread_rng, Rng};
 
     #[test]
@@ -381,12 +804,13 @@ mod tests {
             longitude,
         };
 
+        let precision = get_precision();
         let point = point_from_vertex(&vertex).unwrap();
         assert_eq!(
             point,
             Point {
-                x: longitude,
-                y: latitude,
+                x: precision.degrees(longitude),
+                y: precision.degrees(latitude),
                 srid: Some(DEFAULT_SRID)
             }
         );
@@ -433,7 +857,7 @@ mod tests {
         }
 
         let polygon = polygon_from_vertices_z(&vertices, 122.0).unwrap_err();
-        assert_eq!(polygon, PolygonError::VertexCount);
+        assert_eq!(polygon, GeometryError::from(PolygonError::VertexCount));
 
         // Close the polygon
         vertices.push(vertices.first().unwrap().clone());
@@ -445,8 +869,8 @@ mod tests {
                 points: vertices
                     .iter()
                     .map(|vertex| PointZ {
-                        x: vertex.longitude,
-                        y: vertex.latitude,
+                        x: get_precision().degrees(vertex.longitude),
+                        y: get_precision().degrees(vertex.latitude),
                         z: altitude_meters as f64,
                         srid: Some(DEFAULT_SRID),
                     })
@@ -476,7 +900,14 @@ mod tests {
 
         // Do not close the polygon
         let polygon = polygon_from_vertices_z(&vertices, 100.).unwrap_err();
-        assert_eq!(polygon, PolygonError::OpenPolygon);
+        assert_eq!(polygon.reason, PolygonError::OpenPolygon);
+        assert_eq!(
+            polygon.vertex,
+            Some(InvalidVertex {
+                index: MIN_NUM_POLYGON_VERTICES - 1,
+                coordinates: vertices[MIN_NUM_POLYGON_VERTICES - 1]
+            })
+        );
 
         // Add an invalid vertex
         vertices.push(Coordinates {
@@ -488,7 +919,17 @@ mod tests {
         vertices.push(vertices.first().unwrap().clone());
 
         let polygon = polygon_from_vertices_z(&vertices, 100.).unwrap_err();
-        assert_eq!(polygon, PolygonError::OutOfBounds);
+        assert_eq!(polygon.reason, PolygonError::OutOfBounds);
+        assert_eq!(
+            polygon.vertex,
+            Some(InvalidVertex {
+                index: MIN_NUM_POLYGON_VERTICES,
+                coordinates: Coordinates {
+                    latitude: 0.0,
+                    longitude: 180.1,
+                }
+            })
+        );
     }
 
     #[test]
@@ -545,6 +986,63 @@ mod tests {
         assert_eq!(error.to_string(), "One or more vertices are out of bounds.");
     }
 
+    #[test]
+    fn ut_field_violation() {
+        let error = GeometryError {
+            reason: PolygonError::OpenPolygon,
+            vertex: Some(InvalidVertex {
+                index: 7,
+                coordinates: Coordinates {
+                    latitude: 52.0,
+                    longitude: 4.0,
+                },
+            }),
+        };
+
+        let field = vertices_field("zones[2].vertices", &error);
+        assert_eq!(field, "zones[2].vertices[7]");
+        assert_eq!(
+            vertices_field("zones[2].vertices", &PolygonError::VertexCount.into()),
+            "zones[2].vertices"
+        );
+
+        let violation = field_violation(field, "zone", "NFZ_A", error);
+        assert_eq!(violation.field, "zones[2].vertices[7]");
+        assert_eq!(
+            violation.description,
+            "zone 'NFZ_A': The first and last vertices do not match (open polygon). Vertex 7: (52, 4)."
+        );
+
+        let validation = item_validation("NFZ_A", Some(violation.clone()));
+        assert!(!validation.valid);
+        assert_eq!(validation.violation, Some(violation));
+
+        let validation = item_validation("NFZ_B", None);
+        assert!(validation.valid);
+        assert_eq!(validation.identifier, "NFZ_B");
+    }
+
+    #[test]
+    fn test_geometry_error_display() {
+        let error = GeometryError::from(PolygonError::VertexCount);
+        assert_eq!(error.to_string(), "Invalid number of vertices provided.");
+
+        let error = GeometryError {
+            reason: PolygonError::OutOfBounds,
+            vertex: Some(InvalidVertex {
+                index: 4,
+                coordinates: Coordinates {
+                    latitude: 0.5,
+                    longitude: 180.1,
+                },
+            }),
+        };
+        assert_eq!(
+            error.to_string(),
+            "One or more vertices are out of bounds. Vertex 4: (0.5, 180.1)."
+        );
+    }
+
     #[test]
     fn test_point_error_display() {
         let error = PointError::OutOfBounds;
@@ -563,21 +1061,190 @@ mod tests {
         assert_eq!(error.to_string(), "String contains 'null'.");
     }
 
+    #[test]
+    fn test_precision_error_display() {
+        assert_eq!(
+            PrecisionError::Decimals.to_string(),
+            "Invalid number of decimal places."
+        );
+        assert_eq!(
+            PrecisionError::AlreadySet.to_string(),
+            "Coordinate precision already set."
+        );
+    }
+
+    #[test]
+    fn ut_precision() {
+        let precision = Precision::default();
+        assert_eq!(precision.degrees(52.374_590_512_345), 52.374_590_5);
+        assert_eq!(precision.degrees(-4.916_003_649_9), -4.916_003_6);
+        assert_eq!(precision.meters(120.004_9), 120.0);
+        assert_eq!(precision.meters(120.005_1), 120.01);
+
+        let point = precision.pointz(PointZ::new(
+            4.916_003_649_9,
+            52.374_590_512_345,
+            0.123_4,
+            None,
+        ));
+        assert_eq!(
+            (point.x, point.y, point.z),
+            (4.916_003_6, 52.374_590_5, 0.12)
+        );
+
+        let precision = Precision {
+            degree_decimals: 0,
+            altitude_decimals: 0,
+        };
+        assert_eq!(precision.degrees(52.6), 53.0);
+        assert_eq!(precision.meters(10.4), 10.0);
+    }
+
+    #[test]
+    fn ut_set_precision_invalid() {
+        let precision = Precision {
+            degree_decimals: MAX_DECIMALS + 1,
+            ..Default::default()
+        };
+        assert_eq!(set_precision(precision), Err(PrecisionError::Decimals));
+        assert_eq!(get_precision(), Precision::default());
+    }
+
+    #[test]
+    fn ut_conversions_rounded() {
+        let coordinates = Coordinates {
+            latitude: 52.374_590_512_345,
+            longitude: 4.916_003_649_9,
+        };
+
+        let point = PointZ::from(coordinates);
+        assert_eq!((point.x, point.y), (4.916_003_6, 52.374_590_5));
+
+        let point = point_from_vertex(&coordinates).unwrap();
+        assert_eq!((point.x, point.y), (4.916_003_6, 52.374_590_5));
+
+        let point = PointZ::from(GrpcPointZ {
+            latitude: coordinates.latitude,
+            longitude: coordinates.longitude,
+            altitude_meters: 99.999,
+        });
+        assert_eq!(point.z, 100.0);
+
+        // vertices equal at the stored precision close the polygon
+        let mut vertices = vec![
+            coordinates,
+            Coordinates {
+                latitude: 52.38,
+                longitude: 4.916,
+            },
+            Coordinates {
+                latitude: 52.38,
+                longitude: 4.92,
+            },
+            coordinates,
+        ];
+        vertices[3].latitude += 1e-9;
+        let polygon = polygon_from_vertices_z(&vertices, 10.001).unwrap();
+        let ring = &polygon.rings[0].points;
+        assert_eq!(ring.first(), ring.last());
+        assert_eq!(ring[0].z, 10.0);
+    }
+
+    #[test]
+    fn test_page_error_display() {
+        assert_eq!(PageError::Limit.to_string(), "Invalid page limit.");
+        assert_eq!(PageError::OrderBy.to_string(), "Invalid order field.");
+    }
+
+    #[test]
+    fn ut_pagination_try_from() {
+        // defaults
+        let page = Pagination::try_from(None).unwrap();
+        assert_eq!(page.limit, DEFAULT_PAGE_SIZE);
+        assert_eq!(page.offset, 0);
+        assert_eq!(page.order_by, OrderBy::Identifier);
+        assert!(!page.descending);
+
+        // limit is bounded by the server
+        let page = Pagination::try_from(Some(Page {
+            limit: Some(MAX_PAGE_SIZE + 1),
+            offset: 10,
+            order_by: OrderBy::LastUpdated as i32,
+            descending: true,
+        }))
+        .unwrap();
+        assert_eq!(page.limit, MAX_PAGE_SIZE);
+        assert_eq!(page.offset, 10);
+        assert_eq!(page.order_by, OrderBy::LastUpdated);
+        assert!(page.descending);
+
+        // invalid limit
+        let error = Pagination::try_from(Some(Page {
+            limit: Some(0),
+            ..Default::default()
+        }))
+        .unwrap_err();
+        assert_eq!(error, PageError::Limit);
+
+        // invalid order field
+        let error = Pagination::try_from(Some(Page {
+            order_by: 10000,
+            ..Default::default()
+        }))
+        .unwrap_err();
+        assert_eq!(error, PageError::OrderBy);
+    }
+
+    #[test]
+    fn ut_pagination_next_offset() {
+        let page = Pagination::try_from(Some(Page {
+            limit: Some(10),
+            offset: 20,
+            ..Default::default()
+        }))
+        .unwrap();
+
+        assert_eq!(page.next_offset(10), Some(30));
+        assert_eq!(page.next_offset(9), None);
+
+        let page = Pagination {
+            offset: u32::MAX,
+            ..page
+        };
+        assert_eq!(page.next_offset(10), None);
+    }
+
     #[test]
     fn test_from_position_pointz() {
         let position = Position {
-            latitude: rand::random(),
-            longitude: rand::random(),
-            altitude_meters: rand::random(),
+            latitude: Degrees(rand::random()),
+            longitude: Degrees(rand::random()),
+            altitude_meters: Meters(rand::random()),
         };
 
-        let point = PointZ::from(position.clone());
-        assert_eq!(point.x, position.longitude);
-        assert_eq!(point.y, position.latitude);
-        assert_eq!(point.z, position.altitude_meters);
+        let precision = get_precision();
+        let point = PointZ::from(position);
+        assert_eq!(point.x, precision.degrees(position.longitude.0));
+        assert_eq!(point.y, precision.degrees(position.latitude.0));
+        assert_eq!(point.z, precision.meters(position.altitude_meters.0));
         assert_eq!(point.srid, Some(DEFAULT_SRID));
     }
 
+    #[test]
+    fn test_from_pointz_position() {
+        let point = PointZ {
+            x: rand::random(),
+            y: rand::random(),
+            z: rand::random(),
+            srid: Some(DEFAULT_SRID),
+        };
+
+        let position = Position::from(point);
+        assert_eq!(position.longitude, Degrees(point.x));
+        assert_eq!(position.latitude, Degrees(point.y));
+        assert_eq!(position.altitude_meters, Meters(point.z));
+    }
+
     #[test]
     fn test_from_grpc_pointz() {
         let position = GrpcPointZ {
@@ -586,10 +1253,11 @@ mod tests {
             altitude_meters: rand::random::<f32>(),
         };
 
+        let precision = get_precision();
         let point = PointZ::from(position.clone());
-        assert_eq!(point.x, position.longitude);
-        assert_eq!(point.y, position.latitude);
-        assert_eq!(point.z, position.altitude_meters as f64);
+        assert_eq!(point.x, precision.degrees(position.longitude));
+        assert_eq!(point.y, precision.degrees(position.latitude));
+        assert_eq!(point.z, precision.meters(position.altitude_meters as f64));
         assert_eq!(point.srid, Some(DEFAULT_SRID));
     }
 
@@ -666,4 +1334,154 @@ mod tests {
         );
         assert!(delta < 5.0);
     }
+
+    /// Splits paths at each vertex, without a database
+    struct VertexSplitter;
+
+    #[async_trait]
+    impl PathSplitter for VertexSplitter {
+        async fn split(
+            &self,
+            geom: &LineStringT<PointZ>,
+            _max_segment_len_meters: f32,
+        ) -> Result<Vec<PathPiece>, PostgisError> {
+            // reversed, the pieces are sorted by index
+            Ok(geom
+                .points
+                .windows(2)
+                .enumerate()
+                .rev()
+                .map(|(i, pair)| PathPiece {
+                    idx: i as i64 + 1,
+                    geom: LineStringT {
+                        points: pair.to_vec(),
+                        srid: Some(DEFAULT_SRID),
+                    },
+                    distance_m: (pair[1].x - pair[0].x).abs(),
+                })
+                .collect())
+        }
+    }
+
+    fn path(xs: &[f64]) -> LineStringT<PointZ> {
+        LineStringT {
+            points: xs
+                .iter()
+                .map(|x| PointZ::new(*x, 0.0, 100.0, Some(DEFAULT_SRID)))
+                .collect(),
+            srid: Some(DEFAULT_SRID),
+        }
+    }
+
+    #[test]
+    fn ut_segment_window_ms() {
+        let start = Utc::now();
+        assert_eq!(
+            segment_window_ms(start, start + Duration::try_seconds(3).unwrap()),
+            Ok(3000)
+        );
+
+        // empty and sub-second windows are not truncated to nothing
+        assert_eq!(segment_window_ms(start, start), Ok(MIN_SEGMENT_WINDOW_MS));
+        assert_eq!(
+            segment_window_ms(start, start + Duration::try_milliseconds(500).unwrap()),
+            Ok(500)
+        );
+
+        assert_eq!(
+            segment_window_ms(start, start - Duration::try_seconds(1).unwrap()),
+            Err(SegmentError::TimeWindow)
+        );
+    }
+
+    #[tokio::test]
+    async fn ut_segmentize_with() {
+        let start = Utc::now();
+        let end = start + Duration::try_seconds(40).unwrap();
+
+        // pieces of 10 and 30 units share the window in proportion
+        let segments = segmentize_with(&VertexSplitter, &path(&[0.0, 10.0, 40.0]), start, end, 1.0)
+            .await
+            .unwrap();
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].geom.points[0].x, 0.0);
+        assert_eq!(segments[0].time_start, start);
+        assert_eq!(
+            segments[0].time_end,
+            start + Duration::try_seconds(10).unwrap()
+        );
+        assert_eq!(segments[1].time_start, segments[0].time_end);
+        assert_eq!(segments[1].time_end, end);
+
+        // no length, the window is shared evenly
+        let segments = segmentize_with(&VertexSplitter, &path(&[5.0, 5.0, 5.0]), start, end, 1.0)
+            .await
+            .unwrap();
+        assert_eq!(
+            segments[0].time_end,
+            start + Duration::try_seconds(20).unwrap()
+        );
+        assert_eq!(segments[1].time_end, end);
+
+        // an empty window is stretched, segments never run backwards
+        let segments = segmentize_with(
+            &VertexSplitter,
+            &path(&[0.0, 10.0, 40.0]),
+            start,
+            start,
+            1.0,
+        )
+        .await
+        .unwrap();
+        assert!(segments.iter().all(|s| s.time_end >= s.time_start));
+        assert_eq!(
+            segments[1].time_end,
+            start + Duration::try_milliseconds(MIN_SEGMENT_WINDOW_MS).unwrap()
+        );
+
+        let result = segmentize_with(&VertexSplitter, &path(&[0.0, 10.0]), end, start, 1.0).await;
+        assert_eq!(
+            result.unwrap_err(),
+            PostgisError::Segment(SegmentError::TimeWindow)
+        );
+    }
+
+    #[test]
+    fn test_segment_error_display() {
+        assert_eq!(
+            SegmentError::TimeWindow.to_string(),
+            "The time window ends before it starts."
+        );
+        assert_eq!(
+            SegmentError::Duration.to_string(),
+            "Invalid segment duration."
+        );
+    }
+
+    #[test]
+    fn test_distance_meters_matches_postgis() {
+        // Reference values from PostGIS:
+        //  SELECT ST_Distance('POINT(0 0)'::geography, 'POINT(1 0)'::geography);
+        //  SELECT ST_Distance('POINT(0 0)'::geography, 'POINT(0 1)'::geography);
+        let origin = PointZ {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            srid: Some(DEFAULT_SRID),
+        };
+
+        let east = PointZ { x: 1.0, ..origin };
+        let north = PointZ { y: 1.0, ..origin };
+
+        let delta = (distance_meters(&origin, &east) as f64 - 111_319.490_793).abs();
+        assert!(delta < 0.1);
+
+        let delta = (distance_meters(&origin, &north) as f64 - 110_574.388_564).abs();
+        assert!(delta < 0.1);
+
+        // On a sphere these two distances are equal, on the ellipsoid
+        //  a degree of latitude at the equator is shorter
+        assert!(distance_meters(&origin, &north) < distance_meters(&origin, &east));
+    }
 }
@@ -1,20 +1,49 @@
 //! Updates vertiports in the PostGIS database.
 
-use super::{PostgisError, DEFAULT_SRID, PSQL_SCHEMA};
+use super::identifier::{check_identifier, Entity};
+use super::statements::{Ordering, Statement};
+use super::utils::{field_violation, item_validation, vertices_field, Pagination};
+use super::waypoint::Waypoint;
+use super::{OnceCell, PostgisError, DEFAULT_SRID};
 use crate::grpc::server::grpc_server;
 use deadpool_postgres::Object;
 use grpc_server::Vertiport as RequestVertiport;
-use grpc_server::ZoneType;
+use grpc_server::{
+    Coordinates, GeometryEncoding, GetVertiportsRequest, GetVertiportsResponse, OrderBy,
+};
+use grpc_server::{FieldViolation, ItemValidation, SetVertiportStatusRequest};
+use grpc_server::{VertiportLayers, VertiportStatus, ZoneType};
 use lib_common::time::{DateTime, Utc};
-use postgis::ewkb::PointZ;
+use num_traits::FromPrimitive;
+use once_cell::sync::Lazy;
+use postgis::ewkb::{GeometryZ, PointZ, PolygonZ};
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
-
-/// Allowed characters in a label
-pub const IDENTIFIER_REGEX: &str = r"^[\-0-9A-Za-z_\.]{1,255}$";
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU32, Ordering as AtomicOrdering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
 /// Vertiport overhead no-fly clearance
 const VERTIPORT_CLEARANCE_METERS: f32 = 200.0;
 
+/// Default seconds vertiport routing data is served from memory before it
+///  is read again, zero to read it on every request. Short enough that a
+///  vertiport closed through another instance is no longer routed to within
+///  the time an aircraft position is reported stale.
+pub const DEFAULT_CACHE_TTL_SECONDS: u32 = 10;
+
+/// Seconds vertiport routing data is served from memory
+static CACHE_TTL_SECONDS: AtomicU32 = AtomicU32::new(DEFAULT_CACHE_TTL_SECONDS);
+
+/// Centroids and statuses of the vertiports routed to or from recently
+static CENTROIDS: Lazy<Mutex<VertiportCache<String, Centroid>>> = Lazy::new(Default::default);
+
+/// Waypoints near the line between an origin and a target vertiport routed
+///  between recently, including those around their port zones
+static ROUTE_WAYPOINTS: Lazy<Mutex<VertiportCache<(String, String), Vec<Waypoint>>>> =
+    Lazy::new(Default::default);
+
 /// Possible conversion errors from the GRPC type to GIS type
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum VertiportError {
@@ -38,6 +67,27 @@ pub enum VertiportError {
 
     /// Timestamp error
     Timestamp,
+
+    /// Invalid operational status
+    Status,
+
+    /// Vertiport is closed
+    Closed,
+
+    /// Vertiport does not exist
+    NotFound,
+
+    /// Invalid pagination options
+    Pagination,
+
+    /// Invalid TLOF, FATO or safety area, or approach heading
+    Layers,
+
+    /// Invalid geometry encoding
+    Encoding,
+
+    /// Invalid operating hours
+    OperatingHours,
 }
 
 impl Display for VertiportError {
@@ -50,14 +100,21 @@ impl Display for VertiportError {
             VertiportError::Client => write!(f, "Could not get backend client."),
             VertiportError::DBError => write!(f, "Unknown backend error."),
             VertiportError::Timestamp => write!(f, "Invalid timestamp provided."),
+            VertiportError::Status => write!(f, "Invalid status provided."),
+            VertiportError::Closed => write!(f, "Vertiport is closed."),
+            VertiportError::NotFound => write!(f, "Vertiport not found."),
+            VertiportError::Pagination => write!(f, "Invalid pagination provided."),
+            VertiportError::Layers => write!(f, "Invalid vertiport layers provided."),
+            VertiportError::Encoding => write!(f, "Invalid geometry encoding provided."),
+            VertiportError::OperatingHours => write!(f, "Invalid operating hours provided."),
         }
     }
 }
 
 /// Gets the name of this module's table
-fn get_table_name() -> &'static str {
-    static FULL_NAME: &str = const_format::formatcp!(r#""{PSQL_SCHEMA}"."vertiports""#,);
-    FULL_NAME
+pub(super) fn get_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."vertiports""#, super::get_schema()))
 }
 
 /// Gets a connected postgis client from the pool
@@ -79,21 +136,236 @@ async fn get_client() -> Result<Object, PostgisError> {
         })
 }
 
+/// Routing attributes of a vertiport
+#[derive(Debug, Clone, PartialEq)]
+pub struct Centroid {
+    /// Central point, at the altitude of the vertiport
+    pub geom: PointZ,
+
+    /// Operational status
+    pub status: VertiportStatus,
+
+    /// Weekly operating hours as [start, end) pairs of minutes into the
+    ///  week, empty if open at all hours
+    pub operating_hours: Vec<i32>,
+}
+
+/// Routing data of vertiports, with the time it was read
+///  Vertiports rarely change, so routing requests reuse their data instead
+///  of reading it for each origin and target. Changes made through this
+///  instance drop the entries they affect; changes made through another
+///  instance are seen once the entries expire.
+///
+/// Each invalidation starts a new generation. Data read during an older
+///  generation may predate the change and is not kept.
+#[derive(Debug)]
+struct VertiportCache<K, V> {
+    entries: HashMap<K, (V, Instant)>,
+    generation: u64,
+}
+
+impl<K, V> Default for VertiportCache<K, V> {
+    fn default() -> Self {
+        VertiportCache {
+            entries: HashMap::new(),
+            generation: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> VertiportCache<K, V> {
+    /// Gets an entry, if read less than `ttl` before `now`
+    fn get(&self, key: &K, now: Instant, ttl: Duration) -> Option<V> {
+        self.entries
+            .get(key)
+            .filter(|(_, read)| now.saturating_duration_since(*read) < ttl)
+            .map(|(value, _)| value.clone())
+    }
+
+    /// Keeps an entry read at `now` during `generation`, dropping the
+    ///  expired entries. Returns false if the cache was invalidated since.
+    fn insert(&mut self, key: K, value: V, generation: u64, now: Instant, ttl: Duration) -> bool {
+        if generation != self.generation {
+            return false;
+        }
+
+        self.entries
+            .retain(|_, (_, read)| now.saturating_duration_since(*read) < ttl);
+        self.entries.insert(key, (value, now));
+        true
+    }
+
+    /// Drops the entries matching a predicate
+    fn remove(&mut self, predicate: impl Fn(&K) -> bool) {
+        self.generation += 1;
+        self.entries.retain(|key, _| !predicate(key));
+    }
+}
+
+/// Sets how long vertiport routing data is served from memory, zero to read
+///  it on every request
+pub fn set_cache_ttl_seconds(seconds: u32) {
+    CACHE_TTL_SECONDS.store(seconds, AtomicOrdering::Relaxed);
+    if seconds == 0 {
+        invalidate(&CENTROIDS, |_| true);
+        invalidate(&ROUTE_WAYPOINTS, |_| true);
+    }
+}
+
+/// How long vertiport routing data is served from memory
+fn cache_ttl() -> Duration {
+    Duration::from_secs(CACHE_TTL_SECONDS.load(AtomicOrdering::Relaxed) as u64)
+}
+
+/// Gets a cached entry if still fresh, or else the generation to keep
+///  the entry read instead with
+fn lookup<K: Eq + Hash, V: Clone>(cache: &Mutex<VertiportCache<K, V>>, key: &K) -> Result<V, u64> {
+    let cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+    cache
+        .get(key, Instant::now(), cache_ttl())
+        .ok_or(cache.generation)
+}
+
+/// Caches an entry read during `generation`
+fn keep<K: Eq + Hash, V: Clone>(
+    cache: &Mutex<VertiportCache<K, V>>,
+    key: K,
+    value: V,
+    generation: u64,
+) {
+    let ttl = cache_ttl();
+    if ttl.is_zero() {
+        return;
+    }
+
+    cache.lock().unwrap_or_else(PoisonError::into_inner).insert(
+        key,
+        value,
+        generation,
+        Instant::now(),
+        ttl,
+    );
+}
+
+/// Drops the cached entries matching a predicate
+fn invalidate<K: Eq + Hash, V: Clone>(
+    cache: &Mutex<VertiportCache<K, V>>,
+    predicate: impl Fn(&K) -> bool,
+) {
+    cache
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(predicate);
+}
+
+/// Drops the cached waypoints between vertiports, after waypoints or zones
+///  changed
+pub(super) fn invalidate_route_waypoints() {
+    invalidate(&ROUTE_WAYPOINTS, |_| true);
+}
+
+/// Validated surveyed areas of a vertiport
+#[derive(Debug, Clone, PartialEq)]
+struct Layers {
+    tlof: Option<PolygonZ>,
+    fato: PolygonZ,
+    safety_area: Option<PolygonZ>,
+    approach_headings: Vec<f32>,
+}
+
+/// Converts the vertices of a layer into a 2D polygon for containment checks
+fn layer_polygon(vertices: &[Coordinates]) -> geo::Polygon<f64> {
+    geo::Polygon::new(
+        vertices
+            .iter()
+            .map(|vertex| (vertex.longitude, vertex.latitude))
+            .collect(),
+        vec![],
+    )
+}
+
+/// Validates the layers of a vertiport: the TLOF lies within the FATO,
+///  which lies within the vertiport and the safety area
+fn validate_layers(
+    layers: &VertiportLayers,
+    vertices: &[Coordinates],
+    altitude_meters: f32,
+) -> Result<Layers, VertiportError> {
+    use geo::Contains;
+
+    let polygon = |name: &str, vertices: &[Coordinates]| {
+        super::utils::polygon_from_vertices_z(vertices, altitude_meters).map_err(|e| {
+            postgis_error!("invalid {name} polygon: {}", e);
+            VertiportError::Layers
+        })
+    };
+
+    let fato = polygon("FATO", &layers.fato)?;
+    let fato_2d = layer_polygon(&layers.fato);
+    if !layer_polygon(vertices).contains(&fato_2d) {
+        postgis_error!("FATO extends outside of the vertiport.");
+        return Err(VertiportError::Layers);
+    }
+
+    let tlof = match layers.tlof.is_empty() {
+        true => None,
+        false => {
+            if !fato_2d.contains(&layer_polygon(&layers.tlof)) {
+                postgis_error!("TLOF extends outside of the FATO.");
+                return Err(VertiportError::Layers);
+            }
+
+            Some(polygon("TLOF", &layers.tlof)?)
+        }
+    };
+
+    let safety_area = match layers.safety_area.is_empty() {
+        true => None,
+        false => {
+            if !layer_polygon(&layers.safety_area).contains(&fato_2d) {
+                postgis_error!("safety area doesn't surround the FATO.");
+                return Err(VertiportError::Layers);
+            }
+
+            Some(polygon("safety area", &layers.safety_area)?)
+        }
+    };
+
+    if let Some(heading) = layers
+        .approach_headings_degrees
+        .iter()
+        .find(|heading| !(0.0..=360.0).contains(*heading))
+    {
+        postgis_error!("invalid approach heading: {heading}.");
+        return Err(VertiportError::Layers);
+    }
+
+    Ok(Layers {
+        tlof,
+        fato,
+        safety_area,
+        approach_headings: layers.approach_headings_degrees.clone(),
+    })
+}
+
 /// Helper Struct for Validating Requests
-struct Vertiport {
+pub(super) struct Vertiport {
     identifier: String,
     label: Option<String>,
     geom: postgis::ewkb::PolygonZ,
     altitude_meters_min: f32,
     altitude_meters_max: f32,
     timestamp: DateTime<Utc>,
+    status: Option<VertiportStatus>,
+    layers: Option<Layers>,
+    operating_hours: Vec<i32>,
 }
 
 impl TryFrom<RequestVertiport> for Vertiport {
     type Error = VertiportError;
 
     fn try_from(vertiport: RequestVertiport) -> Result<Self, Self::Error> {
-        super::utils::check_string(&vertiport.identifier, IDENTIFIER_REGEX).map_err(|e| {
+        check_identifier(Entity::Vertiport, &vertiport.identifier).map_err(|e| {
             postgis_error!(
                 "Vertiport {} has invalid identifier {:?}: {}",
                 vertiport.identifier,
@@ -121,6 +393,40 @@ impl TryFrom<RequestVertiport> for Vertiport {
             VertiportError::Timestamp
         })?;
 
+        let status = vertiport
+            .status
+            .map(|status| {
+                FromPrimitive::from_i32(status).ok_or_else(|| {
+                    postgis_error!(
+                        "Vertiport {} has invalid status {}",
+                        vertiport.identifier,
+                        status
+                    );
+
+                    VertiportError::Status
+                })
+            })
+            .transpose()?;
+
+        let layers = vertiport
+            .layers
+            .as_ref()
+            .map(|layers| validate_layers(layers, &vertiport.vertices, vertiport.altitude_meters))
+            .transpose()
+            .map_err(|e| {
+                postgis_error!("Vertiport {} has invalid layers", vertiport.identifier);
+                e
+            })?;
+
+        let operating_hours = super::zone::schedule_from_windows(&vertiport.operating_hours)
+            .map_err(|_| {
+                postgis_error!(
+                    "Vertiport {} has invalid operating hours",
+                    vertiport.identifier
+                );
+                VertiportError::OperatingHours
+            })?;
+
         // TODO(R5): Check altitude
 
         Ok(Vertiport {
@@ -130,30 +436,117 @@ impl TryFrom<RequestVertiport> for Vertiport {
             altitude_meters_min: vertiport.altitude_meters,
             altitude_meters_max: vertiport.altitude_meters + VERTIPORT_CLEARANCE_METERS,
             timestamp: timestamp.into(),
+            status,
+            layers,
+            operating_hours,
         })
     }
 }
 
+/// Describes why a vertiport of an update was rejected
+fn vertiport_violation(
+    index: usize,
+    vertiport: &RequestVertiport,
+    error: VertiportError,
+) -> FieldViolation {
+    let field = |name: &str| format!("vertiports[{index}].{name}");
+    let violation =
+        |name: &str| field_violation(field(name), "vertiport", &vertiport.identifier, error);
+    match error {
+        VertiportError::Identifier => violation("identifier"),
+        VertiportError::Timestamp => violation("timestamp_network"),
+        VertiportError::Status => violation("status"),
+        VertiportError::Layers => violation("layers"),
+        VertiportError::OperatingHours => violation("operating_hours"),
+        VertiportError::Location => {
+            super::utils::polygon_from_vertices_z(&vertiport.vertices, vertiport.altitude_meters)
+                .err()
+                .map(|e| {
+                    field_violation(
+                        vertices_field(&field("vertices"), &e),
+                        "vertiport",
+                        &vertiport.identifier,
+                        e,
+                    )
+                })
+                .unwrap_or_else(|| violation("vertices"))
+        }
+        _ => field_violation(
+            format!("vertiports[{index}]"),
+            "vertiport",
+            &vertiport.identifier,
+            error,
+        ),
+    }
+}
+
+/// Gets the vertiports of an update that would be rejected, and why
+pub fn violations(vertiports: &[RequestVertiport]) -> Vec<FieldViolation> {
+    vertiports
+        .iter()
+        .enumerate()
+        .filter_map(|(index, vertiport)| {
+            Vertiport::try_from(vertiport.clone())
+                .err()
+                .map(|e| vertiport_violation(index, vertiport, e))
+        })
+        .collect()
+}
+
 /// Initialize the vertiports table in the PostGIS database
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) needs a PostGIS backend to test
 pub async fn psql_init() -> Result<(), PostgisError> {
     // Create Vertiport Table
-    let statements = vec![format!(
-        r#"CREATE TABLE IF NOT EXISTS {vertiports_table_name} (
+    let status_enum_name = "vertiportstatus";
+    let statements = vec![
+        super::psql_enum_declaration::<VertiportStatus>(status_enum_name),
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {vertiports_table_name} (
             "identifier" VARCHAR(255) UNIQUE PRIMARY KEY NOT NULL,
             "label" VARCHAR(255) NOT NULL,
             "zone_id" INTEGER NOT NULL,
             "geom" GEOMETRY, -- 3D Polygon
             "altitude_meters" FLOAT(4),
             "last_updated" TIMESTAMPTZ,
+            "operational_status" {status_enum_name} NOT NULL DEFAULT '{status_enum_default}',
+            "tlof" GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+            "fato" GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+            "safety_area" GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+            "approach_headings" FLOAT(4)[] NOT NULL DEFAULT '{{}}',
+            "operating_hours" INTEGER[] NOT NULL DEFAULT '{{}}',
             CONSTRAINT "fk_zone"
                 FOREIGN KEY ("zone_id")
                 REFERENCES {zones_table_name} ("id")
         );"#,
-        vertiports_table_name = get_table_name(),
-        zones_table_name = super::zone::get_table_name(),
-    )];
+            vertiports_table_name = get_table_name(),
+            zones_table_name = super::zone::get_table_name(),
+            status_enum_default = VertiportStatus::Operational,
+        ),
+        // Tables created by earlier versions have no status
+        format!(
+            r#"ALTER TABLE {vertiports_table_name}
+            ADD COLUMN IF NOT EXISTS "operational_status" {status_enum_name}
+                NOT NULL DEFAULT '{status_enum_default}';"#,
+            vertiports_table_name = get_table_name(),
+            status_enum_default = VertiportStatus::Operational,
+        ),
+        // Tables created by earlier versions have no surveyed areas
+        format!(
+            r#"ALTER TABLE {vertiports_table_name}
+            ADD COLUMN IF NOT EXISTS "tlof" GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+            ADD COLUMN IF NOT EXISTS "fato" GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+            ADD COLUMN IF NOT EXISTS "safety_area" GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
+            ADD COLUMN IF NOT EXISTS "approach_headings" FLOAT(4)[] NOT NULL DEFAULT '{{}}';"#,
+            vertiports_table_name = get_table_name(),
+        ),
+        // Tables created by earlier versions have no operating hours
+        format!(
+            r#"ALTER TABLE {vertiports_table_name}
+            ADD COLUMN IF NOT EXISTS "operating_hours" INTEGER[] NOT NULL DEFAULT '{{}}';"#,
+            vertiports_table_name = get_table_name(),
+        ),
+    ];
 
     super::psql_transaction(statements).await
 }
@@ -180,58 +573,7 @@ pub async fn update_vertiports(vertiports: Vec<RequestVertiport>) -> Result<(),
     })?;
 
     let stmt = transaction
-        .prepare_cached(&format!(
-            r#"WITH "tmp" AS (
-                INSERT INTO {zones_table_name} (
-                    "identifier",
-                    "geom",
-                    "altitude_meters_min",
-                    "altitude_meters_max",
-                    "zone_type",
-                    "last_updated"
-                ) VALUES (
-                    $1,
-                    ST_EXTRUDE(
-                        $2::GEOMETRY(POLYGONZ, {DEFAULT_SRID}),
-                        0,
-                        0,
-                        ($4::FLOAT(4) - $3::FLOAT(4))
-                    ),
-                    $3,
-                    $4,
-                    $6,
-                    $7
-                )
-                ON CONFLICT ("identifier") DO UPDATE
-                SET
-                    "geom" = EXCLUDED."geom",
-                    "zone_type" = EXCLUDED."zone_type"
-                RETURNING "id"
-            ) INSERT INTO {vertiports_table_name} (
-                "identifier",
-                "zone_id",
-                "geom",
-                "label",
-                "altitude_meters",
-                "last_updated"
-            ) VALUES (
-                $1::VARCHAR,
-                (SELECT "id" FROM "tmp"),
-                $2::GEOMETRY,
-                $5::VARCHAR,
-                $3::FLOAT(4),
-                $7::TIMESTAMPTZ
-            )
-            ON CONFLICT ("identifier") DO UPDATE
-                SET
-                    "label" = coalesce($5, {vertiports_table_name}."label"),
-                    "zone_id" = EXCLUDED."zone_id",
-                    "geom" = EXCLUDED."geom",
-                    "altitude_meters" = EXCLUDED."altitude_meters",
-                    "last_updated" = EXCLUDED."last_updated";"#,
-            vertiports_table_name = get_table_name(),
-            zones_table_name = super::zone::get_table_name(),
-        ))
+        .prepare_cached(&Statement::UpsertVertiport.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
@@ -239,24 +581,7 @@ pub async fn update_vertiports(vertiports: Vec<RequestVertiport>) -> Result<(),
         })?;
 
     for vertiport in &vertiports {
-        transaction
-            .execute(
-                &stmt,
-                &[
-                    &vertiport.identifier,
-                    &vertiport.geom,
-                    &vertiport.altitude_meters_min,
-                    &vertiport.altitude_meters_max,
-                    &vertiport.label,
-                    &ZoneType::Port,
-                    &vertiport.timestamp,
-                ],
-            )
-            .await
-            .map_err(|e| {
-                postgis_error!("could not execute transaction: {}", e);
-                PostgisError::Vertiport(VertiportError::DBError)
-            })?;
+        upsert_vertiport(&transaction, &stmt, vertiport).await?;
     }
 
     transaction.commit().await.map_err(|e| {
@@ -264,49 +589,379 @@ pub async fn update_vertiports(vertiports: Vec<RequestVertiport>) -> Result<(),
         PostgisError::Vertiport(VertiportError::DBError)
     })?;
 
+    // Port zones and their waypoints were regenerated too
+    invalidate(&CENTROIDS, |identifier| {
+        vertiports.iter().any(|v| &v.identifier == identifier)
+    });
+    invalidate_route_waypoints();
     postgis_debug!("success.");
     Ok(())
 }
 
+/// Upserts a vertiport with its port zone, and regenerates the waypoints
+///  of the zone
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test
+async fn upsert_vertiport(
+    transaction: &deadpool_postgres::Transaction<'_>,
+    stmt: &tokio_postgres::Statement,
+    vertiport: &Vertiport,
+) -> Result<(), PostgisError> {
+    let layers = vertiport.layers.as_ref();
+    let approach_headings = layers
+        .map(|layers| layers.approach_headings.clone())
+        .unwrap_or_default();
+
+    transaction
+        .execute(
+            stmt,
+            &[
+                &vertiport.identifier,
+                &vertiport.geom,
+                &vertiport.altitude_meters_min,
+                &vertiport.altitude_meters_max,
+                &vertiport.label,
+                &ZoneType::Port,
+                &vertiport.timestamp,
+                &vertiport.status,
+                &layers.and_then(|layers| layers.tlof.as_ref()),
+                &layers.map(|layers| &layers.fato),
+                &layers.and_then(|layers| layers.safety_area.as_ref()),
+                &approach_headings,
+                &vertiport.operating_hours,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute transaction: {}", e);
+            PostgisError::Vertiport(VertiportError::DBError)
+        })?;
+
+    super::zone::update_zone_waypoints(transaction, &vertiport.identifier).await
+}
+
+/// Checks an update of vertiports without applying it
+///
+/// Like [`super::zone::validate_zones`], each valid vertiport is applied
+///  under its own savepoint in a transaction that is rolled back.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test
+pub async fn validate_vertiports(
+    vertiports: Vec<RequestVertiport>,
+) -> Result<Vec<ItemValidation>, PostgisError> {
+    postgis_debug!("entry.");
+    if vertiports.is_empty() {
+        return Err(PostgisError::Vertiport(VertiportError::NoVertiports));
+    }
+
+    let mut client = get_client().await?;
+    let mut transaction = client.transaction().await.map_err(|e| {
+        postgis_error!("could not create transaction: {}", e);
+        PostgisError::Vertiport(VertiportError::DBError)
+    })?;
+
+    let stmt = transaction
+        .prepare_cached(&Statement::UpsertVertiport.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Vertiport(VertiportError::DBError)
+        })?;
+
+    let mut validations = Vec::with_capacity(vertiports.len());
+    for (index, request) in vertiports.iter().enumerate() {
+        let vertiport = match Vertiport::try_from(request.clone()) {
+            Ok(vertiport) => vertiport,
+            Err(e) => {
+                let violation = vertiport_violation(index, request, e);
+                validations.push(item_validation(&request.identifier, Some(violation)));
+                continue;
+            }
+        };
+
+        let savepoint = transaction.savepoint("vertiport").await.map_err(|e| {
+            postgis_error!("could not create savepoint: {}", e);
+            PostgisError::Vertiport(VertiportError::DBError)
+        })?;
+
+        // dropping the savepoint rolls it back
+        let result = match upsert_vertiport(&savepoint, &stmt, &vertiport).await {
+            Ok(()) => savepoint.commit().await.map_err(|e| {
+                postgis_error!("could not release savepoint: {}", e);
+                VertiportError::DBError
+            }),
+            Err(_) => Err(VertiportError::DBError),
+        };
+
+        let violation = result.err().map(|e| vertiport_violation(index, request, e));
+        validations.push(item_validation(&request.identifier, violation));
+    }
+
+    transaction.rollback().await.map_err(|e| {
+        postgis_error!("could not roll back transaction: {}", e);
+        PostgisError::Vertiport(VertiportError::DBError)
+    })?;
+
+    Ok(validations)
+}
+
+/// Sets the operational status of a vertiport
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test
+pub async fn set_vertiport_status(request: SetVertiportStatusRequest) -> Result<(), PostgisError> {
+    postgis_debug!("entry, vertiport: '{}'.", request.identifier);
+    check_identifier(Entity::Vertiport, &request.identifier).map_err(|e| {
+        postgis_error!("invalid identifier {:?}: {}", request.identifier, e);
+        PostgisError::Vertiport(VertiportError::Identifier)
+    })?;
+
+    let status: VertiportStatus = FromPrimitive::from_i32(request.status).ok_or_else(|| {
+        postgis_error!("invalid status: {}", request.status);
+        PostgisError::Vertiport(VertiportError::Status)
+    })?;
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::UpdateVertiportStatus.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Vertiport(VertiportError::DBError)
+        })?;
+
+    let updated = client
+        .execute(&stmt, &[&request.identifier, &status])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not update vertiport status: {}", e);
+            PostgisError::Vertiport(VertiportError::DBError)
+        })?;
+
+    if updated == 0 {
+        postgis_error!("vertiport not found: '{}'.", request.identifier);
+        return Err(PostgisError::Vertiport(VertiportError::NotFound));
+    }
+
+    invalidate(&CENTROIDS, |identifier| identifier == &request.identifier);
+    postgis_debug!("success.");
+    Ok(())
+}
+
+/// Gets the routing attributes of a vertiport, from memory if read recently
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test
+pub async fn get_vertiport_centroid(identifier: &str) -> Result<Centroid, PostgisError> {
+    let key = identifier.to_string();
+    let generation = match lookup(&CENTROIDS, &key) {
+        Ok(centroid) => return Ok(centroid),
+        Err(generation) => generation,
+    };
+
+    let centroid = read_vertiport_centroid(identifier).await?;
+    keep(&CENTROIDS, key, centroid.clone(), generation);
+    Ok(centroid)
+}
+
+/// Gets the waypoints within `range_meters` of the line between two
+///  vertiports, from memory if read recently
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test
+pub async fn get_route_waypoints(
+    vertiports: (&str, &str),
+    line: &GeometryZ,
+    range_meters: f32,
+) -> Result<Vec<Waypoint>, PostgisError> {
+    let key = (vertiports.0.to_string(), vertiports.1.to_string());
+    let generation = match lookup(&ROUTE_WAYPOINTS, &key) {
+        Ok(waypoints) => return Ok(waypoints),
+        Err(generation) => generation,
+    };
+
+    let waypoints = super::waypoint::get_waypoints_near_geometry(line, range_meters).await?;
+    keep(&ROUTE_WAYPOINTS, key, waypoints.clone(), generation);
+    Ok(waypoints)
+}
+
 /// Gets the central PointZ geometry of a vertiport (for routing) given its identifier.
+///  Closed vertiports can't be routed to or from.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) needs a PostGIS backend to test
 pub async fn get_vertiport_centroidz(identifier: &str) -> Result<PointZ, PostgisError> {
     postgis_debug!("entry, vertiport: '{identifier}'.");
-    let stmt = format!(
-        r#"
-        SELECT ST_Force3DZ (
-            ST_Centroid("geom"),
-            "altitude_meters"
-        )
-        FROM {table_name}
-        WHERE "identifier" = $1;"#,
-        table_name = get_table_name()
-    );
+    let centroid = get_vertiport_centroid(identifier).await?;
+    if centroid.status == VertiportStatus::Closed {
+        postgis_error!("vertiport '{identifier}' is closed.");
+        return Err(PostgisError::Vertiport(VertiportError::Closed));
+    }
+
+    Ok(centroid.geom)
+}
+
+/// Reads the routing attributes of a vertiport
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test
+async fn read_vertiport_centroid(identifier: &str) -> Result<Centroid, PostgisError> {
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::GetVertiportCentroid.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Vertiport(VertiportError::DBError)
+        })?;
+
+    let row = client.query_one(&stmt, &[&identifier]).await.map_err(|e| {
+        postgis_error!("query failed: {}", e);
+        PostgisError::Vertiport(VertiportError::DBError)
+    })?;
+
+    let status: VertiportStatus = row.try_get(1).map_err(|e| {
+        postgis_error!("could not get status for vertiport '{identifier}': {}", e);
+        PostgisError::Vertiport(VertiportError::DBError)
+    })?;
+
+    let operating_hours: Vec<i32> = row.try_get(2).map_err(|e| {
+        postgis_error!(
+            "could not get operating hours for vertiport '{identifier}': {}",
+            e
+        );
+        PostgisError::Vertiport(VertiportError::DBError)
+    })?;
+
+    let geom = row.try_get::<_, PointZ>(0).map_err(|e| {
+        postgis_error!(
+            "zero or more than one records found for vertiport '{identifier}': {}",
+            e
+        );
+        PostgisError::Vertiport(VertiportError::DBError)
+    })?;
+
+    Ok(Centroid {
+        geom,
+        status,
+        operating_hours,
+    })
+}
+
+/// Gets the column used to order vertiports
+pub(super) fn get_order_column(order_by: OrderBy) -> &'static str {
+    match order_by {
+        OrderBy::Identifier => r#""identifier""#,
+        OrderBy::LastUpdated => r#""last_updated""#,
+    }
+}
+
+/// Converts a vertiport row into the GRPC type
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test, no way to create a Row without querying it
+fn process_vertiport_row(
+    row: tokio_postgres::Row,
+) -> Result<RequestVertiport, tokio_postgres::Error> {
+    let ring: postgis::ewkb::LineString = row.try_get("ring")?;
+    let status: VertiportStatus = row.try_get("operational_status")?;
+    let altitude_meters: Option<f32> = row.try_get("altitude_meters")?;
+    let last_updated: Option<DateTime<Utc>> = row.try_get("last_updated")?;
+    let tlof: Option<postgis::ewkb::LineString> = row.try_get("tlof_ring")?;
+    let fato: Option<postgis::ewkb::LineString> = row.try_get("fato_ring")?;
+    let safety_area: Option<postgis::ewkb::LineString> = row.try_get("safety_area_ring")?;
+    let approach_headings_degrees: Vec<f32> = row.try_get("approach_headings")?;
+    let operating_hours: Vec<i32> = row.try_get("operating_hours")?;
+
+    let coordinates = |ring: &postgis::ewkb::LineString| -> Vec<Coordinates> {
+        ring.points
+            .iter()
+            .map(|p| Coordinates {
+                latitude: p.y,
+                longitude: p.x,
+            })
+            .collect()
+    };
+
+    let layers = fato.map(|fato| VertiportLayers {
+        tlof: tlof.as_ref().map(coordinates).unwrap_or_default(),
+        fato: coordinates(&fato),
+        safety_area: safety_area.as_ref().map(coordinates).unwrap_or_default(),
+        approach_headings_degrees,
+    });
+
+    Ok(RequestVertiport {
+        identifier: row.try_get("identifier")?,
+        vertices: coordinates(&ring),
+        altitude_meters: altitude_meters.unwrap_or_default(),
+        label: row.try_get("label")?,
+        timestamp_network: last_updated.map(|t| t.into()),
+        status: Some(status as i32),
+        layers,
+        compact_vertices: None,
+        operating_hours: super::zone::windows_from_schedule(&operating_hours),
+    })
+}
+
+/// Gets a page of vertiports from the PostGIS database
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs a PostGIS backend to test
+pub async fn get_vertiports(
+    request: GetVertiportsRequest,
+) -> Result<GetVertiportsResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let page = Pagination::try_from(request.page).map_err(|e| {
+        postgis_error!("invalid pagination: {}", e);
+        PostgisError::Vertiport(VertiportError::Pagination)
+    })?;
+
+    let encoding = GeometryEncoding::try_from(request.encoding).map_err(|e| {
+        postgis_error!("invalid geometry encoding: {}", e);
+        PostgisError::Vertiport(VertiportError::Encoding)
+    })?;
 
-    get_client()
-        .await?
-        .query_one(&stmt, &[&identifier])
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::GetVertiports(Ordering::from(&page)).sql())
         .await
         .map_err(|e| {
-            postgis_error!("query failed: {}", e);
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Vertiport(VertiportError::DBError)
+        })?;
+
+    let vertiports = client
+        .query(&stmt, &[&(page.limit as i64), &(page.offset as i64)])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute query: {}", e);
             PostgisError::Vertiport(VertiportError::DBError)
         })?
-        .try_get::<_, PointZ>(0)
+        .into_iter()
+        .map(process_vertiport_row)
+        .collect::<Result<Vec<RequestVertiport>, _>>()
         .map_err(|e| {
-            postgis_error!(
-                "zero or more than one records found for vertiport '{identifier}': {}",
-                e
-            );
+            postgis_error!("could not get vertiport data: {}", e);
             PostgisError::Vertiport(VertiportError::DBError)
-        })
+        })?;
+
+    // The layers are small next to the outline, they keep their coordinates
+    let vertiports = match encoding {
+        GeometryEncoding::Coordinates => vertiports,
+        GeometryEncoding::Compact => vertiports
+            .into_iter()
+            .map(|vertiport| RequestVertiport {
+                compact_vertices: Some(super::utils::compact_ring(&vertiport.vertices)),
+                vertices: vec![],
+                ..vertiport
+            })
+            .collect(),
+    };
+
+    Ok(GetVertiportsResponse {
+        next_offset: page.next_offset(vertiports.len()),
+        vertiports,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::grpc::server::grpc_server::Coordinates;
     use crate::postgis::utils;
+    use grpc_server::{ActiveWindow, Weekday};
     use lib_common::uuid::Uuid;
 
     fn square(latitude: f64, longitude: f64) -> Vec<(f64, f64)> {
@@ -341,6 +996,10 @@ mod tests {
                 identifier: Uuid::new_v4().to_string(),
                 altitude_meters: *altitude_meters,
                 timestamp_network: Some(Utc::now().into()),
+                status: None,
+                layers: None,
+                compact_vertices: None,
+                operating_hours: vec![],
             })
             .collect();
 
@@ -381,6 +1040,10 @@ mod tests {
                 identifier: Uuid::new_v4().to_string(),
                 altitude_meters: 10.0,
                 timestamp_network: Some(Utc::now().into()),
+                status: None,
+                layers: None,
+                compact_vertices: None,
+                operating_hours: vec![],
             })
             .collect();
 
@@ -409,6 +1072,10 @@ mod tests {
                 identifier: identifier.to_string(),
                 altitude_meters: 10.0,
                 timestamp_network: Some(Utc::now().into()),
+                status: None,
+                layers: None,
+                compact_vertices: None,
+                operating_hours: vec![],
             }];
 
             let result = update_vertiports(vertiports).await.unwrap_err();
@@ -484,6 +1151,260 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ut_violations() {
+        let vertiport = |identifier: &str, points: Vec<(f64, f64)>| RequestVertiport {
+            identifier: identifier.to_string(),
+            vertices: points
+                .into_iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude,
+                    longitude,
+                })
+                .collect(),
+            timestamp_network: Some(Utc::now().into()),
+            ..Default::default()
+        };
+
+        let mut open = square(52.3745905, 4.9160036);
+        open[4] = (52.3752144, 4.9153733);
+        let vertiports = vec![
+            vertiport("VP_A", square(52.3745905, 4.9160036)),
+            vertiport("VP_B", open),
+        ];
+
+        let violations = violations(&vertiports);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].field, "vertiports[1].vertices[4]");
+        assert!(violations[0].description.starts_with("vertiport 'VP_B': "));
+    }
+
+    #[test]
+    fn ut_request_invalid_status() {
+        let vertiport = RequestVertiport {
+            identifier: "Vertiport".to_string(),
+            vertices: square(52.3745905, 4.9160036)
+                .iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude: *latitude,
+                    longitude: *longitude,
+                })
+                .collect(),
+            timestamp_network: Some(Utc::now().into()),
+            status: Some(10000),
+            ..Default::default()
+        };
+
+        let error = Vertiport::try_from(vertiport.clone()).err();
+        assert_eq!(error, Some(VertiportError::Status));
+
+        let vertiport = RequestVertiport {
+            status: Some(VertiportStatus::Closed as i32),
+            ..vertiport
+        };
+        let converted = Vertiport::try_from(vertiport).unwrap();
+        assert_eq!(converted.status, Some(VertiportStatus::Closed));
+    }
+
+    #[test]
+    fn ut_request_operating_hours() {
+        let vertiport = RequestVertiport {
+            identifier: "Vertiport".to_string(),
+            vertices: square(52.3745905, 4.9160036)
+                .iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude: *latitude,
+                    longitude: *longitude,
+                })
+                .collect(),
+            timestamp_network: Some(Utc::now().into()),
+            operating_hours: vec![ActiveWindow {
+                days: vec![Weekday::Monday as i32],
+                start_minute: 6 * 60,
+                end_minute: 22 * 60,
+            }],
+            ..Default::default()
+        };
+
+        let converted = Vertiport::try_from(vertiport.clone()).unwrap();
+        assert_eq!(converted.operating_hours, vec![6 * 60, 22 * 60]);
+
+        let vertiport = RequestVertiport {
+            operating_hours: vec![ActiveWindow {
+                days: vec![],
+                start_minute: 6 * 60,
+                end_minute: 22 * 60,
+            }],
+            ..vertiport
+        };
+
+        let error = Vertiport::try_from(vertiport).err();
+        assert_eq!(error, Some(VertiportError::OperatingHours));
+    }
+
+    #[test]
+    fn ut_request_layers() {
+        let coordinates = |half_size: f64| -> Vec<Coordinates> {
+            square(52.3745905, 4.9160036)
+                .iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude: 52.3745905 + (latitude - 52.3745905) * half_size / 0.0001,
+                    longitude: 4.9160036 + (longitude - 4.9160036) * half_size / 0.0001,
+                })
+                .collect()
+        };
+
+        let layers = VertiportLayers {
+            tlof: coordinates(0.00002),
+            fato: coordinates(0.00005),
+            safety_area: coordinates(0.00008),
+            approach_headings_degrees: vec![90.0, 270.0],
+        };
+
+        let vertiport = RequestVertiport {
+            identifier: "Vertiport".to_string(),
+            altitude_meters: 10.0,
+            vertices: coordinates(0.0001),
+            timestamp_network: Some(Utc::now().into()),
+            layers: Some(layers.clone()),
+            ..Default::default()
+        };
+
+        let converted = Vertiport::try_from(vertiport.clone()).unwrap();
+        let converted = converted.layers.unwrap();
+        assert!(converted.tlof.is_some());
+        assert!(converted.safety_area.is_some());
+        assert_eq!(converted.approach_headings, vec![90.0, 270.0]);
+
+        // optional layers may be left out
+        let request = RequestVertiport {
+            layers: Some(VertiportLayers {
+                tlof: vec![],
+                safety_area: vec![],
+                ..layers.clone()
+            }),
+            ..vertiport.clone()
+        };
+        let converted = Vertiport::try_from(request).unwrap().layers.unwrap();
+        assert!(converted.tlof.is_none());
+        assert!(converted.safety_area.is_none());
+
+        let invalid = [
+            // FATO larger than the vertiport
+            VertiportLayers {
+                fato: coordinates(0.0002),
+                ..layers.clone()
+            },
+            // TLOF larger than the FATO
+            VertiportLayers {
+                tlof: coordinates(0.00006),
+                ..layers.clone()
+            },
+            // safety area within the FATO
+            VertiportLayers {
+                safety_area: coordinates(0.00003),
+                ..layers.clone()
+            },
+            // no FATO
+            VertiportLayers {
+                fato: vec![],
+                ..layers.clone()
+            },
+            VertiportLayers {
+                approach_headings_degrees: vec![f32::NAN],
+                ..layers.clone()
+            },
+            VertiportLayers {
+                approach_headings_degrees: vec![-10.0],
+                ..layers.clone()
+            },
+        ];
+
+        for layers in invalid {
+            let request = RequestVertiport {
+                layers: Some(layers),
+                ..vertiport.clone()
+            };
+
+            let error = Vertiport::try_from(request).err();
+            assert_eq!(error, Some(VertiportError::Layers));
+        }
+    }
+
+    #[tokio::test]
+    async fn ut_set_vertiport_status_invalid() {
+        let request = SetVertiportStatusRequest {
+            identifier: "Vertiport;".to_string(),
+            status: VertiportStatus::Closed as i32,
+        };
+        let error = set_vertiport_status(request).await.unwrap_err();
+        assert_eq!(error, PostgisError::Vertiport(VertiportError::Identifier));
+
+        let request = SetVertiportStatusRequest {
+            identifier: "Vertiport".to_string(),
+            status: 10000,
+        };
+        let error = set_vertiport_status(request).await.unwrap_err();
+        assert_eq!(error, PostgisError::Vertiport(VertiportError::Status));
+    }
+
+    #[test]
+    fn ut_centroid_cache() {
+        let centroid = |x: f64, status: VertiportStatus| Centroid {
+            geom: PointZ {
+                x,
+                y: 52.3746,
+                z: 0.0,
+                srid: Some(DEFAULT_SRID),
+            },
+            status,
+            operating_hours: vec![],
+        };
+
+        let key = |identifier: &str| identifier.to_string();
+        let ttl = Duration::from_secs(60);
+        let now = Instant::now();
+        let mut cache = VertiportCache::default();
+        assert_eq!(cache.get(&key("VP-A"), now, ttl), None);
+
+        let a = centroid(4.91, VertiportStatus::Operational);
+        let b = Centroid {
+            operating_hours: vec![6 * 60, 22 * 60],
+            ..centroid(4.92, VertiportStatus::Closed)
+        };
+
+        assert!(cache.insert(key("VP-A"), a.clone(), 0, now, ttl));
+        assert!(cache.insert(key("VP-B"), b.clone(), 0, now, ttl));
+        assert_eq!(
+            cache.get(&key("VP-A"), now + Duration::from_secs(59), ttl),
+            Some(a)
+        );
+        assert_eq!(cache.get(&key("VP-B"), now, ttl), Some(b));
+
+        // expired entries are read again, and dropped on the next insert
+        let later = now + ttl;
+        assert_eq!(cache.get(&key("VP-A"), later, ttl), None);
+        let c = centroid(4.93, VertiportStatus::Operational);
+        assert!(cache.insert(key("VP-C"), c.clone(), 0, later, ttl));
+        assert_eq!(cache.entries.len(), 1);
+
+        // changed vertiports are dropped
+        cache.remove(|identifier| identifier == "VP-C");
+        assert_eq!(cache.get(&key("VP-C"), later, ttl), None);
+
+        // a read started before the change is not kept
+        assert!(!cache.insert(key("VP-C"), c.clone(), 0, later, ttl));
+        assert_eq!(cache.get(&key("VP-C"), later, ttl), None);
+        assert!(cache.insert(key("VP-C"), c.clone(), 1, later, ttl));
+        assert_eq!(cache.get(&key("VP-C"), later, ttl), Some(c));
+    }
+
+    #[test]
+    fn test_get_order_column() {
+        assert_eq!(get_order_column(OrderBy::Identifier), r#""identifier""#);
+        assert_eq!(get_order_column(OrderBy::LastUpdated), r#""last_updated""#);
+    }
+
     #[test]
     fn test_vertiport_error_display() {
         let error = VertiportError::VertiportId;
@@ -506,6 +1427,27 @@ mod tests {
 
         let error = VertiportError::Timestamp;
         assert_eq!(error.to_string(), "Invalid timestamp provided.");
+
+        let error = VertiportError::Status;
+        assert_eq!(error.to_string(), "Invalid status provided.");
+
+        let error = VertiportError::Closed;
+        assert_eq!(error.to_string(), "Vertiport is closed.");
+
+        let error = VertiportError::NotFound;
+        assert_eq!(error.to_string(), "Vertiport not found.");
+
+        let error = VertiportError::Pagination;
+        assert_eq!(error.to_string(), "Invalid pagination provided.");
+
+        let error = VertiportError::Layers;
+        assert_eq!(error.to_string(), "Invalid vertiport layers provided.");
+
+        let error = VertiportError::Encoding;
+        assert_eq!(error.to_string(), "Invalid geometry encoding provided.");
+
+        let error = VertiportError::OperatingHours;
+        assert_eq!(error.to_string(), "Invalid operating hours provided.");
     }
 
     #[test]
@@ -0,0 +1,327 @@
+//! Checks operating volumes against zones, filed flights and volume
+//!  reservations in the PostGIS database.
+
+use super::PostgisError;
+use crate::grpc::server::grpc_server::{CheckVolumeRequest, CheckVolumeResponse, VolumeFlight};
+use deadpool_postgres::Object;
+use lib_common::time::{DateTime, Utc};
+use postgis::ewkb::PolygonZ;
+use std::fmt::{self, Display, Formatter};
+
+/// Possible errors with volume requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VolumeError {
+    /// Invalid Location
+    Location,
+
+    /// Invalid Altitude Range
+    Altitude,
+
+    /// Invalid Time Provided
+    Time,
+
+    /// Could not get client
+    Client,
+
+    /// DBError error
+    DBError,
+}
+
+impl Display for VolumeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            VolumeError::Location => write!(f, "Invalid location provided."),
+            VolumeError::Altitude => write!(f, "Invalid altitude range provided."),
+            VolumeError::Time => write!(f, "Invalid time provided."),
+            VolumeError::Client => write!(f, "Could not get backend client."),
+            VolumeError::DBError => write!(f, "Unknown backend error."),
+        }
+    }
+}
+
+/// A polygon extended between two altitudes, during a time window
+#[derive(Debug)]
+struct Volume {
+    geom: PolygonZ,
+    altitude_meters_min: f32,
+    altitude_meters_max: f32,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+    include_simulated: bool,
+}
+
+impl TryFrom<CheckVolumeRequest> for Volume {
+    type Error = VolumeError;
+
+    fn try_from(request: CheckVolumeRequest) -> Result<Self, Self::Error> {
+        let altitude = request.altitude_meters_min;
+        let geom =
+            super::utils::polygon_from_vertices_z(&request.vertices, altitude).map_err(|e| {
+                postgis_error!("invalid volume polygon: {}", e);
+                VolumeError::Location
+            })?;
+
+        if request.altitude_meters_min > request.altitude_meters_max {
+            postgis_error!(
+                "altitude_meters_min ({}) > altitude_meters_max ({}).",
+                request.altitude_meters_min,
+                request.altitude_meters_max
+            );
+            return Err(VolumeError::Altitude);
+        }
+
+        let time_start: DateTime<Utc> = request
+            .time_start
+            .ok_or_else(|| {
+                postgis_error!("time_start is required.");
+                VolumeError::Time
+            })?
+            .into();
+
+        let time_end: DateTime<Utc> = request
+            .time_end
+            .ok_or_else(|| {
+                postgis_error!("time_end is required.");
+                VolumeError::Time
+            })?
+            .into();
+
+        if time_end < time_start {
+            postgis_error!("time_end ({time_end}) is before time_start ({time_start}).");
+            return Err(VolumeError::Time);
+        }
+
+        Ok(Volume {
+            geom,
+            altitude_meters_min: request.altitude_meters_min,
+            altitude_meters_max: request.altitude_meters_max,
+            time_start,
+            time_end,
+            include_simulated: request.include_simulated,
+        })
+    }
+}
+
+/// Get a client from the PostGIS connection pool
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_client() -> Result<Object, PostgisError> {
+    crate::postgis::DEADPOOL_POSTGIS
+        .get()
+        .ok_or_else(|| {
+            postgis_error!("could not get psql pool.");
+            PostgisError::Volume(VolumeError::Client)
+        })?
+        .get()
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get client from psql connection pool: {}", e);
+            PostgisError::Volume(VolumeError::Client)
+        })
+}
+
+/// Converts a flight row into the GRPC type
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test, no way to create a Row without querying it
+fn process_flight_row(row: tokio_postgres::Row) -> Result<VolumeFlight, tokio_postgres::Error> {
+    let time_start: Option<DateTime<Utc>> = row.try_get("time_start")?;
+    let time_end: Option<DateTime<Utc>> = row.try_get("time_end")?;
+
+    Ok(VolumeFlight {
+        flight_identifier: row.try_get("flight_identifier")?,
+        aircraft_identifier: row.try_get("aircraft_identifier")?,
+        time_start: time_start.map(Into::into),
+        time_end: time_end.map(Into::into),
+    })
+}
+
+/// Gets the zones, filed flights and volume reservations that intersect the
+///  requested volume during the requested time window
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need psql backend to test
+pub async fn check_volume(
+    request: CheckVolumeRequest,
+) -> Result<CheckVolumeResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let volume = Volume::try_from(request).map_err(PostgisError::Volume)?;
+    let client = get_client().await?;
+
+    let zone_stmt = super::zone::get_zone_volume_intersection_stmt(&client).await?;
+    let rows = client
+        .query(
+            &zone_stmt,
+            &[
+                &volume.geom,
+                &volume.altitude_meters_min,
+                &volume.altitude_meters_max,
+                &volume.time_start,
+                &volume.time_end,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not query for zone intersections: {}", e);
+            PostgisError::Volume(VolumeError::DBError)
+        })?;
+
+    // Recurring zones only block the volume during their active windows
+    let mut zone_identifiers = vec![];
+    for row in rows {
+        let schedule: Vec<i32> = row.try_get("schedule").map_err(|e| {
+            postgis_error!("could not get zone schedule: {}", e);
+            PostgisError::Volume(VolumeError::DBError)
+        })?;
+
+        if super::zone::schedule_overlaps(&schedule, volume.time_start, volume.time_end) {
+            zone_identifiers.push(row.try_get("identifier").map_err(|e| {
+                postgis_error!("could not get zone identifier: {}", e);
+                PostgisError::Volume(VolumeError::DBError)
+            })?);
+        }
+    }
+
+    let flight_stmt = super::flight::get_flight_volume_intersection_stmt(&client).await?;
+    let flights = client
+        .query(
+            &flight_stmt,
+            &[
+                &volume.geom,
+                &(volume.altitude_meters_min as f64),
+                &(volume.altitude_meters_max as f64),
+                &volume.time_start,
+                &volume.time_end,
+                &volume.include_simulated,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not query for flight intersections: {}", e);
+            PostgisError::Volume(VolumeError::DBError)
+        })?
+        .into_iter()
+        .map(process_flight_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            postgis_error!("could not get flight data: {}", e);
+            PostgisError::Volume(VolumeError::DBError)
+        })?;
+
+    let reservation_identifiers = super::reservation::volume_reservations(
+        &client,
+        &volume.geom,
+        volume.altitude_meters_min,
+        volume.altitude_meters_max,
+        volume.time_start,
+        volume.time_end,
+    )
+    .await
+    .map_err(|_| PostgisError::Volume(VolumeError::DBError))?;
+
+    postgis_debug!(
+        "found {} zones, {} flights and {} reservations.",
+        zone_identifiers.len(),
+        flights.len(),
+        reservation_identifiers.len()
+    );
+
+    Ok(CheckVolumeResponse {
+        zone_identifiers,
+        flights,
+        reservation_identifiers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::rectangle;
+    use lib_common::time::Duration;
+
+    fn request() -> CheckVolumeRequest {
+        CheckVolumeRequest {
+            vertices: rectangle((4.915, 52.374), (4.917, 52.376)),
+            altitude_meters_min: 20.0,
+            altitude_meters_max: 120.0,
+            time_start: Some(Utc::now().into()),
+            time_end: Some((Utc::now() + Duration::try_hours(1).unwrap()).into()),
+            include_simulated: false,
+        }
+    }
+
+    #[test]
+    fn ut_volume_valid() {
+        let volume = Volume::try_from(request()).unwrap();
+        assert_eq!(volume.altitude_meters_min, 20.0);
+        assert_eq!(volume.altitude_meters_max, 120.0);
+        assert_eq!(volume.geom.rings[0].points.len(), 5);
+        assert!(volume.time_start < volume.time_end);
+        assert!(!volume.include_simulated);
+
+        let volume = Volume::try_from(CheckVolumeRequest {
+            include_simulated: true,
+            ..request()
+        })
+        .unwrap();
+        assert!(volume.include_simulated);
+    }
+
+    #[test]
+    fn ut_volume_invalid_location() {
+        let mut request = request();
+        request.vertices.pop();
+        let error = Volume::try_from(request).unwrap_err();
+        assert_eq!(error, VolumeError::Location);
+
+        let mut request = self::request();
+        request.vertices[1].latitude = 91.0;
+        let error = Volume::try_from(request).unwrap_err();
+        assert_eq!(error, VolumeError::Location);
+    }
+
+    #[test]
+    fn ut_volume_invalid_altitude() {
+        let mut request = request();
+        request.altitude_meters_min = 200.0;
+        let error = Volume::try_from(request).unwrap_err();
+        assert_eq!(error, VolumeError::Altitude);
+    }
+
+    #[test]
+    fn ut_volume_invalid_time() {
+        let mut request = request();
+        request.time_start = None;
+        let error = Volume::try_from(request).unwrap_err();
+        assert_eq!(error, VolumeError::Time);
+
+        let mut request = self::request();
+        request.time_end = None;
+        let error = Volume::try_from(request).unwrap_err();
+        assert_eq!(error, VolumeError::Time);
+
+        let mut request = self::request();
+        std::mem::swap(&mut request.time_start, &mut request.time_end);
+        let error = Volume::try_from(request).unwrap_err();
+        assert_eq!(error, VolumeError::Time);
+    }
+
+    #[test]
+    fn test_volume_error_display() {
+        assert_eq!(
+            format!("{}", VolumeError::Location),
+            "Invalid location provided."
+        );
+        assert_eq!(
+            format!("{}", VolumeError::Altitude),
+            "Invalid altitude range provided."
+        );
+        assert_eq!(format!("{}", VolumeError::Time), "Invalid time provided.");
+        assert_eq!(
+            format!("{}", VolumeError::Client),
+            "Could not get backend client."
+        );
+        assert_eq!(
+            format!("{}", VolumeError::DBError),
+            "Unknown backend error."
+        );
+    }
+}
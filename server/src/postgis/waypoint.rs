@@ -1,14 +1,18 @@
 //! Updates waypoints in the PostGIS database.
 
-use super::{PostgisError, PSQL_SCHEMA};
+use super::identifier::{check_identifier, Entity};
+use super::statements::Statement;
+use super::utils::{field_violation, Pagination};
+use super::{OnceCell, PostgisError};
 use crate::grpc::server::grpc_server;
 use deadpool_postgres::Object;
 use grpc_server::Waypoint as RequestWaypoint;
+use grpc_server::{
+    Coordinates, FieldViolation, GetWaypointsRequest, GetWaypointsResponse, OrderBy,
+    WaypointCluster,
+};
 use std::fmt::{self, Display, Formatter};
 
-/// Allowed characters in a waypoint identifier
-const IDENTIFIER_REGEX: &str = r"^[\-0-9A-Za-z_\.]{1,255}$";
-
 /// Possible conversion errors from the GRPC type to GIS type
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum WaypointError {
@@ -26,6 +30,12 @@ pub enum WaypointError {
 
     /// DBError error
     DBError,
+
+    /// Invalid pagination options
+    Pagination,
+
+    /// Invalid map zoom level
+    Zoom,
 }
 
 impl Display for WaypointError {
@@ -36,14 +46,43 @@ impl Display for WaypointError {
             WaypointError::Location => write!(f, "Invalid location provided."),
             WaypointError::Client => write!(f, "Could not get backend client."),
             WaypointError::DBError => write!(f, "Database error."),
+            WaypointError::Pagination => write!(f, "Invalid pagination provided."),
+            WaypointError::Zoom => write!(f, "Invalid zoom level provided."),
         }
     }
 }
 
+/// Highest map zoom level waypoints can be clustered at
+pub const MAX_CLUSTER_ZOOM: u32 = 22;
+
+/// Clusters across the width of a map tile, about one per 32 pixels of
+///  a 256 pixel tile
+const CLUSTERS_PER_TILE: f64 = 8.0;
+
+/// Size in degrees of the grid cells waypoints are clustered in at a map
+///  zoom level. A tile spans 360 degrees of longitude at zoom 0 and half
+///  as many at each following level.
+pub fn cluster_cell_degrees(zoom: u32) -> Result<f64, WaypointError> {
+    if zoom > MAX_CLUSTER_ZOOM {
+        postgis_error!("zoom level {} is above {}.", zoom, MAX_CLUSTER_ZOOM);
+        return Err(WaypointError::Zoom);
+    }
+
+    Ok(360.0 / 2f64.powi(zoom as i32) / CLUSTERS_PER_TILE)
+}
+
 /// Gets the name of this module's table
-fn get_table_name() -> &'static str {
-    static FULL_NAME: &str = const_format::formatcp!(r#""{PSQL_SCHEMA}"."waypoints""#,);
-    FULL_NAME
+/// pub(super) so that zone waypoints can be generated by the zones module
+pub(super) fn get_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."waypoints""#, super::get_schema()))
+}
+
+/// Gets the name of the table recording renamed zone waypoints
+/// pub(super) so that renames can be recorded by the zones module
+pub(super) fn get_renames_table_name() -> &'static str {
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."waypoint_renames""#, super::get_schema()))
 }
 
 /// Get a client from the PostGIS connection pool
@@ -79,7 +118,7 @@ impl TryFrom<RequestWaypoint> for Waypoint {
     type Error = WaypointError;
 
     fn try_from(waypoint: RequestWaypoint) -> Result<Self, Self::Error> {
-        if let Err(e) = super::utils::check_string(&waypoint.identifier, IDENTIFIER_REGEX) {
+        if let Err(e) = check_identifier(Entity::Waypoint, &waypoint.identifier) {
             postgis_error!(
                 "Invalid waypoint identifier: {}; {}",
                 waypoint.identifier,
@@ -106,6 +145,29 @@ impl TryFrom<RequestWaypoint> for Waypoint {
     }
 }
 
+/// Gets the waypoints of an update that would be rejected, and why
+pub fn violations(waypoints: &[RequestWaypoint]) -> Vec<FieldViolation> {
+    waypoints
+        .iter()
+        .enumerate()
+        .filter_map(|(index, waypoint)| {
+            let error = Waypoint::try_from(waypoint.clone()).err()?;
+            let field = match error {
+                WaypointError::Identifier => format!("waypoints[{index}].identifier"),
+                WaypointError::Location => format!("waypoints[{index}].location"),
+                _ => format!("waypoints[{index}]"),
+            };
+
+            Some(field_violation(
+                field,
+                "waypoint",
+                &waypoint.identifier,
+                error,
+            ))
+        })
+        .collect()
+}
+
 /// Initialize the vertiports table in the PostGIS database
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need running psql backend, integration test
@@ -115,14 +177,40 @@ pub async fn psql_init() -> Result<(), PostgisError> {
         format!(
             r#"CREATE TABLE IF NOT EXISTS {table_name} (
             "identifier" VARCHAR(255) UNIQUE NOT NULL,
-            "geog" GEOGRAPHY NOT NULL
+            "geog" GEOGRAPHY NOT NULL,
+            "zone_id" INTEGER REFERENCES {zones_table_name} ("id") ON DELETE CASCADE
         );"#,
-            table_name = get_table_name()
+            table_name = get_table_name(),
+            zones_table_name = super::zone::get_table_name(),
         ),
         format!(
             r#"CREATE INDEX IF NOT EXISTS "waypoints_geog_idx" ON {table_name} USING GIST ("geog");"#,
             table_name = get_table_name()
         ),
+        // Tables created by earlier versions have no zone reference
+        format!(
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "zone_id" INTEGER
+                REFERENCES {zones_table_name} ("id") ON DELETE CASCADE;"#,
+            table_name = get_table_name(),
+            zones_table_name = super::zone::get_table_name(),
+        ),
+        // Published waypoints are tagged with the source they were imported from
+        format!(
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "source" VARCHAR(32);"#,
+            table_name = get_table_name(),
+        ),
+        format!(
+            r#"CREATE TABLE IF NOT EXISTS {renames_table_name} (
+            "id" SERIAL PRIMARY KEY,
+            "zone_id" INTEGER NOT NULL,
+            "old_identifier" VARCHAR(255) NOT NULL,
+            "new_identifier" VARCHAR(255) NOT NULL,
+            "renamed_at" TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );"#,
+            renames_table_name = get_renames_table_name(),
+        ),
     ];
 
     super::psql_transaction(statements).await
@@ -150,18 +238,7 @@ pub async fn update_waypoints(waypoints: Vec<RequestWaypoint>) -> Result<(), Pos
     })?;
 
     let stmt = transaction
-        .prepare_cached(&format!(
-            r#"INSERT INTO {table_name} (
-            "identifier",
-            "geog"
-        )
-        VALUES ($1, $2::geography)
-        ON CONFLICT ("identifier")
-        DO UPDATE
-            SET "geog" = EXCLUDED."geog";
-        "#,
-            table_name = get_table_name()
-        ))
+        .prepare_cached(&Statement::UpsertWaypoint.sql())
         .await
         .map_err(|e| {
             postgis_error!("could not prepare cached statement: {}", e);
@@ -183,6 +260,7 @@ pub async fn update_waypoints(waypoints: Vec<RequestWaypoint>) -> Result<(), Pos
         PostgisError::Waypoint(WaypointError::DBError)
     })?;
 
+    super::vertiport::invalidate_route_waypoints();
     postgis_debug!("success.");
     Ok(())
 }
@@ -200,19 +278,13 @@ pub async fn get_waypoints_near_geometry(
 
     // Get a subset of waypoints within N meters of the line between the origin and target
     //  This saves computation time by doing shortest path on a smaller graph
-    let stmt = format!(
-        r#"SELECT
-            "identifier",
-            "geog"
-        FROM {table_name}
-        WHERE ST_DWithin(
-            "geog",
-            $1::geography, -- ignores Z-axis
-            $2::FLOAT(4),
-            false
-        );"#,
-        table_name = get_table_name()
-    );
+    let stmt = client
+        .prepare_cached(&Statement::GetWaypointsNearGeometry.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Waypoint(WaypointError::DBError)
+        })?;
 
     let result = client
         .query(&stmt, &[&geom, &range_meters])
@@ -240,6 +312,134 @@ pub async fn get_waypoints_near_geometry(
     Ok(result)
 }
 
+/// Gets a page of waypoints from the PostGIS database
+///  Waypoints have no update timestamp and can only be ordered by identifier
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+pub async fn get_waypoints(
+    request: GetWaypointsRequest,
+) -> Result<GetWaypointsResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let page = Pagination::try_from(request.page).map_err(|e| {
+        postgis_error!("invalid pagination: {}", e);
+        PostgisError::Waypoint(WaypointError::Pagination)
+    })?;
+
+    if page.order_by != OrderBy::Identifier {
+        postgis_error!("waypoints can't be ordered by {:?}.", page.order_by);
+        return Err(PostgisError::Waypoint(WaypointError::Pagination));
+    }
+
+    if let Some(zoom) = request.zoom {
+        let cell_degrees = cluster_cell_degrees(zoom).map_err(PostgisError::Waypoint)?;
+        return get_waypoint_clusters(page, cell_degrees).await;
+    }
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(
+            &Statement::GetWaypoints {
+                descending: page.descending,
+            }
+            .sql(),
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Waypoint(WaypointError::DBError)
+        })?;
+
+    let waypoints = client
+        .query(&stmt, &[&(page.limit as i64), &(page.offset as i64)])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not query waypoints: {}", e);
+            PostgisError::Waypoint(WaypointError::DBError)
+        })?
+        .into_iter()
+        .map(|row| {
+            let geom: postgis::ewkb::Point = row.try_get("geog")?;
+            Ok(RequestWaypoint {
+                identifier: row.try_get("identifier")?,
+                location: Some(Coordinates {
+                    latitude: geom.y,
+                    longitude: geom.x,
+                }),
+            })
+        })
+        .collect::<Result<Vec<RequestWaypoint>, tokio_postgres::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not get waypoint data: {}", e);
+            PostgisError::Waypoint(WaypointError::DBError)
+        })?;
+
+    Ok(GetWaypointsResponse {
+        next_offset: page.next_offset(waypoints.len()),
+        waypoints,
+        clusters: vec![],
+    })
+}
+
+/// Gets a page of waypoint clusters, grouping the waypoints that snap to
+///  the same grid cell. Clusters are ordered by their lowest identifier.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need running psql backend, integration test
+async fn get_waypoint_clusters(
+    page: Pagination,
+    cell_degrees: f64,
+) -> Result<GetWaypointsResponse, PostgisError> {
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(
+            &Statement::GetWaypointClusters {
+                descending: page.descending,
+            }
+            .sql(),
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Waypoint(WaypointError::DBError)
+        })?;
+
+    let clusters = client
+        .query(
+            &stmt,
+            &[&cell_degrees, &(page.limit as i64), &(page.offset as i64)],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not query waypoint clusters: {}", e);
+            PostgisError::Waypoint(WaypointError::DBError)
+        })?
+        .into_iter()
+        .map(|row| {
+            let geom: postgis::ewkb::Point = row.try_get("centroid")?;
+            let count: i64 = row.try_get("count")?;
+            let identifier: String = row.try_get("identifier")?;
+
+            Ok(WaypointCluster {
+                location: Some(Coordinates {
+                    latitude: geom.y,
+                    longitude: geom.x,
+                }),
+                count: count as u32,
+                identifier: (count == 1).then_some(identifier),
+            })
+        })
+        .collect::<Result<Vec<WaypointCluster>, tokio_postgres::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not get waypoint cluster data: {}", e);
+            PostgisError::Waypoint(WaypointError::DBError)
+        })?;
+
+    Ok(GetWaypointsResponse {
+        next_offset: page.next_offset(clusters.len()),
+        waypoints: vec![],
+        clusters,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -358,6 +558,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ut_violations() {
+        let waypoints = vec![
+            RequestWaypoint {
+                identifier: "Waypoint-0".to_string(),
+                location: Some(Coordinates {
+                    latitude: 52.3745905,
+                    longitude: 4.9160036,
+                }),
+            },
+            RequestWaypoint {
+                identifier: "Waypoint-1".to_string(),
+                location: Some(Coordinates {
+                    latitude: 90.1,
+                    longitude: 0.0,
+                }),
+            },
+            RequestWaypoint {
+                identifier: "Waypoint 2".to_string(),
+                location: None,
+            },
+        ];
+
+        let violations = violations(&waypoints);
+        assert_eq!(
+            violations,
+            vec![
+                FieldViolation {
+                    field: "waypoints[1].location".to_string(),
+                    description: format!("waypoint 'Waypoint-1': {}", WaypointError::Location),
+                },
+                FieldViolation {
+                    field: "waypoints[2].identifier".to_string(),
+                    description: format!("waypoint 'Waypoint 2': {}", WaypointError::Identifier),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_waypoint_error_display() {
         let error = WaypointError::NoWaypoints;
@@ -374,5 +613,58 @@ mod tests {
 
         let error = WaypointError::DBError;
         assert_eq!(error.to_string(), "Database error.");
+
+        let error = WaypointError::Pagination;
+        assert_eq!(error.to_string(), "Invalid pagination provided.");
+
+        let error = WaypointError::Zoom;
+        assert_eq!(error.to_string(), "Invalid zoom level provided.");
+    }
+
+    #[test]
+    fn ut_cluster_cell_degrees() {
+        assert_eq!(cluster_cell_degrees(0).unwrap(), 45.0);
+        assert_eq!(cluster_cell_degrees(1).unwrap(), 22.5);
+        assert!(cluster_cell_degrees(MAX_CLUSTER_ZOOM).unwrap() > 0.0);
+        assert_eq!(
+            cluster_cell_degrees(MAX_CLUSTER_ZOOM + 1).unwrap_err(),
+            WaypointError::Zoom
+        );
+    }
+
+    #[tokio::test]
+    async fn ut_get_waypoints_invalid_zoom() {
+        let request = GetWaypointsRequest {
+            page: None,
+            zoom: Some(MAX_CLUSTER_ZOOM + 1),
+        };
+
+        let result = get_waypoints(request).await.unwrap_err();
+        assert_eq!(result, PostgisError::Waypoint(WaypointError::Zoom));
+    }
+
+    #[tokio::test]
+    async fn ut_get_waypoints_invalid_page() {
+        let request = GetWaypointsRequest {
+            page: Some(grpc_server::Page {
+                order_by: OrderBy::LastUpdated as i32,
+                ..Default::default()
+            }),
+            zoom: None,
+        };
+
+        let result = get_waypoints(request).await.unwrap_err();
+        assert_eq!(result, PostgisError::Waypoint(WaypointError::Pagination));
+
+        let request = GetWaypointsRequest {
+            page: Some(grpc_server::Page {
+                limit: Some(0),
+                ..Default::default()
+            }),
+            zoom: None,
+        };
+
+        let result = get_waypoints(request).await.unwrap_err();
+        assert_eq!(result, PostgisError::Waypoint(WaypointError::Pagination));
     }
 }
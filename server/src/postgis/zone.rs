@@ -1,17 +1,52 @@
 //! This module contains functions for updating zones in the PostGIS database.
 //! Zones have various restrictions and can be permanent or temporary.
 
-use super::{PostgisError, DEFAULT_SRID, PSQL_SCHEMA};
+use super::capabilities::ZoneVolumes;
+use super::identifier::{check_identifier, Entity};
+use super::simplify;
+use super::statements::{Ordering, Statement};
+use super::utils::{compact_ring, field_violation, item_validation, vertices_field, Pagination};
+use super::zone_events::ZoneTimes;
+use super::{OnceCell, PostgisError, DEFAULT_SRID};
+use crate::cache::{Consumer, ItemProcessor, Processor};
 use crate::grpc::server::grpc_server;
+use crate::types::{ZoneEvent, ZoneUpdate, ZoneVertex, ZoneWindow};
 use deadpool_postgres::Object;
+use grpc_server::UpdateZoneAttributesRequest;
 use grpc_server::Zone as RequestZone;
+use grpc_server::ZonePart;
 use grpc_server::ZoneType;
-use lib_common::time::{DateTime, Utc};
+use grpc_server::{ActiveWindow, Weekday};
+use grpc_server::{Coordinates, GeometryEncoding, GetZonesRequest, GetZonesResponse, OrderBy};
+use grpc_server::{FieldViolation, ItemValidation};
+use grpc_server::{GetZonesAtPointRequest, GetZonesAtPointResponse};
+use grpc_server::{UpdateZonesSummary, WaypointCandidate, ZoneFailure, ZoneMetadata};
+use lib_common::time::{DateTime, Datelike, Timelike, Utc};
 use num_traits::FromPrimitive;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Display, Formatter};
+use tonic::async_trait;
 
-/// Allowed characters in a identifier
-const IDENTIFIER_REGEX: &str = r"^[\-0-9A-Za-z_\.]{1,255}$";
+/// Default standoff of waypoints around vertiport zones
+pub const PORT_WAYPOINT_BUFFER_METERS: f32 = 20.0;
+
+/// Default standoff of waypoints around restricted zones
+pub const RESTRICTION_WAYPOINT_BUFFER_METERS: f32 = 100.0;
+
+/// Upper bound on per-zone waypoint standoff overrides
+pub const MAX_WAYPOINT_BUFFER_METERS: f32 = 5000.0;
+
+/// Max length of a zone description
+pub const MAX_DESCRIPTION_LENGTH: usize = 4000;
+
+/// Max length of the authority, contact and source URL of a zone
+pub const MAX_METADATA_FIELD_LENGTH: usize = 255;
+
+/// Minutes in a day
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// Minutes in a week
+const MINUTES_PER_WEEK: i32 = 7 * MINUTES_PER_DAY;
 
 #[derive(Clone, Debug)]
 /// Nodes that aircraft can fly between
@@ -22,8 +57,9 @@ pub struct Zone {
     /// The type of zone
     pub zone_type: ZoneType,
 
-    /// The geometry string to feed into PSQL
-    pub geom: postgis::ewkb::PolygonZ,
+    /// The polygons covered by this zone, at the minimum altitude
+    ///  The first polygon is the zone's `vertices`, followed by its parts
+    pub geom: postgis::ewkb::MultiPolygonZ,
 
     /// The minimum altitude of the zone
     pub altitude_meters_min: f32,
@@ -36,6 +72,32 @@ pub struct Zone {
 
     /// The end time of the zone, if applicable
     pub time_end: Option<DateTime<Utc>>,
+
+    /// Active windows as [start, end) pairs of minutes into the week
+    ///  Minute 0 is Monday 00:00 UTC. Empty if always active.
+    pub schedule: Vec<i32>,
+
+    /// Waypoint standoff override for this zone
+    pub waypoint_buffer_meters: Option<f32>,
+
+    /// Descriptive information, not used for routing
+    pub metadata: ZoneMetadata,
+}
+
+impl Zone {
+    /// Gets the standoff distance of waypoints placed around this zone
+    pub fn waypoint_buffer_meters(&self) -> f32 {
+        self.waypoint_buffer_meters
+            .unwrap_or_else(|| default_waypoint_buffer_meters(self.zone_type))
+    }
+}
+
+/// Gets the default standoff distance of waypoints around a zone type
+pub fn default_waypoint_buffer_meters(zone_type: ZoneType) -> f32 {
+    match zone_type {
+        ZoneType::Port => PORT_WAYPOINT_BUFFER_METERS,
+        ZoneType::Restriction => RESTRICTION_WAYPOINT_BUFFER_METERS,
+    }
 }
 
 /// Possible conversion errors from the GRPC type to GIS type
@@ -64,6 +126,30 @@ pub enum ZoneError {
 
     /// Invalid zone type
     ZoneType,
+
+    /// Invalid pagination options
+    Pagination,
+
+    /// Invalid geometry encoding
+    Encoding,
+
+    /// Invalid recurring schedule
+    Schedule,
+
+    /// Altitude minimum is greater than the maximum
+    AltitudeOrder,
+
+    /// Zone does not exist
+    NotFound,
+
+    /// Invalid waypoint buffer distance
+    WaypointBuffer,
+
+    /// Invalid description, authority, contact or source URL
+    Metadata,
+
+    /// The backend refused the data of the zone
+    Rejected,
 }
 
 impl Display for ZoneError {
@@ -77,10 +163,34 @@ impl Display for ZoneError {
             ZoneError::DBError => write!(f, "Unknown backend error."),
             ZoneError::Identifier => write!(f, "Invalid identifier provided."),
             ZoneError::ZoneType => write!(f, "Invalid zone type provided."),
+            ZoneError::Pagination => write!(f, "Invalid pagination provided."),
+            ZoneError::Encoding => write!(f, "Invalid geometry encoding provided."),
+            ZoneError::Schedule => write!(f, "Invalid schedule provided."),
+            ZoneError::AltitudeOrder => write!(f, "Minimum altitude is above maximum altitude."),
+            ZoneError::NotFound => write!(f, "Zone not found."),
+            ZoneError::WaypointBuffer => write!(f, "Invalid waypoint buffer provided."),
+            ZoneError::Metadata => write!(f, "Invalid zone metadata provided."),
+            ZoneError::Rejected => write!(f, "Zone rejected by the backend."),
         }
     }
 }
 
+/// Whether a SQLSTATE code reports a problem with the data of a statement,
+///  such as an invalid geometry or an oversized value, which fails the same
+///  way when retried. Connection, transaction, resource, operator, system
+///  and schema errors are not about the data.
+fn is_rejection_code(code: &str) -> bool {
+    !matches!(code.get(..2), Some("08" | "40" | "42" | "53" | "57" | "58"))
+}
+
+/// The zone error of a failed statement
+fn statement_error(e: &tokio_postgres::Error) -> ZoneError {
+    match e.as_db_error() {
+        Some(db_error) if is_rejection_code(db_error.code().code()) => ZoneError::Rejected,
+        _ => ZoneError::DBError,
+    }
+}
+
 /// Gets a client connection to the PostGIS database
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need postgis backend to test
@@ -99,11 +209,385 @@ async fn get_client() -> Result<Object, PostgisError> {
         })
 }
 
+/// Converts weekly active windows into [start, end) pairs of minutes into the week
+///  Windows that run past Sunday midnight are split in two
+pub(super) fn schedule_from_windows(windows: &[ActiveWindow]) -> Result<Vec<i32>, ZoneError> {
+    let mut schedule = vec![];
+    for window in windows {
+        if window.days.is_empty()
+            || window.start_minute >= MINUTES_PER_DAY as u32
+            || window.end_minute >= MINUTES_PER_DAY as u32
+        {
+            postgis_error!("invalid active window: {:?}", window);
+            return Err(ZoneError::Schedule);
+        }
+
+        let start = window.start_minute as i32;
+        let mut end = window.end_minute as i32;
+        if end <= start {
+            end += MINUTES_PER_DAY;
+        }
+
+        for day in &window.days {
+            let day = Weekday::try_from(*day).map_err(|_| {
+                postgis_error!("invalid weekday: {}", day);
+                ZoneError::Schedule
+            })?;
+
+            let offset = day as i32 * MINUTES_PER_DAY;
+            if offset + end > MINUTES_PER_WEEK {
+                schedule.extend([offset + start, MINUTES_PER_WEEK]);
+                schedule.extend([0, offset + end - MINUTES_PER_WEEK]);
+            } else {
+                schedule.extend([offset + start, offset + end]);
+            }
+        }
+    }
+
+    Ok(schedule)
+}
+
+/// Converts stored minute-of-week pairs back into weekly active windows
+pub(super) fn windows_from_schedule(schedule: &[i32]) -> Vec<ActiveWindow> {
+    schedule
+        .chunks_exact(2)
+        .map(|pair| {
+            let day = pair[0] / MINUTES_PER_DAY;
+            let offset = day * MINUTES_PER_DAY;
+            ActiveWindow {
+                days: vec![day],
+                start_minute: (pair[0] - offset) as u32,
+                end_minute: ((pair[1] - offset) % MINUTES_PER_DAY) as u32,
+            }
+        })
+        .collect()
+}
+
+/// Checks if a zone schedule has an active window between the provided times
+///  An empty schedule is always active
+pub fn schedule_overlaps(
+    schedule: &[i32],
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) -> bool {
+    if schedule.is_empty() {
+        return true;
+    }
+
+    // Round outwards to whole minutes
+    let duration = (time_end - time_start).num_minutes().max(0) + 1;
+    if duration >= MINUTES_PER_WEEK as i64 {
+        return true;
+    }
+
+    let start = time_start.weekday().num_days_from_monday() as i32 * MINUTES_PER_DAY
+        + time_start.hour() as i32 * 60
+        + time_start.minute() as i32;
+    let end = start + duration as i32;
+
+    // The time window may run into the following week
+    schedule.chunks_exact(2).any(|pair| {
+        [0, MINUTES_PER_WEEK]
+            .iter()
+            .any(|shift| pair[0] + shift < end && start < pair[1] + shift)
+    })
+}
+
+/// Mean radius of the earth, used to project zone vertices onto a local plane
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Caps how far a waypoint is pushed out at sharp zone corners,
+///  as a multiple of the buffer distance
+const MITER_LIMIT: f64 = 2.0;
+
+/// Scales a 2D vector to unit length, if it has one
+fn normalize(x: f64, y: f64) -> Option<(f64, f64)> {
+    let length = x.hypot(y);
+    if length < f64::EPSILON {
+        return None;
+    }
+
+    Some((x / length, y / length))
+}
+
+/// Places a waypoint outside each vertex of a zone's exterior ring
+///
+/// Each waypoint sits on the bisector of the two edges meeting at the
+///  vertex, far enough out that both edges are cleared by `buffer_meters`.
+///  Vertices are treated on a local flat projection, which holds for
+///  zone-sized polygons. Degenerate vertices (repeated points) are skipped.
+pub fn zone_waypoint_locations(
+    ring: &[postgis::ewkb::Point],
+    buffer_meters: f64,
+) -> Vec<postgis::ewkb::Point> {
+    // The ring is closed, the last vertex repeats the first
+    let vertices = match ring.split_last() {
+        Some((last, rest)) if rest.first().map(|p| (p.x, p.y)) == Some((last.x, last.y)) => rest,
+        _ => ring,
+    };
+
+    if vertices.len() < 3 {
+        return vec![];
+    }
+
+    // Shoelace sum, positive when the ring is counter-clockwise
+    let area: f64 = (0..vertices.len())
+        .map(|i| {
+            let a = &vertices[i];
+            let b = &vertices[(i + 1) % vertices.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+
+    let orientation = if area > 0.0 { 1.0 } else { -1.0 };
+    let meters_per_degree = EARTH_RADIUS_METERS.to_radians();
+
+    let mut waypoints = vec![];
+    for (i, vertex) in vertices.iter().enumerate() {
+        let previous = &vertices[(i + vertices.len() - 1) % vertices.len()];
+        let next = &vertices[(i + 1) % vertices.len()];
+
+        let meters_per_degree_x = meters_per_degree * vertex.y.to_radians().cos();
+        let Some(incoming) = normalize(
+            (vertex.x - previous.x) * meters_per_degree_x,
+            (vertex.y - previous.y) * meters_per_degree,
+        ) else {
+            continue;
+        };
+
+        let Some(outgoing) = normalize(
+            (next.x - vertex.x) * meters_per_degree_x,
+            (next.y - vertex.y) * meters_per_degree,
+        ) else {
+            continue;
+        };
+
+        // Outward normals of both edges
+        let normal_in = (incoming.1 * orientation, -incoming.0 * orientation);
+        let normal_out = (outgoing.1 * orientation, -outgoing.0 * orientation);
+        let Some(bisector) = normalize(normal_in.0 + normal_out.0, normal_in.1 + normal_out.1)
+        else {
+            continue;
+        };
+
+        let cos_half = bisector.0 * normal_in.0 + bisector.1 * normal_in.1;
+        let distance = buffer_meters / cos_half.max(1.0 / MITER_LIMIT);
+
+        waypoints.push(postgis::ewkb::Point {
+            x: vertex.x + bisector.0 * distance / meters_per_degree_x,
+            y: vertex.y + bisector.1 * distance / meters_per_degree,
+            srid: Some(DEFAULT_SRID),
+        });
+    }
+
+    waypoints
+}
+
+/// Headings along the long axis of a FATO, from both ends
+///
+/// The longest edge of the ring gives the orientation, which holds for the
+///  rectangular and square FATOs of surveyed vertiports.
+pub fn fato_axis_headings(ring: &[postgis::ewkb::Point]) -> Vec<f64> {
+    let meters_per_degree = EARTH_RADIUS_METERS.to_radians();
+    let longest = ring
+        .windows(2)
+        .map(|edge| {
+            let meters_per_degree_x = meters_per_degree * edge[0].y.to_radians().cos();
+            (
+                (edge[1].x - edge[0].x) * meters_per_degree_x,
+                (edge[1].y - edge[0].y) * meters_per_degree,
+            )
+        })
+        .max_by(|a, b| a.0.hypot(a.1).total_cmp(&b.0.hypot(b.1)));
+
+    match longest.and_then(|(east, north)| normalize(east, north)) {
+        Some((east, north)) => {
+            let heading = east.atan2(north).to_degrees().rem_euclid(360.0);
+            vec![heading, (heading + 180.0).rem_euclid(360.0)]
+        }
+        None => vec![],
+    }
+}
+
+/// Places an approach waypoint on the extended centerline of each approach
+///  to a FATO
+///
+/// An aircraft flying a heading on final approach comes from the opposite
+///  direction, so the waypoint sits on that side of the FATO, `buffer_meters`
+///  beyond its furthest vertex. Without headings, the long axis of the FATO
+///  is approached from both ends.
+pub fn fato_approach_locations(
+    ring: &[postgis::ewkb::Point],
+    approach_headings_degrees: &[f32],
+    buffer_meters: f64,
+) -> Vec<postgis::ewkb::Point> {
+    // The ring is closed, the last vertex repeats the first
+    let vertices = match ring.split_last() {
+        Some((last, rest)) if rest.first().map(|p| (p.x, p.y)) == Some((last.x, last.y)) => rest,
+        _ => ring,
+    };
+
+    if vertices.len() < 3 {
+        return vec![];
+    }
+
+    let mut headings: Vec<f64> = if approach_headings_degrees.is_empty() {
+        fato_axis_headings(ring)
+    } else {
+        approach_headings_degrees
+            .iter()
+            .map(|heading| (*heading as f64).rem_euclid(360.0))
+            .collect()
+    };
+
+    headings.sort_by(f64::total_cmp);
+    headings.dedup();
+
+    let count = vertices.len() as f64;
+    let center_x = vertices.iter().map(|p| p.x).sum::<f64>() / count;
+    let center_y = vertices.iter().map(|p| p.y).sum::<f64>() / count;
+    let meters_per_degree = EARTH_RADIUS_METERS.to_radians();
+    let meters_per_degree_x = meters_per_degree * center_y.to_radians().cos();
+
+    headings
+        .into_iter()
+        .map(|heading| {
+            let bearing = (heading + 180.0).to_radians();
+            let (east, north) = (bearing.sin(), bearing.cos());
+
+            // Furthest extent of the FATO towards the approach
+            let extent = vertices
+                .iter()
+                .map(|p| {
+                    (p.x - center_x) * meters_per_degree_x * east
+                        + (p.y - center_y) * meters_per_degree * north
+                })
+                .fold(0.0, f64::max);
+
+            let distance = extent + buffer_meters;
+            postgis::ewkb::Point {
+                x: center_x + east * distance / meters_per_degree_x,
+                y: center_y + north * distance / meters_per_degree,
+                srid: Some(DEFAULT_SRID),
+            }
+        })
+        .collect()
+}
+
+/// Places waypoints around the main area and every part of a zone
+pub fn zone_geom_waypoint_locations(zone: &Zone) -> Vec<postgis::ewkb::Point> {
+    let buffer_meters = zone.waypoint_buffer_meters() as f64;
+    zone.geom
+        .polygons
+        .iter()
+        .filter_map(|polygon| polygon.rings.first())
+        .flat_map(|ring| {
+            let points: Vec<postgis::ewkb::Point> = ring
+                .points
+                .iter()
+                .map(|p| postgis::ewkb::Point::new(p.x, p.y, p.srid))
+                .collect();
+
+            zone_waypoint_locations(&points, buffer_meters)
+        })
+        .collect()
+}
+
+/// Precision of the coordinates hashed into a zone waypoint identifier
+///  1e-7 degrees is about 1cm
+const WAYPOINT_IDENTIFIER_PRECISION: f64 = 1e7;
+
+/// Builds a stable identifier for a waypoint generated around a zone
+///
+/// The identifier is derived from the waypoint location (FNV-1a hash), so
+///  waypoints keep their identifier across zone updates as long as the
+///  part of the zone they are placed around does not move.
+pub fn zone_waypoint_identifier(zone_id: i32, point: &postgis::ewkb::Point) -> String {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    let x = (point.x * WAYPOINT_IDENTIFIER_PRECISION).round() as i64;
+    let y = (point.y * WAYPOINT_IDENTIFIER_PRECISION).round() as i64;
+    let hash = x
+        .to_le_bytes()
+        .iter()
+        .chain(y.to_le_bytes().iter())
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        });
+
+    format!("{zone_id}_waypoint_{hash:016x}")
+}
+
+/// Pairs waypoints removed by a zone update with the waypoints that replaced them
+///  The closest pairs are matched first, leftovers on either side are
+///  plain removals or additions
+fn match_waypoint_renames(
+    removed: &[(String, postgis::ewkb::Point)],
+    added: &[(String, postgis::ewkb::Point)],
+) -> Vec<(String, String)> {
+    let pointz = |p: &postgis::ewkb::Point| postgis::ewkb::PointZ::new(p.x, p.y, 0.0, p.srid);
+
+    let mut pairs = vec![];
+    for (old_index, (_, old_point)) in removed.iter().enumerate() {
+        for (new_index, (_, new_point)) in added.iter().enumerate() {
+            let distance = super::utils::distance_meters(&pointz(old_point), &pointz(new_point));
+            pairs.push((distance, old_index, new_index));
+        }
+    }
+
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut old_used = vec![false; removed.len()];
+    let mut new_used = vec![false; added.len()];
+    let mut renames = vec![];
+    for (_, old_index, new_index) in pairs {
+        if old_used[old_index] || new_used[new_index] {
+            continue;
+        }
+
+        old_used[old_index] = true;
+        new_used[new_index] = true;
+        renames.push((removed[old_index].0.clone(), added[new_index].0.clone()));
+    }
+
+    renames.sort();
+    renames
+}
+
+/// Checks the lengths of the metadata fields, and that the source is a
+///  web link
+fn validate_metadata(metadata: &ZoneMetadata) -> Result<(), ZoneError> {
+    let too_long = |field: &Option<String>, max: usize| {
+        field
+            .as_ref()
+            .is_some_and(|value| value.chars().count() > max)
+    };
+
+    if too_long(&metadata.description, MAX_DESCRIPTION_LENGTH)
+        || too_long(&metadata.authority, MAX_METADATA_FIELD_LENGTH)
+        || too_long(&metadata.contact, MAX_METADATA_FIELD_LENGTH)
+        || too_long(&metadata.source_url, MAX_METADATA_FIELD_LENGTH)
+    {
+        postgis_error!("zone metadata field is too long.");
+        return Err(ZoneError::Metadata);
+    }
+
+    if let Some(url) = &metadata.source_url {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            postgis_error!("zone source is not a web link: {url}");
+            return Err(ZoneError::Metadata);
+        }
+    }
+
+    Ok(())
+}
+
 impl TryFrom<RequestZone> for Zone {
     type Error = ZoneError;
 
     fn try_from(zone: RequestZone) -> Result<Self, Self::Error> {
-        super::utils::check_string(&zone.identifier, IDENTIFIER_REGEX).map_err(|e| {
+        check_identifier(Entity::Zone, &zone.identifier).map_err(|e| {
             postgis_error!("Invalid identifier: {}; {}", zone.identifier, e);
             ZoneError::Identifier
         })?;
@@ -122,18 +606,40 @@ impl TryFrom<RequestZone> for Zone {
             }
         }
 
-        let geom = super::utils::polygon_from_vertices_z(&zone.vertices, zone.altitude_meters_min)
+        let polygons = std::iter::once(&zone.vertices)
+            .chain(zone.parts.iter().map(|part| &part.vertices))
+            .map(|vertices| {
+                super::utils::polygon_from_vertices_z(vertices, zone.altitude_meters_min)
+            })
+            .collect::<Result<Vec<_>, _>>()
             .map_err(|e| {
                 postgis_error!("Error converting zone polygon: {}", e.to_string());
                 ZoneError::Location
             })?;
 
+        let geom = postgis::ewkb::MultiPolygonZ {
+            polygons,
+            srid: Some(DEFAULT_SRID),
+        };
+
         let zone_type = FromPrimitive::from_i32(zone.zone_type).ok_or_else(|| {
             postgis_error!("Invalid zone type: {}", zone.zone_type);
 
             ZoneError::ZoneType
         })?;
 
+        let schedule = schedule_from_windows(&zone.schedule)?;
+
+        if let Some(buffer) = zone.waypoint_buffer_meters {
+            if !(buffer > 0.0 && buffer <= MAX_WAYPOINT_BUFFER_METERS) {
+                postgis_error!("Invalid waypoint buffer: {}", buffer);
+                return Err(ZoneError::WaypointBuffer);
+            }
+        }
+
+        let metadata = zone.metadata.unwrap_or_default();
+        validate_metadata(&metadata)?;
+
         Ok(Zone {
             identifier: zone.identifier,
             zone_type,
@@ -142,15 +648,156 @@ impl TryFrom<RequestZone> for Zone {
             altitude_meters_max: zone.altitude_meters_max,
             time_start,
             time_end,
+            schedule,
+            waypoint_buffer_meters: zone.waypoint_buffer_meters,
+            metadata,
+        })
+    }
+}
+
+/// Describes why a zone of an update was rejected
+///  For invalid vertices, the first polygon of the zone at fault is
+///  converted again to find the vertex.
+fn zone_violation(index: usize, zone: &RequestZone, error: ZoneError) -> FieldViolation {
+    let field = |name: &str| format!("zones[{index}].{name}");
+    let violation = |name: &str| field_violation(field(name), "zone", &zone.identifier, error);
+    match error {
+        ZoneError::Identifier => violation("identifier"),
+        ZoneError::Time | ZoneError::TimeOrder => violation("time_end"),
+        ZoneError::ZoneType => violation("zone_type"),
+        ZoneError::Schedule => violation("schedule"),
+        ZoneError::WaypointBuffer => violation("waypoint_buffer_meters"),
+        ZoneError::Metadata => violation("metadata"),
+        ZoneError::Location => std::iter::once(("vertices".to_string(), &zone.vertices))
+            .chain(
+                zone.parts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, part)| (format!("parts[{i}].vertices"), &part.vertices)),
+            )
+            .find_map(|(name, vertices)| {
+                super::utils::polygon_from_vertices_z(vertices, zone.altitude_meters_min)
+                    .err()
+                    .map(|e| {
+                        field_violation(
+                            vertices_field(&field(&name), &e),
+                            "zone",
+                            &zone.identifier,
+                            e,
+                        )
+                    })
+            })
+            .unwrap_or_else(|| violation("vertices")),
+        _ => field_violation(format!("zones[{index}]"), "zone", &zone.identifier, error),
+    }
+}
+
+/// Gets the zones of an update that would be rejected, and why
+pub fn violations(zones: &[RequestZone]) -> Vec<FieldViolation> {
+    zones
+        .iter()
+        .enumerate()
+        .filter_map(|(index, zone)| {
+            Zone::try_from(zone.clone())
+                .err()
+                .map(|e| zone_violation(index, zone, e))
         })
+        .collect()
+}
+
+/// SQL expression extruding a zone footprint into a volume
+///  Each part is extruded on its own. A single part gives a POLYHEDRALSURFACEZ,
+///  several parts give a GEOMETRYCOLLECTIONZ of them.
+pub(super) fn extrude_footprint_sql(
+    footprint: &str,
+    altitude_min: &str,
+    altitude_max: &str,
+) -> String {
+    format!(
+        r#"CASE
+            WHEN ST_NumGeometries({footprint}) = 1 THEN ST_Extrude(
+                ST_Force3DZ(ST_GeometryN({footprint}, 1), {altitude_min}),
+                0, 0, ({altitude_max} - {altitude_min})
+            )
+            ELSE ST_Collect(ARRAY(
+                SELECT ST_Extrude(
+                    ST_Force3DZ("part"."geom", {altitude_min}),
+                    0, 0, ({altitude_max} - {altitude_min})
+                )
+                FROM ST_Dump({footprint}) AS "part"
+            ))
+        END"#
+    )
+}
+
+/// SQL expression of the footprint a zone volume is extruded from
+///  Footprints with more vertices than the min of the settings are simplified
+///  and then grown by the tolerance with mitred corners, so that the result
+///  contains the original footprint. The tolerance is converted to degrees
+///  of latitude, which are longer than degrees of longitude.
+///  The stored footprint is kept as published for display.
+pub(super) fn simplified_footprint_sql(footprint: &str, settings: &simplify::Settings) -> String {
+    if settings.zone_epsilon_meters <= 0.0 {
+        return footprint.to_string();
+    }
+
+    let degrees = (settings.zone_epsilon_meters / simplify::EARTH_RADIUS_METERS).to_degrees();
+    format!(
+        r#"CASE
+            WHEN ST_NPoints({footprint}) > {min_vertices} THEN ST_Multi(ST_Buffer(
+                ST_SimplifyPreserveTopology(ST_Force2D({footprint}), {degrees}),
+                {degrees},
+                'join=mitre'
+            ))
+            ELSE {footprint}
+        END"#,
+        min_vertices = settings.zone_min_vertices,
+    )
+}
+
+/// SQL expression of the stored zone volume
+///  Zones are stored without a volume when they can't be extruded.
+pub(super) fn zone_volume_sql(
+    volumes: ZoneVolumes,
+    footprint: &str,
+    altitude_min: &str,
+    altitude_max: &str,
+) -> String {
+    match volumes {
+        ZoneVolumes::Extruded => extrude_footprint_sql(
+            &simplified_footprint_sql(footprint, &simplify::get_settings()),
+            altitude_min,
+            altitude_max,
+        ),
+        ZoneVolumes::Footprint => "NULL".to_string(),
+    }
+}
+
+/// SQL condition matching the zones crossed by a LINESTRINGZ path
+///  Without volumes, each segment of the path crossing the footprint is
+///  compared with the altitude bounds. A segment climbing through the
+///  bounds outside of the footprint still counts, so the check errs
+///  towards reporting an intersection.
+pub(super) fn path_intersection_sql(volumes: ZoneVolumes, path: &str) -> String {
+    match volumes {
+        ZoneVolumes::Extruded => format!(r#"ST_3DIntersects("geom", {path})"#),
+        ZoneVolumes::Footprint => format!(
+            r#"ST_Intersects("footprint", ST_Force2D({path}))
+                    AND EXISTS (
+                        SELECT 1 FROM ST_DumpSegments({path}) AS "segment"
+                        WHERE ST_Intersects("footprint", ST_Force2D("segment"."geom"))
+                            AND ST_ZMin("segment"."geom") <= "altitude_meters_max"
+                            AND ST_ZMax("segment"."geom") >= "altitude_meters_min"
+                    )"#
+        ),
     }
 }
 
 /// Get the table name for the zones table
 /// pub(super) so that it can be used by the vertiports module
 pub(super) fn get_table_name() -> &'static str {
-    static FULL_NAME: &str = const_format::formatcp!(r#""{PSQL_SCHEMA}"."zones""#,);
-    FULL_NAME
+    static FULL_NAME: OnceCell<String> = OnceCell::new();
+    FULL_NAME.get_or_init(|| format!(r#""{}"."zones""#, super::get_schema()))
 }
 
 /// Initialize the vertiports table in the PostGIS database
@@ -160,32 +807,181 @@ pub async fn psql_init() -> Result<(), PostgisError> {
     // Create Aircraft Table
 
     let zonetype_str = "zonetype";
-    let statements = vec![
+    let mut statements = vec![
         super::psql_enum_declaration::<ZoneType>(zonetype_str),
         format!(
             r#"CREATE TABLE IF NOT EXISTS {table_name} (
             "id" SERIAL UNIQUE NOT NULL,
             "identifier" VARCHAR(255) UNIQUE NOT NULL PRIMARY KEY,
             "zone_type" {zonetype_str} NOT NULL,
-            "geom" GEOMETRY(POLYHEDRALSURFACEZ, {DEFAULT_SRID}) NOT NULL,
+            "geom" GEOMETRY(GEOMETRYZ, {DEFAULT_SRID}),
+            "footprint" GEOMETRY(MULTIPOLYGON, {DEFAULT_SRID}) NOT NULL,
             "altitude_meters_min" FLOAT(4) NOT NULL,
             "altitude_meters_max" FLOAT(4) NOT NULL,
             "time_start" TIMESTAMPTZ,
             "time_end" TIMESTAMPTZ,
-            "last_updated" TIMESTAMPTZ
+            "last_updated" TIMESTAMPTZ,
+            "schedule" INTEGER[] NOT NULL DEFAULT '{{}}',
+            "waypoint_buffer_meters" FLOAT(4),
+            "description" TEXT,
+            "authority" VARCHAR(255),
+            "contact" VARCHAR(255),
+            "source_url" VARCHAR(255)
         );"#,
             table_name = get_table_name()
         ),
+        // Tables created by earlier versions have no schedule
+        format!(
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "schedule" INTEGER[] NOT NULL DEFAULT '{{}}',
+            ADD COLUMN IF NOT EXISTS "waypoint_buffer_meters" FLOAT(4);"#,
+            table_name = get_table_name()
+        ),
+        // Tables created by earlier versions have no metadata
+        format!(
+            r#"ALTER TABLE {table_name}
+            ADD COLUMN IF NOT EXISTS "description" TEXT,
+            ADD COLUMN IF NOT EXISTS "authority" VARCHAR(255),
+            ADD COLUMN IF NOT EXISTS "contact" VARCHAR(255),
+            ADD COLUMN IF NOT EXISTS "source_url" VARCHAR(255);"#,
+            table_name = get_table_name()
+        ),
+        // Tables created by earlier versions hold a single extruded polygon
+        //  per zone, the first face of which is the base polygon
+        format!(
+            r#"ALTER TABLE {table_name}
+            ALTER COLUMN "geom" TYPE GEOMETRY(GEOMETRYZ, {DEFAULT_SRID}),
+            ADD COLUMN IF NOT EXISTS "footprint" GEOMETRY(MULTIPOLYGON, {DEFAULT_SRID});"#,
+            table_name = get_table_name()
+        ),
+        format!(
+            r#"UPDATE {table_name}
+            SET "footprint" = ST_Multi(ST_Force2D(ST_GeometryN("geom", 1)))
+            WHERE "footprint" IS NULL;"#,
+            table_name = get_table_name()
+        ),
+        format!(
+            r#"ALTER TABLE {table_name} ALTER COLUMN "footprint" SET NOT NULL;"#,
+            table_name = get_table_name()
+        ),
+        // Zones have no volume while SFCGAL is unavailable
+        format!(
+            r#"ALTER TABLE {table_name} ALTER COLUMN "geom" DROP NOT NULL;"#,
+            table_name = get_table_name()
+        ),
         format!(
             r#"CREATE INDEX IF NOT EXISTS "zone_geom_idx" ON {table_name} USING GIST ("geom");"#,
             table_name = get_table_name()
         ),
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS "zone_footprint_idx" ON {table_name} USING GIST ("footprint");"#,
+            table_name = get_table_name()
+        ),
     ];
 
+    // Extrude the zones stored while SFCGAL was unavailable
+    if super::capabilities::zone_volumes() == ZoneVolumes::Extruded {
+        statements.push(format!(
+            r#"UPDATE {table_name}
+            SET "geom" = {extruded}
+            WHERE "geom" IS NULL;"#,
+            table_name = get_table_name(),
+            extruded = zone_volume_sql(
+                ZoneVolumes::Extruded,
+                r#""footprint""#,
+                r#""altitude_meters_min""#,
+                r#""altitude_meters_max""#
+            ),
+        ));
+    }
+
     super::psql_transaction(statements).await
 }
 
+/// Max number of zones applied in a single transaction by [`update_zones`]
+pub const UPDATE_BATCH_SIZE: usize = 500;
+
+/// The columns of a batch of zones, as the arrays bound to
+///  [`Statement::UpsertZones`]
+#[derive(Debug)]
+struct ZoneColumns<'a> {
+    identifiers: Vec<&'a str>,
+    zone_types: Vec<ZoneType>,
+    geoms: Vec<&'a postgis::ewkb::MultiPolygonZ>,
+    altitudes_meters_min: Vec<f32>,
+    altitudes_meters_max: Vec<f32>,
+    times_start: Vec<Option<DateTime<Utc>>>,
+    times_end: Vec<Option<DateTime<Utc>>>,
+    schedules: Vec<String>,
+    waypoint_buffers_meters: Vec<Option<f32>>,
+    descriptions: Vec<Option<&'a str>>,
+    authorities: Vec<Option<&'a str>>,
+    contacts: Vec<Option<&'a str>>,
+    source_urls: Vec<Option<&'a str>>,
+}
+
+impl<'a> From<&'a [Zone]> for ZoneColumns<'a> {
+    fn from(zones: &'a [Zone]) -> Self {
+        ZoneColumns {
+            identifiers: zones.iter().map(|z| z.identifier.as_str()).collect(),
+            zone_types: zones.iter().map(|z| z.zone_type).collect(),
+            geoms: zones.iter().map(|z| &z.geom).collect(),
+            altitudes_meters_min: zones.iter().map(|z| z.altitude_meters_min).collect(),
+            altitudes_meters_max: zones.iter().map(|z| z.altitude_meters_max).collect(),
+            times_start: zones.iter().map(|z| z.time_start).collect(),
+            times_end: zones.iter().map(|z| z.time_end).collect(),
+            schedules: zones
+                .iter()
+                .map(|z| schedule_literal(&z.schedule))
+                .collect(),
+            waypoint_buffers_meters: zones.iter().map(|z| z.waypoint_buffer_meters).collect(),
+            descriptions: zones
+                .iter()
+                .map(|z| z.metadata.description.as_deref())
+                .collect(),
+            authorities: zones
+                .iter()
+                .map(|z| z.metadata.authority.as_deref())
+                .collect(),
+            contacts: zones
+                .iter()
+                .map(|z| z.metadata.contact.as_deref())
+                .collect(),
+            source_urls: zones
+                .iter()
+                .map(|z| z.metadata.source_url.as_deref())
+                .collect(),
+        }
+    }
+}
+
+/// A zone schedule as a PostgreSQL array literal
+///  Arrays of arrays can't be unnested row by row, so each schedule is
+///  passed as text and cast back.
+fn schedule_literal(schedule: &[i32]) -> String {
+    let minutes: Vec<String> = schedule.iter().map(i32::to_string).collect();
+    format!("{{{}}}", minutes.join(","))
+}
+
+/// Keeps the last of the zones sharing an identifier, as if each zone
+///  had been applied in turn. A batch can't update the same row twice.
+fn last_of_each_zone(zones: Vec<Zone>) -> Vec<Zone> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut zones: Vec<Zone> = zones
+        .into_iter()
+        .rev()
+        .filter(|zone| seen.insert(zone.identifier.clone()))
+        .collect();
+
+    zones.reverse();
+    zones
+}
+
 /// Updates zones in the PostGIS database.
+///
+/// The zones are all validated first, then written in transactions of at
+///  most [`UPDATE_BATCH_SIZE`] zones, each with a single upsert. If a
+///  batch fails, the batches before it stay committed.
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) need postgis backend to test
 pub async fn update_zones(zones: Vec<RequestZone>) -> Result<(), PostgisError> {
@@ -201,133 +997,1362 @@ pub async fn update_zones(zones: Vec<RequestZone>) -> Result<(), PostgisError> {
         .collect::<Result<Vec<_>, _>>()
         .map_err(PostgisError::Zone)?;
 
+    let zones = last_of_each_zone(zones);
     let mut client = get_client().await?;
-    let transaction = client.transaction().await.map_err(|e| {
-        postgis_error!("could not create transaction: {}", e);
-        PostgisError::Zone(ZoneError::DBError)
-    })?;
-
-    let stmt = transaction
-        .prepare_cached(&format!(
-            r#"INSERT INTO {table_name} (
-            "identifier",
-            "zone_type",
-            "geom",
-            "altitude_meters_min",
-            "altitude_meters_max",
-            "time_start",
-            "time_end",
-            "last_updated"
-        )
-        VALUES (
-            $1,
-            $2,
-            ST_Extrude($3::GEOMETRY(POLYGONZ, {DEFAULT_SRID}), 0, 0, ($5::FLOAT(4) - $4::FLOAT(4))),
-            $4,
-            $5,
-            $6,
-            $7,
-            NOW()
-        )
-        ON CONFLICT ("identifier") DO UPDATE
-            SET "geom" = EXCLUDED."geom",
-            "altitude_meters_min" = EXCLUDED."altitude_meters_min",
-            "altitude_meters_max" = EXCLUDED."altitude_meters_max",
-            "time_start" = EXCLUDED."time_start",
-            "time_end" = EXCLUDED."time_end";
-        "#,
-            table_name = get_table_name(),
-        ))
-        .await
-        .map_err(|e| {
-            postgis_error!("could not prepare cached statement: {}", e);
+    let mut done = 0;
+    for batch in zones.chunks(UPDATE_BATCH_SIZE) {
+        let transaction = client.transaction().await.map_err(|e| {
+            postgis_error!("could not create transaction: {}", e);
             PostgisError::Zone(ZoneError::DBError)
         })?;
 
-    for zone in &zones {
+        let stmt = transaction
+            .prepare_cached(&Statement::UpsertZones.sql())
+            .await
+            .map_err(|e| {
+                postgis_error!("could not prepare cached statement: {}", e);
+                PostgisError::Zone(ZoneError::DBError)
+            })?;
+
+        let columns = ZoneColumns::from(batch);
         transaction
             .execute(
                 &stmt,
                 &[
-                    &zone.identifier,
-                    &zone.zone_type,
-                    &zone.geom,
-                    &zone.altitude_meters_min,
-                    &zone.altitude_meters_max,
-                    &zone.time_start,
-                    &zone.time_end,
+                    &columns.identifiers,
+                    &columns.zone_types,
+                    &columns.geoms,
+                    &columns.altitudes_meters_min,
+                    &columns.altitudes_meters_max,
+                    &columns.times_start,
+                    &columns.times_end,
+                    &columns.schedules,
+                    &columns.waypoint_buffers_meters,
+                    &columns.descriptions,
+                    &columns.authorities,
+                    &columns.contacts,
+                    &columns.source_urls,
                 ],
             )
             .await
             .map_err(|e| {
                 postgis_error!("could not execute transaction: {}", e);
-                PostgisError::Zone(ZoneError::DBError)
+                PostgisError::Zone(statement_error(&e))
             })?;
-    }
 
-    transaction.commit().await.map_err(|e| {
-        postgis_error!("could not commit transaction: {}", e);
-        PostgisError::Zone(ZoneError::DBError)
-    })?;
+        for zone in batch {
+            update_zone_waypoints(&transaction, &zone.identifier).await?;
+        }
+
+        transaction.commit().await.map_err(|e| {
+            postgis_error!("could not commit transaction: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+        super::vertiport::invalidate_route_waypoints();
+        done += batch.len();
+        postgis_info!("updated {}/{} zones.", done, zones.len());
+    }
 
     postgis_debug!("success.");
     Ok(())
 }
 
-/// Prepares a statement that checks zone intersections with the provided geometry
-#[cfg(not(tarpaulin_include))]
-// no_coverage: (R5) need postgis backend to test
-pub async fn get_zone_intersection_stmt(
-    client: &Object,
-) -> Result<tokio_postgres::Statement, PostgisError> {
-    let result = client
-        .prepare_cached(&format!(
-            r#"
-            SELECT
-                "identifier",
-                "geom",
-                "zone_type",
-                "altitude_meters_min",
-                "altitude_meters_max",
-                "time_start",
-                "time_end"
-            FROM {table_name}
-            WHERE
-                ST_3DIntersects("geom", $1::GEOMETRY(LINESTRINGZ, {DEFAULT_SRID}))
-                AND ("time_start" <= $3 OR "time_start" IS NULL)
-                AND ("time_end" >= $2 OR "time_end" IS NULL)
-                AND "identifier" NOT IN ($4, $5)
-            LIMIT 1;
-        "#,
-            table_name = get_table_name()
-        ))
-        .await;
+/// Max number of zones applied in a single transaction by a zone stream
+pub const STREAM_BATCH_SIZE: usize = 500;
 
-    result.map_err(|e| {
-        postgis_error!("could not prepare cached statement: {}", e);
-        PostgisError::Zone(ZoneError::DBError)
-    })
+/// Converts the zones of a stream chunk, recording the invalid ones
+fn validate_stream_zones(zones: Vec<RequestZone>, failed: &mut Vec<ZoneFailure>) -> Vec<Zone> {
+    zones
+        .into_iter()
+        .filter_map(|zone| {
+            let identifier = zone.identifier.clone();
+            Zone::try_from(zone)
+                .map_err(|e| {
+                    postgis_warn!("invalid zone '{}' in stream: {}", identifier, e);
+                    failed.push(ZoneFailure {
+                        identifier,
+                        reason: e.to_string(),
+                    });
+                })
+                .ok()
+        })
+        .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::grpc::server::grpc_server::Coordinates;
-    use crate::postgis::utils;
-    use lib_common::time::Duration;
+/// Imports zones received in chunks, in transactions of at most
+///  [`STREAM_BATCH_SIZE`] zones
+///
+/// Each zone is applied under a savepoint so that a single bad zone doesn't
+///  roll back the rest of its batch. If a batch fails to commit, all of
+///  its zones are reported as failed.
+#[derive(Debug, Default)]
+pub struct ZoneStream {
+    pending: Vec<Zone>,
+    summary: UpdateZonesSummary,
+}
 
-    fn square(latitude: f64, longitude: f64) -> Vec<(f64, f64)> {
-        vec![
-            (latitude - 0.0001, longitude - 0.0001),
-            (latitude + 0.0001, longitude - 0.0001),
-            (latitude + 0.0001, longitude + 0.0001),
-            (latitude - 0.0001, longitude + 0.0001),
-            (latitude - 0.0001, longitude - 0.0001),
-        ]
+impl ZoneStream {
+    /// Validates a chunk of zones and applies every full batch
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need postgis backend to test
+    pub async fn push(&mut self, zones: Vec<RequestZone>) -> Result<(), PostgisError> {
+        let zones = validate_stream_zones(zones, &mut self.summary.failed);
+        self.pending.extend(zones);
+
+        while self.pending.len() >= STREAM_BATCH_SIZE {
+            let batch: Vec<Zone> = self.pending.drain(..STREAM_BATCH_SIZE).collect();
+            self.apply(batch).await?;
+        }
+
+        Ok(())
     }
 
-    #[test]
-    fn ut_request_valid() {
+    /// Applies the remaining zones and returns the outcome of the stream
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need postgis backend to test
+    pub async fn finish(mut self) -> Result<UpdateZonesSummary, PostgisError> {
+        let batch = std::mem::take(&mut self.pending);
+        if !batch.is_empty() {
+            self.apply(batch).await?;
+        }
+
+        postgis_info!(
+            "zone stream done: {} inserted, {} updated, {} failed.",
+            self.summary.inserted,
+            self.summary.updated,
+            self.summary.failed.len()
+        );
+
+        Ok(self.summary)
+    }
+
+    /// Applies a batch of zones in a single transaction
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need postgis backend to test
+    async fn apply(&mut self, batch: Vec<Zone>) -> Result<(), PostgisError> {
+        postgis_debug!("applying {} zones.", batch.len());
+        let mut client = get_client().await?;
+        let mut transaction = client.transaction().await.map_err(|e| {
+            postgis_error!("could not create transaction: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+        let stmt = transaction
+            .prepare_cached(&Statement::UpsertZone.sql())
+            .await
+            .map_err(|e| {
+                postgis_error!("could not prepare cached statement: {}", e);
+                PostgisError::Zone(ZoneError::DBError)
+            })?;
+
+        let mut inserted: Vec<&str> = vec![];
+        let mut updated: Vec<&str> = vec![];
+        for zone in &batch {
+            let result = upsert_zone_savepoint(&mut transaction, &stmt, zone).await;
+            match result {
+                Ok(true) => inserted.push(&zone.identifier),
+                Ok(false) => updated.push(&zone.identifier),
+                Err(e) => self.summary.failed.push(ZoneFailure {
+                    identifier: zone.identifier.clone(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        if let Err(e) = transaction.commit().await {
+            postgis_error!("could not commit transaction: {}", e);
+            self.summary
+                .failed
+                .extend(
+                    inserted
+                        .into_iter()
+                        .chain(updated)
+                        .map(|identifier| ZoneFailure {
+                            identifier: identifier.to_string(),
+                            reason: ZoneError::DBError.to_string(),
+                        }),
+                );
+
+            return Ok(());
+        }
+
+        super::vertiport::invalidate_route_waypoints();
+        self.summary.inserted += inserted.len() as u32;
+        self.summary.updated += updated.len() as u32;
+        Ok(())
+    }
+}
+
+/// Upserts a zone and regenerates its waypoints under a savepoint,
+///  returning whether the zone is new
+///  The savepoint is rolled back if either fails.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+async fn upsert_zone_savepoint(
+    transaction: &mut deadpool_postgres::Transaction<'_>,
+    stmt: &tokio_postgres::Statement,
+    zone: &Zone,
+) -> Result<bool, ZoneError> {
+    let savepoint = transaction.savepoint("zone").await.map_err(|e| {
+        postgis_error!("could not create savepoint: {}", e);
+        ZoneError::DBError
+    })?;
+
+    let is_new = savepoint
+        .query_one(
+            stmt,
+            &[
+                &zone.identifier,
+                &zone.zone_type,
+                &zone.geom,
+                &zone.altitude_meters_min,
+                &zone.altitude_meters_max,
+                &zone.time_start,
+                &zone.time_end,
+                &zone.schedule,
+                &zone.waypoint_buffer_meters,
+                &zone.metadata.description,
+                &zone.metadata.authority,
+                &zone.metadata.contact,
+                &zone.metadata.source_url,
+            ],
+        )
+        .await
+        .and_then(|row| row.try_get::<_, bool>("inserted"))
+        .map_err(|e| {
+            postgis_error!("could not upsert zone '{}': {}", zone.identifier, e);
+            ZoneError::DBError
+        })?;
+
+    // dropping the savepoint rolls it back
+    update_zone_waypoints(&savepoint, &zone.identifier)
+        .await
+        .map_err(|_| ZoneError::DBError)?;
+
+    savepoint.commit().await.map_err(|e| {
+        postgis_error!("could not release savepoint: {}", e);
+        ZoneError::DBError
+    })?;
+
+    Ok(is_new)
+}
+
+/// Checks an update of zones without applying it
+///
+/// Each valid zone is upserted and has its waypoints regenerated under its
+///  own savepoint, in a transaction that is then rolled back. Zones are
+///  checked in order, each with the valid zones before it applied.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn validate_zones(zones: Vec<RequestZone>) -> Result<Vec<ItemValidation>, PostgisError> {
+    postgis_debug!("entry.");
+    if zones.is_empty() {
+        postgis_error!("no zones provided.");
+        return Err(PostgisError::Zone(ZoneError::NoZones));
+    }
+
+    let mut client = get_client().await?;
+    let mut transaction = client.transaction().await.map_err(|e| {
+        postgis_error!("could not create transaction: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    let stmt = transaction
+        .prepare_cached(&Statement::UpsertZone.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let mut validations = Vec::with_capacity(zones.len());
+    for (index, request) in zones.iter().enumerate() {
+        let result = match Zone::try_from(request.clone()) {
+            Ok(zone) => upsert_zone_savepoint(&mut transaction, &stmt, &zone)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(e),
+        };
+
+        let violation = result.err().map(|e| zone_violation(index, request, e));
+        validations.push(item_validation(&request.identifier, violation));
+    }
+
+    transaction.rollback().await.map_err(|e| {
+        postgis_error!("could not roll back transaction: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    postgis_info!(
+        "validated {} zones, {} invalid.",
+        validations.len(),
+        validations.iter().filter(|v| !v.valid).count()
+    );
+
+    Ok(validations)
+}
+
+/// Gets the waypoints that would be generated around a zone, without
+///  storing the zone
+///
+/// Waypoints falling inside another zone, or inside another part of
+///  this one, are flagged as blocked.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn preview_zone_waypoints(
+    zone: RequestZone,
+) -> Result<Vec<WaypointCandidate>, PostgisError> {
+    postgis_debug!("entry.");
+    let zone = Zone::try_from(zone).map_err(PostgisError::Zone)?;
+    let points = zone_geom_waypoint_locations(&zone);
+    if points.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::GetBlockedZoneWaypoints.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let x: Vec<f64> = points.iter().map(|p| p.x).collect();
+    let y: Vec<f64> = points.iter().map(|p| p.y).collect();
+    let blocked = client
+        .query(&stmt, &[&x, &y, &zone.identifier, &zone.geom])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not check waypoint candidates: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?
+        .iter()
+        .map(|row| row.try_get::<_, i32>("index"))
+        .collect::<Result<HashSet<i32>, tokio_postgres::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not get waypoint candidate data: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    Ok(points
+        .into_iter()
+        .enumerate()
+        .map(|(i, point)| WaypointCandidate {
+            location: Some(Coordinates {
+                latitude: point.y,
+                longitude: point.x,
+            }),
+            blocked: blocked.contains(&(i as i32)),
+        })
+        .collect())
+}
+
+/// Regenerates the waypoints placed around a zone
+///  Called after the zone is upserted, within the same transaction
+///  Each part of the zone gets its own waypoints. Waypoints that would
+///  fall inside any zone (including another part of this one) are not created
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub(super) async fn update_zone_waypoints(
+    transaction: &deadpool_postgres::Transaction<'_>,
+    identifier: &str,
+) -> Result<(), PostgisError> {
+    postgis_debug!("entry, zone: '{}'.", identifier);
+    let zone_stmt = transaction
+        .prepare_cached(&Statement::GetZoneFootprint.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let row = transaction
+        .query_one(&zone_stmt, &[&identifier])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get zone {}: {}", identifier, e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let zone_id: i32 = row.try_get("id").map_err(|e| {
+        postgis_error!("could not get zone id: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    let footprint: postgis::ewkb::MultiPolygon = row.try_get("footprint").map_err(|e| {
+        postgis_error!("could not get zone footprint: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    let buffer_meters = match row.try_get::<_, Option<f32>>("waypoint_buffer_meters") {
+        Ok(Some(buffer_meters)) => buffer_meters,
+        Ok(None) => {
+            let zone_type: ZoneType = row.try_get("zone_type").map_err(|e| {
+                postgis_error!("could not get zone type: {}", e);
+                PostgisError::Zone(ZoneError::DBError)
+            })?;
+
+            default_waypoint_buffer_meters(zone_type)
+        }
+        Err(e) => {
+            postgis_error!("could not get waypoint buffer: {}", e);
+            return Err(PostgisError::Zone(ZoneError::DBError));
+        }
+    };
+
+    let waypoints_stmt = transaction
+        .prepare_cached(&Statement::GetZoneWaypoints.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let rows = transaction
+        .query(&waypoints_stmt, &[&zone_id])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get waypoints of zone {}: {}", identifier, e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let existing = rows
+        .into_iter()
+        .map(|row| Ok((row.try_get("identifier")?, row.try_get("geom")?)))
+        .collect::<Result<Vec<(String, postgis::ewkb::Point)>, tokio_postgres::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not get waypoint data: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let stmt = transaction
+        .prepare_cached(&Statement::InsertZoneWaypoint.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    // Vertiports with a surveyed FATO are only entered on its approach paths
+    let fato: Option<postgis::ewkb::Polygon> = row.try_get("fato").map_err(|e| {
+        postgis_error!("could not get vertiport FATO: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    let approach_headings: Option<Vec<f32>> = row.try_get("approach_headings").map_err(|e| {
+        postgis_error!("could not get vertiport approach headings: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    // Waypoints inside another zone are not inserted
+    let mut kept = vec![];
+    let points: Vec<postgis::ewkb::Point> = match fato.as_ref().and_then(|f| f.rings.first()) {
+        Some(ring) => fato_approach_locations(
+            &ring.points,
+            &approach_headings.unwrap_or_default(),
+            buffer_meters as f64,
+        ),
+        None => footprint
+            .polygons
+            .iter()
+            .filter_map(|polygon| polygon.rings.first())
+            .flat_map(|ring| zone_waypoint_locations(&ring.points, buffer_meters as f64))
+            .collect(),
+    };
+
+    for point in points {
+        let waypoint_identifier = zone_waypoint_identifier(zone_id, &point);
+        let inserted = transaction
+            .execute(&stmt, &[&waypoint_identifier, &point, &zone_id])
+            .await
+            .map_err(|e| {
+                postgis_error!("could not insert waypoint for zone {}: {}", identifier, e);
+                PostgisError::Zone(ZoneError::DBError)
+            })?;
+
+        if inserted > 0 {
+            kept.push((waypoint_identifier, point));
+        }
+    }
+
+    let kept_identifiers: Vec<&String> = kept.iter().map(|(id, _)| id).collect();
+    let delete_stmt = transaction
+        .prepare_cached(&Statement::DeleteStaleZoneWaypoints.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    transaction
+        .execute(&delete_stmt, &[&zone_id, &kept_identifiers])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not delete waypoints of zone {}: {}", identifier, e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let removed: Vec<_> = existing
+        .iter()
+        .filter(|(id, _)| !kept_identifiers.contains(&id))
+        .cloned()
+        .collect();
+
+    let added: Vec<_> = kept
+        .iter()
+        .filter(|(id, _)| !existing.iter().any(|(existing_id, _)| existing_id == id))
+        .cloned()
+        .collect();
+
+    // Record renames in the same transaction, so they are only visible
+    //  if the zone update is committed
+    for (old_identifier, new_identifier) in match_waypoint_renames(&removed, &added) {
+        postgis_info!(
+            "zone {} waypoint renamed: {} -> {}",
+            identifier,
+            old_identifier,
+            new_identifier
+        );
+
+        let rename_stmt = transaction
+            .prepare_cached(&Statement::InsertWaypointRename.sql())
+            .await
+            .map_err(|e| {
+                postgis_error!("could not prepare cached statement: {}", e);
+                PostgisError::Zone(ZoneError::DBError)
+            })?;
+
+        transaction
+            .execute(&rename_stmt, &[&zone_id, &old_identifier, &new_identifier])
+            .await
+            .map_err(|e| {
+                postgis_error!("could not record waypoint rename: {}", e);
+                PostgisError::Zone(ZoneError::DBError)
+            })?;
+    }
+
+    postgis_debug!("success.");
+    Ok(())
+}
+
+/// Zone attributes that can change without resending the geometry
+#[derive(Debug, Clone, PartialEq)]
+struct ZoneAttributes {
+    zone_type: ZoneType,
+    altitude_meters_min: f32,
+    altitude_meters_max: f32,
+    time_start: Option<DateTime<Utc>>,
+    time_end: Option<DateTime<Utc>>,
+    schedule: Vec<i32>,
+}
+
+/// Applies a requested change to an optional time: a new time, or none if
+///  cleared. Both at once is contradictory.
+fn merge_time(
+    current: Option<DateTime<Utc>>,
+    requested: &Option<lib_common::time::Timestamp>,
+    clear: bool,
+) -> Result<Option<DateTime<Utc>>, ZoneError> {
+    match (requested, clear) {
+        (Some(_), true) => {
+            postgis_error!("a time can't be both set and cleared.");
+            Err(ZoneError::Time)
+        }
+        (Some(time), false) => Ok(Some(time.clone().into())),
+        (None, true) => Ok(None),
+        (None, false) => Ok(current),
+    }
+}
+
+impl ZoneAttributes {
+    /// Applies the requested changes over the current attributes
+    fn merge(self, request: &UpdateZoneAttributesRequest) -> Result<Self, ZoneError> {
+        let zone_type = match request.zone_type {
+            Some(zone_type) => FromPrimitive::from_i32(zone_type).ok_or_else(|| {
+                postgis_error!("Invalid zone type: {}", zone_type);
+                ZoneError::ZoneType
+            })?,
+            None => self.zone_type,
+        };
+
+        let schedule = match &request.schedule {
+            Some(schedule) => schedule_from_windows(&schedule.windows)?,
+            None => self.schedule,
+        };
+
+        let merged = ZoneAttributes {
+            zone_type,
+            altitude_meters_min: request
+                .altitude_meters_min
+                .unwrap_or(self.altitude_meters_min),
+            altitude_meters_max: request
+                .altitude_meters_max
+                .unwrap_or(self.altitude_meters_max),
+            time_start: merge_time(
+                self.time_start,
+                &request.time_start,
+                request.clear_time_start,
+            )?,
+            time_end: merge_time(self.time_end, &request.time_end, request.clear_time_end)?,
+            schedule,
+        };
+
+        if merged.altitude_meters_min > merged.altitude_meters_max {
+            postgis_error!("minimum altitude is above maximum altitude.");
+            return Err(ZoneError::AltitudeOrder);
+        }
+
+        if let (Some(ts), Some(te)) = (merged.time_start, merged.time_end) {
+            if te < ts {
+                postgis_error!("end time is earlier than start time.");
+                return Err(ZoneError::TimeOrder);
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+/// Converts a zone row into its current attributes
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_attributes_row(
+    row: &tokio_postgres::Row,
+) -> Result<ZoneAttributes, tokio_postgres::Error> {
+    Ok(ZoneAttributes {
+        zone_type: row.try_get("zone_type")?,
+        altitude_meters_min: row.try_get("altitude_meters_min")?,
+        altitude_meters_max: row.try_get("altitude_meters_max")?,
+        time_start: row.try_get("time_start")?,
+        time_end: row.try_get("time_end")?,
+        schedule: row.try_get("schedule")?,
+    })
+}
+
+/// Updates the attributes of an existing zone without changing its footprint
+///  The volume is only rebuilt if the altitude band changes
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn update_zone_attributes(
+    request: UpdateZoneAttributesRequest,
+) -> Result<(), PostgisError> {
+    postgis_debug!("entry.");
+    check_identifier(Entity::Zone, &request.identifier).map_err(|e| {
+        postgis_error!("Invalid identifier: {}; {}", request.identifier, e);
+        PostgisError::Zone(ZoneError::Identifier)
+    })?;
+
+    let mut client = get_client().await?;
+    let transaction = client.transaction().await.map_err(|e| {
+        postgis_error!("could not create transaction: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    let select_stmt = transaction
+        .prepare_cached(&Statement::LockZoneAttributes.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let row = transaction
+        .query_opt(&select_stmt, &[&request.identifier])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not get zone: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?
+        .ok_or_else(|| {
+            postgis_error!("zone not found: {}", request.identifier);
+            PostgisError::Zone(ZoneError::NotFound)
+        })?;
+
+    let attributes = process_attributes_row(&row)
+        .map_err(|e| {
+            postgis_error!("could not get zone attributes: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?
+        .merge(&request)
+        .map_err(PostgisError::Zone)?;
+
+    let update_stmt = transaction
+        .prepare_cached(&Statement::UpdateZoneAttributes.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    transaction
+        .execute(
+            &update_stmt,
+            &[
+                &request.identifier,
+                &attributes.zone_type,
+                &attributes.altitude_meters_min,
+                &attributes.altitude_meters_max,
+                &attributes.time_start,
+                &attributes.time_end,
+                &attributes.schedule,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            postgis_error!("could not update zone attributes: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    transaction.commit().await.map_err(|e| {
+        postgis_error!("could not commit transaction: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    postgis_debug!("success.");
+    Ok(())
+}
+
+/// Gets the column used to order zones
+pub(super) fn get_order_column(order_by: OrderBy) -> &'static str {
+    match order_by {
+        OrderBy::Identifier => r#""identifier""#,
+        OrderBy::LastUpdated => r#""last_updated""#,
+    }
+}
+
+/// Converts a zone row into the times at which the zone changes state
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+fn process_zone_times_row(row: tokio_postgres::Row) -> Result<ZoneTimes, tokio_postgres::Error> {
+    let zone_type: ZoneType = row.try_get("zone_type")?;
+    Ok(ZoneTimes {
+        identifier: row.try_get("identifier")?,
+        zone_type: zone_type as i32,
+        time_start: row.try_get("time_start")?,
+        time_end: row.try_get("time_end")?,
+    })
+}
+
+/// Converts a zone row into the GRPC type
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test, no way to create a Row without querying it
+pub(super) fn process_zone_row(
+    row: tokio_postgres::Row,
+) -> Result<RequestZone, tokio_postgres::Error> {
+    let footprint: postgis::ewkb::MultiPolygon = row.try_get("footprint")?;
+    let zone_type: ZoneType = row.try_get("zone_type")?;
+    let time_start: Option<DateTime<Utc>> = row.try_get("time_start")?;
+    let time_end: Option<DateTime<Utc>> = row.try_get("time_end")?;
+    let schedule: Vec<i32> = row.try_get("schedule")?;
+    let metadata = ZoneMetadata {
+        description: row.try_get("description")?,
+        authority: row.try_get("authority")?,
+        contact: row.try_get("contact")?,
+        source_url: row.try_get("source_url")?,
+    };
+
+    // The first polygon is returned as the zone vertices
+    let mut parts = footprint.polygons.iter().map(|polygon| {
+        polygon
+            .rings
+            .first()
+            .map(|ring| {
+                ring.points
+                    .iter()
+                    .map(|p| Coordinates {
+                        latitude: p.y,
+                        longitude: p.x,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    });
+
+    Ok(RequestZone {
+        identifier: row.try_get("identifier")?,
+        zone_type: zone_type as i32,
+        vertices: parts.next().unwrap_or_default(),
+        parts: parts
+            .map(|vertices| ZonePart {
+                vertices,
+                compact_vertices: None,
+            })
+            .collect(),
+        altitude_meters_min: row.try_get("altitude_meters_min")?,
+        altitude_meters_max: row.try_get("altitude_meters_max")?,
+        time_start: time_start.map(|t| t.into()),
+        time_end: time_end.map(|t| t.into()),
+        schedule: windows_from_schedule(&schedule),
+        waypoint_buffer_meters: row.try_get("waypoint_buffer_meters")?,
+        compact_vertices: None,
+        metadata: (metadata != ZoneMetadata::default()).then_some(metadata),
+        port_operating_hours: vec![],
+    })
+}
+
+/// Gets a page of zones from the PostGIS database
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn get_zones(request: GetZonesRequest) -> Result<GetZonesResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let page = Pagination::try_from(request.page).map_err(|e| {
+        postgis_error!("invalid pagination: {}", e);
+        PostgisError::Zone(ZoneError::Pagination)
+    })?;
+
+    let encoding = GeometryEncoding::try_from(request.encoding).map_err(|e| {
+        postgis_error!("invalid geometry encoding: {}", e);
+        PostgisError::Zone(ZoneError::Encoding)
+    })?;
+
+    let client = get_client().await?;
+
+    let stmt = client
+        .prepare_cached(&Statement::GetZones(Ordering::from(&page)).sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let zones = client
+        .query(&stmt, &[&(page.limit as i64), &(page.offset as i64)])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute query: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?
+        .into_iter()
+        .map(|row| {
+            // Only set for the port zones of vertiports
+            let port_operating_hours: Option<Vec<i32>> = row.try_get("port_operating_hours")?;
+            Ok(RequestZone {
+                port_operating_hours: windows_from_schedule(
+                    &port_operating_hours.unwrap_or_default(),
+                ),
+                ..process_zone_row(row)?
+            })
+        })
+        .collect::<Result<Vec<RequestZone>, tokio_postgres::Error>>()
+        .map_err(|e| {
+            postgis_error!("could not get zone data: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let zones = match encoding {
+        GeometryEncoding::Coordinates => zones,
+        GeometryEncoding::Compact => zones.into_iter().map(compact_zone).collect(),
+    };
+
+    Ok(GetZonesResponse {
+        next_offset: page.next_offset(zones.len()),
+        zones,
+    })
+}
+
+/// Moves the vertices of a zone and its parts into compact rings
+pub(super) fn compact_zone(zone: RequestZone) -> RequestZone {
+    RequestZone {
+        compact_vertices: Some(compact_ring(&zone.vertices)),
+        vertices: vec![],
+        parts: zone
+            .parts
+            .into_iter()
+            .map(|part| ZonePart {
+                compact_vertices: Some(compact_ring(&part.vertices)),
+                vertices: vec![],
+            })
+            .collect(),
+        ..zone
+    }
+}
+
+/// Gets the zones containing a point at a time, or now if no time is provided
+///
+/// The stored zone volumes are surfaces, so the footprint is lifted to the
+///  altitude of the point and checked along with the zone altitude bounds.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn get_zones_at_point(
+    request: GetZonesAtPointRequest,
+) -> Result<GetZonesAtPointResponse, PostgisError> {
+    postgis_debug!("entry.");
+    let point = request.point.ok_or_else(|| {
+        postgis_error!("no point provided.");
+        PostgisError::Zone(ZoneError::Location)
+    })?;
+
+    let point = postgis::ewkb::PointZ::from(point);
+    super::utils::validate_pointz(&point).map_err(|e| {
+        postgis_error!("invalid point: {}", e);
+        PostgisError::Zone(ZoneError::Location)
+    })?;
+
+    let time: DateTime<Utc> = request.time.map(Into::into).unwrap_or_else(Utc::now);
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::GetZonesAtPoint.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let rows = client.query(&stmt, &[&point, &time]).await.map_err(|e| {
+        postgis_error!("could not execute query: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    let mut zones = vec![];
+    for row in rows {
+        let schedule: Vec<i32> = row.try_get("schedule").map_err(|e| {
+            postgis_error!("could not get zone schedule: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+        if !schedule_overlaps(&schedule, time, time) {
+            continue;
+        }
+
+        zones.push(process_zone_row(row).map_err(|e| {
+            postgis_error!("could not get zone data: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?);
+    }
+
+    Ok(GetZonesAtPointResponse { zones })
+}
+
+/// Gets the activations and expiries of zones that fall after one time,
+///  up to and including another
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub(super) async fn get_zone_events(
+    after: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Vec<ZoneEvent>, PostgisError> {
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::GetZoneTransitions.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let rows = client.query(&stmt, &[&after, &until]).await.map_err(|e| {
+        postgis_error!("could not execute query: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })?;
+
+    let now = Utc::now();
+    let mut events = vec![];
+    for row in rows {
+        let zone = process_zone_times_row(row).map_err(|e| {
+            postgis_error!("could not get zone data: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+        events.extend(zone.events_between(after, until, now));
+    }
+
+    Ok(events)
+}
+
+/// Removes zones from the PostGIS database, along with their waypoints.
+///  Vertiport zones are owned by their vertiports and are not removed.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn delete_zones(identifiers: Vec<String>) -> Result<(), PostgisError> {
+    postgis_debug!("entry.");
+    if identifiers.is_empty() {
+        postgis_error!("no zones provided.");
+        return Err(PostgisError::Zone(ZoneError::NoZones));
+    }
+
+    for identifier in &identifiers {
+        check_identifier(Entity::Zone, identifier).map_err(|e| {
+            postgis_error!("Invalid identifier: {}; {}", identifier, e);
+            PostgisError::Zone(ZoneError::Identifier)
+        })?;
+    }
+
+    let client = get_client().await?;
+    let stmt = client
+        .prepare_cached(&Statement::DeleteZones.sql())
+        .await
+        .map_err(|e| {
+            postgis_error!("could not prepare cached statement: {}", e);
+            PostgisError::Zone(ZoneError::DBError)
+        })?;
+
+    let count = client
+        .execute(&stmt, &[&identifiers, &ZoneType::Port])
+        .await
+        .map_err(|e| {
+            postgis_error!("could not execute statement: {}", e);
+            PostgisError::Zone(statement_error(&e))
+        })?;
+
+    // Waypoints of the deleted zones are removed with them
+    super::vertiport::invalidate_route_waypoints();
+    postgis_info!("deleted {count} of {} zones.", identifiers.len());
+    Ok(())
+}
+
+/// Converts zone queue vertices into the GRPC type
+fn coordinates_from_vertices(vertices: Vec<ZoneVertex>) -> Vec<Coordinates> {
+    vertices
+        .into_iter()
+        .map(|vertex| Coordinates {
+            latitude: vertex.latitude,
+            longitude: vertex.longitude,
+        })
+        .collect()
+}
+
+impl From<ZoneUpdate> for RequestZone {
+    fn from(update: ZoneUpdate) -> Self {
+        RequestZone {
+            identifier: update.identifier,
+            zone_type: update.zone_type,
+            vertices: coordinates_from_vertices(update.vertices),
+            altitude_meters_min: update.altitude_meters_min,
+            altitude_meters_max: update.altitude_meters_max,
+            time_start: update.time_start.map(Into::into),
+            time_end: update.time_end.map(Into::into),
+            schedule: update
+                .schedule
+                .into_iter()
+                .map(|window: ZoneWindow| ActiveWindow {
+                    days: window.days,
+                    start_minute: window.start_minute,
+                    end_minute: window.end_minute,
+                })
+                .collect(),
+            waypoint_buffer_meters: update.waypoint_buffer_meters,
+            parts: update
+                .parts
+                .into_iter()
+                .map(|vertices| ZonePart {
+                    vertices: coordinates_from_vertices(vertices),
+                    compact_vertices: None,
+                })
+                .collect(),
+            compact_vertices: None,
+            metadata: Some(ZoneMetadata {
+                description: update.description,
+                authority: update.authority,
+                contact: update.contact,
+                source_url: update.source_url,
+            }),
+            port_operating_hours: vec![],
+        }
+    }
+}
+
+/// Keeps the latest update of each zone, by network timestamp
+///  Later entries in the queue win ties
+fn coalesce_zone_updates(updates: Vec<ZoneUpdate>) -> Vec<ZoneUpdate> {
+    let mut latest: HashMap<String, ZoneUpdate> = HashMap::new();
+    for update in updates {
+        match latest.get(&update.identifier) {
+            Some(existing) if existing.timestamp_network > update.timestamp_network => (),
+            _ => {
+                latest.insert(update.identifier.clone(), update);
+            }
+        }
+    }
+
+    let mut updates: Vec<ZoneUpdate> = latest.into_values().collect();
+    updates.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+    updates
+}
+
+/// Splits zone updates into the zones to upsert and the zones to delete.
+///  Invalid updates are logged and dropped, as they would never succeed.
+fn split_zone_updates(updates: Vec<ZoneUpdate>) -> (Vec<ZoneUpdate>, Vec<ZoneUpdate>) {
+    let (deleted, updated): (Vec<ZoneUpdate>, Vec<ZoneUpdate>) = coalesce_zone_updates(updates)
+        .into_iter()
+        .partition(|update| update.deleted);
+
+    let updated = updated
+        .into_iter()
+        .filter(|update| {
+            Zone::try_from(RequestZone::from(update.clone()))
+                .map_err(|e| {
+                    postgis_error!("dropping invalid zone {}: {}", update.identifier, e);
+                })
+                .is_ok()
+        })
+        .collect();
+
+    let deleted = deleted
+        .into_iter()
+        .filter(|update| {
+            check_identifier(Entity::Zone, &update.identifier)
+                .map_err(|e| {
+                    postgis_error!(
+                        "dropping invalid zone deletion {}: {}",
+                        update.identifier,
+                        e
+                    );
+                })
+                .is_ok()
+        })
+        .collect();
+
+    (updated, deleted)
+}
+
+/// Why zone updates from the queue were not applied
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ApplyError {
+    /// The backend is unavailable, the updates can be retried as they are
+    Backend,
+
+    /// The backend refused the data of at least one update
+    Rejected,
+}
+
+impl From<PostgisError> for ApplyError {
+    fn from(error: PostgisError) -> Self {
+        match error {
+            PostgisError::Zone(ZoneError::Rejected) => ApplyError::Rejected,
+            _ => ApplyError::Backend,
+        }
+    }
+}
+
+/// Where zone updates from the queue are applied
+#[async_trait]
+trait ZoneStore {
+    /// Gets the identifiers of the zones with an invalid geometry
+    async fn invalid_geometries(
+        &mut self,
+        updates: &[ZoneUpdate],
+    ) -> Result<Vec<String>, ApplyError>;
+
+    /// Upserts the zones, or deletes them if they are marked deleted
+    async fn apply(&mut self, updates: &[ZoneUpdate]) -> Result<(), ApplyError>;
+}
+
+/// Zones stored in PostGIS
+struct PostgisZones;
+
+#[async_trait]
+impl ZoneStore for PostgisZones {
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) needs psql backend to test
+    async fn invalid_geometries(
+        &mut self,
+        updates: &[ZoneUpdate],
+    ) -> Result<Vec<String>, ApplyError> {
+        let zones = updates
+            .iter()
+            .map(|update| Zone::try_from(RequestZone::from(update.clone())))
+            .collect::<Result<Vec<Zone>, ZoneError>>()
+            .map_err(|_| ApplyError::Rejected)?;
+
+        let identifiers: Vec<&str> = zones.iter().map(|z| z.identifier.as_str()).collect();
+        let geoms: Vec<&postgis::ewkb::MultiPolygonZ> = zones.iter().map(|z| &z.geom).collect();
+
+        let client = get_client().await?;
+        let stmt = client
+            .prepare_cached(&Statement::InvalidZoneGeometries.sql())
+            .await
+            .map_err(|e| {
+                postgis_error!("could not prepare cached statement: {}", e);
+                ApplyError::Backend
+            })?;
+
+        client
+            .query(&stmt, &[&identifiers, &geoms])
+            .await
+            .map_err(|e| {
+                postgis_error!("could not check zone geometries: {}", e);
+                ApplyError::from(PostgisError::Zone(statement_error(&e)))
+            })?
+            .iter()
+            .map(|row| row.try_get("identifier"))
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| {
+                postgis_error!("could not get zone identifier: {}", e);
+                ApplyError::Backend
+            })
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) needs psql backend to test
+    async fn apply(&mut self, updates: &[ZoneUpdate]) -> Result<(), ApplyError> {
+        let (deleted, updated): (Vec<&ZoneUpdate>, Vec<&ZoneUpdate>) =
+            updates.iter().partition(|update| update.deleted);
+
+        if !updated.is_empty() {
+            let zones = updated
+                .into_iter()
+                .map(|update| RequestZone::from(update.clone()))
+                .collect();
+
+            update_zones(zones).await?;
+        }
+
+        if !deleted.is_empty() {
+            let identifiers = deleted
+                .into_iter()
+                .map(|update| update.identifier.clone())
+                .collect();
+
+            delete_zones(identifiers).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Applies zone updates, splitting a rejected batch in halves until the
+///  rejected updates are found, so that they don't hold back the others.
+///  Returns the rejected updates.
+async fn apply_isolating<S>(
+    store: &mut S,
+    updates: Vec<ZoneUpdate>,
+) -> Result<Vec<ZoneUpdate>, ApplyError>
+where
+    S: ZoneStore + Send,
+{
+    let mut rejected = vec![];
+    let mut pending = vec![updates];
+    while let Some(mut batch) = pending.pop() {
+        if batch.is_empty() {
+            continue;
+        }
+
+        match store.apply(&batch).await {
+            Ok(()) => (),
+            Err(ApplyError::Backend) => return Err(ApplyError::Backend),
+            Err(ApplyError::Rejected) if batch.len() == 1 => {
+                postgis_warn!("zone update {} rejected.", batch[0].identifier);
+                rejected.append(&mut batch);
+            }
+            Err(ApplyError::Rejected) => {
+                let second = batch.split_off(batch.len() / 2);
+                pending.push(second);
+                pending.push(batch);
+            }
+        }
+    }
+
+    Ok(rejected)
+}
+
+/// Applies zone updates from the queue. Returns the updates the backend
+///  rejected, or an error if it is unavailable.
+async fn apply_zone_updates<S>(
+    store: &mut S,
+    updates: Vec<ZoneUpdate>,
+) -> Result<Vec<ZoneUpdate>, ApplyError>
+where
+    S: ZoneStore + Send,
+{
+    let (updated, deleted) = split_zone_updates(updates);
+    let invalid = if updated.is_empty() {
+        vec![]
+    } else {
+        store.invalid_geometries(&updated).await?
+    };
+
+    let (mut rejected, updated): (Vec<ZoneUpdate>, Vec<ZoneUpdate>) = updated
+        .into_iter()
+        .partition(|update| invalid.contains(&update.identifier));
+
+    for update in &rejected {
+        postgis_warn!("zone {} has an invalid geometry.", update.identifier);
+    }
+
+    rejected.extend(apply_isolating(store, updated).await?);
+    rejected.extend(apply_isolating(store, deleted).await?);
+    Ok(rejected)
+}
+
+#[async_trait]
+impl Processor<ZoneUpdate> for Consumer {
+    /// Backend failures are returned so that the batch is retried
+    async fn process(&mut self, items: Vec<ZoneUpdate>) -> Result<(), ()> {
+        self.process_items(items).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl ItemProcessor<ZoneUpdate> for Consumer {
+    /// Backend failures are returned so that the batch is retried. Updates
+    ///  the backend rejects are returned on their own.
+    async fn process_items(&mut self, items: Vec<ZoneUpdate>) -> Result<Vec<ZoneUpdate>, ()> {
+        if items.is_empty() {
+            return Ok(vec![]);
+        }
+
+        apply_zone_updates(&mut PostgisZones, items)
+            .await
+            .map_err(|_| ())
+    }
+}
+
+/// Prepares a statement that checks zone intersections with the provided geometry
+///  Recurring schedules are not evaluated here, see [`schedule_overlaps`]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn get_zone_intersection_stmt(
+    client: &Object,
+) -> Result<tokio_postgres::Statement, PostgisError> {
+    let result = client
+        .prepare_cached(&Statement::ZonePathIntersection.sql())
+        .await;
+
+    result.map_err(|e| {
+        postgis_error!("could not prepare cached statement: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })
+}
+
+/// Prepares a statement that gets zones overlapping a volume
+///  The volume is a polygon extended between two altitudes
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need postgis backend to test
+pub async fn get_zone_volume_intersection_stmt(
+    client: &Object,
+) -> Result<tokio_postgres::Statement, PostgisError> {
+    let result = client
+        .prepare_cached(&Statement::ZoneVolumeIntersection.sql())
+        .await;
+
+    result.map_err(|e| {
+        postgis_error!("could not prepare cached statement: {}", e);
+        PostgisError::Zone(ZoneError::DBError)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::server::grpc_server::Coordinates;
+    use crate::grpc::server::grpc_server::ZoneSchedule;
+    use crate::postgis::utils;
+    use lib_common::time::Duration;
+
+    fn square(latitude: f64, longitude: f64) -> Vec<(f64, f64)> {
+        vec![
+            (latitude - 0.0001, longitude - 0.0001),
+            (latitude + 0.0001, longitude - 0.0001),
+            (latitude + 0.0001, longitude + 0.0001),
+            (latitude - 0.0001, longitude + 0.0001),
+            (latitude - 0.0001, longitude - 0.0001),
+        ]
+    }
+
+    #[test]
+    fn ut_request_valid() {
         let nodes: Vec<(&str, Vec<(f64, f64)>, f32, f32)> = vec![
             ("NFZ_A", square(52.3745905, 4.9160036), 102.0, 200.0),
             ("NFZ_B", square(52.3749819, 4.9156925), 20.5, 120.0),
@@ -365,10 +2390,60 @@ mod tests {
         for (i, nfz) in zones.iter().enumerate() {
             assert_eq!(nfz.identifier, converted[i].identifier);
             assert_eq!(
-                utils::polygon_from_vertices_z(&nfz.vertices, nfz.altitude_meters_min).unwrap(),
-                converted[i].geom
+                vec![
+                    utils::polygon_from_vertices_z(&nfz.vertices, nfz.altitude_meters_min).unwrap()
+                ],
+                converted[i].geom.polygons
+            );
+        }
+    }
+
+    #[test]
+    fn ut_request_valid_parts() {
+        let vertices = |points: Vec<(f64, f64)>| {
+            points
+                .iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude: *latitude,
+                    longitude: *longitude,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut zone = RequestZone {
+            identifier: "NFZ_MULTI".to_string(),
+            vertices: vertices(square(52.3745905, 4.9160036)),
+            parts: vec![
+                ZonePart {
+                    vertices: vertices(square(52.3749819, 4.9156925)),
+                    compact_vertices: None,
+                },
+                ZonePart {
+                    vertices: vertices(square(52.3752144, 4.9153733)),
+                    compact_vertices: None,
+                },
+            ],
+            altitude_meters_min: 20.0,
+            altitude_meters_max: 120.0,
+            ..Default::default()
+        };
+
+        let converted = Zone::try_from(zone.clone()).unwrap();
+        assert_eq!(converted.geom.polygons.len(), 3);
+        assert_eq!(converted.geom.srid, Some(DEFAULT_SRID));
+        for (polygon, vertices) in converted.geom.polygons.iter().zip(
+            std::iter::once(&zone.vertices).chain(zone.parts.iter().map(|part| &part.vertices)),
+        ) {
+            assert_eq!(
+                *polygon,
+                utils::polygon_from_vertices_z(vertices, zone.altitude_meters_min).unwrap()
             );
         }
+
+        // Each part must be a valid polygon
+        zone.parts[1].vertices.pop();
+        let error = Zone::try_from(zone).unwrap_err();
+        assert_eq!(error, ZoneError::Location);
     }
 
     #[tokio::test]
@@ -467,53 +2542,556 @@ mod tests {
             square(0., 180.),
         ]; // each of these will crate a square outside of the allowable range of lat, lon
 
-        for polygon in polygons {
-            let zones: Vec<RequestZone> = vec![RequestZone {
-                identifier: "Nofly_zone".to_string(),
-                vertices: polygon
+        for polygon in polygons {
+            let zones: Vec<RequestZone> = vec![RequestZone {
+                identifier: "Nofly_zone".to_string(),
+                vertices: polygon
+                    .iter()
+                    .map(|(latitude, longitude)| Coordinates {
+                        latitude: *latitude,
+                        longitude: *longitude,
+                    })
+                    .collect(),
+                ..Default::default()
+            }];
+
+            let result = update_zones(zones).await.unwrap_err();
+            assert_eq!(result, PostgisError::Zone(ZoneError::Location));
+        }
+
+        let polygons = vec![
+            vec![
+                (52.3745905, 4.9160036),
+                (52.3749819, 4.9156925),
+                (52.3752144, 4.9153733),
+            ], // not enough vertices
+            vec![
+                (52.3745905, 4.9160036),
+                (52.3749819, 4.9156925),
+                (52.3752144, 4.9153733),
+                (52.3752144, 4.9153733),
+            ], // open polygon
+        ];
+
+        for polygon in polygons {
+            let zones: Vec<RequestZone> = vec![RequestZone {
+                identifier: "Nofly_zone".to_string(),
+                vertices: polygon
+                    .iter()
+                    .map(|(latitude, longitude)| Coordinates {
+                        latitude: *latitude,
+                        longitude: *longitude,
+                    })
+                    .collect(),
+                ..Default::default()
+            }];
+
+            let result = update_zones(zones).await.unwrap_err();
+            assert_eq!(result, PostgisError::Zone(ZoneError::Location));
+        }
+    }
+
+    fn window(days: &[Weekday], start_minute: u32, end_minute: u32) -> ActiveWindow {
+        ActiveWindow {
+            days: days.iter().map(|day| *day as i32).collect(),
+            start_minute,
+            end_minute,
+        }
+    }
+
+    #[test]
+    fn test_schedule_from_windows() {
+        // 08:00 - 17:00 Monday and Wednesday
+        let schedule =
+            schedule_from_windows(&[window(&[Weekday::Monday, Weekday::Wednesday], 480, 1020)])
+                .unwrap();
+        assert_eq!(schedule, vec![480, 1020, 3360, 3900]);
+
+        // 22:00 - 02:00 Sunday night wraps to Monday morning
+        let schedule = schedule_from_windows(&[window(&[Weekday::Sunday], 1320, 120)]).unwrap();
+        assert_eq!(schedule, vec![9960, MINUTES_PER_WEEK, 0, 120]);
+
+        // Same start and end is a full day
+        let schedule = schedule_from_windows(&[window(&[Weekday::Tuesday], 0, 0)]).unwrap();
+        assert_eq!(schedule, vec![1440, 2880]);
+
+        assert!(schedule_from_windows(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ut_schedule_from_windows_invalid() {
+        let windows = [
+            window(&[], 480, 1020),
+            window(&[Weekday::Monday], MINUTES_PER_DAY as u32, 1020),
+            window(&[Weekday::Monday], 480, MINUTES_PER_DAY as u32),
+            ActiveWindow {
+                days: vec![7],
+                start_minute: 480,
+                end_minute: 1020,
+            },
+        ];
+
+        for window in windows {
+            let error = schedule_from_windows(&[window]).unwrap_err();
+            assert_eq!(error, ZoneError::Schedule);
+        }
+    }
+
+    #[test]
+    fn test_windows_from_schedule() {
+        let windows = vec![
+            window(&[Weekday::Friday], 480, 1020),
+            window(&[Weekday::Tuesday], 1320, 120),
+        ];
+
+        let schedule = schedule_from_windows(&windows).unwrap();
+        assert_eq!(windows_from_schedule(&schedule), windows);
+
+        // Split windows come back as two windows
+        let schedule = schedule_from_windows(&[window(&[Weekday::Sunday], 1320, 120)]).unwrap();
+        assert_eq!(
+            windows_from_schedule(&schedule),
+            vec![
+                window(&[Weekday::Sunday], 1320, 0),
+                window(&[Weekday::Monday], 0, 120)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schedule_overlaps() {
+        // 08:00 - 17:00 weekdays
+        let weekdays = [
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+        ];
+        let schedule = schedule_from_windows(&[window(&weekdays, 480, 1020)]).unwrap();
+
+        // 2023-10-02 is a Monday
+        let monday = DateTime::parse_from_rfc3339("2023-10-02T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let at = |days: i64, hours: i64, minutes: i64| {
+            monday + Duration::days(days) + Duration::hours(hours) + Duration::minutes(minutes)
+        };
+
+        // Tuesday midday
+        assert!(schedule_overlaps(&schedule, at(1, 12, 0), at(1, 13, 0)));
+
+        // Tuesday night
+        assert!(!schedule_overlaps(&schedule, at(1, 18, 0), at(1, 23, 0)));
+
+        // Tuesday night into Wednesday morning
+        assert!(schedule_overlaps(&schedule, at(1, 18, 0), at(2, 9, 0)));
+
+        // Ends one minute before the window opens
+        assert!(!schedule_overlaps(&schedule, at(2, 6, 0), at(2, 7, 58)));
+
+        // Saturday
+        assert!(!schedule_overlaps(&schedule, at(5, 0, 0), at(5, 23, 0)));
+
+        // Sunday into the next Monday
+        assert!(schedule_overlaps(&schedule, at(6, 20, 0), at(7, 9, 0)));
+
+        // Longer than a week
+        assert!(schedule_overlaps(&schedule, at(5, 0, 0), at(13, 0, 0)));
+
+        // No schedule is always active
+        assert!(schedule_overlaps(&[], at(5, 0, 0), at(5, 1, 0)));
+    }
+
+    #[test]
+    fn test_zone_attributes_merge() {
+        let current = ZoneAttributes {
+            zone_type: ZoneType::Restriction,
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 100.0,
+            time_start: Some(Utc::now()),
+            time_end: None,
+            schedule: vec![],
+        };
+
+        // Nothing requested, nothing changes
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(current.clone().merge(&request).unwrap(), current);
+
+        let time_end = Utc::now() + Duration::hours(1);
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            zone_type: Some(ZoneType::Port as i32),
+            altitude_meters_max: Some(200.0),
+            time_end: Some(time_end.into()),
+            ..Default::default()
+        };
+
+        let merged = current.clone().merge(&request).unwrap();
+        assert_eq!(merged.zone_type, ZoneType::Port);
+        assert_eq!(merged.altitude_meters_min, current.altitude_meters_min);
+        assert_eq!(merged.altitude_meters_max, 200.0);
+        assert_eq!(merged.time_start, current.time_start);
+        assert!(merged.time_end.is_some());
+        assert!(merged.schedule.is_empty());
+
+        // The validity window and schedule can be cleared
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            clear_time_start: true,
+            clear_time_end: true,
+            schedule: Some(ZoneSchedule { windows: vec![] }),
+            ..Default::default()
+        };
+
+        let current = ZoneAttributes {
+            schedule: vec![480, 1020],
+            ..merged
+        };
+        let merged = current.clone().merge(&request).unwrap();
+        assert_eq!(merged.time_start, None);
+        assert_eq!(merged.time_end, None);
+        assert!(merged.schedule.is_empty());
+
+        // or the schedule replaced
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            schedule: Some(ZoneSchedule {
+                windows: vec![window(&[Weekday::Monday], 480, 1020)],
+            }),
+            ..Default::default()
+        };
+        let merged = current.merge(&request).unwrap();
+        assert_eq!(merged.schedule, vec![480, 1020]);
+    }
+
+    #[test]
+    fn ut_zone_attributes_merge_invalid() {
+        let current = ZoneAttributes {
+            zone_type: ZoneType::Restriction,
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 100.0,
+            time_start: Some(Utc::now()),
+            time_end: None,
+            schedule: vec![],
+        };
+
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            altitude_meters_min: Some(150.0),
+            ..Default::default()
+        };
+        let error = current.clone().merge(&request).unwrap_err();
+        assert_eq!(error, ZoneError::AltitudeOrder);
+
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            time_end: Some((Utc::now() - Duration::days(1)).into()),
+            ..Default::default()
+        };
+        let error = current.clone().merge(&request).unwrap_err();
+        assert_eq!(error, ZoneError::TimeOrder);
+
+        // set and cleared at once
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            time_end: Some((Utc::now() + Duration::days(1)).into()),
+            clear_time_end: true,
+            ..Default::default()
+        };
+        let error = current.clone().merge(&request).unwrap_err();
+        assert_eq!(error, ZoneError::Time);
+
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            schedule: Some(ZoneSchedule {
+                windows: vec![window(&[Weekday::Monday], MINUTES_PER_DAY as u32, 0)],
+            }),
+            ..Default::default()
+        };
+        let error = current.clone().merge(&request).unwrap_err();
+        assert_eq!(error, ZoneError::Schedule);
+
+        let request = UpdateZoneAttributesRequest {
+            identifier: "NFZ".to_string(),
+            zone_type: Some(10000),
+            ..Default::default()
+        };
+        let error = current.merge(&request).unwrap_err();
+        assert_eq!(error, ZoneError::ZoneType);
+    }
+
+    #[tokio::test]
+    async fn ut_update_zone_attributes_invalid_identifier() {
+        let request = UpdateZoneAttributesRequest {
+            identifier: "Nofly_zone;".to_string(),
+            ..Default::default()
+        };
+
+        let error = update_zone_attributes(request).await.unwrap_err();
+        assert_eq!(error, PostgisError::Zone(ZoneError::Identifier));
+    }
+
+    #[test]
+    fn test_waypoint_buffer_meters() {
+        let mut zone = Zone::try_from(RequestZone {
+            identifier: "NFZ".to_string(),
+            zone_type: ZoneType::Restriction as i32,
+            vertices: square(52.3745905, 4.9160036)
+                .iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude: *latitude,
+                    longitude: *longitude,
+                })
+                .collect(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            zone.waypoint_buffer_meters(),
+            RESTRICTION_WAYPOINT_BUFFER_METERS
+        );
+
+        zone.zone_type = ZoneType::Port;
+        assert_eq!(zone.waypoint_buffer_meters(), PORT_WAYPOINT_BUFFER_METERS);
+
+        zone.waypoint_buffer_meters = Some(250.0);
+        assert_eq!(zone.waypoint_buffer_meters(), 250.0);
+    }
+
+    #[test]
+    fn ut_zone_request_invalid_waypoint_buffer() {
+        for buffer in [0.0, -10.0, f32::NAN, MAX_WAYPOINT_BUFFER_METERS + 1.0] {
+            let zone = RequestZone {
+                identifier: "NFZ".to_string(),
+                vertices: square(52.3745905, 4.9160036)
                     .iter()
                     .map(|(latitude, longitude)| Coordinates {
                         latitude: *latitude,
                         longitude: *longitude,
                     })
                     .collect(),
+                waypoint_buffer_meters: Some(buffer),
                 ..Default::default()
-            }];
+            };
 
-            let result = update_zones(zones).await.unwrap_err();
-            assert_eq!(result, PostgisError::Zone(ZoneError::Location));
+            let error = Zone::try_from(zone).unwrap_err();
+            assert_eq!(error, ZoneError::WaypointBuffer);
         }
+    }
 
-        let polygons = vec![
-            vec![
-                (52.3745905, 4.9160036),
-                (52.3749819, 4.9156925),
-                (52.3752144, 4.9153733),
-            ], // not enough vertices
+    #[test]
+    fn ut_zone_request_metadata() {
+        let zone = RequestZone {
+            identifier: "NOTAM-1".to_string(),
+            vertices: square(52.3745905, 4.9160036)
+                .iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude: *latitude,
+                    longitude: *longitude,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let converted = Zone::try_from(zone.clone()).unwrap();
+        assert_eq!(converted.metadata, ZoneMetadata::default());
+
+        let metadata = ZoneMetadata {
+            description: Some("Airshow, drones prohibited".to_string()),
+            authority: Some("LVNL".to_string()),
+            contact: Some("+31 20 406 2000".to_string()),
+            source_url: Some("https://notams.example.com/NOTAM-1".to_string()),
+        };
+        let converted = Zone::try_from(RequestZone {
+            metadata: Some(metadata.clone()),
+            ..zone.clone()
+        })
+        .unwrap();
+        assert_eq!(converted.metadata, metadata);
+
+        let invalid = [
+            ZoneMetadata {
+                description: Some("x".repeat(MAX_DESCRIPTION_LENGTH + 1)),
+                ..metadata.clone()
+            },
+            ZoneMetadata {
+                authority: Some("x".repeat(MAX_METADATA_FIELD_LENGTH + 1)),
+                ..metadata.clone()
+            },
+            ZoneMetadata {
+                contact: Some("x".repeat(MAX_METADATA_FIELD_LENGTH + 1)),
+                ..metadata.clone()
+            },
+            ZoneMetadata {
+                source_url: Some("javascript:alert(1)".to_string()),
+                ..metadata.clone()
+            },
+        ];
+
+        for metadata in invalid {
+            let error = Zone::try_from(RequestZone {
+                metadata: Some(metadata),
+                ..zone.clone()
+            })
+            .unwrap_err();
+            assert_eq!(error, ZoneError::Metadata);
+        }
+    }
+
+    #[test]
+    fn ut_validate_stream_zones() {
+        let vertices = |points: Vec<(f64, f64)>| -> Vec<Coordinates> {
+            points
+                .into_iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude,
+                    longitude,
+                })
+                .collect()
+        };
+
+        let zones = vec![
+            RequestZone {
+                identifier: "NFZ_A".to_string(),
+                vertices: vertices(square(52.3745905, 4.9160036)),
+                altitude_meters_max: 100.0,
+                ..Default::default()
+            },
+            RequestZone {
+                identifier: "NFZ_B".to_string(),
+                vertices: vertices(square(52.3749819, 4.9156925)),
+                altitude_meters_max: 100.0,
+                zone_type: 99,
+                ..Default::default()
+            },
+            RequestZone {
+                identifier: "NFZ C".to_string(),
+                vertices: vertices(square(52.3752144, 4.9153733)),
+                altitude_meters_max: 100.0,
+                ..Default::default()
+            },
+        ];
+
+        let mut failed = vec![];
+        let valid = validate_stream_zones(zones, &mut failed);
+        assert_eq!(valid.len(), 1);
+        assert_eq!(valid[0].identifier, "NFZ_A");
+        assert_eq!(
+            failed,
             vec![
-                (52.3745905, 4.9160036),
-                (52.3749819, 4.9156925),
-                (52.3752144, 4.9153733),
-                (52.3752144, 4.9153733),
-            ], // open polygon
+                ZoneFailure {
+                    identifier: "NFZ_B".to_string(),
+                    reason: ZoneError::ZoneType.to_string(),
+                },
+                ZoneFailure {
+                    identifier: "NFZ C".to_string(),
+                    reason: ZoneError::Identifier.to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ut_violations() {
+        let vertices = |points: Vec<(f64, f64)>| -> Vec<Coordinates> {
+            points
+                .into_iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude,
+                    longitude,
+                })
+                .collect()
+        };
+
+        let mut out_of_bounds = square(52.3752144, 4.9153733);
+        out_of_bounds[2].0 = 90.1;
+
+        let zones = vec![
+            RequestZone {
+                identifier: "NFZ_A".to_string(),
+                vertices: vertices(square(52.3745905, 4.9160036)),
+                altitude_meters_max: 100.0,
+                ..Default::default()
+            },
+            RequestZone {
+                identifier: "NFZ_B".to_string(),
+                vertices: vertices(square(52.3749819, 4.9156925)),
+                parts: vec![ZonePart {
+                    vertices: vertices(out_of_bounds),
+                    compact_vertices: None,
+                }],
+                altitude_meters_max: 100.0,
+                ..Default::default()
+            },
+            RequestZone {
+                identifier: "NFZ_C".to_string(),
+                vertices: vertices(square(52.3752144, 4.9153733)),
+                altitude_meters_max: 100.0,
+                zone_type: 99,
+                ..Default::default()
+            },
         ];
 
-        for polygon in polygons {
-            let zones: Vec<RequestZone> = vec![RequestZone {
-                identifier: "Nofly_zone".to_string(),
-                vertices: polygon
-                    .iter()
+        let violations = violations(&zones);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].field, "zones[1].parts[0].vertices[2]");
+        assert!(violations[0].description.starts_with("zone 'NFZ_B': "));
+        assert!(violations[0].description.contains("Vertex 2: (90.1, "));
+        assert_eq!(
+            violations[1],
+            FieldViolation {
+                field: "zones[2].zone_type".to_string(),
+                description: format!("zone 'NFZ_C': {}", ZoneError::ZoneType),
+            }
+        );
+    }
+
+    #[test]
+    fn ut_schedule_literal() {
+        assert_eq!(schedule_literal(&[]), "{}");
+        assert_eq!(
+            schedule_literal(&[540, 660, 1980, 2100]),
+            "{540,660,1980,2100}"
+        );
+    }
+
+    #[test]
+    fn ut_zone_columns() {
+        let zone = |identifier: &str, altitude_meters_max: f32| -> Zone {
+            Zone::try_from(RequestZone {
+                identifier: identifier.to_string(),
+                vertices: square(52.3745905, 4.9160036)
+                    .into_iter()
                     .map(|(latitude, longitude)| Coordinates {
-                        latitude: *latitude,
-                        longitude: *longitude,
+                        latitude,
+                        longitude,
                     })
                     .collect(),
+                altitude_meters_max,
                 ..Default::default()
-            }];
-
-            let result = update_zones(zones).await.unwrap_err();
-            assert_eq!(result, PostgisError::Zone(ZoneError::Location));
-        }
+            })
+            .unwrap()
+        };
+
+        // the last update of a zone wins, in the position it was sent
+        let zones = last_of_each_zone(vec![
+            zone("NFZ_A", 100.0),
+            zone("NFZ_B", 100.0),
+            zone("NFZ_A", 200.0),
+        ]);
+
+        let columns = ZoneColumns::from(zones.as_slice());
+        assert_eq!(columns.identifiers, vec!["NFZ_B", "NFZ_A"]);
+        assert_eq!(columns.altitudes_meters_max, vec![100.0, 200.0]);
+        assert_eq!(columns.geoms.len(), 2);
+        assert_eq!(columns.schedules, vec!["{}", "{}"]);
+        assert_eq!(columns.descriptions, vec![None, None]);
     }
 
     #[test]
@@ -540,14 +3118,590 @@ mod tests {
             format!("{}", ZoneError::Identifier),
             "Invalid identifier provided."
         );
+        assert_eq!(
+            format!("{}", ZoneError::Schedule),
+            "Invalid schedule provided."
+        );
+        assert_eq!(
+            format!("{}", ZoneError::AltitudeOrder),
+            "Minimum altitude is above maximum altitude."
+        );
+        assert_eq!(format!("{}", ZoneError::NotFound), "Zone not found.");
+        assert_eq!(
+            format!("{}", ZoneError::WaypointBuffer),
+            "Invalid waypoint buffer provided."
+        );
+        assert_eq!(
+            format!("{}", ZoneError::Metadata),
+            "Invalid zone metadata provided."
+        );
+        assert_eq!(
+            format!("{}", ZoneError::Rejected),
+            "Zone rejected by the backend."
+        );
         assert_eq!(
             format!("{}", ZoneError::ZoneType),
             "Invalid zone type provided."
         );
+        assert_eq!(
+            format!("{}", ZoneError::Pagination),
+            "Invalid pagination provided."
+        );
+        assert_eq!(
+            format!("{}", ZoneError::Encoding),
+            "Invalid geometry encoding provided."
+        );
+    }
+
+    #[test]
+    fn test_get_order_column() {
+        assert_eq!(get_order_column(OrderBy::Identifier), r#""identifier""#);
+        assert_eq!(get_order_column(OrderBy::LastUpdated), r#""last_updated""#);
+    }
+
+    #[tokio::test]
+    async fn ut_get_zones_invalid_page() {
+        let request = GetZonesRequest {
+            page: Some(grpc_server::Page {
+                limit: Some(0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = get_zones(request).await.unwrap_err();
+        assert_eq!(result, PostgisError::Zone(ZoneError::Pagination));
+    }
+
+    #[tokio::test]
+    async fn ut_get_zones_invalid_encoding() {
+        let request = GetZonesRequest {
+            encoding: 100,
+            ..Default::default()
+        };
+
+        let result = get_zones(request).await.unwrap_err();
+        assert_eq!(result, PostgisError::Zone(ZoneError::Encoding));
+    }
+
+    #[test]
+    fn ut_compact_zone() {
+        let vertices: Vec<Coordinates> = square(52.3745905, 4.9160036)
+            .into_iter()
+            .map(|(latitude, longitude)| Coordinates {
+                latitude,
+                longitude,
+            })
+            .collect();
+        let zone = RequestZone {
+            identifier: "ZONE".to_string(),
+            vertices: vertices.clone(),
+            parts: vec![ZonePart {
+                vertices: vertices.clone(),
+                compact_vertices: None,
+            }],
+            ..Default::default()
+        };
+
+        let compact = compact_zone(zone);
+        assert_eq!(compact.identifier, "ZONE");
+        assert!(compact.vertices.is_empty());
+        assert_eq!(compact.compact_vertices, Some(compact_ring(&vertices)));
+        assert!(compact.parts[0].vertices.is_empty());
+        assert_eq!(
+            compact.parts[0].compact_vertices,
+            Some(compact_ring(&vertices))
+        );
+    }
+
+    fn ring(vertices: &[(f64, f64)]) -> Vec<postgis::ewkb::Point> {
+        vertices
+            .iter()
+            .map(|(x, y)| postgis::ewkb::Point {
+                x: *x,
+                y: *y,
+                srid: Some(DEFAULT_SRID),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_zone_waypoint_locations() {
+        let (x, y) = (4.9160036, 52.3745905);
+        let d = 0.001;
+        let counter_clockwise = ring(&[
+            (x - d, y - d),
+            (x + d, y - d),
+            (x + d, y + d),
+            (x - d, y + d),
+            (x - d, y - d),
+        ]);
+
+        let mut clockwise = counter_clockwise.clone();
+        clockwise.reverse();
+
+        let buffer_meters = 100.0;
+        for vertices in [counter_clockwise, clockwise] {
+            let waypoints = zone_waypoint_locations(&vertices, buffer_meters);
+            assert_eq!(waypoints.len(), 4);
+
+            for (vertex, waypoint) in vertices.iter().zip(waypoints.iter()) {
+                // Outside of the zone, diagonally away from the corner
+                assert!((waypoint.x - x).abs() > d);
+                assert!((waypoint.y - y).abs() > d);
+                assert_eq!((waypoint.x - x).signum(), (vertex.x - x).signum());
+                assert_eq!((waypoint.y - y).signum(), (vertex.y - y).signum());
+
+                let distance = utils::distance_meters(
+                    &postgis::ewkb::PointZ::new(vertex.x, vertex.y, 0.0, None),
+                    &postgis::ewkb::PointZ::new(waypoint.x, waypoint.y, 0.0, None),
+                );
+
+                let expected = buffer_meters * 2.0_f64.sqrt();
+                assert!((distance as f64 - expected).abs() < 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zone_waypoint_locations_degenerate() {
+        let (x, y) = (4.9160036, 52.3745905);
+        assert!(zone_waypoint_locations(&[], 20.0).is_empty());
+        assert!(zone_waypoint_locations(&ring(&[(x, y), (x + 0.001, y), (x, y)]), 20.0).is_empty());
+
+        // Repeated vertices are skipped
+        let vertices = ring(&[(x, y), (x, y), (x, y), (x, y)]);
+        assert!(zone_waypoint_locations(&vertices, 20.0).is_empty());
+    }
+
+    #[test]
+    fn test_fato_axis_headings() {
+        let (x, y) = (4.9160036, 52.3745905);
+
+        // Longer from south to north
+        let fato = ring(&[
+            (x, y),
+            (x + 0.0002, y),
+            (x + 0.0002, y + 0.0005),
+            (x, y + 0.0005),
+            (x, y),
+        ]);
+
+        let headings = fato_axis_headings(&fato);
+        assert_eq!(headings.len(), 2);
+        assert!(headings[0].abs() < 1e-6 || (headings[0] - 180.0).abs() < 1e-6);
+        assert!(((headings[1] - headings[0]).abs() - 180.0).abs() < 1e-6);
+
+        assert!(fato_axis_headings(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_fato_approach_locations() {
+        let (x, y) = (4.9160036, 52.3745905);
+        let d = 0.0002;
+        let fato = ring(&[
+            (x - d, y - d),
+            (x + d, y - d),
+            (x + d, y + d),
+            (x - d, y + d),
+            (x - d, y - d),
+        ]);
+
+        // Landing northbound, approaching from the south
+        let buffer_meters = 100.0;
+        let waypoints = fato_approach_locations(&fato, &[0.0, 360.0], buffer_meters);
+        assert_eq!(waypoints.len(), 1);
+        assert!((waypoints[0].x - x).abs() < 1e-9);
+        assert!(waypoints[0].y < y - d);
+
+        let distance = utils::distance_meters(
+            &postgis::ewkb::PointZ::new(x, y - d, 0.0, None),
+            &postgis::ewkb::PointZ::new(waypoints[0].x, waypoints[0].y, 0.0, None),
+        );
+        assert!((distance as f64 - buffer_meters).abs() < 1.0);
+
+        // Landing eastbound, approaching from the west
+        let waypoints = fato_approach_locations(&fato, &[90.0], buffer_meters);
+        assert!(waypoints[0].x < x - d);
+        assert!((waypoints[0].y - y).abs() < 1e-9);
+
+        // The long axis from both ends
+        let waypoints = fato_approach_locations(&fato, &[], buffer_meters);
+        assert_eq!(waypoints.len(), 2);
+
+        assert!(fato_approach_locations(&fato[..2], &[0.0], buffer_meters).is_empty());
+    }
+
+    #[test]
+    fn test_zone_geom_waypoint_locations() {
+        let coordinates = |points: Vec<(f64, f64)>| -> Vec<Coordinates> {
+            points
+                .into_iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude,
+                    longitude,
+                })
+                .collect()
+        };
+
+        let request = RequestZone {
+            identifier: "NFZ".to_string(),
+            vertices: coordinates(square(52.3745905, 4.9160036)),
+            parts: vec![ZonePart {
+                vertices: coordinates(square(52.3845905, 4.9260036)),
+                compact_vertices: None,
+            }],
+            altitude_meters_max: 100.0,
+            waypoint_buffer_meters: Some(50.0),
+            ..Default::default()
+        };
+
+        let zone = Zone::try_from(request).unwrap();
+        let waypoints = zone_geom_waypoint_locations(&zone);
+        assert_eq!(waypoints.len(), 8);
+
+        // the first four surround the main area, the others the part
+        assert!(waypoints[..4].iter().all(|p| p.y < 52.38));
+        assert!(waypoints[4..].iter().all(|p| p.y > 52.38));
+    }
+
+    #[test]
+    fn test_zone_waypoint_identifier() {
+        let point = |x: f64, y: f64| postgis::ewkb::Point {
+            x,
+            y,
+            srid: Some(DEFAULT_SRID),
+        };
+
+        let identifier = zone_waypoint_identifier(7, &point(4.9160036, 52.3745905));
+        assert!(identifier.starts_with("7_waypoint_"));
+        assert!(check_identifier(Entity::Zone, &identifier).is_ok());
+
+        // Stable for the same location, below the hashed precision
+        assert_eq!(
+            identifier,
+            zone_waypoint_identifier(7, &point(4.91600360001, 52.3745905))
+        );
+
+        // Different location or zone
+        assert_ne!(
+            identifier,
+            zone_waypoint_identifier(7, &point(4.9160037, 52.3745905))
+        );
+        assert_ne!(
+            identifier,
+            zone_waypoint_identifier(8, &point(4.9160036, 52.3745905))
+        );
+    }
+
+    #[test]
+    fn test_match_waypoint_renames() {
+        let waypoint = |identifier: &str, x: f64, y: f64| {
+            (
+                identifier.to_string(),
+                postgis::ewkb::Point {
+                    x,
+                    y,
+                    srid: Some(DEFAULT_SRID),
+                },
+            )
+        };
+
+        let removed = vec![waypoint("a", 4.910, 52.370), waypoint("b", 4.920, 52.370)];
+        let added = vec![
+            waypoint("b2", 4.9201, 52.370),
+            waypoint("a2", 4.9101, 52.370),
+            waypoint("c", 4.950, 52.370),
+        ];
+
+        let renames = match_waypoint_renames(&removed, &added);
+        assert_eq!(
+            renames,
+            vec![
+                ("a".to_string(), "a2".to_string()),
+                ("b".to_string(), "b2".to_string())
+            ]
+        );
+
+        // More removed than added, the closest pair wins
+        let renames = match_waypoint_renames(&removed, &added[..1]);
+        assert_eq!(renames, vec![("b".to_string(), "b2".to_string())]);
+
+        assert!(match_waypoint_renames(&removed, &[]).is_empty());
+        assert!(match_waypoint_renames(&[], &added).is_empty());
+    }
+
+    fn zone_update(identifier: &str, seconds: i64, deleted: bool) -> ZoneUpdate {
+        let vertices = square(52.3745905, 4.9160036)
+            .into_iter()
+            .map(|(latitude, longitude)| ZoneVertex {
+                latitude,
+                longitude,
+            })
+            .collect::<Vec<_>>();
+
+        ZoneUpdate {
+            identifier: identifier.to_string(),
+            deleted,
+            zone_type: ZoneType::Restriction as i32,
+            vertices: vertices.clone(),
+            parts: vec![vertices],
+            altitude_meters_min: 20.0,
+            altitude_meters_max: 120.0,
+            time_start: None,
+            time_end: None,
+            schedule: vec![ZoneWindow {
+                days: vec![Weekday::Monday as i32],
+                start_minute: 60,
+                end_minute: 120,
+            }],
+            waypoint_buffer_meters: Some(50.0),
+            description: Some("Airshow".to_string()),
+            authority: None,
+            contact: None,
+            source_url: None,
+            timestamp_network: DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_request_zone_from_zone_update() {
+        let update = zone_update("NOTAM_1", 0, false);
+        let zone = RequestZone::from(update.clone());
+        assert_eq!(zone.identifier, update.identifier);
+        assert_eq!(zone.zone_type, ZoneType::Restriction as i32);
+        assert_eq!(zone.vertices.len(), update.vertices.len());
+        assert_eq!(zone.vertices[0].latitude, update.vertices[0].latitude);
+        assert_eq!(zone.vertices[0].longitude, update.vertices[0].longitude);
+        assert_eq!(zone.parts.len(), 1);
+        assert_eq!(
+            zone.schedule,
+            vec![ActiveWindow {
+                days: vec![Weekday::Monday as i32],
+                start_minute: 60,
+                end_minute: 120,
+            }]
+        );
+        assert_eq!(zone.waypoint_buffer_meters, Some(50.0));
+        assert_eq!(
+            zone.metadata
+                .as_ref()
+                .and_then(|m| m.description.as_deref()),
+            Some("Airshow")
+        );
+
+        let converted = Zone::try_from(zone).unwrap();
+        assert_eq!(converted.geom.polygons.len(), 2);
+    }
+
+    #[test]
+    fn test_zone_update_deserialize() {
+        // Delete markers only need an identifier and timestamp
+        let update: ZoneUpdate = serde_json::from_str(
+            r#"{"identifier":"NOTAM_1","deleted":true,"timestamp_network":"2023-11-14T22:13:20Z"}"#,
+        )
+        .unwrap();
+        assert!(update.deleted);
+        assert!(update.vertices.is_empty());
+        assert!(update.description.is_none());
+        assert_eq!(
+            update.timestamp_network,
+            DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_coalesce_zone_updates() {
+        let updates = vec![
+            zone_update("B", 10, false),
+            zone_update("A", 5, false),
+            zone_update("B", 20, true),
+            zone_update("A", 1, true),
+            zone_update("C", 3, false),
+            zone_update("C", 3, true),
+        ];
+
+        let summary: Vec<(String, bool)> = coalesce_zone_updates(updates)
+            .into_iter()
+            .map(|update| (update.identifier, update.deleted))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                // older delete is ignored
+                ("A".to_string(), false),
+                // newer delete wins
+                ("B".to_string(), true),
+                // ties go to the later entry
+                ("C".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_zone_updates() {
+        let mut invalid = zone_update("NOTAM_2", 0, false);
+        invalid.vertices.pop();
+
+        let updates = vec![
+            zone_update("NOTAM_1", 0, false),
+            invalid,
+            zone_update("NOTAM_3", 0, true),
+            zone_update("NOT A VALID ID", 0, true),
+        ];
+
+        // Invalid zones and deletions are dropped
+        let (zones, deleted) = split_zone_updates(updates);
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones[0].identifier, "NOTAM_1");
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].identifier, "NOTAM_3");
+    }
+
+    /// Zones in memory, refusing the updates of some zones
+    #[derive(Default)]
+    struct MockZones {
+        rejected: Vec<String>,
+        invalid: Vec<String>,
+        unavailable: bool,
+        applied: Vec<String>,
+    }
+
+    #[async_trait]
+    impl ZoneStore for MockZones {
+        async fn invalid_geometries(
+            &mut self,
+            updates: &[ZoneUpdate],
+        ) -> Result<Vec<String>, ApplyError> {
+            Ok(updates
+                .iter()
+                .map(|update| update.identifier.clone())
+                .filter(|identifier| self.invalid.contains(identifier))
+                .collect())
+        }
+
+        async fn apply(&mut self, updates: &[ZoneUpdate]) -> Result<(), ApplyError> {
+            if self.unavailable {
+                return Err(ApplyError::Backend);
+            }
+
+            // like a transaction, one rejected zone fails the whole batch
+            if updates
+                .iter()
+                .any(|update| self.rejected.contains(&update.identifier))
+            {
+                return Err(ApplyError::Rejected);
+            }
+
+            self.applied
+                .extend(updates.iter().map(|update| update.identifier.clone()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn ut_apply_zone_updates_rejected() {
+        let mut store = MockZones {
+            rejected: vec!["NOTAM_2".to_string(), "NOTAM_5".to_string()],
+            invalid: vec!["NOTAM_4".to_string()],
+            ..Default::default()
+        };
+
+        let updates = vec![
+            zone_update("NOTAM_1", 0, false),
+            zone_update("NOTAM_2", 0, false),
+            zone_update("NOTAM_3", 0, false),
+            zone_update("NOTAM_4", 0, false),
+            zone_update("NOTAM_5", 0, true),
+            zone_update("NOTAM_6", 0, true),
+        ];
+
+        // the bad zones don't block the good ones
+        let mut rejected: Vec<String> = apply_zone_updates(&mut store, updates)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|update| update.identifier)
+            .collect();
+        rejected.sort();
+        assert_eq!(rejected, vec!["NOTAM_2", "NOTAM_4", "NOTAM_5"]);
+
+        store.applied.sort();
+        assert_eq!(store.applied, vec!["NOTAM_1", "NOTAM_3", "NOTAM_6"]);
+    }
+
+    #[tokio::test]
+    async fn ut_apply_zone_updates_unavailable() {
+        let mut store = MockZones {
+            unavailable: true,
+            ..Default::default()
+        };
+
+        let updates = vec![zone_update("NOTAM_1", 0, false)];
+        let error = apply_zone_updates(&mut store, updates).await.unwrap_err();
+        assert_eq!(error, ApplyError::Backend);
+    }
+
+    #[test]
+    fn ut_is_rejection_code() {
+        // invalid geometry, string too long, unique violation
+        assert!(is_rejection_code("XX000"));
+        assert!(is_rejection_code("22001"));
+        assert!(is_rejection_code("23505"));
+
+        // connection failure, serialization failure, too many connections,
+        //  admin shutdown, undefined table
+        assert!(!is_rejection_code("08006"));
+        assert!(!is_rejection_code("40001"));
+        assert!(!is_rejection_code("53300"));
+        assert!(!is_rejection_code("57P01"));
+        assert!(!is_rejection_code("42P01"));
+    }
+
+    #[test]
+    fn ut_zone_volume_sql() {
+        let sql = zone_volume_sql(ZoneVolumes::Extruded, "$3", "$4", "$5");
+        assert!(sql.contains("ST_Extrude"));
+        assert_eq!(
+            zone_volume_sql(ZoneVolumes::Footprint, "$3", "$4", "$5"),
+            "NULL"
+        );
+    }
+
+    #[test]
+    fn ut_simplified_footprint_sql() {
+        let settings = simplify::Settings::default();
+        assert_eq!(simplified_footprint_sql("$3", &settings), "$3");
+
+        let settings = simplify::Settings {
+            zone_epsilon_meters: 10.0,
+            zone_min_vertices: 1000,
+            ..Default::default()
+        };
+
+        let sql = simplified_footprint_sql("$3", &settings);
+        assert!(sql.contains("WHEN ST_NPoints($3) > 1000"));
+        assert!(sql.contains("ST_SimplifyPreserveTopology(ST_Force2D($3), 0.0000899"));
+        assert!(sql.contains("'join=mitre'"));
+        assert!(sql.contains("ELSE $3"));
+    }
+
+    #[test]
+    fn ut_path_intersection_sql() {
+        let sql = path_intersection_sql(ZoneVolumes::Extruded, "$1");
+        assert_eq!(sql, r#"ST_3DIntersects("geom", $1)"#);
+
+        let sql = path_intersection_sql(ZoneVolumes::Footprint, "$1");
+        assert!(!sql.contains("ST_3DIntersects"));
+        assert!(sql.contains("ST_DumpSegments($1)"));
+        assert!(sql.contains(r#"ST_ZMin("segment"."geom") <= "altitude_meters_max""#));
+        assert!(sql.contains(r#"ST_ZMax("segment"."geom") >= "altitude_meters_min""#));
     }
 
     #[test]
     fn test_get_table_name() {
+        use crate::postgis::PSQL_SCHEMA;
         assert_eq!(get_table_name(), format!("\"{PSQL_SCHEMA}\".\"zones\""));
     }
 }
@@ -0,0 +1,180 @@
+//! Advance notice of zones activating or expiring.
+//!
+//! Each pass looks for zones whose `time_start` or `time_end` falls within
+//!  the lead time and announces them on the airspace Redis stream, and on
+//!  the pub/sub channel of the same name. A pass covers the time after the
+//!  window of the previous pass, so each change is announced once while the
+//!  server runs. Changes moved into a window that was already covered, and
+//!  the weekly schedules of zones, are not announced.
+
+use super::leader::{Leadership, Task};
+use super::zone;
+use crate::cache::pool::RedisPool;
+use crate::types::{ZoneEvent, ZoneEventKind};
+use lib_common::time::{DateTime, Duration, Utc};
+
+/// Default seconds of notice given before a zone activates or expires
+pub const DEFAULT_LEAD_SECONDS: u32 = 900;
+
+/// Default seconds between checks for upcoming zone changes
+pub const DEFAULT_INTERVAL_SECONDS: u64 = 60;
+
+/// Zone notification settings
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Settings {
+    /// Time between checks for upcoming zone changes
+    pub interval: std::time::Duration,
+
+    /// Seconds of notice given before a zone activates or expires
+    pub lead_seconds: u32,
+}
+
+/// The times at which a zone changes state
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneTimes {
+    /// Unique identifier of the zone
+    pub identifier: String,
+
+    /// The type of zone, as the gRPC `ZoneType` value
+    pub zone_type: i32,
+
+    /// When the zone activates, if it has a start time
+    pub time_start: Option<DateTime<Utc>>,
+
+    /// When the zone expires, if it has an end time
+    pub time_end: Option<DateTime<Utc>>,
+}
+
+impl ZoneTimes {
+    /// Events of this zone falling after `after`, up to and including `until`
+    pub fn events_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Vec<ZoneEvent> {
+        [
+            (ZoneEventKind::Activation, self.time_start),
+            (ZoneEventKind::Expiry, self.time_end),
+        ]
+        .into_iter()
+        .filter_map(|(kind, time)| Some((kind, time?)))
+        .filter(|(_, time)| *time > after && *time <= until)
+        .map(|(kind, time)| ZoneEvent {
+            identifier: self.identifier.clone(),
+            zone_type: self.zone_type,
+            kind,
+            time,
+            timestamp_network: now,
+        })
+        .collect()
+    }
+}
+
+/// End of the window announced by a pass at `now`
+fn horizon(now: DateTime<Utc>, lead_seconds: u32) -> DateTime<Utc> {
+    Duration::try_seconds(lead_seconds as i64)
+        .and_then(|lead| now.checked_add_signed(lead))
+        .unwrap_or(now)
+}
+
+/// Announces zone changes on the airspace stream and channel at each
+///  interval. A window that could not be announced is retried at the next
+///  pass. Replicas that don't lead still advance their window, so a replica
+///  taking over announces from where the previous leader stopped.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) need running postgresql and redis instances
+pub async fn run(settings: Settings, publisher: RedisPool) {
+    let mut interval = tokio::time::interval(settings.interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let mut leadership = Leadership::new(Task::ZoneEvents);
+    let mut after = Utc::now();
+    loop {
+        interval.tick().await;
+        let until = horizon(Utc::now(), settings.lead_seconds);
+        if !leadership.check().await {
+            after = until;
+            continue;
+        }
+
+        let events = match zone::get_zone_events(after, until).await {
+            Ok(events) => events,
+            Err(e) => {
+                postgis_error!("could not get upcoming zone changes: {e}");
+                continue;
+            }
+        };
+
+        if let Err(e) = publisher.push_stream(&events).await {
+            postgis_error!("could not add zone changes to the airspace stream: {e}");
+            continue;
+        }
+
+        // Subscribers that miss a notice can still read it from the stream
+        if let Err(e) = publisher.publish(&events).await {
+            postgis_warn!("could not publish zone changes: {e}");
+        }
+
+        if !events.is_empty() {
+            postgis_info!("announced {} upcoming zone change(s).", events.len());
+        }
+
+        after = until;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone_times(now: DateTime<Utc>) -> ZoneTimes {
+        ZoneTimes {
+            identifier: "NOTAM-1".to_string(),
+            zone_type: 1,
+            time_start: Some(now + Duration::try_minutes(10).unwrap()),
+            time_end: Some(now + Duration::try_minutes(40).unwrap()),
+        }
+    }
+
+    #[test]
+    fn ut_events_between() {
+        let now = Utc::now();
+        let zone = zone_times(now);
+
+        let events = zone.events_between(now, horizon(now, 900), now);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, ZoneEventKind::Activation);
+        assert_eq!(events[0].time, zone.time_start.unwrap());
+        assert_eq!(events[0].identifier, zone.identifier);
+
+        let events = zone.events_between(now, horizon(now, 3600), now);
+        let kinds: Vec<ZoneEventKind> = events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![ZoneEventKind::Activation, ZoneEventKind::Expiry]
+        );
+
+        // the start was announced by an earlier window
+        let after = zone.time_start.unwrap();
+        let events = zone.events_between(after, horizon(after, 3600), now);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, ZoneEventKind::Expiry);
+
+        let permanent = ZoneTimes {
+            time_start: None,
+            time_end: None,
+            ..zone
+        };
+        assert!(permanent
+            .events_between(now, horizon(now, 3600), now)
+            .is_empty());
+    }
+
+    #[test]
+    fn ut_horizon() {
+        let now = Utc::now();
+        assert_eq!(horizon(now, 0), now);
+        assert_eq!(horizon(now, 60), now + Duration::try_seconds(60).unwrap());
+    }
+}
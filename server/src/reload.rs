@@ -0,0 +1,476 @@
+//! Reload of the tunable settings without a restart.
+//!
+//! Connections, ports and the table layout are read once at startup. The
+//!  tunable settings (position quality thresholds, telemetry rules,
+//!  duplicate suppression, simplification tolerances, path weights,
+//!  altitude limits, separation margins, performance profiles, best path
+//!  auditing, retention periods and the queue latency threshold) are
+//!  read again when the server receives SIGHUP or the reload file changes.
+//!
+//! All the new settings are validated before any of them is applied, so a
+//!  bad value leaves every current setting in place. Each setting that
+//!  changed is logged.
+
+use crate::cache::latency;
+use crate::config::Config;
+use crate::postgis::aircraft::{self, QualityThresholds};
+use crate::postgis::routing::{self, AltitudeLimits, CostWeights, SeparationMargins};
+use crate::postgis::{audit, dedupe, maintenance, reachability, rules, simplify};
+use crate::types::AircraftType;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::path::Path;
+use std::sync::{PoisonError, RwLock};
+use std::time::SystemTime;
+
+/// Seconds between checks of the reload file for changes
+pub const WATCH_INTERVAL_SECONDS: u64 = 5;
+
+/// A setting that can be replaced while the server runs
+#[derive(Debug)]
+pub struct Tunable<T>(RwLock<Option<T>>);
+
+impl<T: Clone> Tunable<T> {
+    /// A setting that was not set yet
+    pub const fn new() -> Self {
+        Tunable(RwLock::new(None))
+    }
+
+    /// Replaces the setting
+    pub fn set(&self, value: T) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Some(value);
+    }
+
+    /// Gets the setting, `None` if it was never set
+    pub fn get(&self) -> Option<T> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl<T: Clone> Default for Tunable<T> {
+    fn default() -> Self {
+        Tunable::new()
+    }
+}
+
+/// Possible errors reloading the settings
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReloadError {
+    /// The configuration could not be read
+    Config,
+
+    /// A setting is invalid
+    Setting,
+}
+
+impl Display for ReloadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ReloadError::Config => write!(f, "Could not read the configuration."),
+            ReloadError::Setting => write!(f, "Invalid setting provided."),
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
+/// The settings that can change without a restart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tunables {
+    /// Ages at which aircraft positions become stale or expired
+    pub quality_thresholds: QualityThresholds,
+
+    /// Validation rules applied to aircraft reports
+    pub rules: rules::Rules,
+
+    /// Duplicate suppression of aircraft reports
+    pub dedupe: dedupe::Settings,
+
+    /// Track and flight path simplification
+    pub simplify: simplify::Settings,
+
+    /// Weights of the path objectives when a request doesn't provide any
+    pub weights: CostWeights,
+
+    /// Altitudes each aircraft type may cruise at
+    pub altitude_limits: Vec<(AircraftType, AltitudeLimits)>,
+
+    /// Separation each aircraft type keeps from other flights
+    pub separation_margins: Vec<(AircraftType, SeparationMargins)>,
+
+    /// Performance profiles and detour factor of reachability checks
+    pub reachability: reachability::Settings,
+
+    /// Whether best paths are recorded for audit
+    pub best_path_audit: bool,
+
+    /// Database maintenance and retention periods
+    pub maintenance: maintenance::Settings,
+
+    /// p95 queue latency over which a warning is logged, in milliseconds
+    pub queue_latency_warn_ms: u64,
+}
+
+impl TryFrom<&Config> for Tunables {
+    type Error = ReloadError;
+
+    fn try_from(config: &Config) -> Result<Self, Self::Error> {
+        let tunables = Tunables {
+            quality_thresholds: QualityThresholds {
+                stale_seconds: config.aircraft_stale_seconds,
+                expired_seconds: config.aircraft_expired_seconds,
+            },
+            rules: rules::Rules {
+                max_speed_jump_mps: config.telemetry_max_speed_jump_mps,
+                max_position_jump_meters: config.telemetry_max_position_jump_meters,
+                altitude_ceiling_meters: config.telemetry_altitude_ceiling_meters,
+                clock_skew_seconds: config.telemetry_clock_skew_seconds,
+                bypass: rules::bypass_list(&config.telemetry_bypass_identifiers),
+            },
+            dedupe: dedupe::Settings {
+                position_epsilon_meters: config.dedupe_position_epsilon_meters,
+                velocity_epsilon_mps: config.dedupe_velocity_epsilon_mps,
+                heartbeat_seconds: config.dedupe_heartbeat_seconds,
+            },
+            simplify: simplify::Settings {
+                epsilon_meters: config.track_simplify_epsilon_meters,
+                raw_window_seconds: config.track_raw_window_seconds,
+                path_epsilon_meters: config.flight_path_simplify_epsilon_meters,
+                zone_epsilon_meters: config.zone_simplify_epsilon_meters,
+                zone_min_vertices: config.zone_simplify_min_vertices,
+            },
+            weights: CostWeights {
+                distance: config.path_weight_distance,
+                time: config.path_weight_time,
+                ground_risk: config.path_weight_ground_risk,
+                noise: config.path_weight_noise,
+                energy: config.path_weight_energy,
+            },
+            altitude_limits: routing::parse_altitude_limits(&config.aircraft_altitude_limits)
+                .map_err(|_| ReloadError::Setting)?,
+            separation_margins: routing::parse_separation_margins(
+                &config.flight_separation_margins,
+            )
+            .map_err(|_| ReloadError::Setting)?,
+            reachability: reachability::Settings {
+                profiles: reachability::parse_profiles(&config.aircraft_performance_profiles)
+                    .map_err(|_| ReloadError::Setting)?,
+                detour_factor: config.reachability_detour_factor,
+            },
+            // query-only replicas don't write to the database
+            best_path_audit: config.best_path_audit && !config.grpc_query_only,
+            maintenance: maintenance::Settings {
+                interval: std::time::Duration::from_secs(config.maintenance_interval_seconds),
+                flight_retention_days: config.flight_retention_days,
+                archive_flights: config.flight_retention_archive,
+                audit_retention_days: config.best_path_audit_retention_days,
+                history_retention_days: config.history_retention_days,
+                session_expiry_seconds: config.aircraft_session_expiry_seconds,
+            },
+            queue_latency_warn_ms: config.redis_queue_latency_warn_ms,
+        };
+
+        tunables.check()?;
+        Ok(tunables)
+    }
+}
+
+impl Tunables {
+    /// Checks each setting, the duplicate suppression against the new
+    ///  position quality thresholds
+    fn check(&self) -> Result<(), ReloadError> {
+        aircraft::check_quality_thresholds(&self.quality_thresholds)
+            .and_then(|_| rules::check_rules(&self.rules))
+            .and_then(|_| dedupe::check_settings(&self.dedupe, &self.quality_thresholds))
+            .and_then(|_| routing::check_default_weights(&self.weights))
+            .and_then(|_| reachability::check_settings(&self.reachability))
+            .map_err(|_| ReloadError::Setting)?;
+
+        simplify::check_settings(&self.simplify).map_err(|_| ReloadError::Setting)?;
+
+        if self.maintenance.interval.is_zero() {
+            log::error!("(reload) maintenance interval must be at least one second.");
+            return Err(ReloadError::Setting);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the settings in use. The settings are checked first, and
+    ///  none is replaced if any is invalid.
+    pub fn apply(&self) -> Result<(), ReloadError> {
+        self.check()?;
+
+        // the thresholds first, the duplicate suppression is checked against them
+        aircraft::set_quality_thresholds(self.quality_thresholds)
+            .and_then(|_| rules::set_rules(self.rules.clone()))
+            .and_then(|_| dedupe::set_settings(self.dedupe))
+            .and_then(|_| routing::set_default_weights(self.weights))
+            .and_then(|_| reachability::set_settings(self.reachability.clone()))
+            .map_err(|_| ReloadError::Setting)?;
+
+        simplify::set_settings(self.simplify).map_err(|_| ReloadError::Setting)?;
+        routing::set_altitude_limits(self.altitude_limits.clone());
+        routing::set_separation_margins(self.separation_margins.clone());
+        audit::set_enabled(self.best_path_audit);
+        maintenance::set_settings(self.maintenance);
+        latency::set_warn_ms(self.queue_latency_warn_ms);
+        Ok(())
+    }
+
+    /// Describes each setting that differs in the other settings, as
+    ///  `setting: current -> other`
+    pub fn changes(&self, other: &Tunables) -> Vec<String> {
+        [
+            change(
+                "quality_thresholds",
+                &self.quality_thresholds,
+                &other.quality_thresholds,
+            ),
+            change("rules", &self.rules, &other.rules),
+            change("dedupe", &self.dedupe, &other.dedupe),
+            change("simplify", &self.simplify, &other.simplify),
+            change("weights", &self.weights, &other.weights),
+            change(
+                "altitude_limits",
+                &self.altitude_limits,
+                &other.altitude_limits,
+            ),
+            change(
+                "separation_margins",
+                &self.separation_margins,
+                &other.separation_margins,
+            ),
+            change("reachability", &self.reachability, &other.reachability),
+            change(
+                "best_path_audit",
+                &self.best_path_audit,
+                &other.best_path_audit,
+            ),
+            change("maintenance", &self.maintenance, &other.maintenance),
+            change(
+                "queue_latency_warn_ms",
+                &self.queue_latency_warn_ms,
+                &other.queue_latency_warn_ms,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// Describes a setting if it differs, as `setting: current -> other`
+fn change<T: Debug + PartialEq>(name: &str, current: &T, other: &T) -> Option<String> {
+    (current != other).then(|| format!("{name}: {current:?} -> {other:?}"))
+}
+
+/// Reads the configuration again, the variables of the reload file (if
+///  any) taking precedence over the environment
+fn read_config(file: &str) -> Result<Config, ReloadError> {
+    let config = if file.is_empty() {
+        Config::try_from_env()
+    } else {
+        Config::try_from_env_and_file(file)
+    };
+
+    config.map_err(|e| {
+        log::error!("(reload) could not read the configuration: {e}");
+        ReloadError::Config
+    })
+}
+
+/// Reads the tunable settings again and applies them if valid, logging
+///  each change. The current settings are kept otherwise.
+pub fn reload(current: &mut Tunables, file: &str) -> Result<(), ReloadError> {
+    let next = Tunables::try_from(&read_config(file)?)?;
+    let changes = current.changes(&next);
+    if changes.is_empty() {
+        log::info!("(reload) settings unchanged.");
+        return Ok(());
+    }
+
+    next.apply()?;
+    for change in &changes {
+        log::info!("(reload) {change}");
+    }
+
+    *current = next;
+    Ok(())
+}
+
+/// Last modification time of the reload file, `None` if it can't be read
+fn modified(file: &str) -> Option<SystemTime> {
+    if file.is_empty() {
+        return None;
+    }
+
+    std::fs::metadata(Path::new(file))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reloads the tunable settings on each SIGHUP, and when the reload file
+///  changes
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) waits for signals for the lifetime of the server
+pub async fn watch(mut current: Tunables, file: String) {
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            log::error!("(reload) could not listen for SIGHUP: {e}");
+            return;
+        }
+    };
+
+    let mut last_modified = modified(&file);
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(WATCH_INTERVAL_SECONDS));
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => log::info!("(reload) SIGHUP received."),
+            _ = interval.tick() => {
+                let next_modified = modified(&file);
+                if next_modified == last_modified {
+                    continue;
+                }
+
+                last_modified = next_modified;
+                log::info!("(reload) {file} changed.");
+            }
+        }
+
+        if let Err(e) = reload(&mut current, &file) {
+            log::error!("(reload) settings kept unchanged: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tunables() -> Tunables {
+        Tunables::try_from(&Config::default()).unwrap()
+    }
+
+    #[test]
+    fn ut_tunable() {
+        let tunable: Tunable<u32> = Tunable::new();
+        assert_eq!(tunable.get(), None);
+
+        tunable.set(1);
+        tunable.set(2);
+        assert_eq!(tunable.get(), Some(2));
+    }
+
+    #[test]
+    fn ut_tunables_try_from() {
+        let tunables = tunables();
+        assert_eq!(tunables.quality_thresholds, QualityThresholds::default());
+        assert_eq!(tunables.simplify, simplify::Settings::default());
+        assert_eq!(tunables.maintenance, maintenance::Settings::default());
+        assert!(tunables.altitude_limits.is_empty());
+        assert_eq!(tunables.reachability, reachability::Settings::default());
+        assert!(!tunables.best_path_audit);
+    }
+
+    #[test]
+    fn ut_tunables_query_only() {
+        let config = Config {
+            best_path_audit: true,
+            ..Config::default()
+        };
+        assert!(Tunables::try_from(&config).unwrap().best_path_audit);
+
+        let config = Config {
+            best_path_audit: true,
+            grpc_query_only: true,
+            ..Config::default()
+        };
+        assert!(!Tunables::try_from(&config).unwrap().best_path_audit);
+    }
+
+    #[test]
+    fn ut_tunables_invalid() {
+        // positions would expire before they become stale
+        let config = Config {
+            aircraft_stale_seconds: 30,
+            aircraft_expired_seconds: 10,
+            ..Config::default()
+        };
+        assert_eq!(Tunables::try_from(&config), Err(ReloadError::Setting));
+
+        // checked against the new stale threshold, not the current one
+        let config = Config {
+            aircraft_stale_seconds: 2,
+            dedupe_heartbeat_seconds: 3,
+            ..Config::default()
+        };
+        assert_eq!(Tunables::try_from(&config), Err(ReloadError::Setting));
+
+        let config = Config {
+            track_simplify_epsilon_meters: -1.0,
+            ..Config::default()
+        };
+        assert_eq!(Tunables::try_from(&config), Err(ReloadError::Setting));
+
+        let config = Config {
+            aircraft_altitude_limits: "Rotorcraft:120-0".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(Tunables::try_from(&config), Err(ReloadError::Setting));
+
+        let config = Config {
+            flight_separation_margins: "Rotorcraft:10/0".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(Tunables::try_from(&config), Err(ReloadError::Setting));
+
+        let config = Config {
+            reachability_detour_factor: 0.5,
+            ..Config::default()
+        };
+        assert_eq!(Tunables::try_from(&config), Err(ReloadError::Setting));
+
+        let config = Config {
+            maintenance_interval_seconds: 0,
+            ..Config::default()
+        };
+        assert_eq!(Tunables::try_from(&config), Err(ReloadError::Setting));
+    }
+
+    #[test]
+    fn ut_tunables_changes() {
+        let current = tunables();
+        assert!(current.changes(&current).is_empty());
+
+        let mut next = current.clone();
+        next.queue_latency_warn_ms = 250;
+        next.maintenance.flight_retention_days = 7;
+        assert_eq!(
+            current.changes(&next),
+            vec![
+                format!(
+                    "maintenance: {:?} -> {:?}",
+                    current.maintenance, next.maintenance
+                ),
+                format!("queue_latency_warn_ms: {} -> 250", latency::DEFAULT_WARN_MS),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reload_error_display() {
+        assert_eq!(
+            ReloadError::Config.to_string(),
+            "Could not read the configuration."
+        );
+        assert_eq!(
+            ReloadError::Setting.to_string(),
+            "Invalid setting provided."
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Throwaway PostGIS and Redis backends for the integration tests
+//!
+//! The containers are started with the docker CLI on ports chosen by docker,
+//!  and removed when the [`Backends`] are dropped. The images default to
+//!  those of `docker-compose.yml` and can be overridden with
+//!  `IT_POSTGIS_IMAGE` and `IT_REDIS_IMAGE`.
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+use svc_gis::config::Config;
+use svc_gis::postgis::pool::FailoverPool;
+use svc_gis::postgis::DEADPOOL_POSTGIS;
+
+/// Default image of the PostGIS container
+const POSTGIS_IMAGE: &str = "ghcr.io/arrow-air/tools/arrow-gis:1.0";
+
+/// Default image of the Redis container
+const REDIS_IMAGE: &str = "redis:6.2-alpine";
+
+/// Max time for a container to accept connections
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Time between two readiness checks
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs a docker command, panicking if it fails
+fn docker(args: &[&str]) -> String {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .unwrap_or_else(|e| panic!("could not run docker {args:?}: {e}"));
+
+    assert!(
+        output.status.success(),
+        "docker {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// A detached container, removed when dropped
+#[derive(Debug)]
+pub struct Container {
+    id: String,
+}
+
+impl Container {
+    /// Starts a container publishing its exposed ports on random host ports
+    pub fn start(image: &str, args: &[&str]) -> Self {
+        let mut run = vec!["run", "--detach", "--rm", "--publish-all"];
+        run.extend_from_slice(args);
+        run.push(image);
+
+        Container { id: docker(&run) }
+    }
+
+    /// Host port a container port is published on
+    pub fn host_port(&self, port: u16) -> u16 {
+        let mapping = docker(&["port", &self.id, &format!("{port}/tcp")]);
+
+        // one line per address family, e.g. "0.0.0.0:49153"
+        mapping
+            .lines()
+            .find_map(|line| line.rsplit(':').next()?.parse().ok())
+            .unwrap_or_else(|| panic!("port {port} is not published: {mapping}"))
+    }
+}
+
+impl Drop for Container {
+    fn drop(&mut self) {
+        let _ = Command::new("docker")
+            .args(["rm", "--force", &self.id])
+            .output();
+    }
+}
+
+/// The running backends and the configuration to reach them
+#[derive(Debug)]
+pub struct Backends {
+    /// Configuration pointing at the containers
+    pub config: Config,
+
+    _postgis: Container,
+    _redis: Container,
+}
+
+impl Backends {
+    /// Starts the containers and sets [`DEADPOOL_POSTGIS`] once PostGIS
+    ///  accepts connections. Can only be called once per test binary.
+    pub async fn start() -> Self {
+        let init_sql = concat!(env!("CARGO_MANIFEST_DIR"), "/../scripts/init.sql");
+        let mount = format!("{init_sql}:/docker-entrypoint-initdb.d/init.sql:ro");
+
+        let postgis_image = std::env::var("IT_POSTGIS_IMAGE").unwrap_or(POSTGIS_IMAGE.into());
+        let postgis = Container::start(
+            &postgis_image,
+            &[
+                "--env",
+                "POSTGRES_HOST_AUTH_METHOD=trust",
+                "--volume",
+                &mount,
+            ],
+        );
+
+        let redis_image = std::env::var("IT_REDIS_IMAGE").unwrap_or(REDIS_IMAGE.into());
+        let redis = Container::start(&redis_image, &[]);
+
+        let mut config = Config::new();
+        config.pg.host = Some("localhost".to_string());
+        config.pg.port = Some(postgis.host_port(5432));
+        config.pg.user = Some("svc_gis".to_string());
+        config.pg.dbname = Some("gis".to_string());
+        config.redis.url = Some(format!("redis://localhost:{}", redis.host_port(6379)));
+
+        let pool = FailoverPool::without_tls(config.clone()).expect("could not create pool");
+        DEADPOOL_POSTGIS
+            .set(pool)
+            .expect("DEADPOOL_POSTGIS was already set");
+
+        let backends = Backends {
+            config,
+            _postgis: postgis,
+            _redis: redis,
+        };
+
+        backends.wait_for_postgis().await;
+        backends
+    }
+
+    /// Waits until the init script has run. The server only listens on TCP
+    ///  once the scripts of the entrypoint are done.
+    async fn wait_for_postgis(&self) {
+        let pool = DEADPOOL_POSTGIS.get().expect("DEADPOOL_POSTGIS is not set");
+        let started = Instant::now();
+
+        loop {
+            if let Ok(client) = pool.get().await {
+                if client
+                    .simple_query("SELECT postgis_version();")
+                    .await
+                    .is_ok()
+                {
+                    return;
+                }
+            }
+
+            assert!(
+                started.elapsed() < STARTUP_TIMEOUT,
+                "PostGIS did not accept connections within {STARTUP_TIMEOUT:?}"
+            );
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
@@ -1,9 +1,573 @@
-//! Example for writing an integration test.
-//! More information: https://doc.rust-lang.org/book/testing-rust.html#integration-tests
+//! End-to-end tests against PostGIS and Redis containers
+//!
+//! Run with `cargo test -p svc-gis --features integration_test --test integration_test`,
+//!  a docker daemon must be reachable.
+//!
+//! The database pool is global, so the scenarios share one set of containers
+//!  and run in order within a single test.
 
-// use tmp_lib;
+#![cfg(feature = "integration_test")]
 
-// #[test]
-// fn it_add_one() {
-//     assert_eq!(2, tmp_lib::add_one(1));
-// }
+mod common;
+
+use lib_common::time::{DateTime, Duration, Utc};
+use svc_gis::cache::pool::RedisPool;
+use svc_gis::cache::{Consumer, Processor};
+use svc_gis::fixtures::{self, best_path_request, centroid, rectangle, Scenario};
+use svc_gis::grpc::server::grpc_server::{
+    BestPathRequest, CheckIntersectionRequest, FlightPriority, GetZonesAtPointRequest,
+    ListAuthorizationsRequest, Path, PointZ, ReserveVolumeRequest, UpdateFlightPathRequest,
+    UpdateVertiportsRequest, UpdateZonesRequest, Vertiport, Zone, ZoneAuthorizationRequest,
+    ZoneType,
+};
+use svc_gis::grpc::server::{IngestService, QueryService, ServerImpl};
+use svc_gis::postgis::aircraft::get_aircraft_state;
+use svc_gis::types::{AircraftPosition, AircraftType, Position, REDIS_KEY_AIRCRAFT_POSITION};
+use tonic::{Code, Request};
+
+/// Altitude of the Alkmaar vertiports
+const ALKMAAR_ALTITUDE: f32 = 10.0;
+
+/// Identifiers of the Alkmaar vertiports
+const ALKMAAR_1_ID: &str = "ALKMAAR_1";
+const ALKMAAR_2_ID: &str = "ALKMAAR_2";
+
+/// Identifier of the zone east of Alkmaar
+const ZONE_ID: &str = "IT-ZONE-1";
+
+/// Aircraft authorized to enter the zone east of Alkmaar
+const AUTHORIZED_AIRCRAFT_ID: &str = "IT-AUTH-CRAFT";
+
+/// Operator reserving the volume around the Alkmaar vertiports
+const RESERVING_OPERATOR_ID: &str = "IT-OPERATOR-1";
+
+/// The two vertiports of the Alkmaar example, about 500 meters apart
+fn alkmaar_vertiports() -> (Vertiport, Vertiport) {
+    let vertiport = |identifier: &str, label: &str, vertices| Vertiport {
+        identifier: identifier.to_string(),
+        vertices,
+        altitude_meters: ALKMAAR_ALTITUDE,
+        label: Some(label.to_string()),
+        timestamp_network: Some(Utc::now().into()),
+        status: None,
+        layers: None,
+        compact_vertices: None,
+        operating_hours: vec![],
+    };
+
+    (
+        vertiport(
+            ALKMAAR_1_ID,
+            "Alkmaar 1",
+            rectangle(
+                (4.713655228916873, 52.63006644856257),
+                (4.714239138046231, 52.63040456142831),
+            ),
+        ),
+        vertiport(
+            ALKMAAR_2_ID,
+            "Alkmaar 2",
+            rectangle(
+                (4.7183918814820895, 52.633870374451455),
+                (4.718710818704621, 52.63404933036138),
+            ),
+        ),
+    )
+}
+
+/// Best paths for a request, none if the search found no clear path
+async fn best_path(request: BestPathRequest) -> Vec<Path> {
+    let response = ServerImpl {}.best_path(Request::new(request)).await;
+    match response {
+        Ok(response) => response.into_inner().paths,
+        Err(status) if status.code() == Code::NotFound => vec![],
+        Err(status) => panic!("best path failed: {status}"),
+    }
+}
+
+/// Best paths between the Alkmaar vertiports
+async fn alkmaar_best_path(time_start: DateTime<Utc>, time_end: DateTime<Utc>) -> Vec<Path> {
+    best_path(best_path_request(
+        ALKMAAR_1_ID,
+        ALKMAAR_2_ID,
+        time_start,
+        time_end,
+    ))
+    .await
+}
+
+/// Files a flight between the Alkmaar vertiports at the provided altitude
+async fn file_alkmaar_flight(
+    flight_identifier: &str,
+    altitude_meters: f32,
+    time_start: DateTime<Utc>,
+    time_end: DateTime<Utc>,
+) {
+    let (alkmaar_1, alkmaar_2) = alkmaar_vertiports();
+    let request = UpdateFlightPathRequest {
+        flight_identifier: Some(flight_identifier.to_string()),
+        aircraft_identifier: Some(format!("{flight_identifier}-CRAFT")),
+        path: vec![
+            centroid(&alkmaar_1, altitude_meters),
+            centroid(&alkmaar_2, altitude_meters),
+        ],
+        timestamp_start: Some(time_start.into()),
+        timestamp_end: Some(time_end.into()),
+        simulated: false,
+        aircraft_type: AircraftType::Rotorcraft as i32,
+        priority: FlightPriority::Routine as i32,
+    };
+
+    ServerImpl {}
+        .update_flight_path(Request::new(request))
+        .await
+        .expect("could not file flight");
+}
+
+/// Checks a path for intersections within the next hour
+async fn intersects(path: Vec<PointZ>) -> bool {
+    intersects_for(path, None).await
+}
+
+/// Checks a path flown by an aircraft for intersections within the next
+///  hour
+async fn intersects_for(path: Vec<PointZ>, aircraft_identifier: Option<&str>) -> bool {
+    intersects_between(ALKMAAR_1_ID, ALKMAAR_2_ID, path, aircraft_identifier).await
+}
+
+/// Checks a path between two vertiports, flown by an aircraft, for
+///  intersections within the next hour
+async fn intersects_between(
+    origin_identifier: &str,
+    target_identifier: &str,
+    path: Vec<PointZ>,
+    aircraft_identifier: Option<&str>,
+) -> bool {
+    let time_start = Utc::now();
+    let request = CheckIntersectionRequest {
+        origin_identifier: origin_identifier.to_string(),
+        target_identifier: target_identifier.to_string(),
+        path,
+        time_start: Some(time_start.into()),
+        time_end: Some((time_start + Duration::try_hours(1).unwrap()).into()),
+        include_simulated: false,
+        priority: FlightPriority::Routine as i32,
+        aircraft_identifier: aircraft_identifier.map(str::to_string),
+        flight_identifier: None,
+        aircraft_type: None,
+    };
+
+    ServerImpl {}
+        .check_intersection(Request::new(request))
+        .await
+        .expect("could not check intersection")
+        .into_inner()
+        .intersects
+}
+
+/// Inserts the vertiports, waypoints and zones of a fixture scenario, and
+///  files its flights
+async fn insert(scenario: &Scenario) {
+    if !scenario.vertiports.is_empty() {
+        ServerImpl {}
+            .update_vertiports(Request::new(scenario.vertiports_request()))
+            .await
+            .expect("could not insert vertiports");
+    }
+
+    if !scenario.waypoints.is_empty() {
+        ServerImpl {}
+            .update_waypoints(Request::new(scenario.waypoints_request()))
+            .await
+            .expect("could not insert waypoints");
+    }
+
+    if !scenario.zones.is_empty() {
+        ServerImpl {}
+            .update_zones(Request::new(scenario.zones_request()))
+            .await
+            .expect("could not insert zones");
+    }
+
+    for flight in &scenario.flights {
+        ServerImpl {}
+            .update_flight_path(Request::new(flight.clone()))
+            .await
+            .expect("could not file flight");
+    }
+}
+
+/// Inserts the Alkmaar vertiports
+async fn scenario_vertiports() {
+    let (alkmaar_1, alkmaar_2) = alkmaar_vertiports();
+    let request = UpdateVertiportsRequest {
+        vertiports: vec![alkmaar_1, alkmaar_2],
+        validate_only: false,
+    };
+
+    let response = ServerImpl {}
+        .update_vertiports(Request::new(request))
+        .await
+        .expect("could not insert vertiports");
+    assert!(response.into_inner().updated);
+
+    // invalid vertiports are rejected as a whole
+    let mut invalid = alkmaar_vertiports().0;
+    invalid.vertices.truncate(2);
+    let request = UpdateVertiportsRequest {
+        vertiports: vec![invalid],
+        validate_only: false,
+    };
+
+    assert!(ServerImpl {}
+        .update_vertiports(Request::new(request))
+        .await
+        .is_err());
+}
+
+/// Inserts a restricted zone east of the vertiports and checks paths
+///  through and around it
+async fn scenario_zones() {
+    let zone = Zone {
+        identifier: ZONE_ID.to_string(),
+        zone_type: ZoneType::Restriction as i32,
+        vertices: rectangle((4.7300, 52.6290), (4.7350, 52.6340)),
+        altitude_meters_min: 0.0,
+        altitude_meters_max: 500.0,
+        ..Default::default()
+    };
+
+    let response = ServerImpl {}
+        .update_zones(Request::new(UpdateZonesRequest {
+            zones: vec![zone],
+            validate_only: false,
+        }))
+        .await
+        .expect("could not insert zone");
+    assert!(response.into_inner().updated);
+
+    let point = |longitude, latitude| PointZ {
+        latitude,
+        longitude,
+        altitude_meters: 100.0,
+    };
+
+    let through_zone = vec![point(4.7250, 52.6315), point(4.7400, 52.6315)];
+    assert!(intersects(through_zone).await);
+
+    let clear_of_zone = vec![point(4.7250, 52.6400), point(4.7400, 52.6400)];
+    assert!(!intersects(clear_of_zone).await);
+
+    // An authorized aircraft may fly through the zone, others may not
+    let time_start = Utc::now() - Duration::try_minutes(1).unwrap();
+    let request = ZoneAuthorizationRequest {
+        zone_identifier: ZONE_ID.to_string(),
+        aircraft_identifier: Some(AUTHORIZED_AIRCRAFT_ID.to_string()),
+        flight_identifier: None,
+        time_start: Some(time_start.into()),
+        time_end: Some((time_start + Duration::try_hours(2).unwrap()).into()),
+        reference: None,
+    };
+
+    ServerImpl {}
+        .request_zone_authorization(Request::new(request))
+        .await
+        .expect("could not authorize zone entry");
+
+    let through_zone = vec![point(4.7250, 52.6315), point(4.7400, 52.6315)];
+    assert!(!intersects_for(through_zone.clone(), Some(AUTHORIZED_AIRCRAFT_ID)).await);
+    assert!(intersects_for(through_zone, Some("IT-OTHER-CRAFT")).await);
+
+    let authorizations = ServerImpl {}
+        .list_authorizations(Request::new(ListAuthorizationsRequest {
+            zone_identifier: Some(ZONE_ID.to_string()),
+            ..Default::default()
+        }))
+        .await
+        .expect("could not list authorizations")
+        .into_inner()
+        .authorizations;
+    assert_eq!(authorizations.len(), 1);
+}
+
+/// The Alkmaar example: flights between the same vertiports are only
+///  conflicts when they overlap in space and time
+async fn scenario_alkmaar_flights() {
+    let time_start = Utc::now();
+    let time_end = time_start + Duration::try_minutes(15).unwrap();
+
+    let paths = alkmaar_best_path(time_start, time_end).await;
+    assert_eq!(paths.len(), 1, "no path without other flights");
+
+    // The same route well above the vertiports doesn't block it
+    file_alkmaar_flight("FLIGHT-Y", 200.0, time_start, time_end).await;
+    let paths = alkmaar_best_path(time_start, time_end).await;
+    assert_eq!(paths.len(), 1, "no path with a flight at another altitude");
+
+    // The same route at the same altitude and time does
+    file_alkmaar_flight("AETH12345", ALKMAAR_ALTITUDE, time_start, time_end).await;
+    let paths = alkmaar_best_path(time_start, time_end).await;
+    assert!(paths.is_empty(), "path found through a filed flight");
+
+    let (alkmaar_1, alkmaar_2) = alkmaar_vertiports();
+    assert!(
+        intersects(vec![
+            centroid(&alkmaar_1, ALKMAAR_ALTITUDE),
+            centroid(&alkmaar_2, ALKMAAR_ALTITUDE),
+        ])
+        .await
+    );
+
+    // Once the flight has landed the route is clear again
+    let paths = alkmaar_best_path(
+        time_end + Duration::try_seconds(1).unwrap(),
+        time_end + Duration::try_minutes(1).unwrap(),
+    )
+    .await;
+    assert_eq!(paths.len(), 1, "no path after the filed flight");
+}
+
+/// A volume reserved around the Alkmaar vertiports blocks the paths of
+///  other operators, but not those of the operator holding it
+async fn scenario_own_reservation() {
+    // After the flights filed by the Alkmaar scenario have landed
+    let time_start = Utc::now() + Duration::try_hours(2).unwrap();
+    let time_end = time_start + Duration::try_minutes(15).unwrap();
+
+    let request = ReserveVolumeRequest {
+        operator_identifier: RESERVING_OPERATOR_ID.to_string(),
+        vertices: rectangle((4.7100, 52.6280), (4.7220, 52.6360)),
+        altitude_meters_min: 0.0,
+        altitude_meters_max: 500.0,
+        time_start: Some(time_start.into()),
+        time_end: Some(time_end.into()),
+        reference: None,
+    };
+
+    ServerImpl {}
+        .reserve_volume(Request::new(request))
+        .await
+        .expect("could not reserve volume");
+
+    let request = best_path_request(ALKMAAR_1_ID, ALKMAAR_2_ID, time_start, time_end);
+    let paths = best_path(request.clone()).await;
+    assert!(paths.is_empty(), "path found through a reserved volume");
+
+    let paths = best_path(BestPathRequest {
+        operator_identifier: Some("IT-OPERATOR-2".to_string()),
+        ..request.clone()
+    })
+    .await;
+    assert!(
+        paths.is_empty(),
+        "path found through another operator's volume"
+    );
+
+    let paths = best_path(BestPathRequest {
+        operator_identifier: Some(RESERVING_OPERATOR_ID.to_string()),
+        ..request
+    })
+    .await;
+    assert_eq!(paths.len(), 1, "no path through the operator's own volume");
+}
+
+/// A restriction across the direct route between two vertiports makes the
+///  best path detour through the waypoints around it
+async fn scenario_blocking_zone() {
+    let time_start = Utc::now();
+    let scenario = fixtures::blocking_zone(time_start);
+    insert(&scenario).await;
+
+    let a = scenario.vertiport(fixtures::BLOCKED_PORT_A_ID).unwrap();
+    let b = scenario.vertiport(fixtures::BLOCKED_PORT_B_ID).unwrap();
+    let direct = vec![centroid(a, 50.0), centroid(b, 50.0)];
+    assert!(intersects_between(&a.identifier, &b.identifier, direct, None).await);
+
+    let paths = best_path(best_path_request(
+        &a.identifier,
+        &b.identifier,
+        time_start,
+        time_start + Duration::try_hours(1).unwrap(),
+    ))
+    .await;
+    assert_eq!(paths.len(), 1, "no path around the blocking zone");
+    assert!(paths[0].path.len() > 2, "path did not detour");
+}
+
+/// A vertiport enclosed by a restriction can't be reached
+async fn scenario_port_in_restriction() {
+    let scenario = fixtures::port_in_restriction();
+    insert(&scenario).await;
+
+    let outside = scenario.vertiport(fixtures::OUTSIDE_PORT_ID).unwrap();
+    let enclosed = scenario.vertiport(fixtures::ENCLOSED_PORT_ID).unwrap();
+    let direct = vec![centroid(outside, 50.0), centroid(enclosed, 50.0)];
+    assert!(intersects_between(&outside.identifier, &enclosed.identifier, direct, None).await);
+
+    let time_start = Utc::now();
+    let paths = best_path(best_path_request(
+        &outside.identifier,
+        &enclosed.identifier,
+        time_start,
+        time_start + Duration::try_hours(1).unwrap(),
+    ))
+    .await;
+    assert!(paths.is_empty(), "path found into the restriction");
+}
+
+/// The port zone of a vertiport blocks paths between other vertiports, but
+///  not paths to or from it
+async fn scenario_unrelated_port() {
+    let scenario = fixtures::unrelated_port();
+    insert(&scenario).await;
+
+    let west = scenario.vertiport(fixtures::WEST_PORT_ID).unwrap();
+    let midway = scenario.vertiport(fixtures::MIDWAY_PORT_ID).unwrap();
+    let east = scenario.vertiport(fixtures::EAST_PORT_ID).unwrap();
+    let altitude_meters = fixtures::PORT_ALTITUDE_METERS + 50.0;
+
+    let direct = vec![
+        centroid(west, altitude_meters),
+        centroid(east, altitude_meters),
+    ];
+    assert!(intersects_between(&west.identifier, &east.identifier, direct, None).await);
+
+    let to_midway = vec![
+        centroid(west, altitude_meters),
+        centroid(midway, altitude_meters),
+    ];
+    assert!(!intersects_between(&west.identifier, &midway.identifier, to_midway, None).await);
+
+    let time_start = Utc::now();
+    let paths = best_path(best_path_request(
+        &west.identifier,
+        &east.identifier,
+        time_start,
+        time_start + Duration::try_hours(1).unwrap(),
+    ))
+    .await;
+    assert_eq!(paths.len(), 1, "no path around the midway vertiport");
+    assert!(paths[0].path.len() > 2, "path crossed the midway vertiport");
+}
+
+/// Overlapping temporary zones are both reported where and when they overlap
+async fn scenario_overlapping_zones() {
+    let time_start = Utc::now();
+    insert(&fixtures::overlapping_zones(time_start)).await;
+
+    let zones_at = |minutes: i64, altitude_meters: f32| async move {
+        let request = GetZonesAtPointRequest {
+            point: Some(PointZ {
+                latitude: 52.0925,
+                longitude: 5.1225,
+                altitude_meters,
+            }),
+            time: Some((time_start + Duration::try_minutes(minutes).unwrap()).into()),
+        };
+
+        let mut identifiers: Vec<String> = ServerImpl {}
+            .get_zones_at_point(Request::new(request))
+            .await
+            .expect("could not get zones at point")
+            .into_inner()
+            .zones
+            .into_iter()
+            .map(|zone| zone.identifier)
+            .collect();
+        identifiers.sort();
+        identifiers
+    };
+
+    assert_eq!(
+        zones_at(90, 200.0).await,
+        vec![fixtures::OVERLAP_ZONE_A_ID, fixtures::OVERLAP_ZONE_B_ID]
+    );
+    assert_eq!(zones_at(30, 200.0).await, vec![fixtures::OVERLAP_ZONE_A_ID]);
+    assert_eq!(zones_at(90, 350.0).await, vec![fixtures::OVERLAP_ZONE_B_ID]);
+}
+
+/// A flight converging on the end of a filed flight conflicts with it
+async fn scenario_converging_flights() {
+    let mut scenario = fixtures::converging_flights(Utc::now());
+    let second = scenario.flights.pop().unwrap();
+    insert(&scenario).await;
+
+    assert!(intersects_between("", "", second.path, None).await);
+}
+
+/// Positions pushed on the Redis queue end up in the aircraft table
+async fn scenario_aircraft_queue(backends: &common::Backends) {
+    let config = &backends.config;
+    let mut consumer = Consumer::new(config, REDIS_KEY_AIRCRAFT_POSITION, 100)
+        .await
+        .expect("could not create consumer")
+        .with_publisher(config, &config.redis_aircraft_state_stream)
+        .await
+        .expect("could not create publisher");
+
+    let identifier = "IT-AIRCRAFT-1".to_string();
+    let position = AircraftPosition {
+        identifier: identifier.clone(),
+        position: Position {
+            longitude: 4.7160.into(),
+            latitude: 52.6320.into(),
+            altitude_meters: 120.0.into(),
+        },
+        timestamp_network: Utc::now(),
+        timestamp_asset: None,
+    };
+
+    let producer = RedisPool::new(config, REDIS_KEY_AIRCRAFT_POSITION)
+        .await
+        .expect("could not create producer");
+    producer
+        .push(&[position])
+        .await
+        .expect("could not push position");
+
+    let mut connection = consumer
+        .pool
+        .connection()
+        .await
+        .expect("could not connect to redis");
+    let items: Vec<AircraftPosition> = consumer
+        .pool
+        .pop(&mut connection)
+        .await
+        .expect("could not pop positions");
+    assert_eq!(items.len(), 1);
+
+    consumer
+        .process(items)
+        .await
+        .expect("could not process positions");
+
+    let states = get_aircraft_state(&[identifier])
+        .await
+        .expect("could not get aircraft state");
+    assert_eq!(states.len(), 1);
+
+    let stored = states[0].position.expect("position was not stored");
+    assert!((stored.latitude.0 - 52.6320).abs() < 1e-6);
+    assert!((stored.longitude.0 - 4.7160).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn it_backends() {
+    let backends = common::Backends::start().await;
+
+    svc_gis::postgis::psql_init()
+        .await
+        .expect("could not create tables");
+
+    scenario_vertiports().await;
+    scenario_zones().await;
+    scenario_alkmaar_flights().await;
+    scenario_own_reservation().await;
+    scenario_blocking_zone().await;
+    scenario_port_in_restriction().await;
+    scenario_unrelated_port().await;
+    scenario_overlapping_zones().await;
+    scenario_converging_flights().await;
+    scenario_aircraft_queue(&backends).await;
+}